@@ -7,13 +7,14 @@ use crate::{
     response::{
         bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
         block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
-        ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
+        BadRequestError, ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError,
+        StdApiError,
     },
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_api_types::{
     AptosErrorCode, AsConverter, BcsBlock, GasEstimation, LedgerInfo, ResourceGroup,
-    TransactionOnChainData,
+    StateValueWithProof, TransactionOnChainData,
 };
 use aptos_config::config::{NodeConfig, RoleType};
 use aptos_crypto::HashValue;
@@ -48,13 +49,14 @@ use aptos_vm::{
     move_vm_ext::MoveResolverExt,
 };
 use futures::{channel::oneshot, SinkExt};
-use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use std::{
     collections::{BTreeMap, HashMap},
     ops::Bound::Included,
     sync::{Arc, RwLock, RwLockWriteGuard},
-    time::Instant,
+    time::{Duration, Instant},
 };
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
 
 // Context holds application scope context
 #[derive(Clone)]
@@ -66,6 +68,8 @@ pub struct Context {
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
     gas_estimation_cache: Arc<RwLock<GasEstimationCache>>,
     gas_limit_cache: Arc<RwLock<GasLimitCache>>,
+    committed_version: Arc<watch::Sender<Version>>,
+    streaming_permits: Arc<Semaphore>,
 }
 
 impl std::fmt::Debug for Context {
@@ -100,6 +104,10 @@ impl Context {
                 last_updated_epoch: None,
                 block_gas_limit: None,
             })),
+            committed_version: Arc::new(watch::channel(0).0),
+            streaming_permits: Arc::new(Semaphore::new(
+                node_config.api.max_streaming_subscriptions,
+            )),
         }
     }
 
@@ -119,6 +127,30 @@ impl Context {
         self.node_config.api.max_account_modules_page_size
     }
 
+    pub fn streaming_enabled(&self) -> bool {
+        self.node_config.api.streaming_enabled
+    }
+
+    pub fn streaming_poll_interval(&self) -> Duration {
+        Duration::from_millis(self.node_config.api.streaming_poll_interval_ms)
+    }
+
+    /// Wakes up the open streams, to be called whenever new transactions are committed.
+    pub fn notify_committed_version(&self, version: Version) {
+        self.committed_version.send_replace(version);
+    }
+
+    /// Returns a receiver that is notified whenever new transactions are committed.
+    pub fn subscribe_to_commits(&self) -> watch::Receiver<Version> {
+        self.committed_version.subscribe()
+    }
+
+    /// Takes one of the limited slots for open streams, if any is available.
+    /// The slot is released once the returned permit is dropped.
+    pub fn try_acquire_streaming_permit(&self) -> Option<OwnedSemaphorePermit> {
+        self.streaming_permits.clone().try_acquire_owned().ok()
+    }
+
     pub fn latest_state_view(&self) -> Result<DbStateView> {
         self.db.latest_state_checkpoint_view()
     }
@@ -173,6 +205,14 @@ impl Context {
         self.node_config.api.max_submit_transaction_batch_size
     }
 
+    pub fn max_view_function_batch_size(&self) -> usize {
+        self.node_config.api.max_view_function_batch_size
+    }
+
+    pub fn max_gas_view_function_batch(&self) -> u64 {
+        self.node_config.api.max_gas_view_function_batch
+    }
+
     pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
@@ -274,6 +314,58 @@ impl Context {
             .map_err(|e| E::internal_with_code(e, AptosErrorCode::InternalError, ledger_info))
     }
 
+    /// Reads the value of `state_key` at the state checkpoint at `requested_ledger_version`, or
+    /// at the latest version if not provided, along with the proofs tying it to the latest
+    /// ledger info.
+    pub fn get_state_value_with_proof<E: StdApiError + BadRequestError>(
+        &self,
+        state_key: &StateKey,
+        requested_ledger_version: Option<Version>,
+    ) -> Result<(LedgerInfo, StateValueWithProof), E> {
+        let (latest_ledger_info, version) =
+            self.get_latest_ledger_info_and_verify_lookup_version(requested_ledger_version)?;
+        let internal_error =
+            |e| E::internal_with_code(e, AptosErrorCode::InternalError, &latest_ledger_info);
+
+        // Fetched after the unsigned ledger info above, so it's at least as new.
+        let ledger_info = self
+            .get_latest_ledger_info_with_signatures()
+            .map_err(internal_error)?;
+        let transaction_info_with_proof = self
+            .db
+            .get_transaction_by_version(version, ledger_info.ledger_info().version(), false)
+            .context("Failed to retrieve transaction info proof")
+            .map_err(internal_error)?
+            .proof;
+        if transaction_info_with_proof
+            .transaction_info()
+            .state_checkpoint_hash()
+            .is_none()
+        {
+            return Err(E::bad_request_with_code(
+                format!(
+                    "Ledger version({}) is not a state checkpoint, only state checkpoints can be proven",
+                    version
+                ),
+                AptosErrorCode::InvalidInput,
+                &latest_ledger_info,
+            ));
+        }
+        let (value, proof) = self
+            .db
+            .get_state_value_with_proof_by_version(state_key, version)
+            .context("Failed to retrieve state value with proof")
+            .map_err(internal_error)?;
+
+        Ok((latest_ledger_info, StateValueWithProof {
+            version,
+            value,
+            proof,
+            transaction_info_with_proof,
+            ledger_info,
+        }))
+    }
+
     pub fn get_state_values(
         &self,
         address: AccountAddress,
@@ -811,6 +903,37 @@ impl Context {
         }
     }
 
+    pub fn get_events_by_type(
+        &self,
+        event_type: &TypeTag,
+        start: Option<u64>,
+        limit: u16,
+        ledger_version: u64,
+    ) -> Result<Vec<EventWithVersion>> {
+        if let Some(start) = start {
+            self.db.get_events_by_type(
+                event_type,
+                start,
+                Order::Ascending,
+                limit as u64,
+                ledger_version,
+            )
+        } else {
+            self.db
+                .get_events_by_type(
+                    event_type,
+                    u64::MAX,
+                    Order::Descending,
+                    limit as u64,
+                    ledger_version,
+                )
+                .map(|mut result| {
+                    result.reverse();
+                    result
+                })
+        }
+    }
+
     fn next_bucket(&self, gas_unit_price: u64) -> u64 {
         match self
             .node_config
@@ -9,8 +9,8 @@ use crate::{
     failpoint::fail_point_poem,
     page::Page,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        api_disabled, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResultWith404, InternalError,
     },
     ApiTags,
 };
@@ -19,13 +19,14 @@ use aptos_api_types::{
     verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
     MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
 };
-use aptos_types::event::EventKey;
+use aptos_types::{contract_event::EventWithVersion, event::EventKey};
 use aptos_vm::data_cache::AsMoveResolver;
+use move_core_types::language_storage::{StructTag, TypeTag};
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
 };
-use std::sync::Arc;
+use std::{convert::TryInto, sync::Arc};
 
 pub struct EventsApi {
     pub context: Arc<Context>,
@@ -141,6 +142,87 @@ impl EventsApi {
         let key = account.find_event_key(event_handle.0, field_name.0.into())?;
         self.list(account.latest_ledger_info, accept_type, page, key)
     }
+
+    /// Get events by event type
+    ///
+    /// This API returns the events of the given type, e.g. `0x1::coin::DepositEvent`,
+    /// emitted to any account. The events of a type are numbered in the order they were
+    /// emitted, starting from 0, so the page after `start` starts at `start` plus the
+    /// number of events returned.
+    ///
+    /// This requires the internal indexer to be enabled on the node. When it is enabled on
+    /// a node with existing history, the indexer indexes the events of that history on
+    /// startup, except the events the node already pruned, which are never returned.
+    #[oai(
+        path = "/events/by_type/:event_type",
+        method = "get",
+        operation_id = "get_events_by_type",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_type(
+        &self,
+        accept_type: AcceptType,
+        /// Type of the events e.g. `0x1::coin::DepositEvent`
+        event_type: Path<MoveStructTag>,
+        /// Starting sequence number of events, among the events of the type.
+        ///
+        /// If unspecified, by default will retrieve the most recent events
+        start: Query<Option<U64>>,
+        /// Max number of events to retrieve.
+        ///
+        /// If unspecified, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        event_type
+            .0
+            .verify(0)
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_events_by_type")?;
+        self.context
+            .check_api_output_enabled("Get events by type", &accept_type)?;
+        if !self.context.db.indexer_enabled() {
+            return Err(api_disabled("Get events by type"));
+        }
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let event_type: StructTag = event_type
+            .0
+            .try_into()
+            .context("Given event type was invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &latest_ledger_info,
+                )
+            })?;
+        let page = Page::new(
+            start.0.map(|v| v.0),
+            limit.0,
+            self.context.max_events_page_size(),
+        );
+
+        let ledger_version = latest_ledger_info.version();
+        let events = self
+            .context
+            .get_events_by_type(
+                &TypeTag::Struct(Box::new(event_type.clone())),
+                page.start_option(),
+                page.limit(&latest_ledger_info)?,
+                ledger_version,
+            )
+            .context(format!("Failed to find events by type {}", event_type))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+        self.render(latest_ledger_info, accept_type, events)
+    }
 }
 
 impl EventsApi {
@@ -169,7 +251,16 @@ impl EventsApi {
                     &latest_ledger_info,
                 )
             })?;
+        self.render(latest_ledger_info, accept_type, events)
+    }
 
+    /// Renders events in the accepted format
+    fn render(
+        &self,
+        latest_ledger_info: LedgerInfo,
+        accept_type: AcceptType,
+        events: Vec<EventWithVersion>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
         match accept_type {
             AcceptType::Json => {
                 let events = self
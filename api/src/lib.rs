@@ -18,10 +18,12 @@ mod index;
 mod log;
 pub mod metrics;
 mod page;
+mod rate_limit;
 mod response;
 mod runtime;
 mod set_failpoints;
 mod state;
+mod streaming;
 #[cfg(test)]
 pub mod tests;
 mod transactions;
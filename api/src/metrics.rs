@@ -33,3 +33,12 @@ pub static REQUEST_SOURCE_CLIENT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static RATE_LIMITER_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_api_rate_limiter_requests",
+        "API requests going through rate limiting, grouped by endpoint class, client type (ip or api_key) and result (allowed, throttled or invalid_api_key)",
+        &["endpoint_class", "client_type", "result"]
+    )
+    .unwrap()
+});
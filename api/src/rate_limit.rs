@@ -0,0 +1,469 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::metrics::RATE_LIMITER_REQUESTS;
+use aptos_api_types::{AptosError, AptosErrorCode};
+use aptos_config::config::{ApiRateLimitConfig, EndpointBudgets, RequestBudget};
+use aptos_crypto::HashValue;
+use aptos_rate_limiter::rate_limit::TokenBucketRateLimiter;
+use hyper::Method;
+use poem::{
+    http::{header, HeaderValue, StatusCode},
+    web::Json,
+    Endpoint, IntoResponse, Middleware, Request, Response, Result,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv6Addr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use subtle::ConstantTimeEq;
+
+const BEARER_PREFIX: &str = "Bearer ";
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+/// Interval at which the buckets of idle clients are dropped.
+const GARBAGE_COLLECTION_INTERVAL: Duration = Duration::from_secs(60);
+/// Number of bytes of the hash of an API key that identify its client.
+const API_KEY_FINGERPRINT_BYTES: usize = 16;
+
+/// Classes of endpoints, each with a budget of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EndpointClass {
+    Reads,
+    View,
+    Simulate,
+    Submit,
+}
+
+impl EndpointClass {
+    fn classify(method: &Method, path: &str) -> Self {
+        if method != Method::POST {
+            return EndpointClass::Reads;
+        }
+        match path.trim_end_matches('/') {
+            "/v1/transactions" | "/v1/transactions/batch" => EndpointClass::Submit,
+            // Simulations with state overrides or traces are as expensive as plain ones.
+            path if path.starts_with("/v1/transactions/simulate") => EndpointClass::Simulate,
+            path if path.starts_with("/v1/view") => EndpointClass::View,
+            _ => EndpointClass::Reads,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            EndpointClass::Reads => "reads",
+            EndpointClass::View => "view",
+            EndpointClass::Simulate => "simulate",
+            EndpointClass::Submit => "submit",
+        }
+    }
+}
+
+/// The client a request is accounted to.
+#[derive(Debug, PartialEq, Eq)]
+enum Client {
+    /// The fingerprint of the API key, see `api_key_fingerprint`.
+    ApiKey(String),
+    Ip(IpAddr),
+    /// No API key and no known address, e.g. a unix socket. Not rate limited.
+    Unknown,
+}
+
+/// Token buckets of one type of client, per endpoint class.
+struct ClassLimiters<Key: Eq + std::hash::Hash + Clone + std::fmt::Debug> {
+    reads: TokenBucketRateLimiter<Key>,
+    view: TokenBucketRateLimiter<Key>,
+    simulate: TokenBucketRateLimiter<Key>,
+    submit: TokenBucketRateLimiter<Key>,
+    /// Time after which the buckets of an idle key are full again, and can be dropped.
+    idle_timeout: Duration,
+    usage: Mutex<KeyUsage<Key>>,
+}
+
+/// Tracks when keys were last used, to drop the buckets of idle keys.
+struct KeyUsage<Key> {
+    last_used: HashMap<Key, Instant>,
+    next_garbage_collection: Instant,
+}
+
+impl<Key: Eq + std::hash::Hash + Clone + std::fmt::Debug> ClassLimiters<Key> {
+    fn new(label: &'static str, budgets: &EndpointBudgets, start_percentage: u8) -> Self {
+        let limiter = |budget: RequestBudget, class: EndpointClass| {
+            TokenBucketRateLimiter::new(
+                label,
+                format!("{} {}", label, class.as_str()),
+                start_percentage,
+                budget.burst,
+                budget.requests_per_second,
+                None,
+            )
+        };
+        let refill_secs = [
+            budgets.reads,
+            budgets.view,
+            budgets.simulate,
+            budgets.submit,
+        ]
+        .iter()
+        .map(|budget| (budget.burst + budget.requests_per_second - 1) / budget.requests_per_second)
+        .max()
+        .unwrap_or_default();
+        Self {
+            reads: limiter(budgets.reads, EndpointClass::Reads),
+            view: limiter(budgets.view, EndpointClass::View),
+            simulate: limiter(budgets.simulate, EndpointClass::Simulate),
+            submit: limiter(budgets.submit, EndpointClass::Submit),
+            idle_timeout: GARBAGE_COLLECTION_INTERVAL.max(Duration::from_secs(refill_secs as u64)),
+            usage: Mutex::new(KeyUsage {
+                last_used: HashMap::new(),
+                next_garbage_collection: Instant::now() + GARBAGE_COLLECTION_INTERVAL,
+            }),
+        }
+    }
+
+    /// Takes a request from the bucket of the key, returning when the next one is available
+    /// if the bucket is empty.
+    fn acquire(&self, class: EndpointClass, key: Key) -> Result<(), Option<Instant>> {
+        let limiter = match class {
+            EndpointClass::Reads => &self.reads,
+            EndpointClass::View => &self.view,
+            EndpointClass::Simulate => &self.simulate,
+            EndpointClass::Submit => &self.submit,
+        };
+        let result = limiter.bucket(key.clone()).lock().acquire_all_tokens(1);
+
+        // Recorded after using the bucket, so that a bucket dropped while in use gets tracked
+        // again, and is collected later.
+        let now = Instant::now();
+        let mut usage = self.usage.lock().expect("Failed to lock the key usage");
+        usage.last_used.insert(key, now);
+        if now >= usage.next_garbage_collection {
+            usage.next_garbage_collection = now + GARBAGE_COLLECTION_INTERVAL;
+            self.garbage_collect(&mut usage, now);
+        }
+        result
+    }
+
+    /// Drops the buckets of the keys idle for longer than the idle timeout. Their buckets are
+    /// full, so they're recreated as new buckets on the next request of the key.
+    fn garbage_collect(&self, usage: &mut KeyUsage<Key>, now: Instant) {
+        usage.last_used.retain(|key, last_used| {
+            if now.saturating_duration_since(*last_used) < self.idle_timeout {
+                return true;
+            }
+            for limiter in [&self.reads, &self.view, &self.simulate, &self.submit] {
+                limiter.try_garbage_collect_key(key);
+            }
+            false
+        });
+    }
+}
+
+struct RateLimiters {
+    use_x_forwarded_for: bool,
+    api_keys: Vec<String>,
+    ip: ClassLimiters<IpAddr>,
+    api_key: ClassLimiters<String>,
+}
+
+impl RateLimiters {
+    fn client(&self, req: &Request) -> Result<Client, ()> {
+        if let Some(authorization) = req.headers().get(header::AUTHORIZATION) {
+            let api_key = authorization
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix(BEARER_PREFIX))
+                .map(str::trim)
+                .ok_or(())?;
+            // Compare with every key in constant time, not to leak how much of a key matched.
+            let is_known = self.api_keys.iter().fold(0u8, |known, key| {
+                known | key.as_bytes().ct_eq(api_key.as_bytes()).unwrap_u8()
+            });
+            return if is_known == 1 {
+                Ok(Client::ApiKey(api_key_fingerprint(api_key)))
+            } else {
+                Err(())
+            };
+        }
+
+        let forwarded_ip = if self.use_x_forwarded_for {
+            // The last address is the one added by our load balancer, the ones before it come
+            // from the client.
+            req.headers()
+                .get(X_FORWARDED_FOR)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.rsplit(',').next())
+                .and_then(|ip| ip.trim().parse::<IpAddr>().ok())
+        } else {
+            None
+        };
+        let ip = forwarded_ip.or_else(|| req.remote_addr().as_socket_addr().map(|a| a.ip()));
+
+        Ok(match ip {
+            Some(ip) => Client::Ip(normalize_ip(ip)),
+            None => Client::Unknown,
+        })
+    }
+}
+
+/// Identifies the client of an API key without the key itself, which must not end up in the
+/// logs of the rate limiter.
+fn api_key_fingerprint(api_key: &str) -> String {
+    let hash = HashValue::sha3_256_of(api_key.as_bytes());
+    hex::encode(&hash.as_ref()[..API_KEY_FINGERPRINT_BYTES])
+}
+
+/// IPv6 clients usually get a whole /64, which is what we rate limit on.
+fn normalize_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(ipv6) => match ipv6.to_ipv4_mapped() {
+            Some(ipv4) => IpAddr::V4(ipv4),
+            None => {
+                let segments = ipv6.segments();
+                IpAddr::V6(Ipv6Addr::new(
+                    segments[0],
+                    segments[1],
+                    segments[2],
+                    segments[3],
+                    0,
+                    0,
+                    0,
+                    0,
+                ))
+            },
+        },
+    }
+}
+
+/// This middleware rate limits requests per client, with separate budgets for each class of
+/// endpoints. Clients are identified by their API key if they present one, otherwise by
+/// their IP address. Throttled requests are rejected with a 429 and a `Retry-After` header.
+pub struct RateLimit {
+    limiters: Option<Arc<RateLimiters>>,
+}
+
+impl RateLimit {
+    pub fn new(config: &ApiRateLimitConfig) -> Self {
+        let limiters = config.enabled.then(|| {
+            Arc::new(RateLimiters {
+                use_x_forwarded_for: config.use_x_forwarded_for,
+                api_keys: config.api_keys.clone(),
+                ip: ClassLimiters::new(
+                    "api_ip",
+                    &config.ip_budgets,
+                    config.initial_bucket_fill_percentage,
+                ),
+                api_key: ClassLimiters::new(
+                    "api_key",
+                    &config.api_key_budgets,
+                    config.initial_bucket_fill_percentage,
+                ),
+            })
+        });
+        Self { limiters }
+    }
+}
+
+impl<E: Endpoint> Middleware<E> for RateLimit {
+    type Output = RateLimitEndpoint<E>;
+
+    fn transform(&self, ep: E) -> Self::Output {
+        RateLimitEndpoint {
+            inner: ep,
+            limiters: self.limiters.clone(),
+        }
+    }
+}
+
+/// Endpoint for RateLimit middleware.
+pub struct RateLimitEndpoint<E> {
+    inner: E,
+    limiters: Option<Arc<RateLimiters>>,
+}
+
+#[async_trait::async_trait]
+impl<E: Endpoint> Endpoint for RateLimitEndpoint<E> {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> Result<Self::Output> {
+        let limiters = match &self.limiters {
+            Some(limiters) => limiters,
+            None => return self.inner.call(req).await.map(IntoResponse::into_response),
+        };
+
+        let class = EndpointClass::classify(req.method(), req.uri().path());
+        let (client_type, result) = match limiters.client(&req) {
+            Ok(Client::ApiKey(api_key)) => ("api_key", limiters.api_key.acquire(class, api_key)),
+            Ok(Client::Ip(ip)) => ("ip", limiters.ip.acquire(class, ip)),
+            Ok(Client::Unknown) => ("unknown", Ok(())),
+            Err(()) => {
+                RATE_LIMITER_REQUESTS
+                    .with_label_values(&[class.as_str(), "api_key", "invalid_api_key"])
+                    .inc();
+                return Ok(error_response(
+                    StatusCode::UNAUTHORIZED,
+                    "Invalid API key, expected a known key as `Authorization: Bearer <key>`",
+                    AptosErrorCode::InvalidApiKey,
+                ));
+            },
+        };
+
+        match result {
+            Ok(()) => {
+                RATE_LIMITER_REQUESTS
+                    .with_label_values(&[class.as_str(), client_type, "allowed"])
+                    .inc();
+                self.inner.call(req).await.map(IntoResponse::into_response)
+            },
+            Err(available_at) => {
+                RATE_LIMITER_REQUESTS
+                    .with_label_values(&[class.as_str(), client_type, "throttled"])
+                    .inc();
+                // Round up, so that clients retrying right on time find a refilled bucket.
+                let retry_after_secs = available_at
+                    .map(|at| {
+                        let wait = at.saturating_duration_since(Instant::now());
+                        wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+                    })
+                    .unwrap_or_default()
+                    .max(1);
+                let mut response = error_response(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    &format!(
+                        "Rate limit of {} requests exceeded, retry after {} seconds",
+                        class.as_str(),
+                        retry_after_secs
+                    ),
+                    AptosErrorCode::RateLimitExceeded,
+                );
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+                Ok(response)
+            },
+        }
+    }
+}
+
+fn error_response(status: StatusCode, message: &str, error_code: AptosErrorCode) -> Response {
+    let mut response = Json(AptosError::new_with_error_code(message, error_code)).into_response();
+    response.set_status(status);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        api_key_fingerprint, normalize_ip, ClassLimiters, Client, EndpointClass, RateLimiters,
+    };
+    use aptos_config::config::{EndpointBudgets, RequestBudget};
+    use hyper::Method;
+    use poem::{http::header, Request};
+    use std::{net::IpAddr, time::Instant};
+
+    fn budgets() -> EndpointBudgets {
+        let budget = RequestBudget::new(1, 1);
+        EndpointBudgets {
+            reads: budget,
+            view: budget,
+            simulate: budget,
+            submit: budget,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        for (method, path, class) in [
+            (Method::GET, "/v1/transactions", EndpointClass::Reads),
+            (Method::POST, "/v1/transactions", EndpointClass::Submit),
+            (
+                Method::POST,
+                "/v1/transactions/batch",
+                EndpointClass::Submit,
+            ),
+            (
+                Method::POST,
+                "/v1/transactions/simulate",
+                EndpointClass::Simulate,
+            ),
+            (
+                Method::POST,
+                "/v1/transactions/simulate_with_overrides",
+                EndpointClass::Simulate,
+            ),
+            (
+                Method::POST,
+                "/v1/transactions/simulate_with_trace",
+                EndpointClass::Simulate,
+            ),
+            (Method::POST, "/v1/view", EndpointClass::View),
+            (Method::POST, "/v1/tables/0x1/item", EndpointClass::Reads),
+        ] {
+            assert_eq!(EndpointClass::classify(&method, path), class, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_normalize_ip() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(normalize_ip(ip("1.2.3.4")), ip("1.2.3.4"));
+        assert_eq!(normalize_ip(ip("::ffff:1.2.3.4")), ip("1.2.3.4"));
+        assert_eq!(
+            normalize_ip(ip("2001:db8:1:2:3:4:5:6")),
+            ip("2001:db8:1:2::")
+        );
+    }
+
+    #[test]
+    fn test_api_keys() {
+        let limiters = RateLimiters {
+            use_x_forwarded_for: false,
+            api_keys: vec!["key1".to_string(), "key2".to_string()],
+            ip: ClassLimiters::new("ip", &budgets(), 100),
+            api_key: ClassLimiters::new("api_key", &budgets(), 100),
+        };
+        let client = |authorization: &str| {
+            limiters.client(
+                &Request::builder()
+                    .header(header::AUTHORIZATION, authorization)
+                    .finish(),
+            )
+        };
+        assert_eq!(
+            client("Bearer key2"),
+            Ok(Client::ApiKey(api_key_fingerprint("key2")))
+        );
+        assert_eq!(client("Bearer key"), Err(()));
+        assert_eq!(client("Bearer key22"), Err(()));
+        assert_eq!(client("key1"), Err(()));
+    }
+
+    #[test]
+    fn test_api_key_fingerprint() {
+        let api_key = "a-secret-api-key";
+        let fingerprint = api_key_fingerprint(api_key);
+        assert!(!fingerprint.contains(api_key));
+        assert_eq!(fingerprint.len(), 2 * super::API_KEY_FINGERPRINT_BYTES);
+        assert_eq!(fingerprint, api_key_fingerprint(api_key));
+        assert_ne!(fingerprint, api_key_fingerprint("another-secret-api-key"));
+    }
+
+    #[test]
+    fn test_garbage_collect_idle_keys() {
+        let limiters = ClassLimiters::new("test", &budgets(), 100);
+        assert!(limiters.acquire(EndpointClass::Reads, "client").is_ok());
+        assert!(limiters.acquire(EndpointClass::Reads, "client").is_err());
+
+        let mut usage = limiters.usage.lock().unwrap();
+        limiters.garbage_collect(&mut usage, Instant::now());
+        assert!(usage.last_used.contains_key("client"));
+        limiters.garbage_collect(&mut usage, Instant::now() + limiters.idle_timeout);
+        assert!(usage.last_used.is_empty());
+        drop(usage);
+
+        // The bucket of the key was dropped, so it gets a new full one.
+        assert!(limiters.acquire(EndpointClass::Reads, "client").is_ok());
+    }
+}
@@ -5,16 +5,18 @@
 use crate::{
     accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
     context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    log::middleware_log, rate_limit::RateLimit, set_failpoints, state::StateApi,
+    streaming::StreamingApi, transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::X_APTOS_CLIENT;
 use aptos_config::config::{ApiConfig, NodeConfig};
+use aptos_event_notifications::CommitNotificationListener;
 use aptos_logger::info;
 use aptos_mempool::MempoolClientSender;
 use aptos_storage_interface::DbReader;
 use aptos_types::chain_id::ChainId;
+use futures::StreamExt;
 use poem::{
     http::{header, Method},
     listener::{Listener, RustlsCertificate, RustlsConfig, TcpListener},
@@ -33,12 +35,23 @@ pub fn bootstrap(
     chain_id: ChainId,
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    commit_subscription: Option<CommitNotificationListener>,
 ) -> anyhow::Result<Runtime> {
     let max_runtime_workers = get_max_runtime_workers(&config.api);
     let runtime = aptos_runtimes::spawn_named_runtime("api".into(), Some(max_runtime_workers));
 
     let context = Context::new(chain_id, db, mp_sender, config.clone());
 
+    // Forward commit notifications to the open streams
+    if let Some(mut commit_subscription) = commit_subscription {
+        let context = context.clone();
+        runtime.spawn(async move {
+            while let Some(notification) = commit_subscription.next().await {
+                context.notify_committed_version(notification.version);
+            }
+        });
+    }
+
     attach_poem_to_runtime(runtime.handle(), context, config, false)
         .context("Failed to attach poem to runtime")?;
 
@@ -62,6 +75,7 @@ pub fn get_api_service(
         EventsApi,
         IndexApi,
         StateApi,
+        StreamingApi,
         TransactionsApi,
         ViewFunctionApi,
     ),
@@ -87,6 +101,9 @@ pub fn get_api_service(
         StateApi {
             context: context.clone(),
         },
+        StreamingApi {
+            context: context.clone(),
+        },
         TransactionsApi {
             context: context.clone(),
         },
@@ -118,6 +135,7 @@ pub fn attach_poem_to_runtime(
     let context = Arc::new(context);
 
     let size_limit = context.content_length_limit();
+    let rate_limit = RateLimit::new(&config.api.rate_limit);
 
     let api_service = get_api_service(context.clone());
 
@@ -174,6 +192,7 @@ pub fn attach_poem_to_runtime(
                 header::HeaderName::from_static(X_APTOS_CLIENT),
                 header::CONTENT_TYPE,
                 header::ACCEPT,
+                header::AUTHORIZATION,
             ]);
 
         // Build routes for the API
@@ -191,6 +210,8 @@ pub fn attach_poem_to_runtime(
                         poem::get(set_failpoints::set_failpoint_poem).data(context.clone()),
                     ),
             )
+            // Rate limit inside of CORS, so that browsers can read the 429 responses.
+            .with(rate_limit)
             .with(cors)
             .with(PostSizeLimit::new(size_limit))
             // NOTE: Make sure to keep this after all the `with` middleware.
@@ -283,6 +304,7 @@ mod tests {
             ChainId::test(),
             context.db.clone(),
             context.mempool.ac_client.clone(),
+            None,
         );
         assert!(ret.is_ok());
 
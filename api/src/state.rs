@@ -6,8 +6,8 @@ use crate::{
     failpoint::fail_point_poem,
     response::{
         api_forbidden, build_not_found, module_not_found, resource_not_found, table_item_not_found,
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        version_not_found, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResultWith404, InternalError,
     },
     ApiTags, Context,
 };
@@ -252,9 +252,253 @@ impl StateApi {
 
         self.raw_value(&accept_type, request.0, ledger_version.0)
     }
+
+    /// Get account resource with proof
+    ///
+    /// Retrieves an individual resource from a given account at a state checkpoint, along with
+    /// the proofs tying it to the latest ledger info, so it can be verified without trusting the
+    /// node. If the ledger version is not specified in the request, the latest ledger version is
+    /// used. Only state checkpoint versions, i.e. the last version of each block, can be proven.
+    ///
+    /// A missing resource isn't an error, its absence is proven instead. Members of resource
+    /// groups can't be proven individually, request the resource group instead.
+    ///
+    /// Only BCS is supported, the response is a BCS encoded `StateValueWithProof`.
+    #[oai(
+        path = "/accounts/:address/resource_with_proof/:resource_type",
+        method = "get",
+        operation_id = "get_account_resource_with_proof",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of struct to retrieve e.g. `0x1::account::Account`
+        resource_type: Path<MoveStructTag>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<MoveValue> {
+        resource_type
+            .0
+            .verify(0)
+            .context("'resource_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_with_proof")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get account resource with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get account resource with proof", &accept_type)?;
+
+        let resource_type: StructTag = resource_type
+            .0
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        let access_path = AccessPath::resource_access_path(address.0.into(), resource_type)
+            .context("Failed to build the access path of the resource")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        self.value_with_proof(
+            StateKey::access_path(access_path),
+            ledger_version.0.map(|inner| inner.0),
+        )
+    }
+
+    /// Get raw table item with proof
+    ///
+    /// Get a table item at a state checkpoint from the table identified by {table_handle} in the
+    /// path and the "key" (RawTableItemRequest) provided in the request body, along with the
+    /// proofs tying it to the latest ledger info, so it can be verified without trusting the
+    /// node. If the ledger version is not specified in the request, the latest ledger version is
+    /// used. Only state checkpoint versions, i.e. the last version of each block, can be proven.
+    ///
+    /// A missing item isn't an error, its absence is proven instead.
+    ///
+    /// Only BCS is supported, the response is a BCS encoded `StateValueWithProof`.
+    #[oai(
+        path = "/tables/:table_handle/raw_item_with_proof",
+        method = "post",
+        operation_id = "get_raw_table_item_with_proof",
+        tag = "ApiTags::Tables"
+    )]
+    async fn get_raw_table_item_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Table handle hex encoded 32-byte string
+        table_handle: Path<Address>,
+        /// Table request detailing the key type, key, and value type
+        table_item_request: Json<RawTableItemRequest>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<MoveValue> {
+        fail_point_poem("endpoint_get_raw_table_item_with_proof")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get raw table item with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get raw table item with proof", &accept_type)?;
+
+        let state_key = StateKey::table_item(
+            TableHandle(table_handle.0.into()),
+            table_item_request.0.key.0,
+        );
+        self.value_with_proof(state_key, ledger_version.0.map(|inner| inner.0))
+    }
+
+    /// Get raw state value with proof
+    ///
+    /// Get a state value at a state checkpoint, identified by the key provided in the request
+    /// body, along with the proofs tying it to the latest ledger info, so it can be verified
+    /// without trusting the node. If the ledger version is not specified in the request, the
+    /// latest ledger version is used. Only state checkpoint versions, i.e. the last version of
+    /// each block, can be proven.
+    ///
+    /// A missing value isn't an error, its absence is proven instead.
+    ///
+    /// Only BCS is supported, the response is a BCS encoded `StateValueWithProof`.
+    #[oai(
+        path = "/experimental/state_values/raw_with_proof",
+        method = "post",
+        operation_id = "get_raw_state_value_with_proof",
+        tag = "ApiTags::Experimental",
+        hidden
+    )]
+    async fn get_raw_state_value_with_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Request that carries the state key.
+        request: Json<RawStateValueRequest>,
+        /// Ledger version at which the value is got.
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<MoveValue> {
+        fail_point_poem("endpoint_get_raw_state_value_with_proof")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get raw state value with proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get raw state value with proof", &accept_type)?;
+
+        let state_key = bcs::from_bytes(&request.0.key.0)
+            .context(format!(
+                "Failed deserializing state key. key: {}",
+                request.0.key
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        self.value_with_proof(state_key, ledger_version.0.map(|inner| inner.0))
+    }
+
+    /// Get state proof
+    ///
+    /// Get the latest ledger info along with the epoch changes since the epoch of
+    /// `known_version`, allowing a light client trusting `known_version` to move its trust to
+    /// the latest ledger info.
+    ///
+    /// Only BCS is supported, the response is a BCS encoded `StateProof`.
+    #[oai(
+        path = "/experimental/state_proof",
+        method = "get",
+        operation_id = "get_state_proof",
+        tag = "ApiTags::Experimental",
+        hidden
+    )]
+    async fn get_state_proof(
+        &self,
+        accept_type: AcceptType,
+        /// Latest version known to the client
+        known_version: Query<U64>,
+    ) -> BasicResultWith404<MoveValue> {
+        fail_point_poem("endpoint_get_state_proof")?;
+
+        if AcceptType::Json == accept_type {
+            return Err(api_forbidden(
+                "Get state proof",
+                "Only BCS is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Get state proof", &accept_type)?;
+
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if known_version.0 .0 > ledger_info.version() {
+            return Err(version_not_found(known_version.0 .0, &ledger_info));
+        }
+        let state_proof = self
+            .context
+            .db
+            .get_state_proof(known_version.0 .0)
+            .context("Failed to retrieve state proof")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let bytes = bcs::to_bytes(&state_proof)
+            .context("Failed serializing state proof")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+    }
 }
 
 impl StateApi {
+    /// Read a state value with the proofs to verify it, BCS encoded
+    fn value_with_proof(
+        &self,
+        state_key: StateKey,
+        ledger_version: Option<u64>,
+    ) -> BasicResultWith404<MoveValue> {
+        let (ledger_info, value_with_proof) = self
+            .context
+            .get_state_value_with_proof(&state_key, ledger_version)?;
+        let bytes = bcs::to_bytes(&value_with_proof)
+            .context("Failed serializing state value with proof")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+    }
+
     /// Read a resource at the ledger version
     ///
     /// JSON: Convert to MoveResource
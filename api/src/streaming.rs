@@ -0,0 +1,389 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::AcceptType,
+    context::Context,
+    failpoint::fail_point_poem,
+    generate_error_response,
+    response::{api_disabled, version_pruned, BadRequestError, ServiceUnavailableError},
+    ApiTags,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    Address, AptosErrorCode, AsConverter, EntryFunctionId, MoveStructTag, Transaction,
+    TransactionOnChainData, VerifyInput, VerifyInputWithRecursion, VersionedEvent, U64,
+};
+use aptos_logger::warn;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::{ContractEvent, EventWithVersion},
+    transaction::{TransactionPayload, Version},
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+use poem_openapi::{param::Query, payload::EventStream, OpenApi};
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+use tokio::sync::{watch, OwnedSemaphorePermit};
+
+generate_error_response!(
+    StreamingError,
+    (400, BadRequest),
+    (403, Forbidden),
+    (410, Gone),
+    (500, Internal),
+    (503, ServiceUnavailable)
+);
+
+type StreamingResult<T> = poem::Result<EventStream<BoxStream<'static, T>>, StreamingError>;
+
+/// Interval of the keep-alive messages sent on idle streams, so that proxies
+/// and clients don't time out streams with rarely matching filters.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// API for streaming committed transactions and events as server-sent events
+pub struct StreamingApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl StreamingApi {
+    /// Stream transactions
+    ///
+    /// Streams committed transactions as server-sent events, one transaction
+    /// per event, starting from `start_version` and following the chain as new
+    /// transactions are committed. To resume an interrupted stream, reconnect
+    /// with `start_version` set to one past the version of the last received
+    /// transaction.
+    ///
+    /// Transactions can be filtered by sender, entry function and the types of
+    /// the events they emit. If multiple filters are given, all of them must
+    /// match.
+    #[oai(
+        path = "/stream/transactions",
+        method = "get",
+        operation_id = "stream_transactions",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn stream_transactions(
+        &self,
+        /// Ledger version to start streaming from
+        ///
+        /// If unspecified, starts from the next transaction to be committed
+        start_version: Query<Option<U64>>,
+        /// Only stream user transactions sent by this account
+        sender: Query<Option<Address>>,
+        /// Only stream user transactions calling this entry function
+        /// e.g. `0x1::coin::transfer`
+        entry_function: Query<Option<EntryFunctionId>>,
+        /// Only stream transactions emitting an event of this type
+        /// e.g. `0x1::coin::DepositEvent`
+        event_type: Query<Option<MoveStructTag>>,
+    ) -> StreamingResult<Transaction> {
+        fail_point_poem("endpoint_stream_transactions")?;
+        let streamer = self.streamer(
+            "Stream transactions",
+            start_version.0,
+            sender.0,
+            entry_function.0,
+            event_type.0,
+        )?;
+
+        Ok(streamer.into_event_stream(|context, batch| {
+            let state_view = context.latest_state_view()?;
+            let resolver = state_view.as_move_resolver();
+            let converter = resolver.as_converter(context.db.clone());
+            batch
+                .into_iter()
+                .map(|(timestamp, txn)| converter.try_into_onchain_transaction(timestamp, txn))
+                .collect()
+        }))
+    }
+
+    /// Stream events
+    ///
+    /// Streams the events emitted by committed transactions as server-sent
+    /// events, one event per server-sent event, starting from `start_version`
+    /// and following the chain as new transactions are committed. To resume
+    /// an interrupted stream, reconnect with `start_version` set to one past
+    /// the version of the last received event.
+    ///
+    /// Events can be filtered by their type, and by the sender and entry
+    /// function of the transactions emitting them. If multiple filters are
+    /// given, all of them must match.
+    #[oai(
+        path = "/stream/events",
+        method = "get",
+        operation_id = "stream_events",
+        tag = "ApiTags::Events"
+    )]
+    async fn stream_events(
+        &self,
+        /// Ledger version to start streaming from
+        ///
+        /// If unspecified, starts from the next transaction to be committed
+        start_version: Query<Option<U64>>,
+        /// Only stream events emitted by user transactions sent by this account
+        sender: Query<Option<Address>>,
+        /// Only stream events emitted by user transactions calling this entry
+        /// function e.g. `0x1::coin::transfer`
+        entry_function: Query<Option<EntryFunctionId>>,
+        /// Only stream events of this type e.g. `0x1::coin::DepositEvent`
+        event_type: Query<Option<MoveStructTag>>,
+    ) -> StreamingResult<VersionedEvent> {
+        fail_point_poem("endpoint_stream_events")?;
+        let streamer = self.streamer(
+            "Stream events",
+            start_version.0,
+            sender.0,
+            entry_function.0,
+            event_type.0,
+        )?;
+
+        let filter = streamer.filter.clone();
+        Ok(streamer.into_event_stream(move |context, batch| {
+            let events: Vec<_> = batch
+                .into_iter()
+                .flat_map(|(_, txn)| {
+                    let version = txn.version;
+                    txn.events
+                        .into_iter()
+                        .filter(|event| filter.matches_event(event))
+                        .map(move |event| EventWithVersion::new(version, event))
+                })
+                .collect();
+            context
+                .latest_state_view()?
+                .as_move_resolver()
+                .as_converter(context.db.clone())
+                .try_into_versioned_events(&events)
+        }))
+    }
+}
+
+impl StreamingApi {
+    /// Validates the request and opens a stream for it
+    fn streamer(
+        &self,
+        api_name: &'static str,
+        start_version: Option<U64>,
+        sender: Option<Address>,
+        entry_function: Option<EntryFunctionId>,
+        event_type: Option<MoveStructTag>,
+    ) -> Result<TransactionStreamer, StreamingError> {
+        if !self.context.streaming_enabled() {
+            return Err(api_disabled(api_name));
+        }
+        self.context
+            .check_api_output_enabled(api_name, &AcceptType::Json)?;
+
+        let filter = StreamFilter::new(sender, entry_function, event_type).map_err(|err| {
+            StreamingError::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+        })?;
+
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let start_version = match start_version {
+            Some(start_version) => {
+                if start_version.0 < latest_ledger_info.oldest_ledger_version.0 {
+                    return Err(version_pruned(start_version.0, &latest_ledger_info));
+                }
+                start_version.0
+            },
+            None => latest_ledger_info.version() + 1,
+        };
+
+        let permit = self.context.try_acquire_streaming_permit().ok_or_else(|| {
+            StreamingError::service_unavailable_with_code(
+                "Too many open streams, please retry later",
+                AptosErrorCode::InternalError,
+                &latest_ledger_info,
+            )
+        })?;
+
+        Ok(TransactionStreamer {
+            commits: self.context.subscribe_to_commits(),
+            context: self.context.clone(),
+            filter,
+            next_version: start_version,
+            timestamp: None,
+            _permit: permit,
+        })
+    }
+}
+
+/// Server-side filters of a stream, all of which must match.
+#[derive(Clone)]
+struct StreamFilter {
+    sender: Option<AccountAddress>,
+    entry_function: Option<(ModuleId, Identifier)>,
+    event_type: Option<StructTag>,
+}
+
+impl StreamFilter {
+    fn new(
+        sender: Option<Address>,
+        entry_function: Option<EntryFunctionId>,
+        event_type: Option<MoveStructTag>,
+    ) -> anyhow::Result<Self> {
+        let entry_function = entry_function
+            .map(|entry_function| {
+                entry_function
+                    .verify()
+                    .context("'entry_function' invalid")?;
+                anyhow::Ok((ModuleId::from(entry_function.module), entry_function.name.0))
+            })
+            .transpose()?;
+        let event_type = event_type
+            .map(|event_type| {
+                event_type.verify(0).context("'event_type' invalid")?;
+                StructTag::try_from(event_type).context("'event_type' invalid")
+            })
+            .transpose()?;
+
+        Ok(Self {
+            sender: sender.map(Into::into),
+            entry_function,
+            event_type,
+        })
+    }
+
+    fn matches_transaction(&self, txn: &TransactionOnChainData) -> bool {
+        let user_txn = txn.transaction.try_as_signed_user_txn();
+        if let Some(sender) = self.sender {
+            if user_txn.map(|txn| txn.sender()) != Some(sender) {
+                return false;
+            }
+        }
+        if let Some((module, function)) = &self.entry_function {
+            match user_txn.map(|txn| txn.payload()) {
+                Some(TransactionPayload::EntryFunction(entry_function))
+                    if entry_function.module() == module
+                        && entry_function.function() == function.as_ident_str() => {},
+                _ => return false,
+            }
+        }
+        if self.event_type.is_some() && !txn.events.iter().any(|e| self.matches_event(e)) {
+            return false;
+        }
+        true
+    }
+
+    fn matches_event(&self, event: &ContractEvent) -> bool {
+        match (&self.event_type, event.type_tag()) {
+            (None, _) => true,
+            (Some(event_type), TypeTag::Struct(tag)) => event_type == tag.as_ref(),
+            (Some(_), _) => false,
+        }
+    }
+}
+
+/// Follows the committed transactions from a version on, in batches.
+struct TransactionStreamer {
+    context: Arc<Context>,
+    filter: StreamFilter,
+    /// Notified whenever new transactions are committed.
+    commits: watch::Receiver<Version>,
+    /// Version of the next transaction to go through the filter.
+    next_version: Version,
+    /// Timestamp of the block the next transaction belongs to, initialized
+    /// lazily and tracked through the block metadata transactions after.
+    timestamp: Option<u64>,
+    /// Keeps a slot for open streams until the stream is dropped.
+    _permit: OwnedSemaphorePermit,
+}
+
+impl TransactionStreamer {
+    /// Turns the streamer into an endless server-sent event stream, rendering
+    /// each batch of matching transactions with `render`. The stream ends on
+    /// the first error, as it can't be reported once the response started.
+    fn into_event_stream<T, F>(self, render: F) -> EventStream<BoxStream<'static, T>>
+    where
+        T: Send + 'static,
+        F: Fn(&Context, Vec<(u64, TransactionOnChainData)>) -> anyhow::Result<Vec<T>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let render = Arc::new(render);
+        let stream = stream::unfold(self, move |mut streamer| {
+            let render = render.clone();
+            async move {
+                let result = match streamer.next_batch().await {
+                    Ok(batch) => render(&streamer.context, batch),
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(items) => Some((stream::iter(items), streamer)),
+                    Err(err) => {
+                        warn!(
+                            next_version = streamer.next_version,
+                            error = ?err,
+                            "Closing transaction stream on error"
+                        );
+                        None
+                    },
+                }
+            }
+        })
+        .flatten()
+        .boxed();
+
+        EventStream::new(stream).keep_alive(KEEP_ALIVE_INTERVAL)
+    }
+
+    /// Returns the next non-empty batch of committed transactions matching the
+    /// filter, with the timestamps of their blocks. Waits for new commits once
+    /// caught up with the chain.
+    async fn next_batch(&mut self) -> anyhow::Result<Vec<(u64, TransactionOnChainData)>> {
+        loop {
+            let ledger_version = self.context.db.get_latest_version()?;
+            if self.next_version > ledger_version {
+                // Commit notifications are a best effort, so fall back to
+                // polling to never miss new transactions.
+                let _ = tokio::time::timeout(
+                    self.context.streaming_poll_interval(),
+                    self.commits.changed(),
+                )
+                .await;
+                continue;
+            }
+
+            let mut timestamp = match self.timestamp {
+                Some(timestamp) => timestamp,
+                None => self.context.db.get_block_timestamp(self.next_version)?,
+            };
+            let limit = std::cmp::min(
+                self.context.max_transactions_page_size() as u64,
+                ledger_version - self.next_version + 1,
+            );
+            let txns =
+                self.context
+                    .get_transactions(self.next_version, limit as u16, ledger_version)?;
+            self.next_version += txns.len() as u64;
+
+            let mut batch = vec![];
+            for txn in txns {
+                if let Some(block_metadata) = txn.transaction.try_as_block_metadata() {
+                    timestamp = block_metadata.timestamp_usecs();
+                }
+                if self.filter.matches_transaction(&txn) {
+                    batch.push((timestamp, txn));
+                }
+            }
+            self.timestamp = Some(timestamp);
+
+            if !batch.is_empty() {
+                return Ok(batch);
+            }
+            // Don't hog the runtime while catching up through non-matching history.
+            tokio::task::yield_now().await;
+        }
+    }
+}
@@ -2,8 +2,9 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, super_new_test_context};
 use aptos_api_test_context::current_function_name;
+use aptos_config::config::NodeConfig;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 
 static ACCOUNT_ADDRESS: &str = "0xa550c18";
@@ -85,6 +86,58 @@ async fn test_get_events_by_account_event_handle() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type() {
+    let mut context = super_new_test_context(current_function_name!(), NodeConfig::default(), true);
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let latest = context.get("/events/by_type/0x1::coin::DepositEvent").await;
+    let latest = latest.as_array().unwrap();
+    assert!(latest.len() >= 2);
+    for event in latest {
+        assert_eq!(event["type"], "0x1::coin::DepositEvent");
+    }
+    // The latest deposits are the ones of the mint and of the transfer.
+    let receivers: Vec<_> = latest[latest.len() - 2..]
+        .iter()
+        .map(|event| event["guid"]["account_address"].clone())
+        .collect();
+    assert_eq!(receivers, vec![
+        creator.address().to_hex_literal(),
+        owner.address().to_hex_literal()
+    ]);
+    assert_eq!(latest[latest.len() - 1]["data"]["amount"], "100000");
+
+    // Pages are numbered by the events of the type.
+    let first_two = context
+        .get("/events/by_type/0x1::coin::DepositEvent?start=0&limit=2")
+        .await;
+    let first = context
+        .get("/events/by_type/0x1::coin::DepositEvent?start=0&limit=1")
+        .await;
+    let second = context
+        .get("/events/by_type/0x1::coin::DepositEvent?start=1&limit=1")
+        .await;
+    assert_eq!(first_two.as_array().unwrap().len(), 2);
+    assert_eq!(first_two[0], first[0]);
+    assert_eq!(first_two[1], second[0]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type_without_indexer() {
+    let context = new_test_context(current_function_name!());
+
+    let resp = context
+        .expect_status_code(403)
+        .get("/events/by_type/0x1::coin::DepositEvent")
+        .await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_events_by_invalid_account_event_handle_struct_address() {
     let mut context = new_test_context(current_function_name!());
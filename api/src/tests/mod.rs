@@ -11,8 +11,10 @@ mod invalid_post_request_test;
 mod modules;
 mod multisig_transactions_test;
 mod objects;
+mod rate_limit_test;
 mod resource_groups;
 mod state_test;
+mod streaming_test;
 mod string_resource_test;
 mod transaction_vector_test;
 mod transactions_test;
@@ -0,0 +1,53 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context_with_config;
+use aptos_api_test_context::current_function_name;
+use aptos_config::config::{NodeConfig, RequestBudget};
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rate_limit_exceeded() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.rate_limit.enabled = true;
+    node_config.api.rate_limit.ip_budgets.reads = RequestBudget::new(1, 2);
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    // The bucket refills once a second, so keep going until it's exhausted.
+    let mut throttled = None;
+    for _ in 0..10 {
+        let resp = context
+            .reply(warp::test::request().method("GET").path("/v1/"))
+            .await;
+        if resp.status() == 429 {
+            throttled = Some(resp);
+            break;
+        }
+        assert_eq!(resp.status(), 200);
+    }
+    let resp = throttled.expect("requests were never throttled");
+
+    let retry_after: u64 = resp.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=2).contains(&retry_after), "{}", retry_after);
+    let body: Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["error_code"], "rate_limit_exceeded");
+
+    // Other classes of endpoints have budgets of their own.
+    let resp = context
+        .reply(
+            warp::test::request()
+                .method("POST")
+                .path("/v1/view")
+                .json(&serde_json::json!({
+                    "function": "0x1::coin::balance",
+                    "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+                    "arguments": [context.validator_owner.to_hex_literal()],
+                })),
+        )
+        .await;
+    assert_eq!(resp.status(), 200);
+}
@@ -3,9 +3,11 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, TestContext};
+use aptos_api_types::{mime_types::BCS, StateValueWithProof};
 use aptos_sdk::{transaction_builder::aptos_stdlib::aptos_token_stdlib, types::LocalAccount};
 use aptos_storage_interface::DbReader;
-use move_core_types::account_address::AccountAddress;
+use aptos_types::{access_path::AccessPath, state_store::state_key::StateKey};
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
 use move_package::BuildConfig;
 use serde::Serialize;
 use serde_json::{json, Value};
@@ -66,6 +68,49 @@ async fn test_get_account_resource_struct_tag_not_found() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof() {
+    let context = new_test_context(current_function_name!());
+    let resource_type: StructTag = "0x1::account::Account".parse().unwrap();
+    for (address, exists) in [("0xA550C18", true), ("0xA550C19", false)] {
+        let req = warp::test::request()
+            .method("GET")
+            .header("Accept", BCS)
+            .path(&format!(
+                "/v1/accounts/{}/resource_with_proof/{}",
+                address, resource_type
+            ));
+        let resp = context.reply(req).await;
+        assert_eq!(resp.status(), 200);
+
+        let value_with_proof: StateValueWithProof = bcs::from_bytes(resp.body()).unwrap();
+        assert_eq!(value_with_proof.value.is_some(), exists);
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(
+                AccountAddress::from_hex_literal(address).unwrap(),
+                resource_type.clone(),
+            )
+            .unwrap(),
+        );
+        value_with_proof.verify(&state_key).unwrap();
+        // The proof doesn't hold for any other key.
+        let other_key = StateKey::access_path(
+            AccessPath::resource_access_path(AccountAddress::ONE, resource_type.clone()).unwrap(),
+        );
+        assert!(value_with_proof.verify(&other_key).is_err());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_resource_with_proof_json_forbidden() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context
+        .expect_status_code(403)
+        .get("/accounts/0xA550C18/resource_with_proof/0x1::account::Account")
+        .await;
+    context.check_golden_output(resp);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_resource_with_version() {
     let mut context = new_test_context(current_function_name!());
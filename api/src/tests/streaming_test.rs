@@ -0,0 +1,196 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{new_test_context, new_test_context_with_config};
+use aptos_api_test_context::{current_function_name, ApiSpecificConfig, TestContext};
+use aptos_config::config::NodeConfig;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Opens a stream on the poem backend directly, as the test proxy buffers whole responses.
+async fn open_stream(context: &TestContext, path_and_query: &str) -> reqwest::Response {
+    let ApiSpecificConfig::V1(address) = context.api_specific_config;
+    reqwest::get(format!("http://{}/v1{}", address, path_and_query))
+        .await
+        .unwrap()
+}
+
+/// Reads the data of server-sent events from the stream until at least `count` were received.
+async fn read_events(stream: &mut reqwest::Response, count: usize) -> Vec<Value> {
+    let mut events = vec![];
+    let mut buffer = String::new();
+    while events.len() < count {
+        let chunk = tokio::time::timeout(Duration::from_secs(30), stream.chunk())
+            .await
+            .expect("timed out waiting for events")
+            .unwrap()
+            .expect("stream ended");
+        buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+        while let Some(end) = buffer.find("\n\n") {
+            let event: String = buffer.drain(..end + 2).collect();
+            events.extend(
+                event
+                    .lines()
+                    .filter_map(|line| line.strip_prefix("data:"))
+                    .map(|data| serde_json::from_str(data.trim_start()).unwrap()),
+            );
+        }
+    }
+    events
+}
+
+fn versions(items: &[Value]) -> Vec<u64> {
+    items
+        .iter()
+        .map(|item| item["version"].as_str().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let start_version = context.get_latest_ledger_info().version() + 1;
+    context.create_account().await;
+
+    let mut stream = open_stream(
+        &context,
+        &format!("/stream/transactions?start_version={}", start_version),
+    )
+    .await;
+    assert_eq!(stream.status(), StatusCode::OK);
+
+    // The block committed before opening the stream.
+    let txns = read_events(&mut stream, 3).await;
+    assert_eq!(
+        versions(&txns[..3]),
+        (start_version..start_version + 3).collect::<Vec<_>>()
+    );
+    assert_eq!(txns[0]["type"], "block_metadata_transaction");
+    assert_eq!(txns[1]["type"], "user_transaction");
+    assert_eq!(txns[2]["type"], "state_checkpoint_transaction");
+
+    // The stream follows the chain.
+    context.create_account().await;
+    let txns = read_events(&mut stream, 3).await;
+    assert_eq!(
+        versions(&txns[..3]),
+        (start_version + 3..start_version + 6).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_transactions_by_sender() {
+    let mut context = new_test_context(current_function_name!());
+    let start_version = context.get_latest_ledger_info().version() + 1;
+    let root = context.root_account().await;
+    let account = context.create_account().await;
+    context.create_account().await;
+
+    let mut stream = open_stream(
+        &context,
+        &format!(
+            "/stream/transactions?start_version={}&sender={}&entry_function=0x1::aptos_account::transfer",
+            start_version,
+            root.address().to_hex_literal()
+        ),
+    )
+    .await;
+    assert_eq!(stream.status(), StatusCode::OK);
+
+    let txns = read_events(&mut stream, 2).await;
+    assert_eq!(versions(&txns[..2]), [start_version + 1, start_version + 4]);
+    for txn in &txns[..2] {
+        assert_eq!(txn["type"], "user_transaction");
+        assert_eq!(txn["sender"], root.address().to_hex_literal());
+    }
+    assert_eq!(
+        txns[0]["payload"]["arguments"][0],
+        account.address().to_hex_literal()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_events() {
+    let mut context = new_test_context(current_function_name!());
+    let start_version = context.get_latest_ledger_info().version() + 1;
+    let account = context.create_account().await;
+
+    let mut stream = open_stream(
+        &context,
+        &format!(
+            "/stream/events?start_version={}&event_type=0x1::coin::DepositEvent",
+            start_version
+        ),
+    )
+    .await;
+    assert_eq!(stream.status(), StatusCode::OK);
+
+    let events = read_events(&mut stream, 1).await;
+    assert_eq!(versions(&events[..1]), vec![start_version + 1]);
+    assert_eq!(events[0]["type"], "0x1::coin::DepositEvent");
+    assert_eq!(
+        events[0]["guid"]["account_address"],
+        account.address().to_hex_literal()
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_closed_by_client_releases_its_slot() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_streaming_subscriptions = 1;
+    node_config.api.streaming_poll_interval_ms = 100;
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let stream = open_stream(&context, "/stream/transactions").await;
+    assert_eq!(stream.status(), StatusCode::OK);
+    let resp = open_stream(&context, "/stream/transactions").await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    // The server notices the closed connection once it writes new transactions to it.
+    drop(stream);
+    for _ in 0..50 {
+        context.create_account().await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let resp = open_stream(&context, "/stream/transactions").await;
+        if resp.status() == StatusCode::OK {
+            return;
+        }
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+    panic!("the slot of the closed stream was not released");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_with_invalid_start_version() {
+    let context = new_test_context(current_function_name!());
+
+    for start_version in ["invalid", "-1"] {
+        let resp = open_stream(
+            &context,
+            &format!("/stream/transactions?start_version={}", start_version),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_with_invalid_filter() {
+    let context = new_test_context(current_function_name!());
+
+    let resp = open_stream(&context, "/stream/events?event_type=0x1::coin").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = open_stream(&context, "/stream/transactions?entry_function=transfer").await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_disabled() {
+    let mut node_config = NodeConfig::default();
+    node_config.api.streaming_enabled = false;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    let resp = open_stream(&context, "/stream/transactions").await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
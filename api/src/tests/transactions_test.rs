@@ -5,13 +5,14 @@
 use super::new_test_context;
 use crate::tests::new_test_context_with_config;
 use aptos_api_test_context::{assert_json, current_function_name, pretty, TestContext};
+use aptos_api_types::HexEncodedBytes;
 use aptos_config::config::NodeConfig;
 use aptos_crypto::{
     ed25519::Ed25519PrivateKey,
     multi_ed25519::{MultiEd25519PrivateKey, MultiEd25519PublicKey},
     PrivateKey, SigningKey, Uniform,
 };
-use aptos_sdk::types::LocalAccount;
+use aptos_sdk::{transaction_builder::aptos_stdlib, types::LocalAccount};
 use aptos_types::{
     account_address::AccountAddress,
     transaction::{
@@ -1192,6 +1193,291 @@ async fn test_gas_estimation_disabled() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_state_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+    let balance = context.get_apt_balance(account.address()).await;
+    let receiver = context.gen_account();
+
+    // Simulation rejects valid signatures, so sign with a different key.
+    let txn = context
+        .transaction_factory()
+        .account_transfer(receiver.address(), balance * 2)
+        .sender(account.address())
+        .sequence_number(account.sequence_number())
+        .gas_unit_price(0)
+        .expiration_timestamp_secs(u64::MAX)
+        .build()
+        .sign(receiver.private_key(), account.public_key().clone())
+        .unwrap()
+        .into_inner();
+    let transaction = HexEncodedBytes::from(bcs::to_bytes(&txn).unwrap());
+
+    // The account can't afford the transfer on-chain.
+    let resp = context
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": [],
+            }),
+        )
+        .await;
+    assert!(!resp[0]["success"].as_bool().unwrap());
+
+    // But it can with an overridden balance.
+    let resp = context
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": [{
+                    "address": account.address().to_hex_literal(),
+                    "coin_balance": (balance * 2).to_string(),
+                    "resources": [],
+                    "modules": [],
+                }],
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+
+    // The overrides are never written to storage.
+    assert_eq!(balance, context.get_apt_balance(account.address()).await);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_coin_balance_override_without_coin_store() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+    // Doesn't exist on-chain, so it has no coin store to receive coins.
+    let receiver = context.gen_account();
+
+    // Simulation rejects valid signatures, so sign with a different key.
+    let txn = context
+        .transaction_factory()
+        .payload(aptos_stdlib::coin_transfer(
+            APTOS_COIN_TYPE.clone(),
+            receiver.address(),
+            1,
+        ))
+        .sender(account.address())
+        .sequence_number(account.sequence_number())
+        .expiration_timestamp_secs(u64::MAX)
+        .build()
+        .sign(receiver.private_key(), account.public_key().clone())
+        .unwrap()
+        .into_inner();
+    let transaction = HexEncodedBytes::from(bcs::to_bytes(&txn).unwrap());
+
+    let resp = context
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": [],
+            }),
+        )
+        .await;
+    assert!(!resp[0]["success"].as_bool().unwrap());
+
+    // Overriding its balance gives it a coin store.
+    let resp = context
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": [{
+                    "address": receiver.address().to_hex_literal(),
+                    "coin_balance": "100",
+                    "resources": [],
+                    "modules": [],
+                }],
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    let coin_store_type = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+    let coin_store = resp[0]["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|change| {
+            change["address"] == receiver.address().to_hex_literal()
+                && change["data"]["type"] == coin_store_type
+        })
+        .unwrap();
+    assert_eq!(coin_store["data"]["data"]["coin"]["value"], "101");
+    assert_eq!(coin_store["data"]["data"]["frozen"], false);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_state_overrides_rejects_foreign_module() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+
+    // Only the bytecode is checked before the transaction, so any transaction will do.
+    let txn = context
+        .transaction_factory()
+        .account_transfer(account.address(), 1)
+        .sender(account.address())
+        .sequence_number(account.sequence_number())
+        .expiration_timestamp_secs(u64::MAX)
+        .build()
+        .sign(
+            context.gen_account().private_key(),
+            account.public_key().clone(),
+        )
+        .unwrap()
+        .into_inner();
+    let module = context.get("/accounts/0x1/module/coin").await;
+
+    context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": HexEncodedBytes::from(bcs::to_bytes(&txn).unwrap()),
+                "state_overrides": [{
+                    "address": account.address().to_hex_literal(),
+                    "resources": [],
+                    "modules": [module["bytecode"]],
+                }],
+            }),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_resource_group_state_overrides() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+    let receiver = context.gen_account();
+    let object = context.gen_account().address();
+
+    // Simulation rejects valid signatures, so sign with a different key.
+    let txn = context
+        .transaction_factory()
+        .payload(aptos_stdlib::object_transfer_call(
+            object,
+            receiver.address(),
+        ))
+        .sender(account.address())
+        .sequence_number(account.sequence_number())
+        .expiration_timestamp_secs(u64::MAX)
+        .build()
+        .sign(receiver.private_key(), account.public_key().clone())
+        .unwrap()
+        .into_inner();
+    let transaction = HexEncodedBytes::from(bcs::to_bytes(&txn).unwrap());
+
+    // ObjectCore is stored in the ObjectGroup resource group, the override must be applied there.
+    let resp = context
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": [{
+                    "address": object.to_hex_literal(),
+                    "resources": [{
+                        "type": "0x1::object::ObjectCore",
+                        "data": {
+                            "allow_ungated_transfer": true,
+                            "guid_creation_num": "1125899906842625",
+                            "owner": account.address().to_hex_literal(),
+                            "transfer_events": {
+                                "counter": "0",
+                                "guid": {
+                                    "id": {
+                                        "addr": object.to_hex_literal(),
+                                        "creation_num": "1125899906842624",
+                                    },
+                                },
+                            },
+                        },
+                    }],
+                    "modules": [],
+                }],
+            }),
+        )
+        .await;
+    assert!(resp[0]["success"].as_bool().unwrap(), "{}", pretty(&resp));
+    let object_core = resp[0]["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|change| change["data"]["type"] == "0x1::object::ObjectCore")
+        .unwrap();
+    assert_eq!(
+        object_core["data"]["data"]["owner"],
+        receiver.address().to_hex_literal()
+    );
+
+    // The resource group itself can't be overridden.
+    context
+        .expect_status_code(400)
+        .post(
+            "/transactions/simulate_with_overrides",
+            json!({
+                "transaction": transaction,
+                "state_overrides": [{
+                    "address": object.to_hex_literal(),
+                    "resources": [{
+                        "type": "0x1::object::ObjectGroup",
+                        "data": {},
+                    }],
+                    "modules": [],
+                }],
+            }),
+        )
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_simulate_transaction_with_trace() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.create_account().await;
+    let receiver = context.gen_account();
+
+    // Simulation rejects valid signatures, so sign with a different key.
+    let txn = context
+        .transaction_factory()
+        .account_transfer(receiver.address(), 1)
+        .sender(account.address())
+        .sequence_number(account.sequence_number())
+        .expiration_timestamp_secs(u64::MAX)
+        .build()
+        .sign(receiver.private_key(), account.public_key().clone())
+        .unwrap()
+        .into_inner();
+    let body = bcs::to_bytes(&txn).unwrap();
+
+    let resp = context
+        .post_bcs_txn("/transactions/simulate_with_trace", &body)
+        .await;
+    assert!(
+        resp["transaction"]["success"].as_bool().unwrap(),
+        "{}",
+        pretty(&resp)
+    );
+
+    let trace = &resp["trace"];
+    assert_eq!(trace["function"], "0x1::aptos_account::transfer");
+    assert_eq!(trace["args"][1], "0x0100000000000000");
+    assert_eq!(trace["return_values"], json!([]));
+
+    // The transfer withdraws from the sender and deposits to the receiver somewhere down the
+    // call tree.
+    let rendered = trace.to_string();
+    assert!(rendered.contains(r#""function":"0x1::coin::withdraw""#));
+    assert!(rendered.contains(r#""kind":"emit_event","type":"0x1::coin::DepositEvent""#));
+
+    // Traces are never included in the transactions returned by the other endpoints.
+    let resp = context.post_bcs_txn("/transactions/simulate", &body).await;
+    assert!(resp[0].get("trace").is_none());
+}
+
 fn gen_string(len: u64) -> String {
     let mut rng = thread_rng();
     std::iter::repeat(())
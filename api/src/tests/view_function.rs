@@ -1,8 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_config};
 use aptos_api_test_context::current_function_name;
+use aptos_config::config::NodeConfig;
+use move_core_types::{account_address::AccountAddress, vm_status::StatusCode};
 use serde_json::json;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -79,6 +81,82 @@ async fn test_versioned_simple_view() {
     context.check_golden_output_no_prune(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let resp = context
+        .post(
+            "/view/batch",
+            json!([
+                {
+                    "function":"0x1::coin::balance",
+                    "arguments": vec![owner.address().to_string()],
+                    "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+                },
+                {
+                    "function":"0x1::aptos_account::assert_account_exists",
+                    "arguments": vec![owner.address().to_string()],
+                    "type_arguments": [],
+                },
+            ]),
+        )
+        .await;
+
+    // The failing view function doesn't fail the other one.
+    assert_eq!(resp[0]["values"], json!(["100000"]));
+    assert!(resp[0]["error"].is_null());
+    assert_ne!(resp[0]["gas_used"], json!("0"));
+    assert_eq!(resp[1]["values"], json!([]));
+    assert_eq!(
+        resp[1]["error"]["vm_error_code"],
+        json!(StatusCode::INVALID_MAIN_FUNCTION_SIGNATURE as u64)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_batch_gas_budget() {
+    let balance = json!({
+        "function":"0x1::coin::balance",
+        "arguments": vec![AccountAddress::ONE.to_hex_literal()],
+        "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+    });
+    let batch = json!([balance, balance, balance]);
+    let gas_used = |result: &serde_json::Value| -> u64 {
+        result["gas_used"].as_str().unwrap().parse().unwrap()
+    };
+
+    let context = new_test_context(current_function_name!());
+    let resp = context.post("/view/batch", batch.clone()).await;
+    let gas_per_call = gas_used(&resp[0]);
+    assert!(gas_per_call > 0);
+
+    // Leave enough gas for a single call and a half.
+    let mut node_config = NodeConfig::default();
+    node_config.api.max_gas_view_function_batch = 2 * gas_per_call - 1;
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+    let resp = context.post("/view/batch", batch).await;
+
+    assert!(resp[0]["error"].is_null());
+    assert_eq!(gas_used(&resp[0]), gas_per_call);
+    assert_eq!(
+        resp[1]["error"]["vm_error_code"],
+        json!(StatusCode::OUT_OF_GAS as u64)
+    );
+    assert_eq!(gas_used(&resp[1]), gas_per_call - 1);
+    assert_eq!(
+        resp[2]["error"]["message"],
+        json!("Gas budget of the batch exhausted")
+    );
+    assert_eq!(gas_used(&resp[2]), 0);
+}
+
 #[ignore] // TODO: reactivate with real source
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_view_tuple() {
@@ -18,33 +18,52 @@ use crate::{
     },
     ApiTags,
 };
-use anyhow::{anyhow, Context as AnyhowContext};
+use anyhow::{anyhow, bail, Context as AnyhowContext};
 use aptos_api_types::{
     verify_function_identifier, verify_module_identifier, Address, AptosError, AptosErrorCode,
     AsConverter, EncodeSubmissionRequest, GasEstimation, GasEstimationBcs, HashValue,
-    HexEncodedBytes, LedgerInfo, MoveType, PendingTransaction, SubmitTransactionRequest,
-    Transaction, TransactionData, TransactionOnChainData, TransactionsBatchSingleSubmissionFailure,
-    TransactionsBatchSubmissionResult, UserTransaction, VerifyInput, VerifyInputWithRecursion,
-    MAX_RECURSIVE_TYPES_ALLOWED, U64,
+    HexEncodedBytes, LedgerInfo, MoveConverter, MoveResource, MoveType, PendingTransaction,
+    SimulateTransactionWithOverridesRequest, StateOverride, SubmitTransactionRequest,
+    TracedUserTransaction, Transaction, TransactionData, TransactionOnChainData,
+    TransactionsBatchSingleSubmissionFailure, TransactionsBatchSubmissionResult, UserTransaction,
+    VerifyInput, VerifyInputWithRecursion, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_crypto::{hash::CryptoHash, signing_message};
+use aptos_gas::StandardGasMeter;
+use aptos_gas_profiling::{CallTrace as VmCallTrace, ExecutionTracer};
+use aptos_state_view::{
+    account_with_state_view::AsAccountWithStateView,
+    state_view_with_overrides::StateViewWithOverrides,
+};
 use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
     account_config::CoinStoreResource,
     account_view::AccountView,
+    event::{EventHandle, EventKey},
     mempool_status::MempoolStatusCode,
+    state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
         RawTransactionWithData, SignedTransaction, TransactionPayload, TransactionStatus,
     },
     vm_status::StatusCode,
 };
-use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
+use aptos_vm::{data_cache::AsMoveResolver, move_vm_ext::MoveResolverExt, AptosVM};
+use move_binary_format::CompiledModule;
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    move_resource::MoveStructType,
+};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
     ApiRequest, OpenApi,
 };
-use std::sync::Arc;
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap},
+    sync::Arc,
+};
 
 generate_success_response!(SubmitTransactionResponse, (202, Accepted));
 
@@ -521,8 +540,140 @@ impl TransactionsApi {
             );
         }
 
-        self.simulate(&accept_type, ledger_info, signed_transaction)
-            .await
+        self.simulate(
+            &accept_type,
+            ledger_info,
+            signed_transaction,
+            HashMap::new(),
+        )
+        .await
+    }
+
+    /// Simulate transaction with state overrides
+    ///
+    /// Simulates a transaction the same way as /transactions/simulate, but against the latest
+    /// on-chain state with the given overrides applied on top of it.  This makes it possible to
+    /// simulate transactions that depend on state that doesn't exist yet, e.g. a balance that
+    /// hasn't been funded or a module that hasn't been published.  The overrides only apply to
+    /// the simulation, nothing is written to storage.
+    ///
+    /// The transaction must be a BCS encoded SignedTransaction with a non-valid signature. See
+    /// SignedTransaction in types/src/transaction/mod.rs.
+    #[oai(
+        path = "/transactions/simulate_with_overrides",
+        method = "post",
+        operation_id = "simulate_transaction_with_overrides",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_with_overrides(
+        &self,
+        accept_type: AcceptType,
+        data: Json<SimulateTransactionWithOverridesRequest>,
+    ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        data.0
+            .verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_overrides")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        let SimulateTransactionWithOverridesRequest {
+            transaction,
+            state_overrides,
+        } = data.0;
+        let signed_transaction = self
+            .get_signed_transaction(&ledger_info, SubmitTransactionPost::Bcs(Bcs(transaction.0)))?;
+        let state_overrides = self.resolve_state_overrides(&ledger_info, state_overrides)?;
+
+        self.simulate(
+            &accept_type,
+            ledger_info,
+            signed_transaction,
+            state_overrides,
+        )
+        .await
+    }
+
+    /// Simulate transaction with execution trace
+    ///
+    /// Simulates a transaction the same way as /transactions/simulate, but runs it with the
+    /// execution tracer and returns the call trace of the transaction along with its output.
+    /// The trace records every function call with its arguments and return values, and the
+    /// resources and events accessed during the call.  Only JSON output is supported.
+    ///
+    /// To use this endpoint with BCS, you must submit a SignedTransaction
+    /// encoded as BCS. See SignedTransaction in types/src/transaction/mod.rs.
+    #[oai(
+        path = "/transactions/simulate_with_trace",
+        method = "post",
+        operation_id = "simulate_transaction_with_trace",
+        tag = "ApiTags::Transactions"
+    )]
+    async fn simulate_transaction_with_trace(
+        &self,
+        accept_type: AcceptType,
+        data: SubmitTransactionPost,
+    ) -> SimulateTransactionResult<TracedUserTransaction> {
+        data.verify()
+            .context("Simulated transaction invalid")
+            .map_err(|err| {
+                SubmitTransactionError::bad_request_with_code_no_info(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        fail_point_poem("endpoint_simulate_transaction_with_trace")?;
+        if !self.context.node_config.api.transaction_simulation_enabled {
+            return Err(api_disabled("Simulate transaction"));
+        }
+        self.context
+            .check_api_output_enabled("Simulate transaction", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+        if accept_type == AcceptType::Bcs {
+            return Err(SubmitTransactionError::bad_request_with_code(
+                "Execution traces are only supported for JSON output",
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+        let signed_transaction = self.get_signed_transaction(&ledger_info, data)?;
+
+        let (simulated_txn, call_trace) =
+            self.simulate_output(&ledger_info, signed_transaction, HashMap::new(), true)?;
+        let trace = call_trace.ok_or_else(|| {
+            SubmitTransactionError::internal_with_code(
+                "Simulated transaction has no execution trace",
+                AptosErrorCode::InternalError,
+                &ledger_info,
+            )
+        })?;
+        let transaction = self
+            .render_simulated_transaction(&ledger_info, simulated_txn)?
+            .pop()
+            .ok_or_else(|| {
+                SubmitTransactionError::internal_with_code(
+                    "Simulation returned no transaction",
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        BasicResponse::try_from_json((
+            TracedUserTransaction {
+                transaction,
+                trace: trace.into(),
+            },
+            &ledger_info,
+            BasicResponseStatus::Ok,
+        ))
     }
 
     /// Encode submission
@@ -1160,7 +1311,7 @@ impl TransactionsApi {
     // TODO: This function leverages a lot of types from aptos_types, use the
     // local API types and just return those directly, instead of converting
     // from these types in render_transactions.
-    /// Simulate a transaction in the VM
+    /// Simulate a transaction in the VM, with `state_overrides` layered over the latest state
     ///
     /// Note: this returns a `Vec<UserTransaction>`, but for backwards compatibility, this can't
     /// be removed even though, there is only one possible transaction
@@ -1169,20 +1320,68 @@ impl TransactionsApi {
         accept_type: &AcceptType,
         ledger_info: LedgerInfo,
         txn: SignedTransaction,
+        state_overrides: HashMap<StateKey, StateValue>,
     ) -> SimulateTransactionResult<Vec<UserTransaction>> {
+        let (simulated_txn, _) = self.simulate_output(&ledger_info, txn, state_overrides, false)?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let user_transactions =
+                    self.render_simulated_transaction(&ledger_info, simulated_txn)?;
+                BasicResponse::try_from_json((
+                    user_transactions,
+                    &ledger_info,
+                    BasicResponseStatus::Ok,
+                ))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
+    /// Executes a simulated transaction in the VM, optionally with the execution tracer
+    fn simulate_output(
+        &self,
+        ledger_info: &LedgerInfo,
+        txn: SignedTransaction,
+        state_overrides: HashMap<StateKey, StateValue>,
+        trace: bool,
+    ) -> Result<(TransactionOnChainData, Option<VmCallTrace>), SubmitTransactionError> {
         // Transactions shouldn't have a valid signature or this could be used to attack
         if txn.signature_is_valid() {
             return Err(SubmitTransactionError::bad_request_with_code(
                 "Simulated transactions must have a non-valid signature",
                 AptosErrorCode::InvalidInput,
-                &ledger_info,
+                ledger_info,
             ));
         }
 
         // Simulate transaction
-        let state_view = self.context.latest_state_view_poem(&ledger_info)?;
+        let state_view = StateViewWithOverrides::new(
+            self.context.latest_state_view_poem(ledger_info)?,
+            state_overrides,
+        );
         let move_resolver = state_view.as_move_resolver();
-        let (_, output) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
+        let (output, call_trace) = if trace {
+            let (_, output, tracer) = AptosVM::simulate_signed_transaction_with_custom_gas_meter(
+                &txn,
+                &move_resolver,
+                |gas_feature_version, gas_params, storage_gas_params, balance| {
+                    let gas_meter = StandardGasMeter::new(
+                        gas_feature_version,
+                        gas_params,
+                        storage_gas_params,
+                        balance,
+                    );
+                    Ok(ExecutionTracer::for_payload(gas_meter, txn.payload()))
+                },
+            );
+            (output, tracer.map(ExecutionTracer::finish))
+        } else {
+            let (_, output) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
+            (output, None)
+        };
         let version = ledger_info.version();
 
         // Ensure that all known statuses return their values in the output (even if they aren't supposed to)
@@ -1212,38 +1411,174 @@ impl TransactionsApi {
             accumulator_root_hash: zero_hash,
             changes: output.write_set().clone(),
         };
+        Ok((simulated_txn, call_trace))
+    }
 
-        match accept_type {
-            AcceptType::Json => {
-                let transactions = self
-                    .context
-                    .render_transactions_non_sequential(&ledger_info, vec![simulated_txn])?;
-
-                // Users can only make requests to simulate UserTransactions, so unpack
-                // the Vec<Transaction> into Vec<UserTransaction>.
-                let mut user_transactions = Vec::new();
-                for transaction in transactions.into_iter() {
-                    match transaction {
-                        Transaction::UserTransaction(user_txn) => user_transactions.push(*user_txn),
-                        _ => {
-                            return Err(SubmitTransactionError::internal_with_code(
-                                "Simulation transaction resulted in a non-UserTransaction",
-                                AptosErrorCode::InternalError,
-                                &ledger_info,
-                            ))
-                        },
-                    }
+    /// Renders a simulated transaction as JSON
+    fn render_simulated_transaction(
+        &self,
+        ledger_info: &LedgerInfo,
+        simulated_txn: TransactionOnChainData,
+    ) -> Result<Vec<UserTransaction>, SubmitTransactionError> {
+        let transactions = self
+            .context
+            .render_transactions_non_sequential(ledger_info, vec![simulated_txn])?;
+
+        // Users can only make requests to simulate UserTransactions, so unpack
+        // the Vec<Transaction> into Vec<UserTransaction>.
+        let mut user_transactions = Vec::new();
+        for transaction in transactions.into_iter() {
+            match transaction {
+                Transaction::UserTransaction(user_txn) => user_transactions.push(*user_txn),
+                _ => {
+                    return Err(SubmitTransactionError::internal_with_code(
+                        "Simulation transaction resulted in a non-UserTransaction",
+                        AptosErrorCode::InternalError,
+                        ledger_info,
+                    ))
+                },
+            }
+        }
+        Ok(user_transactions)
+    }
+
+    /// Resolves state overrides of a simulation into the raw state values they replace
+    fn resolve_state_overrides(
+        &self,
+        ledger_info: &LedgerInfo,
+        state_overrides: Vec<StateOverride>,
+    ) -> Result<HashMap<StateKey, StateValue>, SubmitTransactionError> {
+        let state_view = self.context.latest_state_view_poem(ledger_info)?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+
+        let mut resolved = HashMap::new();
+        let mut resource_groups = BTreeMap::new();
+        for state_override in state_overrides {
+            let address: AccountAddress = state_override.address.into();
+            let mut insert = |access_path: AccessPath, bytes: Vec<u8>| {
+                resolved.insert(
+                    StateKey::access_path(access_path),
+                    StateValue::new_legacy(bytes),
+                )
+            };
+
+            if let Some(coin_balance) = state_override.coin_balance {
+                let coin_store = state_view
+                    .as_account_with_state_view(&address)
+                    .get_coin_store_resource()
+                    .map_err(|err| {
+                        SubmitTransactionError::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            ledger_info,
+                        )
+                    })?;
+                let coin_store = match coin_store {
+                    Some(coin_store) => CoinStoreResource::new(
+                        coin_balance.0,
+                        coin_store.frozen(),
+                        coin_store.deposit_events().clone(),
+                        coin_store.withdraw_events().clone(),
+                    ),
+                    // Accounts without a coin store, e.g. ones that don't exist yet, get the one
+                    // `coin::register` would create right after the account and its two events
+                    None => CoinStoreResource::new(
+                        coin_balance.0,
+                        false,
+                        EventHandle::new(EventKey::new(2, address), 0),
+                        EventHandle::new(EventKey::new(3, address), 0),
+                    ),
+                };
+                insert(
+                    AccessPath::new(address, CoinStoreResource::struct_tag().access_vector()),
+                    bcs::to_bytes(&coin_store).map_err(|err| {
+                        SubmitTransactionError::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            ledger_info,
+                        )
+                    })?,
+                );
+            }
+
+            for resource in state_override.resources {
+                let (struct_tag, bytes) = resource_override(&resolver, &converter, resource)
+                    .context("Invalid resource state override")
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            ledger_info,
+                        )
+                    })?;
+                // Members of a resource group are stored together in the group, so they are
+                // applied to the group's existing value instead of their own location
+                match resolver.get_resource_group(&struct_tag) {
+                    Some(resource_group) => {
+                        let members = match resource_groups.entry((address, resource_group)) {
+                            Entry::Occupied(entry) => entry.into_mut(),
+                            Entry::Vacant(entry) => {
+                                let (address, resource_group) = entry.key();
+                                let members =
+                                    resource_group_members(&resolver, address, resource_group)
+                                        .map_err(|err| {
+                                            SubmitTransactionError::internal_with_code(
+                                                err,
+                                                AptosErrorCode::InternalError,
+                                                ledger_info,
+                                            )
+                                        })?;
+                                entry.insert(members)
+                            },
+                        };
+                        members.insert(struct_tag, bytes);
+                    },
+                    None => {
+                        let access_path = AccessPath::resource_access_path(address, struct_tag)
+                            .map_err(|err| {
+                                SubmitTransactionError::bad_request_with_code(
+                                    err,
+                                    AptosErrorCode::InvalidInput,
+                                    ledger_info,
+                                )
+                            })?;
+                        insert(access_path, bytes);
+                    },
                 }
-                BasicResponse::try_from_json((
-                    user_transactions,
-                    &ledger_info,
-                    BasicResponseStatus::Ok,
-                ))
-            },
-            AcceptType::Bcs => {
-                BasicResponse::try_from_bcs((simulated_txn, &ledger_info, BasicResponseStatus::Ok))
-            },
+            }
+
+            for module in state_override.modules {
+                let (access_path, bytes) = module_override(address, module)
+                    .context("Invalid module state override")
+                    .map_err(|err| {
+                        SubmitTransactionError::bad_request_with_code(
+                            err,
+                            AptosErrorCode::InvalidInput,
+                            ledger_info,
+                        )
+                    })?;
+                insert(access_path, bytes);
+            }
+        }
+
+        for ((address, resource_group), members) in resource_groups {
+            let bytes = bcs::to_bytes(&members).map_err(|err| {
+                SubmitTransactionError::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    ledger_info,
+                )
+            })?;
+            resolved.insert(
+                StateKey::access_path(AccessPath::resource_group_access_path(
+                    address,
+                    resource_group,
+                )),
+                StateValue::new_legacy(bytes),
+            );
         }
+        Ok(resolved)
     }
 
     /// Encode message as BCS
@@ -1271,8 +1606,23 @@ impl TransactionsApi {
                 BasicError::bad_request_with_code(err, AptosErrorCode::InvalidInput, &ledger_info)
             })?;
 
-        let raw_message = match request.secondary_signers {
-            Some(secondary_signer_addresses) => signing_message(
+        let raw_message = match (request.secondary_signers, request.fee_payer) {
+            (secondary_signer_addresses, Some(fee_payer)) => signing_message(
+                &RawTransactionWithData::new_fee_payer(
+                    raw_txn,
+                    secondary_signer_addresses
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|v| v.into())
+                        .collect(),
+                    fee_payer.into(),
+                ),
+            )
+            .context("Invalid transaction to generate signing message")
+            .map_err(|err| {
+                BasicError::bad_request_with_code(err, AptosErrorCode::InvalidInput, &ledger_info)
+            })?,
+            (Some(secondary_signer_addresses), None) => signing_message(
                 &RawTransactionWithData::new_multi_agent(
                     raw_txn,
                     secondary_signer_addresses
@@ -1285,7 +1635,7 @@ impl TransactionsApi {
             .map_err(|err| {
                 BasicError::bad_request_with_code(err, AptosErrorCode::InvalidInput, &ledger_info)
             })?,
-            None => raw_txn
+            (None, None) => raw_txn
                 .signing_message()
                 .context("Invalid transaction to generate signing message")
                 .map_err(|err| {
@@ -1325,3 +1675,65 @@ fn override_gas_parameters(
     // TODO: Check that signature is null, this would just be helpful for downstream use
     SignedTransaction::new_with_authenticator(raw_txn, signed_txn.authenticator())
 }
+
+/// Converts a JSON resource override into its type and BCS encoded value
+fn resource_override<R: MoveResolverExt + ?Sized>(
+    resolver: &R,
+    converter: &MoveConverter<'_, R>,
+    resource: MoveResource,
+) -> anyhow::Result<(StructTag, Vec<u8>)> {
+    let struct_tag: StructTag = resource.typ.try_into()?;
+    if resolver.is_resource_group(&struct_tag) {
+        bail!(
+            "Resource group {} can't be overridden, override its members instead",
+            struct_tag
+        );
+    }
+    let value = converter.try_into_vm_value(
+        &TypeTag::Struct(Box::new(struct_tag.clone())),
+        serde_json::to_value(resource.data)?,
+    )?;
+    let bytes = value
+        .simple_serialize()
+        .ok_or_else(|| anyhow!("Failed to serialize resource {}", struct_tag))?;
+    Ok((struct_tag, bytes))
+}
+
+/// Reads the current members of a resource group, so that overrides can be applied to them
+fn resource_group_members<R: MoveResolverExt + ?Sized>(
+    resolver: &R,
+    address: &AccountAddress,
+    resource_group: &StructTag,
+) -> anyhow::Result<BTreeMap<StructTag, Vec<u8>>> {
+    let data = resolver
+        .get_resource_group_data(address, resource_group)
+        .map_err(|err| {
+            anyhow!(
+                "Failed to read resource group {}: {:?}",
+                resource_group,
+                err
+            )
+        })?;
+    match data {
+        Some(bytes) => Ok(bcs::from_bytes(&bytes)?),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Checks that a module override deserializes and belongs to `address`
+fn module_override(
+    address: AccountAddress,
+    module: HexEncodedBytes,
+) -> anyhow::Result<(AccessPath, Vec<u8>)> {
+    let module_id = CompiledModule::deserialize(module.inner())
+        .map_err(|err| anyhow!("Failed to deserialize module: {:?}", err))?
+        .self_id();
+    if module_id.address() != &address {
+        bail!(
+            "Module {} can't be published under account {}",
+            module_id,
+            address
+        );
+    }
+    Ok((AccessPath::code_access_path(module_id), module.0))
+}
@@ -5,11 +5,17 @@ use crate::{
     accept_type::AcceptType,
     failpoint::fail_point_poem,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
+        api_forbidden, BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus,
+        BasicResultWith404,
     },
     ApiTags, Context,
 };
-use aptos_api_types::{AptosErrorCode, AsConverter, MoveValue, ViewRequest, U64};
+use aptos_api_types::{
+    AptosErrorCode, AsConverter, ExplainVMStatus, MoveValue, ViewFunctionError, ViewFunctionResult,
+    ViewRequest, U64,
+};
+use aptos_state_view::StateView;
+use aptos_types::{transaction::ExecutionStatus, vm_status::VMStatus};
 use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
 use move_core_types::language_storage::TypeTag;
 use poem_openapi::{param::Query, payload::Json, OpenApi};
@@ -131,4 +137,178 @@ impl ViewFunctionApi {
             },
         }
     }
+
+    /// Execute a batch of view functions
+    ///
+    /// Execute many view functions against the state at the same ledger version, and return
+    /// the result of each in the order of the requests. A failing view function doesn't fail
+    /// the batch, its result holds the error and the gas used instead.
+    ///
+    /// The gas used by the whole batch is limited: once the gas budget of the batch is
+    /// exhausted, the remaining view functions fail without being executed.
+    ///
+    /// Only JSON is supported as an AcceptType.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/batch",
+        method = "post",
+        operation_id = "view_batch",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_batch(
+        &self,
+        accept_type: AcceptType,
+        /// View function requests with type and position arguments
+        requests: Json<Vec<ViewRequest>>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<ViewFunctionResult>> {
+        fail_point_poem("endpoint_view_function_batch")?;
+        if accept_type == AcceptType::Bcs {
+            return Err(api_forbidden(
+                "View function batch",
+                "Only JSON is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("View function batch", &accept_type)?;
+
+        let (ledger_info, requested_version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(
+                ledger_version.map(|inner| inner.0),
+            )?;
+        if requests.0.len() > self.context.max_view_function_batch_size() {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Requested too many view functions: {}, while limit is {}",
+                    requests.0.len(),
+                    self.context.max_view_function_batch_size(),
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let state_view = self
+            .context
+            .state_view_at_version(requested_version)
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+        let mut remaining_gas = self.context.max_gas_view_function_batch();
+        let results = requests
+            .0
+            .into_iter()
+            .map(|request| {
+                let result = self.view_function_in_batch(&state_view, request, remaining_gas);
+                remaining_gas = remaining_gas.saturating_sub(result.gas_used.0);
+                result
+            })
+            .collect::<Vec<_>>();
+
+        BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+    }
+}
+
+impl ViewFunctionApi {
+    /// Executes one view function of a batch with what is left of the gas budget of the batch,
+    /// turning any failure into the error of its result.
+    fn view_function_in_batch(
+        &self,
+        state_view: &impl StateView,
+        request: ViewRequest,
+        remaining_gas: u64,
+    ) -> ViewFunctionResult {
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+        let failed = |gas_used: u64, error: ViewFunctionError| ViewFunctionResult {
+            values: vec![],
+            gas_used: gas_used.into(),
+            error: Some(error),
+        };
+        let error_with_message = |message: String| ViewFunctionError {
+            message,
+            vm_error_code: None,
+            abort_code: None,
+        };
+
+        if remaining_gas == 0 {
+            return failed(
+                0,
+                error_with_message("Gas budget of the batch exhausted".to_string()),
+            );
+        }
+        let entry_func = match converter.convert_view_function(request) {
+            Ok(entry_func) => entry_func,
+            Err(err) => return failed(0, error_with_message(err.to_string())),
+        };
+        let output = AptosVM::execute_view_function_with_gas_used(
+            state_view,
+            entry_func.module().clone(),
+            entry_func.function().to_owned(),
+            entry_func.ty_args().to_owned(),
+            entry_func.args().to_owned(),
+            std::cmp::min(
+                self.context.node_config.api.max_gas_view_function,
+                remaining_gas,
+            ),
+        );
+        let return_vals = match output.values {
+            Ok(return_vals) => return_vals,
+            Err(status) => {
+                return failed(output.gas_used, ViewFunctionError {
+                    message: explain_view_function_status(&converter, &status),
+                    vm_error_code: Some(status.status_code() as u64),
+                    abort_code: status.move_abort_code().map(U64::from),
+                })
+            },
+        };
+
+        let values = converter
+            .function_return_types(&entry_func)
+            .and_then(|tys| {
+                tys.into_iter()
+                    .map(TypeTag::try_from)
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .and_then(|tys| {
+                return_vals
+                    .iter()
+                    .zip(tys.iter())
+                    .map(|(v, ty)| {
+                        let value = converter.try_into_move_value(ty, v)?;
+                        Ok(serde_json::to_value(value)?)
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()
+            });
+        match values {
+            Ok(values) => ViewFunctionResult {
+                values,
+                gas_used: output.gas_used.into(),
+                error: None,
+            },
+            Err(err) => failed(output.gas_used, error_with_message(err.to_string())),
+        }
+    }
+}
+
+/// Describes the status a view function failed with, the same way the status of a committed
+/// transaction is described where possible.
+fn explain_view_function_status(explainer: &impl ExplainVMStatus, status: &VMStatus) -> String {
+    match status.clone().keep_or_discard() {
+        Ok(kept) => explainer.explain_vm_status(&ExecutionStatus::from(kept)),
+        Err(code) => match status.message() {
+            Some(message) => format!("{:?}: {}", code, message),
+            None => format!("{:?}", code),
+        },
+    }
 }
@@ -89,6 +89,8 @@ pub enum AptosErrorCode {
 
     /// The API's inputs were invalid
     InvalidInput = 300,
+    /// The API key of the request is not accepted by this node
+    InvalidApiKey = 301,
 
     /// The transaction was an invalid update to an already submitted transaction.
     InvalidTransactionUpdate = 401,
@@ -101,6 +103,8 @@ pub enum AptosErrorCode {
     HealthCheckFailed = 500,
     /// The mempool is full, no new transactions can be submitted.
     MempoolIsFull = 501,
+    /// The client sent too many requests, it should retry after the `Retry-After` header.
+    RateLimitExceeded = 502,
 
     /// Internal server error
     InternalError = 600,
@@ -2,6 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::HexEncodedBytes;
+use anyhow::Result;
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{SparseMerkleProof, TransactionInfoWithProof},
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 
@@ -10,3 +18,34 @@ use serde::{Deserialize, Serialize};
 pub struct RawStateValueRequest {
     pub key: HexEncodedBytes,
 }
+
+/// A state value read at a state checkpoint, along with the proofs tying it to a signed
+/// ledger info. Only BCS encoded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StateValueWithProof {
+    /// Version of the state checkpoint the value was read at
+    pub version: Version,
+    /// The value, `None` if the key doesn't exist at `version`
+    pub value: Option<StateValue>,
+    /// Proves the value, or its absence, against the state root hash at `version`
+    pub proof: SparseMerkleProof,
+    /// Proves the state root hash at `version` against `ledger_info`
+    pub transaction_info_with_proof: TransactionInfoWithProof,
+    /// Ledger info the proofs are relative to
+    pub ledger_info: LedgerInfoWithSignatures,
+}
+
+impl StateValueWithProof {
+    /// Verifies the value of `state_key` against the ledger info. The signatures of the ledger
+    /// info are NOT verified, which is up to the caller.
+    pub fn verify(&self, state_key: &StateKey) -> Result<()> {
+        self.transaction_info_with_proof
+            .verify(self.ledger_info.ledger_info(), self.version)?;
+        let state_root_hash = self
+            .transaction_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        self.proof
+            .verify(state_root_hash, state_key.hash(), self.value.as_ref())
+    }
+}
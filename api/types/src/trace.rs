@@ -0,0 +1,172 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::UserTransaction;
+use aptos_gas_profiling::{CallTrace as VmCallTrace, TraceEvent as VmTraceEvent};
+use poem_openapi::{Object, Union};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A simulated transaction along with its execution trace
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct TracedUserTransaction {
+    pub transaction: UserTransaction,
+    pub trace: CallTrace,
+}
+
+/// A function call recorded by the execution tracer
+///
+/// Contains everything that happened while the function was on the call stack.  Move values
+/// are rendered as JSON without type information: structs and vectors become arrays,
+/// `vector<u8>` becomes a hex string and integers wider than 32 bits become decimal strings.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct CallTrace {
+    /// Fully qualified name of the function
+    ///
+    /// The root call of a transaction that doesn't call an entry function is named after the
+    /// payload instead, e.g. "script".
+    pub function: String,
+    pub type_args: Vec<String>,
+    pub args: Vec<Value>,
+    /// The values returned by the function, absent if the transaction aborted or ran out of gas
+    /// before the call finished
+    pub return_values: Option<Vec<Value>>,
+    pub is_native: bool,
+    /// Calls and storage accesses made by the function, in execution order
+    pub events: Vec<TraceEvent>,
+}
+
+/// Something that happened during the execution of a function
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Union)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "kind")]
+pub enum TraceEvent {
+    #[oai(mapping = "call")]
+    Call(CallTrace),
+    #[oai(mapping = "load_resource")]
+    LoadResource(LoadResourceTrace),
+    #[oai(mapping = "borrow_global")]
+    BorrowGlobal(BorrowGlobalTrace),
+    #[oai(mapping = "exists")]
+    Exists(ExistsTrace),
+    #[oai(mapping = "move_from")]
+    MoveFrom(MoveFromTrace),
+    #[oai(mapping = "move_to")]
+    MoveTo(MoveToTrace),
+    #[oai(mapping = "emit_event")]
+    EmitEvent(EmitEventTrace),
+}
+
+/// A resource loaded from storage for the first time in the transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct LoadResourceTrace {
+    pub address: String,
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: String,
+    /// Absent if the resource doesn't exist
+    pub value: Option<Value>,
+}
+
+/// A `borrow_global` or `borrow_global_mut` of a resource
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BorrowGlobalTrace {
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: String,
+    pub is_mut: bool,
+    pub is_success: bool,
+}
+
+/// An `exists` check of a resource
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ExistsTrace {
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: String,
+    pub exists: bool,
+}
+
+/// A `move_from` of a resource
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MoveFromTrace {
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: String,
+    /// Absent if the resource doesn't exist
+    pub value: Option<Value>,
+}
+
+/// A `move_to` of a resource
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MoveToTrace {
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: String,
+    pub value: Value,
+    pub is_success: bool,
+}
+
+/// A contract event emitted via `0x1::event::write_to_event_store`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct EmitEventTrace {
+    #[serde(rename = "type")]
+    #[oai(rename = "type")]
+    pub typ: String,
+    pub data: Value,
+}
+
+impl From<VmCallTrace> for CallTrace {
+    fn from(trace: VmCallTrace) -> Self {
+        Self {
+            function: trace.function,
+            type_args: trace.type_args,
+            args: trace.args,
+            return_values: trace.return_values,
+            is_native: trace.is_native,
+            events: trace.events.into_iter().map(TraceEvent::from).collect(),
+        }
+    }
+}
+
+impl From<VmTraceEvent> for TraceEvent {
+    fn from(event: VmTraceEvent) -> Self {
+        match event {
+            VmTraceEvent::Call(trace) => TraceEvent::Call(trace.into()),
+            VmTraceEvent::LoadResource { address, ty, value } => {
+                TraceEvent::LoadResource(LoadResourceTrace {
+                    address,
+                    typ: ty,
+                    value,
+                })
+            },
+            VmTraceEvent::BorrowGlobal {
+                ty,
+                is_mut,
+                is_success,
+            } => TraceEvent::BorrowGlobal(BorrowGlobalTrace {
+                typ: ty,
+                is_mut,
+                is_success,
+            }),
+            VmTraceEvent::Exists { ty, exists } => {
+                TraceEvent::Exists(ExistsTrace { typ: ty, exists })
+            },
+            VmTraceEvent::MoveFrom { ty, value } => {
+                TraceEvent::MoveFrom(MoveFromTrace { typ: ty, value })
+            },
+            VmTraceEvent::MoveTo {
+                ty,
+                value,
+                is_success,
+            } => TraceEvent::MoveTo(MoveToTrace {
+                typ: ty,
+                value,
+                is_success,
+            }),
+            VmTraceEvent::EmitEvent { ty, data } => {
+                TraceEvent::EmitEvent(EmitEventTrace { typ: ty, data })
+            },
+        }
+    }
+}
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryFunctionId, MoveType};
+use crate::{EntryFunctionId, MoveType, U64};
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 
@@ -14,3 +14,28 @@ pub struct ViewRequest {
     /// Arguments of the function
     pub arguments: Vec<serde_json::Value>,
 }
+
+/// Result of a view function executed as part of a batch
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ViewFunctionResult {
+    /// The JSON encoded return values of the function, empty if it failed
+    pub values: Vec<serde_json::Value>,
+    /// Gas used by the function
+    pub gas_used: U64,
+    /// Why the function failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ViewFunctionError>,
+}
+
+/// Why a view function of a batch failed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ViewFunctionError {
+    /// A message describing the error
+    pub message: String,
+    /// The VM status code, if the function failed in the VM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_error_code: Option<u64>,
+    /// The abort code, if the function aborted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abort_code: Option<U64>,
+}
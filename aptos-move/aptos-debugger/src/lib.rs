@@ -1,12 +1,12 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use anyhow::{format_err, Result};
+use anyhow::{bail, format_err, Result};
 use aptos_gas::{
     AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters, StandardGasMeter,
     LATEST_GAS_FEATURE_VERSION,
 };
-use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
+use aptos_gas_profiling::{CallTrace, ExecutionTracer, GasProfiler, TransactionGasLog};
 use aptos_resource_viewer::{AnnotatedAccountStateBlob, AptosValueAnnotator};
 use aptos_rest_client::Client;
 use aptos_state_view::TStateView;
@@ -103,6 +103,86 @@ impl AptosDebugger {
         Ok((status, output, gas_profiler.finish()))
     }
 
+    pub fn execute_transaction_at_version_with_tracer(
+        &self,
+        version: Version,
+        txn: SignedTransaction,
+    ) -> Result<(VMStatus, VMOutput, CallTrace)> {
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let txn = txn
+            .check_signature()
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+
+        let (status, output, tracer) = AptosVM::execute_user_transaction_with_custom_gas_meter(
+            &state_view,
+            &txn,
+            &log_context,
+            |gas_feature_version, gas_params, storage_gas_params, balance| {
+                let gas_meter = StandardGasMeter::new(
+                    gas_feature_version,
+                    gas_params,
+                    storage_gas_params,
+                    balance,
+                );
+                Ok(ExecutionTracer::for_payload(gas_meter, txn.payload()))
+            },
+        )?;
+
+        Ok((status, output, tracer.finish()))
+    }
+
+    /// Re-executes the committed user transaction at the given version with the execution
+    /// tracer enabled.
+    pub async fn trace_past_transaction(
+        &self,
+        version: Version,
+    ) -> Result<(VMStatus, VMOutput, CallTrace)> {
+        let txn = self.get_committed_user_transaction(version).await?;
+        self.execute_transaction_at_version_with_tracer(version, txn)
+    }
+
+    /// Re-executes the committed user transaction at the given version on its own, e.g. to step
+    /// through it with a debugger attached to the VM.
+    pub async fn execute_past_user_transaction(
+        &self,
+        version: Version,
+    ) -> Result<(VMStatus, VMOutput)> {
+        let txn = self.get_committed_user_transaction(version).await?;
+        let state_view = DebuggerStateView::new(self.debugger.clone(), version);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let txn = txn
+            .check_signature()
+            .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
+
+        let (status, output, _) = AptosVM::execute_user_transaction_with_custom_gas_meter(
+            &state_view,
+            &txn,
+            &log_context,
+            |gas_feature_version, gas_params, storage_gas_params, balance| {
+                Ok(StandardGasMeter::new(
+                    gas_feature_version,
+                    gas_params,
+                    storage_gas_params,
+                    balance,
+                ))
+            },
+        )?;
+        Ok((status, output))
+    }
+
+    async fn get_committed_user_transaction(&self, version: Version) -> Result<SignedTransaction> {
+        let (mut txns, _) = self.debugger.get_committed_transactions(version, 1).await?;
+        match txns.pop() {
+            Some(Transaction::UserTransaction(txn)) => Ok(txn),
+            Some(_) => bail!(
+                "Transaction at version {} is not a user transaction",
+                version
+            ),
+            None => bail!("Transaction at version {} not found", version),
+        }
+    }
+
     pub async fn execute_past_transactions(
         &self,
         mut begin: Version,
@@ -24,8 +24,13 @@ pub struct Argument {
     #[clap(long)]
     begin_version: u64,
 
-    #[clap(long)]
-    limit: u64,
+    #[clap(long, required_unless_present = "trace")]
+    limit: Option<u64>,
+
+    /// Instead of replaying transactions, trace the user transaction at `--begin-version` and
+    /// write its JSON execution trace to this file.
+    #[clap(long, parse(from_os_str))]
+    trace: Option<PathBuf>,
 
     #[clap(long, default_value = "1")]
     concurrency_level: usize,
@@ -44,10 +49,21 @@ async fn main() -> Result<()> {
         Target::DB { path } => AptosDebugger::db(path)?,
     };
 
+    if let Some(trace_path) = args.trace {
+        let (vm_status, _, trace) = debugger.trace_past_transaction(args.begin_version).await?;
+        std::fs::write(&trace_path, serde_json::to_vec_pretty(&trace)?)?;
+        println!(
+            "Transaction finished with {}, execution trace saved to {}",
+            vm_status,
+            trace_path.display()
+        );
+        return Ok(());
+    }
+
     println!(
         "{:#?}",
         debugger
-            .execute_past_transactions(args.begin_version, args.limit)
+            .execute_past_transactions(args.begin_version, args.limit.unwrap_or_default())
             .await?
     );
 
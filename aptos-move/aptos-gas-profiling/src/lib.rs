@@ -1,9 +1,16 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+#[macro_use]
+mod macros;
+
 mod flamegraph;
 mod log;
 mod profiler;
+mod trace;
+mod tracer;
 
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
+pub use trace::{CallTrace, TraceEvent};
+pub use tracer::ExecutionTracer;
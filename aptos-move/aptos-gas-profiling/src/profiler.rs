@@ -36,27 +36,6 @@ pub struct GasProfiler<G> {
     storage_fees: Option<StorageFees>,
 }
 
-// TODO: consider switching to a library like https://docs.rs/delegate/latest/delegate/.
-macro_rules! delegate {
-    ($(
-        fn $fn: ident $(<$($lt: lifetime),*>)? (&self $(, $arg: ident : $ty: ty)* $(,)?) -> $ret_ty: ty;
-    )*) => {
-        $(fn $fn $(<$($lt)*>)? (&self, $($arg: $ty),*) -> $ret_ty {
-            self.base.$fn($($arg),*)
-        })*
-    };
-}
-
-macro_rules! delegate_mut {
-    ($(
-        fn $fn: ident $(<$($lt: lifetime),*>)? (&mut self $(, $arg: ident : $ty: ty)* $(,)?) -> $ret_ty: ty;
-    )*) => {
-        $(fn $fn $(<$($lt)*>)? (&mut self, $($arg: $ty),*) -> $ret_ty {
-            self.base.$fn($($arg),*)
-        })*
-    };
-}
-
 macro_rules! record_bytecode {
     ($(
         $([$op: expr])?
@@ -0,0 +1,262 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId, u256::U256};
+use move_vm_types::views::{ValueView, ValueVisitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// A function call recorded by the execution tracer, along with everything that happened while
+/// it was on the call stack.
+///
+/// Move values are rendered as JSON without type information: structs and vectors become arrays,
+/// `vector<u8>` becomes a hex string and integers wider than 32 bits become decimal strings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CallTrace {
+    /// Fully qualified name of the function. The root frame of a transaction that doesn't call
+    /// an entry function is named after the payload instead, e.g. "script".
+    pub function: String,
+    pub type_args: Vec<String>,
+    pub args: Vec<JsonValue>,
+    /// The values returned by the function, or `None` if it did not return, i.e. the
+    /// transaction aborted or ran out of gas before the call finished.
+    pub return_values: Option<Vec<JsonValue>>,
+    pub is_native: bool,
+    pub events: Vec<TraceEvent>,
+}
+
+/// Something that happened during the execution of a function.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TraceEvent {
+    Call(CallTrace),
+    /// A resource was loaded from storage for the first time in this transaction.
+    LoadResource {
+        address: String,
+        #[serde(rename = "type")]
+        ty: String,
+        /// `None` if the resource does not exist.
+        value: Option<JsonValue>,
+    },
+    BorrowGlobal {
+        #[serde(rename = "type")]
+        ty: String,
+        is_mut: bool,
+        is_success: bool,
+    },
+    Exists {
+        #[serde(rename = "type")]
+        ty: String,
+        exists: bool,
+    },
+    MoveFrom {
+        #[serde(rename = "type")]
+        ty: String,
+        /// `None` if the resource does not exist.
+        value: Option<JsonValue>,
+    },
+    MoveTo {
+        #[serde(rename = "type")]
+        ty: String,
+        value: JsonValue,
+        is_success: bool,
+    },
+    /// A contract event emitted via `0x1::event::write_to_event_store`.
+    EmitEvent {
+        #[serde(rename = "type")]
+        ty: String,
+        data: JsonValue,
+    },
+}
+
+impl CallTrace {
+    pub(crate) fn new(function: String, type_args: Vec<String>, args: Vec<JsonValue>) -> Self {
+        Self {
+            function,
+            type_args,
+            args,
+            return_values: None,
+            is_native: false,
+            events: vec![],
+        }
+    }
+}
+
+pub(crate) fn function_name(module_id: &ModuleId, func_name: &str) -> String {
+    format!("{}::{}", module_id.short_str_lossless(), func_name)
+}
+
+/// Renders a Move value as JSON, following references.
+pub(crate) fn value_to_json(val: impl ValueView) -> JsonValue {
+    let mut builder = JsonBuilder {
+        open: vec![],
+        result: None,
+    };
+    val.visit(&mut builder);
+    builder.finish()
+}
+
+/// A value visitor that assembles the JSON representation of a value.
+///
+/// Values are visited in pre-order, so a container is complete as soon as a value at the same or
+/// a lower depth is visited.
+struct JsonBuilder {
+    /// Containers that are still being filled, along with the depth they were visited at.
+    open: Vec<(usize, Vec<JsonValue>)>,
+    result: Option<JsonValue>,
+}
+
+impl JsonBuilder {
+    fn close_containers(&mut self, depth: usize) {
+        while matches!(self.open.last(), Some((d, _)) if *d >= depth) {
+            let (_, elems) = self.open.pop().expect("container must exist");
+            self.push(JsonValue::Array(elems));
+        }
+    }
+
+    fn push(&mut self, val: JsonValue) {
+        match self.open.last_mut() {
+            Some((_, elems)) => elems.push(val),
+            None => self.result = Some(val),
+        }
+    }
+
+    fn leaf(&mut self, depth: usize, val: JsonValue) {
+        self.close_containers(depth);
+        self.push(val);
+    }
+
+    fn container(&mut self, depth: usize) -> bool {
+        self.close_containers(depth);
+        self.open.push((depth, vec![]));
+        true
+    }
+
+    fn finish(mut self) -> JsonValue {
+        self.close_containers(0);
+        self.result.unwrap_or(JsonValue::Null)
+    }
+}
+
+impl ValueVisitor for JsonBuilder {
+    fn visit_u8(&mut self, depth: usize, val: u8) {
+        self.leaf(depth, val.into());
+    }
+
+    fn visit_u16(&mut self, depth: usize, val: u16) {
+        self.leaf(depth, val.into());
+    }
+
+    fn visit_u32(&mut self, depth: usize, val: u32) {
+        self.leaf(depth, val.into());
+    }
+
+    fn visit_u64(&mut self, depth: usize, val: u64) {
+        self.leaf(depth, val.to_string().into());
+    }
+
+    fn visit_u128(&mut self, depth: usize, val: u128) {
+        self.leaf(depth, val.to_string().into());
+    }
+
+    fn visit_u256(&mut self, depth: usize, val: U256) {
+        self.leaf(depth, val.to_string().into());
+    }
+
+    fn visit_bool(&mut self, depth: usize, val: bool) {
+        self.leaf(depth, val.into());
+    }
+
+    fn visit_address(&mut self, depth: usize, val: AccountAddress) {
+        self.leaf(depth, val.to_hex_literal().into());
+    }
+
+    fn visit_struct(&mut self, depth: usize, _len: usize) -> bool {
+        self.container(depth)
+    }
+
+    fn visit_vec(&mut self, depth: usize, _len: usize) -> bool {
+        self.container(depth)
+    }
+
+    fn visit_ref(&mut self, depth: usize, _is_global: bool) -> bool {
+        // References are transparent: the referenced value takes the place of the reference.
+        self.close_containers(depth);
+        true
+    }
+
+    fn visit_vec_u8(&mut self, depth: usize, vals: &[u8]) {
+        self.leaf(depth, format!("0x{}", hex::encode(vals)).into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_vm_types::values::{Locals, Struct, Value};
+    use serde_json::json;
+
+    #[test]
+    fn test_primitives_to_json() {
+        assert_eq!(value_to_json(&Value::u8(1)), json!(1));
+        assert_eq!(value_to_json(&Value::u32(2)), json!(2));
+        assert_eq!(
+            value_to_json(&Value::u64(u64::MAX)),
+            json!(u64::MAX.to_string())
+        );
+        assert_eq!(value_to_json(&Value::u128(3)), json!("3"));
+        assert_eq!(value_to_json(&Value::bool(true)), json!(true));
+        assert_eq!(
+            value_to_json(&Value::address(AccountAddress::ONE)),
+            json!("0x1")
+        );
+        assert_eq!(
+            value_to_json(&Value::vector_u8(vec![1, 0xff])),
+            json!("0x01ff")
+        );
+    }
+
+    #[test]
+    fn test_containers_to_json() {
+        let value = Value::struct_(Struct::pack(vec![
+            Value::u64(1),
+            Value::struct_(Struct::pack(vec![Value::bool(true)])),
+            Value::vector_u64(vec![2, 3]),
+        ]));
+        assert_eq!(value_to_json(&value), json!(["1", [true], ["2", "3"]]));
+
+        // Empty containers are closed before their siblings.
+        let value = Value::struct_(Struct::pack(vec![
+            Value::vector_u64(vec![]),
+            Value::struct_(Struct::pack(vec![])),
+            Value::u8(7),
+        ]));
+        assert_eq!(value_to_json(&value), json!([[], [], 7]));
+
+        let value = Value::vector_for_testing_only(vec![
+            Value::struct_(Struct::pack(vec![Value::u8(1)])),
+            Value::struct_(Struct::pack(vec![Value::u8(2)])),
+        ]);
+        assert_eq!(value_to_json(&value), json!([[1], [2]]));
+    }
+
+    #[test]
+    fn test_references_to_json() {
+        let mut locals = Locals::new(2);
+        locals.store_loc(0, Value::u64(1), false).unwrap();
+        locals
+            .store_loc(
+                1,
+                Value::struct_(Struct::pack(vec![Value::u8(2), Value::vector_u8(vec![3])])),
+                false,
+            )
+            .unwrap();
+
+        // References are rendered as the values they point to.
+        assert_eq!(value_to_json(&locals.borrow_loc(0).unwrap()), json!("1"));
+        assert_eq!(
+            value_to_json(&locals.borrow_loc(1).unwrap()),
+            json!([2, "0x03"])
+        );
+    }
+}
@@ -0,0 +1,489 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::trace::{function_name, value_to_json, CallTrace, TraceEvent};
+use aptos_gas::{AptosGasMeter, Fee, Gas, GasScalingFactor};
+use aptos_types::{
+    contract_event::ContractEvent,
+    state_store::state_key::StateKey,
+    transaction::{Multisig, MultisigTransactionPayload, TransactionPayload},
+    write_set::WriteOp,
+};
+use move_binary_format::{
+    errors::{PartialVMResult, VMResult},
+    file_format::CodeOffset,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    language_storage::ModuleId,
+    transaction_argument::TransactionArgument,
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+use serde_json::Value as JsonValue;
+
+/// A gas meter adapter that records the call tree of a transaction, including the arguments and
+/// return values of every call, the resources it accessed and the events it emitted.
+///
+/// All charges are forwarded to the underlying gas meter unchanged, so the transaction executes
+/// exactly as it would without the tracer.
+pub struct ExecutionTracer<G> {
+    base: G,
+
+    frames: Vec<CallTrace>,
+}
+
+impl<G> ExecutionTracer<G> {
+    /// Creates a tracer whose root frame is the entry point of the given transaction payload.
+    ///
+    /// Entry function arguments are recorded as hex-encoded BCS, since their types are only
+    /// known once the function is loaded.
+    pub fn for_payload(base: G, payload: &TransactionPayload) -> Self {
+        use MultisigTransactionPayload as M;
+        use TransactionPayload as P;
+
+        let root = match payload {
+            P::Script(script) => CallTrace::new(
+                "script".to_string(),
+                script.ty_args().iter().map(|ty| ty.to_string()).collect(),
+                script
+                    .args()
+                    .iter()
+                    .map(transaction_argument_to_json)
+                    .collect(),
+            ),
+            P::EntryFunction(entry_function)
+            | P::Multisig(Multisig {
+                transaction_payload: Some(M::EntryFunction(entry_function)),
+                ..
+            }) => CallTrace::new(
+                function_name(entry_function.module(), entry_function.function().as_str()),
+                entry_function
+                    .ty_args()
+                    .iter()
+                    .map(|ty| ty.to_string())
+                    .collect(),
+                entry_function
+                    .args()
+                    .iter()
+                    .map(|arg| format!("0x{}", hex::encode(arg)).into())
+                    .collect(),
+            ),
+            // The payload is stored on chain and only fetched during execution.
+            P::Multisig(Multisig {
+                multisig_address,
+                transaction_payload: None,
+            }) => CallTrace::new(
+                "multisig".to_string(),
+                vec![],
+                vec![multisig_address.to_hex_literal().into()],
+            ),
+            P::ModuleBundle(_) => CallTrace::new("module_bundle".to_string(), vec![], vec![]),
+        };
+
+        Self {
+            base,
+
+            frames: vec![root],
+        }
+    }
+}
+
+impl<G> ExecutionTracer<G>
+where
+    G: AptosGasMeter,
+{
+    fn active_event_stream(&mut self) -> &mut Vec<TraceEvent> {
+        &mut self.frames.last_mut().unwrap().events
+    }
+
+    fn push_frame(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: Vec<String>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) {
+        self.frames.push(CallTrace::new(
+            function_name(module_id, func_name),
+            ty_args,
+            args.map(value_to_json).collect(),
+        ));
+    }
+
+    fn pop_frame(&mut self) {
+        let cur = self.frames.pop().expect("frame must exist");
+        self.active_event_stream().push(TraceEvent::Call(cur));
+    }
+}
+
+impl<G> GasMeter for ExecutionTracer<G>
+where
+    G: AptosGasMeter,
+{
+    delegate_mut! {
+        fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()>;
+
+        fn charge_br_true(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()>;
+
+        fn charge_br_false(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()>;
+
+        fn charge_branch(&mut self, target_offset: CodeOffset) -> PartialVMResult<()>;
+
+        fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()>;
+
+        fn charge_ld_const_after_deserialization(&mut self, val: impl ValueView)
+            -> PartialVMResult<()>;
+
+        fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_pack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_unpack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_write_ref(
+            &mut self,
+            new_val: impl ValueView,
+            old_val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        fn charge_vec_pack<'a>(
+            &mut self,
+            ty: impl TypeView + 'a,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+
+        fn charge_vec_borrow(
+            &mut self,
+            is_mut: bool,
+            ty: impl TypeView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_push_back(
+            &mut self,
+            ty: impl TypeView,
+            val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_pop_back(
+            &mut self,
+            ty: impl TypeView,
+            val: Option<impl ValueView>,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_unpack(
+            &mut self,
+            ty: impl TypeView,
+            expect_num_elements: NumArgs,
+            elems: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+
+        fn charge_drop_frame(
+            &mut self,
+            locals: impl Iterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+    }
+
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.base
+            .charge_call(module_id, func_name, args.clone(), num_locals)?;
+        self.push_frame(module_id, func_name, vec![], args);
+        Ok(())
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let ty_tags = ty_args
+            .clone()
+            .map(|ty| ty.to_type_tag().to_string())
+            .collect();
+
+        self.base
+            .charge_call_generic(module_id, func_name, ty_args, args.clone(), num_locals)?;
+        self.push_frame(module_id, func_name, ty_tags, args);
+        Ok(())
+    }
+
+    fn charge_return_values(
+        &mut self,
+        ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        // The root frame is kept on the stack so that it can be picked up by `finish`.
+        self.frames
+            .last_mut()
+            .expect("frame must exist")
+            .return_values = Some(ret_vals.map(value_to_json).collect());
+        if self.frames.len() > 1 {
+            self.pop_frame();
+        }
+        Ok(())
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        self.base
+            .charge_native_function_before_execution(ty_args.clone(), args.clone())?;
+
+        let frame = self.frames.last_mut().expect("frame must exist");
+        frame.is_native = true;
+        if frame.function == EVENT_NATIVE {
+            // write_to_event_store<T>(guid: vector<u8>, count: u64, msg: T)
+            if let (Some(ty), Some(msg)) = (ty_args.last(), args.last()) {
+                frame.events.push(TraceEvent::EmitEvent {
+                    ty: ty.to_type_tag().to_string(),
+                    data: value_to_json(msg),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView> + Clone>,
+    ) -> PartialVMResult<()> {
+        let return_values = ret_vals
+            .clone()
+            .map(|vals| vals.map(value_to_json).collect());
+
+        let res = self.base.charge_native_function(amount, ret_vals);
+
+        self.frames
+            .last_mut()
+            .expect("frame must exist")
+            .return_values = return_values;
+        self.pop_frame();
+
+        res
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        addr: AccountAddress,
+        ty: impl TypeView,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        let ty_tag = ty.to_type_tag();
+        let value = loaded.as_ref().map(|(_, val)| value_to_json(val));
+
+        self.base.charge_load_resource(addr, ty, loaded)?;
+
+        self.active_event_stream().push(TraceEvent::LoadResource {
+            address: addr.to_hex_literal(),
+            ty: ty_tag.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let ty_tag = ty.to_type_tag();
+
+        self.base
+            .charge_borrow_global(is_mut, is_generic, ty, is_success)?;
+
+        self.active_event_stream().push(TraceEvent::BorrowGlobal {
+            ty: ty_tag.to_string(),
+            is_mut,
+            is_success,
+        });
+        Ok(())
+    }
+
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        let ty_tag = ty.to_type_tag();
+
+        self.base.charge_exists(is_generic, ty, exists)?;
+
+        self.active_event_stream().push(TraceEvent::Exists {
+            ty: ty_tag.to_string(),
+            exists,
+        });
+        Ok(())
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let ty_tag = ty.to_type_tag();
+        let value = val.as_ref().map(value_to_json);
+
+        self.base.charge_move_from(is_generic, ty, val)?;
+
+        self.active_event_stream().push(TraceEvent::MoveFrom {
+            ty: ty_tag.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        let ty_tag = ty.to_type_tag();
+        let value = value_to_json(&val);
+
+        self.base.charge_move_to(is_generic, ty, val, is_success)?;
+
+        self.active_event_stream().push(TraceEvent::MoveTo {
+            ty: ty_tag.to_string(),
+            value,
+            is_success,
+        });
+        Ok(())
+    }
+}
+
+impl<G> AptosGasMeter for ExecutionTracer<G>
+where
+    G: AptosGasMeter,
+{
+    delegate! {
+        fn feature_version(&self) -> u64;
+
+        fn balance(&self) -> Gas;
+
+        fn gas_unit_scaling_factor(&self) -> GasScalingFactor;
+
+        fn io_gas_per_write(&self, key: &StateKey, op: &WriteOp) -> InternalGas;
+
+        fn storage_fee_per_write(&self, key: &StateKey, op: &WriteOp) -> Fee;
+
+        fn storage_fee_per_event(&self, event: &ContractEvent) -> Fee;
+
+        fn storage_discount_for_events(&self, total_cost: Fee) -> Fee;
+
+        fn storage_fee_for_transaction_storage(&self, txn_size: NumBytes) -> Fee;
+    }
+
+    delegate_mut! {
+        fn charge_execution(&mut self, amount: InternalGas) -> PartialVMResult<()>;
+
+        fn charge_io(&mut self, amount: InternalGas) -> PartialVMResult<()>;
+
+        fn charge_storage_fee(
+            &mut self,
+            amount: aptos_gas::Fee,
+            gas_unit_price: aptos_gas::FeePerGasUnit,
+        ) -> PartialVMResult<()>;
+
+        fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()>;
+    }
+
+    fn charge_io_gas_for_write_set<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()> {
+        self.base.charge_io_gas_for_write_set(ops)
+    }
+
+    fn charge_storage_fee_for_all<'a>(
+        &mut self,
+        write_ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+        events: impl IntoIterator<Item = &'a ContractEvent>,
+        txn_size: NumBytes,
+        gas_unit_price: aptos_gas::FeePerGasUnit,
+    ) -> VMResult<()> {
+        self.base
+            .charge_storage_fee_for_all(write_ops, events, txn_size, gas_unit_price)
+    }
+}
+
+impl<G> ExecutionTracer<G>
+where
+    G: AptosGasMeter,
+{
+    /// Returns the root frame of the trace. Frames that are still on the call stack, because
+    /// execution stopped inside of them, are folded into their callers.
+    pub fn finish(mut self) -> CallTrace {
+        while self.frames.len() > 1 {
+            self.pop_frame();
+        }
+
+        self.frames.pop().expect("frame must exist")
+    }
+}
+
+const EVENT_NATIVE: &str = "0x1::event::write_to_event_store";
+
+fn transaction_argument_to_json(arg: &TransactionArgument) -> JsonValue {
+    use TransactionArgument as A;
+
+    match arg {
+        A::U8(val) => (*val).into(),
+        A::U16(val) => (*val).into(),
+        A::U32(val) => (*val).into(),
+        A::U64(val) => val.to_string().into(),
+        A::U128(val) => val.to_string().into(),
+        A::U256(val) => val.to_string().into(),
+        A::Address(addr) => addr.to_hex_literal().into(),
+        A::U8Vector(bytes) => format!("0x{}", hex::encode(bytes)).into(),
+        A::Bool(val) => (*val).into(),
+    }
+}
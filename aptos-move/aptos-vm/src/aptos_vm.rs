@@ -33,10 +33,10 @@ use aptos_types::{
     block_metadata::BlockMetadata,
     on_chain_config::{new_epoch_event_key, FeatureFlag, TimedFeatureOverride},
     transaction::{
-        EntryFunction, ExecutionError, ExecutionStatus, ModuleBundle, Multisig,
-        MultisigTransactionPayload, SignatureCheckedTransaction, SignedTransaction, Transaction,
-        TransactionOutput, TransactionPayload, TransactionStatus, VMValidatorResult,
-        WriteSetPayload,
+        authenticator::AccountAuthenticator, EntryFunction, ExecutionError, ExecutionStatus,
+        ModuleBundle, Multisig, MultisigTransactionPayload, SignatureCheckedTransaction,
+        SignedTransaction, Transaction, TransactionOutput, TransactionPayload, TransactionStatus,
+        VMValidatorResult, WriteSetPayload,
     },
     vm_status::{AbortLocation, StatusCode, VMStatus},
     write_set::WriteSet,
@@ -80,6 +80,7 @@ static NUM_EXECUTION_SHARD: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
+static CONFLICT_ANALYTICS: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
 
 pub static RAYON_EXEC_POOL: Lazy<Arc<rayon::ThreadPool>> = Lazy::new(|| {
@@ -98,6 +99,15 @@ pub fn allow_module_bundle_for_test() {
     MODULE_BUNDLE_DISALLOWED.store(false, Ordering::Relaxed);
 }
 
+/// Output of a view function executed by [`AptosVM::execute_view_function_with_gas_used`].
+#[derive(Debug)]
+pub struct ViewFunctionOutput {
+    /// The BCS encoded return values, or the status the function failed with
+    pub values: Result<Vec<Vec<u8>>, VMStatus>,
+    /// Gas used by the function, in gas units
+    pub gas_used: u64,
+}
+
 pub struct AptosVM(pub(crate) AptosVMImpl);
 
 struct AptosSimulationVM(AptosVM);
@@ -209,6 +219,21 @@ impl AptosVM {
         }
     }
 
+    /// Enables Block-STM conflict analytics when invoked the first time.
+    pub fn set_conflict_analytics() {
+        // Only the first call succeeds, due to OnceCell semantics.
+        CONFLICT_ANALYTICS.set(true).ok();
+    }
+
+    /// Get whether the keys and transactions causing conflicts in parallel execution should be
+    /// recorded and reported.
+    pub fn get_conflict_analytics() -> bool {
+        match CONFLICT_ANALYTICS.get() {
+            Some(value) => *value,
+            None => false,
+        }
+    }
+
     pub fn internals(&self) -> AptosVMInternals {
         AptosVMInternals::new(&self.0)
     }
@@ -1329,16 +1354,47 @@ impl AptosVM {
         txn: &SignedTransaction,
         state_view: &impl StateView,
     ) -> (VMStatus, TransactionOutput) {
+        let (vm_status, output, _gas_meter) =
+            Self::simulate_signed_transaction_with_custom_gas_meter(
+                txn,
+                state_view,
+                |gas_feature_version, gas_params, storage_gas_params, balance| {
+                    Ok(StandardGasMeter::new(
+                        gas_feature_version,
+                        gas_params,
+                        storage_gas_params,
+                        balance,
+                    ))
+                },
+            );
+        (vm_status, output)
+    }
+
+    /// Executes a SignedTransaction without performing signature verification, using a custom
+    /// gas meter, e.g. one that records what happened during execution.
+    ///
+    /// The gas meter is returned alongside the output, unless the transaction got discarded
+    /// before it could be created.
+    pub fn simulate_signed_transaction_with_custom_gas_meter<G, F>(
+        txn: &SignedTransaction,
+        state_view: &impl StateView,
+        make_gas_meter: F,
+    ) -> (VMStatus, TransactionOutput, Option<G>)
+    where
+        G: AptosGasMeter,
+        F: Fn(u64, AptosGasParameters, StorageGasParameters, Gas) -> Result<G, VMStatus>,
+    {
         let vm = AptosVM::new(state_view);
         let simulation_vm = AptosSimulationVM(vm);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
 
         // Try to simulate with aggregator enabled.
-        let (vm_status, vm_output) = simulation_vm.simulate_signed_transaction(
+        let (vm_status, vm_output, gas_meter) = simulation_vm.simulate_signed_transaction(
             &state_view.as_move_resolver(),
             txn,
             &log_context,
             true,
+            &make_gas_meter,
         );
 
         // Because simulation returns a VMOutput, it has both writes and deltas
@@ -1348,14 +1404,15 @@ impl AptosVM {
         // case and re-simulate the transaction without an aggregator, in order to
         // obtain the precise location of abort and gas used.
         match vm_output.into_transaction_output(state_view) {
-            Ok(output) => (vm_status, output),
+            Ok(output) => (vm_status, output, gas_meter),
             Err(_) => {
                 // Conversion to TransactionOutput failed, re-simulate without aggregators.
-                let (vm_status, vm_output) = simulation_vm.simulate_signed_transaction(
+                let (vm_status, vm_output, gas_meter) = simulation_vm.simulate_signed_transaction(
                     &state_view.as_move_resolver(),
                     txn,
                     &log_context,
                     false,
+                    &make_gas_meter,
                 );
 
                 // Make sure to return the right types. Note that here conversion
@@ -1365,6 +1422,7 @@ impl AptosVM {
                     vm_output.into_transaction_output(state_view).expect(
                         "Conversion to TransactionOutput without aggregator always succeeds.",
                     ),
+                    gas_meter,
                 )
             },
         }
@@ -1378,42 +1436,86 @@ impl AptosVM {
         arguments: Vec<Vec<u8>>,
         gas_budget: u64,
     ) -> Result<Vec<Vec<u8>>> {
+        Self::execute_view_function_with_gas_used(
+            state_view, module_id, func_name, type_args, arguments, gas_budget,
+        )
+        .values
+        .map_err(|status| anyhow!("Failed to execute function: {:?}", status))
+    }
+
+    /// Executes a view function, reporting how the function failed as a [`VMStatus`] and how
+    /// much gas it used, whether it failed or not.
+    pub fn execute_view_function_with_gas_used(
+        state_view: &impl StateView,
+        module_id: ModuleId,
+        func_name: Identifier,
+        type_args: Vec<TypeTag>,
+        arguments: Vec<Vec<u8>>,
+        gas_budget: u64,
+    ) -> ViewFunctionOutput {
         let vm = AptosVM::new(state_view);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
-        let mut gas_meter = StandardGasMeter::new(
-            vm.0.get_gas_feature_version(),
-            vm.0.get_gas_parameters(&log_context)?.clone(),
-            vm.0.get_storage_gas_parameters(&log_context)?.clone(),
-            gas_budget,
-        );
+        let mut gas_meter = match (
+            vm.0.get_gas_parameters(&log_context),
+            vm.0.get_storage_gas_parameters(&log_context),
+        ) {
+            (Ok(gas_params), Ok(storage_gas_params)) => StandardGasMeter::new(
+                vm.0.get_gas_feature_version(),
+                gas_params.clone(),
+                storage_gas_params.clone(),
+                gas_budget,
+            ),
+            (Err(status), _) | (_, Err(status)) => {
+                return ViewFunctionOutput {
+                    values: Err(status),
+                    gas_used: 0,
+                }
+            },
+        };
         let resolver = &state_view.as_move_resolver();
         let mut session = vm.new_session(resolver, SessionId::Void, true);
 
-        let func_inst = session.load_function(&module_id, &func_name, &type_args)?;
-        let metadata = vm.0.extract_module_metadata(&module_id);
-        let arguments = verifier::view_function::validate_view_function(
-            &mut session,
-            arguments,
-            func_name.as_ident_str(),
-            &func_inst,
-            metadata.as_ref(),
-            vm.0.get_features()
-                .is_enabled(FeatureFlag::STRUCT_CONSTRUCTORS),
-        )?;
-
-        Ok(session
-            .execute_function_bypass_visibility(
-                &module_id,
-                func_name.as_ident_str(),
-                type_args,
-                arguments,
-                &mut gas_meter,
-            )
-            .map_err(|err| anyhow!("Failed to execute function: {:?}", err))?
-            .return_values
-            .into_iter()
-            .map(|(bytes, _ty)| bytes)
-            .collect::<Vec<_>>())
+        let values = session
+            .load_function(&module_id, &func_name, &type_args)
+            .and_then(|func_inst| {
+                let metadata = vm.0.extract_module_metadata(&module_id);
+                verifier::view_function::validate_view_function(
+                    &mut session,
+                    arguments,
+                    func_name.as_ident_str(),
+                    &func_inst,
+                    metadata.as_ref(),
+                    vm.0.get_features()
+                        .is_enabled(FeatureFlag::STRUCT_CONSTRUCTORS),
+                )
+                .map_err(|err| err.finish(Location::Undefined))
+            })
+            .and_then(|arguments| {
+                session.execute_function_bypass_visibility(
+                    &module_id,
+                    func_name.as_ident_str(),
+                    type_args,
+                    arguments,
+                    &mut gas_meter,
+                )
+            })
+            .map(|result| {
+                result
+                    .return_values
+                    .into_iter()
+                    .map(|(bytes, _ty)| bytes)
+                    .collect()
+            })
+            .map_err(|err| err.into_vm_status());
+
+        ViewFunctionOutput {
+            values,
+            gas_used: u64::from(
+                Gas::new(gas_budget)
+                    .checked_sub(gas_meter.balance())
+                    .expect("Balance should always be less than or equal to the gas budget"),
+            ),
+        }
     }
 
     fn run_prologue_with_payload(
@@ -1612,6 +1714,19 @@ impl VMAdapter for AptosVM {
             ));
         }
 
+        if !self
+            .0
+            .get_features()
+            .is_enabled(FeatureFlag::SECP256K1_ECDSA_AUTHENTICATOR)
+            && txn
+                .authenticator_ref()
+                .all_signers()
+                .iter()
+                .any(|signer| matches!(signer, AccountAuthenticator::Secp256k1Ecdsa { .. }))
+        {
+            return Err(VMStatus::Error(StatusCode::FEATURE_UNDER_GATING, None));
+        }
+
         Ok(())
     }
 
@@ -1738,17 +1853,28 @@ impl AptosSimulationVM {
         )
     }
 
-    fn simulate_signed_transaction(
+    fn simulate_signed_transaction<G: AptosGasMeter>(
         &self,
         resolver: &impl MoveResolverExt,
         txn: &SignedTransaction,
         log_context: &AdapterLogSchema,
         aggregator_enabled: bool,
-    ) -> (VMStatus, VMOutput) {
+        make_gas_meter: &impl Fn(
+            u64,
+            AptosGasParameters,
+            StorageGasParameters,
+            Gas,
+        ) -> Result<G, VMStatus>,
+    ) -> (VMStatus, VMOutput, Option<G>) {
+        let discard = |err| {
+            let (vm_status, output) = discard_error_vm_status(err);
+            (vm_status, output, None)
+        };
+
         // simulation transactions should not carry valid signatures, otherwise malicious fullnodes
         // may execute them without user's explicit permission.
         if txn.signature_is_valid() {
-            return discard_error_vm_status(VMStatus::Error(StatusCode::INVALID_SIGNATURE, None));
+            return discard(VMStatus::Error(StatusCode::INVALID_SIGNATURE, None));
         }
 
         // Revalidate the transaction.
@@ -1759,24 +1885,27 @@ impl AptosSimulationVM {
         if let Err(err) =
             self.validate_simulated_transaction(&mut session, resolver, txn, &txn_data, log_context)
         {
-            return discard_error_vm_status(err);
+            return discard(err);
         };
 
         let gas_params = match self.0 .0.get_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
+            Err(err) => return discard(err),
             Ok(s) => s,
         };
         let storage_gas_params = match self.0 .0.get_storage_gas_parameters(log_context) {
-            Err(err) => return discard_error_vm_status(err),
+            Err(err) => return discard(err),
             Ok(s) => s,
         };
 
-        let mut gas_meter = StandardGasMeter::new(
+        let mut gas_meter = match make_gas_meter(
             self.0 .0.get_gas_feature_version(),
             gas_params.clone(),
             storage_gas_params.clone(),
             txn_data.max_gas_amount(),
-        );
+        ) {
+            Err(err) => return discard(err),
+            Ok(gas_meter) => gas_meter,
+        };
 
         let mut new_published_modules_loaded = false;
         let result = match txn.payload() {
@@ -1846,7 +1975,7 @@ impl AptosSimulationVM {
             ),
         };
 
-        match result {
+        let (vm_status, output) = match result {
             Ok(output) => output,
             Err(err) => {
                 // Invalidate the loader cache in case there was a new module loaded from a module
@@ -1878,6 +2007,8 @@ impl AptosSimulationVM {
                     (vm_status, output)
                 }
             },
-        }
+        };
+
+        (vm_status, output, Some(gas_meter))
     }
 }
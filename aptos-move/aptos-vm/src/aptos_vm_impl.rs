@@ -7,7 +7,10 @@ use crate::{
     data_cache::StorageAdapter,
     errors::{convert_epilogue_error, convert_prologue_error, expect_only_successful_execution},
     move_vm_ext::{MoveResolverExt, MoveVmExt, SessionExt, SessionId},
-    system_module_names::{MULTISIG_ACCOUNT_MODULE, VALIDATE_MULTISIG_TRANSACTION},
+    system_module_names::{
+        FEE_PAYER_SCRIPT_PROLOGUE_NAME, MULTISIG_ACCOUNT_MODULE, USER_EPILOGUE_GAS_PAYER_NAME,
+        VALIDATE_MULTISIG_TRANSACTION,
+    },
     transaction_metadata::TransactionMetadata,
 };
 use aptos_framework::RuntimeModuleMetadataV1;
@@ -32,6 +35,7 @@ use aptos_vm_types::output::VMOutput;
 use fail::fail_point;
 use move_binary_format::{errors::VMResult, CompiledModule};
 use move_core_types::{
+    identifier::IdentStr,
     language_storage::ModuleId,
     move_resource::MoveStructType,
     value::{serialize_values, MoveValue},
@@ -366,8 +370,9 @@ impl AptosVMImpl {
         Ok(())
     }
 
-    /// Run the prologue of a transaction by calling into either `SCRIPT_PROLOGUE_NAME` function
-    /// or `MULTI_AGENT_SCRIPT_PROLOGUE_NAME` function stored in the `ACCOUNT_MODULE` on chain.
+    /// Run the prologue of a transaction by calling into either `SCRIPT_PROLOGUE_NAME`,
+    /// `MULTI_AGENT_SCRIPT_PROLOGUE_NAME` or `FEE_PAYER_SCRIPT_PROLOGUE_NAME` function stored in
+    /// the `ACCOUNT_MODULE` on chain.
     pub(crate) fn run_script_prologue(
         &self,
         session: &mut SessionExt,
@@ -387,7 +392,25 @@ impl AptosVMImpl {
             .iter()
             .map(|auth_key| MoveValue::vector_u8(auth_key.to_vec()))
             .collect();
-        let args = if txn_data.is_multi_agent() {
+        let args = if let Some(fee_payer) = txn_data.fee_payer() {
+            let fee_payer_auth_key = txn_data
+                .fee_payer_authentication_key
+                .clone()
+                .unwrap_or_default();
+            vec![
+                MoveValue::Signer(txn_data.sender),
+                MoveValue::U64(txn_sequence_number),
+                MoveValue::vector_u8(txn_authentication_key),
+                MoveValue::vector_address(txn_data.secondary_signers()),
+                MoveValue::Vector(secondary_auth_keys),
+                MoveValue::Address(fee_payer),
+                MoveValue::vector_u8(fee_payer_auth_key),
+                MoveValue::U64(txn_gas_price.into()),
+                MoveValue::U64(txn_max_gas_units.into()),
+                MoveValue::U64(txn_expiration_timestamp_secs),
+                MoveValue::U8(chain_id.id()),
+            ]
+        } else if txn_data.is_multi_agent() {
             vec![
                 MoveValue::Signer(txn_data.sender),
                 MoveValue::U64(txn_sequence_number),
@@ -411,10 +434,14 @@ impl AptosVMImpl {
                 MoveValue::vector_u8(txn_data.script_hash.clone()),
             ]
         };
-        let prologue_function_name = if txn_data.is_multi_agent() {
-            &transaction_validation.multi_agent_prologue_name
+        let prologue_function_name = if txn_data.fee_payer().is_some() {
+            FEE_PAYER_SCRIPT_PROLOGUE_NAME
+        } else if txn_data.is_multi_agent() {
+            transaction_validation
+                .multi_agent_prologue_name
+                .as_ident_str()
         } else {
-            &transaction_validation.script_prologue_name
+            transaction_validation.script_prologue_name.as_ident_str()
         };
         session
             .execute_function_bypass_visibility(
@@ -527,19 +554,23 @@ impl AptosVMImpl {
         let txn_sequence_number = txn_data.sequence_number();
         let txn_gas_price = txn_data.gas_unit_price();
         let txn_max_gas_units = txn_data.max_gas_amount();
+        let (epilogue_function_name, args) = epilogue_function_and_args(
+            transaction_validation,
+            txn_data,
+            vec![
+                MoveValue::U64(txn_sequence_number),
+                MoveValue::U64(txn_gas_price.into()),
+                MoveValue::U64(txn_max_gas_units.into()),
+                MoveValue::U64(gas_remaining.into()),
+            ],
+        );
         session
             .execute_function_bypass_visibility(
                 &transaction_validation.module_id(),
-                &transaction_validation.user_epilogue_name,
+                epilogue_function_name,
                 // TODO: Deprecate this once we remove gas currency on the Move side.
                 vec![],
-                serialize_values(&vec![
-                    MoveValue::Signer(txn_data.sender),
-                    MoveValue::U64(txn_sequence_number),
-                    MoveValue::U64(txn_gas_price.into()),
-                    MoveValue::U64(txn_max_gas_units.into()),
-                    MoveValue::U64(gas_remaining.into()),
-                ]),
+                serialize_values(&args),
                 &mut UnmeteredGasMeter,
             )
             .map(|_return_vals| ())
@@ -560,29 +591,29 @@ impl AptosVMImpl {
         let txn_sequence_number = txn_data.sequence_number();
         let txn_gas_price = txn_data.gas_unit_price();
         let txn_max_gas_units = txn_data.max_gas_amount();
+        let (epilogue_function_name, args) = epilogue_function_and_args(
+            transaction_validation,
+            txn_data,
+            vec![
+                MoveValue::U64(txn_sequence_number),
+                MoveValue::U64(txn_gas_price.into()),
+                MoveValue::U64(txn_max_gas_units.into()),
+                MoveValue::U64(gas_remaining.into()),
+            ],
+        );
         session
             .execute_function_bypass_visibility(
                 &transaction_validation.module_id(),
-                &transaction_validation.user_epilogue_name,
+                epilogue_function_name,
                 // TODO: Deprecate this once we remove gas currency on the Move side.
                 vec![],
-                serialize_values(&vec![
-                    MoveValue::Signer(txn_data.sender),
-                    MoveValue::U64(txn_sequence_number),
-                    MoveValue::U64(txn_gas_price.into()),
-                    MoveValue::U64(txn_max_gas_units.into()),
-                    MoveValue::U64(gas_remaining.into()),
-                ]),
+                serialize_values(&args),
                 &mut UnmeteredGasMeter,
             )
             .map(|_return_vals| ())
             .map_err(expect_no_verification_errors)
             .or_else(|e| {
-                expect_only_successful_execution(
-                    e,
-                    transaction_validation.user_epilogue_name.as_str(),
-                    log_context,
-                )
+                expect_only_successful_execution(e, epilogue_function_name.as_str(), log_context)
             })
     }
 
@@ -656,6 +687,25 @@ impl<'a> AptosVMInternals<'a> {
     }
 }
 
+/// Returns the epilogue to run for the transaction and its arguments, which are the sender, the
+/// gas payer if it differs from the sender, and then `args`.
+fn epilogue_function_and_args<'a>(
+    transaction_validation: &'a TransactionValidation,
+    txn_data: &TransactionMetadata,
+    args: Vec<MoveValue>,
+) -> (&'a IdentStr, Vec<MoveValue>) {
+    let mut epilogue_args = vec![MoveValue::Signer(txn_data.sender)];
+    let epilogue_function_name = match txn_data.fee_payer() {
+        Some(fee_payer) => {
+            epilogue_args.push(MoveValue::Address(fee_payer));
+            USER_EPILOGUE_GAS_PAYER_NAME
+        },
+        None => transaction_validation.user_epilogue_name.as_ident_str(),
+    };
+    epilogue_args.extend(args);
+    (epilogue_function_name, epilogue_args)
+}
+
 pub(crate) fn get_transaction_output<A: AccessPathCache>(
     ap_cache: &mut A,
     session: SessionExt,
@@ -0,0 +1,126 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::counters::BLOCK_STM_HOT_KEY_CONFLICTS;
+use aptos_block_executor::conflict_recorder::{ConflictReport, KeyConflicts};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_logger::info;
+use aptos_types::{
+    access_path::Path,
+    state_store::state_key::{StateKey, StateKeyInner},
+};
+use once_cell::sync::Lazy;
+use std::{cmp::Reverse, collections::HashMap};
+
+/// Number of keys and transactions listed in a block's conflict report, and number of hot keys
+/// exported as metrics.
+const NUM_REPORTED: usize = 10;
+
+/// Upper bound on the number of keys whose conflicts are accumulated across blocks. When it is
+/// reached, the less conflicted half of the keys is dropped.
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Conflicts accumulated across blocks since the last `reset_hot_keys`.
+static HOT_KEYS: Lazy<Mutex<HashMap<StateKey, KeyConflicts>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Renders a state key in a human readable way, e.g. `0xcafe/0x1::coin::CoinStore<..>`.
+pub fn describe_state_key(state_key: &StateKey) -> String {
+    match state_key.inner() {
+        StateKeyInner::AccessPath(access_path) => {
+            let address = access_path.address.to_hex_literal();
+            match bcs::from_bytes::<Path>(&access_path.path) {
+                Ok(Path::Code(module_id)) => format!("{}/module {}", address, module_id.name()),
+                Ok(Path::Resource(struct_tag)) => format!("{}/{}", address, struct_tag),
+                Ok(Path::ResourceGroup(struct_tag)) => {
+                    format!("{}/group {}", address, struct_tag)
+                },
+                Err(_) => format!("{:?}", access_path),
+            }
+        },
+        StateKeyInner::TableItem { handle, key } => format!(
+            "table {}[0x{}]",
+            handle.0.to_hex_literal(),
+            hex::encode(key)
+        ),
+        StateKeyInner::Raw(bytes) => format!("raw 0x{}", hex::encode(bytes)),
+    }
+}
+
+/// Logs the conflicts of a block and adds them to the hot keys.
+pub(crate) fn report_conflicts(report: ConflictReport<StateKey>, txn_hashes: &[HashValue]) {
+    if report.keys.is_empty() {
+        return;
+    }
+
+    let num_conflicts: u64 = report.keys.iter().map(|(_, c)| c.total()).sum();
+    info!(
+        "[BlockSTM]: {} conflicts in {} of {} txns. Hot keys: [{}]. Hot txns: [{}]",
+        num_conflicts,
+        report.txns.len(),
+        txn_hashes.len(),
+        report
+            .keys
+            .iter()
+            .take(NUM_REPORTED)
+            .map(|(key, c)| format!(
+                "{} ({} validation failures, {} dependency waits)",
+                describe_state_key(key),
+                c.validation_failures,
+                c.dependency_waits
+            ))
+            .collect::<Vec<_>>()
+            .join(", "),
+        report
+            .txns
+            .iter()
+            .take(NUM_REPORTED)
+            .map(|(idx, c)| format!(
+                "#{} {} ({} aborts, {} dependency waits, caused {})",
+                idx, txn_hashes[*idx as usize], c.aborts, c.dependency_waits, c.caused
+            ))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+
+    let mut hot_keys = HOT_KEYS.lock();
+    for (key, conflicts) in report.keys {
+        let entry = hot_keys.entry(key).or_default();
+        entry.validation_failures += conflicts.validation_failures;
+        entry.dependency_waits += conflicts.dependency_waits;
+    }
+    if hot_keys.len() > MAX_TRACKED_KEYS {
+        let mut totals: Vec<_> = hot_keys.values().map(KeyConflicts::total).collect();
+        let (_, median, _) = totals.select_nth_unstable(MAX_TRACKED_KEYS / 2);
+        let median = *median;
+        hot_keys.retain(|_, conflicts| conflicts.total() > median);
+    }
+
+    BLOCK_STM_HOT_KEY_CONFLICTS.reset();
+    for (key, conflicts) in top_keys(&hot_keys, NUM_REPORTED) {
+        BLOCK_STM_HOT_KEY_CONFLICTS
+            .with_label_values(&[&describe_state_key(&key)])
+            .set(conflicts.total() as i64);
+    }
+}
+
+/// Returns the `n` keys that caused the most conflicts since the last `reset_hot_keys`, along
+/// with their conflicts. Only populated if conflict analytics are enabled, see
+/// `AptosVM::set_conflict_analytics`.
+pub fn hot_keys(n: usize) -> Vec<(StateKey, KeyConflicts)> {
+    top_keys(&HOT_KEYS.lock(), n)
+}
+
+pub fn reset_hot_keys() {
+    HOT_KEYS.lock().clear();
+}
+
+fn top_keys(hot_keys: &HashMap<StateKey, KeyConflicts>, n: usize) -> Vec<(StateKey, KeyConflicts)> {
+    let mut keys: Vec<_> = hot_keys.iter().collect();
+    keys.sort_by_key(|(key, conflicts)| (Reverse(conflicts.total()), *key));
+    keys.into_iter()
+        .take(n)
+        .map(|(key, conflicts)| (key.clone(), *conflicts))
+        .collect()
+}
@@ -2,11 +2,12 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod conflict_analytics;
 pub(crate) mod vm_wrapper;
 
 use crate::{
     adapter_common::{preprocess_transaction, PreprocessedTransaction},
-    block_executor::vm_wrapper::AptosExecutorTask,
+    block_executor::{conflict_analytics::report_conflicts, vm_wrapper::AptosExecutorTask},
     counters::{
         BLOCK_EXECUTOR_CONCURRENCY, BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS,
         BLOCK_EXECUTOR_SIGNATURE_VERIFICATION_SECONDS,
@@ -22,6 +23,7 @@ use aptos_block_executor::{
         TransactionOutput as BlockExecutorTransactionOutput,
     },
 };
+use aptos_crypto::hash::CryptoHash;
 use aptos_infallible::Mutex;
 use aptos_state_view::{StateView, StateViewId};
 use aptos_types::{
@@ -140,6 +142,9 @@ impl BlockAptosVM {
         maybe_block_gas_limit: Option<u64>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
         let _timer = BLOCK_EXECUTOR_EXECUTE_BLOCK_SECONDS.start_timer();
+        // Hashes are only needed to identify the conflicted transactions in the logs.
+        let txn_hashes = AptosVM::get_conflict_analytics()
+            .then(|| transactions.iter().map(CryptoHash::hash).collect::<Vec<_>>());
         // Verify the signatures of all the transactions in parallel.
         // This is time consuming so don't wait and do the checking
         // sequentially while executing the transactions.
@@ -164,7 +169,7 @@ impl BlockAptosVM {
         }
 
         BLOCK_EXECUTOR_CONCURRENCY.set(concurrency_level as i64);
-        let executor = BlockExecutor::<
+        let mut executor = BlockExecutor::<
             PreprocessedTransaction,
             AptosExecutorTask<S>,
             S,
@@ -174,8 +179,14 @@ impl BlockAptosVM {
             executor_thread_pool,
             maybe_block_gas_limit,
         );
+        if txn_hashes.is_some() {
+            executor = executor.with_conflict_recording();
+        }
 
         let ret = executor.execute_block(state_view, signature_verified_block, state_view);
+        if let (Some(report), Some(txn_hashes)) = (executor.take_conflict_report(), txn_hashes) {
+            report_conflicts(report, &txn_hashes);
+        }
 
         match ret {
             Ok(outputs) => {
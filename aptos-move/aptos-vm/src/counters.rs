@@ -4,7 +4,8 @@
 
 use aptos_metrics_core::{
     exponential_buckets, register_histogram, register_int_counter, register_int_counter_vec,
-    register_int_gauge, Histogram, IntCounter, IntCounterVec, IntGauge,
+    register_int_gauge, register_int_gauge_vec, Histogram, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec,
 };
 use once_cell::sync::Lazy;
 
@@ -137,3 +138,32 @@ pub static TXN_GAS_USAGE: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+/// Count the number of transactions executed by the sharded block executor.
+pub static SHARDED_EXECUTION_TRANSACTIONS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_vm_sharded_execution_transactions",
+        "Number of transactions executed by the sharded block executor"
+    )
+    .unwrap()
+});
+
+/// Count the number of transactions that wrote state also written in another shard.
+pub static SHARDED_EXECUTION_CROSS_SHARD_CONFLICTS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_vm_sharded_execution_cross_shard_conflicts",
+        "Number of transactions that wrote state also written in another shard"
+    )
+    .unwrap()
+});
+
+/// Conflicts caused by the most conflicted state keys, when Block-STM conflict analytics are
+/// enabled.
+pub static BLOCK_STM_HOT_KEY_CONFLICTS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_vm_block_stm_hot_key_conflicts",
+        "Number of Block-STM validation failures and dependency waits caused by the hottest keys",
+        &["key"]
+    )
+    .unwrap()
+});
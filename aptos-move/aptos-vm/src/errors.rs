@@ -31,6 +31,8 @@ pub const EBAD_CHAIN_ID: u64 = 1007;
 pub const ESEQUENCE_NUMBER_TOO_BIG: u64 = 1008;
 // Counts of secondary keys and addresses don't match.
 pub const ESECONDARY_KEYS_ADDRESSES_COUNT_MISMATCH: u64 = 1009;
+// Fee payer transactions are not enabled.
+pub const EFEE_PAYER_NOT_ENABLED: u64 = 1010;
 // Specified account is not a multisig account.
 const EACCOUNT_NOT_MULTISIG: u64 = 2002;
 // Account executing this operation is not an owner of the multisig account.
@@ -118,6 +120,7 @@ pub fn convert_prologue_error(
                 (INVALID_ARGUMENT, ESECONDARY_KEYS_ADDRESSES_COUNT_MISMATCH) => {
                     StatusCode::SECONDARY_KEYS_ADDRESSES_COUNT_MISMATCH
                 },
+                (INVALID_STATE, EFEE_PAYER_NOT_ENABLED) => StatusCode::FEATURE_UNDER_GATING,
                 (category, reason) => {
                     speculative_error!(
                         log_context,
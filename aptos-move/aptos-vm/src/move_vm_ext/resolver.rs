@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::data_cache::get_resource_group_from_metadata;
 use aptos_framework::natives::state_storage::StateStorageUsageResolver;
 use aptos_state_view::StateView;
 use aptos_types::on_chain_config::ConfigStorage;
@@ -44,4 +45,12 @@ pub trait MoveResolverExt:
         })
         .is_some()
     }
+
+    // Move to API does not belong here
+    fn get_resource_group(&self, struct_tag: &StructTag) -> Option<StructTag> {
+        get_resource_group_from_metadata(
+            struct_tag,
+            &self.get_module_metadata(&struct_tag.module_id()),
+        )
+    }
 }
@@ -1,22 +1,43 @@
 // Copyright © Aptos Foundation
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
-use aptos_types::transaction::Transaction;
+use aptos_infallible::RwLock;
+use aptos_types::{
+    account_address::AccountAddress,
+    state_store::state_key::StateKeyInner,
+    transaction::{
+        EntryFunction, Multisig, MultisigTransactionPayload, SignedTransaction, Transaction,
+        TransactionArgument, TransactionOutput, TransactionPayload, TransactionStatus,
+    },
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+};
+
+/// The transactions of a block that are assigned to a single shard.
+pub struct SubBlock {
+    /// The index of each transaction in the block, in ascending order.
+    pub txn_indices: Vec<usize>,
+    pub transactions: Vec<Transaction>,
+}
 
 pub trait BlockPartitioner: Send + Sync {
-    fn partition(&self, transactions: Vec<Transaction>, num_shards: usize)
-        -> Vec<Vec<Transaction>>;
+    /// Splits the block into at most `num_shards` sub-blocks. Every transaction ends up in exactly
+    /// one sub-block, and transactions keep their relative block order within a sub-block.
+    fn partition(&self, transactions: Vec<Transaction>, num_shards: usize) -> Vec<SubBlock>;
+
+    /// Called with the outputs of every executed block, so the partitioner can learn from prior
+    /// executions.
+    fn record_execution(&self, _transactions: &[Transaction], _outputs: &[TransactionOutput]) {}
 }
 
 /// An implementation of partitioner that splits the transactions into equal-sized chunks.
 pub struct UniformPartitioner {}
 
 impl BlockPartitioner for UniformPartitioner {
-    fn partition(
-        &self,
-        transactions: Vec<Transaction>,
-        num_shards: usize,
-    ) -> Vec<Vec<Transaction>> {
+    fn partition(&self, transactions: Vec<Transaction>, num_shards: usize) -> Vec<SubBlock> {
         let total_txns = transactions.len();
         if total_txns == 0 {
             return vec![];
@@ -24,9 +45,422 @@ impl BlockPartitioner for UniformPartitioner {
         let txns_per_shard = (total_txns as f64 / num_shards as f64).ceil() as usize;
 
         let mut result = Vec::new();
-        for chunk in transactions.chunks(txns_per_shard) {
-            result.push(chunk.to_vec());
+        for (i, chunk) in transactions.chunks(txns_per_shard).enumerate() {
+            let start = i * txns_per_shard;
+            result.push(SubBlock {
+                txn_indices: (start..start + chunk.len()).collect(),
+                transactions: chunk.to_vec(),
+            });
         }
         result
     }
 }
+
+/// Once an entry function has written to this many distinct pieces of state that were each only
+/// seen once, they are forgotten. Keeps one-off writes, e.g. to newly created objects, from
+/// piling up.
+const MAX_LEARNED_CANDIDATES: usize = 1024;
+
+/// State accessed by a transaction, at the granularity used to detect conflicts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum StateAccess {
+    /// The resources stored under an account.
+    Resources(AccountAddress),
+    /// The modules published under an account.
+    Code(AccountAddress),
+}
+
+/// Writes made by prior executions of an entry function outside of the accounts named by the
+/// transaction.
+#[derive(Default)]
+struct LearnedWrites {
+    /// Writes made by exactly one execution so far.
+    candidates: HashSet<StateAccess>,
+    /// Writes made by more than one execution, which are expected from every future call.
+    recurring: HashSet<StateAccess>,
+}
+
+impl LearnedWrites {
+    fn record(&mut self, access: StateAccess) {
+        if self.recurring.contains(&access) {
+            return;
+        }
+        if self.candidates.remove(&access) {
+            self.recurring.insert(access);
+        } else {
+            self.candidates.insert(access);
+            if self.candidates.len() > MAX_LEARNED_CANDIDATES {
+                self.candidates.clear();
+            }
+        }
+    }
+}
+
+/// A partitioner that keeps transactions which are expected to conflict on the same shard.
+///
+/// The state a transaction accesses is estimated from the accounts it names, i.e. the sender,
+/// secondary signers, fee payer and the address arguments of its script or entry function, as well
+/// as from the state prior executions of the same entry function wrote outside of the accounts they
+/// named, e.g. a counter stored under the account of the module. Transactions connected through
+/// conflicting accesses form a group, and groups are assigned to the least loaded shard, largest
+/// first. Since transactions keep their block order within a shard and, as far as the estimate
+/// goes, don't conflict with other shards, executing the shards is equivalent to executing the
+/// block serially.
+///
+/// Non-user transactions are always assigned to the first shard.
+#[derive(Default)]
+pub struct DependencyAwarePartitioner {
+    learned_writes: RwLock<HashMap<ModuleId, HashMap<Identifier, LearnedWrites>>>,
+}
+
+impl DependencyAwarePartitioner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlockPartitioner for DependencyAwarePartitioner {
+    fn partition(&self, transactions: Vec<Transaction>, num_shards: usize) -> Vec<SubBlock> {
+        // The transactions reading and writing each piece of state.
+        let mut accesses: HashMap<StateAccess, (Vec<usize>, Vec<usize>)> = HashMap::new();
+        let mut non_user_txns = vec![];
+        {
+            let learned_writes = self.learned_writes.read();
+            for (index, txn) in transactions.iter().enumerate() {
+                let txn = match txn {
+                    Transaction::UserTransaction(txn) => txn,
+                    _ => {
+                        non_user_txns.push(index);
+                        continue;
+                    },
+                };
+                let (reads, writes) = estimate_accesses(txn, &learned_writes);
+                for access in reads {
+                    accesses.entry(access).or_default().0.push(index);
+                }
+                for access in writes {
+                    accesses.entry(access).or_default().1.push(index);
+                }
+            }
+        }
+
+        // Two transactions conflict if one of them writes state the other one reads or writes.
+        let mut groups = DisjointSets::new(transactions.len());
+        for (readers, writers) in accesses.values() {
+            if let Some(&writer) = writers.first() {
+                for &index in readers.iter().chain(writers) {
+                    groups.union(writer, index);
+                }
+            }
+        }
+        let mut group_members: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (index, txn) in transactions.iter().enumerate() {
+            if let Transaction::UserTransaction(_) = txn {
+                group_members
+                    .entry(groups.find(index))
+                    .or_default()
+                    .push(index);
+            }
+        }
+        let mut group_members: Vec<_> = group_members.into_values().collect();
+        // Ties are broken by block order, so that the result is deterministic.
+        group_members.sort_by_key(|members| (Reverse(members.len()), members[0]));
+
+        let mut shard_txn_indices = vec![vec![]; num_shards];
+        shard_txn_indices[0] = non_user_txns;
+        for members in group_members {
+            let shard = shard_txn_indices
+                .iter_mut()
+                .min_by_key(|txn_indices| txn_indices.len())
+                .expect("num_shards must be > 0");
+            shard.extend(members);
+        }
+
+        let mut transactions: Vec<_> = transactions.into_iter().map(Some).collect();
+        shard_txn_indices
+            .into_iter()
+            .filter(|txn_indices| !txn_indices.is_empty())
+            .map(|mut txn_indices| {
+                txn_indices.sort_unstable();
+                let transactions = txn_indices
+                    .iter()
+                    .map(|&index| {
+                        transactions[index]
+                            .take()
+                            .expect("transaction must be assigned to a single shard")
+                    })
+                    .collect();
+                SubBlock {
+                    txn_indices,
+                    transactions,
+                }
+            })
+            .collect()
+    }
+
+    fn record_execution(&self, transactions: &[Transaction], outputs: &[TransactionOutput]) {
+        let mut learned_writes = self.learned_writes.write();
+        for (txn, output) in transactions.iter().zip(outputs) {
+            let txn = match txn {
+                Transaction::UserTransaction(txn) => txn,
+                _ => continue,
+            };
+            let entry_function = match entry_function(txn.payload()) {
+                Some(entry_function) => entry_function,
+                None => continue,
+            };
+            if !matches!(output.status(), TransactionStatus::Keep(_)) {
+                continue;
+            }
+
+            let named_accounts = named_accounts(txn);
+            let writes: HashSet<_> = output
+                .write_set()
+                .iter()
+                .filter_map(|(state_key, _)| match state_key.inner() {
+                    StateKeyInner::AccessPath(access_path) if access_path.is_code() => {
+                        Some(StateAccess::Code(access_path.address))
+                    },
+                    StateKeyInner::AccessPath(access_path)
+                        if !named_accounts.contains(&access_path.address) =>
+                    {
+                        Some(StateAccess::Resources(access_path.address))
+                    },
+                    // Table items are not tracked: the one written by every transaction is the
+                    // total supply aggregator, whose updates commute.
+                    _ => None,
+                })
+                .collect();
+            if writes.is_empty() {
+                continue;
+            }
+
+            let learned = learned_writes
+                .entry(entry_function.module().clone())
+                .or_default()
+                .entry(entry_function.function().to_owned())
+                .or_default();
+            for access in writes {
+                learned.record(access);
+            }
+        }
+    }
+}
+
+/// Returns the estimated reads and writes of a user transaction.
+fn estimate_accesses(
+    txn: &SignedTransaction,
+    learned_writes: &HashMap<ModuleId, HashMap<Identifier, LearnedWrites>>,
+) -> (Vec<StateAccess>, Vec<StateAccess>) {
+    let mut reads = vec![];
+    // Funds may move between any of the named accounts, so they are all considered written.
+    let mut writes: Vec<_> = named_accounts(txn)
+        .into_iter()
+        .map(StateAccess::Resources)
+        .collect();
+    if let TransactionPayload::ModuleBundle(_) = txn.payload() {
+        writes.push(StateAccess::Code(txn.sender()));
+    }
+    if let Some(entry_function) = entry_function(txn.payload()) {
+        reads.push(StateAccess::Code(*entry_function.module().address()));
+        if let Some(learned) = learned_writes
+            .get(entry_function.module())
+            .and_then(|functions| functions.get(entry_function.function()))
+        {
+            writes.extend(learned.recurring.iter().copied());
+        }
+    }
+    (reads, writes)
+}
+
+fn entry_function(payload: &TransactionPayload) -> Option<&EntryFunction> {
+    match payload {
+        TransactionPayload::EntryFunction(entry_function)
+        | TransactionPayload::Multisig(Multisig {
+            transaction_payload: Some(MultisigTransactionPayload::EntryFunction(entry_function)),
+            ..
+        }) => Some(entry_function),
+        _ => None,
+    }
+}
+
+/// Returns the accounts a user transaction names, which may include duplicates.
+fn named_accounts(txn: &SignedTransaction) -> Vec<AccountAddress> {
+    let authenticator = txn.authenticator_ref();
+    let mut accounts = vec![txn.sender()];
+    accounts.extend(authenticator.secondary_signer_addreses());
+    accounts.extend(authenticator.fee_payer_address());
+    match txn.payload() {
+        TransactionPayload::Script(script) => {
+            accounts.extend(script.args().iter().filter_map(|arg| match arg {
+                TransactionArgument::Address(address) => Some(*address),
+                _ => None,
+            }))
+        },
+        TransactionPayload::Multisig(multisig) => accounts.push(multisig.multisig_address),
+        TransactionPayload::ModuleBundle(_) | TransactionPayload::EntryFunction(_) => (),
+    }
+    if let Some(entry_function) = entry_function(txn.payload()) {
+        // Entry function arguments are untyped BCS, but an argument of the length of an address is
+        // most likely one.
+        accounts.extend(
+            entry_function
+                .args()
+                .iter()
+                .filter_map(|arg| AccountAddress::from_bytes(arg).ok()),
+        );
+    }
+    accounts
+}
+
+/// A union-find over transaction indices.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(size: usize) -> Self {
+        Self {
+            parents: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            self.parents[index] = self.parents[self.parents[index]];
+            index = self.parents[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_language_e2e_tests::{account::Account, common_transactions::peer_to_peer_txn};
+    use aptos_types::{
+        access_path::AccessPath,
+        state_store::state_key::StateKey,
+        transaction::ExecutionStatus,
+        write_set::{WriteOp, WriteSetMut},
+    };
+    use move_core_types::language_storage::StructTag;
+    use std::str::FromStr;
+
+    fn transfer(sender: &Account, receiver: &Account, seq_num: u64) -> Transaction {
+        Transaction::UserTransaction(peer_to_peer_txn(sender, receiver, seq_num, 1, 0))
+    }
+
+    /// An output writing a resource under each of the addresses.
+    fn output(addresses: &[AccountAddress], status: TransactionStatus) -> TransactionOutput {
+        let tag = StructTag::from_str("0xcafe::m::Counter").unwrap();
+        let write_set = WriteSetMut::new(addresses.iter().map(|address| {
+            let access_path = AccessPath::resource_access_path(*address, tag.clone()).unwrap();
+            (
+                StateKey::access_path(access_path),
+                WriteOp::Modification(vec![]),
+            )
+        }))
+        .freeze()
+        .unwrap();
+        TransactionOutput::new(write_set, vec![], 0, status)
+    }
+
+    fn success() -> TransactionStatus {
+        TransactionStatus::Keep(ExecutionStatus::Success)
+    }
+
+    #[test]
+    fn test_conflicting_transfers_share_a_shard() {
+        let accounts: Vec<_> = (0..6).map(|_| Account::new()).collect();
+        // 0 -> 1 and 1 -> 2 conflict on account 1, 3 -> 4 and 3 -> 5 on account 3.
+        let block = vec![
+            transfer(&accounts[0], &accounts[1], 0),
+            transfer(&accounts[3], &accounts[4], 0),
+            transfer(&accounts[1], &accounts[2], 0),
+            transfer(&accounts[3], &accounts[5], 1),
+        ];
+
+        let sub_blocks = DependencyAwarePartitioner::new().partition(block.clone(), 4);
+        assert_eq!(sub_blocks.len(), 2);
+        assert_eq!(sub_blocks[0].txn_indices, vec![0, 2]);
+        assert_eq!(sub_blocks[1].txn_indices, vec![1, 3]);
+        for sub_block in sub_blocks {
+            for (index, txn) in sub_block.txn_indices.iter().zip(&sub_block.transactions) {
+                assert_eq!(&block[*index], txn);
+            }
+        }
+    }
+
+    #[test]
+    fn test_independent_transfers_are_balanced() {
+        let accounts: Vec<_> = (0..8).map(|_| Account::new()).collect();
+        let block: Vec<_> = accounts
+            .chunks(2)
+            .map(|pair| transfer(&pair[0], &pair[1], 0))
+            .collect();
+
+        let sub_blocks = DependencyAwarePartitioner::new().partition(block, 2);
+        assert_eq!(sub_blocks.len(), 2);
+        assert_eq!(sub_blocks[0].txn_indices, vec![0, 2]);
+        assert_eq!(sub_blocks[1].txn_indices, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_record_execution() {
+        let accounts: Vec<_> = (0..6).map(|_| Account::new()).collect();
+        let module = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        let access = StateAccess::Resources(module);
+        let block = vec![
+            transfer(&accounts[0], &accounts[1], 0),
+            transfer(&accounts[2], &accounts[3], 0),
+        ];
+        let partitioner = DependencyAwarePartitioner::new();
+        let learned = |partitioner: &DependencyAwarePartitioner| {
+            let learned_writes = partitioner.learned_writes.read();
+            let entry_function = match &block[0] {
+                Transaction::UserTransaction(txn) => entry_function(txn.payload()).unwrap(),
+                _ => unreachable!(),
+            };
+            learned_writes
+                .get(entry_function.module())
+                .and_then(|functions| functions.get(entry_function.function()))
+                .map(|learned| (learned.candidates.clone(), learned.recurring.clone()))
+                .unwrap_or_default()
+        };
+
+        // Writes to the named accounts, and the writes of transactions which were not kept, are
+        // not learned.
+        let sender = *accounts[0].address();
+        partitioner.record_execution(&block[..1], &[output(&[sender], success())]);
+        partitioner.record_execution(&block[1..], &[output(&[module], TransactionStatus::Retry)]);
+        assert_eq!(learned(&partitioner), (HashSet::new(), HashSet::new()));
+
+        // A write made by a single execution is only a candidate, which doesn't affect
+        // partitioning.
+        partitioner.record_execution(&block[..1], &[output(&[module], success())]);
+        assert_eq!(
+            learned(&partitioner),
+            (HashSet::from([access]), HashSet::new())
+        );
+        assert_eq!(partitioner.partition(block.clone(), 2).len(), 2);
+
+        // Once another execution makes the same write, it is expected from every call, so the
+        // transfers are kept on the same shard.
+        partitioner.record_execution(&block[1..], &[output(&[module], success())]);
+        assert_eq!(
+            learned(&partitioner),
+            (HashSet::new(), HashSet::from([access]))
+        );
+        let sub_blocks = partitioner.partition(block, 2);
+        assert_eq!(sub_blocks.len(), 1);
+        assert_eq!(sub_blocks[0].txn_indices, vec![0, 1]);
+    }
+}
@@ -2,15 +2,22 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::sharded_block_executor::{
-    block_partitioner::{BlockPartitioner, UniformPartitioner},
-    executor_shard::ExecutorShard,
+use crate::{
+    counters::{SHARDED_EXECUTION_CROSS_SHARD_CONFLICTS, SHARDED_EXECUTION_TRANSACTIONS},
+    sharded_block_executor::{
+        block_partitioner::{BlockPartitioner, UniformPartitioner},
+        executor_shard::ExecutorShard,
+    },
 };
 use aptos_logger::{error, info, trace};
 use aptos_state_view::StateView;
-use aptos_types::transaction::{Transaction, TransactionOutput};
+use aptos_types::{
+    state_store::state_key::{StateKey, StateKeyInner},
+    transaction::{Transaction, TransactionOutput},
+};
 use move_core_types::vm_status::VMStatus;
 use std::{
+    collections::{HashMap, HashSet},
     marker::PhantomData,
     sync::{
         mpsc::{Receiver, Sender},
@@ -19,7 +26,7 @@ use std::{
     thread,
 };
 
-mod block_partitioner;
+pub mod block_partitioner;
 mod executor_shard;
 
 /// A wrapper around sharded block executors that manages multiple shards and aggregates the results.
@@ -39,6 +46,18 @@ pub enum ExecutorShardCommand<S: StateView + Sync + Send + 'static> {
 
 impl<S: StateView + Sync + Send + 'static> ShardedBlockExecutor<S> {
     pub fn new(num_executor_shards: usize, executor_threads_per_shard: Option<usize>) -> Self {
+        Self::new_with_partitioner(
+            num_executor_shards,
+            executor_threads_per_shard,
+            Arc::new(UniformPartitioner {}),
+        )
+    }
+
+    pub fn new_with_partitioner(
+        num_executor_shards: usize,
+        executor_threads_per_shard: Option<usize>,
+        partitioner: Arc<dyn BlockPartitioner>,
+    ) -> Self {
         assert!(num_executor_shards > 0, "num_executor_shards must be > 0");
         let executor_threads_per_shard = executor_threads_per_shard.unwrap_or_else(|| {
             (num_cpus::get() as f64 / num_executor_shards as f64).ceil() as usize
@@ -65,7 +84,7 @@ impl<S: StateView + Sync + Send + 'static> ShardedBlockExecutor<S> {
         );
         Self {
             num_executor_shards,
-            partitioner: Arc::new(UniformPartitioner {}),
+            partitioner,
             command_txs,
             shard_threads: shard_join_handles,
             result_rxs,
@@ -74,7 +93,8 @@ impl<S: StateView + Sync + Send + 'static> ShardedBlockExecutor<S> {
     }
 
     /// Execute a block of transactions in parallel by splitting the block into num_remote_executors partitions and
-    /// dispatching each partition to a remote executor shard.
+    /// dispatching each partition to a remote executor shard. The outputs are returned in block
+    /// order.
     pub fn execute_block(
         &self,
         state_view: Arc<S>,
@@ -86,25 +106,79 @@ impl<S: StateView + Sync + Send + 'static> ShardedBlockExecutor<S> {
         // Number of partitions might be smaller than the number of executor shards in case of
         // block size is smaller than number of executor shards.
         let num_partitions = block_partitions.len();
-        for (i, transactions) in block_partitions.into_iter().enumerate() {
+        let mut partition_txn_indices = Vec::with_capacity(num_partitions);
+        for (i, sub_block) in block_partitions.into_iter().enumerate() {
+            partition_txn_indices.push(sub_block.txn_indices);
             self.command_txs[i]
                 .send(ExecutorShardCommand::ExecuteBlock(
                     state_view.clone(),
-                    transactions,
+                    sub_block.transactions,
                     concurrency_level_per_shard,
                     maybe_block_gas_limit,
                 ))
                 .unwrap();
         }
-        // wait for all remote executors to send the result back and append them in order by shard id
-        let mut aggregated_results = vec![];
+        // wait for all remote executors to send the result back
+        let mut partition_results = vec![];
         trace!("ShardedBlockExecutor Waiting for results");
         for i in 0..num_partitions {
             let result = self.result_rxs[i].recv().unwrap();
-            aggregated_results.extend(result?);
+            partition_results.push(result?);
+        }
+        record_cross_shard_conflicts(&partition_results);
+
+        // put the outputs back in block order
+        let mut indexed_outputs: Vec<_> = partition_txn_indices
+            .into_iter()
+            .flatten()
+            .zip(partition_results.into_iter().flatten())
+            .collect();
+        indexed_outputs.sort_unstable_by_key(|(index, _)| *index);
+        Ok(indexed_outputs
+            .into_iter()
+            .map(|(_, output)| output)
+            .collect())
+    }
+
+    /// Lets the partitioner learn from the outputs of an executed block.
+    pub fn record_execution(&self, transactions: &[Transaction], outputs: &[TransactionOutput]) {
+        self.partitioner.record_execution(transactions, outputs);
+    }
+}
+
+/// Counts the transactions that wrote a resource or module that was also written by a transaction
+/// in another shard. Shards don't see each other's writes, so each of them is a conflict that
+/// serial execution would have ordered. Table items are left out as the one every transaction
+/// writes is the total supply aggregator, whose updates commute.
+fn record_cross_shard_conflicts(partition_results: &[Vec<TransactionOutput>]) {
+    let num_txns: usize = partition_results.iter().map(Vec::len).sum();
+    SHARDED_EXECUTION_TRANSACTIONS.inc_by(num_txns as u64);
+    if partition_results.len() < 2 {
+        return;
+    }
+
+    // The number of shards that wrote each resource or module.
+    let mut num_writing_shards: HashMap<&StateKey, usize> = HashMap::new();
+    for outputs in partition_results {
+        let keys: HashSet<_> = outputs.iter().flat_map(written_resources).collect();
+        for key in keys {
+            *num_writing_shards.entry(key).or_default() += 1;
         }
-        Ok(aggregated_results)
     }
+    let num_conflicts = partition_results
+        .iter()
+        .flatten()
+        .filter(|output| written_resources(output).any(|key| num_writing_shards[key] > 1))
+        .count();
+    SHARDED_EXECUTION_CROSS_SHARD_CONFLICTS.inc_by(num_conflicts as u64);
+}
+
+fn written_resources(output: &TransactionOutput) -> impl Iterator<Item = &StateKey> {
+    output
+        .write_set()
+        .iter()
+        .map(|(state_key, _)| state_key)
+        .filter(|state_key| matches!(state_key.inner(), StateKeyInner::AccessPath(_)))
 }
 
 impl<S: StateView + Sync + Send + 'static> Drop for ShardedBlockExecutor<S> {
@@ -0,0 +1,126 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_mvhashmap::types::TxnIndex;
+use dashmap::DashMap;
+use std::{
+    cmp::Reverse,
+    hash::Hash,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Conflicts attributed to a storage key.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct KeyConflicts {
+    /// Validations that failed because of a read of the key, each leading to a re-execution.
+    pub validation_failures: u64,
+    /// Times an execution had to wait for another transaction to finish writing the key.
+    pub dependency_waits: u64,
+}
+
+impl KeyConflicts {
+    pub fn total(&self) -> u64 {
+        self.validation_failures + self.dependency_waits
+    }
+}
+
+/// Conflicts attributed to a transaction of the block.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TxnConflicts {
+    /// Times the transaction was aborted, each leading to a re-execution.
+    pub aborts: u64,
+    /// Times the execution of the transaction had to wait for a dependency.
+    pub dependency_waits: u64,
+    /// Times a write of the transaction caused another transaction to abort or wait.
+    pub caused: u64,
+}
+
+impl TxnConflicts {
+    pub fn total(&self) -> u64 {
+        self.aborts + self.dependency_waits + self.caused
+    }
+}
+
+/// The conflicts of a parallel execution of a block.
+#[derive(Debug)]
+pub struct ConflictReport<K> {
+    /// Keys involved in a conflict, most conflicted first.
+    pub keys: Vec<(K, KeyConflicts)>,
+    /// Transactions involved in a conflict, most conflicted first.
+    pub txns: Vec<(TxnIndex, TxnConflicts)>,
+}
+
+#[derive(Default)]
+struct TxnConflictCounters {
+    aborts: AtomicU64,
+    dependency_waits: AtomicU64,
+    caused: AtomicU64,
+}
+
+/// Attributes the speculative aborts and dependency waits of a parallel execution to the keys
+/// and transactions involved.
+pub(crate) struct ConflictRecorder<K> {
+    keys: DashMap<K, KeyConflicts>,
+    txns: Vec<TxnConflictCounters>,
+}
+
+impl<K: Clone + Eq + Hash + Ord> ConflictRecorder<K> {
+    pub(crate) fn new(num_txns: TxnIndex) -> Self {
+        Self {
+            keys: DashMap::new(),
+            txns: (0..num_txns).map(|_| Default::default()).collect(),
+        }
+    }
+
+    /// Records that `txn_idx` was aborted because its read of `key` was invalidated, by a write
+    /// of `writer_idx` if the read would now observe the write of a single transaction.
+    pub(crate) fn record_abort(&self, key: &K, txn_idx: TxnIndex, writer_idx: Option<TxnIndex>) {
+        self.keys
+            .entry(key.clone())
+            .or_default()
+            .validation_failures += 1;
+        self.txns[txn_idx as usize]
+            .aborts
+            .fetch_add(1, Ordering::Relaxed);
+        if let Some(writer_idx) = writer_idx {
+            self.txns[writer_idx as usize]
+                .caused
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Records that `txn_idx` waits for `dep_idx` to be re-executed, as `dep_idx` is estimated to
+    /// write `key`.
+    pub(crate) fn record_dependency_wait(&self, key: &K, txn_idx: TxnIndex, dep_idx: TxnIndex) {
+        self.keys.entry(key.clone()).or_default().dependency_waits += 1;
+        self.txns[txn_idx as usize]
+            .dependency_waits
+            .fetch_add(1, Ordering::Relaxed);
+        self.txns[dep_idx as usize]
+            .caused
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn into_report(self) -> ConflictReport<K> {
+        let mut keys: Vec<_> = self.keys.into_iter().collect();
+        keys.sort_by(|(k1, c1), (k2, c2)| c2.total().cmp(&c1.total()).then_with(|| k1.cmp(k2)));
+
+        let mut txns: Vec<_> = self
+            .txns
+            .into_iter()
+            .enumerate()
+            .map(|(idx, counters)| {
+                let conflicts = TxnConflicts {
+                    aborts: counters.aborts.into_inner(),
+                    dependency_waits: counters.dependency_waits.into_inner(),
+                    caused: counters.caused.into_inner(),
+                };
+                (idx as TxnIndex, conflicts)
+            })
+            .filter(|(_, conflicts)| conflicts.total() > 0)
+            .collect();
+        txns.sort_by_key(|(idx, conflicts)| (Reverse(conflicts.total()), *idx));
+
+        ConflictReport { keys, txns }
+    }
+}
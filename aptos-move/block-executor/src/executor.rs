@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_recorder::{ConflictRecorder, ConflictReport},
     counters,
     counters::{
         PARALLEL_EXECUTION_SECONDS, RAYON_EXECUTION_SECONDS, TASK_EXECUTE_SECONDS,
@@ -15,6 +16,7 @@ use crate::{
     view::{LatestView, MVHashMapView},
 };
 use aptos_aggregator::delta_change_set::{deserialize, serialize};
+use aptos_infallible::Mutex;
 use aptos_logger::{debug, info};
 use aptos_mvhashmap::{
     types::{MVDataError, MVDataOutput, TxnIndex, Version},
@@ -41,12 +43,15 @@ enum CommitRole {
     Worker(Receiver<TxnIndex>),
 }
 
-pub struct BlockExecutor<T, E, S, X> {
+pub struct BlockExecutor<T: Transaction, E, S, X> {
     // number of active concurrent tasks, corresponding to the maximum number of rayon
     // threads that may be concurrently participating in parallel execution.
     concurrency_level: usize,
     executor_thread_pool: Arc<ThreadPool>,
     maybe_block_gas_limit: Option<u64>,
+    record_conflicts: bool,
+    // conflicts of the last parallel execution, if record_conflicts is set.
+    conflict_report: Mutex<Option<ConflictReport<T::Key>>>,
     phantom: PhantomData<(T, E, S, X)>,
 }
 
@@ -73,10 +78,25 @@ where
             concurrency_level,
             executor_thread_pool,
             maybe_block_gas_limit,
+            record_conflicts: false,
+            conflict_report: Mutex::new(None),
             phantom: PhantomData,
         }
     }
 
+    /// Records which keys and transactions cause speculative aborts and dependency waits during
+    /// parallel execution, see `take_conflict_report`.
+    pub fn with_conflict_recording(mut self) -> Self {
+        self.record_conflicts = true;
+        self
+    }
+
+    /// Returns the conflicts of the last parallel execution, if conflict recording is enabled.
+    /// There is no report if the block was executed sequentially.
+    pub fn take_conflict_report(&self) -> Option<ConflictReport<T::Key>> {
+        self.conflict_report.lock().take()
+    }
+
     fn execute(
         &self,
         version: Version,
@@ -86,12 +106,13 @@ where
         scheduler: &Scheduler,
         executor: &E,
         base_view: &S,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
     ) -> SchedulerTask {
         let _timer = TASK_EXECUTE_SECONDS.start_timer();
         let (idx_to_execute, incarnation) = version;
         let txn = &signature_verified_block[idx_to_execute as usize];
 
-        let speculative_view = MVHashMapView::new(versioned_cache, scheduler);
+        let speculative_view = MVHashMapView::new(versioned_cache, scheduler, conflict_recorder);
 
         // VM execution.
         let execute_result = executor.execute_transaction(
@@ -168,6 +189,7 @@ where
         last_input_output: &TxnLastInputOutput<T::Key, E::Output, E::Error>,
         versioned_cache: &MVHashMap<T::Key, T::Value, X>,
         scheduler: &Scheduler,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
     ) -> SchedulerTask {
         use MVDataError::*;
         use MVDataOutput::*;
@@ -178,29 +200,38 @@ where
             .read_set(idx_to_validate)
             .expect("[BlockSTM]: Prior read-set must be recorded");
 
-        let valid = read_set.iter().all(|r| {
-            match versioned_cache.fetch_data(r.path(), idx_to_validate) {
-                Ok(Versioned(version, _)) => r.validate_version(version),
-                Ok(Resolved(value)) => r.validate_resolved(value),
+        // Finds the first read that is no longer valid, along with the transaction whose write
+        // the read would observe now, if any.
+        let invalidated_read = read_set.iter().find_map(|r| {
+            let (valid, writer_idx) = match versioned_cache.fetch_data(r.path(), idx_to_validate) {
+                Ok(Versioned(version, _)) => (r.validate_version(version), Some(version.0)),
+                Ok(Resolved(value)) => (r.validate_resolved(value), None),
                 // Dependency implies a validation failure, and if the original read were to
                 // observe an unresolved delta, it would set the aggregator base value in the
                 // multi-versioned data-structure, resolve, and record the resolved value.
-                Err(Dependency(_)) | Err(Unresolved(_)) => false,
-                Err(NotFound) => r.validate_storage(),
+                Err(Dependency(dep_idx)) => (false, Some(dep_idx)),
+                Err(Unresolved(_)) => (false, None),
+                Err(NotFound) => (r.validate_storage(), None),
                 // We successfully validate when read (again) results in a delta application
                 // failure. If the failure is speculative, a later validation will fail due to
                 // a read without this error. However, if the failure is real, passing
                 // validation here allows to avoid infinitely looping and instead panic when
                 // materializing deltas as writes in the final output preparation state. Panic
                 // is also preferable as it allows testing for this scenario.
-                Err(DeltaApplicationFailure) => r.validate_delta_application_failure(),
-            }
+                Err(DeltaApplicationFailure) => (r.validate_delta_application_failure(), None),
+            };
+            (!valid).then_some((r.path(), writer_idx))
         });
+        let valid = invalidated_read.is_none();
 
         let aborted = !valid && scheduler.try_abort(idx_to_validate, incarnation);
 
         if aborted {
             counters::SPECULATIVE_ABORT_COUNT.inc();
+            if let (Some(recorder), Some((key, writer_idx))) = (conflict_recorder, invalidated_read)
+            {
+                recorder.record_abort(key, idx_to_validate, writer_idx);
+            }
 
             // Any logs from the aborted execution should be cleared and not reported.
             clear_speculative_txn_logs(idx_to_validate as usize);
@@ -341,6 +372,7 @@ where
         scheduler: &Scheduler,
         base_view: &S,
         role: CommitRole,
+        conflict_recorder: Option<&ConflictRecorder<T::Key>>,
     ) {
         // Make executor for each task. TODO: fast concurrent executor.
         let init_timer = VM_INIT_SECONDS.start_timer();
@@ -386,6 +418,7 @@ where
                     last_input_output,
                     versioned_cache,
                     scheduler,
+                    conflict_recorder,
                 ),
                 SchedulerTask::ExecutionTask(version_to_execute, None) => self.execute(
                     version_to_execute,
@@ -395,6 +428,7 @@ where
                     scheduler,
                     &executor,
                     base_view,
+                    conflict_recorder,
                 ),
                 SchedulerTask::ExecutionTask(_, Some(condvar)) => {
                     let (lock, cvar) = &*condvar;
@@ -447,6 +481,9 @@ where
         let num_txns = signature_verified_block.len() as u32;
         let last_input_output = TxnLastInputOutput::new(num_txns);
         let scheduler = Scheduler::new(num_txns);
+        let conflict_recorder = self
+            .record_conflicts
+            .then(|| ConflictRecorder::new(num_txns));
 
         let mut roles: Vec<CommitRole> = vec![];
         let mut senders: Vec<Sender<u32>> = Vec::with_capacity(self.concurrency_level - 1);
@@ -475,12 +512,17 @@ where
                         &scheduler,
                         base_view,
                         role,
+                        conflict_recorder.as_ref(),
                     );
                 });
             }
         });
         drop(timer);
 
+        if let Some(conflict_recorder) = conflict_recorder {
+            *self.conflict_report.lock() = Some(conflict_recorder.into_report());
+        }
+
         let num_txns = num_txns as usize;
         // TODO: for large block sizes and many cores, extract outputs in parallel.
         let mut final_results = Vec::with_capacity(num_txns);
@@ -13,7 +13,7 @@ use aptos_types::{
     executable::{ExecutableTestType, ModulePath},
     write_set::TransactionWrite,
 };
-use claims::{assert_matches, assert_some_eq};
+use claims::{assert_matches, assert_none, assert_some_eq};
 use rand::{prelude::*, random};
 use std::{
     cmp::min,
@@ -261,6 +261,56 @@ fn early_skips() {
     run_and_assert(transactions)
 }
 
+#[test]
+fn conflict_report() {
+    let hot_key = KeyType(random::<[u8; 32]>(), false);
+    let transactions: Vec<_> = (0..TXN_PER_BLOCK)
+        .map(|_| {
+            let key = KeyType(random::<[u8; 32]>(), false);
+            Transaction::Write {
+                incarnation: Arc::new(AtomicUsize::new(0)),
+                reads: vec![vec![hot_key, key]],
+                writes_and_deltas: vec![(
+                    vec![(hot_key, random_value(false)), (key, random_value(false))],
+                    vec![],
+                )],
+            }
+        })
+        .collect();
+
+    let data_view = DeltaDataView::<KeyType<[u8; 32]>, ValueType<Vec<u8>>> {
+        phantom: PhantomData,
+    };
+    let executor_thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(num_cpus::get())
+            .build()
+            .unwrap(),
+    );
+    let executor = BlockExecutor::<
+        Transaction<KeyType<[u8; 32]>, ValueType<Vec<u8>>>,
+        Task<KeyType<[u8; 32]>, ValueType<Vec<u8>>>,
+        DeltaDataView<KeyType<[u8; 32]>, ValueType<Vec<u8>>>,
+        ExecutableTestType,
+    >::new(num_cpus::get(), executor_thread_pool, None)
+    .with_conflict_recording();
+    let output = executor.execute_transactions_parallel((), &transactions, &data_view);
+    ExpectedOutput::generate_baseline(&transactions, None, None).assert_output(&output);
+
+    // Only the key written by every transaction can cause conflicts, and every abort or wait is
+    // attributed to both the key and the transaction that suffered it.
+    let report = executor.take_conflict_report().unwrap();
+    assert!(report.keys.iter().all(|(key, _)| *key == hot_key));
+    let key_conflicts: u64 = report.keys.iter().map(|(_, c)| c.total()).sum();
+    let txn_conflicts: u64 = report
+        .txns
+        .iter()
+        .map(|(_, c)| c.aborts + c.dependency_waits)
+        .sum();
+    assert_eq!(key_conflicts, txn_conflicts);
+    assert_none!(executor.take_conflict_report());
+}
+
 #[test]
 fn scheduler_tasks() {
     let s = Scheduler::new(5);
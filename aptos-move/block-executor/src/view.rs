@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    conflict_recorder::ConflictRecorder,
     counters,
     scheduler::{DependencyResult, DependencyStatus, Scheduler},
     task::Transaction,
@@ -36,6 +37,7 @@ pub(crate) struct MVHashMapView<'a, K, V: TransactionWrite, X: Executable> {
     versioned_map: &'a MVHashMap<K, V, X>,
     scheduler: &'a Scheduler,
     captured_reads: RefCell<Vec<ReadDescriptor<K>>>,
+    conflict_recorder: Option<&'a ConflictRecorder<K>>,
 }
 
 /// A struct which describes the result of the read from the proxy. The client
@@ -61,11 +63,16 @@ impl<
         X: Executable,
     > MVHashMapView<'a, K, V, X>
 {
-    pub(crate) fn new(versioned_map: &'a MVHashMap<K, V, X>, scheduler: &'a Scheduler) -> Self {
+    pub(crate) fn new(
+        versioned_map: &'a MVHashMap<K, V, X>,
+        scheduler: &'a Scheduler,
+        conflict_recorder: Option<&'a ConflictRecorder<K>>,
+    ) -> Self {
         Self {
             versioned_map,
             scheduler,
             captured_reads: RefCell::new(Vec::new()),
+            conflict_recorder,
         }
     }
 
@@ -125,6 +132,9 @@ impl<
                     // `self.txn_idx` estimated to depend on a write from `dep_idx`.
                     match self.scheduler.wait_for_dependency(txn_idx, dep_idx) {
                         DependencyResult::Dependency(dep_condition) => {
+                            if let Some(recorder) = self.conflict_recorder {
+                                recorder.record_dependency_wait(key, txn_idx, dep_idx);
+                            }
                             let _timer = counters::DEPENDENCY_WAIT_SECONDS.start_timer();
                             // Wait on a condition variable corresponding to the encountered
                             // read dependency. Once the dep_idx finishes re-execution, scheduler
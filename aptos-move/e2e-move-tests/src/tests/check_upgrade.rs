@@ -0,0 +1,128 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_abort, assert_success, assert_vm_status, tests::common, MoveHarness};
+use aptos_framework::{
+    natives::code::PackageRegistry,
+    upgrade_check::{check_upgrade, PublishedState, FEATURES_RESOURCE, PACKAGE_REGISTRY_RESOURCE},
+    BuildOptions, BuiltPackage,
+};
+use aptos_types::{
+    access_path::AccessPath, account_address::AccountAddress, state_store::state_key::StateKey,
+};
+use move_core_types::{
+    identifier::Identifier, language_storage::ModuleId, parser::parse_struct_tag,
+    vm_status::StatusCode,
+};
+
+/// Reads the published state of the address from the harness, as `aptos move check-upgrade`
+/// fetches it from a node.
+fn published_state(h: &MoveHarness, address: AccountAddress) -> PublishedState {
+    let features = h
+        .read_resource(
+            &AccountAddress::ONE,
+            parse_struct_tag(FEATURES_RESOURCE).unwrap(),
+        )
+        .unwrap();
+    let mut state = PublishedState::new(address, features);
+    if let Some(registry) = h.read_resource::<PackageRegistry>(
+        &address,
+        parse_struct_tag(PACKAGE_REGISTRY_RESOURCE).unwrap(),
+    ) {
+        for module in registry
+            .packages
+            .iter()
+            .flat_map(|package| &package.modules)
+        {
+            let id = ModuleId::new(address, Identifier::new(module.name.as_str()).unwrap());
+            let bytes = h
+                .read_state_value_bytes(&StateKey::access_path(AccessPath::code_access_path(id)))
+                .unwrap();
+            state.modules.insert(module.name.clone(), bytes);
+        }
+        state.registries.insert(address, registry);
+    }
+    state
+}
+
+/// Checks the package in the given directory against the published state of the address, and
+/// returns the breaking changes found.
+fn check(h: &MoveHarness, address: AccountAddress, dir: &str) -> Vec<String> {
+    let package = BuiltPackage::build(common::test_dir_path(dir), BuildOptions::default()).unwrap();
+    let metadata = package.extract_metadata().unwrap();
+    check_upgrade(&published_state(h, address), &metadata, package.modules())
+        .unwrap()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn check_upgrade_compatible() {
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let addr = *acc.address();
+    assert!(check(&h, addr, "code_publishing.data/pack_initial").is_empty());
+    assert_success!(h.publish_package(
+        &acc,
+        &common::test_dir_path("code_publishing.data/pack_initial"),
+    ));
+
+    assert!(check(&h, addr, "code_publishing.data/pack_upgrade_compat").is_empty());
+    assert_success!(h.publish_package(
+        &acc,
+        &common::test_dir_path("code_publishing.data/pack_upgrade_compat"),
+    ));
+}
+
+#[test]
+fn check_upgrade_incompatible() {
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let addr = *acc.address();
+    assert_success!(h.publish_package(
+        &acc,
+        &common::test_dir_path("code_publishing.data/pack_initial"),
+    ));
+
+    assert_eq!(
+        check(&h, addr, "code_publishing.data/pack_upgrade_incompat"),
+        vec![
+            "module `test`: public function `hello`: parameters changed from `(&signer, u64)` to \
+             `(&signer, u64, u64)`"
+        ]
+    );
+    let status = h.publish_package(
+        &acc,
+        &common::test_dir_path("code_publishing.data/pack_upgrade_incompat"),
+    );
+    assert_vm_status!(status, StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE);
+}
+
+#[test]
+fn check_upgrade_policies() {
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let addr = *acc.address();
+    assert_success!(h.publish_package(
+        &acc,
+        &common::test_dir_path("code_publishing.data/pack_initial_immutable"),
+    ));
+
+    assert_eq!(
+        check(&h, addr, "code_publishing.data/pack_upgrade_compat"),
+        vec![
+            "the published package is `immutable`",
+            "the upgrade policy `compatible` is weaker than the published `immutable`",
+        ]
+    );
+    assert_eq!(
+        check(&h, addr, "code_publishing.data/pack_other_name"),
+        vec!["module `test` is already published by package `test_package`"]
+    );
+    let status = h.publish_package(
+        &acc,
+        &common::test_dir_path("code_publishing.data/pack_upgrade_compat"),
+    );
+    assert_abort!(status, _);
+}
@@ -0,0 +1,101 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress, account_config::CoinStoreResource,
+    on_chain_config::FeatureFlag, transaction::TransactionStatus,
+};
+use move_core_types::{move_resource::MoveStructType, vm_status::StatusCode};
+
+#[test]
+fn test_fee_payer_pays_for_gas() {
+    let mut h = MoveHarness::new();
+
+    let alice = h.new_account_with_key_pair();
+    let bob = h.new_account_with_key_pair();
+    let fee_payer = h.new_account_with_key_pair();
+
+    let alice_start = read_coin(&h, alice.address());
+    let bob_start = read_coin(&h, bob.address());
+    let fee_payer_start = read_coin(&h, fee_payer.address());
+
+    let amount = 100;
+    let transaction = alice
+        .transaction()
+        .fee_payer(fee_payer.clone())
+        .payload(aptos_stdlib::aptos_account_transfer(*bob.address(), amount))
+        .sequence_number(h.sequence_number(alice.address()))
+        .max_gas_amount(1_000_000)
+        .gas_unit_price(1)
+        .sign_fee_payer();
+
+    let output = h.run_raw(transaction);
+    assert_success!(output.status().to_owned());
+
+    assert_eq!(alice_start - amount, read_coin(&h, alice.address()));
+    assert_eq!(bob_start + amount, read_coin(&h, bob.address()));
+    assert_eq!(
+        fee_payer_start - output.gas_used(),
+        read_coin(&h, fee_payer.address())
+    );
+    assert_eq!(h.sequence_number(alice.address()), 1);
+    assert_eq!(h.sequence_number(fee_payer.address()), 0);
+}
+
+#[test]
+fn test_fee_payer_not_enabled() {
+    let mut h = MoveHarness::new_with_features(vec![], vec![FeatureFlag::FEE_PAYER_ENABLED]);
+
+    let alice = h.new_account_with_key_pair();
+    let fee_payer = h.new_account_with_key_pair();
+
+    let transaction = alice
+        .transaction()
+        .fee_payer(fee_payer.clone())
+        .payload(aptos_stdlib::aptos_account_transfer(
+            *fee_payer.address(),
+            1,
+        ))
+        .sequence_number(h.sequence_number(alice.address()))
+        .max_gas_amount(1_000_000)
+        .gas_unit_price(1)
+        .sign_fee_payer();
+
+    assert_eq!(
+        h.run(transaction),
+        TransactionStatus::Discard(StatusCode::FEATURE_UNDER_GATING)
+    );
+}
+
+#[test]
+fn test_fee_payer_without_enough_balance() {
+    let mut h = MoveHarness::new();
+
+    let alice = h.new_account_with_key_pair();
+    let fee_payer = h.new_account_with_balance_and_sequence_number(0, 0);
+
+    let transaction = alice
+        .transaction()
+        .fee_payer(fee_payer.clone())
+        .payload(aptos_stdlib::aptos_account_transfer(
+            *fee_payer.address(),
+            1,
+        ))
+        .sequence_number(h.sequence_number(alice.address()))
+        .max_gas_amount(1_000_000)
+        .gas_unit_price(1)
+        .sign_fee_payer();
+
+    assert_eq!(
+        h.run(transaction),
+        TransactionStatus::Discard(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE)
+    );
+}
+
+fn read_coin(h: &MoveHarness, account: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(account, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
@@ -0,0 +1,175 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::common;
+use aptos_framework::{
+    build_model,
+    lint::{default_lints, LintDiagnostic, LintLevel, Linter},
+};
+use aptos_package_builder::PackageBuilder;
+use std::{collections::BTreeMap, io::Write};
+
+const SOURCE: &str = r#"
+module 0xcafe::m {
+    use std::signer;
+    use std::vector;
+    use aptos_framework::account::{Self, SignerCapability};
+    use aptos_framework::aptos_coin::AptosCoin;
+    use aptos_framework::coin;
+    use aptos_framework::object::{Self, ExtendRef};
+    use std::option::{Self, Option};
+
+    struct Config has key { admins: vector<address>, cap: SignerCapability }
+
+    struct Counter has key { value: u64 }
+
+    struct Refs has key { extend_ref: ExtendRef }
+
+    public fun double(x: u64): u64 {
+        let y = copy x;
+        y + y
+    }
+
+    #[lint_allow(needless_copy)]
+    public fun double_allowed(x: u64): u64 {
+        let y = copy x;
+        y + y
+    }
+
+    public entry fun pay_self(account: &signer, amount: u64) {
+        coin::transfer<AptosCoin>(account, signer::address_of(account), amount);
+    }
+
+    public entry fun pay(account: &signer, to: address, amount: u64) {
+        coin::transfer<AptosCoin>(account, to, amount);
+    }
+
+    public fun value(addr: address): u64 acquires Counter {
+        borrow_global<Counter>(addr).value
+    }
+
+    #[view]
+    public fun viewed_value(addr: address): u64 acquires Counter {
+        borrow_global<Counter>(addr).value
+    }
+
+    public fun increment(addr: address): u64 acquires Counter {
+        let counter = borrow_global_mut<Counter>(addr);
+        counter.value = counter.value + 1;
+        counter.value
+    }
+
+    public entry fun visit_admins(addr: address) acquires Config {
+        let admins = &borrow_global<Config>(addr).admins;
+        let i = 0;
+        while (i < vector::length(admins)) {
+            let _admin = vector::borrow(admins, i);
+            i = i + 1;
+        };
+    }
+
+    public entry fun count(n: u64) {
+        let i = 0;
+        while (i < 10) {
+            i = i + n;
+        };
+    }
+
+    public fun admin_signer(addr: address): signer acquires Config {
+        account::create_signer_with_capability(&borrow_global<Config>(addr).cap)
+    }
+
+    fun internal_signer(addr: address): signer acquires Config {
+        account::create_signer_with_capability(&borrow_global<Config>(addr).cap)
+    }
+
+    public fun extend_ref(owner: &signer): ExtendRef {
+        let constructor_ref = object::create_object_from_account(owner);
+        object::generate_extend_ref(&constructor_ref)
+    }
+
+    public fun take_cap(addr: address, out: &mut Option<SignerCapability>) acquires Config {
+        let Config { admins: _, cap } = move_from<Config>(addr);
+        option::fill(out, cap);
+    }
+
+    public fun object_address(refs: &Refs, owner: &signer): address {
+        let _ = owner;
+        object::address_from_extend_ref(&refs.extend_ref)
+    }
+}
+"#;
+
+/// Lints the source, with the given `[lint]` section in the manifest.
+fn run_lints(lint_section: &str) -> Vec<LintDiagnostic> {
+    let mut builder = PackageBuilder::new("Lint");
+    builder.add_source("m.move", SOURCE);
+    builder.add_local_dep(
+        "AptosFramework",
+        &common::framework_dir_path("aptos-framework").to_string_lossy(),
+    );
+    let dir = builder.write_to_temp().unwrap();
+    let mut manifest = std::fs::OpenOptions::new()
+        .append(true)
+        .open(dir.path().join("Move.toml"))
+        .unwrap();
+    write!(manifest, "\n{}", lint_section).unwrap();
+
+    let mut linter = Linter::new(default_lints());
+    linter.configure_from_manifest(dir.path()).unwrap();
+    let model = build_model(dir.path(), BTreeMap::new(), None, None).unwrap();
+    linter.run(&model)
+}
+
+/// Lints the source, with the given `[lint]` section in the manifest, and returns the level of
+/// each issue found, by lint.
+fn lint(lint_section: &str) -> BTreeMap<&'static str, Vec<LintLevel>> {
+    let mut issues: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for diag in run_lints(lint_section) {
+        issues.entry(diag.lint).or_default().push(diag.level);
+    }
+    issues
+}
+
+#[test]
+fn test_lints() {
+    let issues = lint("");
+    assert_eq!(
+        issues,
+        BTreeMap::from([
+            ("missing_view", vec![LintLevel::Warn]),
+            ("needless_copy", vec![LintLevel::Warn]),
+            ("self_transfer", vec![LintLevel::Warn]),
+            ("signer_leak", vec![LintLevel::Warn; 3]),
+            ("unbounded_iteration", vec![LintLevel::Warn]),
+        ])
+    );
+}
+
+#[test]
+fn test_lint_levels_from_manifest() {
+    let issues = lint("[lint]\nneedless_copy = \"allow\"\nmissing_view = \"error\"\n");
+    assert!(!issues.contains_key("needless_copy"));
+    assert_eq!(issues["missing_view"], vec![LintLevel::Error]);
+    assert_eq!(issues["self_transfer"], vec![LintLevel::Warn]);
+
+    let mut linter = Linter::new(default_lints());
+    assert!(linter.set_level("no_such_lint", LintLevel::Warn).is_err());
+}
+
+#[test]
+fn test_signer_leak() {
+    let messages: Vec<_> = run_lints("")
+        .into_iter()
+        .filter(|diag| diag.lint == "signer_leak")
+        .map(|diag| diag.message)
+        .collect();
+    let flagged = |expected: &str| messages.iter().any(|message| message.contains(expected));
+    assert_eq!(messages.len(), 3);
+    assert!(flagged("`admin_signer` returns a signer"));
+    assert!(flagged("`extend_ref` returns a signer"));
+    assert!(flagged(
+        "`take_cap` can write a signer, or a capability to create one, through `out`"
+    ));
+    assert!(!flagged("`object_address`"));
+}
@@ -5,10 +5,12 @@ mod access_path_test;
 mod aggregator;
 mod attributes;
 mod chain_id;
+mod check_upgrade;
 mod code_publishing;
 mod common;
 mod constructor_args;
 mod error_map;
+mod fee_payer;
 mod fungible_asset;
 mod gas;
 mod generate_upgrade_script;
@@ -16,6 +18,7 @@ mod governance_updates;
 mod infinite_loop;
 mod init_module;
 mod lazy_natives;
+mod lint;
 mod max_loop_depth;
 mod memory_quota;
 mod metadata;
@@ -28,6 +31,7 @@ mod per_category_gas_limits;
 mod resource_groups;
 mod rotate_auth_key;
 mod scripts;
+mod secp256k1_ecdsa;
 mod simple_defi;
 mod smart_data_structures;
 mod stake;
@@ -0,0 +1,79 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{secp256k1_ecdsa::Secp256k1EcdsaPrivateKey, PrivateKey, SigningKey, Uniform};
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{
+    account_config::AccountResource,
+    on_chain_config::FeatureFlag,
+    transaction::{authenticator::AuthenticationKey, SignedTransaction, TransactionStatus},
+};
+use move_core_types::{move_resource::MoveStructType, vm_status::StatusCode};
+
+/// Creates an account whose authentication key is derived from a secp256k1 ECDSA key.
+fn new_secp256k1_ecdsa_account(h: &mut MoveHarness) -> (Account, Secp256k1EcdsaPrivateKey) {
+    let account = h.new_account_with_key_pair();
+    let private_key = Secp256k1EcdsaPrivateKey::generate_for_testing();
+    let auth_key = AuthenticationKey::secp256k1_ecdsa(&private_key.public_key());
+
+    let resource = h
+        .read_resource::<AccountResource>(account.address(), AccountResource::struct_tag())
+        .unwrap();
+    h.set_resource(
+        *account.address(),
+        AccountResource::struct_tag(),
+        &AccountResource::new(
+            resource.sequence_number(),
+            auth_key.to_vec(),
+            resource.coin_register_events().clone(),
+            resource.key_rotation_events().clone(),
+        ),
+    );
+    (account, private_key)
+}
+
+fn transfer(
+    h: &MoveHarness,
+    sender: &Account,
+    private_key: &Secp256k1EcdsaPrivateKey,
+    receiver: &Account,
+) -> SignedTransaction {
+    let raw_txn = sender
+        .transaction()
+        .payload(aptos_stdlib::aptos_account_transfer(*receiver.address(), 1))
+        .sequence_number(h.sequence_number(sender.address()))
+        .max_gas_amount(1_000_000)
+        .gas_unit_price(1)
+        .raw();
+    let signature = private_key.sign(&raw_txn).unwrap();
+    SignedTransaction::new_secp256k1_ecdsa(raw_txn, private_key.public_key(), signature)
+}
+
+#[test]
+fn test_secp256k1_ecdsa_authenticator() {
+    let mut h =
+        MoveHarness::new_with_features(vec![FeatureFlag::SECP256K1_ECDSA_AUTHENTICATOR], vec![]);
+
+    let (alice, private_key) = new_secp256k1_ecdsa_account(&mut h);
+    let bob = h.new_account_with_key_pair();
+
+    assert_success!(h.run(transfer(&h, &alice, &private_key, &bob)));
+    assert_eq!(h.sequence_number(alice.address()), 1);
+}
+
+#[test]
+fn test_secp256k1_ecdsa_authenticator_not_enabled() {
+    let mut h =
+        MoveHarness::new_with_features(vec![], vec![FeatureFlag::SECP256K1_ECDSA_AUTHENTICATOR]);
+
+    let (alice, private_key) = new_secp256k1_ecdsa_account(&mut h);
+    let bob = h.new_account_with_key_pair();
+
+    assert_eq!(
+        h.run(transfer(&h, &alice, &private_key, &bob)),
+        TransactionStatus::Discard(StatusCode::FEATURE_UNDER_GATING)
+    );
+    assert_eq!(h.sequence_number(alice.address()), 0);
+}
@@ -0,0 +1,65 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_language_e2e_tests::{common_transactions::peer_to_peer_txn, executor::FakeExecutor};
+use aptos_types::transaction::Transaction;
+use aptos_vm::sharded_block_executor::{
+    block_partitioner::{BlockPartitioner, SubBlock},
+    ShardedBlockExecutor,
+};
+use std::sync::Arc;
+
+/// Assigns the i-th transaction to shard `i % num_shards`, so that the outputs of the shards,
+/// taken one shard after the other, are not in block order.
+struct InterleavingPartitioner;
+
+impl BlockPartitioner for InterleavingPartitioner {
+    fn partition(&self, transactions: Vec<Transaction>, num_shards: usize) -> Vec<SubBlock> {
+        let mut sub_blocks: Vec<_> = (0..num_shards)
+            .map(|_| SubBlock {
+                txn_indices: vec![],
+                transactions: vec![],
+            })
+            .collect();
+        for (index, txn) in transactions.into_iter().enumerate() {
+            let sub_block = &mut sub_blocks[index % num_shards];
+            sub_block.txn_indices.push(index);
+            sub_block.transactions.push(txn);
+        }
+        sub_blocks.retain(|sub_block| !sub_block.txn_indices.is_empty());
+        sub_blocks
+    }
+}
+
+#[test]
+fn test_sharded_outputs_are_in_block_order() {
+    let mut executor = FakeExecutor::from_head_genesis();
+    // Independent transfers of distinct amounts, so that each output can be told apart.
+    let block: Vec<_> = (0..8)
+        .map(|i| {
+            let sender = executor.create_raw_account_data(1_000_000, 0);
+            let receiver = executor.create_raw_account_data(1_000_000, 0);
+            executor.add_account_data(&sender);
+            executor.add_account_data(&receiver);
+            Transaction::UserTransaction(peer_to_peer_txn(
+                sender.account(),
+                receiver.account(),
+                0,
+                1_000 + i,
+                0,
+            ))
+        })
+        .collect();
+    let expected = executor.execute_transaction_block(block.clone()).unwrap();
+
+    let sharded_executor =
+        ShardedBlockExecutor::new_with_partitioner(3, Some(1), Arc::new(InterleavingPartitioner));
+    let outputs = sharded_executor
+        .execute_block(Arc::new(executor.get_state_view().clone()), block, 1, None)
+        .unwrap();
+    assert_eq!(outputs.len(), expected.len());
+    for (output, expected) in outputs.iter().zip(&expected) {
+        assert_eq!(output.status(), expected.status());
+        assert_eq!(output.events(), expected.events());
+    }
+}
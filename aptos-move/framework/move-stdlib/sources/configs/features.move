@@ -186,6 +186,23 @@ module std::features {
     /// Lifetime: transient
     const CHARGE_INVARIANT_VIOLATION: u64 = 20;
 
+    /// Whether transactions can have a fee payer, i.e., an account other than the sender paying
+    /// for the gas.
+    /// Lifetime: transient
+    const FEE_PAYER_ENABLED: u64 = 21;
+    public fun get_fee_payer_feature(): u64 { FEE_PAYER_ENABLED }
+    public fun fee_payer_enabled(): bool acquires Features {
+        is_enabled(FEE_PAYER_ENABLED)
+    }
+
+    /// Whether transactions can be authenticated with secp256k1 ECDSA keys.
+    /// Lifetime: transient
+    const SECP256K1_ECDSA_AUTHENTICATOR: u64 = 22;
+    public fun get_secp256k1_ecdsa_authenticator_feature(): u64 { SECP256K1_ECDSA_AUTHENTICATOR }
+    public fun secp256k1_ecdsa_authenticator_enabled(): bool acquires Features {
+        is_enabled(SECP256K1_ECDSA_AUTHENTICATOR)
+    }
+
     // ============================================================================================
     // Feature Flag Implementation
 
@@ -56,4 +56,14 @@ spec std::features {
         aborts_if [abstract] false;
         ensures [abstract] result == spec_partial_governance_voting_enabled();
     }
+
+    spec fun spec_fee_payer_enabled(): bool {
+        spec_is_enabled(FEE_PAYER_ENABLED)
+    }
+
+    spec fee_payer_enabled {
+        pragma opaque;
+        aborts_if [abstract] false;
+        ensures [abstract] result == spec_fee_payer_enabled();
+    }
 }
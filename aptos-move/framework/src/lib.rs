@@ -21,9 +21,11 @@ pub use release_builder::*;
 pub mod docgen;
 pub mod extended_checks;
 pub use extended_checks::ResourceGroupScope;
+pub mod lint;
 pub mod prover;
 mod release_bundle;
 mod released_framework;
+pub mod upgrade_check;
 
 use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 pub use release_bundle::*;
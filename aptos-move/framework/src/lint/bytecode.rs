@@ -0,0 +1,202 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for analyzing the bytecode of a function. Lints work on bytecode rather than on the
+//! source because it makes explicit what the source leaves implicit (copies, moves, and the calls
+//! hidden behind inline functions), and because the model keeps no expression tree of function
+//! bodies.
+
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{
+        Bytecode, CodeOffset, CompiledModule, FunctionHandle, LocalIndex, StructDefinitionIndex,
+    },
+};
+use move_core_types::account_address::AccountAddress;
+use std::collections::BTreeSet;
+
+/// Returns the handle of the function called by the instruction, if it is a call.
+fn called_handle<'a>(module: &'a CompiledModule, instr: &Bytecode) -> Option<&'a FunctionHandle> {
+    match instr {
+        Bytecode::Call(idx) => Some(module.function_handle_at(*idx)),
+        Bytecode::CallGeneric(idx) => {
+            Some(module.function_handle_at(module.function_instantiation_at(*idx).handle))
+        },
+        _ => None,
+    }
+}
+
+/// Returns the name of the function called by the instruction, if it is a call.
+pub(crate) fn callee_name<'a>(module: &'a CompiledModule, instr: &Bytecode) -> Option<&'a str> {
+    let handle = called_handle(module, instr)?;
+    Some(module.identifier_at(handle.name).as_str())
+}
+
+/// Returns whether the instruction calls the given function of the framework at `0x1`.
+pub(crate) fn calls_framework(
+    module: &CompiledModule,
+    instr: &Bytecode,
+    module_name: &str,
+    fun_name: &str,
+) -> bool {
+    called_handle(module, instr).map_or(false, |handle| {
+        let module_handle = module.module_handle_at(handle.module);
+        *module.address_identifier_at(module_handle.address) == AccountAddress::ONE
+            && module.identifier_at(module_handle.name).as_str() == module_name
+            && module.identifier_at(handle.name).as_str() == fun_name
+    })
+}
+
+/// Returns the number of values an instruction pops from the stack and the number it pushes.
+fn stack_effect(module: &CompiledModule, instr: &Bytecode) -> (usize, usize) {
+    use Bytecode::*;
+    let field_count = |idx: StructDefinitionIndex| {
+        module
+            .struct_def_at(idx)
+            .declared_field_count()
+            .map_or(0, usize::from)
+    };
+    let call_effect = |handle_idx| {
+        let handle = module.function_handle_at(handle_idx);
+        (
+            module.signature_at(handle.parameters).len(),
+            module.signature_at(handle.return_).len(),
+        )
+    };
+    match instr {
+        Nop | Ret | Branch(_) => (0, 0),
+        Pop | BrTrue(_) | BrFalse(_) | StLoc(_) | Abort => (1, 0),
+        LdU8(_) | LdU16(_) | LdU32(_) | LdU64(_) | LdU128(_) | LdU256(_) | LdConst(_) | LdTrue
+        | LdFalse | CopyLoc(_) | MoveLoc(_) | MutBorrowLoc(_) | ImmBorrowLoc(_) => (0, 1),
+        CastU8
+        | CastU16
+        | CastU32
+        | CastU64
+        | CastU128
+        | CastU256
+        | Not
+        | ReadRef
+        | FreezeRef
+        | MutBorrowField(_)
+        | MutBorrowFieldGeneric(_)
+        | ImmBorrowField(_)
+        | ImmBorrowFieldGeneric(_)
+        | MutBorrowGlobal(_)
+        | MutBorrowGlobalGeneric(_)
+        | ImmBorrowGlobal(_)
+        | ImmBorrowGlobalGeneric(_)
+        | Exists(_)
+        | ExistsGeneric(_)
+        | MoveFrom(_)
+        | MoveFromGeneric(_)
+        | VecLen(_)
+        | VecPopBack(_) => (1, 1),
+        Add | Sub | Mul | Mod | Div | BitOr | BitAnd | Xor | Or | And | Eq | Neq | Lt | Gt | Le
+        | Ge | Shl | Shr | VecImmBorrow(_) | VecMutBorrow(_) => (2, 1),
+        WriteRef | MoveTo(_) | MoveToGeneric(_) | VecPushBack(_) => (2, 0),
+        VecSwap(_) => (3, 0),
+        Call(idx) => call_effect(*idx),
+        CallGeneric(idx) => call_effect(module.function_instantiation_at(*idx).handle),
+        Pack(idx) => (field_count(*idx), 1),
+        PackGeneric(idx) => (field_count(module.struct_instantiation_at(*idx).def), 1),
+        Unpack(idx) => (1, field_count(*idx)),
+        UnpackGeneric(idx) => (1, field_count(module.struct_instantiation_at(*idx).def)),
+        VecPack(_, count) => (*count as usize, 1),
+        VecUnpack(_, count) => (1, *count as usize),
+    }
+}
+
+/// Returns the offsets of the instructions which can follow the one at the given offset.
+fn successors(code: &[Bytecode], offset: usize) -> Vec<usize> {
+    match &code[offset] {
+        Bytecode::Ret | Bytecode::Abort => vec![],
+        Bytecode::Branch(target) => vec![*target as usize],
+        Bytecode::BrTrue(target) | Bytecode::BrFalse(target) => {
+            vec![*target as usize, offset + 1]
+        },
+        _ if offset + 1 < code.len() => vec![offset + 1],
+        _ => vec![],
+    }
+}
+
+/// Returns the loops of the code, as the ranges of offsets between a backward branch and its
+/// target.
+pub(crate) fn loops(code: &[Bytecode]) -> Vec<(CodeOffset, CodeOffset)> {
+    code.iter()
+        .enumerate()
+        .filter_map(|(offset, instr)| match instr {
+            Bytecode::Branch(target) | Bytecode::BrTrue(target) | Bytecode::BrFalse(target)
+                if *target as usize <= offset =>
+            {
+                Some((*target, offset as CodeOffset))
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Evaluates the code with the given abstract semantics, visiting instructions in order. The
+/// `step` function is given the offset of an instruction and the values it pops from the stack,
+/// and returns the value of each result it pushes. The stack is empty at the start of each basic
+/// block, which holds for code generated by the compiler, since it passes values between blocks
+/// in locals.
+pub(crate) fn interpret<V: Clone + Default>(
+    module: &CompiledModule,
+    code: &[Bytecode],
+    mut step: impl FnMut(CodeOffset, &Bytecode, &[V]) -> V,
+) {
+    let mut block_starts = BTreeSet::new();
+    for offset in 0..code.len() {
+        let succs = successors(code, offset);
+        if succs != [offset + 1] {
+            block_starts.extend(succs);
+            block_starts.insert(offset + 1);
+        }
+    }
+    let mut stack: Vec<V> = vec![];
+    for (offset, instr) in code.iter().enumerate() {
+        if block_starts.contains(&offset) {
+            stack.clear();
+        }
+        let (pops, pushes) = stack_effect(module, instr);
+        let mut args = stack.split_off(stack.len().saturating_sub(pops));
+        args.resize(pops, V::default());
+        let result = step(offset as CodeOffset, instr, &args);
+        stack.extend(std::iter::repeat(result).take(pushes));
+    }
+}
+
+/// Returns, for each instruction, the locals whose current value may be read after it executes.
+pub(crate) fn live_locals_after(code: &[Bytecode]) -> Vec<BTreeSet<LocalIndex>> {
+    let mut live_before = vec![BTreeSet::new(); code.len()];
+    let mut live_after = vec![BTreeSet::new(); code.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for offset in (0..code.len()).rev() {
+            let after: BTreeSet<_> = successors(code, offset)
+                .into_iter()
+                .flat_map(|succ| live_before[succ].iter().copied())
+                .collect();
+            let mut before = after.clone();
+            match &code[offset] {
+                Bytecode::StLoc(idx) => {
+                    before.remove(idx);
+                },
+                Bytecode::CopyLoc(idx)
+                | Bytecode::MoveLoc(idx)
+                | Bytecode::MutBorrowLoc(idx)
+                | Bytecode::ImmBorrowLoc(idx) => {
+                    before.insert(*idx);
+                },
+                _ => (),
+            }
+            if before != live_before[offset] {
+                live_before[offset] = before;
+                changed = true;
+            }
+            live_after[offset] = after;
+        }
+    }
+    live_after
+}
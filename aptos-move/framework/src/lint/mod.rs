@@ -0,0 +1,236 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A linter for Move packages, run over the model of a compiled package.
+//!
+//! Each lint has a stable identifier, used to configure its level in the `[lint]` section of
+//! `Move.toml`:
+//!
+//! ```toml
+//! [lint]
+//! needless_copy = "allow"
+//! missing_view = "error"
+//! ```
+//!
+//! and to allow it on a function or a module with an attribute:
+//!
+//! ```move
+//! #[lint_allow(needless_copy, self_transfer)]
+//! fun f() { ... }
+//! ```
+//!
+//! There is no lint for unused `acquires` annotations, since the compiler already rejects them.
+
+mod bytecode;
+mod rules;
+
+use anyhow::{anyhow, bail, Context};
+use codespan_reporting::diagnostic::{Diagnostic, Label, Severity};
+use move_model::{
+    ast::Attribute,
+    model::{FunctionEnv, GlobalEnv, Loc},
+};
+use move_package::source_package::{
+    layout::SourcePackageLayout, manifest_parser::parse_move_manifest_string,
+};
+pub use rules::default_lints;
+use std::{collections::BTreeMap, fmt, path::Path, str::FromStr};
+
+/// Attribute allowing lints on the function or module it is attached to.
+pub const LINT_ALLOW_ATTRIBUTE: &str = "lint_allow";
+/// Section of `Move.toml` configuring the level of lints.
+pub const LINT_MANIFEST_SECTION: &str = "lint";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum LintLevel {
+    /// The lint is not run.
+    Allow,
+    /// Issues found by the lint are reported as warnings.
+    Warn,
+    /// Issues found by the lint are reported as errors.
+    Error,
+}
+
+impl FromStr for LintLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "allow" => Ok(LintLevel::Allow),
+            "warn" => Ok(LintLevel::Warn),
+            "error" => Ok(LintLevel::Error),
+            _ => bail!(
+                "Invalid lint level `{}`, expected one of `allow`, `warn` or `error`",
+                s
+            ),
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LintLevel::Allow => "allow",
+            LintLevel::Warn => "warn",
+            LintLevel::Error => "error",
+        })
+    }
+}
+
+pub trait Lint {
+    /// Stable identifier of the lint, used to configure and allow it.
+    fn id(&self) -> &'static str;
+
+    /// One line description of what the lint checks.
+    fn description(&self) -> &'static str;
+
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Checks a function of a target module, returning the location and message of each issue.
+    fn check_function(&self, fun: &FunctionEnv) -> Vec<(Loc, String)>;
+}
+
+/// An issue found by a lint.
+#[derive(Clone, Debug)]
+pub struct LintDiagnostic {
+    pub lint: &'static str,
+    pub level: LintLevel,
+    pub loc: Loc,
+    pub message: String,
+}
+
+pub struct Linter {
+    lints: Vec<Box<dyn Lint>>,
+    levels: BTreeMap<&'static str, LintLevel>,
+}
+
+impl Linter {
+    pub fn new(lints: Vec<Box<dyn Lint>>) -> Self {
+        let levels = lints
+            .iter()
+            .map(|lint| (lint.id(), lint.default_level()))
+            .collect();
+        Self { lints, levels }
+    }
+
+    pub fn lints(&self) -> impl Iterator<Item = (&dyn Lint, LintLevel)> {
+        self.lints
+            .iter()
+            .map(|lint| (lint.as_ref(), self.levels[lint.id()]))
+    }
+
+    pub fn set_level(&mut self, id: &str, level: LintLevel) -> anyhow::Result<()> {
+        let (_, current) = self
+            .levels
+            .iter_mut()
+            .find(|(lint, _)| **lint == id)
+            .ok_or_else(|| anyhow!("Unknown lint `{}`", id))?;
+        *current = level;
+        Ok(())
+    }
+
+    /// Sets the levels of lints from the `[lint]` section of the manifest of the package, if any.
+    pub fn configure_from_manifest(&mut self, package_path: &Path) -> anyhow::Result<()> {
+        let manifest_path = package_path.join(SourcePackageLayout::Manifest.path());
+        let manifest = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read {}", manifest_path.display()))?;
+        let manifest = parse_move_manifest_string(manifest)?;
+        let section = match manifest.get(LINT_MANIFEST_SECTION) {
+            Some(section) => section.as_table().ok_or_else(|| {
+                anyhow!(
+                    "The `[{}]` section of the manifest must be a table",
+                    LINT_MANIFEST_SECTION
+                )
+            })?,
+            None => return Ok(()),
+        };
+        for (id, level) in section {
+            let level = level
+                .as_str()
+                .ok_or_else(|| anyhow!("The level of lint `{}` must be a string", id))?;
+            self.set_level(id, level.parse()?)?;
+        }
+        Ok(())
+    }
+
+    /// Runs the lints on the target modules of the environment. Issues are reported to `env`, as
+    /// warnings or errors depending on the level of their lint, and returned.
+    pub fn run(&self, env: &GlobalEnv) -> Vec<LintDiagnostic> {
+        let mut diags = vec![];
+        for module in env.get_modules() {
+            if !module.is_target() {
+                continue;
+            }
+            let module_allowed = self.allowed_lints(env, module.get_attributes());
+            for fun in module.get_functions() {
+                let allowed = self.allowed_lints(env, fun.get_attributes());
+                for (lint, level) in self.lints() {
+                    if level == LintLevel::Allow
+                        || module_allowed.contains(&lint.id())
+                        || allowed.contains(&lint.id())
+                    {
+                        continue;
+                    }
+                    for (loc, message) in lint.check_function(&fun) {
+                        diags.push(LintDiagnostic {
+                            lint: lint.id(),
+                            level,
+                            loc,
+                            message,
+                        });
+                    }
+                }
+            }
+        }
+        for diag in &diags {
+            let severity = match diag.level {
+                LintLevel::Error => Severity::Error,
+                LintLevel::Allow | LintLevel::Warn => Severity::Warning,
+            };
+            env.add_diag(
+                Diagnostic::new(severity)
+                    .with_code(diag.lint)
+                    .with_message(&diag.message)
+                    .with_labels(vec![Label::primary(diag.loc.file_id(), diag.loc.span())])
+                    .with_notes(vec![format!(
+                        "add `#[{}({})]` to the function or module to allow this lint",
+                        LINT_ALLOW_ATTRIBUTE, diag.lint
+                    )]),
+            );
+        }
+        diags
+    }
+
+    /// Returns the lints allowed by the given attributes, reporting unknown lints to `env`.
+    fn allowed_lints(&self, env: &GlobalEnv, attributes: &[Attribute]) -> Vec<&'static str> {
+        let mut allowed = vec![];
+        for attr in attributes {
+            let args = match attr {
+                Attribute::Apply(_, name, args)
+                    if env.symbol_pool().string(*name).as_str() == LINT_ALLOW_ATTRIBUTE =>
+                {
+                    args
+                },
+                _ => continue,
+            };
+            for arg in args {
+                let (node_id, name) = match arg {
+                    Attribute::Apply(node_id, name, _) | Attribute::Assign(node_id, name, _) => {
+                        (*node_id, env.symbol_pool().string(*name))
+                    },
+                };
+                match self.levels.keys().find(|id| **id == name.as_str()) {
+                    Some(id) => allowed.push(*id),
+                    None => env.diag(
+                        Severity::Warning,
+                        &env.get_node_loc(node_id),
+                        &format!("unknown lint `{}` in `{}`", name, LINT_ALLOW_ATTRIBUTE),
+                    ),
+                }
+            }
+        }
+        allowed
+    }
+}
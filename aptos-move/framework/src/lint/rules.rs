@@ -0,0 +1,497 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::lint::{
+    bytecode::{callee_name, calls_framework, interpret, live_locals_after, loops},
+    Lint,
+};
+use move_binary_format::file_format::{
+    Bytecode, CodeOffset, CompiledModule, LocalIndex, Visibility,
+};
+use move_model::{
+    ast::Attribute,
+    model::{FunctionEnv, GlobalEnv, Loc, Parameter},
+    ty::{PrimitiveType, Type},
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+const VIEW_FUN_ATTRIBUTE: &str = "view";
+
+/// Returns the lints shipped with the linter.
+pub fn default_lints() -> Vec<Box<dyn Lint>> {
+    vec![
+        Box::new(NeedlessCopy),
+        Box::new(SelfTransfer),
+        Box::new(MissingView),
+        Box::new(UnboundedIteration),
+        Box::new(SignerLeak),
+    ]
+}
+
+/// Returns the bytecode of a function along with its compiled module, if available.
+fn code<'a>(fun: &'a FunctionEnv) -> Option<(&'a CompiledModule, &'a [Bytecode])> {
+    Some((fun.module_env.get_verified_module()?, fun.get_bytecode()?))
+}
+
+fn bytecode_loc(fun: &FunctionEnv, offset: CodeOffset) -> Loc {
+    fun.get_bytecode_loc(offset)
+        .unwrap_or_else(|| fun.get_loc())
+}
+
+/// Returns the name of a local as written in the source, without the suffixes added by the
+/// compiler.
+fn local_name(fun: &FunctionEnv, idx: LocalIndex) -> String {
+    match fun.get_local_name(idx as usize) {
+        Some(name) => {
+            let name = fun.symbol_pool().string(name);
+            name.split('#').next().unwrap_or_default().to_string()
+        },
+        None => format!("$t{}", idx),
+    }
+}
+
+// ----------------------------------------------------------------------------------
+// Needless copy
+
+/// Flags explicit copies of locals which are not used afterwards. The compiler turns implicit
+/// copies of locals at their last use into moves, so the copies of dead locals remaining in the
+/// bytecode are the ones written in the source. Locals which are borrowed are skipped, since a
+/// reference to them may still be used after their last use.
+struct NeedlessCopy;
+
+impl Lint for NeedlessCopy {
+    fn id(&self) -> &'static str {
+        "needless_copy"
+    }
+
+    fn description(&self) -> &'static str {
+        "`copy` of a local which is not used afterwards, and could be moved instead"
+    }
+
+    fn check_function(&self, fun: &FunctionEnv) -> Vec<(Loc, String)> {
+        let (_, code) = match code(fun) {
+            Some(code) => code,
+            None => return vec![],
+        };
+        let live_after = live_locals_after(code);
+        let borrowed: BTreeSet<_> = code
+            .iter()
+            .filter_map(|instr| match instr {
+                Bytecode::MutBorrowLoc(idx) | Bytecode::ImmBorrowLoc(idx) => Some(*idx),
+                _ => None,
+            })
+            .collect();
+        code.iter()
+            .enumerate()
+            .filter_map(|(offset, instr)| match instr {
+                Bytecode::CopyLoc(idx)
+                    if !live_after[offset].contains(idx)
+                        && !borrowed.contains(idx)
+                        && fun.is_temporary(*idx as usize) == Some(false) =>
+                {
+                    Some((
+                        bytecode_loc(fun, offset as CodeOffset),
+                        format!(
+                            "`{}` is not used after this copy, it can be moved instead",
+                            local_name(fun, *idx)
+                        ),
+                    ))
+                },
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+// ----------------------------------------------------------------------------------
+// Self transfer
+
+/// Transfer functions of the framework, with the index of the recipient parameter. The signer
+/// the transfer is made from is the first parameter.
+const TRANSFER_FUNCTIONS: &[(&str, &str, usize)] = &[
+    ("coin", "transfer", 1),
+    ("aptos_account", "transfer", 1),
+    ("aptos_account", "transfer_coins", 1),
+    ("object", "transfer", 2),
+    ("object", "transfer_call", 2),
+    ("object", "transfer_raw", 2),
+];
+
+/// Flags transfers from a signer to its own address, which have no effect.
+struct SelfTransfer;
+
+#[derive(Clone, Default, PartialEq)]
+enum SignerValue {
+    #[default]
+    Other,
+    /// The value of a local, before it is assigned
+    Local(LocalIndex),
+    /// The address of the signer in a local
+    AddressOf(LocalIndex),
+}
+
+impl Lint for SelfTransfer {
+    fn id(&self) -> &'static str {
+        "self_transfer"
+    }
+
+    fn description(&self) -> &'static str {
+        "transfer from a signer to its own address"
+    }
+
+    fn check_function(&self, fun: &FunctionEnv) -> Vec<(Loc, String)> {
+        let (module, code) = match code(fun) {
+            Some(code) => code,
+            None => return vec![],
+        };
+        let mut locals = BTreeMap::new();
+        let mut issues = vec![];
+        interpret(module, code, |offset, instr, args: &[SignerValue]| {
+            match instr {
+                Bytecode::CopyLoc(idx) | Bytecode::MoveLoc(idx) => {
+                    return locals.get(idx).cloned().unwrap_or(SignerValue::Local(*idx));
+                },
+                Bytecode::StLoc(idx) => {
+                    locals.insert(*idx, args[0].clone());
+                },
+                Bytecode::FreezeRef => return args[0].clone(),
+                _ if calls_framework(module, instr, "signer", "address_of") => {
+                    if let SignerValue::Local(idx) = args[0] {
+                        return SignerValue::AddressOf(idx);
+                    }
+                },
+                _ => {
+                    let is_self_transfer =
+                        TRANSFER_FUNCTIONS
+                            .iter()
+                            .any(|(module_name, fun_name, recipient)| {
+                                calls_framework(module, instr, module_name, fun_name)
+                                    && matches!(
+                                        (&args[0], &args[*recipient]),
+                                        (SignerValue::Local(from), SignerValue::AddressOf(to))
+                                            if from == to
+                                    )
+                            });
+                    if is_self_transfer {
+                        issues.push((
+                            bytecode_loc(fun, offset),
+                            "transfer to the address of the signer it is made from, which has no \
+                             effect"
+                                .to_string(),
+                        ));
+                    }
+                },
+            }
+            SignerValue::Other
+        });
+        issues
+    }
+}
+
+// ----------------------------------------------------------------------------------
+// Missing view
+
+/// Flags public functions which read global storage without modifying it, returning values
+/// without taking arguments a view function cannot take, and which are not marked as views. Such
+/// functions can be called off-chain if marked with `#[view]`.
+struct MissingView;
+
+/// Whether a view function can take an argument of the type, which is passed as JSON.
+fn is_view_argument(env: &GlobalEnv, ty: &Type) -> bool {
+    match ty {
+        Type::Primitive(prim) => !matches!(prim, PrimitiveType::Signer),
+        Type::Vector(elem) => is_view_argument(env, elem),
+        Type::Struct(module_id, struct_id, _) => matches!(
+            env.get_struct(module_id.qualified(*struct_id))
+                .get_full_name_with_address()
+                .as_str(),
+            "0x1::string::String" | "0x1::object::Object"
+        ),
+        _ => false,
+    }
+}
+
+fn has_attribute(fun: &FunctionEnv, attr_name: &str) -> bool {
+    fun.get_attributes().iter().any(|attr| {
+        if let Attribute::Apply(_, name, _) = attr {
+            fun.symbol_pool().string(*name).as_str() == attr_name
+        } else {
+            false
+        }
+    })
+}
+
+/// Returns whether the function, or any function it calls, reads and writes global storage.
+fn storage_access(fun: &FunctionEnv) -> (bool, bool) {
+    let env = fun.module_env.env;
+    let mut reads = false;
+    let mut writes = false;
+    let called = fun
+        .get_transitive_closure_of_called_functions()
+        .unwrap_or_default();
+    for fun in std::iter::once(fun.clone()).chain(called.into_iter().map(|id| env.get_function(id)))
+    {
+        if fun.is_native() {
+            match fun.module_env.get_full_name_str().as_str() {
+                "0x1::object" if fun.get_name_str() == "exists_at" => reads = true,
+                "0x1::event" => writes = true,
+                _ => (),
+            }
+        }
+        for instr in fun.get_bytecode().unwrap_or_default() {
+            match instr {
+                Bytecode::ImmBorrowGlobal(_)
+                | Bytecode::ImmBorrowGlobalGeneric(_)
+                | Bytecode::Exists(_)
+                | Bytecode::ExistsGeneric(_) => reads = true,
+                Bytecode::MutBorrowGlobal(_)
+                | Bytecode::MutBorrowGlobalGeneric(_)
+                | Bytecode::MoveFrom(_)
+                | Bytecode::MoveFromGeneric(_)
+                | Bytecode::MoveTo(_)
+                | Bytecode::MoveToGeneric(_) => writes = true,
+                _ => (),
+            }
+        }
+    }
+    (reads, writes)
+}
+
+impl Lint for MissingView {
+    fn id(&self) -> &'static str {
+        "missing_view"
+    }
+
+    fn description(&self) -> &'static str {
+        "public function reading global storage without modifying it, not marked as `#[view]`"
+    }
+
+    fn check_function(&self, fun: &FunctionEnv) -> Vec<(Loc, String)> {
+        let results = fun.get_result_type().flatten();
+        let candidate = fun.visibility() == Visibility::Public
+            && !fun.is_entry()
+            && !fun.is_inline()
+            && !fun.is_native()
+            && !results.is_empty()
+            && !results.iter().any(|ty| ty.is_reference() || ty.is_signer())
+            && fun
+                .get_parameter_types()
+                .iter()
+                .all(|ty| is_view_argument(fun.module_env.env, ty))
+            && !has_attribute(fun, VIEW_FUN_ATTRIBUTE);
+        if candidate && storage_access(fun) == (true, false) {
+            vec![(
+                fun.get_loc(),
+                format!(
+                    "`{}` only reads global storage, consider marking it with `#[view]` so it can \
+                     be called off-chain",
+                    fun.get_name_str()
+                ),
+            )]
+        } else {
+            vec![]
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------------
+// Unbounded iteration
+
+/// Flags loops of entry functions bounded by the length of a vector read from global storage.
+/// Such vectors may grow without bound, until the gas needed to iterate over them exceeds the
+/// maximum gas of a transaction, making the function impossible to execute.
+struct UnboundedIteration;
+
+#[derive(Clone, Default, PartialEq)]
+enum StorageValue {
+    #[default]
+    Other,
+    /// A value read from global storage, or a reference into it
+    Stored,
+    /// The length of a collection read from global storage, or a value computed from it
+    StoredLength,
+}
+
+impl Lint for UnboundedIteration {
+    fn id(&self) -> &'static str {
+        "unbounded_iteration"
+    }
+
+    fn description(&self) -> &'static str {
+        "loop of an entry function over a vector read from global storage"
+    }
+
+    fn check_function(&self, fun: &FunctionEnv) -> Vec<(Loc, String)> {
+        if !fun.is_entry() {
+            return vec![];
+        }
+        let (module, code) = match code(fun) {
+            Some(code) => code,
+            None => return vec![],
+        };
+        let mut locals = BTreeMap::new();
+        let mut length_checks = vec![];
+        interpret(module, code, |offset, instr, args: &[StorageValue]| {
+            let any = |value| args.contains(&value);
+            match instr {
+                Bytecode::MutBorrowGlobal(_)
+                | Bytecode::MutBorrowGlobalGeneric(_)
+                | Bytecode::ImmBorrowGlobal(_)
+                | Bytecode::ImmBorrowGlobalGeneric(_)
+                | Bytecode::MoveFrom(_)
+                | Bytecode::MoveFromGeneric(_) => StorageValue::Stored,
+                Bytecode::CopyLoc(idx)
+                | Bytecode::MoveLoc(idx)
+                | Bytecode::MutBorrowLoc(idx)
+                | Bytecode::ImmBorrowLoc(idx) => locals.get(idx).cloned().unwrap_or_default(),
+                Bytecode::StLoc(idx) => {
+                    locals.insert(*idx, args[0].clone());
+                    StorageValue::Other
+                },
+                Bytecode::VecLen(_) if any(StorageValue::Stored) => StorageValue::StoredLength,
+                // the length of vectors and of other collections of the framework
+                Bytecode::Call(_) | Bytecode::CallGeneric(_) if any(StorageValue::Stored) => {
+                    if callee_name(module, instr) == Some("length") {
+                        StorageValue::StoredLength
+                    } else {
+                        StorageValue::Stored
+                    }
+                },
+                Bytecode::Lt
+                | Bytecode::Le
+                | Bytecode::Gt
+                | Bytecode::Ge
+                | Bytecode::Eq
+                | Bytecode::Neq
+                    if any(StorageValue::StoredLength) =>
+                {
+                    length_checks.push(offset);
+                    StorageValue::Other
+                },
+                Bytecode::Add
+                | Bytecode::Sub
+                | Bytecode::Mul
+                | Bytecode::Div
+                | Bytecode::Mod
+                | Bytecode::CastU8
+                | Bytecode::CastU16
+                | Bytecode::CastU32
+                | Bytecode::CastU64
+                | Bytecode::CastU128
+                | Bytecode::CastU256
+                    if any(StorageValue::StoredLength) =>
+                {
+                    StorageValue::StoredLength
+                },
+                Bytecode::Call(_)
+                | Bytecode::CallGeneric(_)
+                | Bytecode::Lt
+                | Bytecode::Le
+                | Bytecode::Gt
+                | Bytecode::Ge
+                | Bytecode::Neq
+                | Bytecode::Eq => StorageValue::Other,
+                _ if any(StorageValue::Stored) => StorageValue::Stored,
+                _ => StorageValue::Other,
+            }
+        });
+        let loops = loops(code);
+        length_checks
+            .into_iter()
+            .find(|offset| {
+                loops
+                    .iter()
+                    .any(|(start, end)| start <= offset && offset <= end)
+            })
+            .map(|offset| {
+                (
+                    bytecode_loc(fun, offset),
+                    "loop bounded by the length of a vector read from global storage: the gas \
+                     cost of this entry function grows with the vector, which may eventually make \
+                     it impossible to execute"
+                        .to_string(),
+                )
+            })
+            .into_iter()
+            .collect()
+    }
+}
+
+// ----------------------------------------------------------------------------------
+// Signer leak
+
+/// Structs which give access to a signer: the account signer capability, and the object refs
+/// which can generate the object's signer or take ownership of the object.
+const SIGNER_CAPABILITIES: &[&str] = &[
+    "0x1::account::SignerCapability",
+    "0x1::object::ExtendRef",
+    "0x1::object::TransferRef",
+    "0x1::object::DeleteRef",
+];
+
+/// Flags public functions, other than entry functions, handing out a signer or a capability to
+/// create one, either as a result or through a `&mut` parameter, which lets any module act on
+/// behalf of the account.
+struct SignerLeak;
+
+/// Returns whether a value of the type gives access to a signer.
+fn contains_signer(env: &GlobalEnv, ty: &Type) -> bool {
+    match ty {
+        Type::Primitive(PrimitiveType::Signer) => true,
+        Type::Reference(_, ty) | Type::Vector(ty) => contains_signer(env, ty),
+        Type::Tuple(tys) => tys.iter().any(|ty| contains_signer(env, ty)),
+        Type::Struct(module_id, struct_id, tys) => {
+            let name = env
+                .get_struct(module_id.qualified(*struct_id))
+                .get_full_name_with_address();
+            SIGNER_CAPABILITIES.contains(&name.as_str())
+                || tys.iter().any(|ty| contains_signer(env, ty))
+        },
+        _ => false,
+    }
+}
+
+impl Lint for SignerLeak {
+    fn id(&self) -> &'static str {
+        "signer_leak"
+    }
+
+    fn description(&self) -> &'static str {
+        "public non-entry function returning a `signer` or a capability to create one, or \
+         writing one through a `&mut` parameter"
+    }
+
+    fn check_function(&self, fun: &FunctionEnv) -> Vec<(Loc, String)> {
+        if fun.visibility() != Visibility::Public || fun.is_entry() {
+            return vec![];
+        }
+        let env = fun.module_env.env;
+        let mut issues = vec![];
+        if contains_signer(env, &fun.get_result_type()) {
+            issues.push((
+                fun.get_loc(),
+                format!(
+                    "public function `{}` returns a signer, or a capability to create one, \
+                     which lets any module act on behalf of the account; consider restricting \
+                     it to friends",
+                    fun.get_name_str()
+                ),
+            ));
+        }
+        for Parameter(name, ty) in fun.get_parameters() {
+            if matches!(&ty, Type::Reference(true, ty) if contains_signer(env, ty)) {
+                issues.push((
+                    fun.get_loc(),
+                    format!(
+                        "public function `{}` can write a signer, or a capability to create \
+                         one, through `{}`, which lets any module act on behalf of the \
+                         account; consider restricting it to friends",
+                        fun.get_name_str(),
+                        fun.symbol_pool().string(name)
+                    ),
+                ));
+            }
+        }
+        issues
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline check of a package upgrade. Given the published state of the publishing address and of
+//! the dependencies of a package, reproduces the checks done by `code::publish_package` and the
+//! compatibility check done by the VM, and reports every change which would make publishing the
+//! package fail on chain, instead of stopping at the first one.
+
+use crate::natives::code::{PackageDep, PackageMetadata, PackageRegistry, UpgradePolicy};
+use anyhow::Context;
+use aptos_types::on_chain_config::{FeatureFlag, Features};
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, Incompatibility},
+    normalized, CompiledModule,
+};
+use move_core_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// The resource holding the packages published at an address.
+pub const PACKAGE_REGISTRY_RESOURCE: &str = "0x1::code::PackageRegistry";
+/// The resource holding the on-chain feature flags.
+pub const FEATURES_RESOURCE: &str = "0x1::features::Features";
+
+/// The part of the on-chain state which determines whether a package can be published at an
+/// address. It is either fetched from a node or loaded from a snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PublishedState {
+    /// The address the package is published at.
+    pub address: AccountAddress,
+    /// The package registries of the publishing address and of the addresses of dependencies, if
+    /// they exist. Registries of the core addresses `0x1` to `0xa` are not needed.
+    pub registries: BTreeMap<AccountAddress, PackageRegistry>,
+    /// The bytecode of the modules published at the publishing address, by module name.
+    pub modules: BTreeMap<String, Vec<u8>>,
+    /// The on-chain feature flags.
+    pub features: Features,
+}
+
+impl PublishedState {
+    /// Creates the state of an address with no published packages.
+    pub fn new(address: AccountAddress, features: Features) -> Self {
+        Self {
+            address,
+            registries: BTreeMap::new(),
+            modules: BTreeMap::new(),
+            features,
+        }
+    }
+
+    /// Returns the published package of the given name, if any.
+    pub fn package(&self, address: AccountAddress, name: &str) -> Option<&PackageMetadata> {
+        self.registries
+            .get(&address)?
+            .packages
+            .iter()
+            .find(|package| package.name == name)
+    }
+
+    /// Returns the compatibility check the VM applies to upgraded modules.
+    pub fn compatibility(&self) -> Compatibility {
+        Compatibility::new(
+            true,
+            true,
+            !self
+                .features
+                .is_enabled(FeatureFlag::TREAT_FRIEND_AS_PRIVATE),
+        )
+    }
+}
+
+/// A change which makes publishing a package fail. Each variant but `Incompatible` corresponds to
+/// an abort code of `code::publish_package`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BreakingChange {
+    /// The `arbitrary` upgrade policy is disabled (`EINCOMPATIBLE_POLICY_DISABLED`).
+    PolicyDisabled,
+    /// A dependency is not published (`EPACKAGE_DEP_MISSING`).
+    DependencyMissing(PackageDep),
+    /// A dependency has a weaker upgrade policy than the package (`EDEP_WEAKER_POLICY`).
+    DependencyWeakerPolicy {
+        dep: PackageDep,
+        policy: UpgradePolicy,
+    },
+    /// A dependency with the `arbitrary` policy is at another address
+    /// (`EDEP_ARBITRARY_NOT_SAME_ADDRESS`).
+    DependencyArbitraryNotSameAddress(PackageDep),
+    /// The published package is immutable (`EUPGRADE_IMMUTABLE`).
+    Immutable,
+    /// The upgrade policy is weaker than the published one (`EUPGRADE_WEAKER_POLICY`).
+    WeakerPolicy {
+        old: UpgradePolicy,
+        new: UpgradePolicy,
+    },
+    /// A module of the published package was removed (`EMODULE_MISSING`).
+    ModuleMissing(String),
+    /// A module is already published by another package at the address (`EMODULE_NAME_CLASH`).
+    ModuleNameClash { module: String, package: String },
+    /// A module is incompatible with its published version.
+    Incompatible {
+        module: String,
+        incompatibility: Incompatibility,
+    },
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BreakingChange::PolicyDisabled => write!(
+                f,
+                "the `{}` upgrade policy is disabled",
+                UpgradePolicy::arbitrary()
+            ),
+            BreakingChange::DependencyMissing(dep) => write!(
+                f,
+                "dependency `{}` is not published at {}",
+                dep.package_name, dep.account
+            ),
+            BreakingChange::DependencyWeakerPolicy { dep, policy } => write!(
+                f,
+                "dependency `{}` at {} has the weaker upgrade policy `{}`",
+                dep.package_name, dep.account, policy
+            ),
+            BreakingChange::DependencyArbitraryNotSameAddress(dep) => write!(
+                f,
+                "dependency `{}` has the `{}` upgrade policy but is at another address ({})",
+                dep.package_name,
+                UpgradePolicy::arbitrary(),
+                dep.account
+            ),
+            BreakingChange::Immutable => {
+                write!(
+                    f,
+                    "the published package is `{}`",
+                    UpgradePolicy::immutable()
+                )
+            },
+            BreakingChange::WeakerPolicy { old, new } => write!(
+                f,
+                "the upgrade policy `{}` is weaker than the published `{}`",
+                new, old
+            ),
+            BreakingChange::ModuleMissing(module) => {
+                write!(f, "module `{}` was removed", module)
+            },
+            BreakingChange::ModuleNameClash { module, package } => write!(
+                f,
+                "module `{}` is already published by package `{}`",
+                module, package
+            ),
+            BreakingChange::Incompatible {
+                module,
+                incompatibility,
+            } => write!(f, "module `{}`: {}", module, incompatibility),
+        }
+    }
+}
+
+/// Core addresses exempted from the dependency checks, see `code::is_policy_exempted_address`.
+pub fn is_policy_exempted_address(address: AccountAddress) -> bool {
+    let bytes = address.into_bytes();
+    let (prefix, last) = bytes.split_at(AccountAddress::LENGTH - 1);
+    prefix.iter().all(|b| *b == 0) && (1..=10).contains(&last[0])
+}
+
+/// Checks whether the package with the given metadata and modules can be published on top of
+/// the published state, returning every breaking change found. Fails if the bytecode of an
+/// upgraded module is missing from the state or cannot be deserialized.
+pub fn check_upgrade<'a>(
+    state: &PublishedState,
+    metadata: &PackageMetadata,
+    modules: impl IntoIterator<Item = &'a CompiledModule>,
+) -> anyhow::Result<Vec<BreakingChange>> {
+    let mut changes = vec![];
+    if metadata.upgrade_policy == UpgradePolicy::arbitrary() {
+        changes.push(BreakingChange::PolicyDisabled);
+    }
+
+    for dep in &metadata.deps {
+        if is_policy_exempted_address(dep.account) {
+            continue;
+        }
+        match state.package(dep.account, &dep.package_name) {
+            None => changes.push(BreakingChange::DependencyMissing(dep.clone())),
+            Some(dep_package) => {
+                if dep_package.upgrade_policy.policy < metadata.upgrade_policy.policy {
+                    changes.push(BreakingChange::DependencyWeakerPolicy {
+                        dep: dep.clone(),
+                        policy: dep_package.upgrade_policy,
+                    });
+                }
+                if dep_package.upgrade_policy == UpgradePolicy::arbitrary()
+                    && dep.account != state.address
+                {
+                    changes.push(BreakingChange::DependencyArbitraryNotSameAddress(
+                        dep.clone(),
+                    ));
+                }
+            },
+        }
+    }
+
+    let packages = state
+        .registries
+        .get(&state.address)
+        .map_or(&[][..], |registry| &registry.packages);
+    for package in packages {
+        if package.name == metadata.name {
+            if package.upgrade_policy.policy >= UpgradePolicy::immutable().policy {
+                changes.push(BreakingChange::Immutable);
+            }
+            if metadata.upgrade_policy.policy < package.upgrade_policy.policy {
+                changes.push(BreakingChange::WeakerPolicy {
+                    old: package.upgrade_policy,
+                    new: metadata.upgrade_policy,
+                });
+            }
+            for module in &package.modules {
+                if !metadata.modules.iter().any(|m| m.name == module.name) {
+                    changes.push(BreakingChange::ModuleMissing(module.name.clone()));
+                }
+            }
+        } else {
+            for module in &package.modules {
+                if metadata.modules.iter().any(|m| m.name == module.name) {
+                    changes.push(BreakingChange::ModuleNameClash {
+                        module: module.name.clone(),
+                        package: package.name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    let compatibility = state.compatibility();
+    for module in modules {
+        let name = module.self_id().name().to_string();
+        let is_published = packages
+            .iter()
+            .any(|package| package.modules.iter().any(|m| m.name == name));
+        if !is_published {
+            continue;
+        }
+        let bytes = state
+            .modules
+            .get(&name)
+            .with_context(|| format!("Missing the published bytecode of module `{}`", name))?;
+        let old_module = CompiledModule::deserialize(bytes)
+            .with_context(|| format!("Failed to deserialize published module `{}`", name))?;
+        let report = compatibility.report(
+            &normalized::Module::new(&old_module),
+            &normalized::Module::new(module),
+        );
+        changes.extend(
+            report
+                .into_iter()
+                .map(|incompatibility| BreakingChange::Incompatible {
+                    module: name.clone(),
+                    incompatibility,
+                }),
+        );
+    }
+    Ok(changes)
+}
@@ -411,6 +411,8 @@ pub fn default_features() -> Vec<FeatureFlag> {
         FeatureFlag::BLS12_381_STRUCTURES,
         FeatureFlag::STORAGE_SLOT_METADATA,
         FeatureFlag::CHARGE_INVARIANT_VIOLATION,
+        FeatureFlag::FEE_PAYER_ENABLED,
+        FeatureFlag::SECP256K1_ECDSA_AUTHENTICATOR,
     ]
 }
 
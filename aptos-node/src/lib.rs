@@ -388,6 +388,7 @@ fn create_single_node_test_config(
     node_config
         .execution
         .processed_transactions_detailed_counters = false;
+    node_config.execution.conflict_analytics = false;
 
     node_config.peer_monitoring_service.max_concurrent_requests = 1;
     node_config
@@ -499,6 +500,7 @@ pub fn setup_environment_and_start_node(
         mut event_subscription_service,
         mempool_reconfig_subscription,
         consensus_reconfig_subscription,
+        api_commit_subscription,
     ) = state_sync::create_event_subscription_service(&node_config, &db_rw);
 
     // Set up the networks and gather the application network handles
@@ -534,7 +536,12 @@ pub fn setup_environment_and_start_node(
 
     // Bootstrap the API and indexer
     let (mempool_client_receiver, api_runtime, indexer_runtime, indexer_grpc_runtime) =
-        services::bootstrap_api_and_indexer(&node_config, aptos_db, chain_id)?;
+        services::bootstrap_api_and_indexer(
+            &node_config,
+            aptos_db,
+            chain_id,
+            api_commit_subscription,
+        )?;
 
     // Create mempool and get the consensus to mempool sender
     let (mempool_runtime, consensus_to_mempool_sender) =
@@ -4,9 +4,9 @@
 use crate::{bootstrap_api, indexer, mpsc::Receiver, network::ApplicationNetworkInterfaces};
 use aptos_build_info::build_information;
 use aptos_config::config::NodeConfig;
-use aptos_consensus::network_interface::ConsensusMsg;
+use aptos_consensus::{equivocation::RecentEquivocationEvidence, network_interface::ConsensusMsg};
 use aptos_consensus_notifications::ConsensusNotifier;
-use aptos_event_notifications::ReconfigNotificationListener;
+use aptos_event_notifications::{CommitNotificationListener, ReconfigNotificationListener};
 use aptos_indexer_grpc_fullnode::runtime::bootstrap as bootstrap_indexer_grpc;
 use aptos_logger::{debug, telemetry_log_writer::TelemetryLog, LoggerFilterUpdater};
 use aptos_mempool::{network::MempoolSyncMsg, MempoolClientRequest, QuorumStoreRequest};
@@ -33,6 +33,7 @@ pub fn bootstrap_api_and_indexer(
     node_config: &NodeConfig,
     aptos_db: Arc<dyn DbReader>,
     chain_id: ChainId,
+    api_commit_subscription: Option<CommitNotificationListener>,
 ) -> anyhow::Result<(
     Receiver<MempoolClientRequest>,
     Option<Runtime>,
@@ -50,6 +51,7 @@ pub fn bootstrap_api_and_indexer(
             chain_id,
             aptos_db.clone(),
             mempool_client_sender.clone(),
+            api_commit_subscription,
         )?)
     } else {
         None
@@ -134,7 +136,11 @@ pub fn start_node_inspection_service(
     node_config: &NodeConfig,
     peers_and_metadata: Arc<PeersAndMetadata>,
 ) {
-    aptos_inspection_service::start_inspection_service(node_config.clone(), peers_and_metadata)
+    aptos_inspection_service::start_inspection_service(
+        node_config.clone(),
+        peers_and_metadata,
+        Arc::new(RecentEquivocationEvidence),
+    )
 }
 
 /// Starts the peer monitoring service and returns the runtime
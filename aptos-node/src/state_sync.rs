@@ -9,7 +9,9 @@ use aptos_data_streaming_service::{
     streaming_client::{new_streaming_service_client_listener_pair, StreamingServiceClient},
     streaming_service::DataStreamingService,
 };
-use aptos_event_notifications::{EventSubscriptionService, ReconfigNotificationListener};
+use aptos_event_notifications::{
+    CommitNotificationListener, EventSubscriptionService, ReconfigNotificationListener,
+};
 use aptos_executor::chunk_executor::ChunkExecutor;
 use aptos_infallible::RwLock;
 use aptos_mempool_notifications::MempoolNotificationListener;
@@ -33,8 +35,9 @@ use aptos_vm::AptosVM;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
-/// Creates the event subscription service and two reconfiguration
-/// notification listeners (for mempool and consensus, respectively).
+/// Creates the event subscription service, two reconfiguration
+/// notification listeners (for mempool and consensus, respectively)
+/// and a commit notification listener (for the API).
 pub fn create_event_subscription_service(
     node_config: &NodeConfig,
     db_rw: &DbReaderWriter,
@@ -42,6 +45,7 @@ pub fn create_event_subscription_service(
     EventSubscriptionService,
     ReconfigNotificationListener,
     Option<ReconfigNotificationListener>,
+    Option<CommitNotificationListener>,
 ) {
     // Create the event subscription service
     let mut event_subscription_service = EventSubscriptionService::new(
@@ -65,10 +69,22 @@ pub fn create_event_subscription_service(
         None
     };
 
+    // Create a commit subscription for the API (if the API is enabled)
+    let api_commit_subscription = if node_config.api.enabled {
+        Some(
+            event_subscription_service
+                .subscribe_to_commits()
+                .expect("The API must subscribe to commits"),
+        )
+    } else {
+        None
+    };
+
     (
         event_subscription_service,
         mempool_reconfig_subscription,
         consensus_reconfig_subscription,
+        api_commit_subscription,
     )
 }
 
@@ -4,8 +4,9 @@
 
 use crate::{
     config::{
-        config_sanitizer::ConfigSanitizer, gas_estimation_config::GasEstimationConfig,
-        node_config_loader::NodeType, Error, NodeConfig,
+        api_rate_limit_config::ApiRateLimitConfig, config_sanitizer::ConfigSanitizer,
+        gas_estimation_config::GasEstimationConfig, node_config_loader::NodeType, Error,
+        NodeConfig,
     },
     utils,
 };
@@ -62,6 +63,13 @@ pub struct ApiConfig {
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of view functions that can be sent with the Batch view API
+    pub max_view_function_batch_size: usize,
+    /// Maximum gas used by all the view functions of a batch together
+    ///
+    /// Each view function is still limited by `max_gas_view_function`, and the view functions
+    /// after the budget is exhausted fail without being executed.
+    pub max_gas_view_function_batch: u64,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -72,6 +80,16 @@ pub struct ApiConfig {
     pub runtime_worker_multiplier: usize,
     /// Configs for computing unit gas price estimation
     pub gas_estimation: GasEstimationConfig,
+    /// Enables the streaming (server-sent events) APIs
+    #[serde(default = "default_enabled")]
+    pub streaming_enabled: bool,
+    /// Maximum number of concurrently open streams
+    pub max_streaming_subscriptions: usize,
+    /// Interval at which open streams check for new transactions when no
+    /// commit notification arrives (e.g., if the node doesn't provide them)
+    pub streaming_poll_interval_ms: u64,
+    /// Configs for rate limiting requests per client
+    pub rate_limit: ApiRateLimitConfig,
 }
 
 pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -81,6 +99,10 @@ pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 pub const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 pub const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+pub const DEFAULT_MAX_STREAMING_SUBSCRIPTIONS: usize = 100;
+pub const DEFAULT_STREAMING_POLL_INTERVAL_MS: u64 = 1000;
+pub const DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE: usize = 100;
+pub const DEFAULT_MAX_VIEW_BATCH_GAS: u64 = 10_000_000;
 pub const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
 
 fn default_enabled() -> bool {
@@ -113,9 +135,15 @@ impl Default for ApiConfig {
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_function_batch_size: DEFAULT_MAX_VIEW_FUNCTION_BATCH_SIZE,
+            max_gas_view_function_batch: DEFAULT_MAX_VIEW_BATCH_GAS,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
+            streaming_enabled: default_enabled(),
+            max_streaming_subscriptions: DEFAULT_MAX_STREAMING_SUBSCRIPTIONS,
+            streaming_poll_interval_ms: DEFAULT_STREAMING_POLL_INTERVAL_MS,
+            rate_limit: ApiRateLimitConfig::default(),
         }
     }
 }
@@ -163,7 +191,16 @@ impl ConfigSanitizer for ApiConfig {
             ));
         }
 
+        // Validate the streaming properties
+        if api_config.streaming_enabled && api_config.streaming_poll_interval_ms == 0 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "streaming_poll_interval_ms must be greater than 0!".into(),
+            ));
+        }
+
         GasEstimationConfig::sanitize(node_config, node_type, chain_id)?;
+        ApiRateLimitConfig::sanitize(node_config, node_type, chain_id)?;
 
         Ok(())
     }
@@ -227,4 +264,24 @@ mod tests {
             .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_invalid_streaming_poll_interval() {
+        // Create a node config with an invalid streaming poll interval
+        let mut node_config = NodeConfig {
+            api: ApiConfig {
+                enabled: true,
+                streaming_enabled: true,
+                streaming_poll_interval_ms: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails because
+        // the streaming poll interval is invalid.
+        let error = ApiConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+            .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
@@ -0,0 +1,186 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{
+    config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, NodeConfig,
+};
+use aptos_types::chain_id::ChainId;
+use serde::{Deserialize, Serialize};
+
+/// Rate limiting of the REST API. Clients presenting one of the configured API keys (as
+/// `Authorization: Bearer <key>`) get buckets of their own, all other clients share the
+/// buckets of their IP address.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiRateLimitConfig {
+    /// Enables rate limiting of API requests
+    pub enabled: bool,
+    /// Percentage of the bucket size new buckets start with
+    pub initial_bucket_fill_percentage: u8,
+    /// Take the client IP from the `X-Forwarded-For` header, for nodes behind a load balancer.
+    ///
+    /// Only enable if the load balancer sets the header, as clients can spoof it otherwise.
+    pub use_x_forwarded_for: bool,
+    /// Budgets of each IP address without an API key
+    pub ip_budgets: EndpointBudgets,
+    /// Budgets of each API key
+    pub api_key_budgets: EndpointBudgets,
+    /// Accepted API keys, requests with any other key are rejected
+    pub api_keys: Vec<String>,
+}
+
+impl Default for ApiRateLimitConfig {
+    fn default() -> ApiRateLimitConfig {
+        ApiRateLimitConfig {
+            enabled: false,
+            initial_bucket_fill_percentage: 100,
+            use_x_forwarded_for: false,
+            ip_budgets: EndpointBudgets::default(),
+            api_key_budgets: EndpointBudgets {
+                reads: RequestBudget::new(1000, 2000),
+                view: RequestBudget::new(200, 400),
+                simulate: RequestBudget::new(100, 200),
+                submit: RequestBudget::new(200, 400),
+            },
+            api_keys: vec![],
+        }
+    }
+}
+
+/// Request budgets of a client, per class of endpoints
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EndpointBudgets {
+    /// All the endpoints not listed below
+    pub reads: RequestBudget,
+    /// View functions
+    pub view: RequestBudget,
+    /// Transaction simulation
+    pub simulate: RequestBudget,
+    /// Transaction submission, single and batch
+    pub submit: RequestBudget,
+}
+
+impl Default for EndpointBudgets {
+    fn default() -> EndpointBudgets {
+        EndpointBudgets {
+            reads: RequestBudget::new(100, 200),
+            view: RequestBudget::new(20, 40),
+            simulate: RequestBudget::new(10, 20),
+            submit: RequestBudget::new(20, 40),
+        }
+    }
+}
+
+/// A token bucket of requests
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RequestBudget {
+    /// Requests per second the bucket is refilled with
+    pub requests_per_second: usize,
+    /// Maximum burst of requests
+    pub burst: usize,
+}
+
+impl RequestBudget {
+    pub fn new(requests_per_second: usize, burst: usize) -> Self {
+        Self {
+            requests_per_second,
+            burst,
+        }
+    }
+}
+
+impl ConfigSanitizer for ApiRateLimitConfig {
+    fn sanitize(
+        node_config: &mut NodeConfig,
+        _node_type: NodeType,
+        _chain_id: ChainId,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let rate_limit_config = &node_config.api.rate_limit;
+
+        // If rate limiting is disabled, we don't need to do anything
+        if !rate_limit_config.enabled {
+            return Ok(());
+        }
+
+        if rate_limit_config.initial_bucket_fill_percentage > 100 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "initial_bucket_fill_percentage {} must be <= 100",
+                    rate_limit_config.initial_bucket_fill_percentage
+                ),
+            ));
+        }
+
+        // The token buckets can't be empty, nor refill beyond their size
+        for budgets in [
+            &rate_limit_config.ip_budgets,
+            &rate_limit_config.api_key_budgets,
+        ] {
+            for budget in [
+                budgets.reads,
+                budgets.view,
+                budgets.simulate,
+                budgets.submit,
+            ] {
+                if budget.requests_per_second == 0 || budget.burst < budget.requests_per_second {
+                    return Err(Error::ConfigSanitizerFailed(
+                        sanitizer_name,
+                        format!(
+                            "requests_per_second {} must be > 0 and <= burst {}",
+                            budget.requests_per_second, budget.burst
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if rate_limit_config.api_keys.iter().any(|key| key.is_empty()) {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "API keys can't be empty".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiConfig;
+
+    #[test]
+    fn test_sanitize_empty_budget() {
+        // Create a node config with an empty view budget
+        let mut node_config = NodeConfig {
+            api: ApiConfig {
+                rate_limit: ApiRateLimitConfig {
+                    enabled: true,
+                    ip_budgets: EndpointBudgets {
+                        view: RequestBudget::new(0, 10),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error =
+            ApiRateLimitConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Disabling rate limiting makes the config valid
+        node_config.api.rate_limit.enabled = false;
+        ApiRateLimitConfig::sanitize(&mut node_config, NodeType::Validator, ChainId::testnet())
+            .unwrap();
+    }
+}
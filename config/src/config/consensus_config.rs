@@ -65,6 +65,10 @@ pub struct ConsensusConfig {
     // must match one of the CHAIN_HEALTH_WINDOW_SIZES values.
     pub window_for_chain_health: usize,
     pub chain_health_backoff: Vec<ChainHealthBackoffValues>,
+    // If set, the start of every epoch and every inbound consensus message and local timeout are
+    // appended to this file, so that the decisions of the node can be replayed offline with the
+    // consensus-replay tool. Meant for debugging, as the file grows without bound.
+    pub recording_path: Option<PathBuf>,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -205,6 +209,7 @@ impl Default for ConsensusConfig {
                     backoff_proposal_delay_ms: 300,
                 },
             ],
+            recording_path: None,
         }
     }
 }
@@ -3,6 +3,7 @@
 
 use crate::{config::SecureBackend, keys::ConfigKey};
 use aptos_crypto::{bls12381, ed25519::Ed25519PrivateKey, x25519};
+use aptos_secure_storage::{CryptoStorage, Storage};
 use aptos_types::account_address::{AccountAddress, AccountAddress as PeerId};
 use serde::{Deserialize, Serialize};
 use std::{
@@ -65,6 +66,27 @@ impl Identity {
     pub fn from_file(path: PathBuf) -> Self {
         Identity::FromFile(IdentityFromFile { path })
     }
+
+    /// Loads the x25519 private key of the identity, if there is one
+    pub fn private_key(&self) -> Option<x25519::PrivateKey> {
+        match self {
+            Identity::FromConfig(config) => Some(config.key.private_key()),
+            Identity::FromStorage(config) => {
+                let storage: Storage = (&config.backend).into();
+                let key = storage
+                    .export_private_key(&config.key_name)
+                    .expect("Unable to read key");
+                let key = x25519::PrivateKey::from_ed25519_private_bytes(&key.to_bytes())
+                    .expect("Unable to convert key");
+                Some(key)
+            },
+            Identity::FromFile(config) => {
+                let identity_blob: IdentityBlob = IdentityBlob::from_file(&config.path).unwrap();
+                Some(identity_blob.network_private_key)
+            },
+            Identity::None => None,
+        }
+    }
 }
 
 /// The identity is stored within the config.
@@ -4,6 +4,7 @@
 
 // All modules should be declared below
 mod api_config;
+mod api_rate_limit_config;
 mod base_config;
 mod config_optimizer;
 mod config_sanitizer;
@@ -31,6 +32,7 @@ mod utils;
 
 // All public usage statements should be declared below
 pub use api_config::*;
+pub use api_rate_limit_config::*;
 pub use base_config::*;
 pub use consensus_config::*;
 pub use error::*;
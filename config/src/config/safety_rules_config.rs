@@ -6,12 +6,12 @@
 use crate::config::persistable_config::PersistableConfig;
 use crate::{
     config::{
-        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, IdentityBlob,
-        LoggerConfig, NodeConfig, SecureBackend, WaypointConfig,
+        config_sanitizer::ConfigSanitizer, node_config_loader::NodeType, Error, Identity,
+        IdentityBlob, LoggerConfig, NodeConfig, SecureBackend, WaypointConfig,
     },
     keys::ConfigKey,
 };
-use aptos_crypto::{bls12381, Uniform};
+use aptos_crypto::{bls12381, x25519, Uniform};
 use aptos_types::{chain_id::ChainId, network_address::NetworkAddress, waypoint::Waypoint, PeerId};
 use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
@@ -161,7 +161,7 @@ impl InitialSafetyRulesConfig {
 }
 
 /// Defines how safety rules should be executed
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 pub enum SafetyRulesService {
     /// This runs safety rules in the same thread as event processor
@@ -182,10 +182,13 @@ impl SafetyRulesService {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RemoteService {
     pub server_address: NetworkAddress,
+    /// If set, connections to the service are authenticated and encrypted with Noise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub noise: Option<RemoteServiceNoiseConfig>,
 }
 
 impl RemoteService {
@@ -198,6 +201,23 @@ impl RemoteService {
     }
 }
 
+/// The x25519 keys authenticating both ends of a connection to a remote safety rules service.
+/// Consensus and the service each configure their own identity and the public key of the other.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RemoteServiceNoiseConfig {
+    pub identity: Identity,
+    pub peer_public_key: x25519::PublicKey,
+}
+
+impl RemoteServiceNoiseConfig {
+    pub fn identity_key(&self) -> x25519::PrivateKey {
+        self.identity
+            .private_key()
+            .expect("identity key should be present")
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct SafetyRulesTestConfig {
     pub author: PeerId,
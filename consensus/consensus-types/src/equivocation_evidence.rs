@@ -0,0 +1,223 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block::Block,
+    common::{Author, Round},
+    vote::Vote,
+};
+use anyhow::{ensure, format_err, Context};
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::validator_verifier::ValidatorVerifier;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Proof that a validator signed two conflicting messages for the same round, which an honest
+/// validator never does. The evidence only relies on the signatures of the equivocating
+/// validator, so it can be checked offline against the validator set of its epoch.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum EquivocationEvidence {
+    /// Two different blocks proposed by the same proposer for the same round.
+    Proposal { first: Block, second: Block },
+    /// Two votes of the same validator for different ledger infos in the same round.
+    Vote { first: Vote, second: Vote },
+}
+
+/// Provides the equivocation evidence observed by a node, e.g. to expose it outside of consensus.
+pub trait EquivocationEvidenceProvider: Send + Sync {
+    /// Returns the most recent evidence, oldest first.
+    fn recent_equivocation_evidence(&self) -> Vec<EquivocationEvidence>;
+}
+
+impl Display for EquivocationEvidence {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "[{} equivocation, author: {}, epoch: {}, round: {}]",
+            self.kind(),
+            self.author()
+                .map_or_else(|| "(NIL)".to_string(), |author| author.to_string()),
+            self.epoch(),
+            self.round()
+        )
+    }
+}
+
+impl EquivocationEvidence {
+    pub fn new_proposal(first: Block, second: Block) -> Self {
+        Self::Proposal { first, second }
+    }
+
+    pub fn new_vote(first: Vote, second: Vote) -> Self {
+        Self::Vote { first, second }
+    }
+
+    /// Returns "proposal" or "vote", e.g. for metric labels.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Proposal { .. } => "proposal",
+            Self::Vote { .. } => "vote",
+        }
+    }
+
+    /// Returns the validator that equivocated. Only unverified evidence can lack an author.
+    pub fn author(&self) -> Option<Author> {
+        match self {
+            Self::Proposal { first, .. } => first.author(),
+            Self::Vote { first, .. } => Some(first.author()),
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        match self {
+            Self::Proposal { first, .. } => first.epoch(),
+            Self::Vote { first, .. } => first.epoch(),
+        }
+    }
+
+    pub fn round(&self) -> Round {
+        match self {
+            Self::Proposal { first, .. } => first.round(),
+            Self::Vote { first, .. } => first.vote_data().proposed().round(),
+        }
+    }
+
+    /// Verifies that both messages are signed by the same validator of `validator`, and that
+    /// they conflict with each other for the same epoch and round.
+    pub fn verify(&self, validator: &ValidatorVerifier) -> anyhow::Result<()> {
+        match self {
+            Self::Proposal { first, second } => {
+                let author = first
+                    .author()
+                    .ok_or_else(|| format_err!("Equivocating block is not a proposal"))?;
+                ensure!(
+                    second.author() == Some(author),
+                    "Equivocating proposals have different authors"
+                );
+                ensure!(
+                    (first.epoch(), first.round()) == (second.epoch(), second.round()),
+                    "Equivocating proposals are for different rounds"
+                );
+                ensure!(
+                    first.id() != second.id(),
+                    "Equivocating proposals are the same block"
+                );
+                for block in [first, second] {
+                    let signature = block
+                        .signature()
+                        .ok_or_else(|| format_err!("Missing signature in proposal"))?;
+                    validator
+                        .verify(author, block.block_data(), signature)
+                        .context("Failed to verify equivocating proposal")?;
+                }
+            },
+            Self::Vote { first, second } => {
+                ensure!(
+                    first.author() == second.author(),
+                    "Equivocating votes have different authors"
+                );
+                ensure!(
+                    (first.epoch(), first.vote_data().proposed().round())
+                        == (second.epoch(), second.vote_data().proposed().round()),
+                    "Equivocating votes are for different rounds"
+                );
+                ensure!(
+                    first.ledger_info().hash() != second.ledger_info().hash(),
+                    "Equivocating votes are for the same ledger info"
+                );
+                for vote in [first, second] {
+                    ensure!(
+                        vote.ledger_info().consensus_data_hash() == vote.vote_data().hash(),
+                        "Vote's hash mismatch with LedgerInfo"
+                    );
+                    validator
+                        .verify(vote.author(), vote.ledger_info(), vote.signature())
+                        .context("Failed to verify equivocating vote")?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block::block_test_utils::certificate_for_genesis, common::Payload, vote_data::VoteData,
+    };
+    use aptos_crypto::HashValue;
+    use aptos_types::{
+        block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+        validator_verifier::random_validator_verifier,
+    };
+
+    #[test]
+    fn test_proposal_equivocation() {
+        let (signers, validators) = random_validator_verifier(2, None, false);
+        let propose = |timestamp_usecs, signer: &ValidatorSigner| {
+            Block::new_proposal(
+                Payload::empty(false),
+                1,
+                timestamp_usecs,
+                certificate_for_genesis(),
+                signer,
+                Vec::new(),
+            )
+            .unwrap()
+        };
+
+        let first = propose(1, &signers[0]);
+        let evidence = EquivocationEvidence::new_proposal(first.clone(), propose(2, &signers[0]));
+        evidence.verify(&validators).unwrap();
+        assert_eq!(evidence.author(), Some(signers[0].author()));
+
+        // The same proposal twice is not an equivocation.
+        EquivocationEvidence::new_proposal(first.clone(), first.clone())
+            .verify(&validators)
+            .unwrap_err();
+        // Neither are proposals of different validators.
+        EquivocationEvidence::new_proposal(first, propose(2, &signers[1]))
+            .verify(&validators)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_vote_equivocation() {
+        let (signers, validators) = random_validator_verifier(2, None, false);
+        let vote = |proposed_round, signer: &ValidatorSigner| {
+            let proposed = BlockInfo::new(
+                1,
+                proposed_round,
+                HashValue::random(),
+                HashValue::zero(),
+                0,
+                0,
+                None,
+            );
+            let vote_data = VoteData::new(proposed, BlockInfo::random(0));
+            Vote::new(
+                vote_data,
+                signer.author(),
+                LedgerInfo::new(BlockInfo::empty(), HashValue::zero()),
+                signer,
+            )
+            .unwrap()
+        };
+
+        let first = vote(1, &signers[0]);
+        let evidence = EquivocationEvidence::new_vote(first.clone(), vote(1, &signers[0]));
+        evidence.verify(&validators).unwrap();
+        assert_eq!(evidence.round(), 1);
+
+        EquivocationEvidence::new_vote(first.clone(), first.clone())
+            .verify(&validators)
+            .unwrap_err();
+        EquivocationEvidence::new_vote(first.clone(), vote(2, &signers[0]))
+            .verify(&validators)
+            .unwrap_err();
+        EquivocationEvidence::new_vote(first, vote(1, &signers[1]))
+            .verify(&validators)
+            .unwrap_err();
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
     persistent_safety_storage::PersistentSafetyStorage, serializer::SerializerService, SafetyRules,
     TSafetyRules,
 };
+use aptos_config::config::{Identity, RemoteServiceNoiseConfig};
 use aptos_consensus_types::{
     block::Block,
     common::{Payload, Round},
@@ -17,7 +18,10 @@ use aptos_consensus_types::{
     vote_data::VoteData,
     vote_proposal::VoteProposal,
 };
-use aptos_crypto::hash::{CryptoHash, TransactionAccumulatorHasher};
+use aptos_crypto::{
+    hash::{CryptoHash, TransactionAccumulatorHasher},
+    x25519, Uniform,
+};
 use aptos_secure_storage::{InMemoryStorage, Storage};
 use aptos_types::{
     aggregate_signature::{AggregateSignature, PartialSignatures},
@@ -32,6 +36,7 @@ use aptos_types::{
     validator_verifier::generate_validator_verifier,
     waypoint::Waypoint,
 };
+use rand::{rngs::StdRng, SeedableRng};
 
 pub type Proof = AccumulatorExtensionProof<TransactionAccumulatorHasher>;
 
@@ -242,6 +247,25 @@ pub fn test_storage(signer: &ValidatorSigner) -> PersistentSafetyStorage {
     )
 }
 
+/// Returns the Noise configs of a safety rules service and of its client, each expecting the
+/// key of the other.
+pub fn test_noise_configs(
+    signer: &ValidatorSigner,
+) -> (RemoteServiceNoiseConfig, RemoteServiceNoiseConfig) {
+    let mut rng = StdRng::from_seed([0u8; 32]);
+    let server_key = x25519::PrivateKey::generate(&mut rng);
+    let client_key = x25519::PrivateKey::generate(&mut rng);
+    let server_noise = RemoteServiceNoiseConfig {
+        peer_public_key: client_key.public_key(),
+        identity: Identity::from_config(server_key, signer.author()),
+    };
+    let client_noise = RemoteServiceNoiseConfig {
+        peer_public_key: server_noise.identity_key().public_key(),
+        identity: Identity::from_config(client_key, signer.author()),
+    };
+    (server_noise, client_noise)
+}
+
 /// Returns a safety rules instance for testing purposes.
 pub fn test_safety_rules() -> SafetyRules {
     let signer = ValidatorSigner::from_int(0);
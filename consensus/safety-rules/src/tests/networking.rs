@@ -18,3 +18,23 @@ fn test_reconnect() {
     let state1 = safety_rules_manager.client().consensus_state().unwrap();
     assert_eq!(state0, state1);
 }
+
+#[test]
+fn test_reconnect_with_noise() {
+    let signer = ValidatorSigner::from_int(0);
+    let storage = test_utils::test_storage(&signer);
+    let (server_noise, client_noise) = test_utils::test_noise_configs(&signer);
+    // test value for network timeout, in milliseconds.
+    let network_timeout = 5_000;
+    let safety_rules_manager = SafetyRulesManager::new_thread_with_noise(
+        storage,
+        network_timeout,
+        server_noise,
+        client_noise,
+    );
+
+    // Reconnecting runs a new handshake, which must not be mistaken for a replay of the last one
+    let state0 = safety_rules_manager.client().consensus_state().unwrap();
+    let state1 = safety_rules_manager.client().consensus_state().unwrap();
+    assert_eq!(state0, state1);
+}
@@ -10,6 +10,11 @@ fn test() {
     suite::run_test_suite(&safety_rules());
 }
 
+#[test]
+fn test_noise() {
+    suite::run_test_suite(&safety_rules_with_noise());
+}
+
 fn safety_rules() -> suite::Callback {
     Box::new(move || {
         let signer = ValidatorSigner::from_int(0);
@@ -21,3 +26,21 @@ fn safety_rules() -> suite::Callback {
         (safety_rules, signer)
     })
 }
+
+fn safety_rules_with_noise() -> suite::Callback {
+    Box::new(move || {
+        let signer = ValidatorSigner::from_int(0);
+        let storage = test_utils::test_storage(&signer);
+        let (server_noise, client_noise) = test_utils::test_noise_configs(&signer);
+        // Test value for network_timeout, in milliseconds.
+        let network_timeout = 5_000;
+        let safety_rules_manager = SafetyRulesManager::new_thread_with_noise(
+            storage,
+            network_timeout,
+            server_noise,
+            client_noise,
+        );
+        let safety_rules = safety_rules_manager.client();
+        (safety_rules, signer)
+    })
+}
@@ -12,7 +12,8 @@ use crate::{
     persistent_safety_storage::PersistentSafetyStorage,
     remote_service::{self, RemoteService},
 };
-use aptos_config::utils;
+use aptos_config::{config::RemoteServiceNoiseConfig, utils};
+use aptos_secure_net::NoiseKeys;
 use std::{
     net::{IpAddr, Ipv4Addr, SocketAddr},
     thread::{self, JoinHandle},
@@ -24,20 +25,45 @@ pub struct ThreadService {
     _child: JoinHandle<()>,
     server_addr: SocketAddr,
     network_timeout: u64,
+    client_noise: Option<RemoteServiceNoiseConfig>,
 }
 
 impl ThreadService {
     pub fn new(storage: PersistentSafetyStorage, timeout: u64) -> Self {
+        Self::spawn(storage, timeout, None, None)
+    }
+
+    /// Authenticates and encrypts the connections between the thread, identified by
+    /// `server_noise`, and its clients, identified by `client_noise`, with Noise.
+    pub fn new_with_noise(
+        storage: PersistentSafetyStorage,
+        timeout: u64,
+        server_noise: RemoteServiceNoiseConfig,
+        client_noise: RemoteServiceNoiseConfig,
+    ) -> Self {
+        Self::spawn(storage, timeout, Some(server_noise), Some(client_noise))
+    }
+
+    fn spawn(
+        storage: PersistentSafetyStorage,
+        timeout: u64,
+        server_noise: Option<RemoteServiceNoiseConfig>,
+        client_noise: Option<RemoteServiceNoiseConfig>,
+    ) -> Self {
         let listen_port = utils::get_available_port();
         let listen_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listen_port);
         let server_addr = listen_addr;
 
-        let child = thread::spawn(move || remote_service::execute(storage, listen_addr, timeout));
+        let noise_keys = server_noise.as_ref().map(remote_service::noise_keys);
+        let child = thread::spawn(move || {
+            remote_service::execute(storage, listen_addr, timeout, noise_keys)
+        });
 
         Self {
             _child: child,
             server_addr,
             network_timeout: timeout,
+            client_noise,
         }
     }
 }
@@ -50,4 +76,8 @@ impl RemoteService for ThreadService {
     fn network_timeout_ms(&self) -> u64 {
         self.network_timeout
     }
+
+    fn noise_keys(&self) -> Option<NoiseKeys> {
+        self.client_noise.as_ref().map(remote_service::noise_keys)
+    }
 }
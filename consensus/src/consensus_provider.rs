@@ -5,10 +5,12 @@
 use crate::{
     counters,
     epoch_manager::EpochManager,
+    equivocation::load_equivocation_evidence,
     network::NetworkTask,
     network_interface::{ConsensusMsg, ConsensusNetworkClient},
     persistent_liveness_storage::StorageWriteProxy,
     quorum_store::quorum_store_db::QuorumStoreDB,
+    recorder::ConsensusRecorder,
     state_computer::ExecutionProxy,
     txn_notifier::MempoolNotifier,
     util::time_service::ClockTimeService,
@@ -39,6 +41,7 @@ pub fn start_consensus(
 ) -> Runtime {
     let runtime = aptos_runtimes::spawn_named_runtime("consensus".into(), None);
     let storage = Arc::new(StorageWriteProxy::new(node_config, aptos_db.reader.clone()));
+    load_equivocation_evidence(storage.as_ref());
     let quorum_store_db = Arc::new(QuorumStoreDB::new(node_config.storage.dir()));
 
     let txn_notifier = Arc::new(MempoolNotifier::new(
@@ -54,6 +57,13 @@ pub fn start_consensus(
     ));
 
     let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
+    let recorder = node_config.consensus.recording_path.as_ref().map(|path| {
+        info!("Recording consensus inputs to {}", path.display());
+        Arc::new(
+            ConsensusRecorder::new(path, time_service.clone())
+                .expect("Failed to create the consensus recorder"),
+        )
+    });
 
     let (timeout_sender, timeout_receiver) =
         aptos_channels::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
@@ -73,9 +83,11 @@ pub fn start_consensus(
         quorum_store_db,
         reconfig_events,
         bounded_executor,
+        recorder.clone(),
     );
 
-    let (network_task, network_receiver) = NetworkTask::new(network_service_events, self_receiver);
+    let (network_task, network_receiver) =
+        NetworkTask::new(network_service_events, self_receiver, recorder);
 
     runtime.spawn(network_task.start());
     runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
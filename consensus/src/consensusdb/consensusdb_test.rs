@@ -3,8 +3,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
-use aptos_consensus_types::block::block_test_utils::certificate_for_genesis;
+use aptos_consensus_types::{
+    block::block_test_utils::certificate_for_genesis, vote::Vote, vote_data::VoteData,
+};
 use aptos_temppath::TempPath;
+use aptos_types::{
+    block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+};
 
 #[test]
 fn test_put_get() {
@@ -70,3 +75,35 @@ fn test_delete_block_and_qc() {
     assert_eq!(db.get_blocks().unwrap().len(), 0);
     assert_eq!(db.get_quorum_certificates().unwrap().len(), 0);
 }
+
+#[test]
+fn test_equivocation_evidence_pruning() {
+    let tmp_dir = TempPath::new();
+    let db = ConsensusDB::new(&tmp_dir);
+
+    let signer = ValidatorSigner::random(None);
+    let evidence = |round| {
+        let vote = |id| {
+            let proposed = BlockInfo::new(1, round, id, HashValue::zero(), 0, 0, None);
+            Vote::new(
+                VoteData::new(proposed, BlockInfo::random(1)),
+                signer.author(),
+                LedgerInfo::new(BlockInfo::empty(), HashValue::zero()),
+                &signer,
+            )
+            .unwrap()
+        };
+        EquivocationEvidence::new_vote(vote(HashValue::random()), vote(HashValue::random()))
+    };
+
+    // Saved out of order, the evidence of the earliest rounds is pruned first
+    let evidence = vec![evidence(3), evidence(1), evidence(2)];
+    for evidence in &evidence {
+        db.save_equivocation_evidence_with_limit(evidence, 2)
+            .unwrap();
+    }
+    assert_eq!(
+        db.get_equivocation_evidence().unwrap(),
+        vec![evidence[2].clone(), evidence[0].clone()]
+    );
+}
@@ -9,19 +9,26 @@ mod schema;
 use crate::{
     consensusdb::schema::{
         block::BlockSchema,
+        equivocation_evidence::{EquivocationEvidenceKey, EquivocationEvidenceSchema},
         quorum_certificate::QCSchema,
         single_entry::{SingleEntryKey, SingleEntrySchema},
     },
     error::DbError,
 };
 use anyhow::Result;
-use aptos_consensus_types::{block::Block, quorum_cert::QuorumCert};
+use aptos_consensus_types::{
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_schemadb::{Options, ReadOptions, SchemaBatch, DB, DEFAULT_COLUMN_FAMILY_NAME};
-use schema::{BLOCK_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
+use schema::{BLOCK_CF_NAME, EQUIVOCATION_EVIDENCE_CF_NAME, QC_CF_NAME, SINGLE_ENTRY_CF_NAME};
 use std::{collections::HashMap, iter::Iterator, path::Path, time::Instant};
 
+/// The maximum number of pieces of equivocation evidence persisted. Each validator can only
+/// cause one per round, which still adds up over time.
+const MAX_EQUIVOCATION_EVIDENCE: usize = 1_000;
+
 /// The name of the consensus db file
 pub const CONSENSUS_DB_NAME: &str = "consensus_db";
 
@@ -52,6 +59,7 @@ impl ConsensusDB {
             BLOCK_CF_NAME,
             QC_CF_NAME,
             SINGLE_ENTRY_CF_NAME,
+            EQUIVOCATION_EVIDENCE_CF_NAME,
         ];
 
         let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
@@ -140,6 +148,56 @@ impl ConsensusDB {
         self.commit(batch)
     }
 
+    /// Persists the evidence, replacing any previous evidence of the same kind of equivocation
+    /// by the same validator in the same round. Only the `MAX_EQUIVOCATION_EVIDENCE` most recent
+    /// pieces of evidence are kept.
+    pub fn save_equivocation_evidence(
+        &self,
+        evidence: &EquivocationEvidence,
+    ) -> Result<(), DbError> {
+        self.save_equivocation_evidence_with_limit(evidence, MAX_EQUIVOCATION_EVIDENCE)
+    }
+
+    fn save_equivocation_evidence_with_limit(
+        &self,
+        evidence: &EquivocationEvidence,
+        max_evidence: usize,
+    ) -> Result<(), DbError> {
+        let key = EquivocationEvidenceKey::new(evidence);
+        let batch = SchemaBatch::new();
+        batch.put::<EquivocationEvidenceSchema>(&key, evidence)?;
+        self.commit(batch)?;
+
+        // Keys are ordered by epoch and round, so the oldest evidence comes first
+        let mut iter = self
+            .db
+            .iter::<EquivocationEvidenceSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        let keys = iter
+            .map(|result| result.map(|(key, _)| key))
+            .collect::<Result<Vec<_>>>()?;
+        let num_pruned = keys.len().saturating_sub(max_evidence);
+        if num_pruned == 0 {
+            return Ok(());
+        }
+        let batch = SchemaBatch::new();
+        keys.iter()
+            .take(num_pruned)
+            .try_for_each(|key| batch.delete::<EquivocationEvidenceSchema>(key))?;
+        self.commit(batch)
+    }
+
+    /// Get all equivocation evidence, ordered by epoch and round.
+    pub fn get_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>, DbError> {
+        let mut iter = self
+            .db
+            .iter::<EquivocationEvidenceSchema>(ReadOptions::default())?;
+        iter.seek_to_first();
+        Ok(iter
+            .map(|result| result.map(|(_, evidence)| evidence))
+            .collect::<Result<Vec<_>>>()?)
+    }
+
     /// Write the whole schema batch including all data necessary to mutate the ledger
     /// state of some transaction by leveraging rocksdb atomicity support.
     fn commit(&self, batch: SchemaBatch) -> Result<(), DbError> {
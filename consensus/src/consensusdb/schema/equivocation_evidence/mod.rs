@@ -0,0 +1,97 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for evidence of equivocating validators.
+//!
+//! Serialized evidence identified by the epoch and round of the equivocation, the equivocating
+//! validator and the kind of the equivocated messages, so that at most one piece of evidence is
+//! kept per validator and round.
+//! ```text
+//! |<-------------key------------->|<---value--->|
+//! | epoch | round | author | kind |   evidence  |
+//! ```
+
+use super::{ensure_slice_len_eq, EQUIVOCATION_EVIDENCE_CF_NAME};
+use anyhow::{bail, Result};
+use aptos_consensus_types::{
+    common::{Author, Round},
+    equivocation_evidence::EquivocationEvidence,
+};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::mem::size_of;
+
+define_schema!(
+    EquivocationEvidenceSchema,
+    EquivocationEvidenceKey,
+    EquivocationEvidence,
+    EQUIVOCATION_EVIDENCE_CF_NAME
+);
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct EquivocationEvidenceKey {
+    pub epoch: u64,
+    pub round: Round,
+    pub author: Author,
+    pub is_vote: bool,
+}
+
+impl EquivocationEvidenceKey {
+    const ENCODED_LEN: usize = 2 * size_of::<u64>() + Author::LENGTH + size_of::<u8>();
+
+    pub fn new(evidence: &EquivocationEvidence) -> Self {
+        Self {
+            epoch: evidence.epoch(),
+            round: evidence.round(),
+            // Only unverified evidence lacks an author, which is never persisted.
+            author: evidence.author().unwrap_or(Author::ZERO),
+            is_vote: matches!(evidence, EquivocationEvidence::Vote { .. }),
+        }
+    }
+}
+
+impl KeyCodec<EquivocationEvidenceSchema> for EquivocationEvidenceKey {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let mut encoded = Vec::with_capacity(Self::ENCODED_LEN);
+        encoded.write_u64::<BigEndian>(self.epoch)?;
+        encoded.write_u64::<BigEndian>(self.round)?;
+        encoded.extend_from_slice(self.author.as_ref());
+        encoded.write_u8(self.is_vote as u8)?;
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        ensure_slice_len_eq(data, Self::ENCODED_LEN)?;
+        let (mut prefix, rest) = data.split_at(2 * size_of::<u64>());
+        let epoch = prefix.read_u64::<BigEndian>()?;
+        let round = prefix.read_u64::<BigEndian>()?;
+        let (author, mut kind) = rest.split_at(Author::LENGTH);
+        let is_vote = match kind.read_u8()? {
+            0 => false,
+            1 => true,
+            other => bail!("Unexpected equivocation kind {}.", other),
+        };
+        Ok(Self {
+            epoch,
+            round,
+            author: Author::try_from(author)?,
+            is_vote,
+        })
+    }
+}
+
+impl ValueCodec<EquivocationEvidenceSchema> for EquivocationEvidence {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        Ok(bcs::to_bytes(&self)?)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        Ok(bcs::from_bytes(data)?)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,37 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use aptos_consensus_types::{vote::Vote, vote_data::VoteData};
+use aptos_crypto::HashValue;
+use aptos_schemadb::{schema::fuzzing::assert_encode_decode, test_no_panic_decoding};
+use aptos_types::{
+    block_info::BlockInfo, ledger_info::LedgerInfo, validator_signer::ValidatorSigner,
+};
+
+#[test]
+fn test_encode_decode() {
+    let signer = ValidatorSigner::random(None);
+    let vote = |id| {
+        let proposed = BlockInfo::new(1, 2, id, HashValue::zero(), 0, 0, None);
+        Vote::new(
+            VoteData::new(proposed, BlockInfo::random(1)),
+            signer.author(),
+            LedgerInfo::new(BlockInfo::empty(), HashValue::zero()),
+            &signer,
+        )
+        .unwrap()
+    };
+    let evidence =
+        EquivocationEvidence::new_vote(vote(HashValue::random()), vote(HashValue::random()));
+    let key = EquivocationEvidenceKey::new(&evidence);
+    assert_eq!(key, EquivocationEvidenceKey {
+        epoch: 1,
+        round: 2,
+        author: signer.author(),
+        is_vote: true,
+    });
+    assert_encode_decode::<EquivocationEvidenceSchema>(&key, &evidence);
+}
+
+test_no_panic_decoding!(EquivocationEvidenceSchema);
@@ -102,6 +102,17 @@ pub static VOTE_NIL_COUNT: Lazy<IntCounter> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Count of the validators caught equivocating since last restart, by kind of equivocation
+/// (proposal or vote).
+pub static EQUIVOCATION_EVIDENCE_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_consensus_equivocation_evidence_count",
+        "Count of the validators caught equivocating since last restart, by kind of equivocation",
+        &["kind"]
+    )
+    .unwrap()
+});
+
 /// Total voting power of validators in validator set
 pub static TOTAL_VOTING_POWER: Lazy<Gauge> = Lazy::new(|| {
     register_gauge!(
@@ -215,6 +226,15 @@ pub static COMMITTED_VOTES_IN_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Average proposal latency when using the latency aware LeaderReputation as the ProposerElection
+pub static PROPOSAL_LATENCY_MS_IN_WINDOW: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_proposal_latency_ms_in_window",
+        "Average time from a committed proposal of this node to its QC in the current reputation window",
+    )
+    .unwrap()
+});
+
 /// The number of block events the LeaderReputation uses
 pub static LEADER_REPUTATION_ROUND_HISTORY_SIZE: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
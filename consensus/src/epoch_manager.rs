@@ -17,8 +17,8 @@ use crate::{
     liveness::{
         cached_proposer_election::CachedProposerElection,
         leader_reputation::{
-            extract_epoch_to_proposers, AptosDBBackend, LeaderReputation,
-            ProposerAndVoterHeuristic, ReputationHeuristic,
+            extract_epoch_to_proposers, AptosDBBackend, LatencyAwareHeuristic, LeaderReputation,
+            MetadataBackend, ProposerAndVoterHeuristic, ReputationHeuristic,
         },
         proposal_generator::{
             ChainHealthBackoffConfig, PipelineBackpressureConfig, ProposalGenerator,
@@ -43,9 +43,10 @@ use crate::{
         quorum_store_coordinator::CoordinatorCommand,
         quorum_store_db::QuorumStoreStorage,
     },
+    recorder::{ConsensusRecorder, RecordedRead, RecordedReads, RecordingMetadataBackend},
     recovery_manager::RecoveryManager,
     round_manager::{RoundManager, UnverifiedEvent, VerifiedEvent},
-    state_replication::StateComputer,
+    state_replication::{PayloadClient, StateComputer},
     transaction_deduper::create_transaction_deduper,
     transaction_shuffler::create_transaction_shuffler,
     util::time_service::TimeService,
@@ -140,6 +141,10 @@ pub struct EpochManager {
     bounded_executor: BoundedExecutor,
     // recovery_mode is set to true when the recovery manager is spawned
     recovery_mode: bool,
+    recorder: Option<Arc<ConsensusRecorder>>,
+    // set when replaying a recording, see `replay_from`
+    recorded_reads: Option<Arc<RecordedReads>>,
+    recorded_payload_client: Option<Arc<dyn PayloadClient>>,
 }
 
 impl EpochManager {
@@ -155,6 +160,7 @@ impl EpochManager {
         quorum_store_storage: Arc<dyn QuorumStoreStorage>,
         reconfig_events: ReconfigNotificationListener,
         bounded_executor: BoundedExecutor,
+        recorder: Option<Arc<ConsensusRecorder>>,
     ) -> Self {
         let author = node_config.validator_network.as_ref().unwrap().peer_id();
         let config = node_config.consensus.clone();
@@ -186,9 +192,25 @@ impl EpochManager {
             batch_retrieval_tx: None,
             bounded_executor,
             recovery_mode: false,
+            recorder,
+            recorded_reads: None,
+            recorded_payload_client: None,
         }
     }
 
+    /// Replays a recording: the data the recorded node read from its storage and peers is read
+    /// from `reads`, and the payloads of the proposals are pulled from `payload_client` rather
+    /// than from the quorum store.
+    #[cfg(any(test, feature = "replay"))]
+    pub(crate) fn replay_from(
+        &mut self,
+        reads: Arc<RecordedReads>,
+        payload_client: Arc<dyn PayloadClient>,
+    ) {
+        self.recorded_reads = Some(reads);
+        self.recorded_payload_client = Some(payload_client);
+    }
+
     fn epoch_state(&self) -> &EpochState {
         self.epoch_state
             .as_ref()
@@ -262,17 +284,54 @@ impl EpochManager {
                             proposer_and_voter_config.use_history_from_previous_epoch_max_count,
                         )
                     },
+                    LeaderReputationType::LatencyAware(latency_aware_config) => {
+                        let proposer_and_voter_config =
+                            &latency_aware_config.proposer_and_voter_config;
+                        let proposer_window_size = proposers.len()
+                            * proposer_and_voter_config.proposer_window_num_validators_multiplier;
+                        let voter_window_size = proposers.len()
+                            * proposer_and_voter_config.voter_window_num_validators_multiplier;
+                        let heuristic: Box<dyn ReputationHeuristic> =
+                            Box::new(LatencyAwareHeuristic::new(
+                                self.author,
+                                proposer_and_voter_config.active_weight,
+                                proposer_and_voter_config.inactive_weight,
+                                proposer_and_voter_config.failed_weight,
+                                latency_aware_config.slow_weight,
+                                proposer_and_voter_config.failure_threshold_percent,
+                                latency_aware_config.proposal_latency_threshold_ms,
+                                latency_aware_config.vote_inclusion_threshold_percent,
+                                voter_window_size,
+                                proposer_window_size,
+                                leader_reputation_type.use_reputation_window_from_stale_end(),
+                            ));
+                        (
+                            heuristic,
+                            std::cmp::max(proposer_window_size, voter_window_size),
+                            proposer_and_voter_config.weight_by_voting_power,
+                            proposer_and_voter_config.use_history_from_previous_epoch_max_count,
+                        )
+                    },
                 };
 
                 let seek_len = onchain_config.leader_reputation_exclude_round() as usize
                     + onchain_config.max_failed_authors_to_store()
                     + PROPSER_ROUND_BEHIND_STORAGE_BUFFER;
 
-                let backend = Box::new(AptosDBBackend::new(
-                    window_size,
-                    seek_len,
-                    self.storage.aptos_db(),
-                ));
+                let backend: Box<dyn MetadataBackend> = match &self.recorded_reads {
+                    Some(reads) => reads.metadata_backend(),
+                    None => Box::new(AptosDBBackend::new(
+                        window_size,
+                        seek_len,
+                        self.storage.aptos_db(),
+                    )),
+                };
+                let backend: Box<dyn MetadataBackend> = match &self.recorder {
+                    Some(recorder) => {
+                        Box::new(RecordingMetadataBackend::new(backend, recorder.clone()))
+                    },
+                    None => backend,
+                };
                 let voting_powers: Vec<_> = if weight_by_voting_power {
                     proposers
                         .iter()
@@ -293,7 +352,17 @@ impl EpochManager {
                         .saturating_sub(use_history_from_previous_epoch_max_count as u64),
                 );
                 // If we are considering beyond the current epoch, we need to fetch validators for those epochs
-                let epoch_to_proposers = if epoch_state.epoch > first_epoch_to_consider {
+                let epoch_to_proposers = if let Some(reads) = &self.recorded_reads {
+                    reads
+                        .epoch_to_proposers(epoch_state.epoch)
+                        .unwrap_or_else(|| {
+                            warn!(
+                                "No proposers across epochs recorded for epoch {}",
+                                epoch_state.epoch
+                            );
+                            HashMap::from([(epoch_state.epoch, proposers)])
+                        })
+                } else if epoch_state.epoch > first_epoch_to_consider {
                     self.storage
                         .aptos_db()
                         .get_epoch_ending_ledger_infos(first_epoch_to_consider - 1, epoch_state.epoch)
@@ -309,6 +378,13 @@ impl EpochManager {
                     HashMap::from([(epoch_state.epoch, proposers)])
                 };
 
+                if let Some(recorder) = &self.recorder {
+                    recorder.record_read(RecordedRead::EpochToProposers {
+                        epoch: epoch_state.epoch,
+                        epoch_to_proposers: epoch_to_proposers.clone(),
+                    });
+                }
+
                 info!(
                     "Starting epoch {}: proposers across epochs for leader election: {:?}",
                     epoch_state.epoch,
@@ -641,7 +717,8 @@ impl EpochManager {
             self.network_sender.clone(),
             self.self_sender.clone(),
             epoch_state.verifier.clone(),
-        );
+        )
+        .with_recorder(self.recorder.clone());
         let chain_health_backoff_config =
             ChainHealthBackoffConfig::new(self.config.chain_health_backoff.clone());
         let pipeline_backpressure_config =
@@ -686,12 +763,15 @@ impl EpochManager {
             create_transaction_deduper(onchain_execution_config.transaction_deduper_type());
         self.quorum_store_msg_tx = quorum_store_msg_tx;
 
-        let payload_client = QuorumStoreClient::new(
-            consensus_to_quorum_store_tx,
-            self.config.quorum_store_pull_timeout_ms,
-            self.config.wait_for_full_blocks_above_recent_fill_threshold,
-            self.config.wait_for_full_blocks_above_pending_blocks,
-        );
+        let payload_client: Arc<dyn PayloadClient> = match &self.recorded_payload_client {
+            Some(payload_client) => payload_client.clone(),
+            None => Arc::new(QuorumStoreClient::new(
+                consensus_to_quorum_store_tx,
+                self.config.quorum_store_pull_timeout_ms,
+                self.config.wait_for_full_blocks_above_recent_fill_threshold,
+                self.config.wait_for_full_blocks_above_pending_blocks,
+            )),
+        };
         self.commit_state_computer.new_epoch(
             &epoch_state,
             payload_manager.clone(),
@@ -732,7 +812,7 @@ impl EpochManager {
         let proposal_generator = ProposalGenerator::new(
             self.author,
             block_store.clone(),
-            Arc::new(payload_client),
+            payload_client,
             self.time_service.clone(),
             Duration::from_millis(self.config.quorum_store_poll_time_ms),
             self.config
@@ -809,6 +889,19 @@ impl EpochManager {
 
         self.epoch_state = Some(Arc::new(epoch_state.clone()));
 
+        if let Some(recorder) = &self.recorder {
+            recorder.record_header(
+                self.author,
+                epoch_state.epoch,
+                validator_set,
+                self.storage.recover_from_ledger().storage_ledger().clone(),
+                onchain_consensus_config
+                    .as_ref()
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+        }
+
         match self.storage.start() {
             LivenessStorageData::FullRecoveryData(initial_data) => {
                 let consensus_config = onchain_consensus_config.unwrap_or_default();
@@ -1120,6 +1213,9 @@ impl EpochManager {
                     });
                 },
                 round = round_timeout_sender_rx.select_next_some() => {
+                    if let Some(recorder) = &self.recorder {
+                        recorder.record_local_timeout(round);
+                    }
                     monitor!("epoch_manager_process_round_timeout",
                     self.process_local_timeout(round));
                 },
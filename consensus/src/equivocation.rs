@@ -0,0 +1,72 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Keeps track of the evidence of the equivocating validators observed by this node.
+
+use crate::{counters, persistent_liveness_storage::PersistentLivenessStorage};
+use aptos_consensus_types::equivocation_evidence::{
+    EquivocationEvidence, EquivocationEvidenceProvider,
+};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+
+/// Maximum number of evidence kept in memory for the inspection service.
+const MAX_RECENT_EVIDENCE: usize = 100;
+
+static RECENT_EVIDENCE: Lazy<Mutex<VecDeque<EquivocationEvidence>>> =
+    Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Provides the most recent equivocation evidence observed by this node, including the evidence
+/// persisted before the last restart.
+pub struct RecentEquivocationEvidence;
+
+impl EquivocationEvidenceProvider for RecentEquivocationEvidence {
+    fn recent_equivocation_evidence(&self) -> Vec<EquivocationEvidence> {
+        RECENT_EVIDENCE.lock().iter().cloned().collect()
+    }
+}
+
+fn push_recent_evidence(evidence: EquivocationEvidence) {
+    let mut recent_evidence = RECENT_EVIDENCE.lock();
+    if recent_evidence.len() == MAX_RECENT_EVIDENCE {
+        recent_evidence.pop_front();
+    }
+    recent_evidence.push_back(evidence);
+}
+
+/// Loads the evidence persisted by previous runs, so that it can be inspected after a restart.
+pub(crate) fn load_equivocation_evidence(storage: &dyn PersistentLivenessStorage) {
+    match storage.retrieve_equivocation_evidence() {
+        Ok(persisted_evidence) => {
+            let skip = persisted_evidence.len().saturating_sub(MAX_RECENT_EVIDENCE);
+            persisted_evidence
+                .into_iter()
+                .skip(skip)
+                .for_each(push_recent_evidence);
+        },
+        Err(e) => warn!(error = ?e, "Failed to retrieve the equivocation evidence"),
+    }
+}
+
+/// Records the evidence of a validator equivocating. The evidence must have been verified,
+/// and callers must only record one piece of evidence per validator and round, so that an
+/// equivocating validator can't make us write arbitrarily many times to storage.
+pub(crate) fn record_equivocation(
+    storage: &dyn PersistentLivenessStorage,
+    evidence: EquivocationEvidence,
+) {
+    counters::EQUIVOCATION_EVIDENCE_COUNT
+        .with_label_values(&[evidence.kind()])
+        .inc();
+    error!(
+        SecurityEvent::ConsensusEquivocationEvidence,
+        evidence = %evidence,
+        "Collected equivocation evidence"
+    );
+    if let Err(e) = storage.save_equivocation_evidence(&evidence) {
+        warn!(error = ?e, "Failed to persist equivocation evidence {}", evidence);
+    }
+    push_recent_evidence(evidence);
+}
@@ -45,9 +45,16 @@ mod util;
 pub mod consensus_provider;
 /// Required by the telemetry service
 pub mod counters;
+/// Provides the equivocation evidence to the inspection service
+pub mod equivocation;
 /// AptosNet interface.
 pub mod network_interface;
 mod payload_manager;
+/// Recording of the consensus inputs, see `ConsensusConfig::recording_path`.
+pub mod recorder;
+/// Replay of the recorded consensus inputs, see the `consensus-replay` tool.
+#[cfg(any(test, feature = "replay"))]
+pub mod replay;
 mod sender_aware_shuffler;
 mod transaction_deduper;
 mod transaction_shuffler;
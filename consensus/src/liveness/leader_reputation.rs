@@ -9,7 +9,7 @@ use crate::{
         CHAIN_HEALTH_TOTAL_NUM_VALIDATORS, CHAIN_HEALTH_TOTAL_VOTING_POWER,
         CHAIN_HEALTH_WINDOW_SIZES, COMMITTED_PROPOSALS_IN_WINDOW, COMMITTED_VOTES_IN_WINDOW,
         CONSENSUS_PARTICIPATION_STATUS, FAILED_PROPOSALS_IN_WINDOW,
-        LEADER_REPUTATION_ROUND_HISTORY_SIZE,
+        LEADER_REPUTATION_ROUND_HISTORY_SIZE, PROPOSAL_LATENCY_MS_IN_WINDOW,
     },
     liveness::proposer_election::{choose_index, ProposerElection},
 };
@@ -457,6 +457,61 @@ impl NewBlockEventAggregation {
             map
         })
     }
+
+    /// Counts, for each candidate, the blocks in the voter window it could have voted for,
+    /// i.e. the blocks of the epochs in which it was a candidate.
+    pub fn count_vote_opportunities(
+        &self,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        history: &[NewBlockEvent],
+    ) -> HashMap<Author, u32> {
+        Self::history_iter(
+            history,
+            epoch_to_candidates,
+            self.voter_window_size,
+            self.reputation_window_from_stale_end,
+        )
+        .fold(HashMap::new(), |mut map, meta| {
+            for candidate in &epoch_to_candidates[&meta.epoch()] {
+                let count = map.entry(*candidate).or_insert(0);
+                *count += 1;
+            }
+            map
+        })
+    }
+
+    /// Returns, for each proposer in the proposer window, the average time (in microseconds)
+    /// from its proposals to the block of the next round, which carries the QC of the proposal.
+    /// Proposals that are not directly followed by a block of the next round are skipped, as
+    /// the time would include the failed rounds in between.
+    pub fn average_proposal_latencies(
+        &self,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        history: &[NewBlockEvent],
+    ) -> HashMap<Author, u64> {
+        let window: Vec<_> = Self::history_iter(
+            history,
+            epoch_to_candidates,
+            self.proposer_window_size,
+            self.reputation_window_from_stale_end,
+        )
+        .collect();
+
+        // History is ordered from the most recent block, so each block follows the next one.
+        let mut latencies: HashMap<Author, (u64, u64)> = HashMap::new();
+        for pair in window.windows(2) {
+            let (next, meta) = (pair[0], pair[1]);
+            if (next.epoch(), next.round()) == (meta.epoch(), meta.round() + 1) {
+                let (total, count) = latencies.entry(meta.proposer()).or_insert((0, 0));
+                *total += next.proposed_time().saturating_sub(meta.proposed_time());
+                *count += 1;
+            }
+        }
+        latencies
+            .into_iter()
+            .map(|(author, (total, count))| (author, total / count))
+            .collect()
+    }
 }
 
 /// Heuristic that looks at successful and failed proposals, as well as voting history,
@@ -550,6 +605,117 @@ impl ReputationHeuristic for ProposerAndVoterHeuristic {
     }
 }
 
+/// Heuristic that extends `ProposerAndVoterHeuristic` with the latency of the validators, so
+/// that validators that are alive but slow get fewer leader rounds, as each of their rounds takes
+/// longer.
+///
+/// Logic is the same as for `ProposerAndVoterHeuristic`, except that active nodes use
+/// slow_weight instead of active_weight if either:
+///  * the average time from their proposals to the block of the next round, which carries the QC
+///    of the proposal, is strictly above the proposal latency threshold.
+///  * the percentage of blocks in the voter window whose QC includes their vote is strictly below
+///    the vote inclusion threshold. QCs only aggregate the first votes to reach the quorum, so
+///    votes of slow nodes are mostly left out.
+///
+/// Both only rely on the timestamps and vote bitvecs of committed NewBlockEvents, so all nodes
+/// compute the same weights.
+pub struct LatencyAwareHeuristic {
+    author: Author,
+    active_weight: u64,
+    inactive_weight: u64,
+    failed_weight: u64,
+    slow_weight: u64,
+    failure_threshold_percent: u32,
+    proposal_latency_threshold_ms: u64,
+    vote_inclusion_threshold_percent: u32,
+    aggregation: NewBlockEventAggregation,
+}
+
+impl LatencyAwareHeuristic {
+    pub fn new(
+        author: Author,
+        active_weight: u64,
+        inactive_weight: u64,
+        failed_weight: u64,
+        slow_weight: u64,
+        failure_threshold_percent: u32,
+        proposal_latency_threshold_ms: u64,
+        vote_inclusion_threshold_percent: u32,
+        voter_window_size: usize,
+        proposer_window_size: usize,
+        reputation_window_from_stale_end: bool,
+    ) -> Self {
+        Self {
+            author,
+            active_weight,
+            inactive_weight,
+            failed_weight,
+            slow_weight,
+            failure_threshold_percent,
+            proposal_latency_threshold_ms,
+            vote_inclusion_threshold_percent,
+            aggregation: NewBlockEventAggregation::new(
+                voter_window_size,
+                proposer_window_size,
+                reputation_window_from_stale_end,
+            ),
+        }
+    }
+}
+
+impl ReputationHeuristic for LatencyAwareHeuristic {
+    fn get_weights(
+        &self,
+        epoch: u64,
+        epoch_to_candidates: &HashMap<u64, Vec<Author>>,
+        history: &[NewBlockEvent],
+    ) -> Vec<u64> {
+        assert!(epoch_to_candidates.contains_key(&epoch));
+
+        let (votes, proposals, failed_proposals) =
+            self.aggregation
+                .get_aggregated_metrics(epoch_to_candidates, history, &self.author);
+        let vote_opportunities = self
+            .aggregation
+            .count_vote_opportunities(epoch_to_candidates, history);
+        let proposal_latencies = self
+            .aggregation
+            .average_proposal_latencies(epoch_to_candidates, history);
+        PROPOSAL_LATENCY_MS_IN_WINDOW
+            .set((*proposal_latencies.get(&self.author).unwrap_or(&0) / 1000) as i64);
+
+        epoch_to_candidates[&epoch]
+            .iter()
+            .map(|author| {
+                let cur_votes = *votes.get(author).unwrap_or(&0);
+                let cur_proposals = *proposals.get(author).unwrap_or(&0);
+                let cur_failed_proposals = *failed_proposals.get(author).unwrap_or(&0);
+                let cur_vote_opportunities = *vote_opportunities.get(author).unwrap_or(&0);
+                // The threshold is configured on-chain, so can be large enough to overflow.
+                let is_slow_proposer = proposal_latencies.get(author).map_or(false, |latency| {
+                    *latency > self.proposal_latency_threshold_ms.saturating_mul(1000)
+                });
+                let is_slow_voter = cur_votes * 100
+                    < cur_vote_opportunities * self.vote_inclusion_threshold_percent;
+
+                if cur_failed_proposals * 100
+                    > (cur_proposals + cur_failed_proposals) * self.failure_threshold_percent
+                {
+                    self.failed_weight
+                } else if cur_proposals > 0 || cur_votes > 0 {
+                    if is_slow_proposer || is_slow_voter {
+                        self.slow_weight
+                    } else {
+                        self.active_weight
+                    }
+                } else {
+                    self.inactive_weight
+                }
+            })
+            .collect()
+    }
+}
+
 /// Committed history based proposer election implementation that could help bias towards
 /// successful leaders to help improve performance.
 pub struct LeaderReputation {
@@ -3,7 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::leader_reputation::{
-    extract_epoch_to_proposers_impl, AptosDBBackend, ProposerAndVoterHeuristic,
+    extract_epoch_to_proposers_impl, AptosDBBackend, LatencyAwareHeuristic,
+    ProposerAndVoterHeuristic,
 };
 use crate::liveness::{
     leader_reputation::{
@@ -240,6 +241,47 @@ fn test_aggregation_counting() {
     );
 }
 
+/// Rounds 1 to 3 follow each other, round 4 fails, and rounds 5 to 7 follow each other.
+fn add_latency_example(aptos_db: &MockDbReader, validators: &[Author]) {
+    aptos_db.add_event_with_timestamp(validators[0], vec![], vec![], 1_000);
+    aptos_db.add_event_with_timestamp(validators[1], vec![0, 1, 2], vec![], 3_000);
+    aptos_db.add_event_with_timestamp(validators[0], vec![0, 1, 2], vec![], 4_000);
+    aptos_db.add_event_with_timestamp(validators[2], vec![1, 2, 3], vec![3], 10_000);
+    aptos_db.add_event_with_timestamp(validators[0], vec![1, 2, 3], vec![], 10_500);
+    aptos_db.add_event_with_timestamp(validators[1], vec![0, 1, 2], vec![], 11_000);
+}
+
+#[test]
+fn test_aggregation_latencies() {
+    let validators: Vec<_> = (0..4).map(|_| Author::random()).collect();
+    let epoch_to_validators = HashMap::from([(0u64, validators.clone())]);
+    let aptos_db = Arc::new(MockDbReader::new());
+    let backend = AptosDBBackend::new(10, 0, aptos_db.clone());
+    let aggregation = NewBlockEventAggregation::new(3, 10, false);
+
+    add_latency_example(&aptos_db, &validators);
+    let history = backend.get_block_metadata(0, 7).0;
+
+    // round 3 is followed by the failed round 4, so isn't counted.
+    assert_eq!(
+        aggregation.average_proposal_latencies(&epoch_to_validators, &history),
+        HashMap::from([
+            (validators[0], 1_250),
+            (validators[1], 1_000),
+            (validators[2], 500),
+        ])
+    );
+    assert_eq!(
+        aggregation.count_vote_opportunities(&epoch_to_validators, &history),
+        HashMap::from([
+            (validators[0], 3),
+            (validators[1], 3),
+            (validators[2], 3),
+            (validators[3], 3),
+        ])
+    );
+}
+
 /// ####
 
 #[test]
@@ -278,6 +320,63 @@ fn test_proposer_and_voter_heuristic() {
     );
 }
 
+#[test]
+fn test_latency_aware_heuristic() {
+    let validators: Vec<_> = (0..4).map(|_| Author::random()).collect();
+    let epoch_to_validators = HashMap::from([(0u64, validators.clone())]);
+    let aptos_db = Arc::new(MockDbReader::new());
+    let backend = AptosDBBackend::new(10, 0, aptos_db.clone());
+
+    add_latency_example(&aptos_db, &validators);
+    let history = backend.get_block_metadata(0, 7).0;
+
+    let heuristic = ProposerAndVoterHeuristic::new(validators[0], 100, 10, 1, 49, 3, 10, false);
+    assert_eq!(
+        heuristic.get_weights(0, &epoch_to_validators, &history),
+        vec![100, 100, 100, 1]
+    );
+
+    // validators[0] is slow to get its proposals certified (1.25ms on average), and only has its
+    // vote included in 1 of the last 3 QCs.
+    let heuristic =
+        LatencyAwareHeuristic::new(validators[0], 100, 10, 1, 5, 49, 1, 50, 3, 10, false);
+    assert_eq!(
+        heuristic.get_weights(0, &epoch_to_validators, &history),
+        vec![5, 100, 100, 1]
+    );
+
+    // Only slow to get its proposals certified.
+    let heuristic =
+        LatencyAwareHeuristic::new(validators[0], 100, 10, 1, 5, 49, 1, 30, 3, 10, false);
+    assert_eq!(
+        heuristic.get_weights(0, &epoch_to_validators, &history),
+        vec![5, 100, 100, 1]
+    );
+
+    // Only slow to vote.
+    let heuristic =
+        LatencyAwareHeuristic::new(validators[0], 100, 10, 1, 5, 49, 2, 50, 3, 10, false);
+    assert_eq!(
+        heuristic.get_weights(0, &epoch_to_validators, &history),
+        vec![5, 100, 100, 1]
+    );
+
+    let heuristic =
+        LatencyAwareHeuristic::new(validators[0], 100, 10, 1, 5, 49, 2, 30, 3, 10, false);
+    assert_eq!(
+        heuristic.get_weights(0, &epoch_to_validators, &history),
+        vec![100, 100, 100, 1]
+    );
+
+    // A threshold too large to be converted to microseconds never makes a proposer slow.
+    let heuristic =
+        LatencyAwareHeuristic::new(validators[0], 100, 10, 1, 5, 49, u64::MAX, 30, 3, 10, false);
+    assert_eq!(
+        heuristic.get_weights(0, &epoch_to_validators, &history),
+        vec![100, 100, 100, 1]
+    );
+}
+
 /// #### LeaderReputation test ####
 
 #[test]
@@ -390,6 +489,113 @@ fn test_api(use_root_hash: bool) {
     }
 }
 
+/// Simulates `num_rounds` rounds over 8 validators with equal voting power, where validators 0
+/// and 1 are slow to propose, and validators 6 and 7 are slow to vote. QCs only include the first
+/// 6 votes, so votes of 6 and 7 are never included.
+/// Returns how many rounds each validator led, and the total duration of the rounds.
+fn simulate_slow_validators(
+    heuristic: Box<dyn ReputationHeuristic>,
+    proposers: &[Author],
+) -> (Vec<u32>, u64) {
+    let num_rounds = 1000;
+    let propose_delay_usecs = |i: usize| if i < 2 { 2_000_000 } else { 200_000 };
+    let vote_delay_usecs = |i: usize| if i >= 6 { 500_000 } else { 100_000 };
+    let quorum = 6;
+
+    let aptos_db = Arc::new(MockDbReader::new());
+    aptos_db.new_epoch();
+    let leader_reputation = LeaderReputation::new(
+        1,
+        HashMap::from([(1, proposers.to_vec())]),
+        vec![1; proposers.len()],
+        Box::new(AptosDBBackend::new(80, 10, aptos_db.clone())),
+        heuristic,
+        1,
+        false,
+        30,
+    );
+
+    let mut led = vec![0; proposers.len()];
+    let mut now_usecs = 1_000_000;
+    let mut qc_votes = vec![];
+    for round in 1..=num_rounds {
+        let leader = leader_reputation.get_valid_proposer(round);
+        let leader_idx = proposers.iter().position(|p| *p == leader).unwrap();
+        led[leader_idx] += 1;
+        // The block carries the QC of the previous round.
+        assert_eq!(
+            (1, round),
+            aptos_db.add_event_with_timestamp(leader, qc_votes, vec![], now_usecs)
+        );
+
+        let mut voters: Vec<usize> = (0..proposers.len()).collect();
+        voters.sort_by_key(|i| (vote_delay_usecs(*i), *i));
+        voters.truncate(quorum);
+        qc_votes = voters.iter().map(|i| *i as u16).collect();
+        now_usecs += propose_delay_usecs(leader_idx) + vote_delay_usecs(voters[quorum - 1]);
+    }
+    (led, now_usecs - 1_000_000)
+}
+
+#[test]
+fn test_latency_aware_simulation() {
+    let proposers: Vec<Author> = (0..8).map(|_| Author::random()).collect();
+    let slow = [0, 1, 6, 7];
+
+    let (baseline_led, baseline_duration) = simulate_slow_validators(
+        Box::new(ProposerAndVoterHeuristic::new(
+            proposers[0],
+            1000,
+            10,
+            1,
+            10,
+            8,
+            80,
+            false,
+        )),
+        &proposers,
+    );
+    let (led, duration) = simulate_slow_validators(
+        Box::new(LatencyAwareHeuristic::new(
+            proposers[0],
+            1000,
+            10,
+            1,
+            10,
+            10,
+            1000,
+            50,
+            8,
+            80,
+            false,
+        )),
+        &proposers,
+    );
+
+    let slow_rounds = |led: &[u32]| slow.iter().map(|i| led[*i]).sum::<u32>();
+    // All validators are active, so slow ones lead about half of the rounds without latency
+    // awareness.
+    assert!(
+        slow_rounds(&baseline_led) > 400,
+        "baseline: {:?}",
+        baseline_led
+    );
+    // Slow proposers still lead a round once their latency leaves the proposer window, which
+    // is what allows them to recover.
+    assert!(
+        slow_rounds(&led) * 5 < slow_rounds(&baseline_led),
+        "latency aware: {:?}, baseline: {:?}",
+        led,
+        baseline_led
+    );
+    assert!(
+        duration * 3 < baseline_duration * 2,
+        "latency aware: {}, baseline: {}",
+        duration,
+        baseline_duration
+    );
+}
+
 struct MockDbReader {
     events: Mutex<Vec<EventWithVersion>>,
     random_address: Author,
@@ -426,6 +632,18 @@ impl MockDbReader {
         proposer: Author,
         votes: Vec<u16>,
         failed_proposers: Vec<u64>,
+    ) -> (u64, u64) {
+        let timestamp = *self.last_timestamp.lock();
+        *self.last_timestamp.lock() += 100;
+        self.add_event_with_timestamp(proposer, votes, failed_proposers, timestamp)
+    }
+
+    pub fn add_event_with_timestamp(
+        &self,
+        proposer: Author,
+        votes: Vec<u16>,
+        failed_proposers: Vec<u64>,
+        timestamp: u64,
     ) -> (u64, u64) {
         let mut idx = self.idx.lock();
         *idx += 1;
@@ -454,12 +672,11 @@ impl MockDbReader {
                     votes_bitvec.into(),
                     proposer,
                     failed_proposers,
-                    *self.last_timestamp.lock(),
+                    timestamp,
                 ))
                 .unwrap(),
             ),
         ));
-        *self.last_timestamp.lock() += 100;
         (*epoch, *round)
     }
 
@@ -8,8 +8,8 @@ use crate::{
     util::time_service::{SendTask, TimeService},
 };
 use aptos_consensus_types::{
-    common::Round, sync_info::SyncInfo, timeout_2chain::TwoChainTimeoutWithPartialSignatures,
-    vote::Vote,
+    common::Round, equivocation_evidence::EquivocationEvidence, sync_info::SyncInfo,
+    timeout_2chain::TwoChainTimeoutWithPartialSignatures, vote::Vote,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::{prelude::*, Schema};
@@ -286,6 +286,12 @@ impl RoundState {
         }
     }
 
+    /// Returns the evidence of the vote equivocations observed in the current round since the
+    /// last call.
+    pub fn take_equivocation_evidence(&mut self) -> Vec<EquivocationEvidence> {
+        self.pending_votes.take_equivocation_evidence()
+    }
+
     pub fn record_vote(&mut self, vote: Vote) {
         if vote.vote_data().proposed().round() == self.current_round {
             self.vote_sent = Some(vote);
@@ -18,6 +18,8 @@ use std::cmp::Ordering;
 pub struct UnequivocalProposerElection {
     proposer_election: Box<dyn ProposerElection + Send + Sync>,
     already_proposed: Mutex<(Round, HashValue)>,
+    // The last round for which an equivocation was reported by take_equivocated_proposal.
+    last_equivocated_round: Mutex<Round>,
 }
 
 impl ProposerElection for UnequivocalProposerElection {
@@ -36,6 +38,7 @@ impl UnequivocalProposerElection {
         Self {
             proposer_election,
             already_proposed: Mutex::new((0, HashValue::zero())),
+            last_equivocated_round: Mutex::new(0),
         }
     }
 
@@ -89,4 +92,26 @@ impl UnequivocalProposerElection {
             }
         })
     }
+
+    // Return the id of the block the valid proposer already proposed for the round of the given
+    // block, if the given block is a different proposal of the same proposer.
+    // Only returns an id once per round, so that an equivocating proposer can't get reported
+    // repeatedly.
+    pub fn take_equivocated_proposal(&self, block: &Block) -> Option<HashValue> {
+        let valid_author = block.author().map_or(false, |author| {
+            self.is_valid_proposer(author, block.round())
+        });
+        let already_proposed = self.already_proposed.lock();
+        let mut last_equivocated_round = self.last_equivocated_round.lock();
+        if valid_author
+            && block.round() == already_proposed.0
+            && block.id() != already_proposed.1
+            && block.round() > *last_equivocated_round
+        {
+            *last_equivocated_round = block.round();
+            Some(already_proposed.1)
+        } else {
+            None
+        }
+    }
 }
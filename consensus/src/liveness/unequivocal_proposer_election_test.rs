@@ -94,6 +94,14 @@ fn test_is_valid_proposal() {
 
     // another proposal from the valid proposer should fail
     assert!(!pe.is_valid_proposal(&bad_duplicate_proposal));
+    // and is reported as an equivocation, only once
+    assert_eq!(pe.take_equivocated_proposal(&bad_author_proposal), None);
+    assert_eq!(pe.take_equivocated_proposal(&good_proposal), None);
+    assert_eq!(
+        pe.take_equivocated_proposal(&bad_duplicate_proposal),
+        Some(good_proposal.id())
+    );
+    assert_eq!(pe.take_equivocated_proposal(&bad_duplicate_proposal), None);
     // good proposal still passes
     assert!(pe.is_valid_proposal(&good_proposal));
 
@@ -9,6 +9,7 @@ use crate::{
     monitor,
     network_interface::{ConsensusMsg, ConsensusNetworkClient},
     quorum_store::types::{Batch, BatchMsg, BatchRequest},
+    recorder::{ConsensusRecorder, RecordedRead},
 };
 use anyhow::{anyhow, ensure};
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
@@ -41,6 +42,7 @@ use futures::{
 };
 use std::{
     mem::{discriminant, Discriminant},
+    sync::Arc,
     time::Duration,
 };
 
@@ -119,6 +121,7 @@ pub struct NetworkSender {
     // Note that we do not support self rpc requests as it might cause infinite recursive calls.
     self_sender: aptos_channels::Sender<Event<ConsensusMsg>>,
     validators: ValidatorVerifier,
+    recorder: Option<Arc<ConsensusRecorder>>,
 }
 
 impl NetworkSender {
@@ -133,9 +136,16 @@ impl NetworkSender {
             consensus_network_client,
             self_sender,
             validators,
+            recorder: None,
         }
     }
 
+    /// Records the batches fetched from peers with `recorder`.
+    pub fn with_recorder(mut self, recorder: Option<Arc<ConsensusRecorder>>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
     /// Tries to retrieve num of blocks backwards starting from id from the given peer: the function
     /// returns a future that is fulfilled with BlockRetrievalResponse.
     pub async fn request_block(
@@ -344,6 +354,9 @@ impl QuorumStoreSender for NetworkSender {
         match response {
             ConsensusMsg::BatchResponse(batch) => {
                 batch.verify()?;
+                if let Some(recorder) = &self.recorder {
+                    recorder.record_read(RecordedRead::Batch((*batch).clone()));
+                }
                 Ok(*batch)
             },
             _ => Err(anyhow!("Invalid batch response")),
@@ -395,13 +408,16 @@ pub struct NetworkTask {
     >,
     rpc_tx: aptos_channel::Sender<AccountAddress, (AccountAddress, IncomingRpcRequest)>,
     all_events: Box<dyn Stream<Item = Event<ConsensusMsg>> + Send + Unpin>,
+    recorder: Option<Arc<ConsensusRecorder>>,
 }
 
 impl NetworkTask {
     /// Establishes the initial connections with the peers and returns the receivers.
+    /// If a recorder is given, every inbound message is recorded before being dispatched.
     pub fn new(
         network_service_events: NetworkServiceEvents<ConsensusMsg>,
         self_receiver: aptos_channels::Receiver<Event<ConsensusMsg>>,
+        recorder: Option<Arc<ConsensusRecorder>>,
     ) -> (NetworkTask, NetworkReceivers) {
         let (consensus_messages_tx, consensus_messages) = aptos_channel::new(
             QueueStyle::FIFO,
@@ -442,6 +458,7 @@ impl NetworkTask {
                 quorum_store_messages_tx,
                 rpc_tx,
                 all_events,
+                recorder,
             },
             NetworkReceivers {
                 consensus_messages,
@@ -470,6 +487,15 @@ impl NetworkTask {
 
     pub async fn start(mut self) {
         while let Some(message) = self.all_events.next().await {
+            if let Some(recorder) = &self.recorder {
+                match &message {
+                    Event::Message(peer_id, msg) => recorder.record_message(*peer_id, msg),
+                    Event::RpcRequest(peer_id, msg, _, _) => {
+                        recorder.record_rpc_request(*peer_id, msg)
+                    },
+                    _ => {},
+                }
+            }
             monitor!("network_main_loop", match message {
                 Event::Message(peer_id, msg) => {
                     counters::CONSENSUS_RECEIVED_MSGS
@@ -620,7 +620,7 @@ mod tests {
             let network_events = NetworkEvents::new(consensus_rx, conn_status_rx);
             let network_service_events =
                 NetworkServiceEvents::new(hashmap! {NetworkId::Validator => network_events});
-            let (task, receiver) = NetworkTask::new(network_service_events, self_receiver);
+            let (task, receiver) = NetworkTask::new(network_service_events, self_receiver, None);
 
             receivers.push(receiver);
             runtime.handle().spawn(task.start());
@@ -731,7 +731,7 @@ mod tests {
             let network_events = NetworkEvents::new(consensus_rx, conn_status_rx);
             let network_service_events =
                 NetworkServiceEvents::new(hashmap! {NetworkId::Validator => network_events});
-            let (task, receiver) = NetworkTask::new(network_service_events, self_receiver);
+            let (task, receiver) = NetworkTask::new(network_service_events, self_receiver, None);
 
             senders.push(consensus_network_client);
             receivers.push(receiver);
@@ -803,7 +803,7 @@ mod tests {
         let (self_sender, self_receiver) = aptos_channels::new_test(8);
 
         let (network_task, mut network_receivers) =
-            NetworkTask::new(network_service_events, self_receiver);
+            NetworkTask::new(network_service_events, self_receiver, None);
 
         let peer_id = PeerId::random();
         let protocol_id = ProtocolId::ConsensusDirectSendBcs;
@@ -11,6 +11,7 @@
 use crate::counters;
 use aptos_consensus_types::{
     common::Author,
+    equivocation_evidence::EquivocationEvidence,
     quorum_cert::QuorumCert,
     timeout_2chain::{TwoChainTimeoutCertificate, TwoChainTimeoutWithPartialSignatures},
     vote::Vote,
@@ -23,7 +24,7 @@ use aptos_types::{
     validator_verifier::{ValidatorVerifier, VerifyError},
 };
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt,
     sync::Arc,
 };
@@ -68,6 +69,10 @@ pub struct PendingVotes {
     author_to_vote: HashMap<Author, (Vote, HashValue)>,
     /// Whether we have echoed timeout for this round.
     echo_timeout: bool,
+    /// Authors that have been caught equivocating in this round.
+    equivocating_authors: HashSet<Author>,
+    /// Evidence of the equivocations that hasn't been taken yet, at most one per author.
+    equivocation_evidence: Vec<EquivocationEvidence>,
 }
 
 impl PendingVotes {
@@ -78,6 +83,8 @@ impl PendingVotes {
             maybe_partial_2chain_tc: None,
             author_to_vote: HashMap::new(),
             echo_timeout: false,
+            equivocating_authors: HashSet::new(),
+            equivocation_evidence: vec![],
         }
     }
 
@@ -114,6 +121,11 @@ impl PendingVotes {
                     vote = vote,
                     previous_vote = previously_seen_vote
                 );
+                if self.equivocating_authors.insert(vote.author()) {
+                    let evidence =
+                        EquivocationEvidence::new_vote(previously_seen_vote.clone(), vote.clone());
+                    self.equivocation_evidence.push(evidence);
+                }
 
                 return VoteReceptionResult::EquivocateVote;
             }
@@ -257,6 +269,11 @@ impl PendingVotes {
         VoteReceptionResult::VoteAdded(voting_power)
     }
 
+    /// Returns the evidence of the equivocations observed since the last call.
+    pub fn take_equivocation_evidence(&mut self) -> Vec<EquivocationEvidence> {
+        std::mem::take(&mut self.equivocation_evidence)
+    }
+
     pub fn drain_votes(
         &mut self,
     ) -> (
@@ -339,7 +356,8 @@ impl fmt::Display for PendingVotes {
 mod tests {
     use super::{PendingVotes, VoteReceptionResult};
     use aptos_consensus_types::{
-        block::block_test_utils::certificate_for_genesis, vote::Vote, vote_data::VoteData,
+        block::block_test_utils::certificate_for_genesis,
+        equivocation_evidence::EquivocationEvidence, vote::Vote, vote_data::VoteData,
     };
     use aptos_crypto::HashValue;
     use aptos_types::{
@@ -403,6 +421,19 @@ mod tests {
             pending_votes.insert_vote(&vote_data_2_author_0, &validator),
             VoteReceptionResult::EquivocateVote
         );
+        assert_eq!(pending_votes.take_equivocation_evidence(), vec![
+            EquivocationEvidence::new_vote(
+                vote_data_1_author_0.clone(),
+                vote_data_2_author_0.clone(),
+            )
+        ]);
+
+        // the evidence is only collected once per author
+        assert_eq!(
+            pending_votes.insert_vote(&vote_data_2_author_0, &validator),
+            VoteReceptionResult::EquivocateVote
+        );
+        assert!(pending_votes.take_equivocation_evidence().is_empty());
 
         // a different author voting for a different result -> VoteAdded
         let vote_data_2_author_1 = Vote::new(
@@ -6,7 +6,8 @@ use crate::{consensusdb::ConsensusDB, epoch_manager::LivenessStorageData, error:
 use anyhow::{format_err, Context, Result};
 use aptos_config::config::NodeConfig;
 use aptos_consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
@@ -49,6 +50,12 @@ pub trait PersistentLivenessStorage: Send + Sync {
     /// ValidatorVerifier.
     fn retrieve_epoch_change_proof(&self, version: u64) -> Result<EpochChangeProof>;
 
+    /// Persist the evidence of a validator equivocating, so that it survives restarts.
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()>;
+
+    /// Retrieve all the persisted equivocation evidence.
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>>;
+
     /// Returns a handle of the aptosdb.
     fn aptos_db(&self) -> Arc<dyn DbReader>;
 }
@@ -76,6 +83,10 @@ impl LedgerRecoveryData {
         self.storage_ledger.commit_info().round()
     }
 
+    pub fn storage_ledger(&self) -> &LedgerInfoWithSignatures {
+        &self.storage_ledger
+    }
+
     /// Finds the root (last committed block) and returns the root block, the QC to the root block
     /// and the ledger info for the root block, return an error if it can not be found.
     ///
@@ -441,6 +452,14 @@ impl PersistentLivenessStorage for StorageWriteProxy {
         Ok(proofs)
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()> {
+        Ok(self.db.save_equivocation_evidence(evidence)?)
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(self.db.get_equivocation_evidence()?)
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         self.aptos_db.clone()
     }
@@ -123,7 +123,7 @@ impl QuorumStoreStorage for QuorumStoreDB {
 pub(crate) struct MockQuorumStoreDB {}
 
 impl MockQuorumStoreDB {
-    #[cfg(test)]
+    #[cfg(any(test, feature = "fuzzing"))]
     pub fn new() -> Self {
         Self {}
     }
@@ -0,0 +1,401 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Records the inputs of consensus to a file, so that the decisions of a node can be reproduced
+//! offline when investigating an incident, see the `consensus-replay` tool.
+//!
+//! A recording file is a sequence of BCS serialized records, each prefixed with its length as a
+//! little endian `u32`. A [`RecordingHeader`] is recorded whenever the node starts an epoch, and
+//! is followed by the [`RecordedEvent`]s received until the next header, along with the
+//! [`RecordedRead`]s of the data the node read from its storage and peers. The records are written
+//! by a dedicated thread, so that recording never blocks consensus.
+
+use crate::{
+    liveness::leader_reputation::MetadataBackend, network_interface::ConsensusMsg,
+    quorum_store::types::Batch, util::time_service::TimeService,
+};
+use anyhow::Context;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    account_config::NewBlockEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    on_chain_config::{OnChainConsensusConfig, ValidatorSet},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Maximum number of records waiting to be written. Records are dropped once it is reached, so
+/// that a slow disk cannot stall consensus.
+const MAX_PENDING_RECORDS: usize = 10_000;
+
+/// The state a node starts an epoch from, which the recorded inputs are replayed on.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordingHeader {
+    /// Time of the `TimeService` of the node when the epoch started.
+    pub timestamp_usecs: u64,
+    /// The recorded node.
+    pub author: Author,
+    /// The epoch started by the node.
+    pub epoch: u64,
+    /// Validator set of the epoch.
+    pub validator_set: ValidatorSet,
+    /// Latest ledger info in the storage of the node when the epoch started.
+    pub ledger_info: LedgerInfoWithSignatures,
+    /// On-chain consensus config of the epoch.
+    pub consensus_config: OnChainConsensusConfig,
+}
+
+/// An input of consensus.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RecordedInput {
+    /// A message received from a peer, or sent by the node to itself.
+    Message(Author, ConsensusMsg),
+    /// An RPC request received from a peer, or sent by the node to itself.
+    RpcRequest(Author, ConsensusMsg),
+    /// A local timeout of the given round.
+    LocalTimeout(Round),
+}
+
+/// An input of consensus along with the time it was received at.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordedEvent {
+    /// Time of the `TimeService` of the node when the input was received.
+    pub timestamp_usecs: u64,
+    /// The recorded input.
+    pub input: RecordedInput,
+}
+
+/// Data the node read from its storage or its peers rather than received as an input, which a
+/// replayed node reads from the recording instead.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RecordedRead {
+    /// The proposers of the epochs considered by the leader reputation of an epoch.
+    EpochToProposers {
+        /// The epoch started by the node.
+        epoch: u64,
+        /// The proposers of every considered epoch.
+        epoch_to_proposers: HashMap<u64, Vec<Author>>,
+    },
+    /// The block metadata read by the leader reputation to elect the proposer of a round.
+    BlockMetadata {
+        /// Epoch of the round.
+        target_epoch: u64,
+        /// The round whose proposer is elected.
+        target_round: Round,
+        /// The committed blocks the window of the leader reputation is made of.
+        events: Vec<NewBlockEvent>,
+        /// Accumulator root hash of the latest of `events`.
+        root_hash: HashValue,
+    },
+    /// A batch of the quorum store fetched from a peer.
+    Batch(Batch),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+enum Record {
+    Header(RecordingHeader),
+    Event(RecordedEvent),
+    Read(RecordedRead),
+}
+
+/// The inputs received by a node from the start of an epoch.
+#[derive(Clone, Debug)]
+pub struct Recording {
+    /// The state the node started the epoch from.
+    pub header: RecordingHeader,
+    /// The inputs received by the node, in order.
+    pub events: Vec<RecordedEvent>,
+    /// The data read by the node, in order.
+    pub reads: Vec<RecordedRead>,
+}
+
+/// Appends the start of every epoch and every input of consensus to a recording file.
+pub struct ConsensusRecorder {
+    time_service: Arc<dyn TimeService>,
+    sender: Option<SyncSender<Record>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl ConsensusRecorder {
+    /// Opens the recording at `path`, appending to it if it already exists.
+    pub fn new(path: &Path, time_service: Arc<dyn TimeService>) -> anyhow::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open consensus recording {}", path.display()))?;
+        let (sender, receiver) = mpsc::sync_channel(MAX_PENDING_RECORDS);
+        let writer = thread::Builder::new()
+            .name("consensus-recorder".into())
+            .spawn(move || write_records(BufWriter::new(file), receiver))
+            .context("Failed to spawn the consensus recorder thread")?;
+        Ok(Self {
+            time_service,
+            sender: Some(sender),
+            writer: Some(writer),
+        })
+    }
+
+    /// Records the start of `epoch` by `author`, from the `ledger_info` found in its storage.
+    pub fn record_header(
+        &self,
+        author: Author,
+        epoch: u64,
+        validator_set: ValidatorSet,
+        ledger_info: LedgerInfoWithSignatures,
+        consensus_config: OnChainConsensusConfig,
+    ) {
+        self.send(Record::Header(RecordingHeader {
+            timestamp_usecs: self.now_usecs(),
+            author,
+            epoch,
+            validator_set,
+            ledger_info,
+            consensus_config,
+        }));
+    }
+
+    /// Records a message received from `author`.
+    pub fn record_message(&self, author: Author, msg: &ConsensusMsg) {
+        self.record_event(RecordedInput::Message(author, msg.clone()));
+    }
+
+    /// Records an RPC request received from `author`.
+    pub fn record_rpc_request(&self, author: Author, msg: &ConsensusMsg) {
+        self.record_event(RecordedInput::RpcRequest(author, msg.clone()));
+    }
+
+    /// Records a local timeout of `round`.
+    pub fn record_local_timeout(&self, round: Round) {
+        self.record_event(RecordedInput::LocalTimeout(round));
+    }
+
+    /// Records data read by the node outside of its inputs.
+    pub fn record_read(&self, read: RecordedRead) {
+        self.send(Record::Read(read));
+    }
+
+    fn record_event(&self, input: RecordedInput) {
+        self.send(Record::Event(RecordedEvent {
+            timestamp_usecs: self.now_usecs(),
+            input,
+        }));
+    }
+
+    fn now_usecs(&self) -> u64 {
+        self.time_service.get_current_timestamp().as_micros() as u64
+    }
+
+    fn send(&self, record: Record) {
+        if let Some(sender) = &self.sender {
+            if let Err(e) = sender.try_send(record) {
+                warn!(error = ?e, "Failed to record consensus input");
+            }
+        }
+    }
+}
+
+impl Drop for ConsensusRecorder {
+    /// Waits for the pending records to be written.
+    fn drop(&mut self) {
+        // Closing the channel stops the writer thread once it is drained.
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            if writer.join().is_err() {
+                error!("The consensus recorder thread panicked");
+            }
+        }
+    }
+}
+
+fn write_records(mut writer: BufWriter<File>, receiver: Receiver<Record>) {
+    for record in receiver {
+        if let Err(e) = write_record(&mut writer, &record) {
+            warn!(error = ?e, "Failed to write consensus record");
+        }
+    }
+}
+
+fn write_record(writer: &mut impl Write, record: &Record) -> anyhow::Result<()> {
+    let bytes = bcs::to_bytes(record)?;
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    // Flush every record, so that the recording is complete up to a crash of the node.
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads all the recordings of the file at `path`, one per recorded epoch start. Events recorded
+/// before the first header, and an incomplete last record, e.g. written while the node crashed,
+/// are ignored.
+pub fn read_recordings(path: &Path) -> anyhow::Result<Vec<Recording>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open consensus recording {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut recordings: Vec<Recording> = vec![];
+    for index in 0u64.. {
+        let mut len = [0u8; 4];
+        match reader.read_exact(&mut len) {
+            Ok(()) => {},
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => {},
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e.into()),
+        }
+        let record = bcs::from_bytes(&bytes)
+            .with_context(|| format!("Failed to decode record #{} of the recording", index))?;
+        match record {
+            Record::Header(header) => recordings.push(Recording {
+                header,
+                events: vec![],
+                reads: vec![],
+            }),
+            Record::Event(event) => {
+                if let Some(recording) = recordings.last_mut() {
+                    recording.events.push(event);
+                }
+            },
+            Record::Read(read) => {
+                if let Some(recording) = recordings.last_mut() {
+                    recording.reads.push(read);
+                }
+            },
+        }
+    }
+    Ok(recordings)
+}
+
+/// Records the block metadata read by the leader reputation from `backend`.
+pub(crate) struct RecordingMetadataBackend {
+    backend: Box<dyn MetadataBackend>,
+    recorder: Arc<ConsensusRecorder>,
+}
+
+impl RecordingMetadataBackend {
+    pub(crate) fn new(backend: Box<dyn MetadataBackend>, recorder: Arc<ConsensusRecorder>) -> Self {
+        Self { backend, recorder }
+    }
+}
+
+impl MetadataBackend for RecordingMetadataBackend {
+    fn get_block_metadata(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue) {
+        let (events, root_hash) = self.backend.get_block_metadata(target_epoch, target_round);
+        self.recorder.record_read(RecordedRead::BlockMetadata {
+            target_epoch,
+            target_round,
+            events: events.clone(),
+            root_hash,
+        });
+        (events, root_hash)
+    }
+}
+
+/// The data read by a recorded node, served to the replayed node in place of its storage and
+/// peers.
+pub struct RecordedReads {
+    epoch_to_proposers: HashMap<u64, HashMap<u64, Vec<Author>>>,
+    block_metadata: HashMap<(u64, Round), VecDeque<(Vec<NewBlockEvent>, HashValue)>>,
+    batches: HashMap<HashValue, Batch>,
+}
+
+impl RecordedReads {
+    /// Indexes the `reads` of a recording.
+    pub fn new(reads: &[RecordedRead]) -> Self {
+        let mut recorded_reads = Self {
+            epoch_to_proposers: HashMap::new(),
+            block_metadata: HashMap::new(),
+            batches: HashMap::new(),
+        };
+        for read in reads.iter().cloned() {
+            match read {
+                RecordedRead::EpochToProposers {
+                    epoch,
+                    epoch_to_proposers,
+                } => {
+                    recorded_reads
+                        .epoch_to_proposers
+                        .insert(epoch, epoch_to_proposers);
+                },
+                RecordedRead::BlockMetadata {
+                    target_epoch,
+                    target_round,
+                    events,
+                    root_hash,
+                } => recorded_reads
+                    .block_metadata
+                    .entry((target_epoch, target_round))
+                    .or_default()
+                    .push_back((events, root_hash)),
+                RecordedRead::Batch(batch) => {
+                    recorded_reads.batches.insert(*batch.digest(), batch);
+                },
+            }
+        }
+        recorded_reads
+    }
+
+    /// Returns the proposers of the epochs considered by the leader reputation of `epoch`.
+    pub fn epoch_to_proposers(&self, epoch: u64) -> Option<HashMap<u64, Vec<Author>>> {
+        self.epoch_to_proposers.get(&epoch).cloned()
+    }
+
+    /// Returns a backend serving the recorded block metadata, in the order it was read.
+    pub fn metadata_backend(&self) -> Box<dyn MetadataBackend> {
+        Box::new(RecordedMetadataBackend {
+            block_metadata: Mutex::new(self.block_metadata.clone()),
+        })
+    }
+
+    /// Returns the batch with `digest` fetched by the recorded node.
+    pub fn batch(&self, digest: &HashValue) -> Option<Batch> {
+        self.batches.get(digest).cloned()
+    }
+}
+
+struct RecordedMetadataBackend {
+    block_metadata: Mutex<HashMap<(u64, Round), VecDeque<(Vec<NewBlockEvent>, HashValue)>>>,
+}
+
+impl MetadataBackend for RecordedMetadataBackend {
+    /// Returns the metadata recorded for the round, which stays the last one read once all of them
+    /// are served.
+    fn get_block_metadata(
+        &self,
+        target_epoch: u64,
+        target_round: Round,
+    ) -> (Vec<NewBlockEvent>, HashValue) {
+        let mut block_metadata = self.block_metadata.lock();
+        match block_metadata.get_mut(&(target_epoch, target_round)) {
+            Some(reads) if reads.len() > 1 => reads.pop_front().unwrap(),
+            Some(reads) => reads[0].clone(),
+            None => {
+                warn!(
+                    "No block metadata recorded for round {} of epoch {}",
+                    target_round, target_epoch
+                );
+                (vec![], HashValue::zero())
+            },
+        }
+    }
+}
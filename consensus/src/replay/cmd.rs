@@ -0,0 +1,58 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{recorder::read_recordings, replay::replay};
+use anyhow::{format_err, Result};
+use aptos_config::config::{ConsensusConfig, IdentityBlob, NodeConfig};
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[clap(
+    about = "Replay the recorded consensus inputs of a validator and print its decisions as JSON."
+)]
+pub struct Cmd {
+    /// Recording of the validator, see `consensus.recording_path` in the node config.
+    #[clap(long, parse(from_os_str))]
+    recording: PathBuf,
+
+    /// Identity blob of the validator, holding its consensus key.
+    #[clap(long, parse(from_os_str))]
+    identity_blob: PathBuf,
+
+    /// Node config of the validator, whose consensus settings are replayed. The default
+    /// consensus settings are used otherwise.
+    #[clap(long, parse(from_os_str))]
+    node_config: Option<PathBuf>,
+
+    /// Epoch to replay. The first recorded epoch is replayed otherwise.
+    #[clap(long)]
+    epoch: Option<u64>,
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let mut recordings = read_recordings(&self.recording)?.into_iter();
+        let recording = match self.epoch {
+            Some(epoch) => recordings
+                .find(|recording| recording.header.epoch == epoch)
+                .ok_or_else(|| format_err!("Epoch {} is not recorded", epoch))?,
+            None => recordings
+                .next()
+                .ok_or_else(|| format_err!("No epoch is recorded"))?,
+        };
+        let consensus_key = IdentityBlob::from_file(&self.identity_blob)?
+            .consensus_private_key
+            .ok_or_else(|| format_err!("The identity blob has no consensus key"))?;
+        let config = match &self.node_config {
+            Some(path) => NodeConfig::load_from_path(path)?.consensus,
+            None => ConsensusConfig::default(),
+        };
+
+        let decisions = replay(&recording, consensus_key, config)?;
+        for decision in decisions {
+            println!("{}", serde_json::to_string(&decision)?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,10 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_consensus::replay::Cmd;
+use clap::Parser;
+
+fn main() -> Result<()> {
+    Cmd::parse().run()
+}
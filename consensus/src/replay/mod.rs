@@ -0,0 +1,524 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays a recording of the consensus inputs of a validator (see `recorder`) into an
+//! `EpochManager` with a simulated time service and mocked execution and storage, to reproduce
+//! the decisions of the validator offline. See the `consensus-replay` tool.
+//!
+//! The node starts the recorded epoch from the ledger info and validator set of the header of the
+//! recording, so only a recording starting at an epoch boundary can be replayed, e.g. not the one
+//! recorded after a restart of the validator in the middle of an epoch. As the storage is mocked,
+//! the data the validator read from its AptosDB and peers, i.e. the block metadata of the leader
+//! reputation and the batches of the quorum store fetched from peers, is read from the recording.
+//!
+//! The node runs on a paused tokio clock following the recorded time: every input is fed at its
+//! recorded time, and its effects are awaited until the node has nothing left to do, as the clock
+//! only advances once all the tasks of the node are waiting. The block retrievals of the node are
+//! not answered, and the payloads of its proposals are taken from the recording. A proposal
+//! carries the time the node generated it at, so it only matches the recorded one if the validator
+//! proposed as soon as it received the input triggering the proposal.
+
+#[cfg(feature = "replay")]
+mod cmd;
+#[cfg(test)]
+mod replay_test;
+
+use crate::{
+    epoch_manager::EpochManager,
+    error::QuorumStoreError,
+    experimental::buffer_manager::OrderedBlocks,
+    network::NetworkTask,
+    network_interface::{ConsensusMsg, ConsensusNetworkClient, DIRECT_SEND, RPC},
+    quorum_store::quorum_store_db::QuorumStoreDB,
+    recorder::{
+        ConsensusRecorder, RecordedEvent, RecordedInput, RecordedRead, RecordedReads, Recording,
+        RecordingHeader,
+    },
+    state_replication::PayloadClient,
+    test_utils::{MockSharedStorage, MockStateComputer, MockStorage},
+    util::mock_time_service::SimulatedTimeService,
+};
+use anyhow::ensure;
+use aptos_bounded_executor::BoundedExecutor;
+use aptos_channels::{aptos_channel, message_queues::QueueStyle};
+use aptos_config::{
+    config::{
+        ConsensusConfig, NetworkConfig, NodeConfig, OnDiskStorageConfig, SafetyRulesConfig,
+        SafetyRulesTestConfig, SecureBackend,
+    },
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_consensus_types::common::{Author, Payload, PayloadFilter, ProofWithData, Round};
+use aptos_crypto::{bls12381, HashValue};
+use aptos_event_notifications::{ReconfigNotification, ReconfigNotificationListener};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_mempool::{QuorumStoreRequest, QuorumStoreResponse};
+use aptos_network::{
+    application::{
+        interface::{NetworkClient, NetworkServiceEvents},
+        storage::PeersAndMetadata,
+    },
+    peer_manager::{
+        conn_notifs_channel, ConnectionRequestSender, PeerManagerRequest, PeerManagerRequestSender,
+    },
+    protocols::{
+        network,
+        network::{Event, NetworkEvents, NewNetworkEvents, NewNetworkSender},
+        wire::handshake::v1::ProtocolIdSet,
+    },
+    transport::ConnectionMetadata,
+    ProtocolId,
+};
+use aptos_temppath::TempPath;
+use aptos_types::{
+    on_chain_config::{OnChainConfig, OnChainConfigPayload, OnChainConsensusConfig, ValidatorSet},
+    transaction::SignedTransaction,
+    validator_verifier::ValidatorVerifier,
+    waypoint::Waypoint,
+    PeerId,
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::BoxFuture,
+    StreamExt,
+};
+use maplit::hashmap;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    iter::FromIterator,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    runtime::{Builder, Runtime},
+    time::Instant,
+};
+
+#[cfg(feature = "replay")]
+pub use cmd::Cmd;
+
+/// An observable decision of the node.
+#[derive(Debug, Serialize)]
+pub enum Decision {
+    /// A message sent to a validator, possibly the node itself.
+    Send(Author, ConsensusMsg),
+    /// Ids of blocks ordered by the node.
+    Order(Vec<HashValue>),
+}
+
+/// Smallest duration the tokio clock advances by, as its timers have a millisecond resolution.
+const TIMER_RESOLUTION: Duration = Duration::from_millis(1);
+
+/// Replays `recording` on a node holding `consensus_key` and configured with `config`, and
+/// returns its decisions.
+pub fn replay(
+    recording: &Recording,
+    consensus_key: bls12381::PrivateKey,
+    config: ConsensusConfig,
+) -> anyhow::Result<Vec<Decision>> {
+    let payloads = recorded_payloads(&recording.header, &recording.events);
+    let mut node = ReplayNode::new(
+        &recording.header,
+        consensus_key,
+        config,
+        payloads,
+        &recording.reads,
+        SimulatedTimeService::new(),
+        None,
+    )?;
+    let mut decisions = node.take_decisions();
+    for event in &recording.events {
+        decisions.extend(node.process(event));
+    }
+    Ok(decisions)
+}
+
+/// Returns the payloads of the proposals of the node recorded after `header` found in `events`,
+/// in order, so that the replayed node proposes the same blocks.
+fn recorded_payloads(header: &RecordingHeader, events: &[RecordedEvent]) -> VecDeque<Payload> {
+    events
+        .iter()
+        .filter_map(|event| match &event.input {
+            RecordedInput::Message(_, ConsensusMsg::ProposalMsg(proposal))
+                if proposal.proposer() == header.author =>
+            {
+                match proposal.proposal().payload() {
+                    // The data status of the proofs is shared by the clones of the proposal.
+                    Some(Payload::InQuorumStore(proof_with_data)) => Some(Payload::InQuorumStore(
+                        ProofWithData::new(proof_with_data.proofs.clone()),
+                    )),
+                    Some(payload) => Some(payload.clone()),
+                    None => Some(Payload::empty(
+                        header.consensus_config.quorum_store_enabled(),
+                    )),
+                }
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Serves the payloads of the proposals of the recorded node, in order, then empty payloads.
+struct RecordedPayloadClient {
+    payloads: Mutex<VecDeque<Payload>>,
+    quorum_store_enabled: bool,
+}
+
+#[async_trait::async_trait]
+impl PayloadClient for RecordedPayloadClient {
+    async fn pull_payload(
+        &self,
+        _max_poll_time: Duration,
+        _max_items: u64,
+        _max_bytes: u64,
+        _exclude: PayloadFilter,
+        _wait_callback: BoxFuture<'static, ()>,
+        _pending_ordering: bool,
+        _pending_uncommitted_blocks: usize,
+        _recent_max_fill_fraction: f32,
+    ) -> Result<Payload, QuorumStoreError> {
+        Ok(self
+            .payloads
+            .lock()
+            .pop_front()
+            .unwrap_or_else(|| Payload::empty(self.quorum_store_enabled)))
+    }
+}
+
+/// A node started from a recording header, whose inputs are fed one by one, and whose outputs are
+/// captured instead of being sent over the network.
+struct ReplayNode {
+    // Dropped first, which stops the tasks of the node before their channels are closed.
+    runtime: Runtime,
+    author: Author,
+    time_service: SimulatedTimeService,
+    // Time of the tokio clock when the node started, at the time of the header.
+    start: Instant,
+    start_usecs: u64,
+    input_tx: aptos_channels::Sender<Event<ConsensusMsg>>,
+    timeout_tx: aptos_channels::Sender<Round>,
+    network_reqs_rx: aptos_channel::Receiver<(PeerId, ProtocolId), PeerManagerRequest>,
+    self_receiver: aptos_channels::Receiver<Event<ConsensusMsg>>,
+    ordered_blocks_events: mpsc::UnboundedReceiver<OrderedBlocks>,
+    commit_tx: mpsc::UnboundedSender<OrderedBlocks>,
+    mempool_requests: mpsc::Receiver<QuorumStoreRequest>,
+    reads: Arc<RecordedReads>,
+    _state_sync_receiver: mpsc::UnboundedReceiver<Vec<SignedTransaction>>,
+    // Holds the secure storage and the quorum store DB of the node.
+    _data_dir: TempPath,
+}
+
+impl ReplayNode {
+    /// Starts the node, advancing `time_service` to the time of `header`, proposing `payloads`,
+    /// and reading the data recorded in `reads` instead of its storage. The decisions the node
+    /// takes when starting, e.g. its first proposal, are returned by `take_decisions`.
+    fn new(
+        header: &RecordingHeader,
+        consensus_key: bls12381::PrivateKey,
+        mut config: ConsensusConfig,
+        payloads: VecDeque<Payload>,
+        reads: &[RecordedRead],
+        time_service: SimulatedTimeService,
+        recorder: Option<Arc<ConsensusRecorder>>,
+    ) -> anyhow::Result<Self> {
+        let author = header.author;
+        let ledger_info = header.ledger_info.ledger_info();
+        ensure!(
+            ledger_info.ends_epoch(),
+            "The recording of epoch {} does not start at the end of the previous epoch",
+            header.epoch
+        );
+        let verifier = ValidatorVerifier::from(&header.validator_set);
+        ensure!(
+            verifier.get_public_key(&author) == Some(bls12381::PublicKey::from(&consensus_key)),
+            "The consensus key does not belong to {}",
+            author
+        );
+
+        let data_dir = TempPath::new();
+        data_dir.create_as_dir()?;
+        let waypoint = Waypoint::new_epoch_boundary(ledger_info)?;
+        let mut safety_rules_test_config = SafetyRulesTestConfig::new(author);
+        safety_rules_test_config.consensus_key(consensus_key);
+        safety_rules_test_config.waypoint = Some(waypoint);
+        // The quorum store reads the consensus key from the secure storage of the safety rules, so
+        // it must outlive them.
+        let mut secure_storage = OnDiskStorageConfig::default();
+        secure_storage.path = data_dir.path().join("secure_storage.json");
+        config.safety_rules = SafetyRulesConfig {
+            backend: SecureBackend::OnDiskStorage(secure_storage),
+            test: Some(safety_rules_test_config),
+            ..SafetyRulesConfig::default()
+        };
+        // Only the peer id of the validator network identity is used.
+        let mut validator_network = NetworkConfig::network_with_id(NetworkId::Validator);
+        validator_network.random_with_peer_id(&mut StdRng::from_seed([0; 32]), Some(author));
+        let node_config = NodeConfig {
+            consensus: config,
+            validator_network: Some(validator_network),
+            ..NodeConfig::default()
+        };
+
+        let peers_and_metadata = PeersAndMetadata::new(&[NetworkId::Validator]);
+        for peer_id in verifier.get_ordered_account_addresses_iter() {
+            let mut conn_meta = ConnectionMetadata::mock(peer_id);
+            conn_meta.application_protocols = ProtocolIdSet::from_iter([
+                ProtocolId::ConsensusDirectSendJson,
+                ProtocolId::ConsensusDirectSendBcs,
+                ProtocolId::ConsensusRpcBcs,
+            ]);
+            peers_and_metadata.insert_connection_metadata(
+                PeerNetworkId::new(NetworkId::Validator, peer_id),
+                conn_meta,
+            )?;
+        }
+        let (network_reqs_tx, network_reqs_rx) = aptos_channel::new(QueueStyle::FIFO, 1024, None);
+        let (connection_reqs_tx, _) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+        let network_sender = network::NetworkSender::new(
+            PeerManagerRequestSender::new(network_reqs_tx),
+            ConnectionRequestSender::new(connection_reqs_tx),
+        );
+        let network_client = NetworkClient::new(
+            DIRECT_SEND.into(),
+            RPC.into(),
+            hashmap! {NetworkId::Validator => network_sender},
+            peers_and_metadata,
+        );
+        // Inputs are fed to the network task instead of being received from the network.
+        let (_, consensus_rx) = aptos_channel::new(QueueStyle::FIFO, 8, None);
+        let (_, conn_notifs_channel) = conn_notifs_channel::new();
+        let network_events = NetworkEvents::new(consensus_rx, conn_notifs_channel);
+        let network_service_events =
+            NetworkServiceEvents::new(hashmap! {NetworkId::Validator => network_events});
+        let (input_tx, input_rx) = aptos_channels::new_test(1_024);
+        let (self_sender, self_receiver) = aptos_channels::new_test(1_024);
+        // The simulated time service never fires the timeouts of the rounds, the recorded local
+        // timeouts are fed instead.
+        let (timeout_tx, timeout_rx) = aptos_channels::new_test(1_024);
+
+        let storage = Arc::new(MockStorage::new_with_ledger_info(
+            Arc::new(MockSharedStorage::new(header.validator_set.clone())),
+            ledger_info.clone(),
+        ));
+        let (state_sync_client, _state_sync_receiver) = mpsc::unbounded();
+        let (ordered_blocks_tx, ordered_blocks_events) = mpsc::unbounded::<OrderedBlocks>();
+        let state_computer = Arc::new(MockStateComputer::new(
+            state_sync_client,
+            ordered_blocks_tx,
+            storage.clone(),
+        ));
+        let (commit_tx, mut commit_rx) = mpsc::unbounded::<OrderedBlocks>();
+        let (mempool_tx, mempool_requests) = mpsc::channel(1_024);
+
+        let (reconfig_sender, reconfig_events) = aptos_channel::new(QueueStyle::LIFO, 1, None);
+        let reconfig_listener = ReconfigNotificationListener {
+            notification_receiver: reconfig_events,
+        };
+        let mut configs = HashMap::new();
+        configs.insert(
+            ValidatorSet::CONFIG_ID,
+            bcs::to_bytes(&header.validator_set)?,
+        );
+        configs.insert(
+            OnChainConsensusConfig::CONFIG_ID,
+            // Requires double serialization, check deserialize_into_config for more details
+            bcs::to_bytes(&bcs::to_bytes(&header.consensus_config)?)?,
+        );
+        reconfig_sender.push((), ReconfigNotification {
+            version: ledger_info.version(),
+            on_chain_configs: OnChainConfigPayload::new(header.epoch, Arc::new(configs)),
+        })?;
+
+        time_service.advance_to(Duration::from_micros(header.timestamp_usecs));
+        // The clock of the runtime only advances once all the tasks are waiting, see `run_until`.
+        let runtime = Builder::new_current_thread()
+            .thread_name("replay")
+            .enable_all()
+            .start_paused(true)
+            .build()?;
+        let start = runtime.block_on(async { Instant::now() });
+        let reads = Arc::new(RecordedReads::new(reads));
+        let payload_client = Arc::new(RecordedPayloadClient {
+            payloads: Mutex::new(payloads),
+            quorum_store_enabled: header.consensus_config.quorum_store_enabled(),
+        });
+        let mut epoch_mgr = EpochManager::new(
+            &node_config,
+            Arc::new(time_service.clone()),
+            self_sender,
+            ConsensusNetworkClient::new(network_client),
+            timeout_tx.clone(),
+            mempool_tx,
+            state_computer.clone(),
+            storage,
+            Arc::new(QuorumStoreDB::new(data_dir.path())),
+            reconfig_listener,
+            BoundedExecutor::new(2, runtime.handle().clone()),
+            recorder.clone(),
+        );
+        epoch_mgr.replay_from(reads.clone(), payload_client);
+        let (network_task, network_receiver) =
+            NetworkTask::new(network_service_events, input_rx, recorder);
+        runtime.spawn(network_task.start());
+        runtime.spawn(epoch_mgr.start(timeout_rx, network_receiver));
+        // Commits the ordered blocks in order, without blocking the serving of the batches their
+        // payloads wait for.
+        runtime.spawn(async move {
+            while let Some(ordered_blocks) = commit_rx.next().await {
+                if let Err(e) = state_computer.commit_to_storage(ordered_blocks).await {
+                    warn!(error = ?e, "Failed to commit the ordered blocks");
+                }
+            }
+        });
+
+        Ok(Self {
+            runtime,
+            author,
+            time_service,
+            start,
+            start_usecs: header.timestamp_usecs,
+            input_tx,
+            timeout_tx,
+            network_reqs_rx,
+            self_receiver,
+            ordered_blocks_events,
+            commit_tx,
+            mempool_requests,
+            reads,
+            _state_sync_receiver,
+            _data_dir: data_dir,
+        })
+    }
+
+    /// Feeds `event` to the node at its recorded time, and returns the decisions taken since the
+    /// last call.
+    fn process(&mut self, event: &RecordedEvent) -> Vec<Decision> {
+        // Fires the timers of the node due before the input, e.g. of the quorum store.
+        let mut decisions = self.run_until(event.timestamp_usecs);
+        self.time_service
+            .advance_to(Duration::from_micros(event.timestamp_usecs));
+        let result = match event.input.clone() {
+            RecordedInput::Message(author, msg) => {
+                self.input_tx.try_send(Event::Message(author, msg))
+            },
+            RecordedInput::RpcRequest(author, msg) => {
+                // The response of the node is not captured.
+                let (response_tx, _) = oneshot::channel();
+                self.input_tx.try_send(Event::RpcRequest(
+                    author,
+                    msg,
+                    ProtocolId::ConsensusRpcBcs,
+                    response_tx,
+                ))
+            },
+            RecordedInput::LocalTimeout(round) => self.timeout_tx.try_send(round),
+        };
+        if let Err(e) = result {
+            warn!(error = ?e, "Failed to feed the replayed input");
+        }
+        decisions.extend(self.take_decisions());
+        decisions
+    }
+
+    /// Returns the decisions taken since the last call, once the node has nothing left to do.
+    fn take_decisions(&mut self) -> Vec<Decision> {
+        // The node started at the time of the header, so this only waits for it to be done.
+        self.run_until(self.start_usecs)
+    }
+
+    /// Runs the node until the tokio clock reaches the recorded time `timestamp_usecs`, and at
+    /// least until the node has nothing left to do, and returns the decisions it took meanwhile.
+    /// As the clock is paused, it only advances once all the tasks of the node are waiting, so the
+    /// deadline is not reached before the node processed its inputs and fired the timers due
+    /// before it. Meanwhile the blocks ordered by the node are committed, and its requests of
+    /// batches and transactions are served.
+    fn run_until(&mut self, timestamp_usecs: u64) -> Vec<Decision> {
+        let deadline =
+            self.start + Duration::from_micros(timestamp_usecs.saturating_sub(self.start_usecs));
+        let Self {
+            runtime,
+            author,
+            network_reqs_rx,
+            self_receiver,
+            ordered_blocks_events,
+            commit_tx,
+            mempool_requests,
+            reads,
+            ..
+        } = self;
+        runtime.block_on(async {
+            let idle = tokio::time::sleep_until(deadline.max(Instant::now() + TIMER_RESOLUTION));
+            tokio::pin!(idle);
+            let mut decisions = vec![];
+            loop {
+                tokio::select! {
+                    Some(request) = network_reqs_rx.next() => {
+                        decisions.extend(serve_request(request, reads));
+                    },
+                    Some(event) = self_receiver.next() => {
+                        if let Event::Message(_, msg) = event {
+                            decisions.push(Decision::Send(*author, msg));
+                        }
+                    },
+                    Some(ordered_blocks) = ordered_blocks_events.next() => {
+                        let block_ids = ordered_blocks
+                            .ordered_blocks
+                            .iter()
+                            .map(|block| block.id())
+                            .collect();
+                        decisions.push(Decision::Order(block_ids));
+                        if commit_tx.unbounded_send(ordered_blocks).is_err() {
+                            warn!("Failed to commit the ordered blocks");
+                        }
+                    },
+                    Some(request) = mempool_requests.next() => {
+                        // The payloads of the proposals are taken from the recording, and the
+                        // quorum store receives the recorded batches instead of creating its own.
+                        if let QuorumStoreRequest::GetBatchRequest(.., callback) = request {
+                            let _ = callback.send(Ok(QuorumStoreResponse::GetBatchResponse(vec![])));
+                        }
+                    },
+                    _ = &mut idle => break,
+                }
+            }
+            decisions
+        })
+    }
+}
+
+/// Decodes a message sent by the node. Its batch requests are answered with the batches fetched
+/// by the recorded node, and its other RPCs fail, as the replay does not answer them.
+fn serve_request(request: PeerManagerRequest, reads: &RecordedReads) -> Option<Decision> {
+    let (recipient, protocol_id, bytes, response_tx) = match request {
+        PeerManagerRequest::SendDirectSend(recipient, message) => {
+            (recipient, message.protocol_id, message.mdata, None)
+        },
+        PeerManagerRequest::SendRpc(recipient, request) => (
+            recipient,
+            request.protocol_id,
+            request.data,
+            Some(request.res_tx),
+        ),
+    };
+    let msg = match protocol_id.from_bytes(&bytes) {
+        Ok(msg) => msg,
+        Err(e) => {
+            warn!(error = ?e, "Failed to decode consensus message");
+            return None;
+        },
+    };
+    if let (ConsensusMsg::BatchRequestMsg(request), Some(response_tx)) = (&msg, response_tx) {
+        if let Some(batch) = reads.batch(&request.digest()) {
+            match protocol_id.to_bytes(&ConsensusMsg::BatchResponse(Box::new(batch))) {
+                Ok(bytes) => {
+                    let _ = response_tx.send(Ok(bytes.into()));
+                },
+                Err(e) => warn!(error = ?e, "Failed to encode the recorded batch"),
+            }
+        }
+    }
+    Some(Decision::Send(recipient, msg))
+}
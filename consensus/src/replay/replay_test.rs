@@ -0,0 +1,172 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    network_interface::ConsensusMsg,
+    recorder::{
+        read_recordings, ConsensusRecorder, RecordedEvent, RecordedInput, RecordedRead, Recording,
+        RecordingHeader,
+    },
+    replay::{replay, Decision, ReplayNode},
+    util::mock_time_service::SimulatedTimeService,
+};
+use aptos_config::config::ConsensusConfig;
+use aptos_consensus_types::common::Payload;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    aggregate_signature::AggregateSignature,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::{
+        ConsensusConfigV1, OnChainConsensusConfig, ProposerElectionType, ValidatorSet,
+    },
+    validator_verifier::random_validator_verifier,
+};
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+fn ordered_blocks(decisions: &[Decision]) -> Vec<u8> {
+    let orders: Vec<_> = decisions
+        .iter()
+        .filter(|decision| matches!(decision, Decision::Order(_)))
+        .collect();
+    bcs::to_bytes(&orders).unwrap()
+}
+
+/// Runs a node that is the only validator of an epoch with `consensus_config` while recording its
+/// inputs, checks that replaying the recording orders the same blocks, and returns the recording
+/// along with the decisions of the node.
+fn record_and_replay(consensus_config: OnChainConsensusConfig) -> (Recording, Vec<Decision>) {
+    const START_TIME: Duration = Duration::from_secs(1);
+    const MIN_INPUTS: usize = 30;
+    const TIMEOUT_AT_INPUT: usize = 10;
+
+    let (signers, validators) = random_validator_verifier(1, None, false);
+    let validator_set = ValidatorSet::from(&validators);
+    let header = RecordingHeader {
+        timestamp_usecs: START_TIME.as_micros() as u64,
+        author: signers[0].author(),
+        epoch: 1,
+        validator_set: validator_set.clone(),
+        ledger_info: LedgerInfoWithSignatures::new(
+            LedgerInfo::mock_genesis(Some(validator_set)),
+            AggregateSignature::empty(),
+        ),
+        consensus_config,
+    };
+    let recording = TempPath::new();
+
+    // Run a node that is the only validator, feeding it back the messages it sends to itself as
+    // its network would, along with a local timeout, while recording its inputs.
+    let live_decisions = {
+        let time_service = SimulatedTimeService::new();
+        let recorder =
+            ConsensusRecorder::new(recording.path(), Arc::new(time_service.clone())).unwrap();
+        let mut node = ReplayNode::new(
+            &header,
+            signers[0].private_key().clone(),
+            ConsensusConfig::default(),
+            VecDeque::new(),
+            &[],
+            time_service,
+            Some(Arc::new(recorder)),
+        )
+        .unwrap();
+
+        let mut decisions = vec![];
+        let mut new_decisions = node.take_decisions();
+        let mut inputs = VecDeque::new();
+        let mut round = 0;
+        for num_inputs in 1..=MIN_INPUTS {
+            for decision in &new_decisions {
+                if let Decision::Send(recipient, msg) = decision {
+                    if *recipient == node.author {
+                        inputs.push_back(RecordedInput::Message(node.author, msg.clone()));
+                    }
+                }
+            }
+            decisions.append(&mut new_decisions);
+            if num_inputs == TIMEOUT_AT_INPUT {
+                inputs.push_front(RecordedInput::LocalTimeout(round));
+            }
+
+            let input = inputs
+                .pop_front()
+                .expect("The node stopped making progress");
+            if let RecordedInput::Message(_, ConsensusMsg::ProposalMsg(proposal)) = &input {
+                round = proposal.proposal().round();
+            }
+            new_decisions = node.process(&RecordedEvent {
+                timestamp_usecs: (START_TIME + Duration::from_secs(num_inputs as u64)).as_micros()
+                    as u64,
+                input,
+            });
+        }
+        decisions.append(&mut new_decisions);
+        // Stopping the node completes the recording.
+        decisions
+    };
+    assert!(live_decisions
+        .iter()
+        .any(|decision| matches!(decision, Decision::Order(_))));
+
+    let mut recordings = read_recordings(recording.path()).unwrap();
+    assert_eq!(recordings.len(), 1);
+    let recording = recordings.remove(0);
+    assert_eq!(
+        bcs::to_bytes(&recording.header).unwrap(),
+        bcs::to_bytes(&header).unwrap()
+    );
+    assert_eq!(recording.events.len(), MIN_INPUTS);
+    assert!(recording
+        .events
+        .iter()
+        .any(|event| matches!(event.input, RecordedInput::LocalTimeout(_))));
+
+    let replayed_decisions = replay(
+        &recording,
+        signers[0].private_key().clone(),
+        ConsensusConfig::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        ordered_blocks(&live_decisions),
+        ordered_blocks(&replayed_decisions)
+    );
+    (recording, live_decisions)
+}
+
+#[test]
+fn test_replay_reproduces_ordered_blocks() {
+    let (recording, _) = record_and_replay(OnChainConsensusConfig::V1(ConsensusConfigV1 {
+        proposer_election_type: ProposerElectionType::RotatingProposer(1),
+        ..ConsensusConfigV1::default()
+    }));
+    assert!(recording.reads.is_empty());
+}
+
+#[test]
+fn test_replay_leader_reputation() {
+    // The default proposer election is the leader reputation.
+    let (recording, _) =
+        record_and_replay(OnChainConsensusConfig::V1(ConsensusConfigV1::default()));
+    assert!(recording
+        .reads
+        .iter()
+        .any(|read| matches!(read, RecordedRead::EpochToProposers { epoch: 1, .. })));
+    assert!(recording
+        .reads
+        .iter()
+        .any(|read| matches!(read, RecordedRead::BlockMetadata { .. })));
+}
+
+#[test]
+fn test_replay_quorum_store() {
+    let (_, decisions) = record_and_replay(OnChainConsensusConfig::V2(ConsensusConfigV1 {
+        proposer_election_type: ProposerElectionType::RotatingProposer(1),
+        ..ConsensusConfigV1::default()
+    }));
+    assert!(decisions.iter().any(|decision| matches!(
+        decision,
+        Decision::Send(_, ConsensusMsg::ProposalMsg(proposal))
+            if matches!(proposal.proposal().payload(), Some(Payload::InQuorumStore(_)))
+    )));
+}
@@ -7,7 +7,7 @@ use crate::{
         tracing::{observe_block, BlockStage},
         BlockReader, BlockRetriever, BlockStore,
     },
-    counters,
+    counters, equivocation,
     error::{error_kind, VerifyError},
     liveness::{
         proposal_generator::ProposalGenerator,
@@ -30,6 +30,7 @@ use aptos_config::config::ConsensusConfig;
 use aptos_consensus_types::{
     block::Block,
     common::{Author, Round},
+    equivocation_evidence::EquivocationEvidence,
     experimental::{commit_decision::CommitDecision, commit_vote::CommitVote},
     proof_of_store::{ProofOfStoreMsg, SignedBatchInfoMsg},
     proposal_msg::ProposalMsg,
@@ -663,8 +664,12 @@ impl RoundManager {
                 .max_receiving_block_bytes(self.onchain_config.quorum_store_enabled()),
         );
 
+        let is_valid_proposal = self.proposer_election.is_valid_proposal(&proposal);
+        if !is_valid_proposal {
+            self.collect_proposal_equivocation(&proposal);
+        }
         ensure!(
-            self.proposer_election.is_valid_proposal(&proposal),
+            is_valid_proposal,
             "[RoundManager] Proposer {} for block {} is not a valid proposer for this round or created duplicate proposal",
             author,
             proposal,
@@ -902,10 +907,34 @@ impl RoundManager {
             VoteReceptionResult::VoteAdded(_)
             | VoteReceptionResult::EchoTimeout(_)
             | VoteReceptionResult::DuplicateVote => Ok(()),
+            e @ VoteReceptionResult::EquivocateVote => {
+                for evidence in self.round_state.take_equivocation_evidence() {
+                    equivocation::record_equivocation(self.storage.as_ref(), evidence);
+                }
+                Err(anyhow::anyhow!("{:?}", e))
+            },
             e => Err(anyhow::anyhow!("{:?}", e)),
         }
     }
 
+    /// Records the evidence if the given invalid proposal is a second proposal of the valid
+    /// proposer for the round. The first proposal is looked up in the block store, as it has been
+    /// inserted there when it was processed.
+    fn collect_proposal_equivocation(&self, proposal: &Block) {
+        if let Some(first_id) = self.proposer_election.take_equivocated_proposal(proposal) {
+            match self.block_store.get_block(first_id) {
+                Some(first) => equivocation::record_equivocation(
+                    self.storage.as_ref(),
+                    EquivocationEvidence::new_proposal(first.block().clone(), proposal.clone()),
+                ),
+                None => warn!(
+                    "Proposal {} equivocates block {}, which is not in the block store",
+                    proposal, first_id
+                ),
+            }
+        }
+    }
+
     async fn new_qc_aggregated(
         &mut self,
         qc: Arc<QuorumCert>,
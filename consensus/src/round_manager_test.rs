@@ -4,6 +4,7 @@
 
 use crate::{
     block_storage::{BlockReader, BlockStore},
+    equivocation::RecentEquivocationEvidence,
     experimental::buffer_manager::OrderedBlocks,
     liveness::{
         proposal_generator::{
@@ -18,7 +19,7 @@ use crate::{
     network_interface::{ConsensusMsg, ConsensusNetworkClient, DIRECT_SEND, RPC},
     network_tests::{NetworkPlayground, TwinId},
     payload_manager::PayloadManager,
-    persistent_liveness_storage::RecoveryData,
+    persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
     round_manager::RoundManager,
     test_utils::{
         consensus_runtime, timed_block_on, MockPayloadManager, MockStateComputer, MockStorage,
@@ -38,6 +39,7 @@ use aptos_consensus_types::{
     },
     block_retrieval::{BlockRetrievalRequest, BlockRetrievalStatus},
     common::{Author, Payload, Round},
+    equivocation_evidence::{EquivocationEvidence, EquivocationEvidenceProvider},
     experimental::commit_decision::CommitDecision,
     proposal_msg::ProposalMsg,
     sync_info::SyncInfo,
@@ -760,6 +762,62 @@ fn no_vote_on_old_proposal() {
     });
 }
 
+#[test]
+/// A second proposal of the proposer for the same round is recorded as evidence of
+/// equivocation, and persisted.
+fn record_equivocating_proposal() {
+    let runtime = consensus_runtime();
+    let mut playground = NetworkPlayground::new(runtime.handle().clone());
+    let mut nodes = NodeSetup::create_nodes(&mut playground, runtime.handle().clone(), 1, None);
+    let node = &mut nodes[0];
+    let genesis_qc = certificate_for_genesis();
+    let first_block = Block::new_proposal(
+        Payload::empty(false),
+        1,
+        1,
+        genesis_qc.clone(),
+        &node.signer,
+        Vec::new(),
+    )
+    .unwrap();
+    let second_block = Block::new_proposal(
+        Payload::empty(false),
+        1,
+        2,
+        genesis_qc,
+        &node.signer,
+        Vec::new(),
+    )
+    .unwrap();
+    let expected_evidence =
+        EquivocationEvidence::new_proposal(first_block.clone(), second_block.clone());
+    timed_block_on(&runtime, async {
+        // clear the message queue
+        node.next_proposal().await;
+
+        node.round_manager
+            .process_proposal(first_block)
+            .await
+            .unwrap();
+        node.round_manager
+            .process_proposal(second_block.clone())
+            .await
+            .unwrap_err();
+        // The evidence is only recorded once per round
+        node.round_manager
+            .process_proposal(second_block)
+            .await
+            .unwrap_err();
+    });
+
+    assert_eq!(node.storage.retrieve_equivocation_evidence().unwrap(), vec![
+        expected_evidence.clone()
+    ]);
+    assert!(RecentEquivocationEvidence
+        .recent_equivocation_evidence()
+        .contains(&expected_evidence));
+}
+
 #[test]
 /// We don't vote for proposals that 'skips' rounds
 /// After that when we then receive proposal for correct round, we vote for it
@@ -29,7 +29,7 @@ pub struct MockStateComputer {
     executor_channel: UnboundedSender<OrderedBlocks>,
     consensus_db: Arc<MockStorage>,
     block_cache: Mutex<HashMap<HashValue, Payload>>,
-    payload_manager: Arc<PayloadManager>,
+    payload_manager: Mutex<Arc<PayloadManager>>,
 }
 
 impl MockStateComputer {
@@ -43,7 +43,7 @@ impl MockStateComputer {
             executor_channel,
             consensus_db,
             block_cache: Mutex::new(HashMap::new()),
-            payload_manager: Arc::from(PayloadManager::DirectMempool),
+            payload_manager: Mutex::new(Arc::from(PayloadManager::DirectMempool)),
         }
     }
 
@@ -57,13 +57,14 @@ impl MockStateComputer {
         self.consensus_db
             .commit_to_storage(ordered_proof.ledger_info().clone());
         // mock sending commit notif to state sync
+        let payload_manager = self.payload_manager.lock().clone();
         let mut txns = vec![];
         for block in &ordered_blocks {
             self.block_cache
                 .lock()
                 .remove(&block.id())
                 .ok_or_else(|| format_err!("Cannot find block"))?;
-            let mut payload_txns = self.payload_manager.get_transactions(block.block()).await?;
+            let mut payload_txns = payload_manager.get_transactions(block.block()).await?;
             txns.append(&mut payload_txns);
         }
         // they may fail during shutdown
@@ -137,11 +138,12 @@ impl StateComputer for MockStateComputer {
     fn new_epoch(
         &self,
         _: &EpochState,
-        _: Arc<PayloadManager>,
+        payload_manager: Arc<PayloadManager>,
         _: Arc<dyn TransactionShuffler>,
         _: Option<u64>,
         _: Arc<dyn TransactionDeduper>,
     ) {
+        *self.payload_manager.lock() = payload_manager;
     }
 
     fn end_epoch(&self) {}
@@ -10,7 +10,8 @@ use crate::{
 };
 use anyhow::Result;
 use aptos_consensus_types::{
-    block::Block, quorum_cert::QuorumCert, timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
+    block::Block, equivocation_evidence::EquivocationEvidence, quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate, vote::Vote,
 };
 use aptos_crypto::HashValue;
 use aptos_infallible::Mutex;
@@ -32,6 +33,7 @@ pub struct MockSharedStorage {
 
     // Liveness state
     pub highest_2chain_timeout_certificate: Mutex<Option<TwoChainTimeoutCertificate>>,
+    pub equivocation_evidence: Mutex<Vec<EquivocationEvidence>>,
     pub validator_set: ValidatorSet,
 }
 
@@ -43,11 +45,24 @@ impl MockSharedStorage {
             lis: Mutex::new(HashMap::new()),
             last_vote: Mutex::new(None),
             highest_2chain_timeout_certificate: Mutex::new(None),
+            equivocation_evidence: Mutex::new(vec![]),
             validator_set,
         }
     }
 }
 
+/// Serves the ledger infos of the storage, e.g. to the quorum store.
+impl DbReader for MockSharedStorage {
+    fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>> {
+        Ok(self
+            .lis
+            .lock()
+            .values()
+            .max_by_key(|li| li.ledger_info().version())
+            .cloned())
+    }
+}
+
 /// A storage that simulates the operations in-memory, used in the tests that cares about storage
 /// consistency.
 pub struct MockStorage {
@@ -230,8 +245,20 @@ impl PersistentLivenessStorage for MockStorage {
         Ok(EpochChangeProof::new(vec![lis], false))
     }
 
+    fn save_equivocation_evidence(&self, evidence: &EquivocationEvidence) -> Result<()> {
+        self.shared_storage
+            .equivocation_evidence
+            .lock()
+            .push(evidence.clone());
+        Ok(())
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(self.shared_storage.equivocation_evidence.lock().clone())
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
-        unimplemented!()
+        self.shared_storage.clone()
     }
 }
 
@@ -298,6 +325,14 @@ impl PersistentLivenessStorage for EmptyStorage {
         Ok(EpochChangeProof::new(vec![], false))
     }
 
+    fn save_equivocation_evidence(&self, _: &EquivocationEvidence) -> Result<()> {
+        Ok(())
+    }
+
+    fn retrieve_equivocation_evidence(&self) -> Result<Vec<EquivocationEvidence>> {
+        Ok(vec![])
+    }
+
     fn aptos_db(&self) -> Arc<dyn DbReader> {
         unimplemented!()
     }
@@ -153,9 +153,10 @@ impl SMRNode {
             quorum_store_storage,
             reconfig_listener,
             bounded_executor,
+            None,
         );
         let (network_task, network_receiver) =
-            NetworkTask::new(network_service_events, self_receiver);
+            NetworkTask::new(network_service_events, self_receiver, None);
 
         runtime.spawn(network_task.start());
         runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
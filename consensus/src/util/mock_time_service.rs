@@ -121,6 +121,15 @@ impl SimulatedTimeService {
             futures::executor::block_on(t.run());
         }
     }
+
+    /// Moves the current time forward to `now`, without running pending tasks. Time never goes
+    /// backwards, so this is a no-op if `now` is in the past.
+    pub fn advance_to(&self, now: Duration) {
+        let mut inner = self.inner.lock();
+        if now > inner.now {
+            inner.now = now.min(inner.max);
+        }
+    }
 }
 
 impl Clone for SimulatedTimeService {
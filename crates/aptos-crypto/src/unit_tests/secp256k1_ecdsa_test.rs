@@ -0,0 +1,130 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    secp256k1_ecdsa::{
+        Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey, Secp256k1EcdsaSignature,
+        SECP256K1_PRIVATE_KEY_LENGTH, SECP256K1_PUBLIC_KEY_LENGTH, SECP256K1_SIGNATURE_LENGTH,
+    },
+    test_utils::{random_serializable_struct, uniform_keypair_strategy},
+    traits::*,
+};
+use core::convert::TryFrom;
+use proptest::{collection::vec, prelude::*};
+
+/// The order of the secp256k1 curve, big-endian.
+const N: [u8; 32] = [
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE,
+    0xBA, 0xAE, 0xDC, 0xE6, 0xAF, 0x48, 0xA0, 0x3B, 0xBF, 0xD2, 0x5E, 0x8C, 0xD0, 0x36, 0x41, 0x41,
+];
+
+/// Computes `N - s`, for big-endian `s < N`.
+fn negate_s(s: &[u8]) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut diff = N[i] as i16 - s[i] as i16 - borrow;
+        borrow = 0;
+        if diff < 0 {
+            diff += 256;
+            borrow = 1;
+        }
+        result[i] = diff as u8;
+    }
+    result
+}
+
+#[test]
+fn test_invalid_private_keys() {
+    // Zero and the order of the curve are not valid scalars.
+    assert!(Secp256k1EcdsaPrivateKey::try_from(&[0u8; 32][..]).is_err());
+    assert!(Secp256k1EcdsaPrivateKey::try_from(&N[..]).is_err());
+    assert!(Secp256k1EcdsaPrivateKey::try_from(&[1u8; 31][..]).is_err());
+}
+
+proptest! {
+    #[test]
+    fn test_keys_encode(
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        {
+            let encoded = keypair.private_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 + 2 * SECP256K1_PRIVATE_KEY_LENGTH, encoded.len());
+            let decoded = Secp256k1EcdsaPrivateKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.private_key), decoded.ok());
+        }
+        {
+            let encoded = keypair.public_key.to_encoded_string().unwrap();
+            prop_assert_eq!(2 + 2 * SECP256K1_PUBLIC_KEY_LENGTH, encoded.len());
+            let decoded = Secp256k1EcdsaPublicKey::from_encoded_string(&encoded);
+            prop_assert_eq!(Some(keypair.public_key), decoded.ok());
+        }
+    }
+
+    #[test]
+    fn test_keys_custom_serialisation(
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        {
+            let serialized: &[u8] = &(keypair.private_key.to_bytes());
+            prop_assert_eq!(SECP256K1_PRIVATE_KEY_LENGTH, serialized.len());
+            let deserialized = Secp256k1EcdsaPrivateKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.private_key), deserialized.ok());
+        }
+        {
+            let serialized: &[u8] = &(keypair.public_key.to_bytes());
+            prop_assert_eq!(SECP256K1_PUBLIC_KEY_LENGTH, serialized.len());
+            let deserialized = Secp256k1EcdsaPublicKey::try_from(serialized);
+            prop_assert_eq!(Some(keypair.public_key), deserialized.ok());
+        }
+    }
+
+    #[test]
+    fn test_signature_verification_custom_serialisation(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        let signature = keypair.private_key.sign(&message).unwrap();
+        let serialized: &[u8] = &(signature.to_bytes());
+        prop_assert_eq!(SECP256K1_SIGNATURE_LENGTH, serialized.len());
+        let deserialized = Secp256k1EcdsaSignature::try_from(serialized).unwrap();
+        prop_assert!(deserialized.verify(&message, &keypair.public_key).is_ok());
+    }
+
+    #[test]
+    fn test_signature_verification_from_arbitrary(
+        msg in vec(proptest::num::u8::ANY, 1..128),
+        other_msg in vec(proptest::num::u8::ANY, 1..128),
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>(),
+        other_keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        let signature = keypair.private_key.sign_arbitrary_message(&msg);
+        prop_assert!(signature.verify_arbitrary_msg(&msg, &keypair.public_key).is_ok());
+        if msg != other_msg {
+            prop_assert!(signature.verify_arbitrary_msg(&other_msg, &keypair.public_key).is_err());
+        }
+        if keypair.public_key != other_keypair.public_key {
+            prop_assert!(signature.verify_arbitrary_msg(&msg, &other_keypair.public_key).is_err());
+        }
+    }
+
+    // Check for low S.
+    #[test]
+    fn test_signature_malleability(
+        message in random_serializable_struct(),
+        keypair in uniform_keypair_strategy::<Secp256k1EcdsaPrivateKey, Secp256k1EcdsaPublicKey>()
+    ) {
+        let signature = keypair.private_key.sign(&message).unwrap();
+        let serialized = signature.to_bytes();
+        prop_assert!(Secp256k1EcdsaSignature::check_s_malleability(&serialized).is_ok());
+
+        // (r, n - s) is a valid ECDSA signature too, but with a high S.
+        let mut mauled = serialized;
+        mauled[32..].copy_from_slice(&negate_s(&serialized[32..]));
+        prop_assert_eq!(
+            Secp256k1EcdsaSignature::check_s_malleability(&mauled),
+            Err(CryptoMaterialError::CanonicalRepresentationError)
+        );
+        prop_assert!(Secp256k1EcdsaSignature::try_from(&mauled[..]).is_err());
+    }
+}
@@ -0,0 +1,27 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::CONTENT_TYPE_JSON;
+use aptos_consensus_types::equivocation_evidence::EquivocationEvidenceProvider;
+use hyper::{Body, StatusCode};
+use std::sync::Arc;
+
+/// Handles a new consensus evidence request
+pub fn handle_consensus_evidence_request(
+    evidence_provider: Arc<dyn EquivocationEvidenceProvider>,
+) -> (StatusCode, Body, String) {
+    (
+        StatusCode::OK,
+        Body::from(get_consensus_evidence_json(evidence_provider)),
+        CONTENT_TYPE_JSON.into(),
+    )
+}
+
+/// Returns a JSON formatted string with the most recent evidence of
+/// validators equivocating, as observed by this node
+fn get_consensus_evidence_json(evidence_provider: Arc<dyn EquivocationEvidenceProvider>) -> String {
+    match serde_json::to_string(&evidence_provider.recent_equivocation_evidence()) {
+        Ok(consensus_evidence) => consensus_evidence,
+        Err(error) => format!("Failed to get consensus evidence! Error: {}", error),
+    }
+}
@@ -7,18 +7,24 @@ use crate::{
         peer_information::PEER_INFO_DISABLED_MESSAGE, serve_requests,
         system_information::SYS_INFO_DISABLED_MESSAGE, utils::get_all_metrics,
     },
-    CONFIGURATION_PATH, FORGE_METRICS_PATH, INDEX_PATH, JSON_METRICS_PATH, METRICS_PATH,
-    PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
+    CONFIGURATION_PATH, CONSENSUS_EVIDENCE_PATH, FORGE_METRICS_PATH, INDEX_PATH, JSON_METRICS_PATH,
+    METRICS_PATH, PEER_INFORMATION_PATH, SYSTEM_INFORMATION_PATH,
 };
 use aptos_config::config::NodeConfig;
+use aptos_consensus_types::{
+    block::{block_test_utils::certificate_for_genesis, Block},
+    common::Payload,
+    equivocation_evidence::{EquivocationEvidence, EquivocationEvidenceProvider},
+};
 use aptos_network::application::storage::PeersAndMetadata;
+use aptos_types::validator_signer::ValidatorSigner;
 use assert_approx_eq::assert_approx_eq;
 use futures::executor::block_on;
 use hyper::{body, Body, Method, Request, Response, StatusCode};
 use once_cell::sync::Lazy;
 use prometheus::{proto::MetricFamily, register_int_counter, Counter, IntCounter, Opts, Registry};
 use rusty_fork::rusty_fork_test;
-use std::{io::read_to_string, string::String};
+use std::{io::read_to_string, string::String, sync::Arc};
 
 // This metrics counter only exists in this test context; the rest of the
 // system's metrics counters don't exist, so we need to add this for tests.
@@ -53,6 +59,62 @@ async fn test_inspect_configuration() {
     assert!(response_body_string.contains("expose_configuration: true"));
 }
 
+#[tokio::test]
+async fn test_inspect_consensus_evidence() {
+    // Create a validator config
+    let config = NodeConfig::get_default_validator_config();
+
+    // Get the consensus evidence
+    let mut response = send_get_request_to_path(&config, CONSENSUS_EVIDENCE_PATH).await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+    let response_body_string = read_to_string(response_body.as_ref()).unwrap();
+
+    // Verify that no evidence has been collected
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response_body_string, "[]");
+}
+
+#[tokio::test]
+async fn test_inspect_recorded_consensus_evidence() {
+    // Create a validator config
+    let config = NodeConfig::get_default_validator_config();
+
+    // Record the evidence of a validator proposing two blocks for the same round
+    let signer = ValidatorSigner::random(None);
+    let propose = |timestamp_usecs| {
+        Block::new_proposal(
+            Payload::empty(false),
+            1,
+            timestamp_usecs,
+            certificate_for_genesis(),
+            &signer,
+            Vec::new(),
+        )
+        .unwrap()
+    };
+    let evidence = vec![EquivocationEvidence::new_proposal(propose(1), propose(2))];
+
+    // Get the consensus evidence
+    let mut response = send_get_request_with_evidence(
+        &config,
+        CONSENSUS_EVIDENCE_PATH,
+        Arc::new(RecordedEquivocationEvidence(evidence.clone())),
+    )
+    .await;
+    let response_body = body::to_bytes(response.body_mut()).await.unwrap();
+    let response_body_string = read_to_string(response_body.as_ref()).unwrap();
+
+    // Verify that the response contains the serialized evidence
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response_body_string,
+        serde_json::to_string(&evidence).unwrap()
+    );
+    let served_evidence: Vec<EquivocationEvidence> =
+        serde_json::from_str(&response_body_string).unwrap();
+    assert_eq!(served_evidence, evidence);
+}
+
 #[tokio::test]
 async fn test_inspect_forge_metrics() {
     // Create a VFN config
@@ -82,6 +144,7 @@ async fn test_inspect_index() {
     // Verify that the response contains all the endpoints
     assert_eq!(response.status(), StatusCode::OK);
     assert!(response_body_string.contains(CONFIGURATION_PATH));
+    assert!(response_body_string.contains(CONSENSUS_EVIDENCE_PATH));
     assert!(response_body_string.contains(FORGE_METRICS_PATH));
     assert!(response_body_string.contains(JSON_METRICS_PATH));
     assert!(response_body_string.contains(METRICS_PATH));
@@ -243,6 +306,16 @@ fn test_publish_metrics() {
 
 // Exercise the serve_requests() handler with a GET request to the given path
 async fn send_get_request_to_path(config: &NodeConfig, endpoint: &str) -> Response<Body> {
+    send_get_request_with_evidence(config, endpoint, Arc::new(NoEquivocationEvidence)).await
+}
+
+// Exercise the serve_requests() handler with a GET request to the given path,
+// on a node that observed the evidence of the given provider
+async fn send_get_request_with_evidence(
+    config: &NodeConfig,
+    endpoint: &str,
+    evidence_provider: Arc<dyn EquivocationEvidenceProvider>,
+) -> Response<Body> {
     // Build the URI
     let uri = format!("http://127.0.0.1:9201{}", endpoint);
 
@@ -255,7 +328,26 @@ async fn send_get_request_to_path(config: &NodeConfig, endpoint: &str) -> Respon
             .unwrap(),
         config.clone(),
         PeersAndMetadata::new(&[]),
+        evidence_provider,
     )
     .await
     .unwrap()
 }
+
+// An evidence provider for a node that hasn't observed any equivocation
+struct NoEquivocationEvidence;
+
+impl EquivocationEvidenceProvider for NoEquivocationEvidence {
+    fn recent_equivocation_evidence(&self) -> Vec<EquivocationEvidence> {
+        vec![]
+    }
+}
+
+// An evidence provider for a node that observed the given evidence
+struct RecordedEquivocationEvidence(Vec<EquivocationEvidence>);
+
+impl EquivocationEvidenceProvider for RecordedEquivocationEvidence {
+    fn recent_equivocation_evidence(&self) -> Vec<EquivocationEvidence> {
+        self.0.clone()
+    }
+}
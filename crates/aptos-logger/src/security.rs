@@ -41,6 +41,9 @@ pub enum SecurityEvent {
     /// Consensus received an equivocating vote
     ConsensusEquivocatingVote,
 
+    /// Consensus collected evidence of a validator signing conflicting proposals or votes
+    ConsensusEquivocationEvidence,
+
     /// Consensus received an invalid proposal
     InvalidConsensusProposal,
 
@@ -29,6 +29,9 @@ impl serde::Serialize for AccountSignature {
                 account_signature::Signature::MultiEd25519(v) => {
                     struct_ser.serialize_field("multiEd25519", v)?;
                 }
+                account_signature::Signature::Secp256k1Ecdsa(v) => {
+                    struct_ser.serialize_field("secp256k1Ecdsa", v)?;
+                }
             }
         }
         struct_ser.end()
@@ -45,6 +48,8 @@ impl<'de> serde::Deserialize<'de> for AccountSignature {
             "ed25519",
             "multi_ed25519",
             "multiEd25519",
+            "secp256k1_ecdsa",
+            "secp256k1Ecdsa",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -52,6 +57,7 @@ impl<'de> serde::Deserialize<'de> for AccountSignature {
             Type,
             Ed25519,
             MultiEd25519,
+            Secp256k1Ecdsa,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -76,6 +82,7 @@ impl<'de> serde::Deserialize<'de> for AccountSignature {
                             "type" => Ok(GeneratedField::Type),
                             "ed25519" => Ok(GeneratedField::Ed25519),
                             "multiEd25519" | "multi_ed25519" => Ok(GeneratedField::MultiEd25519),
+                            "secp256k1Ecdsa" | "secp256k1_ecdsa" => Ok(GeneratedField::Secp256k1Ecdsa),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -117,6 +124,13 @@ impl<'de> serde::Deserialize<'de> for AccountSignature {
                                 return Err(serde::de::Error::duplicate_field("multiEd25519"));
                             }
                             signature__ = map.next_value::<::std::option::Option<_>>()?.map(account_signature::Signature::MultiEd25519)
+;
+                        }
+                        GeneratedField::Secp256k1Ecdsa => {
+                            if signature__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("secp256k1Ecdsa"));
+                            }
+                            signature__ = map.next_value::<::std::option::Option<_>>()?.map(account_signature::Signature::Secp256k1Ecdsa)
 ;
                         }
                     }
@@ -140,6 +154,7 @@ impl serde::Serialize for account_signature::Type {
             Self::Unspecified => "TYPE_UNSPECIFIED",
             Self::Ed25519 => "TYPE_ED25519",
             Self::MultiEd25519 => "TYPE_MULTI_ED25519",
+            Self::Secp256k1Ecdsa => "TYPE_SECP256K1_ECDSA",
         };
         serializer.serialize_str(variant)
     }
@@ -154,6 +169,7 @@ impl<'de> serde::Deserialize<'de> for account_signature::Type {
             "TYPE_UNSPECIFIED",
             "TYPE_ED25519",
             "TYPE_MULTI_ED25519",
+            "TYPE_SECP256K1_ECDSA",
         ];
 
         struct GeneratedVisitor;
@@ -199,6 +215,7 @@ impl<'de> serde::Deserialize<'de> for account_signature::Type {
                     "TYPE_UNSPECIFIED" => Ok(account_signature::Type::Unspecified),
                     "TYPE_ED25519" => Ok(account_signature::Type::Ed25519),
                     "TYPE_MULTI_ED25519" => Ok(account_signature::Type::MultiEd25519),
+                    "TYPE_SECP256K1_ECDSA" => Ok(account_signature::Type::Secp256k1Ecdsa),
                     _ => Err(serde::de::Error::unknown_variant(value, FIELDS)),
                 }
             }
@@ -1815,6 +1832,169 @@ impl<'de> serde::Deserialize<'de> for EventKey {
         deserializer.deserialize_struct("aptos.transaction.v1.EventKey", FIELDS, GeneratedVisitor)
     }
 }
+impl serde::Serialize for FeePayerSignature {
+    #[allow(deprecated)]
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut len = 0;
+        if self.sender.is_some() {
+            len += 1;
+        }
+        if !self.secondary_signer_addresses.is_empty() {
+            len += 1;
+        }
+        if !self.secondary_signers.is_empty() {
+            len += 1;
+        }
+        if !self.fee_payer_address.is_empty() {
+            len += 1;
+        }
+        if self.fee_payer_signer.is_some() {
+            len += 1;
+        }
+        let mut struct_ser = serializer.serialize_struct("aptos.transaction.v1.FeePayerSignature", len)?;
+        if let Some(v) = self.sender.as_ref() {
+            struct_ser.serialize_field("sender", v)?;
+        }
+        if !self.secondary_signer_addresses.is_empty() {
+            struct_ser.serialize_field("secondarySignerAddresses", &self.secondary_signer_addresses)?;
+        }
+        if !self.secondary_signers.is_empty() {
+            struct_ser.serialize_field("secondarySigners", &self.secondary_signers)?;
+        }
+        if !self.fee_payer_address.is_empty() {
+            struct_ser.serialize_field("feePayerAddress", &self.fee_payer_address)?;
+        }
+        if let Some(v) = self.fee_payer_signer.as_ref() {
+            struct_ser.serialize_field("feePayerSigner", v)?;
+        }
+        struct_ser.end()
+    }
+}
+impl<'de> serde::Deserialize<'de> for FeePayerSignature {
+    #[allow(deprecated)]
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        const FIELDS: &[&str] = &[
+            "sender",
+            "secondary_signer_addresses",
+            "secondarySignerAddresses",
+            "secondary_signers",
+            "secondarySigners",
+            "fee_payer_address",
+            "feePayerAddress",
+            "fee_payer_signer",
+            "feePayerSigner",
+        ];
+
+        #[allow(clippy::enum_variant_names)]
+        enum GeneratedField {
+            Sender,
+            SecondarySignerAddresses,
+            SecondarySigners,
+            FeePayerAddress,
+            FeePayerSigner,
+        }
+        impl<'de> serde::Deserialize<'de> for GeneratedField {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct GeneratedVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+                    type Value = GeneratedField;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(formatter, "expected one of: {:?}", &FIELDS)
+                    }
+
+                    #[allow(unused_variables)]
+                    fn visit_str<E>(self, value: &str) -> std::result::Result<GeneratedField, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "sender" => Ok(GeneratedField::Sender),
+                            "secondarySignerAddresses" | "secondary_signer_addresses" => Ok(GeneratedField::SecondarySignerAddresses),
+                            "secondarySigners" | "secondary_signers" => Ok(GeneratedField::SecondarySigners),
+                            "feePayerAddress" | "fee_payer_address" => Ok(GeneratedField::FeePayerAddress),
+                            "feePayerSigner" | "fee_payer_signer" => Ok(GeneratedField::FeePayerSigner),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+                deserializer.deserialize_identifier(GeneratedVisitor)
+            }
+        }
+        struct GeneratedVisitor;
+        impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+            type Value = FeePayerSignature;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("struct aptos.transaction.v1.FeePayerSignature")
+            }
+
+            fn visit_map<V>(self, mut map: V) -> std::result::Result<FeePayerSignature, V::Error>
+                where
+                    V: serde::de::MapAccess<'de>,
+            {
+                let mut sender__ = None;
+                let mut secondary_signer_addresses__ = None;
+                let mut secondary_signers__ = None;
+                let mut fee_payer_address__ = None;
+                let mut fee_payer_signer__ = None;
+                while let Some(k) = map.next_key()? {
+                    match k {
+                        GeneratedField::Sender => {
+                            if sender__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("sender"));
+                            }
+                            sender__ = map.next_value()?;
+                        }
+                        GeneratedField::SecondarySignerAddresses => {
+                            if secondary_signer_addresses__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("secondarySignerAddresses"));
+                            }
+                            secondary_signer_addresses__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::SecondarySigners => {
+                            if secondary_signers__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("secondarySigners"));
+                            }
+                            secondary_signers__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::FeePayerAddress => {
+                            if fee_payer_address__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("feePayerAddress"));
+                            }
+                            fee_payer_address__ = Some(map.next_value()?);
+                        }
+                        GeneratedField::FeePayerSigner => {
+                            if fee_payer_signer__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("feePayerSigner"));
+                            }
+                            fee_payer_signer__ = map.next_value()?;
+                        }
+                    }
+                }
+                Ok(FeePayerSignature {
+                    sender: sender__,
+                    secondary_signer_addresses: secondary_signer_addresses__.unwrap_or_default(),
+                    secondary_signers: secondary_signers__.unwrap_or_default(),
+                    fee_payer_address: fee_payer_address__.unwrap_or_default(),
+                    fee_payer_signer: fee_payer_signer__,
+                })
+            }
+        }
+        deserializer.deserialize_struct("aptos.transaction.v1.FeePayerSignature", FIELDS, GeneratedVisitor)
+    }
+}
 impl serde::Serialize for GenesisTransaction {
     #[allow(deprecated)]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -4669,6 +4849,119 @@ impl<'de> serde::Deserialize<'de> for ScriptWriteSet {
         deserializer.deserialize_struct("aptos.transaction.v1.ScriptWriteSet", FIELDS, GeneratedVisitor)
     }
 }
+impl serde::Serialize for Secp256k1EcdsaSignature {
+    #[allow(deprecated)]
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut len = 0;
+        if !self.public_key.is_empty() {
+            len += 1;
+        }
+        if !self.signature.is_empty() {
+            len += 1;
+        }
+        let mut struct_ser = serializer.serialize_struct("aptos.transaction.v1.Secp256k1EcdsaSignature", len)?;
+        if !self.public_key.is_empty() {
+            struct_ser.serialize_field("publicKey", pbjson::private::base64::encode(&self.public_key).as_str())?;
+        }
+        if !self.signature.is_empty() {
+            struct_ser.serialize_field("signature", pbjson::private::base64::encode(&self.signature).as_str())?;
+        }
+        struct_ser.end()
+    }
+}
+impl<'de> serde::Deserialize<'de> for Secp256k1EcdsaSignature {
+    #[allow(deprecated)]
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        const FIELDS: &[&str] = &[
+            "public_key",
+            "publicKey",
+            "signature",
+        ];
+
+        #[allow(clippy::enum_variant_names)]
+        enum GeneratedField {
+            PublicKey,
+            Signature,
+        }
+        impl<'de> serde::Deserialize<'de> for GeneratedField {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct GeneratedVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+                    type Value = GeneratedField;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(formatter, "expected one of: {:?}", &FIELDS)
+                    }
+
+                    #[allow(unused_variables)]
+                    fn visit_str<E>(self, value: &str) -> std::result::Result<GeneratedField, E>
+                    where
+                        E: serde::de::Error,
+                    {
+                        match value {
+                            "publicKey" | "public_key" => Ok(GeneratedField::PublicKey),
+                            "signature" => Ok(GeneratedField::Signature),
+                            _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+                deserializer.deserialize_identifier(GeneratedVisitor)
+            }
+        }
+        struct GeneratedVisitor;
+        impl<'de> serde::de::Visitor<'de> for GeneratedVisitor {
+            type Value = Secp256k1EcdsaSignature;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("struct aptos.transaction.v1.Secp256k1EcdsaSignature")
+            }
+
+            fn visit_map<V>(self, mut map: V) -> std::result::Result<Secp256k1EcdsaSignature, V::Error>
+                where
+                    V: serde::de::MapAccess<'de>,
+            {
+                let mut public_key__ = None;
+                let mut signature__ = None;
+                while let Some(k) = map.next_key()? {
+                    match k {
+                        GeneratedField::PublicKey => {
+                            if public_key__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("publicKey"));
+                            }
+                            public_key__ =
+                                Some(map.next_value::<::pbjson::private::BytesDeserialize<_>>()?.0)
+                            ;
+                        }
+                        GeneratedField::Signature => {
+                            if signature__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("signature"));
+                            }
+                            signature__ =
+                                Some(map.next_value::<::pbjson::private::BytesDeserialize<_>>()?.0)
+                            ;
+                        }
+                    }
+                }
+                Ok(Secp256k1EcdsaSignature {
+                    public_key: public_key__.unwrap_or_default(),
+                    signature: signature__.unwrap_or_default(),
+                })
+            }
+        }
+        deserializer.deserialize_struct("aptos.transaction.v1.Secp256k1EcdsaSignature", FIELDS, GeneratedVisitor)
+    }
+}
 impl serde::Serialize for Signature {
     #[allow(deprecated)]
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
@@ -4700,6 +4993,12 @@ impl serde::Serialize for Signature {
                 signature::Signature::MultiAgent(v) => {
                     struct_ser.serialize_field("multiAgent", v)?;
                 }
+                signature::Signature::FeePayer(v) => {
+                    struct_ser.serialize_field("feePayer", v)?;
+                }
+                signature::Signature::Secp256k1Ecdsa(v) => {
+                    struct_ser.serialize_field("secp256k1Ecdsa", v)?;
+                }
             }
         }
         struct_ser.end()
@@ -4718,6 +5017,10 @@ impl<'de> serde::Deserialize<'de> for Signature {
             "multiEd25519",
             "multi_agent",
             "multiAgent",
+            "fee_payer",
+            "feePayer",
+            "secp256k1_ecdsa",
+            "secp256k1Ecdsa",
         ];
 
         #[allow(clippy::enum_variant_names)]
@@ -4726,6 +5029,8 @@ impl<'de> serde::Deserialize<'de> for Signature {
             Ed25519,
             MultiEd25519,
             MultiAgent,
+            FeePayer,
+            Secp256k1Ecdsa,
         }
         impl<'de> serde::Deserialize<'de> for GeneratedField {
             fn deserialize<D>(deserializer: D) -> std::result::Result<GeneratedField, D::Error>
@@ -4751,6 +5056,8 @@ impl<'de> serde::Deserialize<'de> for Signature {
                             "ed25519" => Ok(GeneratedField::Ed25519),
                             "multiEd25519" | "multi_ed25519" => Ok(GeneratedField::MultiEd25519),
                             "multiAgent" | "multi_agent" => Ok(GeneratedField::MultiAgent),
+                            "feePayer" | "fee_payer" => Ok(GeneratedField::FeePayer),
+                            "secp256k1Ecdsa" | "secp256k1_ecdsa" => Ok(GeneratedField::Secp256k1Ecdsa),
                             _ => Err(serde::de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -4799,6 +5106,20 @@ impl<'de> serde::Deserialize<'de> for Signature {
                                 return Err(serde::de::Error::duplicate_field("multiAgent"));
                             }
                             signature__ = map.next_value::<::std::option::Option<_>>()?.map(signature::Signature::MultiAgent)
+;
+                        }
+                        GeneratedField::FeePayer => {
+                            if signature__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("feePayer"));
+                            }
+                            signature__ = map.next_value::<::std::option::Option<_>>()?.map(signature::Signature::FeePayer)
+;
+                        }
+                        GeneratedField::Secp256k1Ecdsa => {
+                            if signature__.is_some() {
+                                return Err(serde::de::Error::duplicate_field("secp256k1Ecdsa"));
+                            }
+                            signature__ = map.next_value::<::std::option::Option<_>>()?.map(signature::Signature::Secp256k1Ecdsa)
 ;
                         }
                     }
@@ -4823,6 +5144,8 @@ impl serde::Serialize for signature::Type {
             Self::Ed25519 => "TYPE_ED25519",
             Self::MultiEd25519 => "TYPE_MULTI_ED25519",
             Self::MultiAgent => "TYPE_MULTI_AGENT",
+            Self::FeePayer => "TYPE_FEE_PAYER",
+            Self::Secp256k1Ecdsa => "TYPE_SECP256K1_ECDSA",
         };
         serializer.serialize_str(variant)
     }
@@ -4838,6 +5161,8 @@ impl<'de> serde::Deserialize<'de> for signature::Type {
             "TYPE_ED25519",
             "TYPE_MULTI_ED25519",
             "TYPE_MULTI_AGENT",
+            "TYPE_FEE_PAYER",
+            "TYPE_SECP256K1_ECDSA",
         ];
 
         struct GeneratedVisitor;
@@ -4884,6 +5209,8 @@ impl<'de> serde::Deserialize<'de> for signature::Type {
                     "TYPE_ED25519" => Ok(signature::Type::Ed25519),
                     "TYPE_MULTI_ED25519" => Ok(signature::Type::MultiEd25519),
                     "TYPE_MULTI_AGENT" => Ok(signature::Type::MultiAgent),
+                    "TYPE_FEE_PAYER" => Ok(signature::Type::FeePayer),
+                    "TYPE_SECP256K1_ECDSA" => Ok(signature::Type::Secp256k1Ecdsa),
                     _ => Err(serde::de::Error::unknown_variant(value, FIELDS)),
                 }
             }
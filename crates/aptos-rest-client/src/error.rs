@@ -159,6 +159,8 @@ pub enum RestError {
     Unknown(anyhow::Error),
     #[error("HTTP error {0}: {1}")]
     Http(StatusCode, reqwest::Error),
+    #[error("Proof verification failed {0}")]
+    InvalidProof(anyhow::Error),
 }
 
 impl From<(AptosError, Option<State>, StatusCode)> for RestError {
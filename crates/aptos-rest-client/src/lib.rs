@@ -12,12 +12,15 @@ pub mod response;
 pub use response::Response;
 pub mod client_builder;
 pub mod state;
+pub mod stream;
 pub mod types;
+pub mod verifying_client;
 
 pub use crate::client_builder::{AptosBaseUrl, ClientBuilder};
 use crate::{
     aptos::{AptosVersion, Balance},
     error::RestError,
+    stream::{Follower, StreamFilter},
 };
 use anyhow::{anyhow, Result};
 pub use aptos_api_types::{
@@ -27,8 +30,9 @@ use aptos_api_types::{
     deserialize_from_string,
     mime_types::{BCS, BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, JSON},
     AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, IndexResponse, MoveModuleId,
-    TransactionData, TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction,
-    VersionedEvent, ViewRequest,
+    SimulateTransactionWithOverridesRequest, StateOverride, StateValueWithProof, TransactionData,
+    TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction, VersionedEvent,
+    ViewFunctionResult, ViewRequest,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::{debug, info, sample, sample::SampleRate};
@@ -36,9 +40,11 @@ use aptos_types::{
     account_address::AccountAddress,
     account_config::{AccountResource, CoinStoreResource, NewBlockEvent, CORE_CODE_ADDRESS},
     contract_event::EventWithVersion,
+    state_proof::StateProof,
     state_store::state_key::StateKey,
     transaction::SignedTransaction,
 };
+use futures::Stream;
 use move_core_types::language_storage::StructTag;
 use reqwest::{
     header::{ACCEPT, CONTENT_TYPE},
@@ -50,6 +56,7 @@ pub use state::State;
 use std::{collections::BTreeMap, future::Future, time::Duration};
 use tokio::time::Instant;
 pub use types::{deserialize_from_prefixed_hex_string, Account, Resource};
+pub use verifying_client::VerifyingClient;
 use url::Url;
 
 pub const DEFAULT_VERSION_PATH_BASE: &str = "v1/";
@@ -307,6 +314,30 @@ impl Client {
         self.json(response).await
     }
 
+    /// Executes many view functions against the state at the same version. Each result holds
+    /// either the return values of its view function or the error it failed with.
+    pub async fn view_batch(
+        &self,
+        requests: &[ViewRequest],
+        version: Option<u64>,
+    ) -> AptosResult<Response<Vec<ViewFunctionResult>>> {
+        let requests = serde_json::to_string(requests)?;
+        let mut url = self.build_path("view/batch")?;
+        if let Some(version) = version {
+            url.set_query(Some(format!("ledger_version={}", version).as_str()));
+        }
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, JSON)
+            .body(requests)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
     pub async fn simulate(
         &self,
         txn: &SignedTransaction,
@@ -349,6 +380,30 @@ impl Client {
         self.json(response).await
     }
 
+    /// Simulates the transaction against the latest state with `state_overrides` applied. The
+    /// overrides are only visible to the simulation.
+    pub async fn simulate_with_overrides(
+        &self,
+        txn: &SignedTransaction,
+        state_overrides: Vec<StateOverride>,
+    ) -> AptosResult<Response<Vec<UserTransaction>>> {
+        let request = serde_json::to_string(&SimulateTransactionWithOverridesRequest {
+            transaction: HexEncodedBytes::from(bcs::to_bytes(txn)?),
+            state_overrides,
+        })?;
+        let url = self.build_path("transactions/simulate_with_overrides")?;
+
+        let response = self
+            .inner
+            .post(url)
+            .header(CONTENT_TYPE, JSON)
+            .body(request)
+            .send()
+            .await?;
+
+        self.json(response).await
+    }
+
     pub async fn simulate_bcs(
         &self,
         txn: &SignedTransaction,
@@ -813,6 +868,52 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Streams the committed transactions matching `filter`, from `start_version` on or from
+    /// the next transaction to be committed if `None`. The stream follows the chain as new
+    /// transactions are committed, reconnecting and resuming after the last received
+    /// transaction whenever the connection drops. It only ends on errors.
+    pub async fn stream_transactions(
+        &self,
+        start_version: Option<u64>,
+        filter: StreamFilter,
+    ) -> AptosResult<impl Stream<Item = AptosResult<Transaction>>> {
+        let start_version = self.stream_start_version(start_version).await?;
+        Ok(Follower::new(
+            self.clone(),
+            "stream/transactions",
+            start_version,
+            filter,
+            |txn: &Transaction| txn.version().unwrap_or_default(),
+        )
+        .into_stream())
+    }
+
+    /// Streams the events matching `filter` emitted by committed transactions, with the same
+    /// semantics as [`Client::stream_transactions`].
+    pub async fn stream_events(
+        &self,
+        start_version: Option<u64>,
+        filter: StreamFilter,
+    ) -> AptosResult<impl Stream<Item = AptosResult<VersionedEvent>>> {
+        let start_version = self.stream_start_version(start_version).await?;
+        Ok(Follower::new(
+            self.clone(),
+            "stream/events",
+            start_version,
+            filter,
+            |event: &VersionedEvent| event.version.0,
+        )
+        .into_stream())
+    }
+
+    /// Pins the start of a stream, so that reconnections don't skip anything committed since.
+    async fn stream_start_version(&self, start_version: Option<u64>) -> AptosResult<u64> {
+        match start_version {
+            Some(start_version) => Ok(start_version),
+            None => Ok(self.get_ledger_information().await?.into_inner().version + 1),
+        }
+    }
+
     pub async fn get_transaction_by_hash(
         &self,
         hash: HashValue,
@@ -1280,6 +1381,79 @@ impl Client {
         Ok(response.map(|inner| inner.to_vec()))
     }
 
+    /// Gets a resource along with the proofs to verify it, see [`VerifyingClient`] for
+    /// verification.
+    pub async fn get_account_resource_with_proof(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        version: Option<u64>,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let mut url = self.build_path(&format!(
+            "accounts/{}/resource_with_proof/{}",
+            address, resource_type
+        ))?;
+        if let Some(version) = version {
+            url.query_pairs_mut()
+                .append_pair("ledger_version", &version.to_string());
+        }
+
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
+    /// Gets a table item along with the proofs to verify it, see [`VerifyingClient`] for
+    /// verification.
+    pub async fn get_raw_table_item_with_proof(
+        &self,
+        table_handle: AccountAddress,
+        key: &[u8],
+        version: Option<u64>,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let mut url =
+            self.build_path(&format!("tables/{}/raw_item_with_proof", table_handle))?;
+        if let Some(version) = version {
+            url.query_pairs_mut()
+                .append_pair("ledger_version", &version.to_string());
+        }
+        let data = json!({
+            "key": hex::encode(key),
+        });
+
+        let response = self.post_bcs(url, data).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
+    /// Gets a state value along with the proofs to verify it, see [`VerifyingClient`] for
+    /// verification.
+    pub async fn get_raw_state_value_with_proof(
+        &self,
+        state_key: &StateKey,
+        version: Option<u64>,
+    ) -> AptosResult<Response<StateValueWithProof>> {
+        let mut url = self.build_path("experimental/state_values/raw_with_proof")?;
+        if let Some(version) = version {
+            url.query_pairs_mut()
+                .append_pair("ledger_version", &version.to_string());
+        }
+        let data = json!({
+            "key": hex::encode(bcs::to_bytes(state_key)?),
+        });
+
+        let response = self.post_bcs(url, data).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
+    /// Gets the latest ledger info along with the epoch changes since `known_version`.
+    pub async fn get_state_proof(&self, known_version: u64) -> AptosResult<Response<StateProof>> {
+        let url = self.build_path(&format!(
+            "experimental/state_proof?known_version={}",
+            known_version
+        ))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_account(&self, address: AccountAddress) -> AptosResult<Response<Account>> {
         let url = self.build_path(&format!("accounts/{}", address))?;
         let response = self.inner.get(url).send().await?;
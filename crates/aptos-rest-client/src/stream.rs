@@ -0,0 +1,221 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client side of the streaming endpoints, which push committed transactions and events as
+//! server-sent events. Streams transparently reconnect when the connection drops, resuming
+//! right after the last item received.
+
+use crate::{error::RestError, AptosResult, Client};
+use aptos_types::account_address::AccountAddress;
+use bytes::Bytes;
+use futures::{
+    stream::{self, BoxStream},
+    Stream, StreamExt,
+};
+use serde::de::DeserializeOwned;
+use std::{collections::VecDeque, time::Duration};
+
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+
+/// Server-side filters of a stream, all of which must match.
+#[derive(Clone, Debug, Default)]
+pub struct StreamFilter {
+    /// Only user transactions sent by this account.
+    pub sender: Option<AccountAddress>,
+    /// Only user transactions calling this entry function, e.g. `0x1::coin::transfer`.
+    pub entry_function: Option<String>,
+    /// Only events of this type, or transactions emitting one, e.g. `0x1::coin::DepositEvent`.
+    pub event_type: Option<String>,
+}
+
+impl StreamFilter {
+    fn to_query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![];
+        if let Some(sender) = self.sender {
+            query.push(("sender", sender.to_hex_literal()));
+        }
+        if let Some(entry_function) = &self.entry_function {
+            query.push(("entry_function", entry_function.clone()));
+        }
+        if let Some(event_type) = &self.event_type {
+            query.push(("event_type", event_type.clone()));
+        }
+        query
+    }
+}
+
+/// Follows a streaming endpoint, reconnecting from where it left off on disconnection.
+pub(crate) struct Follower<T> {
+    client: Client,
+    path: &'static str,
+    filter: StreamFilter,
+    version_of: fn(&T) -> u64,
+    /// Version to (re)connect from.
+    resume_version: u64,
+    /// Number of items at `resume_version` already received, to be skipped on reconnection.
+    received_at_resume_version: usize,
+    /// Number of items still to skip on the current connection.
+    to_skip: usize,
+    body: Option<BoxStream<'static, reqwest::Result<Bytes>>>,
+    parser: SseParser,
+    pending: VecDeque<T>,
+    done: bool,
+}
+
+impl<T: DeserializeOwned + Send + 'static> Follower<T> {
+    pub(crate) fn new(
+        client: Client,
+        path: &'static str,
+        start_version: u64,
+        filter: StreamFilter,
+        version_of: fn(&T) -> u64,
+    ) -> Self {
+        Self {
+            client,
+            path,
+            filter,
+            version_of,
+            resume_version: start_version,
+            received_at_resume_version: 0,
+            to_skip: 0,
+            body: None,
+            parser: SseParser::default(),
+            pending: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    pub(crate) fn into_stream(self) -> impl Stream<Item = AptosResult<T>> {
+        stream::unfold(self, |mut follower| async move {
+            follower.next().await.map(|item| (item, follower))
+        })
+    }
+
+    async fn next(&mut self) -> Option<AptosResult<T>> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+
+            if self.body.is_none() {
+                match self.connect().await {
+                    Ok(body) => self.body = Some(body),
+                    Err(err) => {
+                        self.done = true;
+                        return Some(Err(err));
+                    },
+                }
+            }
+
+            match self.body.as_mut().expect("Connected above.").next().await {
+                Some(Ok(bytes)) => {
+                    for data in self.parser.feed(&bytes) {
+                        if let Err(err) = self.receive(&data) {
+                            self.done = true;
+                            return Some(Err(err));
+                        }
+                    }
+                },
+                // The connection dropped or the server closed the stream, resume.
+                Some(Err(_)) | None => {
+                    self.body = None;
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                },
+            }
+        }
+    }
+
+    async fn connect(&mut self) -> AptosResult<BoxStream<'static, reqwest::Result<Bytes>>> {
+        let url = self.client.build_path(self.path)?;
+        let response = self
+            .client
+            .inner
+            .get(url)
+            .query(&[("start_version", self.resume_version)])
+            .query(&self.filter.to_query())
+            .send()
+            .await?;
+        let (response, _state) = self.client.check_response(response).await?;
+
+        self.parser = SseParser::default();
+        self.to_skip = self.received_at_resume_version;
+        Ok(response.bytes_stream().boxed())
+    }
+
+    fn receive(&mut self, data: &str) -> AptosResult<()> {
+        let item: T = serde_json::from_str(data).map_err(RestError::Json)?;
+        let version = (self.version_of)(&item);
+        if version == self.resume_version && self.to_skip > 0 {
+            // Already received before reconnecting.
+            self.to_skip -= 1;
+            return Ok(());
+        }
+
+        if version == self.resume_version {
+            self.received_at_resume_version += 1;
+        } else {
+            self.resume_version = version;
+            self.received_at_resume_version = 1;
+        }
+        self.pending.push_back(item);
+        Ok(())
+    }
+}
+
+/// Incremental parser of a server-sent event stream, yielding the data of each event.
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    data: Option<String>,
+}
+
+impl SseParser {
+    fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut events = vec![];
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(&['\n', '\r'][..]);
+
+            if line.is_empty() {
+                // Blank line, dispatch the event if it had any data.
+                if let Some(data) = self.data.take() {
+                    events.push(data);
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match self.data.as_mut() {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    },
+                    None => self.data = Some(value.to_string()),
+                }
+            }
+            // Comments (keep-alives) and other fields are ignored.
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SseParser;
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"data: {\"a\"").is_empty());
+        assert!(parser.feed(b":1}\n").is_empty());
+        assert_eq!(parser.feed(b"\n: keep-alive\n\n"), vec!["{\"a\":1}"]);
+        assert_eq!(
+            parser.feed(b"event: message\r\ndata: 1\r\ndata: 2\r\n\r\ndata:3\n\n"),
+            vec!["1\n2", "3"]
+        );
+    }
+}
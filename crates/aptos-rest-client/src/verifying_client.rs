@@ -0,0 +1,438 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A client reading state from an untrusted node, verifying every response against a trusted
+//! waypoint.
+
+use crate::{error::RestError, AptosResult, Client};
+use anyhow::anyhow;
+use aptos_api_types::StateValueWithProof;
+use aptos_infallible::Mutex;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    epoch_change::{EpochChangeProof, Verifier},
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::{state_key::StateKey, state_value::StateValue, table::TableHandle},
+    transaction::Version,
+    trusted_state::{TrustedState, TrustedStateChange},
+    waypoint::Waypoint,
+};
+use move_core_types::language_storage::StructTag;
+use serde::de::DeserializeOwned;
+
+/// Wraps a [`Client`] to verify the state it reads with the proofs served by the node. The
+/// client keeps track of a [`TrustedState`], starting from a waypoint, and moves it forward
+/// through the epoch changes as the node serves newer ledger infos.
+pub struct VerifyingClient {
+    client: Client,
+    trusted_state: Mutex<TrustedState>,
+}
+
+impl VerifyingClient {
+    /// Creates a client trusting the given epoch waypoint, e.g. the genesis waypoint.
+    pub fn new(client: Client, waypoint: Waypoint) -> Self {
+        Self::from_trusted_state(client, TrustedState::from_epoch_waypoint(waypoint))
+    }
+
+    pub fn from_trusted_state(client: Client, trusted_state: TrustedState) -> Self {
+        Self {
+            client,
+            trusted_state: Mutex::new(trusted_state),
+        }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// The current trusted state, which can be persisted to resume from later.
+    pub fn trusted_state(&self) -> TrustedState {
+        self.trusted_state.lock().clone()
+    }
+
+    /// Moves the trusted state to the latest ledger info of the node, verifying the epoch
+    /// changes in between.
+    pub async fn sync(&self) -> AptosResult<()> {
+        let known_version = self.trusted_state().version();
+        let state_proof = self
+            .client
+            .get_state_proof(known_version)
+            .await?
+            .into_inner();
+
+        let mut trusted_state = self.trusted_state.lock();
+        if state_proof.latest_ledger_info().version() < trusted_state.version() {
+            // Raced with another sync, which got us further already.
+            return Ok(());
+        }
+        match trusted_state
+            .verify_and_ratchet(&state_proof)
+            .map_err(RestError::InvalidProof)?
+        {
+            TrustedStateChange::Version { new_state }
+            | TrustedStateChange::Epoch { new_state, .. } => *trusted_state = new_state,
+            TrustedStateChange::NoChange => {},
+        }
+        Ok(())
+    }
+
+    /// Gets a state value at the state checkpoint at `version`, or at the latest version if
+    /// `None`, verified against the trusted state. Returns `None` if the node proved the
+    /// value doesn't exist.
+    pub async fn get_state_value(
+        &self,
+        state_key: &StateKey,
+        version: Option<Version>,
+    ) -> AptosResult<Option<StateValue>> {
+        let value_with_proof = self
+            .client
+            .get_raw_state_value_with_proof(state_key, version)
+            .await?
+            .into_inner();
+        self.verify(state_key, version, value_with_proof).await
+    }
+
+    /// Gets a BCS encoded resource, verified against the trusted state. See
+    /// [`VerifyingClient::get_state_value`].
+    pub async fn get_account_resource_bcs<T: DeserializeOwned>(
+        &self,
+        address: AccountAddress,
+        resource_type: &StructTag,
+        version: Option<Version>,
+    ) -> AptosResult<Option<T>> {
+        let state_key = StateKey::access_path(AccessPath::resource_access_path(
+            address,
+            resource_type.clone(),
+        )?);
+        let value_with_proof = self
+            .client
+            .get_account_resource_with_proof(address, &resource_type.to_canonical_string(), version)
+            .await?
+            .into_inner();
+
+        match self.verify(&state_key, version, value_with_proof).await? {
+            Some(value) => Ok(Some(bcs::from_bytes(value.bytes())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets a table item, verified against the trusted state. See
+    /// [`VerifyingClient::get_state_value`].
+    pub async fn get_raw_table_item(
+        &self,
+        table_handle: AccountAddress,
+        key: &[u8],
+        version: Option<Version>,
+    ) -> AptosResult<Option<Vec<u8>>> {
+        let state_key = StateKey::table_item(TableHandle(table_handle), key.to_vec());
+        let value_with_proof = self
+            .client
+            .get_raw_table_item_with_proof(table_handle, key, version)
+            .await?
+            .into_inner();
+
+        Ok(self
+            .verify(&state_key, version, value_with_proof)
+            .await?
+            .map(StateValue::into_bytes))
+    }
+
+    async fn verify(
+        &self,
+        state_key: &StateKey,
+        version: Option<Version>,
+        value_with_proof: StateValueWithProof,
+    ) -> AptosResult<Option<StateValue>> {
+        if let Some(version) = version {
+            if value_with_proof.version != version {
+                return Err(RestError::InvalidProof(anyhow!(
+                    "Value read at version {}, expected {}",
+                    value_with_proof.version,
+                    version
+                )));
+            }
+        }
+        value_with_proof
+            .verify(state_key)
+            .map_err(RestError::InvalidProof)?;
+        self.verify_ledger_info(&value_with_proof.ledger_info, version.is_none())
+            .await?;
+
+        Ok(value_with_proof.value)
+    }
+
+    /// Verifies the signatures of a ledger info, syncing to its epoch first if needed. A ledger
+    /// info serving a read of the latest version must not be older than the trusted state.
+    async fn verify_ledger_info(
+        &self,
+        ledger_info: &LedgerInfoWithSignatures,
+        is_latest: bool,
+    ) -> AptosResult<()> {
+        if self.try_verify_ledger_info(ledger_info, is_latest)? {
+            return Ok(());
+        }
+        self.sync().await?;
+        if self.try_verify_ledger_info(ledger_info, is_latest)? {
+            return Ok(());
+        }
+        Err(RestError::InvalidProof(anyhow!(
+            "Ledger info in epoch {} can't be verified, trusted epoch is {:?}",
+            ledger_info.ledger_info().epoch(),
+            self.trusted_epoch(),
+        )))
+    }
+
+    /// Verifies a ledger info, moving the trusted state to it if it's newer. Returns false if
+    /// the ledger info isn't in the trusted epoch.
+    fn try_verify_ledger_info(
+        &self,
+        ledger_info: &LedgerInfoWithSignatures,
+        is_latest: bool,
+    ) -> AptosResult<bool> {
+        let mut trusted_state = self.trusted_state.lock();
+        match &*trusted_state {
+            TrustedState::EpochState { epoch_state, .. }
+                if epoch_state.epoch == ledger_info.ledger_info().epoch() => {},
+            _ => return Ok(false),
+        }
+
+        if ledger_info.ledger_info().version() < trusted_state.version() {
+            // Otherwise a node could serve any old state as the latest one.
+            if is_latest {
+                return Err(RestError::InvalidProof(anyhow!(
+                    "Latest ledger info at version {} is older than the trusted version {}",
+                    ledger_info.ledger_info().version(),
+                    trusted_state.version(),
+                )));
+            }
+            trusted_state
+                .verify(ledger_info)
+                .map_err(RestError::InvalidProof)?;
+        } else {
+            // An epoch ending ledger info moves us into the next epoch, which requires it to
+            // be in the epoch change proof as well.
+            let epoch_change_proof = if ledger_info.ledger_info().ends_epoch() {
+                EpochChangeProof::new(vec![ledger_info.clone()], false)
+            } else {
+                EpochChangeProof::new(vec![], false)
+            };
+            match trusted_state
+                .verify_and_ratchet_inner(ledger_info, &epoch_change_proof)
+                .map_err(RestError::InvalidProof)?
+            {
+                TrustedStateChange::Version { new_state }
+                | TrustedStateChange::Epoch { new_state, .. } => *trusted_state = new_state,
+                TrustedStateChange::NoChange => {},
+            }
+        }
+        Ok(true)
+    }
+
+    fn trusted_epoch(&self) -> Option<u64> {
+        match &*self.trusted_state.lock() {
+            TrustedState::EpochWaypoint(_) => None,
+            TrustedState::EpochState { epoch_state, .. } => Some(epoch_state.epoch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::{
+        hash::{CryptoHash, TransactionAccumulatorHasher},
+        HashValue,
+    };
+    use aptos_types::{
+        block_info::BlockInfo,
+        epoch_state::EpochState,
+        ledger_info::{generate_ledger_info_with_sig, LedgerInfo},
+        proof::{
+            accumulator::InMemoryAccumulator, SparseMerkleLeafNode, SparseMerkleProof,
+            TransactionAccumulatorProof, TransactionInfoWithProof,
+        },
+        transaction::{ExecutionStatus, TransactionInfo},
+        validator_signer::ValidatorSigner,
+        validator_verifier::generate_validator_verifier,
+    };
+    use url::Url;
+
+    /// Version the values are read at.
+    const VERSION: Version = 1;
+
+    fn state_key() -> StateKey {
+        StateKey::raw(b"key".to_vec())
+    }
+
+    fn validators(seed: u8, epoch: u64) -> (Vec<ValidatorSigner>, EpochState) {
+        let signers = vec![ValidatorSigner::random([seed; 32])];
+        let epoch_state = EpochState {
+            epoch,
+            verifier: generate_validator_verifier(&signers),
+        };
+        (signers, epoch_state)
+    }
+
+    /// A client trusting the given epoch, at the given version.
+    fn client(epoch_state: EpochState, version: Version) -> VerifyingClient {
+        let ledger_info = LedgerInfo::new(
+            BlockInfo::new(
+                epoch_state.epoch,
+                0,
+                HashValue::zero(),
+                HashValue::zero(),
+                version,
+                0,
+                None,
+            ),
+            HashValue::zero(),
+        );
+        VerifyingClient::from_trusted_state(
+            Client::new(Url::parse("http://localhost:8080").unwrap()),
+            TrustedState::EpochState {
+                waypoint: Waypoint::new_any(&ledger_info),
+                epoch_state,
+            },
+        )
+    }
+
+    /// Proves `value` at `VERSION`, against a ledger info of `epoch` signed by `signers`.
+    fn value_with_proof(
+        signers: &[ValidatorSigner],
+        epoch: u64,
+        next_epoch_state: Option<EpochState>,
+        value: StateValue,
+    ) -> StateValueWithProof {
+        let leaf = SparseMerkleLeafNode::new(state_key().hash(), value.hash());
+        let transaction_info = TransactionInfo::new(
+            HashValue::zero(),
+            HashValue::zero(),
+            HashValue::zero(),
+            Some(leaf.hash()),
+            0,
+            ExecutionStatus::Success,
+        );
+        // The transaction at `VERSION` follows another one in the accumulator.
+        let previous_transaction = HashValue::sha3_256_of(b"previous transaction");
+        let accumulator = InMemoryAccumulator::<TransactionAccumulatorHasher>::from_leaves(&[
+            previous_transaction,
+            transaction_info.hash(),
+        ]);
+        let ledger_info = LedgerInfo::new(
+            BlockInfo::new(
+                epoch,
+                1,
+                HashValue::zero(),
+                accumulator.root_hash(),
+                VERSION,
+                0,
+                next_epoch_state,
+            ),
+            HashValue::zero(),
+        );
+        StateValueWithProof {
+            version: VERSION,
+            value: Some(value),
+            proof: SparseMerkleProof::new(Some(leaf), vec![]),
+            transaction_info_with_proof: TransactionInfoWithProof::new(
+                TransactionAccumulatorProof::new(vec![previous_transaction]),
+                transaction_info,
+            ),
+            ledger_info: generate_ledger_info_with_sig(signers, ledger_info),
+        }
+    }
+
+    fn value() -> StateValue {
+        StateValue::new_legacy(b"value".to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_verify() {
+        let (signers, epoch_state) = validators(1, 1);
+        let client = client(epoch_state, 0);
+
+        let response = value_with_proof(&signers, 1, None, value());
+        assert_eq!(
+            client.verify(&state_key(), None, response).await.unwrap(),
+            Some(value())
+        );
+        // The trusted state moved to the newer ledger info.
+        assert_eq!(client.trusted_state().version(), VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_bad_proof() {
+        let (signers, epoch_state) = validators(1, 1);
+        let client = client(epoch_state, 0);
+
+        let mut response = value_with_proof(&signers, 1, None, value());
+        response.value = Some(StateValue::new_legacy(b"another value".to_vec()));
+        assert!(matches!(
+            client.verify(&state_key(), None, response).await,
+            Err(RestError::InvalidProof(_))
+        ));
+
+        // Signed by validators not in the epoch.
+        let (other_signers, _) = validators(2, 1);
+        let response = value_with_proof(&other_signers, 1, None, value());
+        assert!(matches!(
+            client.verify(&state_key(), None, response).await,
+            Err(RestError::InvalidProof(_))
+        ));
+        assert_eq!(client.trusted_state().version(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stale_ledger_info() {
+        let (signers, epoch_state) = validators(1, 1);
+        let client = client(epoch_state, VERSION + 1);
+
+        // A ledger info older than the trusted one can't prove the latest state.
+        let response = value_with_proof(&signers, 1, None, value());
+        assert!(matches!(
+            client.verify(&state_key(), None, response).await,
+            Err(RestError::InvalidProof(_))
+        ));
+
+        // It can prove the state at its own version.
+        let response = value_with_proof(&signers, 1, None, value());
+        assert_eq!(
+            client
+                .verify(&state_key(), Some(VERSION), response)
+                .await
+                .unwrap(),
+            Some(value())
+        );
+        assert_eq!(client.trusted_state().version(), VERSION + 1);
+    }
+
+    #[tokio::test]
+    async fn test_epoch_change() {
+        let (signers, epoch_state) = validators(1, 1);
+        let (_, next_epoch_state) = validators(2, 2);
+        let client = client(epoch_state, 0);
+
+        let response = value_with_proof(&signers, 1, Some(next_epoch_state), value());
+        assert_eq!(
+            client.verify(&state_key(), None, response).await.unwrap(),
+            Some(value())
+        );
+        assert_eq!(client.trusted_epoch(), Some(2));
+        assert_eq!(client.trusted_state().version(), VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_version_mismatch() {
+        let (signers, epoch_state) = validators(1, 1);
+        let client = client(epoch_state, 0);
+
+        let response = value_with_proof(&signers, 1, None, value());
+        assert!(matches!(
+            client
+                .verify(&state_key(), Some(VERSION + 1), response)
+                .await,
+            Err(RestError::InvalidProof(_))
+        ));
+    }
+}
@@ -300,6 +300,10 @@ impl From<RestError> for ApiError {
                 AptosErrorCode::BcsNotSupported => ApiError::InvalidInput(Some(err.error.message)),
                 AptosErrorCode::InternalError => ApiError::InternalError(Some(err.error.message)),
                 AptosErrorCode::ApiDisabled => ApiError::InternalError(Some(err.error.message)),
+                AptosErrorCode::InvalidApiKey => ApiError::InternalError(Some(err.error.message)),
+                AptosErrorCode::RateLimitExceeded => {
+                    ApiError::InternalError(Some(err.error.message))
+                },
             },
             RestError::Bcs(_) => ApiError::DeserializationFailed(None),
             RestError::Json(_) => ApiError::DeserializationFailed(None),
@@ -310,6 +314,7 @@ impl From<RestError> for ApiError {
             RestError::UrlParse(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Timeout(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Unknown(err) => ApiError::InternalError(Some(err.to_string())),
+            RestError::InvalidProof(err) => ApiError::InternalError(Some(err.to_string())),
         }
     }
 }
@@ -27,7 +27,7 @@ use aptos_global_constants::adjust_gas_headroom;
 use aptos_keygen::KeyGen;
 use aptos_logger::Level;
 use aptos_rest_client::{
-    aptos_api_types::{EntryFunctionId, HashValue, MoveType, ViewRequest},
+    aptos_api_types::{EntryFunctionId, HashValue, MoveType, StateOverride, ViewRequest},
     error::RestError,
     AptosBaseUrl, Client, Transaction,
 };
@@ -35,8 +35,9 @@ use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
 use aptos_types::{
     chain_id::ChainId,
     transaction::{
-        authenticator::AuthenticationKey, EntryFunction, MultisigTransactionPayload, Script,
-        SignedTransaction, TransactionArgument, TransactionPayload, TransactionStatus,
+        authenticator::{AccountAuthenticator, AuthenticationKey},
+        EntryFunction, MultisigTransactionPayload, RawTransaction, Script, SignedTransaction,
+        TransactionArgument, TransactionPayload, TransactionStatus,
     },
 };
 use async_trait::async_trait;
@@ -367,6 +368,8 @@ pub enum KeyType {
     X25519,
     /// A BLS12381 key for consensus
     Bls12381,
+    /// Secp256k1 ECDSA key used for signing
+    Secp256k1,
 }
 
 impl Display for KeyType {
@@ -375,6 +378,7 @@ impl Display for KeyType {
             KeyType::Ed25519 => "ed25519",
             KeyType::X25519 => "x25519",
             KeyType::Bls12381 => "bls12381",
+            KeyType::Secp256k1 => "secp256k1",
         };
         write!(f, "{}", str)
     }
@@ -388,7 +392,8 @@ impl FromStr for KeyType {
             "ed25519" => Ok(KeyType::Ed25519),
             "x25519" => Ok(KeyType::X25519),
             "bls12381" => Ok(KeyType::Bls12381),
-            _ => Err("Invalid key type: Must be one of [ed25519, x25519]"),
+            "secp256k1" => Ok(KeyType::Secp256k1),
+            _ => Err("Invalid key type: Must be one of [ed25519, x25519, bls12381, secp256k1]"),
         }
     }
 }
@@ -1315,6 +1320,32 @@ pub struct TransactionOptions {
     /// flamegraphs that reflect the gas usage.
     #[clap(long)]
     pub(crate) profile_gas: bool,
+
+    /// If this option is set, simulate the transaction on the remote node instead of submitting
+    /// it.
+    #[clap(long, conflicts_with = "profile-gas")]
+    pub(crate) simulate: bool,
+
+    /// If this option is set, simulate the transaction locally using the debugger and save a JSON
+    /// trace of its execution, with the arguments, return values, resource accesses and events
+    /// of every call.
+    #[clap(long, conflicts_with_all = &["profile-gas", "simulate"])]
+    pub(crate) trace: bool,
+
+    /// JSON file of state overrides to apply when simulating
+    ///
+    /// The file holds a list of account state overrides, in the format of `StateOverride` in the
+    /// REST API, e.g. `[{"address": "0x1", "coin_balance": "100", "resources": [], "modules": []}]`.
+    /// The overrides are only visible to the simulation.
+    #[clap(long, requires = "simulate", parse(from_os_str))]
+    pub(crate) state_overrides_file: Option<PathBuf>,
+
+    /// Profile of the account paying for the gas of the transaction
+    ///
+    /// If set, the transaction is also signed by this account, which is charged for the gas
+    /// instead of the sender.
+    #[clap(long)]
+    pub(crate) fee_payer_profile: Option<String>,
 }
 
 impl TransactionOptions {
@@ -1337,6 +1368,26 @@ impl TransactionOptions {
         Ok(self.get_key_and_address()?.1)
     }
 
+    /// Retrieves the private key and address of the fee payer, if any
+    pub fn get_fee_payer_key_and_address(
+        &self,
+    ) -> CliTypedResult<Option<(Ed25519PrivateKey, AccountAddress)>> {
+        let profile_name = match self.fee_payer_profile.as_ref() {
+            Some(profile_name) => profile_name.trim(),
+            None => return Ok(None),
+        };
+        let profile =
+            CliConfig::load_profile(Some(profile_name), ConfigSearchMode::CurrentDirAndParents)?
+                .ok_or_else(|| CliError::ConfigNotFoundError(profile_name.to_string()))?;
+        match (profile.private_key, profile.account) {
+            (Some(private_key), Some(address)) => Ok(Some((private_key, address))),
+            _ => Err(CliError::CommandArgumentError(format!(
+                "Fee payer profile {} must have a private key and an account",
+                profile_name
+            ))),
+        }
+    }
+
     /// Gets the auth key by account address. We need to fetch the auth key from Rest API rather than creating an
     /// auth key out of the public key.
     pub(crate) async fn auth_key(
@@ -1364,6 +1415,7 @@ impl TransactionOptions {
     ) -> CliTypedResult<Transaction> {
         let client = self.rest_client()?;
         let (sender_key, sender_address) = self.get_key_and_address()?;
+        let fee_payer = self.get_fee_payer_key_and_address()?;
 
         // Ask to confirm price if the gas unit price is estimated above the lowest value when
         // it is automatically estimated
@@ -1417,11 +1469,8 @@ impl TransactionOptions {
                 .expiration_timestamp_secs(expiration_time_secs)
                 .build();
 
-            let signed_transaction = SignedTransaction::new(
-                unsigned_transaction,
-                sender_key.public_key(),
-                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
-            );
+            let signed_transaction =
+                sign_for_simulation(unsigned_transaction, &sender_key, fee_payer.as_ref());
 
             let txns = client
                 .simulate_with_gas_estimation(&signed_transaction, true, false)
@@ -1459,8 +1508,16 @@ impl TransactionOptions {
             .with_max_gas_amount(max_gas)
             .with_transaction_expiration_time(self.gas_options.expiration_secs);
         let sender_account = &mut LocalAccount::new(sender_address, sender_key, sequence_number);
-        let transaction =
-            sender_account.sign_with_transaction_builder(transaction_factory.payload(payload));
+        let transaction = if let Some((fee_payer_key, fee_payer_address)) = fee_payer {
+            let fee_payer_account = LocalAccount::new(fee_payer_address, fee_payer_key, 0);
+            sender_account.sign_fee_payer_with_transaction_builder(
+                vec![],
+                &fee_payer_account,
+                transaction_factory.payload(payload),
+            )
+        } else {
+            sender_account.sign_with_transaction_builder(transaction_factory.payload(payload))
+        };
         let response = client
             .submit_and_wait(&transaction)
             .await
@@ -1469,17 +1526,61 @@ impl TransactionOptions {
         Ok(response.into_inner())
     }
 
-    /// Simulate the transaction locally using the debugger, with the gas profiler enabled.
-    pub async fn profile_gas(
+    /// Simulate the transaction on the remote node, with the state overrides of
+    /// `--state-overrides-file` applied if set. Nothing is submitted.
+    pub async fn simulate_transaction(
         &self,
         payload: TransactionPayload,
     ) -> CliTypedResult<TransactionSummary> {
-        println!();
-        println!("Simulating transaction locally with the gas profiler...");
-        println!("This is still experimental so results may be inaccurate.");
-
         let client = self.rest_client()?;
+        let (sender_key, sender_address) = self.get_key_and_address()?;
+        let fee_payer = self.get_fee_payer_key_and_address()?;
+        let state_overrides: Vec<StateOverride> = match self.state_overrides_file.as_ref() {
+            Some(path) => parse_json_file(path)?,
+            None => vec![],
+        };
+
+        let gas_unit_price = if let Some(gas_unit_price) = self.gas_options.gas_unit_price {
+            gas_unit_price
+        } else {
+            client.estimate_gas_price().await?.into_inner().gas_estimate
+        };
+        let (account, state) = get_account_with_state(&client, sender_address).await?;
+
+        let mut transaction_factory = TransactionFactory::new(ChainId::new(state.chain_id))
+            .with_gas_unit_price(gas_unit_price)
+            .with_transaction_expiration_time(self.gas_options.expiration_secs);
+        if let Some(max_gas) = self.gas_options.max_gas {
+            transaction_factory = transaction_factory.with_max_gas_amount(max_gas);
+        }
+        let unsigned_transaction = transaction_factory
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(account.sequence_number)
+            .build();
+        let signed_transaction =
+            sign_for_simulation(unsigned_transaction, &sender_key, fee_payer.as_ref());
+
+        let simulated_txn = client
+            .simulate_with_overrides(&signed_transaction, state_overrides)
+            .await?
+            .into_inner()
+            .pop()
+            .ok_or_else(|| {
+                CliError::UnexpectedError("Simulation returned no transaction".to_string())
+            })?;
+        Ok(TransactionSummary::from(Transaction::UserTransaction(
+            Box::new(simulated_txn),
+        )))
+    }
 
+    /// Create and sign a transaction for local simulation with the debugger, against the latest
+    /// state of the chain. Returns the transaction along with the version to simulate it at.
+    async fn sign_transaction_for_local_simulation(
+        &self,
+        client: &Client,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<(SignedTransaction, u64)> {
         // Fetch the chain states required for the simulation
         // TODO(Gas): get the following from the chain
         const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
@@ -1490,7 +1591,7 @@ impl TransactionOptions {
             .gas_options
             .gas_unit_price
             .unwrap_or(DEFAULT_GAS_UNIT_PRICE);
-        let (account, state) = get_account_with_state(&client, sender_address).await?;
+        let (account, state) = get_account_with_state(client, sender_address).await?;
         let version = state.version;
         let chain_id = ChainId::new(state.chain_id);
         let sequence_number = account.sequence_number;
@@ -1517,11 +1618,29 @@ impl TransactionOptions {
         let sender_account = &mut LocalAccount::new(sender_address, sender_key, sequence_number);
         let transaction =
             sender_account.sign_with_transaction_builder(transaction_factory.payload(payload));
+
+        Ok((transaction, version))
+    }
+
+    /// Simulate the transaction locally using the debugger, with the gas profiler enabled.
+    pub async fn profile_gas(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        println!();
+        println!("Simulating transaction locally with the gas profiler...");
+        println!("This is still experimental so results may be inaccurate.");
+
+        let client = self.rest_client()?;
+        let (transaction, version) = self
+            .sign_transaction_for_local_simulation(&client, payload)
+            .await?;
         let hash = transaction.clone().committed_hash();
 
         // Execute the transaction using the debugger
         let debugger = AptosDebugger::rest_client(client).unwrap();
-        let res = debugger.execute_transaction_at_version_with_gas_profiler(version, transaction);
+        let res =
+            debugger.execute_transaction_at_version_with_gas_profiler(version, transaction.clone());
         let (vm_status, output, gas_log) = res.map_err(|err| {
             CliError::UnexpectedError(format!("failed to simulate txn with gas profiler: {}", err))
         })?;
@@ -1611,26 +1730,67 @@ impl TransactionOptions {
 
         println!();
 
-        // Generate the transaction summary
+        Ok(local_simulation_summary(
+            &transaction,
+            version,
+            output.status(),
+            output.gas_used(),
+            vm_status.to_string(),
+        ))
+    }
 
-        // TODO(Gas): double check if this is correct.
-        let success = match output.status() {
-            TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
-            TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
-        };
+    /// Simulate the transaction locally using the debugger, with the execution tracer enabled.
+    pub async fn trace_transaction(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        println!();
+        println!("Simulating transaction locally with the execution tracer...");
 
-        Ok(TransactionSummary {
-            transaction_hash: hash.into(),
-            gas_used: Some(output.gas_used()),
-            gas_unit_price: Some(gas_unit_price),
-            pending: None,
-            sender: Some(sender_address),
-            sequence_number: None, // The transaction is not comitted so there is no new sequence number.
-            success,
-            timestamp_us: None,
-            version: Some(version), // The transaction is not comitted so there is no new version.
-            vm_status: Some(vm_status.to_string()),
-        })
+        let client = self.rest_client()?;
+        let (transaction, version) = self
+            .sign_transaction_for_local_simulation(&client, payload)
+            .await?;
+        let hash = transaction.clone().committed_hash();
+
+        // Execute the transaction using the debugger
+        let debugger = AptosDebugger::rest_client(client).unwrap();
+        let res = debugger.execute_transaction_at_version_with_tracer(version, transaction.clone());
+        let (vm_status, output, trace) = res.map_err(|err| {
+            CliError::UnexpectedError(format!("failed to simulate txn with tracer: {}", err))
+        })?;
+
+        // Save the trace
+        let dir: &Path = Path::new("execution-traces");
+        if let Err(err) = std::fs::create_dir(dir) {
+            if err.kind() != std::io::ErrorKind::AlreadyExists {
+                return Err(CliError::UnexpectedError(format!(
+                    "failed to create directory {}",
+                    dir.display()
+                )));
+            }
+        }
+        let trace_file_path = Path::join(dir, format!("txn-{}.json", hash));
+        let trace_bytes = serde_json::to_vec_pretty(&trace)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        std::fs::write(&trace_file_path, trace_bytes).map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to write execution trace to file {} : {:?}",
+                trace_file_path.display(),
+                err
+            ))
+        })?;
+        println!();
+        println!("Execution trace saved to {}", trace_file_path.display());
+        println!();
+
+        Ok(local_simulation_summary(
+            &transaction,
+            version,
+            output.status(),
+            output.gas_used(),
+            vm_status.to_string(),
+        ))
     }
 
     pub async fn estimate_gas_price(&self) -> CliTypedResult<u64> {
@@ -1648,6 +1808,55 @@ impl TransactionOptions {
     }
 }
 
+/// Summarizes a transaction that was simulated locally with the debugger at the given version.
+fn local_simulation_summary(
+    transaction: &SignedTransaction,
+    version: u64,
+    status: &TransactionStatus,
+    gas_used: u64,
+    vm_status: String,
+) -> TransactionSummary {
+    // TODO(Gas): double check if this is correct.
+    let success = match status {
+        TransactionStatus::Keep(exec_status) => Some(exec_status.is_success()),
+        TransactionStatus::Discard(_) | TransactionStatus::Retry => None,
+    };
+
+    TransactionSummary {
+        transaction_hash: transaction.clone().committed_hash().into(),
+        gas_used: Some(gas_used),
+        gas_unit_price: Some(transaction.gas_unit_price()),
+        pending: None,
+        sender: Some(transaction.sender()),
+        sequence_number: None, // The transaction is not comitted so there is no new sequence number.
+        success,
+        timestamp_us: None,
+        version: Some(version), // The transaction is not comitted so there is no new version.
+        vm_status: Some(vm_status),
+    }
+}
+
+/// Signs a transaction with zeroed signatures, which the simulation API requires
+fn sign_for_simulation(
+    unsigned_transaction: RawTransaction,
+    sender_key: &Ed25519PrivateKey,
+    fee_payer: Option<&(Ed25519PrivateKey, AccountAddress)>,
+) -> SignedTransaction {
+    let signature = Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap();
+    if let Some((fee_payer_key, fee_payer_address)) = fee_payer {
+        SignedTransaction::new_fee_payer(
+            unsigned_transaction,
+            AccountAuthenticator::ed25519(sender_key.public_key(), signature.clone()),
+            vec![],
+            vec![],
+            *fee_payer_address,
+            AccountAuthenticator::ed25519(fee_payer_key.public_key(), signature),
+        )
+    } else {
+        SignedTransaction::new(unsigned_transaction, sender_key.public_key(), signature)
+    }
+}
+
 #[derive(Parser)]
 pub struct OptionalPoolAddressArgs {
     /// Address of the Staking pool
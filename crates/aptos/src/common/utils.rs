@@ -457,7 +457,7 @@ pub fn start_logger(level: Level) {
     logger.build();
 }
 
-/// For transaction payload and options, either get gas profile or submit for execution.
+/// For transaction payload and options, either get gas profile, simulate, or submit for execution.
 pub async fn profile_or_submit(
     payload: TransactionPayload,
     txn_options_ref: &TransactionOptions,
@@ -465,6 +465,10 @@ pub async fn profile_or_submit(
     // Profile gas if needed.
     if txn_options_ref.profile_gas {
         txn_options_ref.profile_gas(payload).await
+    } else if txn_options_ref.simulate {
+        txn_options_ref.simulate_transaction(payload).await
+    } else if txn_options_ref.trace {
+        txn_options_ref.trace_transaction(payload).await
     } else {
         // Otherwise submit the transaction.
         txn_options_ref
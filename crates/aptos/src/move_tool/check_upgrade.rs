@@ -0,0 +1,199 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions, RestOptions,
+        },
+        utils::{read_from_file, write_to_file},
+    },
+    move_tool::IncludedArtifacts,
+};
+use aptos_framework::{
+    natives::code::{PackageMetadata, PackageRegistry},
+    upgrade_check::{
+        check_upgrade, is_policy_exempted_address, PublishedState, FEATURES_RESOURCE,
+        PACKAGE_REGISTRY_RESOURCE,
+    },
+    BuildOptions, BuiltPackage,
+};
+use aptos_rest_client::{
+    aptos_api_types::{AptosError, AptosErrorCode},
+    error::{AptosErrorResponse, RestError},
+    Client,
+};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use serde::de::DeserializeOwned;
+use std::{collections::BTreeSet, path::PathBuf};
+
+/// Checks whether a package can be published as an upgrade of the one on chain
+///
+/// The package is built locally and checked against the packages and modules published at the
+/// account, without submitting a transaction. Every change which would make publishing fail is
+/// reported: breaking changes to modules (removed functions, changed signatures, struct layout,
+/// abilities), removed modules, and violations of the upgrade policy by the package or its
+/// dependencies.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Address of the account the package is published at
+    ///
+    /// Defaults to the account of the profile. Ignored when reading a snapshot.
+    #[clap(long, parse(try_from_str = crate::common::types::load_account_arg))]
+    pub(crate) account: Option<AccountAddress>,
+
+    /// Check against the published state saved in this file, instead of fetching it
+    #[clap(long, parse(from_os_str))]
+    pub(crate) snapshot: Option<PathBuf>,
+
+    /// Save the published state fetched from the node to this file, to check against it later
+    #[clap(long, parse(from_os_str), conflicts_with = "snapshot")]
+    pub(crate) save_snapshot: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let build_options = BuildOptions {
+            install_dir: self.move_options.output_dir.clone(),
+            bytecode_version: self.move_options.bytecode_version,
+            ..IncludedArtifacts::None.build_options(
+                self.move_options.skip_fetch_latest_git_deps,
+                self.move_options.named_addresses(),
+                self.move_options.bytecode_version,
+            )
+        };
+        let package = BuiltPackage::build(self.move_options.get_package_path()?, build_options)
+            .map_err(|e| CliError::MoveCompilationError(format!("{:#}", e)))?;
+        let metadata = package.extract_metadata()?;
+
+        let state = if let Some(snapshot) = &self.snapshot {
+            bcs::from_bytes(&read_from_file(snapshot)?)
+                .map_err(|err| CliError::BCS("snapshot", err))?
+        } else {
+            let address = match self.account {
+                Some(account) => account,
+                None => self.profile_options.account_address()?,
+            };
+            let client = Client::new(self.rest_options.url(&self.profile_options)?);
+            let state = fetch_published_state(&client, address, &metadata).await?;
+            if let Some(path) = &self.save_snapshot {
+                write_to_file(path, "snapshot", &bcs::to_bytes(&state)?)?;
+            }
+            state
+        };
+
+        let changes = check_upgrade(&state, &metadata, package.modules())?;
+        if changes.is_empty() {
+            Ok(format!(
+                "Package `{}` can be published at {}",
+                metadata.name, state.address
+            ))
+        } else {
+            Err(CliError::UnexpectedError(format!(
+                "Package `{}` cannot be published at {}:\n{}",
+                metadata.name,
+                state.address,
+                changes
+                    .iter()
+                    .map(|change| format!("  - {}", change))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )))
+        }
+    }
+}
+
+/// Fetches the state needed to check the package with the given metadata: the package registries
+/// of the address and of the dependencies, the published version of the modules of the package,
+/// and the feature flags.
+async fn fetch_published_state(
+    client: &Client,
+    address: AccountAddress,
+    metadata: &PackageMetadata,
+) -> CliTypedResult<PublishedState> {
+    let features = get_resource(client, AccountAddress::ONE, FEATURES_RESOURCE)
+        .await?
+        .unwrap_or_default();
+    let mut state = PublishedState::new(address, features);
+
+    let accounts: BTreeSet<_> = std::iter::once(address)
+        .chain(
+            metadata
+                .deps
+                .iter()
+                .map(|dep| dep.account)
+                .filter(|account| !is_policy_exempted_address(*account)),
+        )
+        .collect();
+    for account in accounts {
+        if let Some(registry) =
+            get_resource::<PackageRegistry>(client, account, PACKAGE_REGISTRY_RESOURCE).await?
+        {
+            state.registries.insert(account, registry);
+        }
+    }
+
+    let published: BTreeSet<_> = state
+        .registries
+        .get(&address)
+        .into_iter()
+        .flat_map(|registry| &registry.packages)
+        .flat_map(|package| &package.modules)
+        .map(|module| module.name.clone())
+        .collect();
+    for module in &metadata.modules {
+        if published.contains(&module.name) {
+            let bytes = client
+                .get_account_module_bcs(address, &module.name)
+                .await?
+                .into_inner();
+            state.modules.insert(module.name.clone(), bytes.to_vec());
+        }
+    }
+    Ok(state)
+}
+
+/// Fetches a resource, returning `None` if it or its account does not exist.
+async fn get_resource<T: DeserializeOwned>(
+    client: &Client,
+    address: AccountAddress,
+    resource: &str,
+) -> CliTypedResult<Option<T>> {
+    match client
+        .get_account_resource_bcs::<T>(address, resource)
+        .await
+    {
+        Ok(response) => Ok(Some(response.into_inner())),
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::ResourceNotFound,
+                    ..
+                },
+            ..
+        }))
+        | Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::AccountNotFound,
+                    ..
+                },
+            ..
+        })) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Wire format of the Debug Adapter Protocol, see
+//! <https://microsoft.github.io/debug-adapter-protocol/specification>.
+//!
+//! Each message is a JSON object preceded by a `Content-Length` header.
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+/// A request of the client.
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    pub seq: u64,
+    pub command: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+/// Reads the next message of the client, `None` if the connection was closed.
+pub fn read_request(reader: &mut impl BufRead) -> anyhow::Result<Option<Request>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(length) = header.strip_prefix("Content-Length:") {
+            content_length = Some(
+                length
+                    .trim()
+                    .parse::<usize>()
+                    .context("Invalid Content-Length")?,
+            );
+        }
+    }
+    let content_length = match content_length {
+        Some(content_length) => content_length,
+        None => bail!("Missing Content-Length header"),
+    };
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(
+        serde_json::from_slice(&content).context("Invalid request")?,
+    ))
+}
+
+/// Sends responses and events to the client.
+pub struct Sender<W> {
+    writer: W,
+    seq: u64,
+}
+
+impl<W: Write> Sender<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, seq: 0 }
+    }
+
+    pub fn respond(&mut self, request: &Request, body: Value) -> anyhow::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "command": request.command,
+            "success": true,
+            "body": body,
+        }))
+    }
+
+    pub fn respond_error(&mut self, request: &Request, message: &str) -> anyhow::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "command": request.command,
+            "success": false,
+            "message": message,
+        }))
+    }
+
+    pub fn event(&mut self, event: &str, body: Value) -> anyhow::Result<()> {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }))
+    }
+
+    fn send(&mut self, mut message: Value) -> anyhow::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        let content = serde_json::to_vec(&message)?;
+        write!(self.writer, "Content-Length: {}\r\n\r\n", content.len())?;
+        self.writer.write_all(&content)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_requests() {
+        let first = r#"{"seq":1,"command":"initialize","arguments":{"adapterID":"move"}}"#;
+        let second = r#"{"seq":2,"type":"request","command":"threads"}"#;
+        let input = format!(
+            "Content-Length: {}\r\n\r\n{}Content-Length: {}\r\n\r\n{}",
+            first.len(),
+            first,
+            second.len(),
+            second
+        );
+        let mut reader = Cursor::new(input);
+
+        let request = read_request(&mut reader).unwrap().unwrap();
+        assert_eq!(request.seq, 1);
+        assert_eq!(request.command, "initialize");
+        assert_eq!(request.arguments["adapterID"], "move");
+        let request = read_request(&mut reader).unwrap().unwrap();
+        assert_eq!(request.command, "threads");
+        assert_eq!(request.arguments, Value::Null);
+        assert!(read_request(&mut reader).unwrap().is_none());
+
+        let mut reader = Cursor::new("Content-Type: json\r\n\r\n{}");
+        assert!(read_request(&mut reader).is_err());
+    }
+
+    #[test]
+    fn test_send() {
+        let request = Request {
+            seq: 7,
+            command: "threads".to_string(),
+            arguments: Value::Null,
+        };
+        let mut sender = Sender::new(vec![]);
+        sender.respond(&request, json!({"threads": []})).unwrap();
+        sender.event("initialized", json!({})).unwrap();
+
+        let mut reader = Cursor::new(sender.writer);
+        let mut messages = vec![];
+        while let Some(message) = read_message(&mut reader) {
+            messages.push(message);
+        }
+        assert_eq!(messages[0]["seq"], 1);
+        assert_eq!(messages[0]["request_seq"], 7);
+        assert_eq!(messages[0]["success"], true);
+        assert_eq!(messages[1]["seq"], 2);
+        assert_eq!(messages[1]["event"], "initialized");
+    }
+
+    fn read_message(reader: &mut impl BufRead) -> Option<Value> {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let length = header
+            .trim_end()
+            .strip_prefix("Content-Length: ")?
+            .parse::<usize>()
+            .ok()?;
+        reader.read_line(&mut header).ok()?;
+        let mut content = vec![0; length];
+        reader.read_exact(&mut content).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+}
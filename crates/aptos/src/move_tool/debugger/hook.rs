@@ -0,0 +1,260 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    sources::{CodeLocation, SourceIndex, SourceLine},
+    SessionEvent,
+};
+use move_binary_format::file_format::FunctionDefinitionIndex;
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::debugger::{DebugHook, InterpreterState};
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+/// How execution continues after a stop.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Resume {
+    Continue,
+    /// Stop at the next source line, including in called functions.
+    StepIn,
+    /// Stop at the next source line of the current function or of its callers.
+    StepOver,
+    /// Stop once the current function returned.
+    StepOut,
+}
+
+/// Breakpoints shared between the session, which sets them, and the hook, which checks them.
+#[derive(Default)]
+pub struct Breakpoints(Mutex<HashMap<ModuleId, HashSet<(FunctionDefinitionIndex, u16)>>>);
+
+impl Breakpoints {
+    pub fn set(&self, instructions: &BTreeSet<CodeLocation>) {
+        let mut breakpoints = self.0.lock().unwrap();
+        breakpoints.clear();
+        for instruction in instructions {
+            breakpoints
+                .entry(instruction.module_id.clone())
+                .or_default()
+                .insert((instruction.function_index, instruction.pc));
+        }
+    }
+
+    fn contains(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        pc: u16,
+    ) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .get(module_id)
+            .map_or(false, |instructions| {
+                instructions.contains(&(function_index, pc))
+            })
+    }
+}
+
+/// Pauses the VM where the client asked to, and reports the state of the VM to the session.
+pub struct SessionHook {
+    sources: Arc<SourceIndex>,
+    breakpoints: Arc<Breakpoints>,
+    pause_requested: Arc<AtomicBool>,
+    events: Sender<SessionEvent>,
+    resumes: Receiver<Resume>,
+    resume: Resume,
+    /// Source line and call stack depth of the last stop, which steps are relative to.
+    last_stop: (Option<SourceLine>, usize),
+}
+
+impl SessionHook {
+    pub fn new(
+        sources: Arc<SourceIndex>,
+        breakpoints: Arc<Breakpoints>,
+        pause_requested: Arc<AtomicBool>,
+        events: Sender<SessionEvent>,
+        resumes: Receiver<Resume>,
+        stop_on_entry: bool,
+    ) -> Self {
+        Self {
+            sources,
+            breakpoints,
+            pause_requested,
+            events,
+            resumes,
+            // Stepping in from nowhere stops at the first line with a source.
+            resume: if stop_on_entry {
+                Resume::StepIn
+            } else {
+                Resume::Continue
+            },
+            last_stop: (None, 0),
+        }
+    }
+
+    fn stop_reason(&self, line: Option<SourceLine>, depth: usize) -> Option<&'static str> {
+        let (last_line, last_depth) = self.last_stop;
+        let step_done = line.is_some()
+            && match self.resume {
+                Resume::Continue => false,
+                Resume::StepIn => line != last_line || depth != last_depth,
+                Resume::StepOver => {
+                    depth < last_depth || (depth == last_depth && line != last_line)
+                },
+                Resume::StepOut => depth < last_depth,
+            };
+        if step_done {
+            Some("step")
+        } else if self.pause_requested.load(Ordering::Relaxed) {
+            Some("pause")
+        } else {
+            None
+        }
+    }
+}
+
+impl DebugHook for SessionHook {
+    fn on_instruction(&mut self, state: &InterpreterState) {
+        let module_id = match state.module_id() {
+            Some(module_id) => module_id,
+            // Scripts are not compiled from the package, so have no sources.
+            None => return,
+        };
+        let line = self
+            .sources
+            .line(module_id, state.function_index(), state.pc());
+        let depth = state.depth();
+
+        let reason = if self
+            .breakpoints
+            .contains(module_id, state.function_index(), state.pc())
+        {
+            Some("breakpoint")
+        } else {
+            self.stop_reason(line, depth)
+        };
+        if let Some(reason) = reason {
+            self.pause_requested.store(false, Ordering::Relaxed);
+            let stopped = SessionEvent::Stopped {
+                reason,
+                frames: state.frames(),
+            };
+            // Without a session anymore, run to completion.
+            self.resume = if self.events.send(stopped).is_ok() {
+                self.resumes.recv().unwrap_or(Resume::Continue)
+            } else {
+                Resume::Continue
+            };
+            self.last_stop = (line, depth);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::account_address::AccountAddress;
+    use std::sync::mpsc;
+
+    fn hook(stop_on_entry: bool) -> SessionHook {
+        let (events, _) = mpsc::channel();
+        let (_, resumes) = mpsc::channel();
+        SessionHook::new(
+            Arc::new(SourceIndex::default()),
+            Arc::new(Breakpoints::default()),
+            Arc::new(AtomicBool::new(false)),
+            events,
+            resumes,
+            stop_on_entry,
+        )
+    }
+
+    fn line(line: u64) -> Option<SourceLine> {
+        Some(SourceLine { file: 0, line })
+    }
+
+    /// A hook which last stopped at line 10, in a function called at depth 2.
+    fn stopped_hook(resume: Resume) -> SessionHook {
+        let mut hook = hook(false);
+        hook.resume = resume;
+        hook.last_stop = (line(10), 2);
+        hook
+    }
+
+    #[test]
+    fn test_stop_on_entry() {
+        assert_eq!(hook(false).stop_reason(line(1), 1), None);
+        let hook = hook(true);
+        assert_eq!(hook.stop_reason(None, 1), None);
+        assert_eq!(hook.stop_reason(line(1), 1), Some("step"));
+    }
+
+    #[test]
+    fn test_step_in() {
+        let hook = stopped_hook(Resume::StepIn);
+        assert_eq!(hook.stop_reason(line(10), 2), None);
+        assert_eq!(hook.stop_reason(line(11), 2), Some("step"));
+        // Into a callee, even when it is on the same line, e.g. for recursion.
+        assert_eq!(hook.stop_reason(line(10), 3), Some("step"));
+        assert_eq!(hook.stop_reason(line(3), 1), Some("step"));
+        // Instructions without a source are stepped through.
+        assert_eq!(hook.stop_reason(None, 3), None);
+    }
+
+    #[test]
+    fn test_step_over() {
+        let hook = stopped_hook(Resume::StepOver);
+        assert_eq!(hook.stop_reason(line(10), 2), None);
+        assert_eq!(hook.stop_reason(line(20), 3), None);
+        assert_eq!(hook.stop_reason(line(11), 2), Some("step"));
+        // Returning from the function stops in the caller.
+        assert_eq!(hook.stop_reason(line(3), 1), Some("step"));
+        assert_eq!(hook.stop_reason(None, 1), None);
+    }
+
+    #[test]
+    fn test_step_out() {
+        let hook = stopped_hook(Resume::StepOut);
+        assert_eq!(hook.stop_reason(line(11), 2), None);
+        assert_eq!(hook.stop_reason(line(20), 3), None);
+        assert_eq!(hook.stop_reason(line(10), 1), Some("step"));
+        assert_eq!(hook.stop_reason(None, 1), None);
+    }
+
+    #[test]
+    fn test_pause() {
+        let hook = stopped_hook(Resume::Continue);
+        assert_eq!(hook.stop_reason(line(11), 2), None);
+        hook.pause_requested.store(true, Ordering::Relaxed);
+        assert_eq!(hook.stop_reason(line(11), 2), Some("pause"));
+        assert_eq!(hook.stop_reason(None, 2), Some("pause"));
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let module_id = ModuleId::new(AccountAddress::ONE, "m".parse().unwrap());
+        let location = |function_index, pc| CodeLocation {
+            module_id: module_id.clone(),
+            function_index: FunctionDefinitionIndex(function_index),
+            pc,
+        };
+        let breakpoints = Breakpoints::default();
+        breakpoints.set(&[location(0, 3), location(1, 0)].into_iter().collect());
+        assert!(breakpoints.contains(&module_id, FunctionDefinitionIndex(0), 3));
+        assert!(breakpoints.contains(&module_id, FunctionDefinitionIndex(1), 0));
+        assert!(!breakpoints.contains(&module_id, FunctionDefinitionIndex(0), 0));
+        let other_module_id = ModuleId::new(AccountAddress::TWO, "m".parse().unwrap());
+        assert!(!breakpoints.contains(&other_module_id, FunctionDefinitionIndex(0), 3));
+
+        // Setting breakpoints replaces the previous ones.
+        breakpoints.set(&[location(1, 2)].into_iter().collect());
+        assert!(!breakpoints.contains(&module_id, FunctionDefinitionIndex(0), 3));
+        assert!(breakpoints.contains(&module_id, FunctionDefinitionIndex(1), 2));
+    }
+}
@@ -0,0 +1,531 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod dap;
+mod hook;
+mod sources;
+
+use crate::{
+    common::types::{
+        CliCommand, CliError, CliTypedResult, MovePackageDir, ProfileOptions, RestOptions,
+    },
+    move_tool::aptos_debug_natives::aptos_debug_natives,
+};
+use aptos_debugger::AptosDebugger;
+use aptos_gas::{AbstractValueSizeGasParameters, NativeGasParameters};
+use aptos_rest_client::Client;
+use aptos_types::transaction::Version;
+use async_trait::async_trait;
+use clap::Parser;
+use dap::Request;
+use hook::{Breakpoints, Resume, SessionHook};
+use move_cli::base::test::{run_move_unit_tests, UnitTestResult};
+use move_package::BuildConfig;
+use move_unit_test::UnitTestingConfig;
+use move_vm_runtime::debugger::{set_debug_hook, FrameInfo};
+use serde_json::{json, Value};
+use sources::{CodeLocation, SourceIndex};
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{self, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+use tokio::{runtime::Handle, task};
+
+/// The VM runs a single thread, as far as the client is concerned.
+const THREAD_ID: u64 = 1;
+
+/// Debug a Move unit test or a committed transaction
+///
+/// Starts a Debug Adapter Protocol server on localhost, and waits for a client (e.g. an
+/// editor) to connect. Breakpoints can be set on the lines of the package and its dependencies,
+/// and their locals, type arguments and call stacks can be inspected when stopped.
+///
+/// When debugging a transaction, the modules it calls must have been published from the same
+/// sources as the local package for their lines to match.
+#[derive(Parser)]
+pub struct DebugPackage {
+    /// A filter string to determine which unit tests to debug
+    ///
+    /// Matching tests are run one after the other.
+    #[clap(long, short)]
+    pub filter: Option<String>,
+
+    /// Debug the committed user transaction with this version instead of unit tests
+    #[clap(long, conflicts_with = "filter")]
+    pub txn_version: Option<Version>,
+
+    /// Port to listen on for the debug adapter client
+    #[clap(long, default_value_t = 4711)]
+    pub port: u16,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<&'static str> for DebugPackage {
+    fn command_name(&self) -> &'static str {
+        "DebugPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<&'static str> {
+        let package_path = self.move_options.get_package_path()?;
+        // Unit tests are compiled along with the test only code, while published modules are not.
+        let build_config = BuildConfig {
+            additional_named_addresses: self.move_options.named_addresses(),
+            test_mode: self.txn_version.is_none(),
+            install_dir: self.move_options.output_dir.clone(),
+            skip_fetch_latest_git_deps: self.move_options.skip_fetch_latest_git_deps,
+            ..Default::default()
+        };
+        let package = build_config
+            .clone()
+            .compile_package(package_path.as_path(), &mut io::stderr())
+            .map_err(|err| CliError::MoveCompilationError(err.to_string()))?;
+        let sources = SourceIndex::new(&package)?;
+
+        let target = match self.txn_version {
+            Some(version) => DebugTarget::Transaction {
+                client: self.rest_options.client(&self.profile_options)?,
+                version,
+                runtime: Handle::current(),
+            },
+            None => DebugTarget::UnitTests {
+                package_path,
+                build_config,
+                config: UnitTestingConfig {
+                    filter: self.filter,
+                    // The debugger follows a single test at a time.
+                    num_threads: 1,
+                    report_stacktrace_on_abort: true,
+                    ..UnitTestingConfig::default_with_bound(None)
+                },
+            },
+        };
+
+        let address = format!("127.0.0.1:{}", self.port);
+        let listener =
+            TcpListener::bind(&address).map_err(|err| CliError::IO(address.clone(), err))?;
+        eprintln!("Waiting for a debug adapter client on {}", address);
+        task::spawn_blocking(move || -> CliTypedResult<()> {
+            let (stream, _) = listener
+                .accept()
+                .map_err(|err| CliError::IO(address, err))?;
+            Session::new(stream, sources, target)?.run()?;
+            Ok(())
+        })
+        .await
+        .map_err(|err| CliError::UnexpectedError(err.to_string()))??;
+        Ok("Debug session ended")
+    }
+}
+
+/// What is being debugged.
+enum DebugTarget {
+    UnitTests {
+        package_path: PathBuf,
+        build_config: BuildConfig,
+        config: UnitTestingConfig,
+    },
+    Transaction {
+        client: Client,
+        version: Version,
+        runtime: Handle,
+    },
+}
+
+impl DebugTarget {
+    /// Runs the target to completion, and describes the outcome.
+    fn run(self, events: Sender<SessionEvent>) -> String {
+        match self {
+            DebugTarget::UnitTests {
+                package_path,
+                build_config,
+                config,
+            } => {
+                let result = run_move_unit_tests(
+                    package_path.as_path(),
+                    build_config,
+                    config,
+                    aptos_debug_natives(
+                        NativeGasParameters::zeros(),
+                        AbstractValueSizeGasParameters::zeros(),
+                    ),
+                    None,
+                    false,
+                    &mut OutputWriter(events),
+                );
+                match result {
+                    Ok(UnitTestResult::Success) => "Tests passed".to_string(),
+                    Ok(UnitTestResult::Failure) => "Tests failed".to_string(),
+                    Err(err) => format!("Failed to run tests: {}", err),
+                }
+            },
+            DebugTarget::Transaction {
+                client,
+                version,
+                runtime,
+            } => {
+                let result = AptosDebugger::rest_client(client).and_then(|debugger| {
+                    runtime.block_on(debugger.execute_past_user_transaction(version))
+                });
+                match result {
+                    Ok((status, _)) => format!("Transaction finished with {}", status),
+                    Err(err) => format!("Failed to execute transaction: {}", err),
+                }
+            },
+        }
+    }
+}
+
+/// Forwards the output of the target to the client.
+struct OutputWriter(Sender<SessionEvent>);
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let output = String::from_utf8_lossy(buf).into_owned();
+        // The session may be gone, in which case the output isn't needed anymore.
+        let _ = self.0.send(SessionEvent::Output(output));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Inputs of the session, from the client and from the target.
+pub(crate) enum SessionEvent {
+    Request(Request),
+    Disconnected,
+    Stopped {
+        reason: &'static str,
+        frames: Vec<FrameInfo>,
+    },
+    Output(String),
+    Finished(String),
+}
+
+struct Session {
+    client: dap::Sender<TcpStream>,
+    events: Sender<SessionEvent>,
+    events_rx: Receiver<SessionEvent>,
+    sources: Arc<SourceIndex>,
+    breakpoints: Arc<Breakpoints>,
+    breakpoints_by_file: HashMap<PathBuf, BTreeSet<CodeLocation>>,
+    pause_requested: Arc<AtomicBool>,
+    target: Option<DebugTarget>,
+    stop_on_entry: bool,
+    resumes: Option<Sender<Resume>>,
+    /// Call stack where the target is stopped, empty while it runs.
+    frames: Vec<FrameInfo>,
+}
+
+impl Session {
+    fn new(stream: TcpStream, sources: SourceIndex, target: DebugTarget) -> io::Result<Self> {
+        let (events, events_rx) = mpsc::channel();
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let requests = events.clone();
+        thread::spawn(move || loop {
+            let event = match dap::read_request(&mut reader) {
+                Ok(Some(request)) => SessionEvent::Request(request),
+                Ok(None) | Err(_) => SessionEvent::Disconnected,
+            };
+            let disconnected = matches!(event, SessionEvent::Disconnected);
+            if requests.send(event).is_err() || disconnected {
+                break;
+            }
+        });
+
+        Ok(Self {
+            client: dap::Sender::new(stream),
+            events,
+            events_rx,
+            sources: Arc::new(sources),
+            breakpoints: Arc::new(Breakpoints::default()),
+            breakpoints_by_file: HashMap::new(),
+            pause_requested: Arc::new(AtomicBool::new(false)),
+            target: Some(target),
+            stop_on_entry: false,
+            resumes: None,
+            frames: vec![],
+        })
+    }
+
+    fn run(mut self) -> anyhow::Result<()> {
+        while let Ok(event) = self.events_rx.recv() {
+            match event {
+                SessionEvent::Request(request) => {
+                    if !self.handle_request(&request)? {
+                        break;
+                    }
+                },
+                SessionEvent::Disconnected => break,
+                SessionEvent::Stopped { reason, frames } => {
+                    self.frames = frames;
+                    self.client.event(
+                        "stopped",
+                        json!({
+                            "reason": reason,
+                            "threadId": THREAD_ID,
+                            "allThreadsStopped": true,
+                        }),
+                    )?;
+                },
+                SessionEvent::Output(output) => {
+                    self.client
+                        .event("output", json!({"category": "stdout", "output": output}))?;
+                },
+                SessionEvent::Finished(outcome) => {
+                    self.client.event(
+                        "output",
+                        json!({"category": "console", "output": format!("{}\n", outcome)}),
+                    )?;
+                    self.client.event("terminated", json!({}))?;
+                },
+            }
+        }
+        // Lets the target run to completion without stopping.
+        self.resumes = None;
+        Ok(())
+    }
+
+    /// Handles a request of the client, returns false once the client disconnects.
+    fn handle_request(&mut self, request: &Request) -> anyhow::Result<bool> {
+        let args = &request.arguments;
+        let body = match request.command.as_str() {
+            "initialize" => {
+                self.client
+                    .respond(request, json!({"supportsConfigurationDoneRequest": true}))?;
+                self.client.event("initialized", json!({}))?;
+                return Ok(true);
+            },
+            "launch" => {
+                self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+                json!({})
+            },
+            "setBreakpoints" => self.set_breakpoints(args),
+            "setExceptionBreakpoints" => json!({}),
+            "configurationDone" => {
+                self.start();
+                json!({})
+            },
+            "threads" => json!({"threads": [{"id": THREAD_ID, "name": "main"}]}),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => self.scopes(args["frameId"].as_u64().unwrap_or(0) as usize),
+            "variables" => self.variables(args["variablesReference"].as_u64().unwrap_or(0)),
+            "continue" => {
+                self.resume(Resume::Continue);
+                json!({"allThreadsContinued": true})
+            },
+            "next" => {
+                self.resume(Resume::StepOver);
+                json!({})
+            },
+            "stepIn" => {
+                self.resume(Resume::StepIn);
+                json!({})
+            },
+            "stepOut" => {
+                self.resume(Resume::StepOut);
+                json!({})
+            },
+            "pause" => {
+                self.pause_requested.store(true, Ordering::Relaxed);
+                json!({})
+            },
+            "disconnect" | "terminate" => {
+                self.client.respond(request, json!({}))?;
+                return Ok(false);
+            },
+            command => {
+                self.client
+                    .respond_error(request, &format!("Unsupported request {}", command))?;
+                return Ok(true);
+            },
+        };
+        self.client.respond(request, body)?;
+        Ok(true)
+    }
+
+    /// Starts the target, with the VM stopping where the client asked to.
+    fn start(&mut self) {
+        let target = match self.target.take() {
+            Some(target) => target,
+            None => return,
+        };
+        let (resumes, resumes_rx) = mpsc::channel();
+        self.resumes = Some(resumes);
+        set_debug_hook(Some(Box::new(SessionHook::new(
+            self.sources.clone(),
+            self.breakpoints.clone(),
+            self.pause_requested.clone(),
+            self.events.clone(),
+            resumes_rx,
+            self.stop_on_entry,
+        ))));
+
+        let events = self.events.clone();
+        thread::spawn(move || {
+            let outcome = target.run(events.clone());
+            set_debug_hook(None);
+            let _ = events.send(SessionEvent::Finished(outcome));
+        });
+    }
+
+    fn resume(&mut self, resume: Resume) {
+        // Only a stopped target waits for a resume.
+        if self.frames.is_empty() {
+            return;
+        }
+        self.frames.clear();
+        if let Some(resumes) = &self.resumes {
+            let _ = resumes.send(resume);
+        }
+    }
+
+    /// Replaces the breakpoints of a source file.
+    fn set_breakpoints(&mut self, args: &Value) -> Value {
+        let path = PathBuf::from(args["source"]["path"].as_str().unwrap_or_default());
+        let mut instructions = BTreeSet::new();
+        let mut breakpoints = vec![];
+        for breakpoint in args["breakpoints"].as_array().into_iter().flatten() {
+            let line = breakpoint["line"].as_u64().unwrap_or(0);
+            let line_instructions = self.sources.instructions_at_line(&path, line);
+            breakpoints.push(json!({"verified": !line_instructions.is_empty(), "line": line}));
+            instructions.extend(line_instructions);
+        }
+        self.breakpoints_by_file.insert(path, instructions);
+        self.breakpoints.set(
+            &self
+                .breakpoints_by_file
+                .values()
+                .flatten()
+                .cloned()
+                .collect(),
+        );
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn stack_trace(&self) -> Value {
+        let frames: Vec<_> = self
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(id, frame)| {
+                let (name, line) = match &frame.module_id {
+                    Some(module_id) => (
+                        format!(
+                            "{}::{}",
+                            module_id.short_str_lossless(),
+                            frame.function_name
+                        ),
+                        self.sources.line(module_id, frame.function_index, frame.pc),
+                    ),
+                    None => (frame.function_name.clone(), None),
+                };
+                match line {
+                    Some(line) => json!({
+                        "id": id,
+                        "name": name,
+                        "source": {"path": self.sources.file_path(line.file).display().to_string()},
+                        "line": line.line,
+                        "column": 1,
+                    }),
+                    // Without sources, the bytecode offset is the best we can show.
+                    None => json!({
+                        "id": id,
+                        "name": format!("{} (offset {})", name, frame.pc),
+                        "line": 0,
+                        "column": 0,
+                        "presentationHint": "subtle",
+                    }),
+                }
+            })
+            .collect();
+        json!({"totalFrames": frames.len(), "stackFrames": frames})
+    }
+
+    // Variables of frame `id` are referenced by `2 * id + 1` for its locals, and by `2 * id + 2`
+    // for its type arguments, as 0 means no variables.
+    fn scopes(&self, frame_id: usize) -> Value {
+        let mut scopes = vec![json!({
+            "name": "Locals",
+            "variablesReference": 2 * frame_id + 1,
+            "expensive": false,
+        })];
+        if self
+            .frames
+            .get(frame_id)
+            .map_or(false, |frame| !frame.type_arguments.is_empty())
+        {
+            scopes.push(json!({
+                "name": "Type Arguments",
+                "variablesReference": 2 * frame_id + 2,
+                "expensive": false,
+            }));
+        }
+        json!({ "scopes": scopes })
+    }
+
+    fn variables(&self, reference: u64) -> Value {
+        let frame = match reference
+            .checked_sub(1)
+            .and_then(|idx| self.frames.get(idx as usize / 2))
+        {
+            Some(frame) => frame,
+            None => return json!({"variables": []}),
+        };
+        let module_id = frame.module_id.as_ref();
+        let variables: Vec<_> = if reference % 2 == 1 {
+            frame
+                .locals
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, value)| {
+                    // Moved out or not yet assigned.
+                    let value = value.as_ref()?;
+                    let name = module_id
+                        .and_then(|module_id| {
+                            self.sources
+                                .local_name(module_id, frame.function_index, idx)
+                        })
+                        .unwrap_or_else(|| format!("local{}", idx));
+                    // Hide the temporaries introduced by the compiler.
+                    if name.starts_with('%') {
+                        return None;
+                    }
+                    Some(json!({"name": name, "value": value, "variablesReference": 0}))
+                })
+                .collect()
+        } else {
+            frame
+                .type_arguments
+                .iter()
+                .enumerate()
+                .map(|(idx, ty)| {
+                    let name = module_id
+                        .and_then(|module_id| {
+                            self.sources
+                                .type_parameter_name(module_id, frame.function_index, idx)
+                        })
+                        .unwrap_or_else(|| format!("T{}", idx));
+                    json!({"name": name, "value": ty, "variablesReference": 0})
+                })
+                .collect()
+        };
+        json!({ "variables": variables })
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context;
+use move_binary_format::{access::ModuleAccess, file_format::FunctionDefinitionIndex};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::FileHash;
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_core_types::language_storage::ModuleId;
+use move_ir_types::location::Loc;
+use move_package::compilation::compiled_package::CompiledPackage;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+};
+
+/// An instruction of a module, e.g. where a breakpoint is set.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct CodeLocation {
+    pub module_id: ModuleId,
+    pub function_index: FunctionDefinitionIndex,
+    pub pc: u16,
+}
+
+/// A line of a source file of the package, lines starting at 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SourceLine {
+    pub file: usize,
+    pub line: u64,
+}
+
+struct SourceFile {
+    path: PathBuf,
+    /// Byte offsets of the start of each line.
+    line_starts: Vec<usize>,
+}
+
+struct ModuleSources {
+    source_map: SourceMap,
+    num_functions: usize,
+}
+
+/// Maps the bytecode of the modules of a package and its dependencies to their source code.
+#[derive(Default)]
+pub struct SourceIndex {
+    files: Vec<SourceFile>,
+    files_by_hash: HashMap<FileHash, usize>,
+    modules: HashMap<ModuleId, ModuleSources>,
+}
+
+impl SourceIndex {
+    pub fn new(package: &CompiledPackage) -> anyhow::Result<Self> {
+        let mut index = Self {
+            files: vec![],
+            files_by_hash: HashMap::new(),
+            modules: HashMap::new(),
+        };
+        for unit in package.all_compiled_units_with_source() {
+            index.add_file(&unit.source_path)?;
+            if let CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) = &unit.unit
+            {
+                index.modules.insert(module.self_id(), ModuleSources {
+                    source_map: source_map.clone(),
+                    num_functions: module.function_defs().len(),
+                });
+            }
+        }
+        Ok(index)
+    }
+
+    fn add_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read source file {}", path.display()))?;
+        let hash = FileHash::new(&contents);
+        if self.files_by_hash.contains_key(&hash) {
+            return Ok(());
+        }
+        let line_starts = std::iter::once(0)
+            .chain(contents.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        self.files_by_hash.insert(hash, self.files.len());
+        self.files.push(SourceFile {
+            path: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+            line_starts,
+        });
+        Ok(())
+    }
+
+    pub fn file_path(&self, file: usize) -> &Path {
+        &self.files[file].path
+    }
+
+    /// Returns the source line of an instruction, if the module was compiled from source.
+    pub fn line(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        pc: u16,
+    ) -> Option<SourceLine> {
+        let loc = self
+            .modules
+            .get(module_id)?
+            .source_map
+            .get_code_location(function_index, pc)
+            .ok()?;
+        self.line_of(loc)
+    }
+
+    fn line_of(&self, loc: Loc) -> Option<SourceLine> {
+        let file = *self.files_by_hash.get(&loc.file_hash())?;
+        let line_starts = &self.files[file].line_starts;
+        let line = line_starts.partition_point(|start| *start <= loc.start() as usize);
+        Some(SourceLine {
+            file,
+            line: line as u64,
+        })
+    }
+
+    /// Returns the instructions where execution enters the given line of a source file, i.e.
+    /// the ones that need a breakpoint for the line.
+    pub fn instructions_at_line(&self, path: &Path, line: u64) -> BTreeSet<CodeLocation> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let mut instructions = BTreeSet::new();
+        for (module_id, module) in &self.modules {
+            for function_index in 0..module.num_functions {
+                let function_index = FunctionDefinitionIndex(function_index as u16);
+                let function = match module.source_map.get_function_source_map(function_index) {
+                    Ok(function) => function,
+                    Err(_) => continue,
+                };
+                let mut previous_line = None;
+                for (pc, loc) in &function.code_map {
+                    let current_line = self.line_of(*loc);
+                    let matches = current_line.map_or(false, |current| {
+                        current.line == line && self.files[current.file].path == path
+                    });
+                    if matches && current_line != previous_line {
+                        instructions.insert(CodeLocation {
+                            module_id: module_id.clone(),
+                            function_index,
+                            pc: *pc,
+                        });
+                    }
+                    previous_line = current_line;
+                }
+            }
+        }
+        instructions
+    }
+
+    /// Returns the source name of a parameter or local. Temporaries introduced by the compiler
+    /// have names starting with `%`.
+    pub fn local_name(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        idx: usize,
+    ) -> Option<String> {
+        let (name, _) = self
+            .modules
+            .get(module_id)?
+            .source_map
+            .get_parameter_or_local_name(function_index, idx as u64)
+            .ok()?;
+        Some(name)
+    }
+
+    pub fn type_parameter_name(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        idx: usize,
+    ) -> Option<String> {
+        let (name, _) = self
+            .modules
+            .get(module_id)?
+            .source_map
+            .get_function_source_map(function_index)
+            .ok()?
+            .get_type_parameter_name(idx)?;
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::account_address::AccountAddress;
+    use move_package::BuildConfig;
+
+    const MOVE_TOML: &str = r#"
+[package]
+name = "Debuggee"
+version = "0.0.0"
+"#;
+
+    // Line numbers are the ones asserted on below.
+    const MODULE: &str = r#"module 0x42::m {
+    fun add<T>(a: u64, b: u64): u64 {
+        let sum = a + b;
+        sum
+    }
+
+    public fun twice(a: u64): u64 {
+        // Adds twice the same value.
+        add<bool>(a, a)
+    }
+}
+"#;
+
+    fn compile() -> (tempfile::TempDir, SourceIndex) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Move.toml"), MOVE_TOML).unwrap();
+        std::fs::create_dir(dir.path().join("sources")).unwrap();
+        std::fs::write(dir.path().join("sources").join("m.move"), MODULE).unwrap();
+        let package = BuildConfig {
+            install_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        }
+        .compile_package(dir.path(), &mut Vec::new())
+        .unwrap();
+        let sources = SourceIndex::new(&package).unwrap();
+        (dir, sources)
+    }
+
+    fn source_path(dir: &tempfile::TempDir) -> PathBuf {
+        dir.path().join("sources").join("m.move")
+    }
+
+    #[test]
+    fn test_instructions_at_line() {
+        let (dir, sources) = compile();
+        let path = source_path(&dir);
+        let module_id = ModuleId::new(
+            AccountAddress::from_hex_literal("0x42").unwrap(),
+            "m".parse().unwrap(),
+        );
+
+        for line in [3, 9] {
+            // Execution enters each line once, at its first instruction.
+            let instructions = sources.instructions_at_line(&path, line);
+            assert_eq!(instructions.len(), 1, "line {}", line);
+            let instruction = instructions.into_iter().next().unwrap();
+            assert_eq!(instruction.module_id, module_id);
+
+            let source_line = sources
+                .line(&module_id, instruction.function_index, instruction.pc)
+                .unwrap();
+            assert_eq!(source_line.line, line);
+            assert_eq!(
+                sources.file_path(source_line.file),
+                path.canonicalize().unwrap()
+            );
+        }
+
+        // Blank lines and comments have no code.
+        for line in [1, 6, 8] {
+            assert!(sources.instructions_at_line(&path, line).is_empty());
+        }
+        assert!(sources
+            .instructions_at_line(&dir.path().join("Move.toml"), 3)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_names() {
+        let (dir, sources) = compile();
+        let add = sources
+            .instructions_at_line(&source_path(&dir), 3)
+            .into_iter()
+            .next()
+            .unwrap();
+        assert_eq!(
+            sources.type_parameter_name(&add.module_id, add.function_index, 0),
+            Some("T".to_string())
+        );
+        assert_eq!(
+            sources.type_parameter_name(&add.module_id, add.function_index, 1),
+            None
+        );
+        assert!(sources
+            .local_name(&add.module_id, add.function_index, 0)
+            .is_some());
+    }
+
+    #[test]
+    fn test_unknown_module() {
+        let (_dir, sources) = compile();
+        let module_id = ModuleId::new(AccountAddress::ONE, "m".parse().unwrap());
+        assert_eq!(
+            sources.line(&module_id, FunctionDefinitionIndex(0), 0),
+            None
+        );
+        assert_eq!(
+            sources.local_name(&module_id, FunctionDefinitionIndex(0), 0),
+            None
+        );
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::{
+    types::{CliCommand, CliError, CliTypedResult, MovePackageDir},
+    utils::write_to_file,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_command_line_common::files::find_move_filenames;
+use move_formatter::{format_source, FormatConfig};
+use move_package::source_package::layout::SourcePackageLayout;
+use std::path::Path;
+
+/// Formats the Move source files of a package
+///
+/// The sources, tests, scripts and examples of the package are formatted. Formatting can be
+/// configured with a `movefmt.toml` file in the package directory, setting `max_width`,
+/// `indent_width`, and `imports` which is one of `sort`, `by_address` or `preserve`.
+#[derive(Parser)]
+pub struct FormatPackage {
+    /// Check that the files are formatted without changing them, failing if any is not
+    #[clap(long)]
+    pub check: bool,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for FormatPackage {
+    fn command_name(&self) -> &'static str {
+        "FormatPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let package_dir = self.move_options.get_package_path()?;
+        let config = FormatConfig::load(&package_dir)?;
+        let dirs: Vec<_> = [
+            SourcePackageLayout::Sources,
+            SourcePackageLayout::Tests,
+            SourcePackageLayout::Scripts,
+            SourcePackageLayout::Examples,
+        ]
+        .iter()
+        .map(|layout| package_dir.join(layout.path()))
+        .filter(|dir| dir.exists())
+        .collect();
+
+        let mut changed = vec![];
+        for file in find_move_filenames(&dirs, false)? {
+            let source =
+                std::fs::read_to_string(&file).map_err(|err| CliError::IO(file.clone(), err))?;
+            let formatted = format_source(&file, &source, &config)
+                .map_err(|err| CliError::MoveCompilationError(err.to_string()))?;
+            if formatted != source {
+                if !self.check {
+                    write_to_file(Path::new(&file), &file, formatted.as_bytes())?;
+                }
+                changed.push(file);
+            }
+        }
+
+        if self.check && !changed.is_empty() {
+            return Err(CliError::UnexpectedError(format!(
+                "{} file(s) are not formatted, run `aptos move fmt` to format them: {}",
+                changed.len(),
+                changed.join(", ")
+            )));
+        }
+        Ok(changed)
+    }
+}
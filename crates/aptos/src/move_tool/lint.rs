@@ -0,0 +1,71 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, MovePackageDir};
+use aptos_framework::{
+    build_model,
+    lint::{default_lints, LintLevel, Linter},
+};
+use async_trait::async_trait;
+use clap::Parser;
+use codespan_reporting::{
+    diagnostic::Severity,
+    term::termcolor::{ColorChoice, StandardStream},
+};
+
+/// Lints the Move source files of a package
+///
+/// The level of each lint (`allow`, `warn` or `error`) can be set in a `[lint]` section of
+/// `Move.toml`, and lints can be allowed on a function or module with
+/// `#[lint_allow(<lint>, ...)]`. The command fails if any lint at level `error` reports an issue.
+#[derive(Parser)]
+pub struct LintPackage {
+    /// List the lints with their level and description, instead of linting the package
+    #[clap(long)]
+    pub list: bool,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for LintPackage {
+    fn command_name(&self) -> &'static str {
+        "LintPackage"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let package_path = self.move_options.get_package_path()?;
+        let mut linter = Linter::new(default_lints());
+        linter
+            .configure_from_manifest(&package_path)
+            .map_err(|err| CliError::UnableToParse("Move.toml", err.to_string()))?;
+        if self.list {
+            return Ok(linter
+                .lints()
+                .map(|(lint, level)| format!("{} ({}): {}", lint.id(), level, lint.description()))
+                .collect());
+        }
+
+        let model = build_model(
+            package_path.as_path(),
+            self.move_options.named_addresses(),
+            None,
+            self.move_options.bytecode_version,
+        )?;
+        let diags = linter.run(&model);
+        if model.diag_count(Severity::Warning) > 0 {
+            let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+            model.report_diag(&mut error_writer, Severity::Warning);
+        }
+        if diags.iter().any(|diag| diag.level == LintLevel::Error) {
+            return Err(CliError::MoveCompilationError(
+                "lints at level `error` reported issues".to_string(),
+            ));
+        }
+        Ok(diags
+            .into_iter()
+            .map(|diag| format!("{}: {}", diag.lint, diag.message))
+            .collect())
+    }
+}
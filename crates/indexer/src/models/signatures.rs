@@ -6,8 +6,9 @@ use crate::{models::transactions::Transaction, schema::signatures, util::standar
 use anyhow::{Context, Result};
 use aptos_api_types::{
     AccountSignature as APIAccountSignature, Ed25519Signature as APIEd25519Signature,
-    MultiAgentSignature as APIMultiAgentSignature,
+    FeePayerSignature as APIFeePayerSignature, MultiAgentSignature as APIMultiAgentSignature,
     MultiEd25519Signature as APIMultiEd25519Signature,
+    Secp256k1EcdsaSignature as APISecp256k1EcdsaSignature,
     TransactionSignature as APITransactionSignature,
 };
 use aptos_bitvec::BitVec;
@@ -74,6 +75,23 @@ impl Signature {
                 transaction_version,
                 transaction_block_height,
             ),
+            APITransactionSignature::FeePayerSignature(sig) => Self::parse_fee_payer_signature(
+                sig,
+                sender,
+                transaction_version,
+                transaction_block_height,
+            ),
+            APITransactionSignature::Secp256k1EcdsaSignature(sig) => {
+                Ok(vec![Self::parse_secp256k1_ecdsa_signature(
+                    sig,
+                    sender,
+                    transaction_version,
+                    transaction_block_height,
+                    true,
+                    0,
+                    None,
+                )])
+            },
         }
     }
 
@@ -86,6 +104,10 @@ impl Signature {
             APITransactionSignature::MultiAgentSignature(_) => {
                 String::from("multi_agent_signature")
             },
+            APITransactionSignature::FeePayerSignature(_) => String::from("fee_payer_signature"),
+            APITransactionSignature::Secp256k1EcdsaSignature(_) => {
+                String::from("secp256k1_ecdsa_signature")
+            },
         }
     }
 
@@ -114,6 +136,31 @@ impl Signature {
         }
     }
 
+    fn parse_secp256k1_ecdsa_signature(
+        s: &APISecp256k1EcdsaSignature,
+        sender: &String,
+        transaction_version: i64,
+        transaction_block_height: i64,
+        is_sender_primary: bool,
+        multi_agent_index: i64,
+        override_address: Option<&String>,
+    ) -> Self {
+        let signer = standardize_address(override_address.unwrap_or(sender));
+        Self {
+            transaction_version,
+            transaction_block_height,
+            signer,
+            is_sender_primary,
+            type_: String::from("secp256k1_ecdsa_signature"),
+            public_key: s.public_key.to_string(),
+            threshold: 1,
+            public_key_indices: serde_json::Value::Array(vec![]),
+            signature: s.signature.to_string(),
+            multi_agent_index,
+            multi_sig_index: 0,
+        }
+    }
+
     fn parse_multi_signature(
         s: &APIMultiEd25519Signature,
         sender: &String,
@@ -192,6 +239,35 @@ impl Signature {
         Ok(signatures)
     }
 
+    fn parse_fee_payer_signature(
+        s: &APIFeePayerSignature,
+        sender: &String,
+        transaction_version: i64,
+        transaction_block_height: i64,
+    ) -> Result<Vec<Self>> {
+        let mut signatures = Self::parse_multi_agent_signature(
+            &APIMultiAgentSignature {
+                sender: s.sender.clone(),
+                secondary_signer_addresses: s.secondary_signer_addresses.clone(),
+                secondary_signers: s.secondary_signers.clone(),
+            },
+            sender,
+            transaction_version,
+            transaction_block_height,
+        )?;
+        // The fee payer signs after the secondary signers
+        signatures.append(&mut Self::parse_multi_agent_signature_helper(
+            &s.fee_payer_signer,
+            sender,
+            transaction_version,
+            transaction_block_height,
+            false,
+            s.secondary_signers.len() as i64,
+            Some(&s.fee_payer_address.to_string()),
+        ));
+        Ok(signatures)
+    }
+
     fn parse_multi_agent_signature_helper(
         s: &APIAccountSignature,
         sender: &String,
@@ -220,6 +296,17 @@ impl Signature {
                 multi_agent_index,
                 override_address,
             ),
+            APIAccountSignature::Secp256k1EcdsaSignature(sig) => {
+                vec![Self::parse_secp256k1_ecdsa_signature(
+                    sig,
+                    sender,
+                    transaction_version,
+                    transaction_block_height,
+                    is_sender_primary,
+                    multi_agent_index,
+                    override_address,
+                )]
+            },
         }
     }
 }
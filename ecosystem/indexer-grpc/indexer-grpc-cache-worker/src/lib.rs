@@ -6,7 +6,9 @@ pub mod worker;
 
 use anyhow::{Ok, Result};
 use aptos_indexer_grpc_server_framework::RunnableConfig;
-use aptos_indexer_grpc_utils::config::IndexerGrpcFileStoreConfig;
+use aptos_indexer_grpc_utils::config::{
+    deserialize_cache_config, IndexerGrpcCacheConfig, IndexerGrpcFileStoreConfig,
+};
 use serde::{Deserialize, Serialize};
 use worker::Worker;
 
@@ -15,7 +17,11 @@ use worker::Worker;
 pub struct IndexerGrpcCacheWorkerConfig {
     pub fullnode_grpc_address: String,
     pub file_store_config: IndexerGrpcFileStoreConfig,
-    pub redis_main_instance_address: String,
+    #[serde(
+        alias = "redis_main_instance_address",
+        deserialize_with = "deserialize_cache_config"
+    )]
+    pub cache_config: IndexerGrpcCacheConfig,
 }
 
 #[async_trait::async_trait]
@@ -23,7 +29,7 @@ impl RunnableConfig for IndexerGrpcCacheWorkerConfig {
     async fn run(&self) -> Result<()> {
         let mut worker = Worker::new(
             self.fullnode_grpc_address.clone(),
-            self.redis_main_instance_address.clone(),
+            self.cache_config.clone(),
             self.file_store_config.clone(),
         )
         .await;
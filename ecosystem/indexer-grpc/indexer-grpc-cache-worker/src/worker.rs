@@ -6,8 +6,8 @@ use crate::metrics::{
     PROCESSED_VERSIONS_COUNT,
 };
 use aptos_indexer_grpc_utils::{
-    cache_operator::CacheOperator,
-    config::IndexerGrpcFileStoreConfig,
+    cache_operator::{create_cache_operator, CacheOperator},
+    config::{IndexerGrpcCacheConfig, IndexerGrpcFileStoreConfig},
     create_grpc_client,
     file_store_operator::{
         FileStoreMetadata, FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator,
@@ -27,8 +27,8 @@ type ChainID = u32;
 type StartingVersion = u64;
 
 pub struct Worker {
-    /// Redis client.
-    redis_client: redis::Client,
+    /// Cache config.
+    cache_config: IndexerGrpcCacheConfig,
     /// Fullnode grpc address.
     fullnode_grpc_address: String,
     /// File store config
@@ -59,13 +59,11 @@ pub(crate) enum GrpcDataStatus {
 impl Worker {
     pub async fn new(
         fullnode_grpc_address: String,
-        redis_main_instance_address: String,
+        cache_config: IndexerGrpcCacheConfig,
         file_store: IndexerGrpcFileStoreConfig,
     ) -> Self {
-        let redis_client = redis::Client::open(format!("redis://{}", redis_main_instance_address))
-            .expect("Create redis client failed.");
         Self {
-            redis_client,
+            cache_config,
             file_store,
             fullnode_grpc_address: format!("http://{}", fullnode_grpc_address),
         }
@@ -82,11 +80,9 @@ impl Worker {
     pub async fn run(&mut self) {
         // Re-connect if lost.
         loop {
-            let conn = self
-                .redis_client
-                .get_tokio_connection()
+            let cache_operator = create_cache_operator(&self.cache_config)
                 .await
-                .expect("Get redis connection failed.");
+                .expect("Create cache operator failed.");
 
             let mut rpc_client = create_grpc_client(self.fullnode_grpc_address.clone()).await;
 
@@ -120,14 +116,15 @@ impl Worker {
                 .unwrap();
 
             // 3&4. Infinite streaming until error happens. Either stream ends or worker crashes.
-            process_streaming_response(conn, file_store_metadata, response.into_inner()).await;
+            process_streaming_response(cache_operator, file_store_metadata, response.into_inner())
+                .await;
         }
     }
 }
 
 async fn process_transactions_from_node_response(
     response: TransactionsFromNodeResponse,
-    cache_operator: &mut CacheOperator<redis::aio::Connection>,
+    cache_operator: &mut dyn CacheOperator,
 ) -> anyhow::Result<GrpcDataStatus> {
     match response.response.unwrap() {
         Response::Status(status) => {
@@ -193,13 +190,9 @@ async fn process_transactions_from_node_response(
 
 /// Setup the cache operator with init signal, includeing chain id and starting version from fullnode.
 async fn setup_cache_with_init_signal(
-    conn: redis::aio::Connection,
+    mut cache_operator: Box<dyn CacheOperator>,
     init_signal: TransactionsFromNodeResponse,
-) -> (
-    CacheOperator<redis::aio::Connection>,
-    ChainID,
-    StartingVersion,
-) {
+) -> (Box<dyn CacheOperator>, ChainID, StartingVersion) {
     let (fullnode_chain_id, starting_version) =
         match init_signal.response.expect("Response type not exists.") {
             Response::Status(status_frame) => {
@@ -215,7 +208,6 @@ async fn setup_cache_with_init_signal(
             },
         };
 
-    let mut cache_operator = CacheOperator::new(conn);
     cache_operator.cache_setup_if_needed().await;
     cache_operator
         .update_or_verify_chain_id(fullnode_chain_id as u64)
@@ -227,7 +219,7 @@ async fn setup_cache_with_init_signal(
 
 // Infinite streaming processing. Retry if error happens; crash if fatal.
 async fn process_streaming_response(
-    conn: redis::aio::Connection,
+    cache_operator: Box<dyn CacheOperator>,
     file_store_metadata: Option<FileStoreMetadata>,
     mut resp_stream: impl futures_core::Stream<Item = Result<TransactionsFromNodeResponse, tonic::Status>>
         + std::marker::Unpin,
@@ -242,7 +234,7 @@ async fn process_streaming_response(
         },
     };
     let (mut cache_operator, fullnode_chain_id, starting_version) =
-        setup_cache_with_init_signal(conn, init_signal).await;
+        setup_cache_with_init_signal(cache_operator, init_signal).await;
     // It's required to start the worker with the same version as file store.
     if let Some(file_store_metadata) = file_store_metadata {
         if file_store_metadata.version != starting_version {
@@ -269,7 +261,7 @@ async fn process_streaming_response(
             panic!("[Indexer Cache] Chain id mismatch happens during data streaming.");
         }
 
-        match process_transactions_from_node_response(received, &mut cache_operator).await {
+        match process_transactions_from_node_response(received, cache_operator.as_mut()).await {
             Ok(status) => match status {
                 GrpcDataStatus::ChunkDataOk {
                     start_version,
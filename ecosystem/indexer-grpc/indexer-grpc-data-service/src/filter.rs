@@ -0,0 +1,481 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_protos::{
+    indexer::v1::TransactionFilter,
+    transaction::v1::{
+        multisig_transaction_payload, transaction::TxnData, transaction_payload,
+        write_set_change::Change, EntryFunctionId, Event, Transaction,
+    },
+};
+
+/// Maximum number of filters in a single request.
+pub const MAX_FILTERS_PER_REQUEST: usize = 10;
+
+/// The filters of a request. A transaction passes if it matches any of the filters, or if there
+/// are none.
+#[derive(Debug, Default)]
+pub struct TransactionFilters {
+    filters: Vec<CompiledFilter>,
+}
+
+impl TransactionFilters {
+    /// Validates the filters of a request, normalizing addresses and types to the way the
+    /// fullnode renders them.
+    pub fn new(filters: &[TransactionFilter]) -> Result<Self, String> {
+        if filters.len() > MAX_FILTERS_PER_REQUEST {
+            return Err(format!(
+                "Too many filters: {}, at most {} are allowed",
+                filters.len(),
+                MAX_FILTERS_PER_REQUEST
+            ));
+        }
+        Ok(Self {
+            filters: filters
+                .iter()
+                .map(CompiledFilter::new)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.filters.is_empty()
+            || self
+                .filters
+                .iter()
+                .any(|filter| filter.matches(transaction))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct EntryFunctionFilter {
+    address: String,
+    module: String,
+    function: Option<String>,
+}
+
+impl EntryFunctionFilter {
+    fn matches(&self, id: &EntryFunctionId) -> bool {
+        match &id.module {
+            Some(module) => {
+                module.address == self.address
+                    && module.name == self.module
+                    && self.function.as_ref().map_or(true, |name| *name == id.name)
+            },
+            None => false,
+        }
+    }
+}
+
+/// A `TransactionFilter`, all of whose present fields must match.
+#[derive(Debug, PartialEq, Eq)]
+struct CompiledFilter {
+    sender: Option<String>,
+    entry_function: Option<EntryFunctionFilter>,
+    event_type: Option<String>,
+    write_set_resource_type: Option<String>,
+    success: Option<bool>,
+}
+
+impl CompiledFilter {
+    fn new(filter: &TransactionFilter) -> Result<Self, String> {
+        let sender = filter
+            .sender
+            .as_deref()
+            .map(|sender| {
+                normalize_address(sender).ok_or_else(|| format!("Invalid sender: {}", sender))
+            })
+            .transpose()?;
+
+        let entry_function = filter
+            .entry_function
+            .as_deref()
+            .map(|entry_function| {
+                let invalid = || format!("Invalid entry function: {}", entry_function);
+                let parts: Vec<_> = entry_function.trim().split("::").collect();
+                let (address, module, function) = match parts.as_slice() {
+                    [address, module] => (*address, *module, None),
+                    [address, module, function] => (*address, *module, Some(*function)),
+                    _ => return Err(invalid()),
+                };
+                if !is_identifier(module) || !function.map_or(true, is_identifier) {
+                    return Err(invalid());
+                }
+                Ok(EntryFunctionFilter {
+                    address: normalize_address(address).ok_or_else(invalid)?,
+                    module: module.to_string(),
+                    function: function.map(str::to_string),
+                })
+            })
+            .transpose()?;
+
+        let event_type = filter
+            .event_type
+            .as_deref()
+            .map(|event_type| {
+                normalize_type(event_type)
+                    .ok_or_else(|| format!("Invalid event type: {}", event_type))
+            })
+            .transpose()?;
+
+        let write_set_resource_type = filter
+            .write_set_resource_type
+            .as_deref()
+            .map(|resource_type| {
+                normalize_type(resource_type)
+                    .ok_or_else(|| format!("Invalid write set resource type: {}", resource_type))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            sender,
+            entry_function,
+            event_type,
+            write_set_resource_type,
+            success: filter.success,
+        })
+    }
+
+    fn matches(&self, transaction: &Transaction) -> bool {
+        if let Some(success) = self.success {
+            if transaction.info.as_ref().map(|info| info.success) != Some(success) {
+                return false;
+            }
+        }
+
+        if self.sender.is_some() || self.entry_function.is_some() {
+            let request = match &transaction.txn_data {
+                Some(TxnData::User(user_transaction)) => user_transaction.request.as_ref(),
+                _ => None,
+            };
+            let request = match request {
+                Some(request) => request,
+                None => return false,
+            };
+            if let Some(sender) = &self.sender {
+                if request.sender != *sender {
+                    return false;
+                }
+            }
+            if let Some(entry_function) = &self.entry_function {
+                let function = request
+                    .payload
+                    .as_ref()
+                    .and_then(|payload| entry_function_id(payload.payload.as_ref()?));
+                if !function.map_or(false, |function| entry_function.matches(function)) {
+                    return false;
+                }
+            }
+        }
+
+        if let Some(event_type) = &self.event_type {
+            if !events(transaction)
+                .iter()
+                .any(|event| event.type_str == *event_type)
+            {
+                return false;
+            }
+        }
+
+        if let Some(resource_type) = &self.write_set_resource_type {
+            let changes = transaction
+                .info
+                .as_ref()
+                .map(|info| info.changes.as_slice())
+                .unwrap_or_default();
+            if !changes.iter().any(|change| match &change.change {
+                Some(Change::WriteResource(resource)) => resource.type_str == *resource_type,
+                Some(Change::DeleteResource(resource)) => resource.type_str == *resource_type,
+                _ => false,
+            }) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// The entry function called by a payload, directly or through a multisig account.
+fn entry_function_id(payload: &transaction_payload::Payload) -> Option<&EntryFunctionId> {
+    match payload {
+        transaction_payload::Payload::EntryFunctionPayload(payload) => payload.function.as_ref(),
+        transaction_payload::Payload::MultisigPayload(multisig) => {
+            match multisig.transaction_payload.as_ref()?.payload.as_ref()? {
+                multisig_transaction_payload::Payload::EntryFunctionPayload(payload) => {
+                    payload.function.as_ref()
+                },
+            }
+        },
+        _ => None,
+    }
+}
+
+fn events(transaction: &Transaction) -> &[Event] {
+    match &transaction.txn_data {
+        Some(TxnData::User(txn)) => &txn.events,
+        Some(TxnData::Genesis(txn)) => &txn.events,
+        Some(TxnData::BlockMetadata(txn)) => &txn.events,
+        Some(TxnData::StateCheckpoint(_)) | None => &[],
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Normalizes an address to the short form the fullnode renders addresses in, e.g. `0x1`.
+fn normalize_address(address: &str) -> Option<String> {
+    let hex = address.trim().strip_prefix("0x")?;
+    if hex.is_empty() || hex.len() > 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let hex = hex.trim_start_matches('0').to_ascii_lowercase();
+    Some(if hex.is_empty() {
+        "0x0".to_string()
+    } else {
+        format!("0x{}", hex)
+    })
+}
+
+/// Normalizes a move type to the way the fullnode renders types, e.g.
+/// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`: short addresses, and generic type
+/// parameters separated by `, `.
+fn normalize_type(type_str: &str) -> Option<String> {
+    let type_str: String = type_str.chars().filter(|c| !c.is_whitespace()).collect();
+    if !type_str.contains("::") {
+        return None;
+    }
+
+    let mut normalized = String::with_capacity(type_str.len());
+    let mut depth = 0usize;
+    for token in split_type_tokens(&type_str) {
+        match token {
+            "<" => depth += 1,
+            ">" => depth = depth.checked_sub(1)?,
+            _ => {},
+        }
+        if token.starts_with("0x") {
+            normalized.push_str(&normalize_address(token)?);
+        } else if token == "," {
+            normalized.push_str(", ");
+        } else {
+            normalized.push_str(token);
+        }
+    }
+    (depth == 0).then_some(normalized)
+}
+
+/// Splits a type into the tokens `<`, `>`, `,`, `::` and everything in between.
+fn split_type_tokens(type_str: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut start = 0;
+    let mut chars = type_str.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let len = match c {
+            '<' | '>' | ',' => 1,
+            ':' if type_str[i..].starts_with("::") => {
+                chars.next();
+                2
+            },
+            _ => continue,
+        };
+        if start < i {
+            tokens.push(&type_str[start..i]);
+        }
+        tokens.push(&type_str[i..i + len]);
+        start = i + len;
+    }
+    if start < type_str.len() {
+        tokens.push(&type_str[start..]);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::transaction::v1::{
+        EntryFunctionPayload, MoveModuleId, TransactionInfo, TransactionPayload, UserTransaction,
+        UserTransactionRequest, WriteResource, WriteSetChange,
+    };
+
+    fn user_transaction(sender: &str, function: &str, event_type: &str) -> Transaction {
+        let (module, name) = function.rsplit_once("::").unwrap();
+        let (address, module) = module.split_once("::").unwrap();
+        Transaction {
+            info: Some(TransactionInfo {
+                success: true,
+                changes: vec![WriteSetChange {
+                    change: Some(Change::WriteResource(WriteResource {
+                        type_str: "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>".to_string(),
+                        ..WriteResource::default()
+                    })),
+                    ..WriteSetChange::default()
+                }],
+                ..TransactionInfo::default()
+            }),
+            txn_data: Some(TxnData::User(UserTransaction {
+                request: Some(UserTransactionRequest {
+                    sender: sender.to_string(),
+                    payload: Some(TransactionPayload {
+                        payload: Some(transaction_payload::Payload::EntryFunctionPayload(
+                            EntryFunctionPayload {
+                                function: Some(EntryFunctionId {
+                                    module: Some(MoveModuleId {
+                                        address: address.to_string(),
+                                        name: module.to_string(),
+                                    }),
+                                    name: name.to_string(),
+                                }),
+                                ..EntryFunctionPayload::default()
+                            },
+                        )),
+                        ..TransactionPayload::default()
+                    }),
+                    ..UserTransactionRequest::default()
+                }),
+                events: vec![Event {
+                    type_str: event_type.to_string(),
+                    ..Event::default()
+                }],
+            })),
+            ..Transaction::default()
+        }
+    }
+
+    fn filters(filters: Vec<TransactionFilter>) -> TransactionFilters {
+        TransactionFilters::new(&filters).unwrap()
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize_address("0x0001").unwrap(), "0x1");
+        assert_eq!(normalize_address("0x00").unwrap(), "0x0");
+        assert_eq!(normalize_address("0xAbC").unwrap(), "0xabc");
+        assert!(normalize_address("1").is_none());
+        assert!(normalize_address("0xg").is_none());
+
+        assert_eq!(
+            normalize_type("0x01::coin::CoinStore< 0x1::aptos_coin::AptosCoin >").unwrap(),
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+        );
+        assert_eq!(
+            normalize_type("0x1::pair::Pair<u64,vector<0x02::a::B>>").unwrap(),
+            "0x1::pair::Pair<u64, vector<0x2::a::B>>"
+        );
+        assert!(normalize_type("0x1::coin::CoinStore<u64").is_none());
+        assert!(normalize_type("CoinStore").is_none());
+    }
+
+    #[test]
+    fn test_filters() {
+        let transaction =
+            user_transaction("0xa11ce", "0x1::coin::transfer", "0x1::coin::DepositEvent");
+
+        assert!(filters(vec![]).matches(&transaction));
+        for filter in [
+            TransactionFilter {
+                sender: Some("0x00a11ce".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                entry_function: Some("0x1::coin".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                entry_function: Some("0x1::coin::transfer".to_string()),
+                event_type: Some("0x1::coin::DepositEvent".to_string()),
+                success: Some(true),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                write_set_resource_type: Some(
+                    "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>".to_string(),
+                ),
+                ..TransactionFilter::default()
+            },
+        ] {
+            assert!(
+                filters(vec![filter.clone()]).matches(&transaction),
+                "{:?}",
+                filter
+            );
+        }
+
+        for filter in [
+            TransactionFilter {
+                sender: Some("0xb0b".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                entry_function: Some("0x1::coin::register".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                entry_function: Some("0x1::coin::transfer".to_string()),
+                success: Some(false),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                event_type: Some("0x1::coin::WithdrawEvent".to_string()),
+                ..TransactionFilter::default()
+            },
+        ] {
+            assert!(
+                !filters(vec![filter.clone()]).matches(&transaction),
+                "{:?}",
+                filter
+            );
+        }
+
+        // Any of the filters is enough.
+        assert!(filters(vec![
+            TransactionFilter {
+                sender: Some("0xb0b".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                sender: Some("0xa11ce".to_string()),
+                ..TransactionFilter::default()
+            },
+        ])
+        .matches(&transaction));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        for filter in [
+            TransactionFilter {
+                sender: Some("alice".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                entry_function: Some("0x1".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                entry_function: Some("0x1::coin::transfer::extra".to_string()),
+                ..TransactionFilter::default()
+            },
+            TransactionFilter {
+                event_type: Some("DepositEvent".to_string()),
+                ..TransactionFilter::default()
+            },
+        ] {
+            assert!(
+                TransactionFilters::new(&[filter.clone()]).is_err(),
+                "{:?}",
+                filter
+            );
+        }
+        let too_many = vec![TransactionFilter::default(); MAX_FILTERS_PER_REQUEST + 1];
+        assert!(TransactionFilters::new(&too_many).is_err());
+    }
+}
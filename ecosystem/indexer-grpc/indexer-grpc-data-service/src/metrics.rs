@@ -37,6 +37,16 @@ pub static PROCESSED_VERSIONS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Number of transactions that have been filtered out by the filters of the requests.
+pub static FILTERED_OUT_VERSIONS_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "indexer_grpc_data_service_filtered_out_versions",
+        "Number of transactions that have been filtered out by data service",
+        &["request_token", "processor_name"],
+    )
+    .unwrap()
+});
+
 /// Number of errors that data service has encountered.
 pub static ERROR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     register_int_counter_vec!(
@@ -1,27 +1,33 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::metrics::{
-    ERROR_COUNT, LATEST_PROCESSED_VERSION, OBSERVED_LATEST_PROCESSED_VERSION, PROCESSED_BATCH_SIZE,
-    PROCESSED_LATENCY_IN_SECS, PROCESSED_LATENCY_IN_SECS_ALL, PROCESSED_VERSIONS_COUNT,
+use crate::{
+    filter::TransactionFilters,
+    metrics::{
+        ERROR_COUNT, FILTERED_OUT_VERSIONS_COUNT, LATEST_PROCESSED_VERSION,
+        OBSERVED_LATEST_PROCESSED_VERSION, PROCESSED_BATCH_SIZE, PROCESSED_LATENCY_IN_SECS,
+        PROCESSED_LATENCY_IN_SECS_ALL, PROCESSED_VERSIONS_COUNT,
+    },
 };
 use aptos_indexer_grpc_utils::{
     build_protobuf_encoded_transaction_wrappers,
-    cache_operator::{CacheBatchGetStatus, CacheOperator},
-    config::IndexerGrpcFileStoreConfig,
+    cache_operator::{create_cache_operator, CacheBatchGetStatus, CacheOperator},
+    config::{IndexerGrpcCacheConfig, IndexerGrpcFileStoreConfig},
     constants::{GRPC_AUTH_TOKEN_HEADER, GRPC_REQUEST_NAME_HEADER},
     file_store_operator::{FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator},
     time_diff_since_pb_timestamp_in_secs, EncodedTransactionWithVersion,
 };
 use aptos_moving_average::MovingAverage;
 use aptos_protos::{
-    indexer::v1::{raw_data_server::RawData, GetTransactionsRequest, TransactionsResponse},
+    indexer::v1::{
+        raw_data_server::RawData, GetTransactionsRequest, ProcessedRange, TransactionsResponse,
+    },
     transaction::v1::Transaction,
 };
 use futures::Stream;
 use prost::Message;
 use serde::{Deserialize, Serialize};
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{pin::Pin, time::Duration};
 use tokio::sync::{
     mpsc::{channel, error::SendTimeoutError},
     watch::channel as watch_channel,
@@ -57,17 +63,17 @@ const MAX_RESPONSE_CHANNEL_SIZE: usize = 40;
 const RESPONSE_CHANNEL_SEND_TIMEOUT: Duration = Duration::from_secs(120);
 
 pub struct RawDataServerWrapper {
-    pub redis_client: Arc<redis::Client>,
+    pub cache_config: IndexerGrpcCacheConfig,
     pub file_store_config: IndexerGrpcFileStoreConfig,
 }
 
 impl RawDataServerWrapper {
-    pub fn new(redis_address: String, file_store_config: IndexerGrpcFileStoreConfig) -> Self {
+    pub fn new(
+        cache_config: IndexerGrpcCacheConfig,
+        file_store_config: IndexerGrpcFileStoreConfig,
+    ) -> Self {
         Self {
-            redis_client: Arc::new(
-                redis::Client::open(format!("redis://{}", redis_address))
-                    .expect("Create redis client failed."),
-            ),
+            cache_config,
             file_store_config,
         }
     }
@@ -117,6 +123,10 @@ impl RawData for RawDataServerWrapper {
                 return Result::Err(Status::aborted("Starting version is not set"));
             },
         };
+        let filters = match TransactionFilters::new(&request.filters) {
+            Ok(filters) => filters,
+            Err(e) => return Result::Err(Status::invalid_argument(e)),
+        };
         // This is to monitor the latest processed version.
         let (watch_sender, mut watch_receiver) = watch_channel(current_version);
 
@@ -140,28 +150,27 @@ impl RawData for RawDataServerWrapper {
             request_source = request_metadata.request_source.as_str(),
         );
 
-        let redis_client = self.redis_client.clone();
+        let cache_config = self.cache_config.clone();
         let request_metadata_clone = request_metadata.clone();
         tokio::spawn(
             async move {
                 let mut transactions_count = transactions_count;
                 let request_metadata = request_metadata_clone;
-                let conn = match redis_client.get_async_connection().await {
-                    Ok(conn) => conn,
+                let mut cache_operator = match create_cache_operator(&cache_config).await {
+                    Ok(cache_operator) => cache_operator,
                     Err(e) => {
                         ERROR_COUNT
-                            .with_label_values(&["redis_connection_failed"])
+                            .with_label_values(&["cache_connection_failed"])
                             .inc();
                         // Connection will be dropped anyway, so we ignore the error here.
-                        let _result = tx.send_timeout(Err(Status::unavailable("[Indexer Data] Cannot connect to Redis; please retry.")), RESPONSE_CHANNEL_SEND_TIMEOUT).await;
+                        let _result = tx.send_timeout(Err(Status::unavailable("[Indexer Data] Cannot connect to the cache; please retry.")), RESPONSE_CHANNEL_SEND_TIMEOUT).await;
                         error!(
                             error = e.to_string(),
-                            "[Indexer Data] Failed to get redis connection."
+                            "[Indexer Data] Failed to connect to the cache."
                         );
                         return;
                     },
                 };
-                let mut cache_operator = CacheOperator::new(conn);
                 file_store_operator.verify_storage_bucket_existence().await;
 
                 let chain_id = match cache_operator.get_chain_id().await {
@@ -192,7 +201,7 @@ impl RawData for RawDataServerWrapper {
                     // 1. Fetch data from cache and file store.
                     let mut transaction_data = match data_fetch(
                         current_version,
-                        &mut cache_operator,
+                        cache_operator.as_mut(),
                         file_store_operator.as_ref(),
                     )
                     .await
@@ -228,18 +237,18 @@ impl RawData for RawDataServerWrapper {
                         }
                     };
                     // 2. Push the data to the response channel, i.e. stream the data to the client.
-                    let resp_item =
-                        get_transactions_response_builder(transaction_data, chain_id as u32);
-                    let current_batch_size = resp_item.transactions.as_slice().len();
-                    let end_of_batch_version =
-                        resp_item.transactions.as_slice().last().unwrap().version;
-                    let data_latency_in_secs = resp_item
-                        .transactions
+                    let transactions = decode_transactions(transaction_data);
+                    let current_batch_size = transactions.len();
+                    let end_of_batch_version = transactions.last().unwrap().version;
+                    let data_latency_in_secs = transactions
                         .first()
                         .unwrap()
                         .timestamp
                         .as_ref()
                         .map(time_diff_since_pb_timestamp_in_secs);
+                    let resp_item =
+                        get_transactions_response_builder(transactions, chain_id as u32, &filters);
+                    let filtered_out_count = current_batch_size - resp_item.transactions.len();
                     match tx.send_timeout(Result::<TransactionsResponse, Status>::Ok(resp_item), RESPONSE_CHANNEL_SEND_TIMEOUT).await {
                         Ok(_) => {
                             PROCESSED_BATCH_SIZE
@@ -260,6 +269,12 @@ impl RawData for RawDataServerWrapper {
                                     request_metadata.request_name.as_str(),
                                 ])
                                 .inc_by(current_batch_size as u64);
+                            FILTERED_OUT_VERSIONS_COUNT
+                                .with_label_values(&[
+                                    request_metadata.request_token.as_str(),
+                                    request_metadata.request_name.as_str(),
+                                ])
+                                .inc_by(filtered_out_count as u64);
                             if let Some(data_latency_in_secs) = data_latency_in_secs {
                                 PROCESSED_LATENCY_IN_SECS
                                     .with_label_values(&[
@@ -342,21 +357,47 @@ impl RawData for RawDataServerWrapper {
     }
 }
 
+/// Decodes the transactions fetched from cache or file store.
+fn decode_transactions(data: Vec<EncodedTransactionWithVersion>) -> Vec<Transaction> {
+    data.into_iter()
+        .map(|(encoded, _)| {
+            let decoded_transaction = base64::decode(encoded).unwrap();
+            let transaction = Transaction::decode(&*decoded_transaction);
+            transaction.unwrap()
+        })
+        .collect()
+}
+
 /// Builds the response for the get transactions request. Partial batch is ok, i.e., a batch with transactions < 1000.
+/// If the request has filters, the transactions not matching them are dropped, and the response
+/// reports the range of versions it covers so that clients can tell filtered out versions apart
+/// from missing ones.
 fn get_transactions_response_builder(
-    data: Vec<EncodedTransactionWithVersion>,
+    transactions: Vec<Transaction>,
     chain_id: u32,
+    filters: &TransactionFilters,
 ) -> TransactionsResponse {
+    if filters.is_empty() {
+        return TransactionsResponse {
+            chain_id: Some(chain_id as u64),
+            transactions,
+            processed_range: None,
+        };
+    }
+    let processed_range = match (transactions.first(), transactions.last()) {
+        (Some(first), Some(last)) => Some(ProcessedRange {
+            first_version: first.version,
+            last_version: last.version,
+        }),
+        _ => None,
+    };
     TransactionsResponse {
         chain_id: Some(chain_id as u64),
-        transactions: data
+        transactions: transactions
             .into_iter()
-            .map(|(encoded, _)| {
-                let decoded_transaction = base64::decode(encoded).unwrap();
-                let transaction = Transaction::decode(&*decoded_transaction);
-                transaction.unwrap()
-            })
+            .filter(|transaction| filters.matches(transaction))
             .collect(),
+        processed_range,
     }
 }
 
@@ -364,7 +405,7 @@ fn get_transactions_response_builder(
 /// Otherwise, it returns the status of the data fetching.
 async fn data_fetch(
     starting_version: u64,
-    cache_operator: &mut CacheOperator<redis::aio::Connection>,
+    cache_operator: &mut dyn CacheOperator,
     file_store_operator: &dyn FileStoreOperator,
 ) -> anyhow::Result<TransactionsDataStatus> {
     let batch_get_result = cache_operator
@@ -6,7 +6,9 @@ pub mod processor;
 
 use anyhow::Result;
 use aptos_indexer_grpc_server_framework::RunnableConfig;
-use aptos_indexer_grpc_utils::config::IndexerGrpcFileStoreConfig;
+use aptos_indexer_grpc_utils::config::{
+    deserialize_cache_config, IndexerGrpcCacheConfig, IndexerGrpcFileStoreConfig,
+};
 use processor::Processor;
 use serde::{Deserialize, Serialize};
 
@@ -14,16 +16,18 @@ use serde::{Deserialize, Serialize};
 #[serde(deny_unknown_fields)]
 pub struct IndexerGrpcFileStoreWorkerConfig {
     pub file_store_config: IndexerGrpcFileStoreConfig,
-    pub redis_main_instance_address: String,
+    #[serde(
+        alias = "redis_main_instance_address",
+        deserialize_with = "deserialize_cache_config"
+    )]
+    pub cache_config: IndexerGrpcCacheConfig,
 }
 
 #[async_trait::async_trait]
 impl RunnableConfig for IndexerGrpcFileStoreWorkerConfig {
     async fn run(&self) -> Result<()> {
-        let mut processor = Processor::new(
-            self.redis_main_instance_address.clone(),
-            self.file_store_config.clone(),
-        );
+        let mut processor =
+            Processor::new(self.cache_config.clone(), self.file_store_config.clone());
         processor.run().await;
         Ok(())
     }
@@ -4,8 +4,8 @@
 use crate::metrics::{LATEST_PROCESSED_VERSION, PROCESSED_VERSIONS_COUNT};
 use aptos_indexer_grpc_utils::{
     build_protobuf_encoded_transaction_wrappers,
-    cache_operator::{CacheBatchGetStatus, CacheOperator},
-    config::IndexerGrpcFileStoreConfig,
+    cache_operator::{create_cache_operator, CacheBatchGetStatus, CacheOperator},
+    config::{IndexerGrpcCacheConfig, IndexerGrpcFileStoreConfig},
     constants::BLOB_STORAGE_SIZE,
     file_store_operator::{FileStoreOperator, GcsFileStoreOperator, LocalFileStoreOperator},
     EncodedTransactionWithVersion,
@@ -19,37 +19,33 @@ const AHEAD_OF_CACHE_SLEEP_DURATION_IN_MILLIS: u64 = 100;
 
 /// Processor tails the data in cache and stores the data in file store.
 pub struct Processor {
-    cache_operator: Option<CacheOperator<redis::aio::Connection>>,
+    cache_operator: Option<Box<dyn CacheOperator>>,
     file_store_processor: Option<Box<dyn FileStoreOperator>>,
     cache_chain_id: Option<u64>,
-    redis_main_instance_address: String,
+    cache_config: IndexerGrpcCacheConfig,
     file_store_config: IndexerGrpcFileStoreConfig,
 }
 
 impl Processor {
     pub fn new(
-        redis_main_instance_address: String,
+        cache_config: IndexerGrpcCacheConfig,
         file_store_config: IndexerGrpcFileStoreConfig,
     ) -> Self {
         Self {
             cache_operator: None,
             file_store_processor: None,
             cache_chain_id: None,
-            redis_main_instance_address,
+            cache_config,
             file_store_config,
         }
     }
 
-    /// Init the processor, including creating the redis connection and file store operator.
+    /// Init the processor, including creating the cache operator and file store operator.
     async fn init(&mut self) {
-        // Connection to redis is a hard dependency for file store processor.
-        let conn = redis::Client::open(format!("redis://{}", self.redis_main_instance_address))
-            .expect("Create redis client failed.")
-            .get_async_connection()
+        // Connection to the cache is a hard dependency for file store processor.
+        let mut cache_operator = create_cache_operator(&self.cache_config)
             .await
-            .expect("Create redis connection failed.");
-
-        let mut cache_operator = CacheOperator::new(conn);
+            .expect("Create cache operator failed.");
         let chain_id = cache_operator
             .get_chain_id()
             .await
@@ -6,8 +6,9 @@ use aptos_api_types::{
     EntryFunctionPayload, Event, GenesisPayload, MoveAbility, MoveFunction,
     MoveFunctionGenericTypeParam, MoveFunctionVisibility, MoveModule, MoveModuleBytecode,
     MoveModuleId, MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType,
-    MultiEd25519Signature, MultisigPayload, MultisigTransactionPayload, ScriptPayload, Transaction,
-    TransactionInfo, TransactionPayload, TransactionSignature, WriteSet, WriteSetChange,
+    MultiEd25519Signature, MultisigPayload, MultisigTransactionPayload, ScriptPayload,
+    Secp256k1EcdsaSignature, Transaction, TransactionInfo, TransactionPayload,
+    TransactionSignature, WriteSet, WriteSetChange,
 };
 use aptos_bitvec::BitVec;
 use aptos_logger::warn;
@@ -541,6 +542,15 @@ pub fn convert_ed25519_signature(sig: &Ed25519Signature) -> transaction::Ed25519
     }
 }
 
+pub fn convert_secp256k1_ecdsa_signature(
+    sig: &Secp256k1EcdsaSignature,
+) -> transaction::Secp256k1EcdsaSignature {
+    transaction::Secp256k1EcdsaSignature {
+        public_key: sig.public_key.0.clone(),
+        signature: sig.signature.0.clone(),
+    }
+}
+
 pub fn convert_multi_ed25519_signature(
     sig: &MultiEd25519Signature,
 ) -> transaction::MultiEd25519Signature {
@@ -564,6 +574,9 @@ pub fn convert_account_signature(
         AccountSignature::MultiEd25519Signature(_) => {
             transaction::account_signature::Type::MultiEd25519
         },
+        AccountSignature::Secp256k1EcdsaSignature(_) => {
+            transaction::account_signature::Type::Secp256k1Ecdsa
+        },
     };
     let signature = match account_signature {
         AccountSignature::Ed25519Signature(s) => {
@@ -574,6 +587,11 @@ pub fn convert_account_signature(
                 convert_multi_ed25519_signature(s),
             )
         },
+        AccountSignature::Secp256k1EcdsaSignature(s) => {
+            transaction::account_signature::Signature::Secp256k1Ecdsa(
+                convert_secp256k1_ecdsa_signature(s),
+            )
+        },
     };
     transaction::AccountSignature {
         r#type: r#type as i32,
@@ -594,6 +612,10 @@ pub fn convert_transaction_signature(
             transaction::signature::Type::MultiEd25519
         },
         TransactionSignature::MultiAgentSignature(_) => transaction::signature::Type::MultiAgent,
+        TransactionSignature::FeePayerSignature(_) => transaction::signature::Type::FeePayer,
+        TransactionSignature::Secp256k1EcdsaSignature(_) => {
+            transaction::signature::Type::Secp256k1Ecdsa
+        },
     };
 
     let signature = match signature {
@@ -618,6 +640,26 @@ pub fn convert_transaction_signature(
                     .collect(),
             })
         },
+        TransactionSignature::FeePayerSignature(s) => {
+            transaction::signature::Signature::FeePayer(transaction::FeePayerSignature {
+                sender: Some(convert_account_signature(&s.sender)),
+                secondary_signer_addresses: s
+                    .secondary_signer_addresses
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                secondary_signers: s
+                    .secondary_signers
+                    .iter()
+                    .map(convert_account_signature)
+                    .collect(),
+                fee_payer_address: s.fee_payer_address.to_string(),
+                fee_payer_signer: Some(convert_account_signature(&s.fee_payer_signer)),
+            })
+        },
+        TransactionSignature::Secp256k1EcdsaSignature(s) => {
+            transaction::signature::Signature::Secp256k1Ecdsa(convert_secp256k1_ecdsa_signature(s))
+        },
     };
 
     Some(transaction::Signature {
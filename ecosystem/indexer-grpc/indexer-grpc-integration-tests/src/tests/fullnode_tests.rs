@@ -7,8 +7,8 @@ use aptos_indexer_grpc_server_framework::{
     run_server_with_config, setup_logging, setup_panic_handler, GenericConfig, RunnableConfig,
 };
 use aptos_indexer_grpc_utils::{
-    cache_operator::CacheOperator,
-    config::{IndexerGrpcFileStoreConfig, LocalFileStore},
+    cache_operator::{CacheOperator, InMemoryCacheOperator},
+    config::{InMemoryCache, IndexerGrpcCacheConfig, IndexerGrpcFileStoreConfig, LocalFileStore},
     constants::BLOB_STORAGE_SIZE,
     file_store_operator::{FileStoreOperator, LocalFileStoreOperator},
 };
@@ -23,9 +23,11 @@ use tracing::info;
 
 static TESTNET_REST_API_URL: &str = "http://localhost:8080";
 static TESTNET_FULLNODE_GRPC_URL: &str = "localhost:50051";
-static REDIS_PRIMARY_URL: &str = "localhost:6379";
 
 static MINT_KEY_FILE_NAME: &str = "mint.key";
+// Path to the mint key of the local testnet. If not set, the key is read from the validator
+// running in docker.
+static MINT_KEY_PATH_ENV_VAR: &str = "INDEXER_GRPC_TEST_MINT_KEY_PATH";
 
 /// Get the name of docker containers that match the given regex
 /// This works around different docker compose v1 and v2 naming conventions
@@ -42,35 +44,21 @@ fn get_container_by_name_regex(name_regex: Regex) -> Result<Vec<String>> {
     Ok(ret)
 }
 
-/// Connects to the local redis running in docker and resets it
-async fn reset_redis() -> Result<()> {
-    let redis_containers = get_container_by_name_regex(Regex::new(r".*redis.*")?)?;
-    for container in redis_containers {
-        let _ = std::process::Command::new("docker")
-            .args(&["exec", &container, "redis-cli", "FLUSHALL"])
-            .output()?;
-    }
-
-    let conn = redis::Client::open(format!("redis://{}", REDIS_PRIMARY_URL))
-        .expect("Create redis client failed.")
-        .get_async_connection()
-        .await
-        .expect("Create redis connection failed.");
-    let mut cache_operator = CacheOperator::new(conn);
-    match cache_operator.get_latest_version().await {
-        Ok(x) => {
-            bail!(
-                "Redis did not scale down properly. There's still stuff in the cache. Latest version: {}",
-                x
-            );
-        },
-        Err(_) => info!("Redis scaled down properly"),
-    }
-    Ok(())
+/// Creates an in-memory cache config, unique to the test so that tests don't share a cache.
+fn in_memory_cache_config(test_name: &str) -> IndexerGrpcCacheConfig {
+    IndexerGrpcCacheConfig::InMemoryCache(InMemoryCache {
+        in_memory_cache_name: test_name.to_string(),
+        ..InMemoryCache::default()
+    })
 }
 
 /// Fetch the mint key from the running local testnet and dump it into the path specified
 async fn dump_mint_key_to_file(path: &PathBuf) -> Result<String> {
+    if let Ok(mint_key_path) = std::env::var(MINT_KEY_PATH_ENV_VAR) {
+        info!("Mint key path: {}", mint_key_path);
+        return Ok(mint_key_path);
+    }
+
     let validator_containers =
         get_container_by_name_regex(Regex::new(r"validator-testnet.*validator.*")?)?;
     if validator_containers.len() != 1 {
@@ -195,7 +183,6 @@ async fn setup_test() {
     // so we need to centrally set up logging and panic handler, whereas they are usually done in the same service
     setup_logging();
     setup_panic_handler();
-    reset_redis().await.expect("Failed to reset redis for test");
 
     // aptos_logger too
     aptos_logger::Logger::init_for_testing();
@@ -226,7 +213,7 @@ async fn test_cold_start_cache_worker_progress() {
         file_store_config: IndexerGrpcFileStoreConfig::LocalFileStore(LocalFileStore {
             local_file_store_path: tmp_dir.path().to_path_buf(),
         }),
-        redis_main_instance_address: REDIS_PRIMARY_URL.to_string(),
+        cache_config: in_memory_cache_config("test_cold_start_cache_worker_progress"),
     };
 
     let (_cache_worker_port, _cache_worker_handle) =
@@ -234,18 +221,15 @@ async fn test_cold_start_cache_worker_progress() {
             .await
             .expect("Failed to start CacheWorker");
 
-    let conn = redis::Client::open(format!("redis://{}", REDIS_PRIMARY_URL.to_string()))
-        .expect("Create redis client failed.")
-        .get_async_connection()
-        .await
-        .expect("Create redis connection failed.");
-
     let check_cache_secs = 30;
     let check_cache_frequency_secs = 5;
     let tries = check_cache_secs / check_cache_frequency_secs;
 
     // check that the cache was written to
-    let mut cache_operator = CacheOperator::new(conn);
+    let mut cache_operator = InMemoryCacheOperator::new(
+        "test_cold_start_cache_worker_progress",
+        InMemoryCache::default().in_memory_cache_capacity,
+    );
     let mut chain_id = 0;
     for _ in 0..tries {
         match cache_operator.get_chain_id().await {
@@ -292,11 +276,11 @@ async fn test_cold_start_file_store_worker_progress() {
         file_store_config: IndexerGrpcFileStoreConfig::LocalFileStore(LocalFileStore {
             local_file_store_path: tmp_dir.path().to_path_buf(),
         }),
-        redis_main_instance_address: REDIS_PRIMARY_URL.to_string(),
+        cache_config: in_memory_cache_config("test_cold_start_file_store_worker_progress"),
     };
 
     let file_store_worker_config = IndexerGrpcFileStoreWorkerConfig {
-        redis_main_instance_address: REDIS_PRIMARY_URL.to_string(),
+        cache_config: in_memory_cache_config("test_cold_start_file_store_worker_progress"),
         file_store_config: IndexerGrpcFileStoreConfig::LocalFileStore(LocalFileStore {
             local_file_store_path: tmp_dir.path().to_path_buf(),
         }),
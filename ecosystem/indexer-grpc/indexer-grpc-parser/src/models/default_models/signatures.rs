@@ -9,8 +9,9 @@ use anyhow::{Context, Result};
 use aptos_protos::transaction::v1::{
     account_signature::Signature as AccountSignatureEnum, signature::Signature as SignatureEnum,
     AccountSignature as ProtoAccountSignature, Ed25519Signature as Ed25519SignaturePB,
-    MultiAgentSignature as ProtoMultiAgentSignature,
-    MultiEd25519Signature as ProtoMultiEd25519Signature, Signature as TransactionSignaturePB,
+    FeePayerSignature as ProtoFeePayerSignature, MultiAgentSignature as ProtoMultiAgentSignature,
+    MultiEd25519Signature as ProtoMultiEd25519Signature,
+    Secp256k1EcdsaSignature as ProtoSecp256k1EcdsaSignature, Signature as TransactionSignaturePB,
 };
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
@@ -73,6 +74,21 @@ impl Signature {
                 transaction_version,
                 transaction_block_height,
             ),
+            SignatureEnum::FeePayer(sig) => Self::parse_fee_payer_signature(
+                sig,
+                sender,
+                transaction_version,
+                transaction_block_height,
+            ),
+            SignatureEnum::Secp256k1Ecdsa(sig) => Ok(vec![Self::parse_secp256k1_ecdsa_signature(
+                sig,
+                sender,
+                transaction_version,
+                transaction_block_height,
+                true,
+                0,
+                None,
+            )]),
         }
     }
 
@@ -81,6 +97,8 @@ impl Signature {
             SignatureEnum::Ed25519(_) => String::from("ed25519_signature"),
             SignatureEnum::MultiEd25519(_) => String::from("multi_ed25519_signature"),
             SignatureEnum::MultiAgent(_) => String::from("multi_agent_signature"),
+            SignatureEnum::FeePayer(_) => String::from("fee_payer_signature"),
+            SignatureEnum::Secp256k1Ecdsa(_) => String::from("secp256k1_ecdsa_signature"),
         }
     }
 
@@ -109,6 +127,31 @@ impl Signature {
         }
     }
 
+    fn parse_secp256k1_ecdsa_signature(
+        s: &ProtoSecp256k1EcdsaSignature,
+        sender: &String,
+        transaction_version: i64,
+        transaction_block_height: i64,
+        is_sender_primary: bool,
+        multi_agent_index: i64,
+        override_address: Option<&String>,
+    ) -> Self {
+        let signer = standardize_address(override_address.unwrap_or(sender));
+        Self {
+            transaction_version,
+            transaction_block_height,
+            signer,
+            is_sender_primary,
+            type_: String::from("secp256k1_ecdsa_signature"),
+            public_key: format!("0x{}", hex::encode(s.public_key.as_slice())),
+            threshold: 1,
+            public_key_indices: serde_json::Value::Array(vec![]),
+            signature: format!("0x{}", hex::encode(s.signature.as_slice())),
+            multi_agent_index,
+            multi_sig_index: 0,
+        }
+    }
+
     fn parse_multi_signature(
         s: &ProtoMultiEd25519Signature,
         sender: &String,
@@ -191,6 +234,35 @@ impl Signature {
         Ok(signatures)
     }
 
+    fn parse_fee_payer_signature(
+        s: &ProtoFeePayerSignature,
+        sender: &String,
+        transaction_version: i64,
+        transaction_block_height: i64,
+    ) -> Result<Vec<Self>> {
+        let mut signatures = Self::parse_multi_agent_signature(
+            &ProtoMultiAgentSignature {
+                sender: s.sender.clone(),
+                secondary_signer_addresses: s.secondary_signer_addresses.clone(),
+                secondary_signers: s.secondary_signers.clone(),
+            },
+            sender,
+            transaction_version,
+            transaction_block_height,
+        )?;
+        // The fee payer signs after the secondary signers
+        signatures.append(&mut Self::parse_multi_agent_signature_helper(
+            s.fee_payer_signer.as_ref().unwrap(),
+            sender,
+            transaction_version,
+            transaction_block_height,
+            false,
+            s.secondary_signers.len() as i64,
+            Some(&s.fee_payer_address),
+        ));
+        Ok(signatures)
+    }
+
     fn parse_multi_agent_signature_helper(
         s: &ProtoAccountSignature,
         sender: &String,
@@ -220,6 +292,17 @@ impl Signature {
                 multi_agent_index,
                 override_address,
             ),
+            AccountSignatureEnum::Secp256k1Ecdsa(sig) => {
+                vec![Self::parse_secp256k1_ecdsa_signature(
+                    sig,
+                    sender,
+                    transaction_version,
+                    transaction_block_height,
+                    is_sender_primary,
+                    multi_agent_index,
+                    override_address,
+                )]
+            },
         }
     }
 }
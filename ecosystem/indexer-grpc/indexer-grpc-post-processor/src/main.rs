@@ -13,7 +13,6 @@ use tracing::info;
 pub struct IndexerGrpcPostProcessorConfig {
     pub file_store_bucket_name: String,
     pub verfied_backup_bucket_name: String,
-    pub redis_main_instance_address: String,
     pub fullnode_grpc_address: String,
     pub public_fullnode_address: String,
 }
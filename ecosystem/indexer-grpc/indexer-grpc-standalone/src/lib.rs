@@ -0,0 +1,84 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, Result};
+use aptos_indexer_grpc_cache_worker::IndexerGrpcCacheWorkerConfig;
+use aptos_indexer_grpc_data_service::IndexerGrpcDataServiceConfig;
+use aptos_indexer_grpc_file_store::IndexerGrpcFileStoreWorkerConfig;
+use aptos_indexer_grpc_server_framework::RunnableConfig;
+use aptos_indexer_grpc_utils::{
+    cache_operator::create_cache_operator,
+    config::{IndexerGrpcCacheConfig, IndexerGrpcFileStoreConfig},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::info;
+
+// How often to check whether the cache worker has set up the cache.
+const CACHE_SETUP_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Runs the cache worker, file store and data service in a single process. With the default
+/// in-memory cache, this needs neither redis nor GCS when used with a local file store.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct IndexerGrpcStandaloneConfig {
+    pub fullnode_grpc_address: String,
+    pub data_service_grpc_listen_address: String,
+    pub whitelisted_auth_tokens: Vec<String>,
+    pub file_store_config: IndexerGrpcFileStoreConfig,
+    #[serde(default)]
+    pub cache_config: IndexerGrpcCacheConfig,
+}
+
+#[async_trait::async_trait]
+impl RunnableConfig for IndexerGrpcStandaloneConfig {
+    async fn run(&self) -> Result<()> {
+        let cache_worker_config = IndexerGrpcCacheWorkerConfig {
+            fullnode_grpc_address: self.fullnode_grpc_address.clone(),
+            file_store_config: self.file_store_config.clone(),
+            cache_config: self.cache_config.clone(),
+        };
+        let file_store_worker_config = IndexerGrpcFileStoreWorkerConfig {
+            file_store_config: self.file_store_config.clone(),
+            cache_config: self.cache_config.clone(),
+        };
+        let data_service_config = IndexerGrpcDataServiceConfig {
+            data_service_grpc_listen_address: self.data_service_grpc_listen_address.clone(),
+            whitelisted_auth_tokens: self.whitelisted_auth_tokens.clone(),
+            file_store_config: self.file_store_config.clone(),
+            cache_config: self.cache_config.clone(),
+        };
+
+        let cache_worker = tokio::spawn(async move { cache_worker_config.run().await });
+        // The file store and data service read the chain id from the cache on startup.
+        wait_for_cache_setup(&self.cache_config).await?;
+        let file_store_worker = tokio::spawn(async move { file_store_worker_config.run().await });
+        let data_service = tokio::spawn(async move { data_service_config.run().await });
+
+        // The services run forever, so any of them exiting is an error.
+        let (service, result) = tokio::select! {
+            result = cache_worker => ("cache worker", result),
+            result = file_store_worker => ("file store worker", result),
+            result = data_service => ("data service", result),
+        };
+        match result {
+            Ok(Ok(())) => Err(anyhow!("The {} exited unexpectedly.", service)),
+            Ok(Err(e)) => Err(e.context(format!("The {} failed.", service))),
+            Err(e) => Err(anyhow!("The {} panicked: {}", service, e)),
+        }
+    }
+
+    fn get_server_name(&self) -> String {
+        "idxstandalone".to_string()
+    }
+}
+
+/// Waits until the cache worker has set the chain id in the cache.
+async fn wait_for_cache_setup(cache_config: &IndexerGrpcCacheConfig) -> Result<()> {
+    let mut cache_operator = create_cache_operator(cache_config).await?;
+    while cache_operator.get_chain_id().await.is_err() {
+        tokio::time::sleep(CACHE_SETUP_CHECK_INTERVAL).await;
+    }
+    info!("[Indexer Standalone] Cache is set up, starting the file store and data service.");
+    Ok(())
+}
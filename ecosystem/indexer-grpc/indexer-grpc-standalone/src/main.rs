@@ -0,0 +1,13 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_indexer_grpc_server_framework::ServerArgs;
+use aptos_indexer_grpc_standalone::IndexerGrpcStandaloneConfig;
+use clap::Parser;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = ServerArgs::parse();
+    args.run::<IndexerGrpcStandaloneConfig>().await
+}
@@ -0,0 +1,345 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{cache_operator::*, constants::BLOB_STORAGE_SIZE};
+use once_cell::sync::Lazy;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, RwLock},
+};
+
+// In-memory caches of this process, by name. Operators created with the same name share
+// the cache, e.g., the cache worker, file store and data service running in one binary.
+static IN_MEMORY_CACHES: Lazy<Mutex<HashMap<String, Arc<RwLock<InMemoryCacheState>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct InMemoryCacheState {
+    // Maximum number of transactions kept; the oldest ones are evicted first.
+    capacity: u64,
+    chain_id: Option<u64>,
+    // The next version to be cached, i.e., versions before it are available.
+    latest_version: Option<u64>,
+    // Encoded transactions, by version.
+    transactions: BTreeMap<u64, String>,
+}
+
+// Cache operator keeping the transactions in the memory of the process, for deployments
+// running all the services on a single host without redis.
+pub struct InMemoryCacheOperator {
+    state: Arc<RwLock<InMemoryCacheState>>,
+}
+
+impl InMemoryCacheOperator {
+    /// Opens the cache with the given name, creating it with the given capacity if it
+    /// doesn't exist yet.
+    pub fn new(name: &str, capacity: u64) -> Self {
+        let state = IN_MEMORY_CACHES
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                Arc::new(RwLock::new(InMemoryCacheState {
+                    capacity: capacity.max(1),
+                    chain_id: None,
+                    latest_version: None,
+                    transactions: BTreeMap::new(),
+                }))
+            })
+            .clone();
+        Self { state }
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheOperator for InMemoryCacheOperator {
+    async fn cache_setup_if_needed(&mut self) -> bool {
+        let mut state = self.state.write().unwrap();
+        if state.latest_version.is_some() {
+            return false;
+        }
+        state.latest_version = Some(0);
+        tracing::info!(
+            initialized_latest_version = 0,
+            "Cache latest version is initialized."
+        );
+        true
+    }
+
+    async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<()> {
+        let mut state = self.state.write().unwrap();
+        match state.chain_id {
+            Some(cached_chain_id) if cached_chain_id != chain_id => {
+                anyhow::bail!("Chain id is not correct.")
+            },
+            Some(_) => Ok(()),
+            None => {
+                state.chain_id = Some(chain_id);
+                Ok(())
+            },
+        }
+    }
+
+    async fn get_chain_id(&mut self) -> anyhow::Result<u64> {
+        self.state
+            .read()
+            .unwrap()
+            .chain_id
+            .ok_or_else(|| anyhow::anyhow!("Chain id is not in cache yet."))
+    }
+
+    async fn get_latest_version(&mut self) -> anyhow::Result<u64> {
+        self.state
+            .read()
+            .unwrap()
+            .latest_version
+            .ok_or_else(|| anyhow::anyhow!("Latest version is not in cache yet."))
+    }
+
+    async fn update_cache_transactions(
+        &mut self,
+        transactions: Vec<(u64, String, u64)>,
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.write().unwrap();
+        for (version, encoded_proto_data, _timestamp_in_seconds) in transactions {
+            state.transactions.insert(version, encoded_proto_data);
+        }
+        while state.transactions.len() as u64 > state.capacity {
+            state.transactions.pop_first();
+        }
+        Ok(())
+    }
+
+    async fn update_cache_latest_version(
+        &mut self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<()> {
+        let mut state = self.state.write().unwrap();
+        tracing::debug!(
+            num_of_versions = num_of_versions,
+            version = version,
+            "Updating latest version in cache."
+        );
+        let first_cached_version = state.transactions.keys().next().copied();
+        match state.latest_version {
+            // Unlike redis, the cache doesn't survive restarts, so a new cache starts at the first
+            // transactions cached, i.e., the version of the file store. Older versions are served
+            // from the file store.
+            Some(0) if first_cached_version == version.checked_sub(num_of_versions) => {
+                state.latest_version = Some(version)
+            },
+            Some(latest_version) if latest_version + num_of_versions < version => {
+                tracing::error!(
+                    version = version,
+                    "Cache latest version update failed. The version is beyond the next expected version."
+                );
+                panic!("version is not right.");
+            },
+            Some(latest_version) => state.latest_version = Some(latest_version.max(version)),
+            None => state.latest_version = Some(version),
+        }
+        Ok(())
+    }
+
+    async fn batch_get_encoded_proto_data(
+        &mut self,
+        start_version: u64,
+    ) -> anyhow::Result<CacheBatchGetStatus> {
+        let state = self.state.read().unwrap();
+        let latest_version = state
+            .latest_version
+            .ok_or_else(|| anyhow::anyhow!("Latest version is not in cache yet."))?;
+        if start_version >= latest_version {
+            return Ok(CacheBatchGetStatus::NotReady);
+        }
+        if !state.transactions.contains_key(&start_version) {
+            return Ok(CacheBatchGetStatus::EvictedFromCache);
+        }
+
+        let end_version = latest_version.min(start_version + BLOB_STORAGE_SIZE as u64);
+        let encoded_transactions = state
+            .transactions
+            .range(start_version..end_version)
+            .map(|(_, encoded_proto_data)| encoded_proto_data.clone())
+            .collect::<Vec<String>>();
+        if encoded_transactions.len() as u64 != end_version - start_version {
+            anyhow::bail!(
+                "Cache is missing transactions in [{}, {}).",
+                start_version,
+                end_version
+            );
+        }
+        Ok(CacheBatchGetStatus::Ok(encoded_transactions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transactions(versions: std::ops::Range<u64>) -> Vec<(u64, String, u64)> {
+        versions.map(|v| (v, v.to_string(), 0)).collect()
+    }
+
+    #[tokio::test]
+    async fn cache_is_setup_once() {
+        let mut cache_operator = InMemoryCacheOperator::new("cache_is_setup_once", 10);
+        assert!(cache_operator.cache_setup_if_needed().await);
+        assert!(!cache_operator.cache_setup_if_needed().await);
+        assert_eq!(cache_operator.get_latest_version().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn chain_id_is_verified() {
+        let mut cache_operator = InMemoryCacheOperator::new("chain_id_is_verified", 10);
+        assert!(cache_operator.get_chain_id().await.is_err());
+        cache_operator.update_or_verify_chain_id(1).await.unwrap();
+        cache_operator.update_or_verify_chain_id(1).await.unwrap();
+        assert!(cache_operator.update_or_verify_chain_id(2).await.is_err());
+        assert_eq!(cache_operator.get_chain_id().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn caches_with_the_same_name_are_shared() {
+        let mut writer = InMemoryCacheOperator::new("caches_with_the_same_name_are_shared", 10);
+        let mut reader = InMemoryCacheOperator::new("caches_with_the_same_name_are_shared", 10);
+        let mut other = InMemoryCacheOperator::new("caches_with_the_same_name_are_not_shared", 10);
+        writer.cache_setup_if_needed().await;
+        writer.update_or_verify_chain_id(1).await.unwrap();
+        assert_eq!(reader.get_chain_id().await.unwrap(), 1);
+        assert!(other.get_chain_id().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn batch_get_cached_transactions() {
+        let mut cache_operator = InMemoryCacheOperator::new("batch_get_cached_transactions", 10);
+        cache_operator.cache_setup_if_needed().await;
+        assert_eq!(
+            cache_operator
+                .batch_get_encoded_proto_data(0)
+                .await
+                .unwrap(),
+            CacheBatchGetStatus::NotReady
+        );
+
+        cache_operator
+            .update_cache_transactions(transactions(0..3))
+            .await
+            .unwrap();
+        cache_operator
+            .update_cache_latest_version(3, 3)
+            .await
+            .unwrap();
+        assert_eq!(
+            cache_operator
+                .batch_get_encoded_proto_data(1)
+                .await
+                .unwrap(),
+            CacheBatchGetStatus::Ok(vec!["1".to_string(), "2".to_string()])
+        );
+        assert_eq!(
+            cache_operator
+                .batch_get_encoded_proto_data(3)
+                .await
+                .unwrap(),
+            CacheBatchGetStatus::NotReady
+        );
+    }
+
+    #[tokio::test]
+    async fn oldest_transactions_are_evicted() {
+        let mut cache_operator = InMemoryCacheOperator::new("oldest_transactions_are_evicted", 5);
+        cache_operator.cache_setup_if_needed().await;
+        cache_operator
+            .update_cache_transactions(transactions(0..8))
+            .await
+            .unwrap();
+        cache_operator
+            .update_cache_latest_version(8, 8)
+            .await
+            .unwrap();
+        assert_eq!(
+            cache_operator
+                .batch_get_encoded_proto_data(2)
+                .await
+                .unwrap(),
+            CacheBatchGetStatus::EvictedFromCache
+        );
+        assert_eq!(
+            cache_operator
+                .batch_get_encoded_proto_data(6)
+                .await
+                .unwrap(),
+            CacheBatchGetStatus::Ok(vec!["6".to_string(), "7".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_get_is_capped_at_blob_storage_size() {
+        let mut cache_operator = InMemoryCacheOperator::new(
+            "batch_get_is_capped_at_blob_storage_size",
+            2 * BLOB_STORAGE_SIZE as u64,
+        );
+        cache_operator.cache_setup_if_needed().await;
+        let num_of_versions = BLOB_STORAGE_SIZE as u64 + 10;
+        cache_operator
+            .update_cache_transactions(transactions(0..num_of_versions))
+            .await
+            .unwrap();
+        cache_operator
+            .update_cache_latest_version(num_of_versions, num_of_versions)
+            .await
+            .unwrap();
+        match cache_operator
+            .batch_get_encoded_proto_data(5)
+            .await
+            .unwrap()
+        {
+            CacheBatchGetStatus::Ok(encoded_transactions) => {
+                assert_eq!(encoded_transactions.len(), BLOB_STORAGE_SIZE)
+            },
+            status => panic!("Unexpected status: {:?}", status),
+        }
+    }
+
+    #[tokio::test]
+    async fn new_cache_starts_at_first_cached_version() {
+        let mut cache_operator =
+            InMemoryCacheOperator::new("new_cache_starts_at_first_cached_version", 10);
+        cache_operator.cache_setup_if_needed().await;
+        cache_operator
+            .update_cache_transactions(transactions(5..8))
+            .await
+            .unwrap();
+        cache_operator
+            .update_cache_latest_version(3, 8)
+            .await
+            .unwrap();
+        assert_eq!(cache_operator.get_latest_version().await.unwrap(), 8);
+        assert_eq!(
+            cache_operator
+                .batch_get_encoded_proto_data(2)
+                .await
+                .unwrap(),
+            CacheBatchGetStatus::EvictedFromCache
+        );
+        assert_eq!(
+            cache_operator
+                .batch_get_encoded_proto_data(5)
+                .await
+                .unwrap(),
+            CacheBatchGetStatus::Ok(vec!["5".to_string(), "6".to_string(), "7".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    #[should_panic]
+    async fn latest_version_gap_panics() {
+        let mut cache_operator = InMemoryCacheOperator::new("latest_version_gap_panics", 10);
+        cache_operator.cache_setup_if_needed().await;
+        cache_operator
+            .update_cache_latest_version(3, 5)
+            .await
+            .unwrap();
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::IndexerGrpcCacheConfig;
+
+pub mod in_memory_cache;
+pub use in_memory_cache::*;
+pub mod redis_cache;
+pub use redis_cache::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheBatchGetStatus {
+    /// OK with batch of encoded transactions.
+    Ok(Vec<String>),
+    /// Requested version is already evicted from cache. Visit file store instead.
+    EvictedFromCache,
+    /// Not ready yet. Wait and retry.
+    NotReady,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheUpdateStatus {
+    /// 0 - Cache is updated from version x to x + 1. New key `x+1` with corresponding encoded data is added.
+    Ok,
+    /// 1 - Cache is not updated because current version is ahead of the latest version.
+    AheadOfLatestVersion,
+    /// 2 - Cache is not updated but verified. This is the case when the cache is updated by other workers from an old version.
+    VerifiedWithoutUpdate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CacheCoverageStatus {
+    /// Requested version is not processed by cache worker yet.
+    DataNotReady,
+    /// Requested version is cached.
+    /// Transactions are available in cache: [requested_version, requested_version + value).
+    CacheHit(u64),
+    /// Requested version is evicted from cache.
+    CacheEvicted,
+}
+
+#[async_trait::async_trait]
+pub trait CacheOperator: Send {
+    /// Sets up the cache if needed, i.e., initializes the latest version to 0. Returns true if
+    /// the cache was set up by this call.
+    async fn cache_setup_if_needed(&mut self) -> bool;
+    /// Updates the chain id in cache if missing; otherwise, verifies the chain id.
+    async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<()>;
+    /// Gets the chain id, for downstream systems to infer it from cache.
+    async fn get_chain_id(&mut self) -> anyhow::Result<u64>;
+    /// Gets the latest version, i.e., the next version to be cached.
+    async fn get_latest_version(&mut self) -> anyhow::Result<u64>;
+    /// Caches the transactions, as (version, encoded transaction, timestamp in seconds).
+    async fn update_cache_transactions(
+        &mut self,
+        transactions: Vec<(u64, String, u64)>,
+    ) -> anyhow::Result<()>;
+    /// Moves the latest version to `version` after `num_of_versions` transactions were cached.
+    /// Panics if that leaves a gap in the cache.
+    async fn update_cache_latest_version(
+        &mut self,
+        num_of_versions: u64,
+        version: u64,
+    ) -> anyhow::Result<()>;
+    /// Gets a batch of consecutive encoded transactions, starting from `start_version`.
+    async fn batch_get_encoded_proto_data(
+        &mut self,
+        start_version: u64,
+    ) -> anyhow::Result<CacheBatchGetStatus>;
+}
+
+/// Creates the cache operator for the configured cache.
+pub async fn create_cache_operator(
+    cache_config: &IndexerGrpcCacheConfig,
+) -> anyhow::Result<Box<dyn CacheOperator>> {
+    match cache_config {
+        IndexerGrpcCacheConfig::RedisCache(redis_cache) => {
+            let conn = redis::Client::open(format!("redis://{}", redis_cache.redis_address))?
+                .get_async_connection()
+                .await?;
+            Ok(Box::new(RedisCacheOperator::new(conn)))
+        },
+        IndexerGrpcCacheConfig::InMemoryCache(in_memory_cache) => {
+            Ok(Box::new(InMemoryCacheOperator::new(
+                &in_memory_cache.in_memory_cache_name,
+                in_memory_cache.in_memory_cache_capacity,
+            )))
+        },
+    }
+}
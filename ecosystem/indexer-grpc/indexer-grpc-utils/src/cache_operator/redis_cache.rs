@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::constants::BLOB_STORAGE_SIZE;
+use crate::{cache_operator::*, constants::BLOB_STORAGE_SIZE};
 use anyhow::Context;
 use redis::{AsyncCommands, RedisError, RedisResult};
 
@@ -67,37 +67,6 @@ const CACHE_SCRIPT_UPDATE_LATEST_VERSION: &str = r#"
     end
 "#;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum CacheBatchGetStatus {
-    /// OK with batch of encoded transactions.
-    Ok(Vec<String>),
-    /// Requested version is already evicted from cache. Visit file store instead.
-    EvictedFromCache,
-    /// Not ready yet. Wait and retry.
-    NotReady,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum CacheUpdateStatus {
-    /// 0 - Cache is updated from version x to x + 1. New key `x+1` with corresponding encoded data is added.
-    Ok,
-    /// 1 - Cache is not updated because current version is ahead of the latest version.
-    AheadOfLatestVersion,
-    /// 2 - Cache is not updated but verified. This is the case when the cache is updated by other workers from an old version.
-    VerifiedWithoutUpdate,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum CacheCoverageStatus {
-    /// Requested version is not processed by cache worker yet.
-    DataNotReady,
-    /// Requested version is cached.
-    /// Transactions are available in cache: [requested_version, requested_version + value).
-    CacheHit(u64),
-    /// Requested version is evicted from cache.
-    CacheEvicted,
-}
-
 /// Get the TTL in seconds for a given timestamp.
 pub fn get_ttl_in_seconds(timestamp_in_seconds: u64) -> u64 {
     let current_time = std::time::SystemTime::now()
@@ -108,17 +77,48 @@ pub fn get_ttl_in_seconds(timestamp_in_seconds: u64) -> u64 {
 }
 
 // Cache operator directly interacts with redis conn.
-pub struct CacheOperator<T: redis::aio::ConnectionLike + Send> {
+pub struct RedisCacheOperator<T: redis::aio::ConnectionLike + Send> {
     conn: T,
 }
 
-impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
+impl<T: redis::aio::ConnectionLike + Send> RedisCacheOperator<T> {
     pub fn new(conn: T) -> Self {
         Self { conn }
     }
 
+    // Internal function to get the latest version from cache.
+    pub(crate) async fn check_cache_coverage_status(
+        &mut self,
+        requested_version: u64,
+    ) -> anyhow::Result<CacheCoverageStatus> {
+        let latest_version: u64 = match self
+            .conn
+            .get::<&str, String>(CACHE_KEY_LATEST_VERSION)
+            .await
+        {
+            Ok(v) => v
+                .parse::<u64>()
+                .expect("Redis latest_version is not a number."),
+            Err(err) => return Err(err.into()),
+        };
+
+        if requested_version >= latest_version {
+            Ok(CacheCoverageStatus::DataNotReady)
+        } else if requested_version + CACHE_SIZE_ESTIMATION < latest_version {
+            Ok(CacheCoverageStatus::CacheEvicted)
+        } else {
+            Ok(CacheCoverageStatus::CacheHit(std::cmp::min(
+                latest_version - requested_version,
+                BLOB_STORAGE_SIZE as u64,
+            )))
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T: redis::aio::ConnectionLike + Send> CacheOperator for RedisCacheOperator<T> {
     // Set up the cache if needed.
-    pub async fn cache_setup_if_needed(&mut self) -> bool {
+    async fn cache_setup_if_needed(&mut self) -> bool {
         let version_inserted: bool = redis::cmd("SET")
             .arg(CACHE_KEY_LATEST_VERSION)
             .arg(CACHE_DEFAULT_LATEST_VERSION_NUMBER)
@@ -137,7 +137,7 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
 
     // Update the chain id in cache if missing; otherwise, verify the chain id.
     // It's a fatal error if the chain id is not correct.
-    pub async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<()> {
+    async fn update_or_verify_chain_id(&mut self, chain_id: u64) -> anyhow::Result<()> {
         let script = redis::Script::new(CACHE_SCRIPT_UPDATE_OR_VERIFY_CHAIN_ID);
         let result: u8 = script
             .key(CACHE_KEY_CHAIN_ID)
@@ -152,7 +152,7 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
     }
 
     // Downstream system can infer the chain id from cache.
-    pub async fn get_chain_id(&mut self) -> anyhow::Result<u64> {
+    async fn get_chain_id(&mut self) -> anyhow::Result<u64> {
         let chain_id: u64 = match self.conn.get::<&str, String>(CACHE_KEY_CHAIN_ID).await {
             Ok(v) => v
                 .parse::<u64>()
@@ -162,7 +162,7 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
         Ok(chain_id)
     }
 
-    pub async fn get_latest_version(&mut self) -> anyhow::Result<u64> {
+    async fn get_latest_version(&mut self) -> anyhow::Result<u64> {
         let chain_id: u64 = match self
             .conn
             .get::<&str, String>(CACHE_KEY_LATEST_VERSION)
@@ -176,35 +176,7 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
         Ok(chain_id)
     }
 
-    // Internal function to get the latest version from cache.
-    pub(crate) async fn check_cache_coverage_status(
-        &mut self,
-        requested_version: u64,
-    ) -> anyhow::Result<CacheCoverageStatus> {
-        let latest_version: u64 = match self
-            .conn
-            .get::<&str, String>(CACHE_KEY_LATEST_VERSION)
-            .await
-        {
-            Ok(v) => v
-                .parse::<u64>()
-                .expect("Redis latest_version is not a number."),
-            Err(err) => return Err(err.into()),
-        };
-
-        if requested_version >= latest_version {
-            Ok(CacheCoverageStatus::DataNotReady)
-        } else if requested_version + CACHE_SIZE_ESTIMATION < latest_version {
-            Ok(CacheCoverageStatus::CacheEvicted)
-        } else {
-            Ok(CacheCoverageStatus::CacheHit(std::cmp::min(
-                latest_version - requested_version,
-                BLOB_STORAGE_SIZE as u64,
-            )))
-        }
-    }
-
-    pub async fn update_cache_transactions(
+    async fn update_cache_transactions(
         &mut self,
         transactions: Vec<(u64, String, u64)>,
     ) -> anyhow::Result<()> {
@@ -238,7 +210,7 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
     }
 
     // Update the latest version in cache.
-    pub async fn update_cache_latest_version(
+    async fn update_cache_latest_version(
         &mut self,
         num_of_versions: u64,
         version: u64,
@@ -265,7 +237,7 @@ impl<T: redis::aio::ConnectionLike + Send> CacheOperator<T> {
         }
     }
 
-    pub async fn batch_get_encoded_proto_data(
+    async fn batch_get_encoded_proto_data(
         &mut self,
         start_version: u64,
     ) -> anyhow::Result<CacheBatchGetStatus> {
@@ -305,8 +277,8 @@ mod tests {
             Ok("1"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert!(cache_operator.cache_setup_if_needed().await);
     }
@@ -321,8 +293,8 @@ mod tests {
             Ok("0"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert!(!cache_operator.cache_setup_if_needed().await);
     }
@@ -334,8 +306,8 @@ mod tests {
             Ok("12"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(
             cache_operator
@@ -353,8 +325,8 @@ mod tests {
             Ok("120000000"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(
             cache_operator.check_cache_coverage_status(1).await.unwrap(),
@@ -369,8 +341,8 @@ mod tests {
             Ok("123"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         // Transactions are 100..123, thus 23 transactions are cached.
         assert_eq!(
@@ -389,8 +361,8 @@ mod tests {
             Ok("12300"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(
             cache_operator
@@ -417,8 +389,8 @@ mod tests {
             ),
         ];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(
             cache_operator
@@ -442,8 +414,8 @@ mod tests {
             MockCmd::new(redis::cmd("MGET").arg(keys), Ok(bulck_value)),
         ];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(
             cache_operator
@@ -461,8 +433,8 @@ mod tests {
             Ok("100000000"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(
             cache_operator
@@ -480,8 +452,8 @@ mod tests {
             Ok("1"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(
             cache_operator
@@ -502,8 +474,8 @@ mod tests {
             Ok("123"),
         )];
         let mock_connection = MockRedisConnection::new(cmds);
-        let mut cache_operator: CacheOperator<MockRedisConnection> =
-            CacheOperator::new(mock_connection);
+        let mut cache_operator: RedisCacheOperator<MockRedisConnection> =
+            RedisCacheOperator::new(mock_connection);
 
         assert_eq!(cache_operator.get_chain_id().await.unwrap(), 123);
     }
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 /// Common configuration for Indexer GRPC Store.
 use std::path::PathBuf;
 
@@ -30,3 +30,112 @@ impl Default for IndexerGrpcFileStoreConfig {
         })
     }
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RedisCache {
+    pub redis_address: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InMemoryCache {
+    // Services in the same process configured with the same name share the cache.
+    #[serde(default = "InMemoryCache::default_name")]
+    pub in_memory_cache_name: String,
+    // Maximum number of transactions kept in the cache; older ones are evicted.
+    #[serde(default = "InMemoryCache::default_capacity")]
+    pub in_memory_cache_capacity: u64,
+}
+
+impl InMemoryCache {
+    fn default_name() -> String {
+        "default".to_string()
+    }
+
+    fn default_capacity() -> u64 {
+        100_000
+    }
+}
+
+impl Default for InMemoryCache {
+    fn default() -> Self {
+        Self {
+            in_memory_cache_name: Self::default_name(),
+            in_memory_cache_capacity: Self::default_capacity(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "cache_type")]
+pub enum IndexerGrpcCacheConfig {
+    RedisCache(RedisCache),
+    InMemoryCache(InMemoryCache),
+}
+
+impl Default for IndexerGrpcCacheConfig {
+    fn default() -> Self {
+        IndexerGrpcCacheConfig::InMemoryCache(InMemoryCache::default())
+    }
+}
+
+/// Deserializes an `IndexerGrpcCacheConfig`, also accepting a bare redis address so that
+/// configs written for the old `redis_main_instance_address` field keep working.
+pub fn deserialize_cache_config<'de, D>(deserializer: D) -> Result<IndexerGrpcCacheConfig, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum CacheConfigOrAddress {
+        Config(IndexerGrpcCacheConfig),
+        RedisAddress(String),
+    }
+
+    Ok(match CacheConfigOrAddress::deserialize(deserializer)? {
+        CacheConfigOrAddress::Config(config) => config,
+        CacheConfigOrAddress::RedisAddress(redis_address) => {
+            IndexerGrpcCacheConfig::RedisCache(RedisCache { redis_address })
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct TestConfig {
+        #[serde(
+            alias = "redis_main_instance_address",
+            deserialize_with = "deserialize_cache_config"
+        )]
+        cache_config: IndexerGrpcCacheConfig,
+    }
+
+    #[test]
+    fn test_deserialize_cache_config() {
+        let config: TestConfig = serde_yaml::from_str(
+            "cache_config:\n  cache_type: InMemoryCache\n  in_memory_cache_capacity: 10\n",
+        )
+        .unwrap();
+        match config.cache_config {
+            IndexerGrpcCacheConfig::InMemoryCache(cache) => {
+                assert_eq!(cache.in_memory_cache_name, "default");
+                assert_eq!(cache.in_memory_cache_capacity, 10);
+            },
+            config => panic!("unexpected cache config: {:?}", config),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_legacy_redis_address() {
+        let config: TestConfig =
+            serde_yaml::from_str("redis_main_instance_address: 127.0.0.1:6379\n").unwrap();
+        match config.cache_config {
+            IndexerGrpcCacheConfig::RedisCache(cache) => {
+                assert_eq!(cache.redis_address, "127.0.0.1:6379");
+            },
+            config => panic!("unexpected cache config: {:?}", config),
+        }
+    }
+}
@@ -9,6 +9,7 @@ mod db_reliable_submitter;
 mod metrics;
 pub mod native_executor;
 pub mod pipeline;
+pub mod sharded_executor;
 pub mod transaction_committer;
 pub mod transaction_executor;
 pub mod transaction_generator;
@@ -34,7 +35,13 @@ use aptos_storage_interface::DbReaderWriter;
 use aptos_transaction_generator_lib::{
     create_txn_generator_creator, TransactionGeneratorCreator, TransactionType,
 };
-use aptos_vm::counters::TXN_GAS_USAGE;
+use aptos_vm::{
+    block_executor::conflict_analytics::{describe_state_key, hot_keys, reset_hot_keys},
+    counters::{
+        SHARDED_EXECUTION_CROSS_SHARD_CONFLICTS, SHARDED_EXECUTION_TRANSACTIONS, TXN_GAS_USAGE,
+    },
+    AptosVM,
+};
 use db_reliable_submitter::DbReliableTransactionSubmitter;
 use pipeline::PipelineConfig;
 use std::{
@@ -46,6 +53,8 @@ use std::{
 };
 use tokio::runtime::Runtime;
 
+const NUM_REPORTED_HOT_KEYS: usize = 20;
+
 pub fn init_db_and_executor<V>(config: &NodeConfig) -> (DbReaderWriter, BlockExecutor<V>)
 where
     V: TransactionBlockExecutor,
@@ -149,6 +158,9 @@ pub fn run_benchmark<V>(
 
     let mut start_time = Instant::now();
     let start_gas = TXN_GAS_USAGE.get_sample_sum();
+    let start_sharded_txns = SHARDED_EXECUTION_TRANSACTIONS.get();
+    let start_cross_shard_conflicts = SHARDED_EXECUTION_CROSS_SHARD_CONFLICTS.get();
+    reset_hot_keys();
 
     let start_execution_total = APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum();
     let start_vm_only = APTOS_EXECUTOR_VM_EXECUTE_BLOCK_SECONDS.get_sample_sum();
@@ -211,6 +223,29 @@ pub fn run_benchmark<V>(
     info!("Overall TPS: {} txn/s", delta_v / elapsed);
     info!("Overall GPS: {} gas/s", delta_gas / elapsed);
 
+    let delta_sharded_txns = SHARDED_EXECUTION_TRANSACTIONS.get() - start_sharded_txns;
+    if delta_sharded_txns > 0 {
+        let delta_conflicts =
+            SHARDED_EXECUTION_CROSS_SHARD_CONFLICTS.get() - start_cross_shard_conflicts;
+        info!(
+            "Overall cross-shard conflict rate: {:.3} ({} of {} txns)",
+            delta_conflicts as f64 / delta_sharded_txns as f64,
+            delta_conflicts,
+            delta_sharded_txns
+        );
+    }
+
+    if AptosVM::get_conflict_analytics() {
+        for (key, conflicts) in hot_keys(NUM_REPORTED_HOT_KEYS) {
+            info!(
+                "Overall hot key: {} ({} validation failures, {} dependency waits)",
+                describe_state_key(&key),
+                conflicts.validation_failures,
+                conflicts.dependency_waits
+            );
+        }
+    }
+
     let time_in_execution =
         APTOS_EXECUTOR_EXECUTE_BLOCK_SECONDS.get_sample_sum() - start_execution_total;
     info!(
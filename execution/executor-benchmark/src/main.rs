@@ -6,7 +6,11 @@ use aptos_config::config::{
     EpochSnapshotPrunerConfig, LedgerPrunerConfig, PrunerConfig, StateMerklePrunerConfig,
 };
 use aptos_executor::block_executor::TransactionBlockExecutor;
-use aptos_executor_benchmark::{native_executor::NativeExecutor, pipeline::PipelineConfig};
+use aptos_executor_benchmark::{
+    native_executor::NativeExecutor,
+    pipeline::PipelineConfig,
+    sharded_executor::{PartitionerType, ShardedExecutor},
+};
 use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_push_metrics::MetricsPusher;
 use aptos_transaction_generator_lib::args::TransactionTypeArg;
@@ -138,6 +142,19 @@ struct Opt {
 
     #[clap(long)]
     use_native_executor: bool,
+
+    /// Execute blocks with the sharded block executor, using this many shards. Shards don't see
+    /// each other's writes yet, so conflicting transactions may need `--allow-discards`.
+    #[clap(long, conflicts_with = "use-native-executor")]
+    num_executor_shards: Option<usize>,
+
+    /// How blocks are partitioned between shards, when `--num-executor-shards` is set.
+    #[clap(long, arg_enum, ignore_case = true, default_value = "uniform")]
+    partitioner: PartitionerType,
+
+    /// Record which state keys cause Block-STM re-executions, and report the hottest ones.
+    #[clap(long)]
+    conflict_analytics: bool,
 }
 
 impl Opt {
@@ -295,8 +312,18 @@ fn main() {
     AptosVM::set_concurrency_level_once(opt.concurrency_level());
     NativeExecutor::set_concurrency_level_once(opt.concurrency_level());
 
+    if let Some(num_executor_shards) = opt.num_executor_shards {
+        AptosVM::set_num_shards_once(num_executor_shards);
+        ShardedExecutor::set_partitioner_once(opt.partitioner);
+    }
+    if opt.conflict_analytics {
+        AptosVM::set_conflict_analytics();
+    }
+
     if opt.use_native_executor {
         run::<NativeExecutor>(opt);
+    } else if opt.num_executor_shards.is_some() {
+        run::<ShardedExecutor>(opt);
     } else {
         run::<AptosVM>(opt);
     }
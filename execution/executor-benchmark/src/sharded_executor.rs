@@ -0,0 +1,87 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_executor::{
+    block_executor::TransactionBlockExecutor, components::chunk_output::ChunkOutput,
+};
+use aptos_infallible::Mutex;
+use aptos_storage_interface::cached_state_view::CachedStateView;
+use aptos_types::transaction::Transaction;
+use aptos_vm::{
+    sharded_block_executor::{
+        block_partitioner::{BlockPartitioner, DependencyAwarePartitioner, UniformPartitioner},
+        ShardedBlockExecutor,
+    },
+    AptosVM,
+};
+use clap::ArgEnum;
+use once_cell::sync::{Lazy, OnceCell};
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, ArgEnum)]
+pub enum PartitionerType {
+    Uniform,
+    DependencyAware,
+}
+
+/// Executes blocks with the sharded block executor, using `AptosVM::get_num_shards()` shards that
+/// split the VM concurrency level between them.
+pub struct ShardedExecutor {}
+
+static SHARDED_EXECUTOR_PARTITIONER: OnceCell<PartitionerType> = OnceCell::new();
+static SHARDED_BLOCK_EXECUTOR: Lazy<Mutex<ShardedBlockExecutor<CachedStateView>>> =
+    Lazy::new(|| {
+        let partitioner: Arc<dyn BlockPartitioner> = match ShardedExecutor::get_partitioner() {
+            PartitionerType::Uniform => Arc::new(UniformPartitioner {}),
+            PartitionerType::DependencyAware => Arc::new(DependencyAwarePartitioner::new()),
+        };
+        Mutex::new(ShardedBlockExecutor::new_with_partitioner(
+            AptosVM::get_num_shards(),
+            Some(ShardedExecutor::concurrency_level_per_shard()),
+            partitioner,
+        ))
+    });
+
+impl ShardedExecutor {
+    pub fn set_partitioner_once(partitioner: PartitionerType) {
+        SHARDED_EXECUTOR_PARTITIONER.set(partitioner).ok();
+    }
+
+    pub fn get_partitioner() -> PartitionerType {
+        match SHARDED_EXECUTOR_PARTITIONER.get() {
+            Some(partitioner) => *partitioner,
+            None => PartitionerType::Uniform,
+        }
+    }
+
+    fn concurrency_level_per_shard() -> usize {
+        (AptosVM::get_concurrency_level() / AptosVM::get_num_shards()).max(1)
+    }
+}
+
+impl TransactionBlockExecutor for ShardedExecutor {
+    fn execute_transaction_block(
+        transactions: Vec<Transaction>,
+        state_view: CachedStateView,
+        maybe_block_gas_limit: Option<u64>,
+    ) -> Result<ChunkOutput> {
+        let state_view = Arc::new(state_view);
+        let sharded_block_executor = SHARDED_BLOCK_EXECUTOR.lock();
+        let transaction_outputs = sharded_block_executor.execute_block(
+            state_view.clone(),
+            transactions.clone(),
+            Self::concurrency_level_per_shard(),
+            maybe_block_gas_limit,
+        )?;
+        sharded_block_executor.record_execution(&transactions, &transaction_outputs);
+        drop(sharded_block_executor);
+
+        Ok(ChunkOutput {
+            transactions,
+            transaction_outputs,
+            // Unwrapping here is safe because the shards drop the state view once they are done.
+            state_cache: Arc::try_unwrap(state_view).unwrap().into_state_cache(),
+        })
+    }
+}
@@ -15,15 +15,29 @@
 //!
 //! Internally both the client and server leverage a NetworkStream that communications in blocks
 //! where a block is a length prefixed array of bytes.
-
+//!
+//! Optionally, the client and server can authenticate each other and encrypt their messages by
+//! running a Noise IK handshake when a connection is established. Each end is then identified by
+//! an x25519 key, and only accepts connections with the key it has been configured to expect.
+//! The initiator sends the current time as the handshake payload, and the responder rejects any
+//! handshake whose timestamp isn't later than the last one it accepted, so a recorded handshake
+//! can't be replayed.
+
+use aptos_crypto::{
+    noise::{self, NoiseConfig, NoiseError, NoiseSession},
+    x25519,
+};
 use aptos_logger::{info, trace, warn, Schema};
 use aptos_metrics_core::{register_int_counter_vec, IntCounterVec};
 use once_cell::sync::Lazy;
+use rand::rngs::OsRng;
 use serde::Serialize;
 use std::{
     io::{Read, Write},
     net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::atomic::{AtomicU64, Ordering},
     thread, time,
+    time::{SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
 
@@ -56,6 +70,7 @@ enum LogEvent {
     ConnectionAttempt,
     ConnectionSuccessful,
     ConnectionFailed,
+    HandshakeFailed,
     DisconnectedPeerOnRead,
     DisconnectedPeerOnWrite,
     Shutdown,
@@ -139,14 +154,123 @@ pub enum Error {
     AlreadyShutdown,
     #[error("Found data that is too large to decode: {0}")]
     DataTooLarge(usize),
+    #[error("Handshake initialization message is missing a timestamp")]
+    MissingHandshakeTimestamp,
     #[error("Internal network error:")]
     NetworkError(#[from] std::io::Error),
+    #[error("Noise error: {0}")]
+    NoiseError(#[from] NoiseError),
     #[error("No active stream")]
     NoActiveStream,
     #[error("Overflow error: {0}")]
     OverflowError(String),
     #[error("Remote stream cleanly closed")]
     RemoteStreamClosed,
+    #[error("Handshake timestamp {0} is not later than the last accepted one")]
+    ReplayedHandshake(u64),
+    #[error("Peer authenticated with an unexpected public key: {0}")]
+    UnexpectedPeer(x25519::PublicKey),
+}
+
+/// The keys used to authenticate and encrypt a connection with a Noise IK handshake.
+pub struct NoiseKeys {
+    /// The identity of this end of the connection.
+    pub private_key: x25519::PrivateKey,
+    /// The public key the other end of the connection must authenticate with.
+    pub remote_public_key: x25519::PublicKey,
+}
+
+/// The size of the timestamp sent as the payload of the handshake initialization message.
+const TIMESTAMP_SIZE: usize = 8;
+
+/// Milliseconds since the unix epoch. Strictly increasing within the process, so that clients
+/// reconnecting within the same millisecond aren't mistaken for replays.
+fn now() -> u64 {
+    static LAST_TIMESTAMP: AtomicU64 = AtomicU64::new(0);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before the unix epoch")
+        .as_millis() as u64;
+    let last_timestamp = LAST_TIMESTAMP
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            Some(now.max(last + 1))
+        })
+        .expect("The update always succeeds");
+    now.max(last_timestamp + 1)
+}
+
+struct NoiseHandshake {
+    config: NoiseConfig,
+    remote_public_key: x25519::PublicKey,
+    /// The source of the timestamps sent by the initiator.
+    now: fn() -> u64,
+    /// The timestamp of the last handshake the responder accepted. Timestamps must be strictly
+    /// increasing, mirroring the anti-replay protection of the validator network.
+    last_timestamp: Option<u64>,
+}
+
+impl NoiseHandshake {
+    fn new(keys: NoiseKeys) -> Self {
+        Self {
+            config: NoiseConfig::new(keys.private_key),
+            remote_public_key: keys.remote_public_key,
+            now,
+            last_timestamp: None,
+        }
+    }
+
+    /// Runs the initiator side of the handshake on a new stream. Binding the service name in the
+    /// prologue prevents a handshake meant for one service from being used with another.
+    fn initiate(&self, service: &'static str, stream: &mut NetworkStream) -> Result<(), Error> {
+        let timestamp = (self.now)().to_le_bytes();
+        let mut init_message = vec![0; noise::handshake_init_msg_len(TIMESTAMP_SIZE)];
+        let handshake_state = self.config.initiate_connection(
+            &mut OsRng,
+            service.as_bytes(),
+            self.remote_public_key,
+            Some(&timestamp),
+            &mut init_message,
+        )?;
+        stream.write(&init_message)?;
+
+        let response = stream.read()?;
+        let (_, session) = self
+            .config
+            .finalize_connection(handshake_state, &response)?;
+        stream.session = Some(session);
+        Ok(())
+    }
+
+    /// Runs the responder side of the handshake on a new stream, rejecting any initiator that
+    /// does not authenticate with the expected public key or replays an earlier handshake.
+    fn respond(&mut self, service: &'static str, stream: &mut NetworkStream) -> Result<(), Error> {
+        let init_message = stream.read()?;
+        let (remote_public_key, handshake_state, payload) = self
+            .config
+            .parse_client_init_message(service.as_bytes(), &init_message)?;
+        if remote_public_key != self.remote_public_key {
+            return Err(Error::UnexpectedPeer(remote_public_key));
+        }
+
+        let timestamp: [u8; TIMESTAMP_SIZE] = payload
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::MissingHandshakeTimestamp)?;
+        let timestamp = u64::from_le_bytes(timestamp);
+        if matches!(self.last_timestamp, Some(last_timestamp) if timestamp <= last_timestamp) {
+            return Err(Error::ReplayedHandshake(timestamp));
+        }
+        self.last_timestamp = Some(timestamp);
+
+        let mut response = vec![0; noise::handshake_resp_msg_len(0)];
+        let session =
+            self.config
+                .respond_to_client(&mut OsRng, handshake_state, None, &mut response)?;
+        stream.write(&response)?;
+        stream.session = Some(session);
+        Ok(())
+    }
 }
 
 pub struct NetworkClient {
@@ -155,6 +279,7 @@ pub struct NetworkClient {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseHandshake>,
 }
 
 impl NetworkClient {
@@ -164,6 +289,20 @@ impl NetworkClient {
             server,
             stream: None,
             timeout_ms,
+            noise: None,
+        }
+    }
+
+    /// Creates a client that authenticates the server and encrypts all messages with Noise.
+    pub fn new_with_noise(
+        service: &'static str,
+        server: SocketAddr,
+        timeout_ms: u64,
+        noise_keys: NoiseKeys,
+    ) -> Self {
+        Self {
+            noise: Some(NoiseHandshake::new(noise_keys)),
+            ..Self::new(service, server, timeout_ms)
         }
     }
 
@@ -258,7 +397,21 @@ impl NetworkClient {
 
             let stream = stream?;
             stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, self.server, self.timeout_ms));
+            let mut stream = NetworkStream::new(stream, self.server, self.timeout_ms);
+            if let Some(noise) = &self.noise {
+                if let Err(err) = noise.initiate(self.service, &mut stream) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Client,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&self.server));
+                    return Err(err);
+                }
+            }
+            self.stream = Some(stream);
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -278,6 +431,7 @@ pub struct NetworkServer {
     stream: Option<NetworkStream>,
     /// Read, Write, Connect timeout in milliseconds.
     timeout_ms: u64,
+    noise: Option<NoiseHandshake>,
 }
 
 impl NetworkServer {
@@ -288,6 +442,21 @@ impl NetworkServer {
             listener: Some(listener.unwrap()),
             stream: None,
             timeout_ms,
+            noise: None,
+        }
+    }
+
+    /// Creates a server that only accepts clients authenticating with
+    /// `noise_keys.remote_public_key`, and encrypts all messages with Noise.
+    pub fn new_with_noise(
+        service: &'static str,
+        listen: SocketAddr,
+        timeout_ms: u64,
+        noise_keys: NoiseKeys,
+    ) -> Self {
+        Self {
+            noise: Some(NoiseHandshake::new(noise_keys)),
+            ..Self::new(service, listen, timeout_ms)
         }
     }
 
@@ -391,6 +560,22 @@ impl NetworkServer {
                 },
             };
 
+            stream.set_nodelay(true)?;
+            let mut stream = NetworkStream::new(stream, stream_addr, self.timeout_ms);
+            if let Some(noise) = &mut self.noise {
+                if let Err(err) = noise.respond(self.service, &mut stream) {
+                    self.increment_counter(Method::Connect, MethodResult::Failure);
+                    warn!(SecureNetLogSchema::new(
+                        self.service,
+                        NetworkMode::Server,
+                        LogEvent::HandshakeFailed,
+                    )
+                    .error(&err)
+                    .remote_peer(&stream_addr));
+                    return Err(err);
+                }
+            }
+
             self.increment_counter(Method::Connect, MethodResult::Success);
             info!(SecureNetLogSchema::new(
                 self.service,
@@ -398,9 +583,7 @@ impl NetworkServer {
                 LogEvent::ConnectionSuccessful,
             )
             .remote_peer(&stream_addr));
-
-            stream.set_nodelay(true)?;
-            self.stream = Some(NetworkStream::new(stream, stream_addr, self.timeout_ms));
+            self.stream = Some(stream);
         }
 
         self.stream.as_mut().ok_or(Error::NoActiveStream)
@@ -412,6 +595,8 @@ struct NetworkStream {
     remote: SocketAddr,
     buffer: Vec<u8>,
     temp_buffer: [u8; 1024],
+    /// Set once a Noise handshake completed, after which all messages are encrypted.
+    session: Option<NoiseSession>,
 }
 
 impl NetworkStream {
@@ -426,11 +611,20 @@ impl NetworkStream {
             remote,
             buffer: Vec::new(),
             temp_buffer: [0; 1024],
+            session: None,
         }
     }
 
     /// Blocking read until able to successfully read an entire message
     pub fn read(&mut self) -> Result<Vec<u8>, Error> {
+        let block = self.read_block()?;
+        match self.session.as_mut() {
+            Some(session) => decrypt(session, block),
+            None => Ok(block),
+        }
+    }
+
+    fn read_block(&mut self) -> Result<Vec<u8>, Error> {
         let result = self.read_buffer();
         if !result.is_empty() {
             return Ok(result);
@@ -460,6 +654,18 @@ impl NetworkStream {
 
     /// Blocking write until able to successfully send an entire message
     pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        let encrypted;
+        let block = match self.session.as_mut() {
+            Some(session) => {
+                encrypted = encrypt(session, data)?;
+                &encrypted
+            },
+            None => data,
+        };
+        self.write_block(block)
+    }
+
+    fn write_block(&mut self, data: &[u8]) -> Result<(), Error> {
         let u32_max = u32::max_value() as usize;
         if u32_max <= data.len() {
             return Err(Error::DataTooLarge(data.len()));
@@ -515,15 +721,61 @@ impl NetworkStream {
     }
 }
 
+/// Noise limits the size of a single encrypted message, so a message is split into frames that
+/// are encrypted separately. The plaintext is prefixed with its length, so that dropping trailing
+/// frames from a block is detected.
+const MAX_FRAME_PLAINTEXT_LEN: usize = noise::MAX_SIZE_NOISE_MSG - noise::AES_GCM_TAGLEN;
+
+fn encrypt(session: &mut NoiseSession, data: &[u8]) -> Result<Vec<u8>, Error> {
+    let data_len = u32::try_from(data.len()).map_err(|_| Error::DataTooLarge(data.len()))?;
+    let mut plaintext = Vec::with_capacity(4 + data.len());
+    plaintext.extend_from_slice(&data_len.to_le_bytes());
+    plaintext.extend_from_slice(data);
+
+    let mut ciphertext = Vec::with_capacity(noise::encrypted_len(plaintext.len()));
+    for frame in plaintext.chunks_mut(MAX_FRAME_PLAINTEXT_LEN) {
+        let tag = session.write_message_in_place(frame)?;
+        ciphertext.extend_from_slice(frame);
+        ciphertext.extend_from_slice(&tag);
+    }
+    Ok(ciphertext)
+}
+
+fn decrypt(session: &mut NoiseSession, mut block: Vec<u8>) -> Result<Vec<u8>, Error> {
+    let mut plaintext = Vec::with_capacity(block.len());
+    for frame in block.chunks_mut(noise::MAX_SIZE_NOISE_MSG) {
+        plaintext.extend_from_slice(session.read_message_in_place(frame)?);
+    }
+
+    if plaintext.len() < 4 {
+        return Err(NoiseError::MsgTooShort.into());
+    }
+    let mut u32_bytes = [0; 4];
+    u32_bytes.copy_from_slice(&plaintext[..4]);
+    if plaintext.len() - 4 != u32::from_le_bytes(u32_bytes) as usize {
+        return Err(NoiseError::MsgTooShort.into());
+    }
+    Ok(plaintext.split_off(4))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use aptos_config::utils;
+    use aptos_crypto::{Uniform, ValidCryptoMaterial};
+    use rand::{rngs::StdRng, SeedableRng};
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     /// Read, Write, Connect timeout in milliseconds.
     const TIMEOUT: u64 = 5_000;
 
+    fn noise_keys(private_key: &x25519::PrivateKey, remote: &x25519::PrivateKey) -> NoiseKeys {
+        NoiseKeys {
+            private_key: x25519::PrivateKey::try_from(private_key.to_bytes().as_slice()).unwrap(),
+            remote_public_key: remote.public_key(),
+        }
+    }
+
     #[test]
     fn test_ping() {
         let server_port = utils::get_available_port();
@@ -657,4 +909,117 @@ mod test {
         let result2 = server2.read().unwrap();
         assert_eq!(data2, result2);
     }
+
+    #[test]
+    fn test_noise_ping() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let server_key = x25519::PrivateKey::generate(&mut rng);
+        let client_key = x25519::PrivateKey::generate(&mut rng);
+
+        let mut server = NetworkServer::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            noise_keys(&server_key, &client_key),
+        );
+        // The handshake needs both ends to make progress, so the server echoes from a thread.
+        let server_thread = thread::spawn(move || {
+            for _ in 0..2 {
+                let data = server.read().unwrap();
+                server.write(&data).unwrap();
+            }
+        });
+        let mut client = NetworkClient::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            noise_keys(&client_key, &server_key),
+        );
+
+        let data = vec![0, 1, 2, 3];
+        client.write(&data).unwrap();
+        assert_eq!(data, client.read().unwrap());
+
+        // Larger than a single Noise message
+        let data: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+        client.write(&data).unwrap();
+        assert_eq!(data, client.read().unwrap());
+
+        server_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_noise_unexpected_client() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let server_key = x25519::PrivateKey::generate(&mut rng);
+        let client_key = x25519::PrivateKey::generate(&mut rng);
+        let other_key = x25519::PrivateKey::generate(&mut rng);
+
+        let mut server = NetworkServer::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            noise_keys(&server_key, &client_key),
+        );
+        let server_thread = thread::spawn(move || server.read());
+        let mut client = NetworkClient::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            noise_keys(&other_key, &server_key),
+        );
+
+        client.write(&[0, 1, 2, 3]).unwrap_err();
+        let result = server_thread.join().unwrap();
+        assert!(matches!(result, Err(Error::UnexpectedPeer(key)) if key == other_key.public_key()));
+    }
+
+    #[test]
+    fn test_noise_replayed_handshake() {
+        let server_port = utils::get_available_port();
+        let server_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), server_port);
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let server_key = x25519::PrivateKey::generate(&mut rng);
+        let client_key = x25519::PrivateKey::generate(&mut rng);
+        let new_client = |now: fn() -> u64| {
+            let mut client = NetworkClient::new_with_noise(
+                "test",
+                server_addr,
+                TIMEOUT,
+                noise_keys(&client_key, &server_key),
+            );
+            client.noise.as_mut().unwrap().now = now;
+            client
+        };
+
+        let mut server = NetworkServer::new_with_noise(
+            "test",
+            server_addr,
+            TIMEOUT,
+            noise_keys(&server_key, &client_key),
+        );
+        let server_thread =
+            thread::spawn(move || (0..4).map(|_| server.read()).collect::<Vec<_>>());
+
+        let data = vec![0, 1, 2, 3];
+        let mut client = new_client(|| 1);
+        client.write(&data).unwrap();
+        client.shutdown().unwrap();
+
+        // Same timestamp as the previous handshake
+        new_client(|| 1).write(&data).unwrap_err();
+
+        let mut client = new_client(|| 2);
+        client.write(&data).unwrap();
+
+        let results = server_thread.join().unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), &data);
+        assert!(results[1].is_err());
+        assert!(matches!(results[2], Err(Error::ReplayedHandshake(1))));
+        assert_eq!(results[3].as_ref().unwrap(), &data);
+    }
 }
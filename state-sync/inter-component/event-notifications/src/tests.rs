@@ -5,8 +5,8 @@
 #![forbid(unsafe_code)]
 
 use crate::{
-    Error, EventNotificationListener, EventNotificationSender, EventSubscriptionService,
-    ReconfigNotificationListener,
+    CommitNotificationListener, Error, EventNotificationListener, EventNotificationSender,
+    EventSubscriptionService, ReconfigNotificationListener,
 };
 use aptos_db::AptosDB;
 use aptos_executor_test_helpers::bootstrap_genesis;
@@ -99,6 +99,34 @@ fn test_reconfig_notification_no_queuing() {
     assert_eq!(notification_count, 1);
 }
 
+#[test]
+fn test_commit_notification_no_queuing() {
+    // Create subscription service and mock database
+    let mut event_service = create_event_subscription_service();
+
+    // Create commit subscribers
+    let mut listener_1 = event_service.subscribe_to_commits().unwrap();
+    let mut listener_2 = event_service.subscribe_to_commits().unwrap();
+
+    // Verify that no notifications have been received yet
+    verify_no_commit_notifications(vec![&mut listener_1, &mut listener_2]);
+
+    // Notify the subscription service of several commits (with and without events)
+    let event = create_test_event(create_random_event_key());
+    for version in 0..10 {
+        notify_events(&mut event_service, version, vec![event.clone()]);
+    }
+    notify_events(&mut event_service, 10, vec![]);
+
+    // Verify that only the latest commit was received by both listeners
+    verify_commit_notification_received(vec![&mut listener_1, &mut listener_2], 10);
+    verify_no_commit_notifications(vec![&mut listener_1, &mut listener_2]);
+
+    // Verify that forcing the initial configs doesn't notify commit subscribers
+    notify_initial_configs(&mut event_service, 10);
+    verify_no_commit_notifications(vec![&mut listener_1, &mut listener_2]);
+}
+
 #[test]
 fn test_dynamic_subscribers() {
     // Create subscription service and mock database
@@ -469,6 +497,27 @@ fn verify_no_reconfig_notifications(listeners: Vec<&mut ReconfigNotificationList
     }
 }
 
+// Ensures that no commit notifications have been received by the listeners
+fn verify_no_commit_notifications(listeners: Vec<&mut CommitNotificationListener>) {
+    for listener in listeners {
+        assert!(listener.select_next_some().now_or_never().is_none());
+    }
+}
+
+// Ensures that the specified listeners have received the expected commit notification.
+fn verify_commit_notification_received(
+    listeners: Vec<&mut CommitNotificationListener>,
+    expected_version: Version,
+) {
+    for listener in listeners {
+        if let Some(commit_notification) = listener.select_next_some().now_or_never() {
+            assert_eq!(commit_notification.version, expected_version);
+        } else {
+            panic!("Expected a commit notification but got None!");
+        }
+    }
+}
+
 // Ensures that the specified listeners have received the expected notifications.
 fn verify_event_notification_received(
     listeners: Vec<&mut EventNotificationListener>,
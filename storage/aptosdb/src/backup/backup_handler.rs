@@ -6,14 +6,15 @@ use crate::{
     event_store::EventStore,
     ledger_store::LedgerStore,
     metrics::{
-        BACKUP_EPOCH_ENDING_EPOCH, BACKUP_STATE_SNAPSHOT_LEAF_IDX, BACKUP_STATE_SNAPSHOT_VERSION,
-        BACKUP_TXN_VERSION,
+        BACKUP_EPOCH_ENDING_EPOCH, BACKUP_STATE_DIFF_KEY_IDX, BACKUP_STATE_DIFF_VERSION,
+        BACKUP_STATE_SNAPSHOT_LEAF_IDX, BACKUP_STATE_SNAPSHOT_VERSION, BACKUP_TXN_VERSION,
     },
     state_store::StateStore,
     transaction_store::TransactionStore,
 };
 use anyhow::{anyhow, ensure, Context, Result};
-use aptos_crypto::hash::HashValue;
+use aptos_crypto::hash::{CryptoHash, HashValue};
+use aptos_storage_interface::DbReader;
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
@@ -23,7 +24,7 @@ use aptos_types::{
     write_set::WriteSet,
 };
 use serde::{Deserialize, Serialize};
-use std::{fmt, sync::Arc};
+use std::{collections::BTreeMap, fmt, sync::Arc};
 
 /// `BackupHandler` provides functionalities for AptosDB data backup.
 #[derive(Clone)]
@@ -133,6 +134,41 @@ impl BackupHandler {
         Ok(Box::new(iterator))
     }
 
+    /// Gets an iterator which yields the net state changes between two versions, i.e. the latest
+    /// value (`None` for deletion) of each key written in (`base_version`, `version`], ordered by
+    /// key hash. Only the changed keys are collected upfront; their values are read from the DB
+    /// as the iterator advances.
+    pub fn get_state_diff_iter(
+        &self,
+        base_version: Version,
+        version: Version,
+    ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, Option<StateValue>)>> + Send + Sync>>
+    {
+        ensure!(
+            version > base_version,
+            "Bad state diff range: ({}, {}]",
+            base_version,
+            version,
+        );
+        let mut keys = BTreeMap::new();
+        for write_set_res in self
+            .transaction_store
+            .get_write_set_iter(base_version + 1, (version - base_version) as usize)?
+        {
+            for (key, _op) in write_set_res?.iter() {
+                keys.entry(key.hash()).or_insert_with(|| key.clone());
+            }
+        }
+        let state_store = Arc::clone(&self.state_store);
+        let iterator = keys.into_values().enumerate().map(move |(idx, key)| {
+            let value = state_store.get_state_value_by_version(&key, version)?;
+            BACKUP_STATE_DIFF_VERSION.set(version as i64);
+            BACKUP_STATE_DIFF_KEY_IDX.set(idx as i64);
+            Ok((key, value))
+        });
+        Ok(Box::new(iterator))
+    }
+
     /// Gets the proof that proves a range of accounts.
     pub fn get_account_state_range_proof(
         &self,
@@ -7,19 +7,23 @@ use crate::{
     db_metadata::{DbMetadataKey, DbMetadataSchema},
     event_store::EventStore,
     ledger_store::LedgerStore,
+    new_sharded_kv_schema_batch,
     state_restore::{StateSnapshotRestore, StateSnapshotRestoreMode},
     state_store::StateStore,
     transaction_store::TransactionStore,
     AptosDB,
 };
-use anyhow::Result;
-use aptos_crypto::HashValue;
+use anyhow::{ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_schemadb::SchemaBatch;
 use aptos_storage_interface::DbReader;
 use aptos_types::{
     contract_event::ContractEvent,
     ledger_info::LedgerInfoWithSignatures,
     proof::definition::LeafCount,
-    state_store::{state_key::StateKey, state_value::StateValue},
+    state_store::{
+        create_empty_sharded_state_updates, state_key::StateKey, state_value::StateValue,
+    },
     transaction::{Transaction, TransactionInfo, Version},
     write_set::WriteSet,
 };
@@ -68,6 +72,76 @@ impl RestoreHandler {
         )
     }
 
+    /// Applies the net state changes between two state snapshots on top of the one at
+    /// `base_version`, producing a snapshot at `version`. Nothing is written unless the resulting
+    /// root hash matches `expected_root_hash`.
+    pub fn apply_state_diff(
+        &self,
+        base_version: Version,
+        version: Version,
+        expected_root_hash: HashValue,
+        updates: Vec<(StateKey, Option<StateValue>)>,
+    ) -> Result<()> {
+        let value_hashes = updates
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.hash(),
+                    value.as_ref().map(|v| (CryptoHash::hash(v), key.clone())),
+                )
+            })
+            .collect::<Vec<_>>();
+        let value_set = value_hashes
+            .iter()
+            .map(|(key_hash, value)| (*key_hash, value.as_ref()))
+            .collect();
+        let (top_levels_batch, sharded_merkle_batch, root_hash) =
+            self.state_store.state_merkle_db.merklize_value_set(
+                value_set,
+                None, /* node_hashes */
+                version,
+                Some(base_version),
+                None, /* previous_epoch_ending_version */
+            )?;
+        ensure!(
+            root_hash == expected_root_hash,
+            "State root hash mismatch after applying state diff {} -> {}. expected: {}, calculated: {}",
+            base_version,
+            version,
+            expected_root_hash,
+            root_hash,
+        );
+
+        let mut sharded_updates = create_empty_sharded_state_updates();
+        for (key, value) in updates {
+            sharded_updates[key.get_shard_id() as usize].insert(key, value);
+        }
+        let ledger_batch = SchemaBatch::new();
+        let sharded_kv_batches = new_sharded_kv_schema_batch();
+        self.state_store.put_state_diff(
+            base_version,
+            version,
+            &sharded_updates,
+            &ledger_batch,
+            &sharded_kv_batches,
+        )?;
+
+        // Commit the values before the tree, so a tree at `version` always has its values.
+        self.state_store
+            .state_db
+            .state_kv_db
+            .commit(version, sharded_kv_batches)?;
+        self.aptosdb
+            .ledger_db
+            .metadata_db()
+            .write_schemas(ledger_batch)?;
+        self.state_store.state_merkle_db.commit(
+            version,
+            top_levels_batch,
+            sharded_merkle_batch,
+        )
+    }
+
     pub fn reset_state_store(&self) {
         self.state_store.reset();
     }
@@ -49,7 +49,7 @@ use aptos_types::{
 use arc_swap::ArcSwapOption;
 use dashmap::DashMap;
 use itertools::zip_eq;
-use move_core_types::move_resource::MoveStructType;
+use move_core_types::{language_storage::TypeTag, move_resource::MoveStructType};
 use std::{borrow::Borrow, collections::HashMap, mem::swap, sync::Arc};
 
 /// Alternate implementation of [crate::state_store::buffered_state::BufferedState] for use with consensus-only-perf-test feature.
@@ -614,6 +614,18 @@ impl DbReader for FakeAptosDB {
             .get_events(event_key, start, order, limit, ledger_version)
     }
 
+    fn get_events_by_type(
+        &self,
+        event_type: &TypeTag,
+        start: u64,
+        order: aptos_storage_interface::Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        self.inner
+            .get_events_by_type(event_type, start, order, limit, ledger_version)
+    }
+
     fn get_block_timestamp(&self, version: Version) -> Result<u64> {
         self.inner.get_block_timestamp(version)
     }
@@ -117,6 +117,7 @@ use aptos_types::{
 use aptos_vm::data_cache::AsMoveResolver;
 use arr_macro::arr;
 use itertools::zip_eq;
+use move_core_types::language_storage::TypeTag;
 use move_resource_viewer::MoveValueAnnotator;
 use once_cell::sync::Lazy;
 use rayon::prelude::*;
@@ -464,13 +465,26 @@ impl AptosDB {
         let ledger_next_version = self
             .get_latest_transaction_info_option()?
             .map_or(0, |(v, _)| v + 1);
+        // The events of pruned transactions can't be indexed by type anymore.
+        let min_readable_version = self.ledger_pruner.get_min_readable_version();
+        if indexer.next_event_version() < min_readable_version {
+            warn!(
+                indexer_next_event_version = indexer.next_event_version(),
+                min_readable_version = min_readable_version,
+                "Events pruned before they were indexed by type are not indexed.",
+            );
+            indexer.skip_events_before(min_readable_version)?;
+        }
         info!(
             indexer_next_version = indexer.next_version(),
+            indexer_next_event_version = indexer.next_event_version(),
             ledger_next_version = ledger_next_version,
             "Opened AptosDB Indexer.",
         );
 
-        if indexer.next_version() < ledger_next_version {
+        let indexer_next_version =
+            std::cmp::min(indexer.next_version(), indexer.next_event_version());
+        if indexer_next_version < ledger_next_version {
             let state_view = DbStateView {
                 db: self.state_store.clone(),
                 version: Some(ledger_next_version - 1),
@@ -479,7 +493,7 @@ impl AptosDB {
             let annotator = MoveValueAnnotator::new(&resolver);
 
             const BATCH_SIZE: Version = 10000;
-            let mut next_version = indexer.next_version();
+            let mut next_version = indexer_next_version;
             while next_version < ledger_next_version {
                 info!(next_version = next_version, "AptosDB Indexer catching up. ",);
                 let end_version = std::cmp::min(ledger_next_version, next_version + BATCH_SIZE);
@@ -487,7 +501,18 @@ impl AptosDB {
                     .transaction_store
                     .get_write_sets(next_version, end_version)?;
                 let write_sets_ref: Vec<_> = write_sets.iter().collect();
-                indexer.index_with_annotator(&annotator, next_version, &write_sets_ref)?;
+                let num_versions = (end_version - next_version) as usize;
+                let events = self
+                    .event_store
+                    .get_events_by_version_iter(next_version, num_versions)?
+                    .collect::<Result<Vec<_>>>()?;
+                let events_ref: Vec<_> = events.iter().map(Vec::as_slice).collect();
+                indexer.index_with_annotator(
+                    &annotator,
+                    next_version,
+                    &write_sets_ref,
+                    &events_ref,
+                )?;
 
                 next_version = end_version;
             }
@@ -797,6 +822,70 @@ impl AptosDB {
         Ok(events_with_version)
     }
 
+    fn get_events_by_event_type(
+        &self,
+        event_type: &TypeTag,
+        start_seq_num: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
+        let indexer = match &self.indexer {
+            Some(indexer) => indexer,
+            None => bail!("Indexer not enabled."),
+        };
+        let get_latest = order == Order::Descending && start_seq_num == u64::max_value();
+
+        let cursor = if get_latest {
+            // Caller wants the latest, figure out the latest seq_num.
+            // In the case of no events of that type, use 0 and expect empty result below.
+            indexer
+                .get_latest_event_sequence_number_by_type(event_type, ledger_version)?
+                .unwrap_or(0)
+        } else {
+            start_seq_num
+        };
+
+        // Convert requested range and order to a range in ascending order.
+        let (first_seq, real_limit) = get_first_seq_num_and_limit(order, cursor, limit)?;
+
+        // Query the index.
+        let mut event_indices =
+            indexer.lookup_events_by_type(event_type, first_seq, real_limit, ledger_version)?;
+
+        // When descending, it's possible that user is asking for something beyond the latest
+        // sequence number, in which case we will consider it a bad request and return an empty
+        // list. See `get_events_by_event_key`.
+        if order == Order::Descending {
+            if let Some((seq_num, _, _)) = event_indices.last() {
+                if *seq_num < cursor {
+                    event_indices = Vec::new();
+                }
+            }
+        }
+
+        let mut events_with_version = event_indices
+            .into_iter()
+            .map(|(_seq, ver, idx)| {
+                self.error_if_ledger_pruned("Event", ver)?;
+                let event = self.event_store.get_event_by_version_and_index(ver, idx)?;
+                ensure!(
+                    event.type_tag() == event_type,
+                    "Index broken, expected type:{}, actual:{}",
+                    event_type,
+                    event.type_tag()
+                );
+                Ok(EventWithVersion::new(ver, event))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if order == Order::Descending {
+            events_with_version.reverse();
+        }
+
+        Ok(events_with_version)
+    }
+
     fn save_ledger_info(
         &self,
         new_root_hash: HashValue,
@@ -1128,7 +1217,16 @@ impl AptosDB {
                 .iter()
                 .map(|txn| txn.borrow().write_set())
                 .collect();
-            indexer.index(self.state_store.clone(), first_version, &write_sets)?;
+            let events: Vec<_> = txns_to_commit
+                .iter()
+                .map(|txn| txn.borrow().events())
+                .collect();
+            indexer.index(
+                self.state_store.clone(),
+                first_version,
+                &write_sets,
+                &events,
+            )?;
         }
 
         // Once everything is successfully persisted, update the latest in-memory ledger info.
@@ -1453,6 +1551,19 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn get_events_by_type(
+        &self,
+        event_type: &TypeTag,
+        start: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        gauged_api("get_events_by_type", || {
+            self.get_events_by_event_type(event_type, start, order, limit, ledger_version)
+        })
+    }
+
     fn get_transaction_iterator(
         &self,
         start_version: Version,
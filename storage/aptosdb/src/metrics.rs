@@ -198,6 +198,22 @@ pub(crate) static BACKUP_STATE_SNAPSHOT_VERSION: Lazy<IntGauge> = Lazy::new(|| {
     .unwrap()
 });
 
+pub(crate) static BACKUP_STATE_DIFF_VERSION: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_backup_handler_state_diff_version",
+        "Target version of requested state diff backup."
+    )
+    .unwrap()
+});
+
+pub(crate) static BACKUP_STATE_DIFF_KEY_IDX: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_backup_handler_state_diff_key_index",
+        "Index of current key returned in a state diff backup."
+    )
+    .unwrap()
+});
+
 pub(crate) static BACKUP_STATE_SNAPSHOT_LEAF_IDX: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
         "aptos_backup_handler_state_snapshot_leaf_index",
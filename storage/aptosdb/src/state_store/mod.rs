@@ -709,6 +709,77 @@ impl StateStore {
         Ok(())
     }
 
+    /// Put the net state changes between `base_version` and `version` as if they were all written
+    /// at `version`, together with the stale indices of the values they overwrite and the storage
+    /// usage at `version`. Used to apply incremental state snapshots, where the transactions in
+    /// between are not available.
+    pub fn put_state_diff(
+        &self,
+        base_version: Version,
+        version: Version,
+        updates: &ShardedStateUpdates,
+        batch: &SchemaBatch,
+        sharded_state_kv_batches: &ShardedStateKvSchemaBatch,
+    ) -> Result<()> {
+        ensure!(
+            version > base_version,
+            "State diff target version {} must be newer than base version {}.",
+            version,
+            base_version,
+        );
+        let base_usage = self.get_usage(Some(base_version))?;
+
+        let usage_deltas = sharded_state_kv_batches
+            .par_iter()
+            .zip(updates.par_iter())
+            .map(|(kv_batch, kvs)| {
+                let mut items_delta = 0i64;
+                let mut bytes_delta = 0i64;
+                for (key, value) in kvs {
+                    if let Some(value) = value {
+                        items_delta += 1;
+                        bytes_delta += (key.size() + value.size()) as i64;
+                    } else {
+                        kv_batch.put::<StaleStateValueIndexSchema>(
+                            &StaleStateValueIndex {
+                                stale_since_version: version,
+                                version,
+                                state_key: key.clone(),
+                            },
+                            &(),
+                        )?;
+                    }
+                    if let Some((old_version, old_value)) = self
+                        .state_db
+                        .get_state_value_with_version_by_version(key, base_version)?
+                    {
+                        items_delta -= 1;
+                        bytes_delta -= (key.size() + old_value.size()) as i64;
+                        kv_batch.put::<StaleStateValueIndexSchema>(
+                            &StaleStateValueIndex {
+                                stale_since_version: version,
+                                version: old_version,
+                                state_key: key.clone(),
+                            },
+                            &(),
+                        )?;
+                    }
+                    kv_batch.put::<StateValueSchema>(&(key.clone(), version), value)?;
+                }
+                Ok((items_delta, bytes_delta))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (items_delta, bytes_delta) = usage_deltas
+            .into_iter()
+            .fold((0, 0), |(items, bytes), (i, b)| (items + i, bytes + b));
+        let usage = StateStorageUsage::new(
+            (base_usage.items() as i64 + items_delta) as usize,
+            (base_usage.bytes() as i64 + bytes_delta) as usize,
+        );
+        batch.put::<VersionDataSchema>(&version, &usage.into())
+    }
+
     pub fn get_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         let _timer = OTHER_TIMERS_SECONDS
             .with_label_values(&["get_usage"])
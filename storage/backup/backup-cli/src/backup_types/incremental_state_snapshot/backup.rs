@@ -0,0 +1,273 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::incremental_state_snapshot::manifest::{
+        IncrementalStateSnapshotBackup, IncrementalStateSnapshotChunk,
+    },
+    metadata::Metadata,
+    storage::{BackupHandleRef, BackupStorage, FileHandle, ShellSafeName},
+    utils::{
+        backup_service_client::BackupServiceClient, read_record_bytes::ReadRecordBytes,
+        should_cut_chunk, storage_ext::BackupStorageExt, GlobalBackupOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use bytes::Bytes;
+use clap::Parser;
+use once_cell::sync::Lazy;
+use std::{convert::TryInto, str::FromStr, sync::Arc};
+use tokio::{io::AsyncWriteExt, time::Instant};
+
+#[derive(Parser)]
+pub struct IncrementalStateSnapshotBackupOpt {
+    #[clap(
+        long,
+        help = "Version of the state snapshot (full or incremental) the increment is based on."
+    )]
+    pub base_version: Version,
+
+    #[clap(
+        long = "state-snapshot-version",
+        help = "Version at which the incremental state snapshot is to be taken. Must be a state \
+        checkpoint version."
+    )]
+    pub version: Version,
+}
+
+pub struct IncrementalStateSnapshotBackupController {
+    base_version: Version,
+    version: Version,
+    max_chunk_size: usize,
+    client: Arc<BackupServiceClient>,
+    storage: Arc<dyn BackupStorage>,
+}
+
+impl IncrementalStateSnapshotBackupController {
+    pub fn new(
+        opt: IncrementalStateSnapshotBackupOpt,
+        global_opt: GlobalBackupOpt,
+        client: Arc<BackupServiceClient>,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            base_version: opt.base_version,
+            version: opt.version,
+            max_chunk_size: global_opt.max_chunk_size,
+            client,
+            storage,
+        }
+    }
+
+    pub async fn run(self) -> Result<FileHandle> {
+        info!(
+            "Incremental state snapshot backup started, from version {} to version {}.",
+            self.base_version, self.version,
+        );
+        let ret = self
+            .run_impl()
+            .await
+            .map_err(|e| anyhow!("Incremental state snapshot backup failed: {}", e))?;
+        info!(
+            "Incremental state snapshot backup succeeded. Manifest: {}",
+            ret
+        );
+        Ok(ret)
+    }
+
+    async fn run_impl(self) -> Result<FileHandle> {
+        ensure!(
+            self.base_version < self.version,
+            "Base version {} must be older than the target version {}.",
+            self.base_version,
+            self.version,
+        );
+
+        let backup_handle = self
+            .storage
+            .create_backup_with_random_suffix(&self.backup_name())
+            .await?;
+
+        let mut chunks = vec![];
+        let mut chunk_bytes = vec![];
+        let mut chunk_first_key = HashValue::zero();
+        let mut chunk_last_key = HashValue::zero();
+        let mut chunk_first_idx: usize = 0;
+        let mut current_idx: usize = 0;
+
+        let mut state_diff_file = self
+            .client
+            .get_state_diff(self.base_version, self.version)
+            .await?;
+
+        let start = Instant::now();
+        while let Some(record_bytes) = state_diff_file.read_record_bytes().await? {
+            if should_cut_chunk(&chunk_bytes, &record_bytes, self.max_chunk_size) {
+                let chunk = self
+                    .write_chunk(
+                        &backup_handle,
+                        &chunk_bytes,
+                        chunk_first_idx,
+                        current_idx - 1,
+                        chunk_first_key,
+                        chunk_last_key,
+                    )
+                    .await?;
+                chunks.push(chunk);
+                chunk_bytes = vec![];
+                chunk_first_idx = current_idx;
+
+                info!(
+                    last_idx = current_idx - 1,
+                    values_per_second = (current_idx as f64 / start.elapsed().as_secs_f64()) as u64,
+                    "Chunk written."
+                );
+            }
+
+            let key = Self::parse_key(&record_bytes)?;
+            if chunk_bytes.is_empty() {
+                chunk_first_key = key;
+            }
+            chunk_last_key = key;
+            chunk_bytes.extend((record_bytes.len() as u32).to_be_bytes());
+            chunk_bytes.extend(&record_bytes);
+            current_idx += 1;
+        }
+
+        // An empty diff is legit (nothing changed in between), in which case there's no chunk.
+        if !chunk_bytes.is_empty() {
+            let chunk = self
+                .write_chunk(
+                    &backup_handle,
+                    &chunk_bytes,
+                    chunk_first_idx,
+                    current_idx - 1,
+                    chunk_first_key,
+                    chunk_last_key,
+                )
+                .await?;
+            chunks.push(chunk);
+        }
+
+        self.write_manifest(&backup_handle, chunks).await
+    }
+}
+
+impl IncrementalStateSnapshotBackupController {
+    fn backup_name(&self) -> String {
+        format!(
+            "incremental_state_ver_{}-{}",
+            self.base_version, self.version
+        )
+    }
+
+    fn manifest_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("incremental_state.manifest").unwrap());
+        &NAME
+    }
+
+    fn proof_name() -> &'static ShellSafeName {
+        static NAME: Lazy<ShellSafeName> =
+            Lazy::new(|| ShellSafeName::from_str("incremental_state.proof").unwrap());
+        &NAME
+    }
+
+    fn chunk_name(first_idx: usize) -> ShellSafeName {
+        format!("{}-.chunk", first_idx).try_into().unwrap()
+    }
+
+    fn parse_key(record: &Bytes) -> Result<HashValue> {
+        let (key, _): (StateKey, Option<StateValue>) = bcs::from_bytes(record)?;
+        Ok(key.hash())
+    }
+
+    async fn write_chunk(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunk_bytes: &[u8],
+        first_idx: usize,
+        last_idx: usize,
+        first_key: HashValue,
+        last_key: HashValue,
+    ) -> Result<IncrementalStateSnapshotChunk> {
+        let (chunk_handle, mut chunk_file) = self
+            .storage
+            .create_for_write(backup_handle, &Self::chunk_name(first_idx))
+            .await?;
+        chunk_file.write_all(chunk_bytes).await?;
+        chunk_file.shutdown().await?;
+
+        Ok(IncrementalStateSnapshotChunk {
+            first_idx,
+            last_idx,
+            first_key,
+            last_key,
+            blobs: chunk_handle,
+        })
+    }
+
+    /// Returns the state root hash at `version`, the epoch it's in and the serialized proof.
+    async fn get_state_root(&self, version: Version) -> Result<(HashValue, u64, Vec<u8>)> {
+        let proof_bytes = self.client.get_state_root_proof(version).await?;
+        let (txn_info, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            bcs::from_bytes(&proof_bytes)?;
+        let root_hash = txn_info.transaction_info().ensure_state_checkpoint_hash()?;
+        Ok((root_hash, li.ledger_info().epoch(), proof_bytes))
+    }
+
+    async fn write_manifest(
+        &self,
+        backup_handle: &BackupHandleRef,
+        chunks: Vec<IncrementalStateSnapshotChunk>,
+    ) -> Result<FileHandle> {
+        let (base_root_hash, _, _) = self.get_state_root(self.base_version).await?;
+        let (root_hash, epoch, proof_bytes) = self.get_state_root(self.version).await?;
+
+        let (proof_handle, mut proof_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::proof_name())
+            .await?;
+        proof_file.write_all(&proof_bytes).await?;
+        proof_file.shutdown().await?;
+
+        let manifest = IncrementalStateSnapshotBackup {
+            base_version: self.base_version,
+            base_root_hash,
+            version: self.version,
+            epoch,
+            root_hash,
+            chunks,
+            proof: proof_handle,
+        };
+
+        let (manifest_handle, mut manifest_file) = self
+            .storage
+            .create_for_write(backup_handle, Self::manifest_name())
+            .await?;
+        manifest_file
+            .write_all(&serde_json::to_vec(&manifest)?)
+            .await?;
+        manifest_file.shutdown().await?;
+
+        let metadata = Metadata::new_incremental_state_snapshot_backup(
+            epoch,
+            self.base_version,
+            self.version,
+            manifest_handle.clone(),
+        );
+        self.storage
+            .save_metadata_line(&metadata.name(), &metadata.to_text_line()?)
+            .await?;
+
+        Ok(manifest_handle)
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::FileHandle;
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// A chunk of an incremental state snapshot manifest, representing the changed keys in the key
+/// range [`first_key`, `last_key`] (right side inclusive).
+#[derive(Deserialize, Serialize)]
+pub struct IncrementalStateSnapshotChunk {
+    /// index of the first changed key in this chunk over all changed keys.
+    pub first_idx: usize,
+    /// index of the last changed key in this chunk over all changed keys.
+    pub last_idx: usize,
+    /// hash of the first changed key in this chunk.
+    pub first_key: HashValue,
+    /// hash of the last changed key in this chunk.
+    pub last_key: HashValue,
+    /// Repeated `len(record) + record` where `record` is BCS serialized tuple
+    /// `(key, Option<state_value>)`, `None` meaning the key is deleted.
+    pub blobs: FileHandle,
+}
+
+/// Incremental state snapshot backup manifest, representing the net state changes between the
+/// state snapshot (full or incremental) at `base_version` and the state at `version`.
+///
+/// Unlike in a full state snapshot, the chunks don't carry range proofs, since the changes alone
+/// don't add up to a root hash. Instead, applying the changes on top of the base state must
+/// result in `root_hash`, which is checked on restore.
+#[derive(Deserialize, Serialize)]
+pub struct IncrementalStateSnapshotBackup {
+    /// Version of the state snapshot this is based on.
+    pub base_version: Version,
+    /// Hash of the state tree root at `base_version`.
+    pub base_root_hash: HashValue,
+    /// Version at which this state snapshot is taken.
+    pub version: Version,
+    /// Epoch in which this state snapshot is taken.
+    pub epoch: u64,
+    /// Hash of the state tree root at `version`.
+    pub root_hash: HashValue,
+    /// All changed state values in chunks, ordered by key hash.
+    pub chunks: Vec<IncrementalStateSnapshotChunk>,
+    /// BCS serialized
+    /// `Tuple(TransactionInfoWithProof, LedgerInfoWithSignatures)`, proving `root_hash` at
+    /// `version`, same as `StateSnapshotBackup::proof`.
+    pub proof: FileHandle,
+}
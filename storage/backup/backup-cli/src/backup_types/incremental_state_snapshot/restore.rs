@@ -0,0 +1,195 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::EpochHistory,
+        incremental_state_snapshot::manifest::IncrementalStateSnapshotBackup,
+    },
+    metrics::OTHER_TIMERS_SECONDS,
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt, stream::StreamX,
+        GlobalRestoreOptions, RestoreRunMode,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_crypto::hash::CryptoHash;
+use aptos_logger::prelude::*;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    proof::TransactionInfoWithProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+};
+use clap::Parser;
+use futures::{stream, TryStreamExt};
+use std::sync::Arc;
+use tokio::time::Instant;
+
+#[derive(Parser)]
+pub struct IncrementalStateSnapshotRestoreOpt {
+    #[clap(long = "incremental-state-manifest")]
+    pub manifest_handle: FileHandle,
+}
+
+pub struct IncrementalStateSnapshotRestoreController {
+    storage: Arc<dyn BackupStorage>,
+    run_mode: Arc<RestoreRunMode>,
+    manifest_handle: FileHandle,
+    /// Global "target_version" for the entire restore process, if the version of the incremental
+    /// snapshot is newer than this, nothing will be done, otherwise, this has no effect.
+    target_version: Version,
+    epoch_history: Option<Arc<EpochHistory>>,
+    concurrent_downloads: usize,
+}
+
+impl IncrementalStateSnapshotRestoreController {
+    pub fn new(
+        opt: IncrementalStateSnapshotRestoreOpt,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+        epoch_history: Option<Arc<EpochHistory>>,
+    ) -> Self {
+        Self {
+            storage,
+            run_mode: global_opt.run_mode,
+            manifest_handle: opt.manifest_handle,
+            target_version: global_opt.target_version,
+            epoch_history,
+            concurrent_downloads: global_opt.concurrent_downloads,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let name = self.name();
+        let start = Instant::now();
+        info!("{} started. Manifest: {}", name, self.manifest_handle);
+        self.run_impl()
+            .await
+            .map_err(|e| anyhow!("{} failed: {}", name, e))?;
+        info!(time = start.elapsed().as_secs(), "{} succeeded.", name);
+        Ok(())
+    }
+}
+
+impl IncrementalStateSnapshotRestoreController {
+    fn name(&self) -> String {
+        format!("incremental state snapshot {}", self.run_mode.name())
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        let manifest: IncrementalStateSnapshotBackup =
+            self.storage.load_json_file(&self.manifest_handle).await?;
+        if manifest.version > self.target_version {
+            warn!(
+                "Trying to restore incremental state snapshot to version {}, which is newer than the target version {}, skipping.",
+                manifest.version,
+                self.target_version,
+            );
+            return Ok(());
+        }
+
+        let (txn_info_with_proof, li): (TransactionInfoWithProof, LedgerInfoWithSignatures) =
+            self.storage.load_bcs_file(&manifest.proof).await?;
+        txn_info_with_proof.verify(li.ledger_info(), manifest.version)?;
+        let state_root_hash = txn_info_with_proof
+            .transaction_info()
+            .ensure_state_checkpoint_hash()?;
+        ensure!(
+            state_root_hash == manifest.root_hash,
+            "Root hash mismatch with that in proof. root hash: {}, expected: {}",
+            manifest.root_hash,
+            state_root_hash,
+        );
+        if let Some(epoch_history) = self.epoch_history.as_ref() {
+            epoch_history.verify_ledger_info(&li)?;
+        }
+
+        if !self.run_mode.is_verify() {
+            let base = self
+                .run_mode
+                .get_state_snapshot_before(manifest.base_version + 1);
+            ensure!(
+                base == Some((manifest.base_version, manifest.base_root_hash)),
+                "Base state snapshot (version {}, root hash {}) not found in DB, latest snapshot \
+                in DB before that: {:?}",
+                manifest.base_version,
+                manifest.base_root_hash,
+                base,
+            );
+        }
+
+        let total_values = manifest.chunks.last().map_or(0, |c| c.last_idx + 1);
+        let storage = self.storage.clone();
+        let futs_iter = manifest.chunks.into_iter().map(|chunk| {
+            let storage = storage.clone();
+            async move {
+                tokio::spawn(async move {
+                    let blobs = Self::read_state_updates(&storage, chunk.blobs.clone()).await?;
+                    Result::<_>::Ok((chunk, blobs))
+                })
+                .await?
+            }
+        });
+        let con = self.concurrent_downloads;
+        let mut futs_stream = stream::iter(futs_iter).buffered_x(con * 2, con);
+
+        let mut updates: Vec<(StateKey, Option<StateValue>)> = Vec::with_capacity(total_values);
+        while let Some((chunk, blobs)) = futs_stream.try_next().await? {
+            ensure!(
+                blobs.len() == chunk.last_idx + 1 - chunk.first_idx,
+                "Number of values in chunk doesn't match manifest. expected: {}, actual: {}",
+                chunk.last_idx + 1 - chunk.first_idx,
+                blobs.len(),
+            );
+            for (key, value) in blobs {
+                let key_hash = key.hash();
+                if let Some((prev_key, _)) = updates.last() {
+                    ensure!(
+                        prev_key.hash() < key_hash,
+                        "State keys not in strictly increasing hash order. prev: {:?}, current: {:?}",
+                        prev_key,
+                        key,
+                    );
+                }
+                updates.push((key, value));
+            }
+        }
+
+        let _timer = OTHER_TIMERS_SECONDS
+            .with_label_values(&["apply_state_diff"])
+            .start_timer();
+        let run_mode = self.run_mode.clone();
+        let (base_version, version, root_hash) =
+            (manifest.base_version, manifest.version, manifest.root_hash);
+        tokio::task::spawn_blocking(move || {
+            run_mode.apply_state_diff(base_version, version, root_hash, updates)
+        })
+        .await??;
+        self.run_mode.finish();
+        info!(
+            base_version = base_version,
+            version = version,
+            num_values = total_values,
+            "State diff applied."
+        );
+
+        Ok(())
+    }
+
+    async fn read_state_updates(
+        storage: &Arc<dyn BackupStorage>,
+        file_handle: FileHandle,
+    ) -> Result<Vec<(StateKey, Option<StateValue>)>> {
+        let mut file = storage.open_for_read(&file_handle).await?;
+
+        let mut chunk = vec![];
+
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            chunk.push(bcs::from_bytes(&record_bytes)?);
+        }
+
+        Ok(chunk)
+    }
+}
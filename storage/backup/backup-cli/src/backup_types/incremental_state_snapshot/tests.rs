@@ -0,0 +1,167 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        incremental_state_snapshot::{
+            backup::{IncrementalStateSnapshotBackupController, IncrementalStateSnapshotBackupOpt},
+            restore::{
+                IncrementalStateSnapshotRestoreController, IncrementalStateSnapshotRestoreOpt,
+            },
+        },
+        state_snapshot::{
+            backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+            restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        },
+    },
+    storage::{local_fs::LocalFs, BackupStorage},
+    utils::{
+        backup_service_client::BackupServiceClient,
+        test_utils::{start_local_backup_service, tmp_db_with_random_blocks},
+        ConcurrentDownloadsOpt, GlobalBackupOpt, GlobalRestoreOpt, ReplayConcurrencyLevelOpt,
+        RocksdbOpt, TrustedWaypointOpt,
+    },
+};
+use aptos_crypto::HashValue;
+use aptos_db::{state_restore::StateSnapshotRestoreMode, AptosDB};
+use aptos_storage_interface::DbReader;
+use aptos_temppath::TempPath;
+use aptos_types::transaction::Version;
+use std::{convert::TryInto, sync::Arc};
+use tokio::time::Duration;
+
+fn state_root_hash(db: &AptosDB, version: Version) -> HashValue {
+    db.get_transactions(version, 1, version, false)
+        .unwrap()
+        .proof
+        .transaction_infos
+        .pop()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap()
+}
+
+fn global_restore_opt(db_dir: &TempPath) -> GlobalRestoreOpt {
+    GlobalRestoreOpt {
+        dry_run: false,
+        db_dir: Some(db_dir.path().to_path_buf()),
+        target_version: None, // max
+        trusted_waypoints: TrustedWaypointOpt::default(),
+        rocksdb_opt: RocksdbOpt::default(),
+        concurrent_downloads: ConcurrentDownloadsOpt::default(),
+        replay_concurrency_level: ReplayConcurrencyLevelOpt::default(),
+    }
+}
+
+#[test]
+fn end_to_end() {
+    // The first block always ends epoch 0, so with at least two blocks the latest version is
+    // strictly newer than the end of epoch 0.
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_blocks(2, 10);
+    let tgt_db_dir = TempPath::new();
+    tgt_db_dir.create_as_dir().unwrap();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let store: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(backup_dir.path().to_path_buf()));
+
+    let version = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .version();
+    let epoch = 0;
+    let base_version = src_db
+        .get_epoch_ending_ledger_infos(epoch, epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs
+        .pop()
+        .unwrap()
+        .ledger_info()
+        .version();
+    assert!(
+        base_version < version,
+        "Expected transactions after the end of epoch 0."
+    );
+    let base_root_hash = state_root_hash(&src_db, base_version);
+    let root_hash = state_root_hash(&src_db, version);
+
+    let (rt, port) = start_local_backup_service(src_db);
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_backup_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+    let manifest_handle = rt
+        .block_on(
+            StateSnapshotBackupController::new(
+                StateSnapshotBackupOpt { epoch },
+                global_backup_opt.clone(),
+                Arc::clone(&client),
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+    let incremental_manifest_handle = rt
+        .block_on(
+            IncrementalStateSnapshotBackupController::new(
+                IncrementalStateSnapshotBackupOpt {
+                    base_version,
+                    version,
+                },
+                global_backup_opt,
+                client,
+                Arc::clone(&store),
+            )
+            .run(),
+        )
+        .unwrap();
+
+    rt.block_on(
+        StateSnapshotRestoreController::new(
+            StateSnapshotRestoreOpt {
+                manifest_handle,
+                version: base_version,
+                validate_modules: false,
+                restore_mode: StateSnapshotRestoreMode::Default,
+            },
+            global_restore_opt(&tgt_db_dir).try_into().unwrap(),
+            Arc::clone(&store),
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        IncrementalStateSnapshotRestoreController::new(
+            IncrementalStateSnapshotRestoreOpt {
+                manifest_handle: incremental_manifest_handle,
+            },
+            global_restore_opt(&tgt_db_dir).try_into().unwrap(),
+            store,
+            None, /* epoch_history */
+        )
+        .run(),
+    )
+    .unwrap();
+
+    let tgt_db = AptosDB::new_readonly_for_test(&tgt_db_dir);
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(base_version + 1)
+            .unwrap()
+            .unwrap(),
+        (base_version, base_root_hash)
+    );
+    assert_eq!(
+        tgt_db
+            .get_state_snapshot_before(version + 1)
+            .unwrap()
+            .unwrap(),
+        (version, root_hash)
+    );
+
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
@@ -0,0 +1,174 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::EpochHistoryRestoreController,
+        incremental_state_snapshot::restore::{
+            IncrementalStateSnapshotRestoreController, IncrementalStateSnapshotRestoreOpt,
+        },
+        state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+    },
+    metadata,
+    metadata::cache::MetadataCacheOpt,
+    storage::BackupStorage,
+    utils::GlobalRestoreOptions,
+};
+use anyhow::{anyhow, bail, Result};
+use aptos_db::state_restore::StateSnapshotRestoreMode;
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use clap::Parser;
+use std::sync::Arc;
+
+#[derive(Parser)]
+pub struct IncrementalStateRestoreCoordinatorOpt {
+    #[clap(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[clap(long, help = "Skip restoring epoch ending info, used for debugging.")]
+    pub skip_epoch_endings: bool,
+}
+
+/// Bootstraps the state of a DB to the newest version reachable at or before the target version,
+/// by restoring a full state snapshot and applying a chain of incremental state snapshots on top
+/// of it, without replaying any transaction.
+pub struct IncrementalStateRestoreCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    global_opt: GlobalRestoreOptions,
+    metadata_cache_opt: MetadataCacheOpt,
+    skip_epoch_endings: bool,
+}
+
+impl IncrementalStateRestoreCoordinator {
+    pub fn new(
+        opt: IncrementalStateRestoreCoordinatorOpt,
+        global_opt: GlobalRestoreOptions,
+        storage: Arc<dyn BackupStorage>,
+    ) -> Self {
+        Self {
+            storage,
+            global_opt,
+            metadata_cache_opt: opt.metadata_cache_opt,
+            skip_epoch_endings: opt.skip_epoch_endings,
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        info!("Incremental state restore coordinator started.");
+        let ret = self.run_impl().await;
+
+        if let Err(e) = &ret {
+            error!(
+                error = ?e,
+                "Incremental state restore coordinator failed."
+            );
+        } else {
+            info!("Incremental state restore coordinator exiting with success.");
+        }
+
+        ret
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.global_opt.concurrent_downloads,
+        )
+        .await?;
+
+        let (full_snapshot, incrementals) = metadata_view
+            .select_state_snapshot_chain(self.global_opt.target_version)?
+            .ok_or_else(|| {
+                anyhow!(
+                    "No state snapshot found at or before version {}.",
+                    self.global_opt.target_version
+                )
+            })?;
+        let target_version = incrementals
+            .last()
+            .map_or(full_snapshot.version, |s| s.version);
+        info!(
+            full_snapshot_version = full_snapshot.version,
+            num_incremental_snapshots = incrementals.len(),
+            target_version = target_version,
+            "State snapshot chain selected."
+        );
+
+        let epoch_history = if !self.skip_epoch_endings {
+            let epoch_handles = metadata_view
+                .select_epoch_ending_backups(target_version)?
+                .iter()
+                .filter(|e| e.first_version <= target_version)
+                .map(|backup| backup.manifest.clone())
+                .collect();
+            Some(Arc::new(
+                EpochHistoryRestoreController::new(
+                    epoch_handles,
+                    self.global_opt.clone(),
+                    self.storage.clone(),
+                )
+                .run()
+                .await?,
+            ))
+        } else {
+            None
+        };
+
+        // Resume from the latest state snapshot already in the DB, if any.
+        let (restore_full, incrementals_to_apply) = match self
+            .global_opt
+            .run_mode
+            .get_state_snapshot_before(Version::MAX)
+        {
+            None => (true, incrementals.as_slice()),
+            Some((version, _)) if version == full_snapshot.version => {
+                (false, incrementals.as_slice())
+            },
+            Some((version, _)) => match incrementals.iter().position(|s| s.version == version) {
+                Some(pos) => (false, &incrementals[pos + 1..]),
+                None => bail!(
+                    "State snapshot at version {} in DB is not on the selected chain, \
+                        please restore into an empty DB.",
+                    version,
+                ),
+            },
+        };
+
+        if restore_full {
+            StateSnapshotRestoreController::new(
+                StateSnapshotRestoreOpt {
+                    manifest_handle: full_snapshot.manifest.clone(),
+                    version: full_snapshot.version,
+                    validate_modules: false,
+                    restore_mode: StateSnapshotRestoreMode::Default,
+                },
+                self.global_opt.clone(),
+                Arc::clone(&self.storage),
+                epoch_history.clone(),
+            )
+            .run()
+            .await?;
+        } else {
+            info!(
+                "Skipping restored state snapshots, {} incremental state snapshot(s) to apply.",
+                incrementals_to_apply.len(),
+            );
+        }
+
+        for incremental in incrementals_to_apply {
+            IncrementalStateSnapshotRestoreController::new(
+                IncrementalStateSnapshotRestoreOpt {
+                    manifest_handle: incremental.manifest.clone(),
+                },
+                self.global_opt.clone(),
+                Arc::clone(&self.storage),
+                epoch_history.clone(),
+            )
+            .run()
+            .await?;
+        }
+
+        Ok(())
+    }
+}
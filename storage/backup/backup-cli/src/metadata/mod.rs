@@ -18,6 +18,7 @@ pub(crate) enum Metadata {
     EpochEndingBackup(EpochEndingBackupMeta),
     StateSnapshotBackup(StateSnapshotBackupMeta),
     TransactionBackup(TransactionBackupMeta),
+    IncrementalStateSnapshotBackup(IncrementalStateSnapshotBackupMeta),
     Identity(IdentityMeta),
     CompactionTimestamps(CompactionTimestampsMeta),
 }
@@ -47,6 +48,20 @@ impl Metadata {
         })
     }
 
+    pub fn new_incremental_state_snapshot_backup(
+        epoch: u64,
+        base_version: Version,
+        version: Version,
+        manifest: FileHandle,
+    ) -> Self {
+        Self::IncrementalStateSnapshotBackup(IncrementalStateSnapshotBackupMeta {
+            epoch,
+            version,
+            base_version,
+            manifest,
+        })
+    }
+
     pub fn new_transaction_backup(
         first_version: Version,
         last_version: Version,
@@ -112,6 +127,25 @@ impl Metadata {
         Ok((res, name.parse()?))
     }
 
+    pub fn compact_incremental_state_snapshot_backup_range(
+        backup_metas: Vec<IncrementalStateSnapshotBackupMeta>,
+    ) -> Result<(Vec<TextLine>, ShellSafeName)> {
+        ensure!(
+            !backup_metas.is_empty(),
+            "compacting an empty metadata vector"
+        );
+        let name = format!(
+            "incremental_state_snapshot_compacted_ver_{}_{}.meta",
+            backup_metas[0].version,
+            backup_metas[backup_metas.len() - 1].version
+        );
+        let res: Vec<TextLine> = backup_metas
+            .into_iter()
+            .map(|e| Metadata::IncrementalStateSnapshotBackup(e).to_text_line())
+            .collect::<Result<_>>()?;
+        Ok((res, name.parse()?))
+    }
+
     pub fn compact_transaction_backup_range(
         backup_metas: Vec<TransactionBackupMeta>,
     ) -> Result<(Vec<TextLine>, ShellSafeName)> {
@@ -159,6 +193,10 @@ impl Metadata {
             Self::TransactionBackup(t) => {
                 format!("transaction_{}-{}.meta", t.first_version, t.last_version)
             },
+            Self::IncrementalStateSnapshotBackup(s) => format!(
+                "incremental_state_snapshot_ver_{}-{}.meta",
+                s.base_version, s.version
+            ),
             Metadata::Identity(_) => "identity.meta".into(),
             Self::CompactionTimestamps(e) => {
                 format!("compaction_timestamps_{}.meta", e.file_compacted_at,)
@@ -189,6 +227,16 @@ pub struct StateSnapshotBackupMeta {
     pub manifest: FileHandle,
 }
 
+/// An incremental state snapshot holds the state changes between the state snapshot (full or
+/// incremental) at `base_version` and `version`.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
+pub struct IncrementalStateSnapshotBackupMeta {
+    pub epoch: u64,
+    pub version: Version,
+    pub base_version: Version,
+    pub manifest: FileHandle,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Ord, PartialOrd)]
 pub struct TransactionBackupMeta {
     pub first_version: Version,
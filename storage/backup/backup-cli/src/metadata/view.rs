@@ -4,8 +4,9 @@
 
 use crate::{
     metadata::{
-        CompactionTimestampsMeta, EpochEndingBackupMeta, IdentityMeta, Metadata,
-        StateSnapshotBackupMeta, TransactionBackupMeta,
+        CompactionTimestampsMeta, EpochEndingBackupMeta, IdentityMeta,
+        IncrementalStateSnapshotBackupMeta, Metadata, StateSnapshotBackupMeta,
+        TransactionBackupMeta,
     },
     storage::FileHandle,
 };
@@ -13,13 +14,14 @@ use anyhow::{anyhow, ensure, Result};
 use aptos_infallible::duration_since_epoch;
 use aptos_types::transaction::Version;
 use itertools::Itertools;
-use std::{fmt, str::FromStr};
+use std::{collections::BTreeMap, fmt, str::FromStr};
 
 #[derive(Debug)]
 pub struct MetadataView {
     epoch_ending_backups: Vec<EpochEndingBackupMeta>,
     state_snapshot_backups: Vec<StateSnapshotBackupMeta>,
     transaction_backups: Vec<TransactionBackupMeta>,
+    incremental_state_snapshot_backups: Vec<IncrementalStateSnapshotBackupMeta>,
     _identity: Option<IdentityMeta>,
     // The compaction timestamps of the file handles producing this view
     compaction_timestamps: Option<CompactionTimestampsMeta>,
@@ -30,6 +32,7 @@ impl MetadataView {
         let mut epoch_ending_backups = Vec::new();
         let mut state_snapshot_backups = Vec::new();
         let mut transaction_backups = Vec::new();
+        let mut incremental_state_snapshot_backups = Vec::new();
         let mut identity = None;
         let mut compaction_timestamps = Vec::new();
 
@@ -38,6 +41,9 @@ impl MetadataView {
                 Metadata::EpochEndingBackup(e) => epoch_ending_backups.push(e),
                 Metadata::StateSnapshotBackup(s) => state_snapshot_backups.push(s),
                 Metadata::TransactionBackup(t) => transaction_backups.push(t),
+                Metadata::IncrementalStateSnapshotBackup(s) => {
+                    incremental_state_snapshot_backups.push(s)
+                },
                 Metadata::Identity(i) => identity = Some(i),
                 Metadata::CompactionTimestamps(t) => compaction_timestamps.push(t),
             }
@@ -48,6 +54,8 @@ impl MetadataView {
         state_snapshot_backups.dedup();
         transaction_backups.sort_unstable();
         transaction_backups.dedup();
+        incremental_state_snapshot_backups.sort_unstable();
+        incremental_state_snapshot_backups.dedup();
 
         let mut compaction_meta_opt = compaction_timestamps.iter().max().cloned();
         if let Some(ref mut compaction_meta) = compaction_meta_opt {
@@ -72,6 +80,7 @@ impl MetadataView {
             epoch_ending_backups,
             state_snapshot_backups,
             transaction_backups,
+            incremental_state_snapshot_backups,
             _identity: identity,
             compaction_timestamps: compaction_meta_opt,
         }
@@ -125,6 +134,43 @@ impl MetadataView {
             .ok_or_else(|| anyhow!("State snapshot not found at version {}", version))
     }
 
+    /// Selects the newest state reachable at or before `target_version`, by a full state snapshot
+    /// followed by a chain of incremental state snapshots, each based on the previous one.
+    /// Among chains reaching the same version, the shortest one is preferred.
+    pub fn select_state_snapshot_chain(
+        &self,
+        target_version: Version,
+    ) -> Result<Option<(StateSnapshotBackupMeta, Vec<IncrementalStateSnapshotBackupMeta>)>> {
+        let mut chains: BTreeMap<Version, (StateSnapshotBackupMeta, Vec<_>)> = self
+            .state_snapshot_backups
+            .iter()
+            .filter(|s| s.version <= target_version)
+            .map(|s| (s.version, (s.clone(), Vec::new())))
+            .collect();
+        // Sorted by version, so all chains leading to the base of an incremental snapshot are
+        // settled by the time it's visited.
+        for incremental in self
+            .incremental_state_snapshot_backups
+            .iter()
+            .sorted_by_key(|s| s.version)
+            .filter(|s| s.version <= target_version)
+        {
+            if let Some((full, base_chain)) = chains.get(&incremental.base_version) {
+                let is_shorter = chains
+                    .get(&incremental.version)
+                    .map_or(true, |(_, chain)| chain.len() > base_chain.len() + 1);
+                if is_shorter {
+                    let full = full.clone();
+                    let mut chain = base_chain.clone();
+                    chain.push(incremental.clone());
+                    chains.insert(incremental.version, (full, chain));
+                }
+            }
+        }
+
+        Ok(chains.into_iter().next_back().map(|(_, chain)| chain))
+    }
+
     pub fn select_transaction_backups(
         &self,
         start_version: Version,
@@ -229,6 +275,13 @@ impl MetadataView {
         Self::compact_backups(&self.state_snapshot_backups, compaction_cnt)
     }
 
+    pub fn compact_incremental_state_backups(
+        &mut self,
+        compaction_cnt: usize,
+    ) -> Result<Vec<&[IncrementalStateSnapshotBackupMeta]>> {
+        Self::compact_backups(&self.incremental_state_snapshot_backups, compaction_cnt)
+    }
+
     pub fn get_file_handles(&self) -> Vec<FileHandle> {
         self.select_latest_compaction_timestamps()
             .as_ref()
@@ -0,0 +1,707 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A `BackupStorage` layer that transparently compresses and / or encrypts everything written
+//! through it, on top of any other `BackupStorage` implementation.
+//!
+//! Every encoded file starts with a fixed size header that records the codec (compression and
+//! encryption flags) and the id of the encryption key, so readers can decode without being told
+//! how the backup was taken. Following the header, the content is split into frames of at most
+//! `FRAME_SIZE` bytes of plaintext, each of which is compressed with zstd and then sealed with
+//! AES-256-GCM, using a nonce derived from a random per-file prefix and the frame index. The file
+//! header, the frame index and whether the frame is the last one are authenticated along with every
+//! frame, so a tampered header or a truncated file is detected.
+//!
+//! Metadata files must stay text lines, so an encoded metadata file is saved as a single line
+//! holding `TEXT_MAGIC` followed by the base64 of the binary encoding.
+//!
+//! Files without either magic are passed through as is, so backups taken before the codec was
+//! turned on remain readable.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use aes_gcm::{
+    aead::{Aead, NewAead, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_config::config::SecureBackend;
+use aptos_crypto::HashValue;
+use aptos_secure_storage::{KVStorage, Storage};
+use async_trait::async_trait;
+use clap::Parser;
+use std::{
+    convert::TryInto,
+    fmt, io,
+    io::Cursor,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+
+const MAGIC: &[u8; 8] = b"APTBKCDC";
+const TEXT_MAGIC: &str = "#aptos-backup-codec:";
+const FORMAT_VERSION: u8 = 1;
+/// magic + version + flags + key id + nonce prefix
+const HEADER_LEN: usize = MAGIC.len() + 1 + 1 + KEY_ID_LEN + NONCE_PREFIX_LEN;
+const KEY_ID_LEN: usize = 8;
+const NONCE_PREFIX_LEN: usize = 8;
+/// is_last flag + u32 length
+const FRAME_HEADER_LEN: usize = 1 + 4;
+const FRAME_SIZE: usize = 1 << 20;
+/// Upper bound of an encoded frame, generous enough for the zstd worst case plus the AEAD tag.
+const MAX_ENCODED_FRAME_SIZE: usize = FRAME_SIZE + FRAME_SIZE / 8 + 1024;
+
+const FLAG_ZSTD: u8 = 0b01;
+const FLAG_AES_256_GCM: u8 = 0b10;
+
+#[derive(Clone, Parser)]
+pub struct StorageCodecOpt {
+    #[clap(
+        long,
+        help = "Compress every file written to the backup storage with zstd. Compressed backups are \
+        detected and decompressed automatically when read, regardless of this flag."
+    )]
+    pub compress: bool,
+    #[clap(
+        long,
+        default_value = "3",
+        help = "zstd compression level, used when --compress is set."
+    )]
+    pub compression_level: i32,
+    #[clap(
+        long,
+        parse(from_os_str),
+        conflicts_with = "encryption-key-secure-storage-config",
+        help = "File holding the hex encoded 32 byte AES-256-GCM key. When set, every file written \
+        to the backup storage is encrypted, and encrypted files are decrypted when read."
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+    #[clap(
+        long,
+        parse(from_os_str),
+        help = "YAML file holding a `SecureBackend` config, from which the hex encoded 32 byte \
+        AES-256-GCM key is read under the name given by --encryption-key-name."
+    )]
+    pub encryption_key_secure_storage_config: Option<PathBuf>,
+    #[clap(
+        long,
+        default_value = "backup_encryption_key",
+        help = "Name of the encryption key in the secure storage."
+    )]
+    pub encryption_key_name: String,
+}
+
+impl StorageCodecOpt {
+    fn load_key(&self) -> Result<Option<EncryptionKey>> {
+        let hex_key = if let Some(path) = &self.encryption_key_file {
+            std::fs::read_to_string(path).err_notes(path)?
+        } else if let Some(path) = &self.encryption_key_secure_storage_config {
+            let backend: SecureBackend =
+                serde_yaml::from_str(&std::fs::read_to_string(path).err_notes(path)?)?;
+            Storage::from(&backend)
+                .get::<String>(&self.encryption_key_name)
+                .map_err(|e| {
+                    format_err!(
+                        "Failed to read encryption key {} from secure storage: {}",
+                        self.encryption_key_name,
+                        e
+                    )
+                })?
+                .value
+        } else {
+            return Ok(None);
+        };
+        EncryptionKey::from_bytes(&hex::decode(hex_key.trim())?).map(Some)
+    }
+
+    /// Wraps `inner` with the codec layer. Always wrapping is harmless: with neither compression
+    /// nor encryption configured, files are written as is and encoded files are still decoded
+    /// on read (failing only if they are encrypted, since there's no key).
+    pub fn wrap(&self, inner: Arc<dyn BackupStorage>) -> Result<Arc<dyn BackupStorage>> {
+        let compression_level = if self.compress {
+            Some(self.compression_level)
+        } else {
+            None
+        };
+        Ok(Arc::new(CodecStorage::new(
+            inner,
+            compression_level,
+            self.load_key()?,
+        )))
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct KeyId([u8; KEY_ID_LEN]);
+
+impl KeyId {
+    const NONE: KeyId = KeyId([0; KEY_ID_LEN]);
+}
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+pub struct EncryptionKey {
+    id: KeyId,
+    cipher: Aes256Gcm,
+}
+
+impl EncryptionKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() == 32,
+            "Encryption key must be 32 bytes, got {}.",
+            bytes.len()
+        );
+        // The key id is a hash of the key, so it can be recorded in the backup without leaking
+        // the key, and checked against the key provided on restore.
+        let id = KeyId(HashValue::sha3_256_of(bytes).to_vec()[..KEY_ID_LEN].try_into()?);
+        Ok(Self {
+            id,
+            cipher: Aes256Gcm::new(Key::from_slice(bytes)),
+        })
+    }
+
+    pub fn id(&self) -> KeyId {
+        self.id
+    }
+}
+
+#[derive(Clone, Copy)]
+struct FileHeader {
+    flags: u8,
+    key_id: KeyId,
+    nonce_prefix: [u8; NONCE_PREFIX_LEN],
+}
+
+impl FileHeader {
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+        bytes.push(self.flags);
+        bytes.extend_from_slice(&self.key_id.0);
+        bytes.extend_from_slice(&self.nonce_prefix);
+        bytes
+    }
+
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() == HEADER_LEN && bytes.starts_with(MAGIC),
+            "Not a valid codec header.",
+        );
+        let version = bytes[MAGIC.len()];
+        ensure!(
+            version == FORMAT_VERSION,
+            "Unsupported codec format version {}.",
+            version
+        );
+        let flags = bytes[MAGIC.len() + 1];
+        ensure!(
+            flags & !(FLAG_ZSTD | FLAG_AES_256_GCM) == 0,
+            "Unknown codec flags {:#b}.",
+            flags
+        );
+        let key_id_start = MAGIC.len() + 2;
+        let nonce_start = key_id_start + KEY_ID_LEN;
+        Ok(Self {
+            flags,
+            key_id: KeyId(bytes[key_id_start..nonce_start].try_into()?),
+            nonce_prefix: bytes[nonce_start..].try_into()?,
+        })
+    }
+}
+
+struct Codec {
+    header: FileHeader,
+    compression_level: i32,
+    key: Option<Arc<EncryptionKey>>,
+}
+
+impl Codec {
+    fn nonce(&self, frame_index: u32) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[..NONCE_PREFIX_LEN].copy_from_slice(&self.header.nonce_prefix);
+        nonce[NONCE_PREFIX_LEN..].copy_from_slice(&frame_index.to_be_bytes());
+        nonce
+    }
+
+    /// Data authenticated along with a frame: the file header, so that tampering with the codec
+    /// flags or the key id fails decryption, then the index of the frame and the last frame flag.
+    fn aad(&self, frame_index: u32, is_last: bool) -> Vec<u8> {
+        let mut aad = self.header.to_bytes();
+        aad.extend_from_slice(&frame_index.to_be_bytes());
+        aad.push(is_last as u8);
+        aad
+    }
+
+    fn key(&self) -> Result<&EncryptionKey> {
+        self.key
+            .as_deref()
+            .ok_or_else(|| format_err!("Encryption key is not provided."))
+    }
+
+    /// Appends the encoded frame to `out`.
+    fn seal_frame(
+        &self,
+        frame_index: u32,
+        is_last: bool,
+        plaintext: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<()> {
+        let mut payload = if self.header.flags & FLAG_ZSTD != 0 {
+            zstd::bulk::compress(plaintext, self.compression_level)?
+        } else {
+            plaintext.to_vec()
+        };
+        if self.header.flags & FLAG_AES_256_GCM != 0 {
+            payload = self
+                .key()?
+                .cipher
+                .encrypt(
+                    Nonce::from_slice(&self.nonce(frame_index)),
+                    Payload {
+                        msg: &payload,
+                        aad: &self.aad(frame_index, is_last),
+                    },
+                )
+                .map_err(|_| format_err!("Failed to encrypt backup frame {}.", frame_index))?;
+        }
+        out.push(is_last as u8);
+        out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        out.extend_from_slice(&payload);
+        Ok(())
+    }
+
+    fn open_frame(&self, frame_index: u32, is_last: bool, payload: &[u8]) -> Result<Vec<u8>> {
+        let mut plaintext = if self.header.flags & FLAG_AES_256_GCM != 0 {
+            self.key()?
+                .cipher
+                .decrypt(
+                    Nonce::from_slice(&self.nonce(frame_index)),
+                    Payload {
+                        msg: payload,
+                        aad: &self.aad(frame_index, is_last),
+                    },
+                )
+                .map_err(|_| {
+                    format_err!(
+                        "Failed to decrypt backup frame {}, the file is corrupted or tampered with.",
+                        frame_index
+                    )
+                })?
+        } else {
+            payload.to_vec()
+        };
+        if self.header.flags & FLAG_ZSTD != 0 {
+            plaintext = zstd::bulk::decompress(&plaintext, FRAME_SIZE)?;
+        }
+        Ok(plaintext)
+    }
+}
+
+/// Wraps a `BackupStorage`, encoding files on write and decoding them on read. See module doc.
+pub struct CodecStorage {
+    inner: Arc<dyn BackupStorage>,
+    compression_level: Option<i32>,
+    key: Option<Arc<EncryptionKey>>,
+}
+
+impl CodecStorage {
+    pub fn new(
+        inner: Arc<dyn BackupStorage>,
+        compression_level: Option<i32>,
+        key: Option<EncryptionKey>,
+    ) -> Self {
+        Self {
+            inner,
+            compression_level,
+            key: key.map(Arc::new),
+        }
+    }
+
+    fn is_plain(&self) -> bool {
+        self.compression_level.is_none() && self.key.is_none()
+    }
+
+    fn codec_for_write(&self) -> Codec {
+        let mut flags = 0;
+        if self.compression_level.is_some() {
+            flags |= FLAG_ZSTD;
+        }
+        if self.key.is_some() {
+            flags |= FLAG_AES_256_GCM;
+        }
+        Codec {
+            header: FileHeader {
+                flags,
+                key_id: self.key.as_ref().map_or(KeyId::NONE, |k| k.id()),
+                nonce_prefix: rand::random(),
+            },
+            compression_level: self.compression_level.unwrap_or_default(),
+            key: self.key.clone(),
+        }
+    }
+
+    fn codec_for_read(&self, header: FileHeader) -> Result<Codec> {
+        if header.flags & FLAG_AES_256_GCM != 0 {
+            match &self.key {
+                None => bail!(
+                    "Backup file is encrypted with key id {}, but no encryption key is provided.",
+                    header.key_id,
+                ),
+                Some(key) => ensure!(
+                    key.id() == header.key_id,
+                    "Backup file is encrypted with key id {}, but the provided key has id {}.",
+                    header.key_id,
+                    key.id(),
+                ),
+            }
+        }
+        Ok(Codec {
+            header,
+            compression_level: 0,
+            key: self.key.clone(),
+        })
+    }
+
+    fn encode_bytes(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let codec = self.codec_for_write();
+        let mut out = codec.header.to_bytes();
+        let mut chunks = plaintext.chunks(FRAME_SIZE).peekable();
+        let mut frame_index = 0u32;
+        loop {
+            let chunk = chunks.next().unwrap_or(&[]);
+            let is_last = chunks.peek().is_none();
+            codec.seal_frame(frame_index, is_last, chunk, &mut out)?;
+            if is_last {
+                break;
+            }
+            frame_index += 1;
+        }
+        Ok(out)
+    }
+}
+
+/// Reads until `buf` is full or EOF is reached, returning the number of bytes read.
+async fn read_up_to<R: AsyncRead + Unpin + ?Sized>(
+    reader: &mut R,
+    buf: &mut [u8],
+) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+#[async_trait]
+impl BackupStorage for CodecStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.inner.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let (file_handle, file) = self.inner.create_for_write(backup_handle, name).await?;
+        if self.is_plain() {
+            return Ok((file_handle, file));
+        }
+        Ok((
+            file_handle,
+            Box::new(EncodingWriter::new(file, self.codec_for_write())),
+        ))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let mut file = self.inner.open_for_read(file_handle).await?;
+        let mut prefix = vec![0u8; HEADER_LEN];
+        let len = read_up_to(&mut file, &mut prefix)
+            .await
+            .err_notes(file_handle)?;
+        prefix.truncate(len);
+
+        if prefix.starts_with(MAGIC) {
+            let codec = self
+                .codec_for_read(FileHeader::parse(&prefix)?)
+                .err_notes(file_handle)?;
+            Ok(Box::new(DecodingReader::new(file, codec)))
+        } else if prefix.starts_with(TEXT_MAGIC.as_bytes()) {
+            let mut text = prefix;
+            file.read_to_end(&mut text).await.err_notes(file_handle)?;
+            let encoded =
+                base64::decode(std::str::from_utf8(&text[TEXT_MAGIC.len()..])?.trim_end())?;
+            ensure!(
+                encoded.len() >= HEADER_LEN,
+                "Encoded metadata file {} is truncated.",
+                file_handle,
+            );
+            let codec = self
+                .codec_for_read(FileHeader::parse(&encoded[..HEADER_LEN])?)
+                .err_notes(file_handle)?;
+            let frames = Cursor::new(encoded[HEADER_LEN..].to_vec());
+            Ok(Box::new(DecodingReader::new(Box::new(frames), codec)))
+        } else {
+            // Not encoded, hand back what's been consumed followed by the rest of the file.
+            Ok(Box::new(Cursor::new(prefix).chain(file)))
+        }
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.inner.list_metadata_files().await
+    }
+
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.backup_metadata_file(file_handle).await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        if self.is_plain() {
+            return self.inner.save_metadata_lines(name, lines).await;
+        }
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        let encoded = self.encode_bytes(content.as_bytes())?;
+        let line = TextLine::new(&format!("{}{}", TEXT_MAGIC, base64::encode(encoded)))?;
+        self.inner.save_metadata_lines(name, &[line]).await
+    }
+}
+
+fn to_io_error(e: anyhow::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// Buffers plaintext into frames, and writes encoded frames into the inner writer.
+///
+/// Notice that `flush()` doesn't force a partial frame out, only `shutdown()` does, which seals
+/// the last frame. Not shutting down the writer results in a truncated file that fails to decode.
+struct EncodingWriter {
+    inner: Box<dyn AsyncWrite + Send + Unpin>,
+    codec: Codec,
+    next_frame: u32,
+    plaintext: Vec<u8>,
+    /// Encoded bytes yet to be written to `inner`, starting from `encoded_pos`.
+    encoded: Vec<u8>,
+    encoded_pos: usize,
+    finished: bool,
+}
+
+impl EncodingWriter {
+    fn new(inner: Box<dyn AsyncWrite + Send + Unpin>, codec: Codec) -> Self {
+        let encoded = codec.header.to_bytes();
+        Self {
+            inner,
+            codec,
+            next_frame: 0,
+            plaintext: Vec::with_capacity(FRAME_SIZE),
+            encoded,
+            encoded_pos: 0,
+            finished: false,
+        }
+    }
+
+    fn seal_frame(&mut self, is_last: bool) -> io::Result<()> {
+        let frame_index = self.next_frame;
+        self.next_frame = self.next_frame.checked_add(1).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "Too many frames in one backup file.")
+        })?;
+        self.codec
+            .seal_frame(frame_index, is_last, &self.plaintext, &mut self.encoded)
+            .map_err(to_io_error)?;
+        self.plaintext.clear();
+        Ok(())
+    }
+
+    fn poll_write_encoded(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.encoded_pos < self.encoded.len() {
+            let n = futures::ready!(
+                Pin::new(&mut self.inner).poll_write(cx, &self.encoded[self.encoded_pos..])
+            )?;
+            if n == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.encoded_pos += n;
+        }
+        self.encoded.clear();
+        self.encoded_pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for EncodingWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.finished {
+            return Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Writing to a shut down backup file.",
+            )));
+        }
+        futures::ready!(this.poll_write_encoded(cx))?;
+
+        let n = std::cmp::min(buf.len(), FRAME_SIZE - this.plaintext.len());
+        this.plaintext.extend_from_slice(&buf[..n]);
+        if this.plaintext.len() == FRAME_SIZE {
+            this.seal_frame(false)?;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        futures::ready!(this.poll_write_encoded(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        futures::ready!(this.poll_write_encoded(cx))?;
+        if !this.finished {
+            this.seal_frame(true)?;
+            this.finished = true;
+            futures::ready!(this.poll_write_encoded(cx))?;
+        }
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// Reads encoded frames from the inner reader (positioned right after the file header) and
+/// yields the decoded plaintext.
+struct DecodingReader {
+    inner: Box<dyn AsyncRead + Send + Unpin>,
+    codec: Codec,
+    next_frame: u32,
+    /// Bytes read from `inner` but not yet decoded.
+    raw: Vec<u8>,
+    inner_eof: bool,
+    plaintext: Vec<u8>,
+    plaintext_pos: usize,
+    finished: bool,
+}
+
+impl DecodingReader {
+    fn new(inner: Box<dyn AsyncRead + Send + Unpin>, codec: Codec) -> Self {
+        Self {
+            inner,
+            codec,
+            next_frame: 0,
+            raw: Vec::new(),
+            inner_eof: false,
+            plaintext: Vec::new(),
+            plaintext_pos: 0,
+            finished: false,
+        }
+    }
+
+    /// Decodes the next frame if it's been fully read from `inner`, returns false otherwise.
+    fn try_open_frame(&mut self) -> Result<bool> {
+        if self.raw.len() < FRAME_HEADER_LEN {
+            return Ok(false);
+        }
+        let is_last = match self.raw[0] {
+            0 => false,
+            1 => true,
+            flag => bail!("Invalid frame flag {}.", flag),
+        };
+        let len = u32::from_be_bytes(self.raw[1..FRAME_HEADER_LEN].try_into()?) as usize;
+        ensure!(
+            len <= MAX_ENCODED_FRAME_SIZE,
+            "Frame length {} exceeds the limit.",
+            len
+        );
+        if self.raw.len() < FRAME_HEADER_LEN + len {
+            return Ok(false);
+        }
+
+        self.plaintext = self.codec.open_frame(
+            self.next_frame,
+            is_last,
+            &self.raw[FRAME_HEADER_LEN..][..len],
+        )?;
+        self.plaintext_pos = 0;
+        self.raw.drain(..FRAME_HEADER_LEN + len);
+        self.next_frame += 1;
+        if is_last {
+            ensure!(
+                self.raw.is_empty(),
+                "Unexpected trailing bytes after the last frame."
+            );
+            self.finished = true;
+        }
+        Ok(true)
+    }
+}
+
+impl AsyncRead for DecodingReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.plaintext_pos < this.plaintext.len() {
+                let n = std::cmp::min(buf.remaining(), this.plaintext.len() - this.plaintext_pos);
+                buf.put_slice(&this.plaintext[this.plaintext_pos..this.plaintext_pos + n]);
+                this.plaintext_pos += n;
+                return Poll::Ready(Ok(()));
+            }
+            if this.finished {
+                return Poll::Ready(Ok(()));
+            }
+            if this.try_open_frame().map_err(to_io_error)? {
+                continue;
+            }
+            if this.inner_eof {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Encoded backup file is truncated.",
+                )));
+            }
+
+            let mut chunk = [0u8; 64 * 1024];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            futures::ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+            if chunk_buf.filled().is_empty() {
+                this.inner_eof = true;
+            } else {
+                this.raw.extend_from_slice(chunk_buf.filled());
+            }
+        }
+    }
+}
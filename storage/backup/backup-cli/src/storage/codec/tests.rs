@@ -0,0 +1,187 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    local_fs::LocalFs,
+    test_util::{
+        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+        test_write_and_read_impl,
+    },
+};
+use aptos_temppath::TempPath;
+use proptest::prelude::*;
+use tokio::{io::AsyncWriteExt, runtime::Runtime};
+
+fn key(seed: u8) -> EncryptionKey {
+    EncryptionKey::from_bytes(&[seed; 32]).unwrap()
+}
+
+fn codec_store(
+    local_fs: Arc<dyn BackupStorage>,
+    compress: bool,
+    key: Option<EncryptionKey>,
+) -> CodecStorage {
+    CodecStorage::new(local_fs, if compress { Some(3) } else { None }, key)
+}
+
+async fn write_file(store: &dyn BackupStorage, name: &str, content: &[u8]) -> FileHandle {
+    let backup_handle = store
+        .create_backup(&"backup".parse().unwrap())
+        .await
+        .unwrap();
+    let (file_handle, mut file) = store
+        .create_for_write(&backup_handle, &name.parse().unwrap())
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    file_handle
+}
+
+async fn read_file(store: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+    Ok(buf)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups(),
+        compress in any::<bool>(),
+        encrypt in any::<bool>(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let local_fs = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+        let store = codec_store(local_fs, compress, if encrypt { Some(key(1)) } else { None });
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+        compress in any::<bool>(),
+        encrypt in any::<bool>(),
+    ) {
+        let tmpdir = TempPath::new();
+        tmpdir.create_as_dir().unwrap();
+        let local_fs = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+        let store = codec_store(local_fs, compress, if encrypt { Some(key(1)) } else { None });
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+}
+
+#[tokio::test]
+async fn test_multi_frame_file() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let local_fs: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+    let store = codec_store(local_fs.clone(), true, Some(key(1)));
+
+    let content = (0..FRAME_SIZE * 2 + 7)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+    let file_handle = write_file(&store, "multi_frame", &content).await;
+
+    assert_eq!(read_file(&store, &file_handle).await.unwrap(), content);
+    // Raw content on the underlying storage is encoded.
+    let raw = read_file(local_fs.as_ref(), &file_handle).await.unwrap();
+    assert!(raw.starts_with(MAGIC));
+    assert!(raw.len() < content.len());
+}
+
+#[tokio::test]
+async fn test_read_plain_file() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let local_fs: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+    let file_handle = write_file(local_fs.as_ref(), "plain", b"plain").await;
+
+    let store = codec_store(local_fs, true, Some(key(1)));
+    assert_eq!(read_file(&store, &file_handle).await.unwrap(), b"plain");
+}
+
+#[tokio::test]
+async fn test_wrong_or_missing_key() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let local_fs: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+    let file_handle = write_file(
+        &codec_store(local_fs.clone(), false, Some(key(1))),
+        "encrypted",
+        b"secret",
+    )
+    .await;
+
+    assert!(
+        read_file(&codec_store(local_fs.clone(), false, None), &file_handle)
+            .await
+            .is_err()
+    );
+    assert!(read_file(
+        &codec_store(local_fs.clone(), false, Some(key(2))),
+        &file_handle
+    )
+    .await
+    .is_err());
+    assert_eq!(
+        read_file(&codec_store(local_fs, false, Some(key(1))), &file_handle)
+            .await
+            .unwrap(),
+        b"secret"
+    );
+}
+
+#[tokio::test]
+async fn test_truncated_file() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let local_fs: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+    let store = codec_store(local_fs.clone(), true, Some(key(1)));
+    let content = vec![7u8; FRAME_SIZE + 1];
+    let file_handle = write_file(&store, "truncated", &content).await;
+
+    let path = tmpdir.path().join(&file_handle);
+    let raw = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &raw[..raw.len() - 1]).unwrap();
+
+    assert!(read_file(&store, &file_handle).await.is_err());
+}
+
+#[tokio::test]
+async fn test_tampered_header() {
+    let tmpdir = TempPath::new();
+    tmpdir.create_as_dir().unwrap();
+    let local_fs: Arc<dyn BackupStorage> = Arc::new(LocalFs::new(tmpdir.path().to_path_buf()));
+
+    for compress in [false, true] {
+        let store = codec_store(local_fs.clone(), compress, Some(key(1)));
+        let name = if compress {
+            "compressed"
+        } else {
+            "uncompressed"
+        };
+        let file_handle = write_file(&store, name, b"secret").await;
+        assert_eq!(read_file(&store, &file_handle).await.unwrap(), b"secret");
+
+        // Flipping the compression flag leaves a valid header, which fails authentication.
+        let path = tmpdir.path().join(&file_handle);
+        let mut raw = std::fs::read(&path).unwrap();
+        raw[MAGIC.len() + 1] ^= FLAG_ZSTD;
+        std::fs::write(&path, &raw).unwrap();
+
+        assert!(read_file(&store, &file_handle).await.is_err());
+    }
+}
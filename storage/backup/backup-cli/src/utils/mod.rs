@@ -238,6 +238,25 @@ impl RestoreRunMode {
         }
     }
 
+    pub fn apply_state_diff(
+        &self,
+        base_version: Version,
+        version: Version,
+        expected_root_hash: HashValue,
+        updates: Vec<(StateKey, Option<StateValue>)>,
+    ) -> Result<()> {
+        match self {
+            Self::Restore { restore_handler } => restore_handler.apply_state_diff(
+                base_version,
+                version,
+                expected_root_hash,
+                updates,
+            ),
+            // Without the base state, there's nothing to apply the diff to and check against.
+            Self::Verify => Ok(()),
+        }
+    }
+
     pub fn get_next_expected_transaction_version(&self) -> Result<Version> {
         match self {
             RestoreRunMode::Restore { restore_handler } => {
@@ -5,7 +5,9 @@
 use aptos_backup_service::start_backup_service;
 use aptos_config::utils::get_available_port;
 use aptos_db::{
-    test_helper::{arb_blocks_to_commit, update_in_memory_state},
+    test_helper::{
+        arb_blocks_to_commit, arb_blocks_to_commit_with_block_nums, update_in_memory_state,
+    },
     AptosDB,
 };
 use aptos_proptest_helpers::ValueGenerator;
@@ -32,12 +34,34 @@ pub fn tmp_db_with_random_content() -> (
     TempPath,
     Arc<AptosDB>,
     Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    tmp_db_with_blocks(ValueGenerator::new().generate(arb_blocks_to_commit()))
+}
+
+/// Same as `tmp_db_with_random_content`, with a number of blocks in the given range.
+pub fn tmp_db_with_random_blocks(
+    min_blocks: usize,
+    max_blocks: usize,
+) -> (
+    TempPath,
+    Arc<AptosDB>,
+    Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) {
+    let blocks = arb_blocks_to_commit_with_block_nums(min_blocks, max_blocks);
+    tmp_db_with_blocks(ValueGenerator::new().generate(blocks))
+}
+
+fn tmp_db_with_blocks(
+    blocks: Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
+) -> (
+    TempPath,
+    Arc<AptosDB>,
+    Vec<(Vec<TransactionToCommit>, LedgerInfoWithSignatures)>,
 ) {
     let (tmpdir, db) = tmp_db_empty();
     let mut cur_ver: Version = 0;
     let mut in_memory_state = db.buffered_state().lock().current_state().clone();
     let _ancestor = in_memory_state.base.clone();
-    let blocks = ValueGenerator::new().generate(arb_blocks_to_commit());
     for (txns_to_commit, ledger_info_with_sigs) in &blocks {
         update_in_memory_state(&mut in_memory_state, txns_to_commit.as_slice());
         db.save_transactions(
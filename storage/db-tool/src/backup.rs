@@ -6,6 +6,9 @@ use anyhow::Result;
 use aptos_backup_cli::{
     backup_types::{
         epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        incremental_state_snapshot::backup::{
+            IncrementalStateSnapshotBackupController, IncrementalStateSnapshotBackupOpt,
+        },
         state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
@@ -104,6 +107,12 @@ enum BackupType {
         #[clap[flatten]]
         storage: DBToolStorageOpt,
     },
+    IncrementalStateSnapshot {
+        #[clap(flatten)]
+        opt: IncrementalStateSnapshotBackupOpt,
+        #[clap[flatten]]
+        storage: DBToolStorageOpt,
+    },
 }
 
 #[derive(Parser)]
@@ -203,6 +212,16 @@ impl Command {
                         .run()
                         .await?;
                     },
+                    BackupType::IncrementalStateSnapshot { opt, storage } => {
+                        IncrementalStateSnapshotBackupController::new(
+                            opt,
+                            global_opt,
+                            client,
+                            storage.init_storage().await?,
+                        )
+                        .run()
+                        .await?;
+                    },
                 }
             },
             Command::Continuously(opt) => {
@@ -5,10 +5,18 @@ use anyhow::Result;
 use aptos_backup_cli::{
     backup_types::{
         epoch_ending::restore::{EpochEndingRestoreController, EpochEndingRestoreOpt},
+        incremental_state_snapshot::restore::{
+            IncrementalStateSnapshotRestoreController, IncrementalStateSnapshotRestoreOpt,
+        },
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::{TransactionRestoreController, TransactionRestoreOpt},
     },
-    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    coordinators::{
+        incremental_state_restore::{
+            IncrementalStateRestoreCoordinator, IncrementalStateRestoreCoordinatorOpt,
+        },
+        restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    },
     storage::DBToolStorageOpt,
     utils::GlobalRestoreOpt,
 };
@@ -20,6 +28,11 @@ use clap::{Parser, Subcommand};
 pub enum Command {
     #[clap(about = "run continuously to restore the DB")]
     BootstrapDB(BootstrapDB),
+    #[clap(
+        about = "restore the state of the DB from a full state snapshot and the incremental \
+        state snapshots on top of it, without replaying transactions"
+    )]
+    BootstrapState(BootstrapState),
     #[clap(subcommand)]
     Oneoff(Oneoff),
 }
@@ -34,6 +47,16 @@ pub struct BootstrapDB {
     global: GlobalRestoreOpt,
 }
 
+#[derive(Parser)]
+pub struct BootstrapState {
+    #[clap(flatten)]
+    storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    opt: IncrementalStateRestoreCoordinatorOpt,
+    #[clap(flatten)]
+    global: GlobalRestoreOpt,
+}
+
 #[derive(Parser)]
 pub enum Oneoff {
     EpochEnding {
@@ -60,6 +83,14 @@ pub enum Oneoff {
         #[clap(flatten)]
         global: GlobalRestoreOpt,
     },
+    IncrementalStateSnapshot {
+        #[clap(flatten)]
+        storage: DBToolStorageOpt,
+        #[clap(flatten)]
+        opt: IncrementalStateSnapshotRestoreOpt,
+        #[clap(flatten)]
+        global: GlobalRestoreOpt,
+    },
 }
 
 impl Command {
@@ -109,6 +140,20 @@ impl Command {
                         .run()
                         .await?;
                     },
+                    Oneoff::IncrementalStateSnapshot {
+                        storage,
+                        opt,
+                        global,
+                    } => {
+                        IncrementalStateSnapshotRestoreController::new(
+                            opt,
+                            global.try_into()?,
+                            storage.init_storage().await?,
+                            None, /* epoch_history */
+                        )
+                        .run()
+                        .await?;
+                    },
                 }
             },
             Command::BootstrapDB(bootstrap) => {
@@ -120,6 +165,15 @@ impl Command {
                 .run()
                 .await?;
             },
+            Command::BootstrapState(bootstrap) => {
+                IncrementalStateRestoreCoordinator::new(
+                    bootstrap.opt,
+                    bootstrap.global.try_into()?,
+                    bootstrap.storage.init_storage().await?,
+                )
+                .run()
+                .await?;
+            },
         }
 
         Ok(())
@@ -9,18 +9,20 @@ use crate::{
     db::INDEX_DB_NAME,
     metadata::{MetadataKey, MetadataValue},
     schema::{
-        column_families, indexer_metadata::IndexerMetadataSchema, table_info::TableInfoSchema,
+        column_families, event_by_type::EventByTypeSchema, indexer_metadata::IndexerMetadataSchema,
+        table_info::TableInfoSchema,
     },
 };
 use anyhow::{bail, ensure, Result};
 use aptos_config::config::RocksdbConfig;
 use aptos_logger::warn;
 use aptos_rocksdb_options::gen_rocksdb_options;
-use aptos_schemadb::{SchemaBatch, DB};
+use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
 use aptos_storage_interface::{state_view::DbStateView, DbReader};
 use aptos_types::{
     access_path::Path,
     account_address::AccountAddress,
+    contract_event::ContractEvent,
     state_store::{
         state_key::{StateKey, StateKeyInner},
         table::{TableHandle, TableInfo},
@@ -44,6 +46,9 @@ use std::{
 pub struct Indexer {
     db: DB,
     next_version: AtomicVersion,
+    /// Events are indexed by type separately, as they were added to an existing index and
+    /// must not be indexed twice.
+    next_event_version: AtomicVersion,
 }
 
 impl Indexer {
@@ -63,10 +68,14 @@ impl Indexer {
         let next_version = db
             .get::<IndexerMetadataSchema>(&MetadataKey::LatestVersion)?
             .map_or(0, |v| v.expect_version());
+        let next_event_version = db
+            .get::<IndexerMetadataSchema>(&MetadataKey::EventByTypeNextVersion)?
+            .map_or(0, |v| v.expect_version());
 
         Ok(Self {
             db,
             next_version: AtomicVersion::new(next_version),
+            next_event_version: AtomicVersion::new(next_event_version),
         })
     }
 
@@ -75,6 +84,7 @@ impl Indexer {
         db_reader: Arc<dyn DbReader>,
         first_version: Version,
         write_sets: &[&WriteSet],
+        events: &[&[ContractEvent]],
     ) -> Result<()> {
         let last_version = first_version + write_sets.len() as Version;
         let state_view = DbStateView {
@@ -83,7 +93,7 @@ impl Indexer {
         };
         let resolver = state_view.as_move_resolver();
         let annotator = MoveValueAnnotator::new(&resolver);
-        self.index_with_annotator(&annotator, first_version, write_sets)
+        self.index_with_annotator(&annotator, first_version, write_sets, events)
     }
 
     pub fn index_with_annotator(
@@ -91,40 +101,70 @@ impl Indexer {
         annotator: &MoveValueAnnotator<StorageAdapter<DbStateView>>,
         first_version: Version,
         write_sets: &[&WriteSet],
+        events: &[&[ContractEvent]],
     ) -> Result<()> {
+        ensure!(
+            write_sets.len() == events.len(),
+            "Expecting the events of each transaction. Got {} write sets, {} event lists.",
+            write_sets.len(),
+            events.len(),
+        );
         let next_version = self.next_version();
+        let next_event_version = self.next_event_version();
+        let expected_version = std::cmp::min(next_version, next_event_version);
         ensure!(
-            first_version <= next_version,
+            first_version <= expected_version,
             "Indexer expects to see continuous transaction versions. Expecting: {}, got: {}",
-            next_version,
+            expected_version,
             first_version,
         );
         let end_version = first_version + write_sets.len() as Version;
-        if end_version <= next_version {
+        if end_version <= expected_version {
             warn!(
                 "Seeing old transactions. Expecting version: {}, got {} transactions starting from version {}.",
-                next_version,
+                expected_version,
                 write_sets.len(),
                 first_version,
             );
             return Ok(());
         }
 
-        let mut table_info_parser = TableInfoParser::new(self, annotator);
-        for write_set in write_sets {
-            for (state_key, write_op) in write_set.iter() {
-                table_info_parser.parse_write_op(state_key, write_op)?;
+        let mut batch = SchemaBatch::new();
+        if end_version > next_version {
+            let mut table_info_parser = TableInfoParser::new(self, annotator);
+            for write_set in write_sets {
+                for (state_key, write_op) in write_set.iter() {
+                    table_info_parser.parse_write_op(state_key, write_op)?;
+                }
             }
+            table_info_parser.finish(&mut batch)?;
+            batch.put::<IndexerMetadataSchema>(
+                &MetadataKey::LatestVersion,
+                &MetadataValue::Version(end_version - 1),
+            )?;
+        }
+
+        // Unlike table infos, events must not be indexed twice as they get sequence numbers.
+        if end_version > next_event_version {
+            let mut event_indexer = EventByTypeIndexer::new(self);
+            let num_indexed = (next_event_version - first_version) as usize;
+            for (version, events) in (next_event_version..).zip(&events[num_indexed..]) {
+                event_indexer.index_events(version, events)?;
+            }
+            event_indexer.finish(&mut batch)?;
+            batch.put::<IndexerMetadataSchema>(
+                &MetadataKey::EventByTypeNextVersion,
+                &MetadataValue::Version(end_version),
+            )?;
         }
 
-        let mut batch = SchemaBatch::new();
-        table_info_parser.finish(&mut batch)?;
-        batch.put::<IndexerMetadataSchema>(
-            &MetadataKey::LatestVersion,
-            &MetadataValue::Version(end_version - 1),
-        )?;
         self.db.write_schemas(batch)?;
-        self.next_version.store(end_version, Ordering::Relaxed);
+        self.next_version
+            .store(std::cmp::max(next_version, end_version), Ordering::Relaxed);
+        self.next_event_version.store(
+            std::cmp::max(next_event_version, end_version),
+            Ordering::Relaxed,
+        );
 
         Ok(())
     }
@@ -133,9 +173,133 @@ impl Indexer {
         self.next_version.load(Ordering::Relaxed)
     }
 
+    /// Returns the next version whose events are to be indexed by type. It is behind
+    /// [`Indexer::next_version`] when the index of events by type is still catching up.
+    pub fn next_event_version(&self) -> Version {
+        self.next_event_version.load(Ordering::Relaxed)
+    }
+
+    /// Starts indexing events by type at `version`, for when the events of the versions
+    /// before it can't be indexed anymore, as they were pruned.
+    pub fn skip_events_before(&self, version: Version) -> Result<()> {
+        if version > self.next_event_version() {
+            self.db.put::<IndexerMetadataSchema>(
+                &MetadataKey::EventByTypeNextVersion,
+                &MetadataValue::Version(version),
+            )?;
+            self.next_event_version.store(version, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
     pub fn get_table_info(&self, handle: TableHandle) -> Result<Option<TableInfo>> {
         self.db.get::<TableInfoSchema>(&handle)
     }
+
+    /// Returns the sequence number of the latest event of the type emitted at or before
+    /// `ledger_version`, among the events of that type.
+    pub fn get_latest_event_sequence_number_by_type(
+        &self,
+        event_type: &TypeTag,
+        ledger_version: Version,
+    ) -> Result<Option<u64>> {
+        let mut iter = self
+            .db
+            .rev_iter::<EventByTypeSchema>(ReadOptions::default())?;
+        iter.seek_for_prev(&(event_type.clone(), u64::max_value()))?;
+        for res in iter {
+            let ((type_tag, seq), (version, _idx)) = res?;
+            if &type_tag != event_type {
+                break;
+            }
+            if version <= ledger_version {
+                return Ok(Some(seq));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Given `event_type` and `start_seq_num`, returns events identified by transaction version
+    /// and index among all events emitted by the same transaction. Result won't contain records
+    /// with a transaction version > `ledger_version` and is in ascending order.
+    pub fn lookup_events_by_type(
+        &self,
+        event_type: &TypeTag,
+        start_seq_num: u64,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<
+        Vec<(
+            u64,     // sequence number among events of the type
+            Version, // transaction version it belongs to
+            u64,     // index among events for the same transaction
+        )>,
+    > {
+        let mut iter = self.db.iter::<EventByTypeSchema>(ReadOptions::default())?;
+        iter.seek(&(event_type.clone(), start_seq_num))?;
+
+        let mut result = Vec::new();
+        let mut cur_seq = start_seq_num;
+        for res in iter.take(limit as usize) {
+            let ((type_tag, seq), (ver, idx)) = res?;
+            if &type_tag != event_type || ver > ledger_version {
+                break;
+            }
+            ensure!(
+                seq == cur_seq,
+                "DB corruption: Sequence number not continuous. expected: {}, actual: {}",
+                cur_seq,
+                seq
+            );
+            result.push((seq, ver, idx));
+            cur_seq += 1;
+        }
+
+        Ok(result)
+    }
+
+    fn next_event_sequence_number_by_type(&self, event_type: &TypeTag) -> Result<u64> {
+        Ok(self
+            .get_latest_event_sequence_number_by_type(event_type, Version::max_value())?
+            .map_or(0, |seq| seq + 1))
+    }
+}
+
+/// Assigns the events of each type consecutive sequence numbers, in the order they are emitted.
+struct EventByTypeIndexer<'a> {
+    indexer: &'a Indexer,
+    next_seq_nums: HashMap<TypeTag, u64>,
+    entries: Vec<((TypeTag, u64), (Version, u64))>,
+}
+
+impl<'a> EventByTypeIndexer<'a> {
+    fn new(indexer: &'a Indexer) -> Self {
+        Self {
+            indexer,
+            next_seq_nums: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    fn index_events(&mut self, version: Version, events: &[ContractEvent]) -> Result<()> {
+        for (idx, event) in events.iter().enumerate() {
+            let type_tag = event.type_tag();
+            let seq = match self.next_seq_nums.get(type_tag) {
+                Some(next_seq) => *next_seq,
+                None => self.indexer.next_event_sequence_number_by_type(type_tag)?,
+            };
+            self.next_seq_nums.insert(type_tag.clone(), seq + 1);
+            self.entries
+                .push(((type_tag.clone(), seq), (version, idx as u64)));
+        }
+        Ok(())
+    }
+
+    fn finish(self, batch: &mut SchemaBatch) -> Result<()> {
+        self.entries
+            .iter()
+            .try_for_each(|(key, value)| batch.put::<EventByTypeSchema>(key, value))
+    }
 }
 
 struct TableInfoParser<'a> {
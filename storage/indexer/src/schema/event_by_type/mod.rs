@@ -0,0 +1,87 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module defines physical storage schema for an event index via which a ContractEvent (
+//! represented by a <txn_version, event_idx> tuple so that it can be fetched from the ledger DB)
+//! can be found by <type_tag, seq_num> tuple, where seq_num counts the events of the type.
+//!
+//! ```text
+//! |<-------key------->|<----value---->|
+//! | type_tag | seq_num | txn_ver | idx |
+//! ```
+//!
+//! The type tag is BCS encoded, which is prefix free, so the events of a type are contiguous.
+
+use crate::schema::EVENT_BY_TYPE_CF_NAME;
+use anyhow::{ensure, Result};
+use aptos_schemadb::{
+    define_schema,
+    schema::{KeyCodec, ValueCodec},
+};
+use aptos_types::transaction::Version;
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use move_core_types::language_storage::TypeTag;
+use std::mem::size_of;
+
+define_schema!(EventByTypeSchema, Key, Value, EVENT_BY_TYPE_CF_NAME);
+
+type SeqNum = u64;
+type Key = (TypeTag, SeqNum);
+
+type Index = u64;
+type Value = (Version, Index);
+
+impl KeyCodec<EventByTypeSchema> for Key {
+    fn encode_key(&self) -> Result<Vec<u8>> {
+        let (ref type_tag, seq_num) = *self;
+
+        let mut encoded = bcs::to_bytes(type_tag)?;
+        encoded.write_u64::<BigEndian>(seq_num)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_key(data: &[u8]) -> Result<Self> {
+        const SEQ_NUM_SIZE: usize = size_of::<SeqNum>();
+        ensure!(
+            data.len() > SEQ_NUM_SIZE,
+            "Unexpected key length: {}",
+            data.len()
+        );
+
+        let type_tag_len = data.len() - SEQ_NUM_SIZE;
+        let type_tag = bcs::from_bytes(&data[..type_tag_len])?;
+        let seq_num = (&data[type_tag_len..]).read_u64::<BigEndian>()?;
+
+        Ok((type_tag, seq_num))
+    }
+}
+
+impl ValueCodec<EventByTypeSchema> for Value {
+    fn encode_value(&self) -> Result<Vec<u8>> {
+        let (version, index) = *self;
+
+        let mut encoded = Vec::with_capacity(size_of::<Version>() + size_of::<Index>());
+        encoded.write_u64::<BigEndian>(version)?;
+        encoded.write_u64::<BigEndian>(index)?;
+
+        Ok(encoded)
+    }
+
+    fn decode_value(data: &[u8]) -> Result<Self> {
+        ensure!(
+            data.len() == size_of::<Self>(),
+            "Unexpected value length: {}",
+            data.len()
+        );
+
+        const VERSION_SIZE: usize = size_of::<Version>();
+        let version = (&data[..VERSION_SIZE]).read_u64::<BigEndian>()?;
+        let index = (&data[VERSION_SIZE..]).read_u64::<BigEndian>()?;
+
+        Ok((version, index))
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,54 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{StateViewId, TStateView};
+use anyhow::Result;
+use aptos_types::state_store::{
+    state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+};
+use std::collections::HashMap;
+
+/// A state view with a set of values layered over a base state view. Reads of an overridden key
+/// return the override, everything else is read from the base view. Used to execute transactions
+/// against hypothetical state, e.g. when simulating.
+pub struct StateViewWithOverrides<S> {
+    base: S,
+    overrides: HashMap<StateKey, StateValue>,
+}
+
+impl<S> StateViewWithOverrides<S> {
+    pub fn new(base: S, overrides: HashMap<StateKey, StateValue>) -> Self {
+        Self { base, overrides }
+    }
+
+    pub fn base(&self) -> &S {
+        &self.base
+    }
+
+    pub fn overrides(&self) -> &HashMap<StateKey, StateValue> {
+        &self.overrides
+    }
+}
+
+impl<S: TStateView<Key = StateKey>> TStateView for StateViewWithOverrides<S> {
+    type Key = StateKey;
+
+    fn id(&self) -> StateViewId {
+        self.base.id()
+    }
+
+    fn get_state_value(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        match self.overrides.get(state_key) {
+            Some(state_value) => Ok(Some(state_value.clone())),
+            None => self.base.get_state_value(state_key),
+        }
+    }
+
+    fn is_genesis(&self) -> bool {
+        self.base.is_genesis()
+    }
+
+    fn get_usage(&self) -> Result<StateStorageUsage> {
+        self.base.get_usage()
+    }
+}
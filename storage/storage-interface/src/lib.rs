@@ -35,6 +35,7 @@ use aptos_types::{
     },
     write_set::WriteSet,
 };
+use move_core_types::language_storage::TypeTag;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use thiserror::Error;
@@ -198,6 +199,20 @@ pub trait DbReader: Send + Sync {
         unimplemented!()
     }
 
+    /// Returns events of the given type from the internal indexer. Like with
+    /// [`DbReader::get_events`], `start` is a sequence number, counting all the events of
+    /// the type.
+    fn get_events_by_type(
+        &self,
+        event_type: &TypeTag,
+        start: u64,
+        order: Order,
+        limit: u64,
+        ledger_version: Version,
+    ) -> Result<Vec<EventWithVersion>> {
+        unimplemented!()
+    }
+
     fn get_transaction_iterator(
         &self,
         start_version: Version,
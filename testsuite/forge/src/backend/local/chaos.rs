@@ -0,0 +1,711 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Network chaos for the local swarm. Every validator dials its peers through its own HTTP CONNECT
+//! proxy (set via `HTTPS_PROXY`, which the network TCP transport honours), so the proxies see each
+//! validator-to-validator connection along with both of its ends. The proxies emulate the chaos
+//! on these connections: they delay, pace or cut the traffic, and model packet loss as the TCP
+//! retransmission delay it would cause.
+
+use crate::{SwarmChaos, SwarmNetworkPartition};
+use anyhow::{anyhow, bail, Result};
+use aptos_infallible::Mutex;
+use aptos_logger::{info, warn};
+use aptos_sdk::types::PeerId;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    runtime::Runtime,
+    sync::{mpsc, watch},
+};
+
+// Maximum size of the CONNECT request sent by the nodes.
+const MAX_CONNECT_REQUEST_SIZE: usize = 4096;
+// Maximum number of bytes read from a connection at once.
+const CHUNK_SIZE: usize = 64 * 1024;
+// Maximum number of chunks in flight per connection direction, i.e., the emulated link buffer.
+const MAX_CHUNKS_IN_FLIGHT: usize = 64;
+// Delay added to a lost chunk, i.e., the minimum TCP retransmission timeout on Linux.
+const RETRANSMISSION_DELAY: Duration = Duration::from_millis(200);
+// Chaos rates are in megabytes per second.
+const BYTES_PER_MEGABYTE: u64 = 1_000_000;
+
+/// The chaos applied to the traffic sent from one validator to another.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct LinkRule {
+    blocked: bool,
+    latency_ms: u64,
+    jitter_ms: u64,
+    delay_correlation_percentage: u64,
+    loss_percentage: u64,
+    loss_correlation_percentage: u64,
+    /// Rate in bytes per second
+    rate: Option<u64>,
+}
+
+impl LinkRule {
+    /// Stacks another rule on top of this one, e.g., when several chaoses are injected.
+    fn merge(&mut self, other: &LinkRule) {
+        self.blocked |= other.blocked;
+        self.latency_ms += other.latency_ms;
+        self.jitter_ms += other.jitter_ms;
+        self.delay_correlation_percentage = self
+            .delay_correlation_percentage
+            .max(other.delay_correlation_percentage);
+        // A chunk goes through if neither of the rules loses it
+        let delivered =
+            (100 - self.loss_percentage.min(100)) * (100 - other.loss_percentage.min(100));
+        self.loss_percentage = 100 - delivered / 100;
+        self.loss_correlation_percentage = self
+            .loss_correlation_percentage
+            .max(other.loss_correlation_percentage);
+        self.rate = match (self.rate, other.rate) {
+            (Some(rate), Some(other_rate)) => Some(rate.min(other_rate)),
+            (rate, other_rate) => rate.or(other_rate),
+        };
+    }
+}
+
+/// The chaos applied to the traffic between the validators.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct LinkRules {
+    links: HashMap<(PeerId, PeerId), LinkRule>,
+    /// Rate in bytes per second of all the traffic sent by a validator
+    egress_rates: HashMap<PeerId, u64>,
+}
+
+impl LinkRules {
+    fn new(chaoses: &HashSet<SwarmChaos>, validators: &[PeerId]) -> Result<Self> {
+        let mut rules = Self::default();
+        for chaos in chaoses {
+            match chaos {
+                SwarmChaos::Delay(delay) => {
+                    for group in &delay.group_network_delays {
+                        rules.add_between(&group.source_nodes, &group.target_nodes, LinkRule {
+                            latency_ms: group.latency_ms,
+                            jitter_ms: group.jitter_ms,
+                            delay_correlation_percentage: group.correlation_percentage,
+                            ..LinkRule::default()
+                        });
+                    }
+                },
+                SwarmChaos::Partition(SwarmNetworkPartition {
+                    partition_percentage,
+                }) => {
+                    // Like the k8s chaos, cut the first validators off from all the others
+                    let num_partitioned =
+                        validators.len() * (*partition_percentage).min(100) as usize / 100;
+                    rules.add_between(&validators[..num_partitioned], validators, LinkRule {
+                        blocked: true,
+                        ..LinkRule::default()
+                    });
+                },
+                SwarmChaos::Bandwidth(bandwidth) => {
+                    for group in &bandwidth.group_network_bandwidths {
+                        for validator in validators {
+                            let rate = group.rate * BYTES_PER_MEGABYTE;
+                            rules
+                                .egress_rates
+                                .entry(*validator)
+                                .and_modify(|egress_rate| *egress_rate = rate.min(*egress_rate))
+                                .or_insert(rate);
+                        }
+                    }
+                },
+                SwarmChaos::Loss(loss) => {
+                    rules.add_between(validators, validators, LinkRule {
+                        loss_percentage: loss.loss_percentage,
+                        loss_correlation_percentage: loss.correlation_percentage,
+                        ..LinkRule::default()
+                    });
+                },
+                SwarmChaos::NetEm(netem) => {
+                    for group in &netem.group_netems {
+                        rules.add_between(&group.source_nodes, &group.target_nodes, LinkRule {
+                            latency_ms: group.delay_latency_ms,
+                            jitter_ms: group.delay_jitter_ms,
+                            delay_correlation_percentage: group.delay_correlation_percentage,
+                            loss_percentage: group.loss_percentage,
+                            loss_correlation_percentage: group.loss_correlation_percentage,
+                            rate: Some(group.rate_in_mbps * BYTES_PER_MEGABYTE),
+                            ..LinkRule::default()
+                        });
+                    }
+                },
+                SwarmChaos::CpuStress(_) => {
+                    bail!("Chaos {:?} is not supported by the local swarm", chaos)
+                },
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Adds the rule to the traffic between the source and target nodes, in both directions.
+    fn add_between(&mut self, sources: &[PeerId], targets: &[PeerId], rule: LinkRule) {
+        for source in sources {
+            for target in targets.iter().filter(|target| *target != source) {
+                for link in [(*source, *target), (*target, *source)] {
+                    self.links.entry(link).or_default().merge(&rule);
+                }
+            }
+        }
+    }
+
+    fn link(&self, source: PeerId, target: PeerId) -> LinkRule {
+        self.links
+            .get(&(source, target))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Paces the traffic sent over a link to a rate.
+#[derive(Debug)]
+struct Pacer {
+    // When the link is done sending the traffic already paced.
+    next_free: Mutex<Instant>,
+}
+
+impl Pacer {
+    fn new() -> Self {
+        Self {
+            next_free: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Reserves the link for sending `len` bytes from `now` on, returning when they are sent.
+    fn reserve(&self, now: Instant, len: usize, rate: u64) -> Instant {
+        let mut next_free = self.next_free.lock();
+        let start = now.max(*next_free);
+        *next_free = start + Duration::from_secs_f64(len as f64 / rate.max(1) as f64);
+        *next_free
+    }
+}
+
+/// A uniform random variable correlated with its previous value, like the ones used by netem.
+#[derive(Debug, Default)]
+struct Correlated {
+    last: f64,
+}
+
+impl Correlated {
+    fn next<R: Rng>(&mut self, rng: &mut R, correlation_percentage: u64) -> f64 {
+        let correlation = correlation_percentage.min(100) as f64 / 100.0;
+        self.last = correlation * self.last + (1.0 - correlation) * rng.gen::<f64>();
+        self.last
+    }
+}
+
+/// What the proxies know about the validators.
+#[derive(Debug)]
+struct ProxyNetwork {
+    /// Validators by the port of their validator network
+    validator_ports: HashMap<u16, PeerId>,
+    /// Pacers of the traffic sent by each validator
+    egress_pacers: HashMap<PeerId, Pacer>,
+    rules: watch::Receiver<LinkRules>,
+}
+
+/// Decides when the chunks sent over one direction of a connection are delivered.
+struct LinkShaper {
+    source: PeerId,
+    target: PeerId,
+    pacer: Pacer,
+    delay: Correlated,
+    loss: Correlated,
+    rng: StdRng,
+}
+
+impl LinkShaper {
+    fn new(source: PeerId, target: PeerId) -> Self {
+        Self {
+            source,
+            target,
+            pacer: Pacer::new(),
+            delay: Correlated::default(),
+            loss: Correlated::default(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    fn deliver_at(&mut self, network: &ProxyNetwork, now: Instant, len: usize) -> Instant {
+        let rules = network.rules.borrow();
+        let rule = rules.link(self.source, self.target);
+        let mut sent_at = now;
+        if let (Some(rate), Some(pacer)) = (
+            rules.egress_rates.get(&self.source),
+            network.egress_pacers.get(&self.source),
+        ) {
+            sent_at = pacer.reserve(sent_at, len, *rate);
+        }
+        if let Some(rate) = rule.rate {
+            sent_at = self.pacer.reserve(sent_at, len, rate);
+        }
+
+        let jitter = self
+            .delay
+            .next(&mut self.rng, rule.delay_correlation_percentage)
+            * 2.0
+            - 1.0;
+        let delay_ms = (rule.latency_ms as f64 + rule.jitter_ms as f64 * jitter).max(0.0);
+        let mut deliver_at = sent_at + Duration::from_secs_f64(delay_ms / 1000.0);
+        let loss = self
+            .loss
+            .next(&mut self.rng, rule.loss_correlation_percentage);
+        if loss * 100.0 < rule.loss_percentage as f64 {
+            deliver_at += RETRANSMISSION_DELAY;
+        }
+        deliver_at
+    }
+}
+
+/// Proxies through which the validators of a local swarm connect to each other, to inject
+/// network chaos between them.
+#[derive(Debug)]
+pub(crate) struct ChaosProxies {
+    // Dropped in the background, as the swarm may be dropped in an async context.
+    runtime: Option<Runtime>,
+    /// Validators by index, to pick the partitioned ones
+    validators: Vec<PeerId>,
+    proxy_addresses: HashMap<PeerId, SocketAddr>,
+    chaoses: HashSet<SwarmChaos>,
+    rules: watch::Sender<LinkRules>,
+}
+
+impl ChaosProxies {
+    /// Starts a proxy for each of the validators, given by index with their validator network
+    /// port.
+    pub fn new(validators: Vec<(PeerId, u16)>) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .thread_name("local-swarm-chaos")
+            .worker_threads(2)
+            .enable_all()
+            .build()?;
+        let (rules, rules_receiver) = watch::channel(LinkRules::default());
+        let network = Arc::new(ProxyNetwork {
+            validator_ports: validators
+                .iter()
+                .map(|(peer_id, port)| (*port, *peer_id))
+                .collect(),
+            egress_pacers: validators
+                .iter()
+                .map(|(peer_id, _)| (*peer_id, Pacer::new()))
+                .collect(),
+            rules: rules_receiver,
+        });
+
+        let mut proxy_addresses = HashMap::new();
+        for (peer_id, _) in &validators {
+            // Bind here, so the proxies are ready before the validators start
+            let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+            listener.set_nonblocking(true)?;
+            proxy_addresses.insert(*peer_id, listener.local_addr()?);
+            runtime.spawn(run_proxy(listener, *peer_id, network.clone()));
+        }
+
+        Ok(Self {
+            runtime: Some(runtime),
+            validators: validators.into_iter().map(|(peer_id, _)| peer_id).collect(),
+            proxy_addresses,
+            chaoses: HashSet::new(),
+            rules,
+        })
+    }
+
+    /// Address of the proxy through which the validator connects to its peers.
+    pub fn proxy_address(&self, peer_id: PeerId) -> Option<SocketAddr> {
+        self.proxy_addresses.get(&peer_id).copied()
+    }
+
+    pub fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        info!("Injecting chaos into the local swarm: {:?}", chaos);
+        let mut chaoses = self.chaoses.clone();
+        chaoses.insert(chaos);
+        self.update_chaoses(chaoses)
+    }
+
+    pub fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        let mut chaoses = self.chaoses.clone();
+        if !chaoses.remove(&chaos) {
+            bail!("Chaos {:?} not found", chaos);
+        }
+        info!("Removing chaos from the local swarm: {:?}", chaos);
+        self.update_chaoses(chaoses)
+    }
+
+    pub fn remove_all_chaos(&mut self) -> Result<()> {
+        self.update_chaoses(HashSet::new())
+    }
+
+    fn update_chaoses(&mut self, chaoses: HashSet<SwarmChaos>) -> Result<()> {
+        let rules = LinkRules::new(&chaoses, &self.validators)?;
+        self.rules.send_replace(rules);
+        self.chaoses = chaoses;
+        Ok(())
+    }
+}
+
+impl Drop for ChaosProxies {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+async fn run_proxy(listener: std::net::TcpListener, source: PeerId, network: Arc<ProxyNetwork>) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(error) => {
+            warn!("Failed to start the chaos proxy of {}: {}", source, error);
+            return;
+        },
+    };
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let network = network.clone();
+                tokio::spawn(async move {
+                    if let Err(error) = handle_connection(stream, source, network).await {
+                        warn!("Chaos proxy connection of {} failed: {}", source, error);
+                    }
+                });
+            },
+            Err(error) => warn!("Chaos proxy of {} failed to accept: {}", source, error),
+        }
+    }
+}
+
+async fn handle_connection(
+    mut client: TcpStream,
+    source: PeerId,
+    network: Arc<ProxyNetwork>,
+) -> Result<()> {
+    let (host, port) = read_connect_request(&mut client).await?;
+    // Connections to anything but another validator are forwarded as they are
+    let target = network.validator_ports.get(&port).copied();
+    if let Some(target) = target {
+        let blocked = network.rules.borrow().link(source, target).blocked;
+        if blocked {
+            client
+                .write_all(b"HTTP/1.0 503 Service Unavailable\r\n\r\n")
+                .await?;
+            return Ok(());
+        }
+    }
+    let server = match TcpStream::connect((host.as_str(), port)).await {
+        Ok(server) => server,
+        Err(error) => {
+            client
+                .write_all(b"HTTP/1.0 502 Bad Gateway\r\n\r\n")
+                .await?;
+            return Err(error.into());
+        },
+    };
+    client.write_all(b"HTTP/1.0 200 OK\r\n\r\n").await?;
+
+    let target = match target {
+        Some(target) => target,
+        None => {
+            let (mut client, mut server) = (client, server);
+            tokio::io::copy_bidirectional(&mut client, &mut server).await?;
+            return Ok(());
+        },
+    };
+    let (client_reader, client_writer) = client.into_split();
+    let (server_reader, server_writer) = server.into_split();
+    // Dropping the pumps closes the connection, once the link gets blocked
+    tokio::select! {
+        result = futures::future::try_join(
+            pump(client_reader, server_writer, LinkShaper::new(source, target), &network),
+            pump(server_reader, client_writer, LinkShaper::new(target, source), &network),
+        ) => {
+            result?;
+        },
+        _ = wait_until_blocked(network.rules.clone(), source, target) => {},
+    }
+    Ok(())
+}
+
+/// Reads the CONNECT request sent by the node, returning the host and port to connect to.
+async fn read_connect_request(client: &mut TcpStream) -> Result<(String, u16)> {
+    let mut request = Vec::new();
+    let mut buffer = [0; MAX_CONNECT_REQUEST_SIZE];
+    while !request.ends_with(b"\r\n\r\n") {
+        let len = client.read(&mut buffer).await?;
+        if len == 0 || request.len() + len > MAX_CONNECT_REQUEST_SIZE {
+            bail!(
+                "Invalid CONNECT request: {}",
+                String::from_utf8_lossy(&request)
+            );
+        }
+        request.extend_from_slice(&buffer[..len]);
+    }
+    parse_connect_request(&request)
+}
+
+fn parse_connect_request(request: &[u8]) -> Result<(String, u16)> {
+    let request = std::str::from_utf8(request)?;
+    let invalid_request = || anyhow!("Invalid CONNECT request: {}", request);
+    let mut request_line = request
+        .lines()
+        .next()
+        .ok_or_else(invalid_request)?
+        .split_whitespace();
+    if request_line.next() != Some("CONNECT") {
+        return Err(invalid_request());
+    }
+    let (host, port) = request_line
+        .next()
+        .and_then(|address| address.rsplit_once(':'))
+        .ok_or_else(invalid_request)?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    Ok((host.to_string(), port.parse()?))
+}
+
+/// Forwards the traffic from the reader to the writer, delivering it as the shaper decides.
+async fn pump(
+    mut reader: OwnedReadHalf,
+    mut writer: OwnedWriteHalf,
+    mut shaper: LinkShaper,
+    network: &ProxyNetwork,
+) -> io::Result<()> {
+    let (sender, mut receiver) = mpsc::channel::<(Instant, Vec<u8>)>(MAX_CHUNKS_IN_FLIGHT);
+    let read = async move {
+        let mut buffer = vec![0; CHUNK_SIZE];
+        loop {
+            let len = reader.read(&mut buffer).await?;
+            if len == 0 {
+                return Ok(());
+            }
+            let deliver_at = shaper.deliver_at(network, Instant::now(), len);
+            if sender
+                .send((deliver_at, buffer[..len].to_vec()))
+                .await
+                .is_err()
+            {
+                return Ok(());
+            }
+        }
+    };
+    let write = async move {
+        while let Some((deliver_at, chunk)) = receiver.recv().await {
+            tokio::time::sleep_until(deliver_at.into()).await;
+            writer.write_all(&chunk).await?;
+        }
+        writer.shutdown().await
+    };
+    futures::future::try_join(read, write).await?;
+    Ok(())
+}
+
+async fn wait_until_blocked(mut rules: watch::Receiver<LinkRules>, source: PeerId, target: PeerId) {
+    loop {
+        let blocked = rules.borrow().link(source, target).blocked;
+        if blocked {
+            return;
+        }
+        if rules.changed().await.is_err() {
+            // The proxies are shutting down
+            futures::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        GroupNetEm, GroupNetworkDelay, SwarmCpuStress, SwarmNetEm, SwarmNetworkDelay,
+        SwarmNetworkLoss,
+    };
+    use std::io::{Read, Write};
+
+    fn delay_chaos(source: PeerId, target: PeerId, latency_ms: u64) -> SwarmChaos {
+        SwarmChaos::Delay(SwarmNetworkDelay {
+            group_network_delays: vec![GroupNetworkDelay {
+                name: "delay".to_string(),
+                source_nodes: vec![source],
+                target_nodes: vec![target],
+                latency_ms,
+                jitter_ms: 0,
+                correlation_percentage: 0,
+            }],
+        })
+    }
+
+    #[test]
+    fn parse_connect_requests() {
+        assert_eq!(
+            parse_connect_request(b"CONNECT 127.0.0.1:6180 HTTP/1.0\r\n\r\n").unwrap(),
+            ("127.0.0.1".to_string(), 6180)
+        );
+        assert_eq!(
+            parse_connect_request(b"CONNECT [::1]:6180 HTTP/1.1\r\nHost: [::1]\r\n\r\n").unwrap(),
+            ("::1".to_string(), 6180)
+        );
+        assert!(parse_connect_request(b"GET / HTTP/1.1\r\n\r\n").is_err());
+        assert!(parse_connect_request(b"CONNECT 127.0.0.1 HTTP/1.0\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn partition_cuts_off_the_first_validators() {
+        let validators: Vec<_> = (0..4).map(|_| PeerId::random()).collect();
+        let chaoses = HashSet::from([SwarmChaos::Partition(SwarmNetworkPartition {
+            partition_percentage: 50,
+        })]);
+        let rules = LinkRules::new(&chaoses, &validators).unwrap();
+        for source in &validators {
+            for target in validators.iter().filter(|target| *target != source) {
+                let partitioned =
+                    validators[..2].contains(source) || validators[..2].contains(target);
+                assert_eq!(rules.link(*source, *target).blocked, partitioned);
+            }
+        }
+    }
+
+    #[test]
+    fn chaoses_are_stacked() {
+        let validators: Vec<_> = (0..3).map(|_| PeerId::random()).collect();
+        let (a, b, c) = (validators[0], validators[1], validators[2]);
+        let chaoses = HashSet::from([
+            delay_chaos(a, b, 100),
+            SwarmChaos::NetEm(SwarmNetEm {
+                group_netems: vec![GroupNetEm {
+                    name: "netem".to_string(),
+                    source_nodes: vec![a],
+                    target_nodes: vec![b, c],
+                    delay_latency_ms: 50,
+                    delay_jitter_ms: 10,
+                    delay_correlation_percentage: 20,
+                    loss_percentage: 50,
+                    loss_correlation_percentage: 0,
+                    rate_in_mbps: 2,
+                }],
+            }),
+            SwarmChaos::Loss(SwarmNetworkLoss {
+                loss_percentage: 50,
+                correlation_percentage: 10,
+            }),
+        ]);
+        let rules = LinkRules::new(&chaoses, &validators).unwrap();
+
+        let expected = LinkRule {
+            blocked: false,
+            latency_ms: 150,
+            jitter_ms: 10,
+            delay_correlation_percentage: 20,
+            loss_percentage: 75,
+            loss_correlation_percentage: 10,
+            rate: Some(2 * BYTES_PER_MEGABYTE),
+        };
+        assert_eq!(rules.link(a, b), expected);
+        assert_eq!(rules.link(b, a), expected);
+        assert_eq!(rules.link(a, c).latency_ms, 50);
+        assert_eq!(rules.link(b, c), LinkRule {
+            loss_percentage: 50,
+            loss_correlation_percentage: 10,
+            ..LinkRule::default()
+        });
+    }
+
+    #[test]
+    fn cpu_stress_is_not_supported() {
+        let chaoses = HashSet::from([SwarmChaos::CpuStress(SwarmCpuStress {
+            group_cpu_stresses: vec![],
+        })]);
+        assert!(LinkRules::new(&chaoses, &[PeerId::random()]).is_err());
+    }
+
+    #[test]
+    fn pacer_paces_to_rate() {
+        let pacer = Pacer::new();
+        let now = Instant::now() + Duration::from_secs(1);
+        assert_eq!(pacer.reserve(now, 1000, 1000), now + Duration::from_secs(1));
+        assert_eq!(
+            pacer.reserve(now, 500, 1000),
+            now + Duration::from_millis(1500)
+        );
+        let later = now + Duration::from_secs(10);
+        assert_eq!(
+            pacer.reserve(later, 100, 1000),
+            later + Duration::from_millis(100)
+        );
+    }
+
+    // Returns the stream and the status line of the response, up to the status code.
+    fn connect_via_proxy(proxy: SocketAddr, port: u16) -> (std::net::TcpStream, String) {
+        let mut stream = std::net::TcpStream::connect(proxy).unwrap();
+        write!(stream, "CONNECT 127.0.0.1:{} HTTP/1.0\r\n\r\n", port).unwrap();
+        let mut status = [0; 12];
+        stream.read_exact(&mut status).unwrap();
+        let status = String::from_utf8_lossy(&status).to_string();
+        if status.ends_with("200") {
+            // Skip the rest of the response, i.e., " OK\r\n\r\n"
+            stream.read_exact(&mut [0; 7]).unwrap();
+        }
+        (stream, status)
+    }
+
+    #[test]
+    fn proxy_injects_chaos() {
+        // An echo server standing in for the target validator
+        let server = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let server_port = server.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for mut stream in server.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut buffer = [0; 1024];
+                    while let Ok(len) = stream.read(&mut buffer) {
+                        if len == 0 || stream.write_all(&buffer[..len]).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let (source, target) = (PeerId::random(), PeerId::random());
+        let mut proxies = ChaosProxies::new(vec![(source, 0), (target, server_port)]).unwrap();
+        let proxy = proxies.proxy_address(source).unwrap();
+
+        let (mut stream, response) = connect_via_proxy(proxy, server_port);
+        assert_eq!(response, "HTTP/1.0 200");
+        let delay = delay_chaos(source, target, 200);
+        proxies.inject_chaos(delay.clone()).unwrap();
+        let start = Instant::now();
+        stream.write_all(b"ping").unwrap();
+        let mut pong = [0; 4];
+        stream.read_exact(&mut pong).unwrap();
+        assert_eq!(&pong, b"ping");
+        // Delayed in both directions
+        assert!(start.elapsed() >= Duration::from_millis(400));
+        proxies.remove_chaos(delay.clone()).unwrap();
+        assert!(proxies.remove_chaos(delay).is_err());
+
+        // Partitioning the source closes its connections and rejects new ones
+        proxies
+            .inject_chaos(SwarmChaos::Partition(SwarmNetworkPartition {
+                partition_percentage: 50,
+            }))
+            .unwrap();
+        assert_eq!(stream.read(&mut pong).unwrap(), 0);
+        let (_, response) = connect_via_proxy(proxy, server_port);
+        assert_eq!(response, "HTTP/1.0 503");
+
+        proxies.remove_all_chaos().unwrap();
+        let (_, response) = connect_via_proxy(proxy, server_port);
+        assert_eq!(response, "HTTP/1.0 200");
+    }
+}
@@ -18,6 +18,7 @@ use std::{
 };
 
 mod cargo;
+mod chaos;
 mod node;
 mod swarm;
 pub use self::swarm::ActiveNodesGuard;
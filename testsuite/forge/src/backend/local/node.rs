@@ -15,6 +15,7 @@ use aptos_state_sync_driver::metadata_storage::STATE_SYNC_DB_NAME;
 use std::{
     env,
     fs::{self, OpenOptions},
+    net::SocketAddr,
     path::PathBuf,
     process::{Child, Command},
     str::FromStr,
@@ -51,6 +52,8 @@ pub struct LocalNode {
     peer_id: AccountAddress,
     directory: PathBuf,
     config: NodeConfig,
+    // HTTP proxy through which the node connects to its peers, see the chaos module.
+    proxy: Option<SocketAddr>,
 }
 
 impl LocalNode {
@@ -82,6 +85,7 @@ impl LocalNode {
             peer_id,
             directory,
             config,
+            proxy: None,
         })
     }
 
@@ -105,6 +109,10 @@ impl LocalNode {
         &self.account_private_key
     }
 
+    pub(crate) fn set_proxy(&mut self, proxy: Option<SocketAddr>) {
+        self.proxy = proxy;
+    }
+
     pub fn start(&mut self) -> Result<()> {
         ensure!(self.process.is_none(), "node {} already running", self.name);
 
@@ -125,6 +133,15 @@ impl LocalNode {
             // Only set our RUST_LOG if its not present in environment
             node_command.env("RUST_LOG", "debug");
         }
+        if let Some(proxy) = self.proxy {
+            // The network TCP transport dials through the HTTPS proxy, unless told not to
+            let proxy = format!("http://{}", proxy);
+            node_command
+                .env("https_proxy", &proxy)
+                .env("HTTPS_PROXY", &proxy)
+                .env_remove("no_proxy")
+                .env_remove("NO_PROXY");
+        }
         node_command.stdout(log_file.try_clone()?).stderr(log_file);
         let process = node_command.spawn().with_context(|| {
             format!(
@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use super::chaos::ChaosProxies;
 use crate::{
     interface::system_metrics::SystemMetricsThreshold, ChainInfo, FullNode, HealthCheckError,
     LocalNode, LocalVersion, Node, Swarm, SwarmChaos, SwarmExt, Validator, Version,
@@ -98,6 +99,7 @@ pub struct LocalSwarm {
     root_account: LocalAccount,
     chain_id: ChainId,
     root_key: ConfigKey<Ed25519PrivateKey>,
+    chaos_proxies: ChaosProxies,
 
     launched: bool,
     #[allow(dead_code)]
@@ -191,6 +193,30 @@ impl LocalSwarm {
             })
             .collect::<Result<HashMap<_, _>>>()?;
 
+        // Validators connect to each other through proxies, which inject the network chaos
+        let mut validator_ports = validators
+            .values()
+            .map(|validator| {
+                let port = validator
+                    .config()
+                    .validator_network
+                    .as_ref()
+                    .and_then(|network| network.listen_address.find_port())
+                    .ok_or_else(|| anyhow!("no validator network port for {}", validator.name()))?;
+                Ok((validator.index(), validator.peer_id(), port))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        validator_ports.sort_by_key(|(index, _, _)| *index);
+        let chaos_proxies = ChaosProxies::new(
+            validator_ports
+                .into_iter()
+                .map(|(_, peer_id, port)| (peer_id, port))
+                .collect(),
+        )?;
+        for validator in validators.values_mut() {
+            validator.set_proxy(chaos_proxies.proxy_address(validator.peer_id()));
+        }
+
         // After genesis, remove public network from validator and add to public_networks
         let public_networks = validators
             .values_mut()
@@ -255,6 +281,7 @@ impl LocalSwarm {
             root_account,
             chain_id: ChainId::test(),
             root_key,
+            chaos_proxies,
             launched: false,
             guard,
         })
@@ -591,16 +618,16 @@ impl Swarm for LocalSwarm {
         self.dir.display().to_string()
     }
 
-    fn inject_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    fn inject_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        self.chaos_proxies.inject_chaos(chaos)
     }
 
-    fn remove_chaos(&mut self, _chaos: SwarmChaos) -> Result<()> {
-        todo!()
+    fn remove_chaos(&mut self, chaos: SwarmChaos) -> Result<()> {
+        self.chaos_proxies.remove_chaos(chaos)
     }
 
     fn remove_all_chaos(&mut self) -> Result<()> {
-        todo!()
+        self.chaos_proxies.remove_all_chaos()
     }
 
     async fn ensure_no_validator_restart(&self) -> Result<()> {
@@ -7,13 +7,16 @@ use clap::Parser;
 use crossbeam::channel::{bounded, select};
 use lsp_server::{Connection, Message, Notification, Request, Response};
 use lsp_types::{
-    notification::Notification as _, request::Request as _, CompletionOptions, Diagnostic,
-    HoverProviderCapability, OneOf, SaveOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
+    notification::Notification as _, request::Request as _, CodeActionProviderCapability,
+    CompletionOptions, Diagnostic, HoverProviderCapability, OneOf, RenameOptions, SaveOptions,
+    SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
     TextDocumentSyncOptions, TypeDefinitionProviderCapability, WorkDoneProgressOptions,
 };
 use move_analyzer::{
+    code_action::on_code_action_request,
     completion::on_completion_request,
     context::Context,
+    formatting::on_formatting_request,
     symbols,
     vfs::{on_text_document_sync_notification, VirtualFileSystem},
 };
@@ -60,7 +63,7 @@ fn main() {
         .initialize_start()
         .expect("could not start connection initialization");
 
-    let capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
+    let mut capabilities = serde_json::to_value(lsp_types::ServerCapabilities {
         // The server receives notifications from the client as users open, close,
         // and modify documents.
         text_document_sync: Some(TextDocumentSyncCapability::Options(
@@ -106,9 +109,28 @@ fn main() {
         )),
         references_provider: Some(OneOf::Left(symbols::DEFS_AND_REFS_SUPPORT)),
         document_symbol_provider: Some(OneOf::Left(true)),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        })),
+        // Signatures are shown when starting to type the arguments of a call, and updated when
+        // moving on to the next argument.
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+            retrigger_characters: None,
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        document_formatting_provider: Some(OneOf::Left(true)),
         ..Default::default()
     })
     .expect("could not serialize server capabilities");
+    // Inlay hints are not part of the version of the protocol supported by `lsp_types`.
+    capabilities["inlayHintProvider"] = serde_json::json!(true);
 
     let (diag_sender, diag_receiver) = bounded::<Result<BTreeMap<Symbol, Vec<Diagnostic>>>>(0);
     let mut symbolicator_runner = symbols::SymbolicatorRunner::idle();
@@ -240,6 +262,24 @@ fn on_request(context: &Context, request: &Request) {
         lsp_types::request::DocumentSymbolRequest::METHOD => {
             symbols::on_document_symbol_request(context, request, &context.symbols.lock().unwrap());
         },
+        lsp_types::request::PrepareRenameRequest::METHOD => {
+            symbols::on_prepare_rename_request(context, request, &context.symbols.lock().unwrap());
+        },
+        lsp_types::request::Rename::METHOD => {
+            symbols::on_rename_request(context, request, &context.symbols.lock().unwrap());
+        },
+        lsp_types::request::SignatureHelpRequest::METHOD => {
+            symbols::on_signature_help_request(context, request, &context.symbols.lock().unwrap());
+        },
+        lsp_types::request::CodeActionRequest::METHOD => {
+            on_code_action_request(context, request, &context.symbols.lock().unwrap());
+        },
+        lsp_types::request::Formatting::METHOD => {
+            on_formatting_request(context, request);
+        },
+        symbols::InlayHintRequest::METHOD => {
+            symbols::on_inlay_hint_request(context, request, &context.symbols.lock().unwrap());
+        },
         _ => eprintln!("handle request '{}' from client", request.method),
     }
 }
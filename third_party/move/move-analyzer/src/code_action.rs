@@ -0,0 +1,305 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quick fixes for compiler diagnostics. Fixes are computed from the diagnostics sent back by the
+//! client, the symbolication information of the package, and the text of the buffer being edited
+//! (which the compiler may not have seen yet).
+
+use crate::{
+    context::Context,
+    symbols::{ModuleDefs, Symbols},
+    utils::{offset_to_position, position_to_offset, tokens},
+};
+use lsp_server::Request;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, Position, Range,
+    TextEdit, WorkspaceEdit,
+};
+use move_compiler::parser::lexer::Tok;
+use move_symbol_pool::Symbol;
+use std::collections::{BTreeSet, HashMap};
+use url::Url;
+
+/// Handles code action request of the language server
+pub fn on_code_action_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<CodeActionParams>(request.params.clone())
+        .expect("could not deserialize code action request");
+
+    let uri = parameters.text_document.uri;
+    let fpath = uri.to_file_path().unwrap();
+    eprintln!("on_code_action_request: {:?}", fpath);
+
+    let actions: Vec<CodeActionOrCommand> = match context.files.get(&fpath) {
+        Some(buffer) => quick_fixes(symbols, buffer, &uri, &parameters.context.diagnostics)
+            .into_iter()
+            .map(CodeActionOrCommand::CodeAction)
+            .collect(),
+        None => vec![],
+    };
+    let response = lsp_server::Response::new_ok(request.id.clone(), actions);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send code action response: {:?}", err);
+    }
+}
+
+/// Computes the quick fixes for the given diagnostics of a buffer:
+/// - a `use` declaration for unbound module aliases, types and functions defined in one of the
+///   modules of the package or of its dependencies
+/// - an `acquires` annotation for resources acquired by calls without being declared
+pub fn quick_fixes(
+    symbols: &Symbols,
+    buffer: &str,
+    uri: &Url,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeAction> {
+    let tokens = tokens(buffer);
+    let mut actions = vec![];
+    for diagnostic in diagnostics {
+        let offset = match position_to_offset(buffer, &diagnostic.range.start) {
+            Some(offset) => offset,
+            None => continue,
+        };
+        let message = diagnostic.message.as_str();
+        let fixes = if let Some(alias) = quoted(message, "Unbound module alias '") {
+            let modules =
+                modules_where(symbols, |mod_defs| mod_defs.module_name().as_str() == alias);
+            modules
+                .into_iter()
+                .filter_map(|module| add_use(buffer, &tokens, offset, &module))
+                .collect()
+        } else if let Some(name) =
+            quoted(message, "Unbound type '").or_else(|| quoted(message, "Unbound function: '"))
+        {
+            let current_module = enclosing_module(&tokens, offset).map(|(_, name)| name);
+            let modules = modules_where(symbols, |mod_defs| {
+                Some(mod_defs.module_name().as_str()) != current_module
+                    && (mod_defs.struct_names().any(|s| s.as_str() == name)
+                        || mod_defs.functions().contains_key(&Symbol::from(name)))
+            });
+            modules
+                .into_iter()
+                .filter_map(|module| {
+                    add_use(buffer, &tokens, offset, &format!("{}::{}", module, name))
+                })
+                .collect()
+        } else if let Some(resource) = quoted(message, "The call acquires '") {
+            // the resource is qualified with the module, whose address may be rendered with its
+            // value (e.g. `(std=0x1)::m::R`)
+            let name = resource.rsplit("::").next().unwrap_or(resource);
+            add_acquires(buffer, &tokens, offset, name)
+                .into_iter()
+                .collect()
+        } else {
+            vec![]
+        };
+        for (title, edit) in fixes {
+            actions.push(CodeAction {
+                title,
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit::new(HashMap::from([(
+                    uri.clone(),
+                    vec![edit],
+                )]))),
+                ..Default::default()
+            });
+        }
+    }
+    actions
+}
+
+/// Returns the part of a message following the given prefix, up to the next quote
+fn quoted<'a>(message: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = message.strip_prefix(prefix)?;
+    rest.find('\'').map(|end| &rest[..end])
+}
+
+/// Returns the address-qualified names of the modules satisfying the given predicate
+fn modules_where(symbols: &Symbols, pred: impl Fn(&ModuleDefs) -> bool) -> BTreeSet<String> {
+    symbols
+        .file_mods()
+        .values()
+        .flatten()
+        .filter(|mod_defs| pred(mod_defs))
+        .map(|mod_defs| mod_defs.ide_name())
+        .collect()
+}
+
+/// Returns the index of the `module` token of the module declaration enclosing the given offset,
+/// along with the name of the module
+fn enclosing_module<'a>(
+    tokens: &[(Tok, &'a str, usize)],
+    offset: usize,
+) -> Option<(usize, &'a str)> {
+    let module_idx = tokens
+        .iter()
+        .enumerate()
+        .take_while(|(_, (_, _, start))| *start < offset)
+        .filter(|(idx, (tok, _, _))| {
+            // `spec module` blocks are not module declarations
+            *tok == Tok::Module && (*idx == 0 || tokens[idx - 1].0 != Tok::Spec)
+        })
+        .map(|(idx, _)| idx)
+        .last()?;
+    let name = tokens[module_idx..]
+        .iter()
+        .take_while(|(tok, _, _)| *tok != Tok::LBrace)
+        .filter(|(tok, _, _)| *tok == Tok::Identifier)
+        .last()?
+        .1;
+    Some((module_idx, name))
+}
+
+/// Computes the edit adding a `use` declaration at the beginning of the module enclosing the
+/// given offset
+fn add_use(
+    buffer: &str,
+    tokens: &[(Tok, &str, usize)],
+    offset: usize,
+    path: &str,
+) -> Option<(String, TextEdit)> {
+    let (module_idx, _) = enclosing_module(tokens, offset)?;
+    let (_, _, brace_offset) = tokens[module_idx..]
+        .iter()
+        .find(|(tok, _, _)| *tok == Tok::LBrace)?;
+    let module_offset = tokens[module_idx].2;
+    let module_line_start = buffer[..module_offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let brace_pos = offset_to_position(buffer, *brace_offset);
+    let indent: String = buffer[module_line_start..module_offset]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+    let declaration = format!("use {};", path);
+    let pos = Position {
+        line: brace_pos.line + 1,
+        character: 0,
+    };
+    let edit = TextEdit::new(
+        Range::new(pos, pos),
+        format!("{}    {}\n", indent, declaration),
+    );
+    Some((format!("Add `{}`", declaration), edit))
+}
+
+/// Computes the edit adding a resource to the `acquires` list of the function enclosing the given
+/// offset
+fn add_acquires(
+    buffer: &str,
+    tokens: &[(Tok, &str, usize)],
+    offset: usize,
+    name: &str,
+) -> Option<(String, TextEdit)> {
+    let fun_idx = tokens
+        .iter()
+        .rposition(|(tok, _, start)| *tok == Tok::Fun && *start < offset)?;
+    // find the body of the function, skipping its parameters
+    let mut depth = 0;
+    let mut has_acquires = false;
+    let mut body_idx = None;
+    for (idx, (tok, _, _)) in tokens.iter().enumerate().skip(fun_idx) {
+        match tok {
+            Tok::LParen => depth += 1,
+            Tok::RParen => depth -= 1,
+            Tok::Acquires if depth == 0 => has_acquires = true,
+            Tok::LBrace if depth == 0 => {
+                body_idx = Some(idx);
+                break;
+            },
+            _ => (),
+        }
+    }
+    let (_, content, start) = tokens[body_idx? - 1];
+    let pos = offset_to_position(buffer, start + content.len());
+    let text = if has_acquires {
+        format!(", {}", name)
+    } else {
+        format!(" acquires {}", name)
+    };
+    Some((
+        format!("Add `{}` to the acquires list", name),
+        TextEdit::new(Range::new(pos, pos), text),
+    ))
+}
+
+#[test]
+fn add_use_test() {
+    let dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let (symbols_opt, _) =
+        crate::symbols::Symbolicator::get_symbols(&dir.join("tests/symbols")).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let buffer = r#"module Symbols::M10 {
+    fun f(): u64 {
+        M7::create_other_struct(7)
+    }
+}
+"#;
+    let uri = Url::from_file_path(dir.join("tests/symbols/sources/M10.move")).unwrap();
+    let diagnostic = Diagnostic {
+        range: Range::new(Position::new(2, 8), Position::new(2, 10)),
+        message: "Unbound module alias 'M7'".to_string(),
+        ..Default::default()
+    };
+    let actions = quick_fixes(&symbols, buffer, &uri, &[diagnostic]);
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].title, "Add `use Symbols::M7;`");
+    let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+    assert_eq!(
+        edits,
+        &vec![TextEdit::new(
+            Range::new(Position::new(1, 0), Position::new(1, 0)),
+            "    use Symbols::M7;\n".to_string(),
+        )]
+    );
+}
+
+#[test]
+fn add_acquires_test() {
+    let buffer = r#"module 0x1::m {
+    struct R has key { v: u64 }
+
+    fun f(a: address): u64 acquires R {
+        g(a)
+    }
+
+    fun g(a: address): u64 {
+        borrow_global<R>(a).v
+    }
+}
+"#;
+    let uri = Url::parse("file:///m.move").unwrap();
+    let diagnostic = |line| Diagnostic {
+        range: Range::new(Position::new(line, 8), Position::new(line, 12)),
+        message: "The call acquires '0x1::m::R', but the 'acquires' list for the current \
+                  function 'g` does not contain this type. It must be present in the calling \
+                  context's acquires list"
+            .to_string(),
+        ..Default::default()
+    };
+    let symbols = crate::symbols::Symbolicator::empty_symbols();
+    let actions = quick_fixes(&symbols, buffer, &uri, &[diagnostic(8)]);
+    let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+    assert_eq!(
+        edits,
+        &vec![TextEdit::new(
+            Range::new(Position::new(7, 26), Position::new(7, 26)),
+            " acquires R".to_string(),
+        )]
+    );
+
+    // the resource is added to an existing acquires list
+    let buffer = buffer.replace("acquires R {", "acquires S {");
+    let actions = quick_fixes(&symbols, &buffer, &uri, &[diagnostic(4)]);
+    let edits = &actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+    assert_eq!(
+        edits,
+        &vec![TextEdit::new(
+            Range::new(Position::new(3, 37), Position::new(3, 37)),
+            ", R".to_string(),
+        )]
+    );
+}
@@ -0,0 +1,69 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Formatting of whole buffers, configured by the `movefmt.toml` file of the package the buffer
+//! belongs to, if any.
+
+use crate::{context::Context, symbols::SymbolicatorRunner, utils::offset_to_position};
+use lsp_server::Request;
+use lsp_types::{DocumentFormattingParams, Position, Range, TextEdit};
+use move_formatter::{format_source, FormatConfig};
+use std::path::Path;
+
+/// Handles formatting request of the language server
+pub fn on_formatting_request(context: &Context, request: &Request) {
+    let parameters = serde_json::from_value::<DocumentFormattingParams>(request.params.clone())
+        .expect("could not deserialize formatting request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    eprintln!("on_formatting_request: {:?}", fpath);
+
+    let edits = context
+        .files
+        .get(&fpath)
+        .and_then(|buffer| format_buffer(&fpath, buffer));
+    let response = lsp_server::Response::new_ok(request.id.clone(), edits);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send formatting response: {:?}", err);
+    }
+}
+
+/// Computes the edit replacing the given buffer with its formatted text, if the buffer can be
+/// formatted
+pub fn format_buffer(fpath: &Path, buffer: &str) -> Option<Vec<TextEdit>> {
+    let config = match fpath.parent().and_then(SymbolicatorRunner::root_dir) {
+        Some(root_dir) => FormatConfig::load(&root_dir),
+        None => Ok(FormatConfig::default()),
+    };
+    let formatted = config
+        .and_then(|config| format_source(&fpath.to_string_lossy(), buffer, &config))
+        .map_err(|err| eprintln!("could not format {:?}: {}", fpath, err))
+        .ok()?;
+    let end = offset_to_position(buffer, buffer.len());
+    Some(vec![TextEdit::new(
+        Range::new(Position::new(0, 0), end),
+        formatted,
+    )])
+}
+
+#[test]
+fn format_buffer_test() {
+    let fpath =
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/symbols/sources/M1.move");
+    let buffer = "module Symbols::M {\nfun f( x:u64 ):u64 { x+1 }\n}\n";
+    let edits = format_buffer(&fpath, buffer).unwrap();
+    assert_eq!(
+        edits,
+        vec![TextEdit::new(
+            Range::new(Position::new(0, 0), Position::new(3, 0)),
+            "module Symbols::M {\n    fun f(x: u64): u64 { x + 1 }\n}\n".to_string(),
+        )]
+    );
+
+    // buffers with syntax errors are not formatted
+    assert!(format_buffer(&fpath, "module Symbols::M {").is_none());
+}
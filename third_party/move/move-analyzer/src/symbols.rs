@@ -49,7 +49,8 @@
 use crate::{
     context::Context,
     diagnostics::{lsp_diagnostics, lsp_empty_diagnostics},
-    utils::get_loc,
+    utils::{get_loc, offset_to_position, position_to_offset, tokens},
+    vfs::VirtualFileSystem,
 };
 use anyhow::{anyhow, Result};
 use codespan_reporting::files::SimpleFiles;
@@ -59,14 +60,16 @@ use im::ordmap::OrdMap;
 use lsp_server::{Request, RequestId};
 use lsp_types::{
     request::GotoTypeDefinitionParams, Diagnostic, DocumentSymbol, DocumentSymbolParams,
-    GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString, Location,
-    MarkedString, Position, Range, ReferenceParams, SymbolKind,
+    Documentation, GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString,
+    Location, MarkedString, ParameterInformation, ParameterLabel, Position, PrepareRenameResponse,
+    Range, ReferenceParams, RenameParams, SignatureHelp, SignatureHelpParams, SignatureInformation,
+    SymbolKind, TextDocumentIdentifier, TextDocumentPositionParams, TextEdit, WorkspaceEdit,
 };
 use move_command_line_common::files::FileHash;
 use move_compiler::{
     expansion::ast::{Address, Fields, ModuleIdent, ModuleIdent_},
     naming::ast::{StructDefinition, StructFields, TParam, Type, TypeName_, Type_},
-    parser::ast::StructName,
+    parser::{ast::StructName, lexer::Tok},
     shared::Identifier,
     typing::ast::{
         BuiltinFunction_, Exp, ExpListItem, Function, FunctionBody_, LValue, LValueList, LValue_,
@@ -77,6 +80,7 @@ use move_compiler::{
 use move_ir_types::location::*;
 use move_package::compilation::build_plan::BuildPlan;
 use move_symbol_pool::Symbol;
+use serde::{Deserialize, Serialize};
 use std::{
     cmp,
     collections::{BTreeMap, BTreeSet, HashMap},
@@ -94,6 +98,37 @@ pub const DEFS_AND_REFS_SUPPORT: bool = true;
 // Building Move code requires a larger stack size on Windows (16M has been chosen somewhat
 // arbitrarily)
 pub const STACK_SIZE_BYTES: usize = 16 * 1024 * 1024;
+/// Kind of inlay hints showing the type of an identifier
+pub const INLAY_HINT_KIND_TYPE: u32 = 1;
+
+/// Inlay hint request of the language server. Inlay hints were introduced in version 3.17 of the
+/// protocol, which is more recent than the one supported by `lsp_types`.
+pub enum InlayHintRequest {}
+
+impl lsp_types::request::Request for InlayHintRequest {
+    type Params = InlayHintParams;
+    type Result = Vec<InlayHint>;
+
+    const METHOD: &'static str = "textDocument/inlayHint";
+}
+
+/// Parameters of the inlay hint request
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    pub text_document: TextDocumentIdentifier,
+    /// Visible part of the document for which hints are requested
+    pub range: Range,
+}
+
+/// A label displayed inline with the source code
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    pub kind: Option<u32>,
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Copy)]
 /// Location of a definition's identifier
@@ -207,6 +242,9 @@ pub struct Symbolicator {
     type_params: BTreeMap<Symbol, DefLoc>,
     /// Current processed module (always set before module processing starts)
     current_mod: Option<ModuleIdent>,
+    /// Types of the variables bound by `let` without a type annotation in the current processed
+    /// module
+    type_hints: Vec<InlayHint>,
 }
 
 /// Maps a line number to a list of use-def pairs on a given line (use-def set is sorted by
@@ -228,6 +266,8 @@ pub struct Symbols {
     file_name_mapping: BTreeMap<FileHash, Symbol>,
     /// A mapping from filePath to ModuleDefs
     file_mods: BTreeMap<PathBuf, BTreeSet<ModuleDefs>>,
+    /// A mapping from file paths to inferred types of the variables bound in a file
+    file_type_hints: BTreeMap<PathBuf, Vec<InlayHint>>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -246,6 +286,24 @@ impl ModuleDefs {
     pub fn functions(&self) -> &BTreeMap<Symbol, FunctionDef> {
         &self.functions
     }
+
+    pub fn struct_names(&self) -> impl Iterator<Item = &Symbol> {
+        self.structs.keys()
+    }
+
+    pub fn module_name(&self) -> Symbol {
+        self.name.module.value()
+    }
+
+    /// Returns the name of the module qualified with its address, as written in the source code
+    /// (i.e. using the name of a named address)
+    pub fn ide_name(&self) -> String {
+        format!(
+            "{}::{}",
+            addr_to_ide_string(&self.name.address),
+            self.name.module.value()
+        )
+    }
 }
 
 impl fmt::Display for IdentType {
@@ -590,6 +648,18 @@ impl FunctionIdentTypeMap {
 
 impl Symbols {
     pub fn merge(&mut self, other: Self) {
+        // references located in previous versions of the files that were symbolicated again are
+        // outdated, and are identified by the hashes of the previous file contents
+        let updated_files: BTreeSet<_> = other.file_name_mapping.values().collect();
+        let file_name_mapping = &self.file_name_mapping;
+        for uses in self.references.values_mut() {
+            uses.retain(|use_loc| {
+                file_name_mapping
+                    .get(&use_loc.fhash)
+                    .map_or(true, |fname| !updated_files.contains(fname))
+            });
+        }
+        self.references.retain(|_, uses| !uses.is_empty());
         for (k, v) in other.references {
             self.references
                 .entry(k)
@@ -599,11 +669,203 @@ impl Symbols {
         self.file_use_defs.extend(other.file_use_defs);
         self.file_name_mapping.extend(other.file_name_mapping);
         self.file_mods.extend(other.file_mods);
+        self.file_type_hints.extend(other.file_type_hints);
     }
 
     pub fn file_mods(&self) -> &BTreeMap<PathBuf, BTreeSet<ModuleDefs>> {
         &self.file_mods
     }
+
+    /// Returns the use-def pair of the identifier at the given position in a file
+    fn use_def_at(&self, fpath: &Path, pos: &Position) -> Option<UseDef> {
+        self.file_use_defs
+            .get(fpath)?
+            .get(pos.line)?
+            .into_iter()
+            .filter(|u| pos.character >= u.col_start && pos.character <= u.col_end)
+            .last()
+    }
+
+    /// Returns the use-def pair of the identifier at the given position in a file if this
+    /// identifier can be renamed, i.e. if it's defined in the same package as the file rather than
+    /// in one of its dependencies
+    fn rename_target(&self, fpath: &Path, pos: &Position) -> Result<UseDef, String> {
+        let use_def = self
+            .use_def_at(fpath, pos)
+            .ok_or_else(|| "No identifier to rename at this position".to_string())?;
+        let def_path = match self.file_name_mapping.get(&use_def.def_loc.fhash) {
+            Some(p) => PathBuf::from(p.as_str()),
+            None => return Err("Unknown definition of the identifier".to_string()),
+        };
+        let package_dir =
+            |p: &Path| SymbolicatorRunner::root_dir(&dunce::canonicalize(p).unwrap_or_default());
+        match package_dir(&def_path) {
+            Some(def_dir) if Some(&def_dir) == package_dir(fpath).as_ref() => Ok(use_def),
+            _ => Err("Cannot rename an identifier defined outside of the package".to_string()),
+        }
+    }
+
+    /// Computes the edits renaming the identifier at the given position in a file, along with
+    /// all its references
+    fn rename_edits(
+        &self,
+        files: &VirtualFileSystem,
+        fpath: &Path,
+        pos: &Position,
+        new_name: &str,
+    ) -> Result<HashMap<Url, Vec<TextEdit>>, String> {
+        let new_name_tokens = tokens(new_name);
+        if !matches!(new_name_tokens.as_slice(), [(Tok::Identifier, name, 0)] if *name == new_name)
+        {
+            return Err(format!("'{}' is not a valid identifier", new_name));
+        }
+        let use_def = self.rename_target(fpath, pos)?;
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        let mut add_edit = |path: &Symbol, start: Position, len: u32| {
+            let end = Position {
+                line: start.line,
+                character: start.character + len,
+            };
+            changes
+                .entry(Url::from_file_path(path.as_str()).unwrap())
+                .or_insert_with(Vec::new)
+                .push(TextEdit::new(Range { start, end }, new_name.to_string()));
+        };
+        let mut use_files = BTreeSet::new();
+        for use_loc in self.references.get(&use_def.def_loc).into_iter().flatten() {
+            if let Some(path) = self.file_name_mapping.get(&use_loc.fhash) {
+                add_edit(
+                    path,
+                    use_loc.start,
+                    use_loc.col_end - use_loc.start.character,
+                );
+                use_files.insert(*path);
+            }
+        }
+
+        // members imported with `use` declarations are also named there, but these declarations
+        // are not part of the typed AST
+        if let Some((module, member)) = self.module_member(&use_def.def_loc) {
+            for path in use_files {
+                let text = match files.get(&PathBuf::from(path.as_str())) {
+                    Some(text) => text.to_string(),
+                    None => match std::fs::read_to_string(path.as_str()) {
+                        Ok(text) => text,
+                        Err(_) => continue,
+                    },
+                };
+                for offset in member_use_offsets(&text, module, member) {
+                    add_edit(
+                        &path,
+                        offset_to_position(&text, offset),
+                        member.as_str().len() as u32,
+                    );
+                }
+            }
+        }
+        // the same location may be both recorded as a reference and found in a declaration
+        for edits in changes.values_mut() {
+            edits.sort_by_key(|edit| (edit.range.start.line, edit.range.start.character));
+            edits.dedup_by_key(|edit| edit.range.start);
+        }
+        Ok(changes)
+    }
+
+    /// Returns the name of the module and the name of a module member (a struct, a function or a
+    /// constant) given the location of the member's definition
+    fn module_member(&self, def_loc: &DefLoc) -> Option<(Symbol, Symbol)> {
+        self.file_mods
+            .values()
+            .flatten()
+            .filter(|mod_defs| mod_defs.fhash == def_loc.fhash)
+            .find_map(|mod_defs| {
+                let structs = mod_defs.structs.iter().map(|(n, s)| (n, &s.name_start));
+                let functions = mod_defs.functions.iter().map(|(n, f)| (n, &f.start));
+                let mut members = structs.chain(functions).chain(mod_defs.constants.iter());
+                let (name, _) = members.find(|(_, start)| **start == def_loc.start)?;
+                Some((mod_defs.module_name(), *name))
+            })
+    }
+
+    /// Computes the signature of the function called by the innermost call enclosing the given
+    /// position in a buffer
+    fn signature_help(&self, buffer: &str, fpath: &Path, pos: &Position) -> Option<SignatureHelp> {
+        let offset = position_to_offset(buffer, pos)?;
+        let tokens = tokens(buffer);
+        let (callee_idx, arg_idx) = enclosing_call(&tokens, offset)?;
+        let (_, callee, callee_offset) = tokens[callee_idx];
+
+        // use symbolication information for the call if available, and otherwise (e.g. when the
+        // call is being typed and is yet to be compiled) look the function up by its name
+        let callee_pos = offset_to_position(buffer, callee_offset);
+        let (fun_type, doc_string) = match self.use_def_at(fpath, &callee_pos) {
+            Some(u) if matches!(u.use_type, IdentType::FunctionType(..)) => {
+                (u.use_type, u.doc_string)
+            },
+            _ => {
+                let module = match &tokens[..callee_idx] {
+                    [.., (Tok::Identifier, m, _), (Tok::ColonColon, _, _)] => Some(*m),
+                    _ => None,
+                };
+                (self.find_function(fpath, module, callee)?, String::new())
+            },
+        };
+        let parameters: Vec<_> = match &fun_type {
+            IdentType::FunctionType(_, _, _, arg_names, arg_types, _, _) => arg_names
+                .iter()
+                .zip(arg_types)
+                .map(|(n, t)| ParameterInformation {
+                    label: ParameterLabel::Simple(format!("{}: {}", n, type_to_ide_string(t))),
+                    documentation: None,
+                })
+                .collect(),
+            IdentType::RegularType(_) => return None,
+        };
+        let active_parameter = arg_idx.min(parameters.len().saturating_sub(1) as u32);
+        Some(SignatureHelp {
+            signatures: vec![SignatureInformation {
+                label: fun_type.to_string(),
+                documentation: if doc_string.is_empty() {
+                    None
+                } else {
+                    Some(Documentation::String(doc_string))
+                },
+                parameters: Some(parameters),
+                active_parameter: None,
+            }],
+            active_signature: Some(0),
+            active_parameter: Some(active_parameter),
+        })
+    }
+
+    /// Finds a function by name in the modules with the given name (or alias), or in the modules
+    /// of the given file if no module is specified
+    fn find_function(&self, fpath: &Path, module: Option<&str>, name: &str) -> Option<IdentType> {
+        let name = Symbol::from(name);
+        let mut mods: Box<dyn Iterator<Item = &ModuleDefs> + '_> = match module {
+            Some(module) => Box::new(
+                self.file_mods
+                    .values()
+                    .flatten()
+                    .filter(move |mod_defs| mod_defs.module_name().as_str() == module),
+            ),
+            None => Box::new(self.file_mods.get(fpath)?.iter()),
+        };
+        mods.find_map(|mod_defs| mod_defs.functions.get(&name))
+            .map(|fun_def| fun_def.ident_type.clone())
+    }
+
+    /// Returns the inlay hints located within the given range of a file
+    fn inlay_hints(&self, fpath: &Path, range: &Range) -> Vec<InlayHint> {
+        self.file_type_hints
+            .get(fpath)
+            .into_iter()
+            .flatten()
+            .filter(|hint| hint.position >= range.start && hint.position <= range.end)
+            .cloned()
+            .collect()
+    }
 }
 
 impl Symbolicator {
@@ -616,6 +878,9 @@ impl Symbolicator {
     ) -> Result<(Option<Symbols>, BTreeMap<Symbol, Vec<Diagnostic>>)> {
         let build_config = move_package::BuildConfig {
             test_mode: true,
+            // packages often leave their own addresses unassigned, and only assign them in
+            // dev-addresses
+            dev_mode: true,
             install_dir: Some(tempdir().unwrap().path().to_path_buf()),
             ..Default::default()
         };
@@ -738,10 +1003,12 @@ impl Symbolicator {
             file_id_to_lines,
             type_params: BTreeMap::new(),
             current_mod: None,
+            type_hints: vec![],
         };
 
         let mut references = BTreeMap::new();
         let mut file_use_defs = BTreeMap::new();
+        let mut file_type_hints = BTreeMap::new();
         let mut function_ident_type = FunctionIdentTypeMap::new();
 
         for (pos, module_ident, module_def) in modules {
@@ -753,6 +1020,7 @@ impl Symbolicator {
                 &mut use_defs,
                 &mut function_ident_type,
             );
+            let type_hints = std::mem::take(&mut symbolicator.type_hints);
 
             let fpath = match source_files.get(&pos.file_hash()) {
                 Some((p, _)) => p,
@@ -762,6 +1030,10 @@ impl Symbolicator {
             let fpath_buffer = dunce::canonicalize(fpath.as_str())
                 .unwrap_or_else(|_| PathBuf::from(fpath.as_str()));
 
+            file_type_hints
+                .entry(fpath_buffer.clone())
+                .or_insert_with(Vec::new)
+                .extend(type_hints);
             file_use_defs
                 .entry(fpath_buffer)
                 .or_insert_with(UseDefMap::new)
@@ -773,6 +1045,7 @@ impl Symbolicator {
             file_use_defs,
             file_name_mapping,
             file_mods,
+            file_type_hints,
         };
 
         eprintln!("get_symbols load complete");
@@ -787,6 +1060,7 @@ impl Symbolicator {
             references: BTreeMap::new(),
             file_name_mapping: BTreeMap::new(),
             file_mods: BTreeMap::new(),
+            file_type_hints: BTreeMap::new(),
         }
     }
 
@@ -1197,7 +1471,7 @@ impl Symbolicator {
 
     /// Get symbols for a sequence representing function body
     fn seq_item_symbols(
-        &self,
+        &mut self,
         scope: &mut OrdMap<Symbol, DefLoc>,
         seq_item: &SequenceItem,
         references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
@@ -1220,10 +1494,41 @@ impl Symbolicator {
                     }
                 }
                 self.lvalue_list_symbols(true, lvalues, scope, references, use_defs);
+                // after expansion, the type annotation of a binding annotates the bound expression
+                if !matches!(e.exp.value, UnannotatedExp_::Annotate(_, _)) {
+                    self.add_type_hints(lvalues);
+                }
             },
         }
     }
 
+    /// Add inlay hints showing the types of the variables bound by a list of lvalues
+    fn add_type_hints(&mut self, lvalues: &LValueList) {
+        for lval in &lvalues.value {
+            let (var, t) = match &lval.value {
+                LValue_::Var(var, t) => (var, t),
+                _ => continue,
+            };
+            if matches!(
+                t.value,
+                Type_::Anything | Type_::Var(_) | Type_::UnresolvedError
+            ) {
+                continue;
+            }
+            if let Some(start) = Self::get_start_loc(&var.loc(), &self.files, &self.file_id_mapping)
+            {
+                self.type_hints.push(InlayHint {
+                    position: Position {
+                        line: start.line,
+                        character: start.character + var.value().as_str().len() as u32,
+                    },
+                    label: format!(": {}", type_to_ide_string(t)),
+                    kind: Some(INLAY_HINT_KIND_TYPE),
+                });
+            }
+        }
+    }
+
     /// Get symbols for a list of lvalues
     fn lvalue_list_symbols(
         &self,
@@ -1327,7 +1632,7 @@ impl Symbolicator {
 
     /// Get symbols for an expression
     fn exp_symbols(
-        &self,
+        &mut self,
         exp: &Exp,
         scope: &mut OrdMap<Symbol, DefLoc>,
         references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
@@ -1518,7 +1823,7 @@ impl Symbolicator {
     }
 
     fn mod_call_symbols(
-        &self,
+        &mut self,
         mod_call: &ModuleCall,
         scope: &mut OrdMap<Symbol, DefLoc>,
         references: &mut BTreeMap<DefLoc, BTreeSet<UseLoc>>,
@@ -1553,7 +1858,7 @@ impl Symbolicator {
 
     /// Get symbols for the pack expression
     fn pack_symbols(
-        &self,
+        &mut self,
         ident: &ModuleIdent,
         name: &StructName,
         tparams: &Vec<Type>,
@@ -2356,6 +2661,217 @@ pub fn on_document_symbol_request(context: &Context, request: &Request, symbols:
     }
 }
 
+/// Handles prepare rename request of the language server
+pub fn on_prepare_rename_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<TextDocumentPositionParams>(request.params.clone())
+        .expect("could not deserialize prepare rename request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let pos = parameters.position;
+    eprintln!("on_prepare_rename_request: {:?} {:?}", fpath, pos);
+
+    let response = match symbols.rename_target(&fpath, &pos) {
+        Ok(use_def) => {
+            let range = Range {
+                start: Position {
+                    line: pos.line,
+                    character: use_def.col_start,
+                },
+                end: Position {
+                    line: pos.line,
+                    character: use_def.col_end,
+                },
+            };
+            lsp_server::Response::new_ok(request.id.clone(), PrepareRenameResponse::Range(range))
+        },
+        Err(msg) => lsp_server::Response::new_err(
+            request.id.clone(),
+            lsp_server::ErrorCode::InvalidRequest as i32,
+            msg,
+        ),
+    };
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send prepare rename response: {:?}", err);
+    }
+}
+
+/// Handles rename request of the language server
+pub fn on_rename_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<RenameParams>(request.params.clone())
+        .expect("could not deserialize rename request");
+
+    let fpath = parameters
+        .text_document_position
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let pos = parameters.text_document_position.position;
+    eprintln!("on_rename_request: {:?} {:?}", fpath, pos);
+
+    let response = match symbols.rename_edits(&context.files, &fpath, &pos, &parameters.new_name) {
+        Ok(changes) => {
+            lsp_server::Response::new_ok(request.id.clone(), WorkspaceEdit::new(changes))
+        },
+        Err(msg) => lsp_server::Response::new_err(
+            request.id.clone(),
+            lsp_server::ErrorCode::InvalidRequest as i32,
+            msg,
+        ),
+    };
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send rename response: {:?}", err);
+    }
+}
+
+/// Handles signature help request of the language server
+pub fn on_signature_help_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<SignatureHelpParams>(request.params.clone())
+        .expect("could not deserialize signature help request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let pos = parameters.text_document_position_params.position;
+    eprintln!("on_signature_help_request: {:?} {:?}", fpath, pos);
+
+    let signature_help = context
+        .files
+        .get(&fpath)
+        .and_then(|buffer| symbols.signature_help(buffer, &fpath, &pos));
+    let response = lsp_server::Response::new_ok(request.id.clone(), signature_help);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send signature help response: {:?}", err);
+    }
+}
+
+/// Handles inlay hint request of the language server
+pub fn on_inlay_hint_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<InlayHintParams>(request.params.clone())
+        .expect("could not deserialize inlay hint request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    eprintln!("on_inlay_hint_request: {:?}", fpath);
+
+    let hints = symbols.inlay_hints(&fpath, &parameters.range);
+    let response = lsp_server::Response::new_ok(request.id.clone(), hints);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send inlay hint response: {:?}", err);
+    }
+}
+
+/// Returns the offsets of the names of a module member in the `use` declarations of a source
+/// importing it, e.g. `use 0x1::m::member` or `use 0x1::m::{Self, member as alias}`
+fn member_use_offsets(text: &str, module: Symbol, member: Symbol) -> Vec<usize> {
+    let tokens = tokens(text);
+    let mut offsets = vec![];
+    let mut idx = 0;
+    while idx < tokens.len() {
+        if tokens[idx].0 != Tok::Use {
+            idx += 1;
+            continue;
+        }
+        // find the module path, which precedes the members in the declaration
+        let end = tokens[idx..]
+            .iter()
+            .position(|(tok, _, _)| *tok == Tok::Semicolon)
+            .map_or(tokens.len(), |pos| idx + pos);
+        let decl = &tokens[idx..end];
+        let module_pos = decl.windows(2).position(|pair| {
+            matches!(pair, [(Tok::Identifier, m, _), (Tok::ColonColon, _, _)]
+                if *m == module.as_str())
+        });
+        if let Some(module_pos) = module_pos {
+            // members are either a single identifier or a braced list, possibly with aliases
+            let mut after_as = false;
+            for (tok, content, offset) in &decl[module_pos + 2..] {
+                match tok {
+                    Tok::Identifier if !after_as && *content == member.as_str() => {
+                        offsets.push(*offset)
+                    },
+                    _ => (),
+                }
+                after_as = *tok == Tok::As;
+            }
+        }
+        idx = end;
+    }
+    offsets
+}
+
+/// Finds the innermost function call whose arguments enclose the given offset, returning the
+/// index of the token naming the called function and the index of the argument at the offset
+fn enclosing_call(tokens: &[(Tok, &str, usize)], offset: usize) -> Option<(usize, u32)> {
+    // delimiters opened before the offset, with the called function for argument lists and the
+    // number of commas seen so far
+    let mut open: Vec<(Option<usize>, u32)> = vec![];
+    for (idx, (tok, _, start)) in tokens.iter().enumerate() {
+        if *start >= offset {
+            break;
+        }
+        match tok {
+            Tok::LParen => open.push((callee(tokens, idx), 0)),
+            Tok::LBrace | Tok::LBracket => open.push((None, 0)),
+            Tok::RParen | Tok::RBrace | Tok::RBracket => {
+                open.pop();
+            },
+            Tok::Comma => {
+                if let Some((_, commas)) = open.last_mut() {
+                    *commas += 1;
+                }
+            },
+            _ => (),
+        }
+    }
+    open.into_iter()
+        .rev()
+        .find_map(|(callee, commas)| Some((callee?, commas)))
+}
+
+/// Returns the index of the token naming the called function if the parenthesis at the given
+/// index starts the arguments of a call, skipping type arguments (e.g. `f<T>(...)`)
+fn callee(tokens: &[(Tok, &str, usize)], lparen_idx: usize) -> Option<usize> {
+    let mut idx = lparen_idx.checked_sub(1)?;
+    if matches!(tokens[idx].0, Tok::Greater | Tok::GreaterGreater) {
+        let mut depth = 0;
+        loop {
+            match tokens[idx].0 {
+                Tok::Greater => depth += 1,
+                Tok::GreaterGreater => depth += 2,
+                Tok::Less => depth -= 1,
+                _ => (),
+            }
+            if depth <= 0 {
+                break;
+            }
+            idx = idx.checked_sub(1)?;
+        }
+        idx = idx.checked_sub(1)?;
+    }
+    // parameters of function declarations are not arguments
+    let declaration = idx > 0 && tokens[idx - 1].0 == Tok::Fun;
+    (tokens[idx].0 == Tok::Identifier && !declaration).then_some(idx)
+}
+
 /// Helper function to handle struct fields
 #[allow(deprecated)]
 fn handle_struct_fields(struct_def: StructDef, fields: &mut Vec<DocumentSymbol>) {
@@ -3669,3 +4185,155 @@ fn symbols_test() {
         None,
     );
 }
+
+#[test]
+/// Tests renaming a struct used in other modules, including in `use` declarations.
+fn rename_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+    let files = VirtualFileSystem::default();
+
+    let cpath = dunce::canonicalize(path.join("sources/M6.move")).unwrap();
+    let changes = symbols
+        .rename_edits(&files, &cpath, &Position::new(38, 36), "RenamedStruct")
+        .unwrap();
+
+    let m6_url = Url::from_file_path(&cpath).unwrap();
+    let m6_starts: Vec<_> = changes[&m6_url].iter().map(|e| e.range.start).collect();
+    assert_eq!(m6_starts, vec![
+        Position::new(26, 41),
+        Position::new(36, 28),
+        Position::new(38, 35),
+    ]);
+    let m7_url =
+        Url::from_file_path(dunce::canonicalize(path.join("sources/M7.move")).unwrap()).unwrap();
+    let m7_edits = &changes[&m7_url];
+    assert!(m7_edits.contains(&TextEdit::new(
+        Range::new(Position::new(3, 11), Position::new(3, 25)),
+        "RenamedStruct".to_string()
+    )));
+
+    assert!(symbols
+        .rename_edits(&files, &cpath, &Position::new(38, 36), "0x42")
+        .is_err());
+}
+
+#[test]
+/// Tests signature help for the arguments of a function call.
+fn signature_help_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let cpath = dunce::canonicalize(path.join("sources/M1.move")).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+
+    // second argument of `M2::multi_arg(SOME_CONST, SOME_CONST)`
+    let help = symbols
+        .signature_help(&buffer, &cpath, &Position::new(40, 36))
+        .unwrap();
+    assert_eq!(
+        help.signatures[0].label,
+        "fun Symbols::M2::multi_arg(p1: u64, p2: u64): u64"
+    );
+    assert_eq!(help.active_parameter, Some(1));
+
+    // not within the arguments of a call
+    assert!(symbols
+        .signature_help(&buffer, &cpath, &Position::new(15, 12))
+        .is_none());
+}
+
+#[test]
+/// Tests inlay hints for the types of variables bound without a type annotation.
+fn inlay_hints_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let cpath = dunce::canonicalize(path.join("sources/M1.move")).unwrap();
+    let all = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+    let hints = symbols.inlay_hints(&cpath, &all);
+
+    let hint_at = |line| hints.iter().find(|h| h.position.line == line);
+    // `let ret = value;`
+    assert_eq!(hint_at(15).unwrap().position, Position::new(15, 15));
+    assert_eq!(hint_at(15).unwrap().label, ": u64");
+    // `let ret = SomeStruct { some_field: SOME_CONST };`
+    assert_eq!(hint_at(20).unwrap().label, ": Symbols::M1::SomeStruct");
+    // `let value: u64;` and `let tmp = (SOME_CONST: u64);` are annotated
+    assert!(hint_at(49).is_none());
+    assert!(hint_at(118).is_none());
+
+    let first_lines = Range::new(Position::new(0, 0), Position::new(16, 0));
+    assert_eq!(symbols.inlay_hints(&cpath, &first_lines).len(), 1);
+}
+
+#[test]
+/// Tests a package depending on the Aptos framework, with positions on lines containing characters
+/// encoded as two UTF-16 code units.
+fn aptos_framework_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/aptos_framework");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+    let files = VirtualFileSystem::default();
+
+    let cpath = dunce::canonicalize(path.join("sources/wallet.move")).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+
+    // `coin::balance` is defined in the framework
+    let coin_balance = symbols.use_def_at(&cpath, &Position::new(10, 24)).unwrap();
+    assert!(symbols
+        .file_name_mapping
+        .get(&coin_balance.def_loc.fhash)
+        .unwrap()
+        .as_str()
+        .ends_with("coin.move"));
+    assert!(symbols
+        .rename_edits(&files, &cpath, &Position::new(10, 24), "renamed")
+        .is_err());
+
+    // within the arguments of `coin::balance<AptosCoin>(signer::address_of(account))`
+    let help = symbols
+        .signature_help(&buffer, &cpath, &Position::new(10, 45))
+        .unwrap();
+    assert!(help.signatures[0]
+        .label
+        .starts_with("fun aptos_framework::coin::balance"));
+
+    // `let deposits = ...` after a comment with two emojis
+    let all = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+    let hints = symbols.inlay_hints(&cpath, &all);
+    assert_eq!(hints.len(), 1);
+    assert_eq!(hints[0].position, Position::new(14, 31));
+    assert_eq!(hints[0].label, ": u64");
+
+    // `wallet::balance`, defined after a comment with an emoji
+    let changes = symbols
+        .rename_edits(&files, &cpath, &Position::new(15, 20), "coins")
+        .unwrap();
+    let wallet_url = Url::from_file_path(&cpath).unwrap();
+    assert_eq!(changes[&wallet_url], vec![
+        TextEdit::new(
+            Range::new(Position::new(9, 24), Position::new(9, 31)),
+            "coins".to_string()
+        ),
+        TextEdit::new(
+            Range::new(Position::new(15, 19), Position::new(15, 26)),
+            "coins".to_string()
+        ),
+    ]);
+}
@@ -4,6 +4,7 @@
 use codespan_reporting::files::{Files, SimpleFiles};
 use lsp_types::Position;
 use move_command_line_common::files::FileHash;
+use move_compiler::parser::lexer::{Lexer, Tok};
 use move_ir_types::location::*;
 use move_symbol_pool::Symbol;
 use std::collections::HashMap;
@@ -20,12 +21,55 @@ pub fn get_loc(
         Some(v) => v,
         None => return None,
     };
-    match files.location(*id, pos as usize) {
-        Ok(v) => Some(Position {
-            // we need 0-based column location
-            line: v.line_number as u32 - 1,
-            character: v.column_number as u32 - 1,
-        }),
-        Err(_) => None,
+    // the columns computed by `codespan` count chars rather than UTF-16 code units
+    let text = files.source(*id).ok()?;
+    text.get(..pos as usize)?;
+    Some(offset_to_position(text, pos as usize))
+}
+
+/// Converts a line/character (Position) location in the given text to a byte offset, if the
+/// location is within the text. As in LSP, characters are counted in UTF-16 code units.
+pub fn position_to_offset(text: &str, position: &Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        line_start += text[line_start..].find('\n')? + 1;
+    }
+    let line = text[line_start..].split('\n').next().unwrap_or_default();
+    let mut character = 0;
+    for (idx, c) in line.char_indices() {
+        if character == position.character as usize {
+            return Some(line_start + idx);
+        }
+        character += c.len_utf16();
+    }
+    // the position is either at the end of the line, past it, or within a char
+    (character == position.character as usize).then_some(line_start + line.len())
+}
+
+/// Converts a byte offset in the given text to the line/character (Position) format, where
+/// line/character are 0-based. As in LSP, characters are counted in UTF-16 code units.
+pub fn offset_to_position(text: &str, offset: usize) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+/// Lexes the given Move source, returning its tokens along with their contents and byte offsets.
+/// Lexing stops at the first invalid token, as sources being edited are often not well-formed.
+pub fn tokens(text: &str) -> Vec<(Tok, &str, usize)> {
+    let mut lexer = Lexer::new(text, FileHash::new(text));
+    let mut tokens = vec![];
+    if lexer.advance().is_err() {
+        return tokens;
+    }
+    while lexer.peek() != Tok::EOF {
+        tokens.push((lexer.peek(), lexer.content(), lexer.start_loc()));
+        if lexer.advance().is_err() {
+            break;
+        }
     }
+    tokens
 }
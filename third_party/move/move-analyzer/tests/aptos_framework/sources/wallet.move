@@ -0,0 +1,18 @@
+module wallet::wallet {
+    use std::signer;
+    use aptos_framework::aptos_coin::AptosCoin;
+    use aptos_framework::coin;
+
+    struct Wallet has key {
+        deposits: u64,
+    }
+
+    /* 💰 */ public fun balance(account: &signer): u64 {
+        /* 💰 */ coin::balance<AptosCoin>(signer::address_of(account))
+    }
+
+    public fun total(account: &signer): u64 acquires Wallet {
+        /* 💰💰 */ let deposits = borrow_global<Wallet>(signer::address_of(account)).deposits;
+        deposits + balance(account)
+    }
+}
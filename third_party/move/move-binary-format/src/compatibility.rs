@@ -6,10 +6,10 @@ use crate::{
     errors::{PartialVMError, PartialVMResult},
     file_format::{AbilitySet, StructTypeParameter, Visibility},
     file_format_common::VERSION_5,
-    normalized::Module,
+    normalized::{Field, Module, Type},
 };
-use move_core_types::vm_status::StatusCode;
-use std::collections::BTreeSet;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, vm_status::StatusCode};
+use std::{collections::BTreeSet, fmt};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -71,142 +71,413 @@ impl Compatibility {
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
     pub fn check(&self, old_module: &Module, new_module: &Module) -> PartialVMResult<()> {
-        let mut struct_and_pub_function_linking = true;
-        let mut struct_layout = true;
-        let mut friend_linking = true;
+        if self.report(old_module, new_module).is_empty() {
+            Ok(())
+        } else {
+            Err(PartialVMError::new(
+                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+            ))
+        }
+    }
+
+    /// Returns the changes of `new_module` relative to old module `old_module` which break the
+    /// guarantees checked by this configuration. The update is compatible if there are none.
+    pub fn report(&self, old_module: &Module, new_module: &Module) -> Vec<Incompatibility> {
+        incompatibilities(old_module, new_module)
+            .into_iter()
+            .filter(|incompatibility| {
+                (self.check_struct_and_pub_function_linking
+                    && incompatibility.breaks_struct_and_pub_function_linking())
+                    || (self.check_struct_layout && incompatibility.breaks_struct_layout())
+                    || (self.check_friend_linking && incompatibility.breaks_friend_linking())
+            })
+            .collect()
+    }
+}
+
+/// A change of a module which breaks some of the guarantees checked by `Compatibility`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Incompatibility {
+    /// The address or the name of the module changed.
+    ModuleRenamed,
+    StructRemoved(Identifier),
+    /// Some abilities of the struct were removed.
+    StructAbilitiesRemoved {
+        name: Identifier,
+        removed: AbilitySet,
+    },
+    /// The number of type parameters of the struct changed, a constraint was added to one of
+    /// them, or a phantom declaration was removed.
+    StructTypeParametersChanged(Identifier),
+    StructFieldsChanged {
+        name: Identifier,
+        old: Vec<Field>,
+        new: Vec<Field>,
+    },
+    /// A function exposed by the module (public, friend or entry) was removed.
+    FunctionRemoved {
+        name: Identifier,
+        visibility: Visibility,
+    },
+    /// A function exposed by the module changed in a way existing callers may not link against.
+    FunctionChanged {
+        name: Identifier,
+        visibility: Visibility,
+        change: FunctionChange,
+    },
+    FriendRemoved(ModuleId),
+}
 
-        // module's name and address are unchanged
-        if old_module.address != new_module.address || old_module.name != new_module.name {
-            struct_and_pub_function_linking = false;
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FunctionChange {
+    Visibility(Visibility),
+    /// The function is no longer an entry function, or, for modules of file format versions
+    /// before 5, was made an entry function.
+    Entry {
+        is_entry: bool,
+    },
+    Parameters {
+        old: Vec<Type>,
+        new: Vec<Type>,
+    },
+    Return {
+        old: Vec<Type>,
+        new: Vec<Type>,
+    },
+    /// The number of type parameters of the function changed, or a constraint was added to one
+    /// of them.
+    TypeParameters {
+        old: Vec<AbilitySet>,
+        new: Vec<AbilitySet>,
+    },
+}
+
+impl Incompatibility {
+    /// Whether dependent modules referencing public functions or structs of the module may no
+    /// longer link.
+    pub fn breaks_struct_and_pub_function_linking(&self) -> bool {
+        match self {
+            Incompatibility::ModuleRenamed
+            | Incompatibility::StructRemoved(_)
+            | Incompatibility::StructAbilitiesRemoved { .. }
+            | Incompatibility::StructTypeParametersChanged(_) => true,
+            Incompatibility::FunctionRemoved { visibility, .. }
+            | Incompatibility::FunctionChanged { visibility, .. } => {
+                !matches!(visibility, Visibility::Friend)
+            },
+            Incompatibility::StructFieldsChanged { .. } | Incompatibility::FriendRemoved(_) => {
+                false
+            },
         }
+    }
 
-        // old module's structs are a subset of the new module's structs
-        for (name, old_struct) in &old_module.structs {
-            let new_struct = match new_module.structs.get(name) {
-                Some(new_struct) => new_struct,
-                None => {
-                    // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
-                    // Also, struct layout cannot be guaranteed transitively, because after
-                    // removing the struct, it could be re-added later with a different layout.
-                    struct_and_pub_function_linking = false;
-                    struct_layout = false;
-                    break;
-                },
-            };
-
-            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
-                || !struct_type_parameters_compatibile(
-                    &old_struct.type_parameters,
-                    &new_struct.type_parameters,
-                )
-            {
-                struct_and_pub_function_linking = false;
-            }
-            if new_struct.fields != old_struct.fields {
-                // Fields changed. Code in this module will fail at runtime if it tries to
-                // read a previously published struct value
-                // TODO: this is a stricter definition than required. We could in principle
-                // choose that changing the name (but not position or type) of a field is
-                // compatible. The VM does not care about the name of a field
-                // (it's purely informational), but clients presumably do.
-                struct_layout = false
-            }
+    /// Whether struct values published by the previous version of the module may no longer be
+    /// read.
+    pub fn breaks_struct_layout(&self) -> bool {
+        // after removing a struct, it could be re-added later with a different layout
+        matches!(
+            self,
+            Incompatibility::StructRemoved(_) | Incompatibility::StructFieldsChanged { .. }
+        )
+    }
+
+    /// Whether friend modules referencing friend functions of the module may no longer link.
+    pub fn breaks_friend_linking(&self) -> bool {
+        match self {
+            Incompatibility::FunctionRemoved { visibility, .. }
+            | Incompatibility::FunctionChanged { visibility, .. } => {
+                matches!(visibility, Visibility::Friend)
+            },
+            Incompatibility::FriendRemoved(_) => true,
+            _ => false,
         }
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::ModuleRenamed => {
+                write!(f, "the address or name of the module changed")
+            },
+            Incompatibility::StructRemoved(name) => write!(f, "struct `{}` was removed", name),
+            Incompatibility::StructAbilitiesRemoved { name, removed } => write!(
+                f,
+                "struct `{}` lost abilities `{}`",
+                name,
+                display_abilities(*removed)
+            ),
+            Incompatibility::StructTypeParametersChanged(name) => write!(
+                f,
+                "type parameters of struct `{}` changed: their number changed, a constraint was \
+                 added, or a phantom declaration was removed",
+                name
+            ),
+            Incompatibility::StructFieldsChanged { name, old, new } => write!(
+                f,
+                "fields of struct `{}` changed from `{{ {} }}` to `{{ {} }}`",
+                name,
+                display_fields(old),
+                display_fields(new)
+            ),
+            Incompatibility::FunctionRemoved { name, visibility } => write!(
+                f,
+                "{} function `{}` was removed",
+                display_visibility(*visibility),
+                name
+            ),
+            Incompatibility::FunctionChanged {
+                name,
+                visibility,
+                change,
+            } => {
+                write!(
+                    f,
+                    "{} function `{}`: ",
+                    display_visibility(*visibility),
+                    name
+                )?;
+                match change {
+                    FunctionChange::Visibility(new) => {
+                        write!(f, "visibility changed to `{}`", display_visibility(*new))
+                    },
+                    FunctionChange::Entry { is_entry: true } => {
+                        write!(f, "was made an entry function")
+                    },
+                    FunctionChange::Entry { is_entry: false } => {
+                        write!(f, "is no longer an entry function")
+                    },
+                    FunctionChange::Parameters { old, new } => write!(
+                        f,
+                        "parameters changed from `({})` to `({})`",
+                        display_types(old),
+                        display_types(new)
+                    ),
+                    FunctionChange::Return { old, new } => write!(
+                        f,
+                        "return types changed from `({})` to `({})`",
+                        display_types(old),
+                        display_types(new)
+                    ),
+                    FunctionChange::TypeParameters { old, new } => write!(
+                        f,
+                        "type parameters changed from `<{}>` to `<{}>`",
+                        display_type_parameters(old),
+                        display_type_parameters(new)
+                    ),
+                }
+            },
+            Incompatibility::FriendRemoved(module_id) => write!(
+                f,
+                "friend declaration of `{}` was removed",
+                module_id.short_str_lossless()
+            ),
+        }
+    }
+}
+
+fn display_visibility(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "public",
+        Visibility::Friend => "public(friend)",
+        Visibility::Private => "private",
+    }
+}
+
+fn display_abilities(abilities: AbilitySet) -> String {
+    abilities
+        .into_iter()
+        .map(|ability| format!("{:?}", ability).to_lowercase())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_types(types: &[Type]) -> String {
+    types
+        .iter()
+        .map(|ty| ty.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_fields(fields: &[Field]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name, field.type_))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-        // The modules are considered as compatible function-wise when all the conditions are met:
-        //
-        // - old module's public functions are a subset of the new module's public functions
-        //   (i.e. we cannot remove or change public functions)
-        // - old module's script functions are a subset of the new module's script functions
-        //   (i.e. we cannot remove or change script functions)
-        // - for any friend function that is removed or changed in the old module
-        //   - if the function visibility is upgraded to public, it is OK
-        //   - otherwise, it is considered as incompatible.
-        //
-        // NOTE: it is possible to relax the compatibility checking for a friend function, i.e.,
-        // we can remove/change a friend function if the function is not used by any module in the
-        // friend list. But for simplicity, we decided to go to the more restrictive form now and
-        // we may revisit this in the future.
-        for (name, old_func) in &old_module.exposed_functions {
-            let new_func = match new_module.exposed_functions.get(name) {
-                Some(new_func) => new_func,
-                None => {
-                    if matches!(old_func.visibility, Visibility::Friend) {
-                        friend_linking = false;
-                    } else {
-                        struct_and_pub_function_linking = false;
-                    }
-                    continue;
-                },
-            };
-            let is_vis_compatible = match (old_func.visibility, new_func.visibility) {
-                // public must remain public
-                (Visibility::Public, Visibility::Public) => true,
-                (Visibility::Public, _) => false,
-                // friend can become public or remain friend
-                (Visibility::Friend, Visibility::Public)
-                | (Visibility::Friend, Visibility::Friend) => true,
-                (Visibility::Friend, _) => false,
-                // private can become public or friend, or stay private
-                (Visibility::Private, _) => true,
-            };
-            let is_entry_compatible = if old_module.file_format_version < VERSION_5
-                && new_module.file_format_version < VERSION_5
-            {
-                // if it was public(script), it must remain pubic(script)
-                // if it was not public(script), it _cannot_ become public(script)
-                old_func.is_entry == new_func.is_entry
+fn display_type_parameters(type_parameters: &[AbilitySet]) -> String {
+    type_parameters
+        .iter()
+        .enumerate()
+        .map(|(idx, constraints)| {
+            if *constraints == AbilitySet::EMPTY {
+                format!("T{}", idx)
             } else {
-                // If it was an entry function, it must remain one.
-                // If it was not an entry function, it is allowed to become one.
-                !old_func.is_entry || new_func.is_entry
-            };
-            if !is_vis_compatible
-                || !is_entry_compatible
-                || old_func.parameters != new_func.parameters
-                || old_func.return_ != new_func.return_
-                || !fun_type_parameters_compatibile(
-                    &old_func.type_parameters,
-                    &new_func.type_parameters,
+                format!(
+                    "T{}: {}",
+                    idx,
+                    display_abilities(*constraints).replace(", ", " + ")
                 )
-            {
-                if matches!(old_func.visibility, Visibility::Friend) {
-                    friend_linking = false;
-                } else {
-                    struct_and_pub_function_linking = false;
-                }
             }
-        }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns all the changes of `new_module` relative to old module `old_module` which break some of
+/// the guarantees checked by `Compatibility`.
+pub fn incompatibilities(old_module: &Module, new_module: &Module) -> Vec<Incompatibility> {
+    let mut incompatibilities = vec![];
+
+    // module's name and address are unchanged
+    if old_module.address != new_module.address || old_module.name != new_module.name {
+        incompatibilities.push(Incompatibility::ModuleRenamed);
+    }
+
+    // old module's structs are a subset of the new module's structs
+    for (name, old_struct) in &old_module.structs {
+        let new_struct = match new_module.structs.get(name) {
+            Some(new_struct) => new_struct,
+            None => {
+                // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
+                // Also, struct layout cannot be guaranteed transitively, because after
+                // removing the struct, it could be re-added later with a different layout.
+                incompatibilities.push(Incompatibility::StructRemoved(name.clone()));
+                continue;
+            },
+        };
 
-        // check friend declarations compatibility
-        //
-        // - additions to the list are allowed
-        // - removals are not allowed
-        //
-        let old_friend_module_ids: BTreeSet<_> = old_module.friends.iter().cloned().collect();
-        let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().cloned().collect();
-        if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-            friend_linking = false;
+        if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities) {
+            incompatibilities.push(Incompatibility::StructAbilitiesRemoved {
+                name: name.clone(),
+                removed: old_struct
+                    .abilities
+                    .into_iter()
+                    .filter(|ability| !new_struct.abilities.has_ability(*ability))
+                    .fold(AbilitySet::EMPTY, AbilitySet::add),
+            });
+        }
+        if !struct_type_parameters_compatibile(
+            &old_struct.type_parameters,
+            &new_struct.type_parameters,
+        ) {
+            incompatibilities.push(Incompatibility::StructTypeParametersChanged(name.clone()));
         }
+        if new_struct.fields != old_struct.fields {
+            // Fields changed. Code in this module will fail at runtime if it tries to
+            // read a previously published struct value
+            // TODO: this is a stricter definition than required. We could in principle
+            // choose that changing the name (but not position or type) of a field is
+            // compatible. The VM does not care about the name of a field
+            // (it's purely informational), but clients presumably do.
+            incompatibilities.push(Incompatibility::StructFieldsChanged {
+                name: name.clone(),
+                old: old_struct.fields.clone(),
+                new: new_struct.fields.clone(),
+            });
+        }
+    }
 
-        if self.check_struct_and_pub_function_linking && !struct_and_pub_function_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
+    // The modules are considered as compatible function-wise when all the conditions are met:
+    //
+    // - old module's public functions are a subset of the new module's public functions
+    //   (i.e. we cannot remove or change public functions)
+    // - old module's script functions are a subset of the new module's script functions
+    //   (i.e. we cannot remove or change script functions)
+    // - for any friend function that is removed or changed in the old module
+    //   - if the function visibility is upgraded to public, it is OK
+    //   - otherwise, it is considered as incompatible.
+    //
+    // NOTE: it is possible to relax the compatibility checking for a friend function, i.e.,
+    // we can remove/change a friend function if the function is not used by any module in the
+    // friend list. But for simplicity, we decided to go to the more restrictive form now and
+    // we may revisit this in the future.
+    for (name, old_func) in &old_module.exposed_functions {
+        let new_func = match new_module.exposed_functions.get(name) {
+            Some(new_func) => new_func,
+            None => {
+                incompatibilities.push(Incompatibility::FunctionRemoved {
+                    name: name.clone(),
+                    visibility: old_func.visibility,
+                });
+                continue;
+            },
+        };
+        let mut changes = vec![];
+        let is_vis_compatible = match (old_func.visibility, new_func.visibility) {
+            // public must remain public
+            (Visibility::Public, Visibility::Public) => true,
+            (Visibility::Public, _) => false,
+            // friend can become public or remain friend
+            (Visibility::Friend, Visibility::Public) | (Visibility::Friend, Visibility::Friend) => {
+                true
+            },
+            (Visibility::Friend, _) => false,
+            // private can become public or friend, or stay private
+            (Visibility::Private, _) => true,
+        };
+        if !is_vis_compatible {
+            changes.push(FunctionChange::Visibility(new_func.visibility));
         }
-        if self.check_struct_layout && !struct_layout {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
+        let is_entry_compatible = if old_module.file_format_version < VERSION_5
+            && new_module.file_format_version < VERSION_5
+        {
+            // if it was public(script), it must remain pubic(script)
+            // if it was not public(script), it _cannot_ become public(script)
+            old_func.is_entry == new_func.is_entry
+        } else {
+            // If it was an entry function, it must remain one.
+            // If it was not an entry function, it is allowed to become one.
+            !old_func.is_entry || new_func.is_entry
+        };
+        if !is_entry_compatible {
+            changes.push(FunctionChange::Entry {
+                is_entry: new_func.is_entry,
+            });
         }
-        if self.check_friend_linking && !friend_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
+        if old_func.parameters != new_func.parameters {
+            changes.push(FunctionChange::Parameters {
+                old: old_func.parameters.clone(),
+                new: new_func.parameters.clone(),
+            });
+        }
+        if old_func.return_ != new_func.return_ {
+            changes.push(FunctionChange::Return {
+                old: old_func.return_.clone(),
+                new: new_func.return_.clone(),
+            });
         }
+        if !fun_type_parameters_compatibile(&old_func.type_parameters, &new_func.type_parameters) {
+            changes.push(FunctionChange::TypeParameters {
+                old: old_func.type_parameters.clone(),
+                new: new_func.type_parameters.clone(),
+            });
+        }
+        incompatibilities.extend(changes.into_iter().map(|change| {
+            Incompatibility::FunctionChanged {
+                name: name.clone(),
+                visibility: old_func.visibility,
+                change,
+            }
+        }));
+    }
 
-        Ok(())
+    // check friend declarations compatibility
+    //
+    // - additions to the list are allowed
+    // - removals are not allowed
+    //
+    let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().collect();
+    for friend in &old_module.friends {
+        if !new_friend_module_ids.contains(friend) {
+            incompatibilities.push(Incompatibility::FriendRemoved(friend.clone()));
+        }
     }
+
+    incompatibilities
 }
 
 // When upgrading, the new abilities must be a superset of the old abilities.
@@ -1,9 +1,14 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*, normalized};
+use crate::{
+    compatibility::{Compatibility, FunctionChange, Incompatibility},
+    file_format::*,
+    file_format_common::VERSION_MAX,
+    normalized,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
-use std::convert::TryFrom;
+use std::{collections::BTreeMap, convert::TryFrom};
 
 fn mk_module(vis: u8) -> normalized::Module {
     let (visibility, is_entry) = if vis == Visibility::DEPRECATED_SCRIPT {
@@ -114,3 +119,94 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+fn mk_normalized_module(
+    fields: Vec<(&str, normalized::Type)>,
+    abilities: AbilitySet,
+    functions: Vec<(&str, Visibility, Vec<normalized::Type>)>,
+) -> normalized::Module {
+    let fields = fields
+        .into_iter()
+        .map(|(name, type_)| normalized::Field {
+            name: Identifier::new(name).unwrap(),
+            type_,
+        })
+        .collect();
+    normalized::Module {
+        file_format_version: VERSION_MAX,
+        address: AccountAddress::ZERO,
+        name: Identifier::new("M").unwrap(),
+        friends: vec![],
+        structs: BTreeMap::from([(Identifier::new("S").unwrap(), normalized::Struct {
+            abilities,
+            type_parameters: vec![],
+            fields,
+        })]),
+        exposed_functions: functions
+            .into_iter()
+            .map(|(name, visibility, parameters)| {
+                (Identifier::new(name).unwrap(), normalized::Function {
+                    visibility,
+                    is_entry: false,
+                    type_parameters: vec![],
+                    parameters,
+                    return_: vec![],
+                })
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn report_incompatibilities() {
+    let old_module = mk_normalized_module(
+        vec![("a", normalized::Type::U64)],
+        AbilitySet::EMPTY | Ability::Copy | Ability::Drop,
+        vec![
+            ("f", Visibility::Public, vec![normalized::Type::U64]),
+            ("g", Visibility::Friend, vec![]),
+        ],
+    );
+    let new_module = mk_normalized_module(
+        vec![("a", normalized::Type::U64), ("b", normalized::Type::Bool)],
+        AbilitySet::EMPTY | Ability::Drop,
+        vec![(
+            "f",
+            Visibility::Public,
+            vec![normalized::Type::U64, normalized::Type::Bool],
+        )],
+    );
+
+    let report = Compatibility::full_check().report(&old_module, &new_module);
+    let messages: Vec<_> = report.iter().map(|inc| inc.to_string()).collect();
+    assert_eq!(messages, vec![
+        "struct `S` lost abilities `copy`",
+        "fields of struct `S` changed from `{ a: u64 }` to `{ a: u64, b: bool }`",
+        "public function `f`: parameters changed from `(u64)` to `(u64, bool)`",
+        "public(friend) function `g` was removed",
+    ]);
+    assert!(matches!(
+        &report[2],
+        Incompatibility::FunctionChanged {
+            change: FunctionChange::Parameters { .. },
+            ..
+        }
+    ));
+    assert!(Compatibility::full_check()
+        .check(&old_module, &new_module)
+        .is_err());
+
+    // friend functions are not checked when friends are treated as private
+    let report = Compatibility::new(true, true, false).report(&old_module, &new_module);
+    assert_eq!(report.len(), 3);
+
+    // only the layout of structs is checked
+    let report = Compatibility::new(false, true, false).report(&old_module, &new_module);
+    assert!(matches!(&report[..], [
+        Incompatibility::StructFieldsChanged { .. }
+    ]));
+
+    assert!(Compatibility::full_check()
+        .report(&old_module, &old_module)
+        .is_empty());
+}
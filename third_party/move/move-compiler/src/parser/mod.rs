@@ -205,3 +205,14 @@ fn parse_file(
     files.insert(file_hash, (fname, source_buffer));
     Ok((defs, comments, diags, file_hash))
 }
+
+/// Parses the given source text of a file, checking it the same way as when compiling the file.
+/// This is meant for tools working on source text, which need to ensure it's well-formed.
+pub fn parse_source(
+    compilation_env: &mut CompilationEnv,
+    file_hash: FileHash,
+    source: &str,
+) -> Result<Vec<parser::ast::Definition>, Diagnostics> {
+    verify_string(file_hash, source)?;
+    parse_file_string(compilation_env, file_hash, source).map(|(defs, _)| defs)
+}
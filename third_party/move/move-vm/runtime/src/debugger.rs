@@ -0,0 +1,151 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks to observe and pause the interpreter before each instruction, used to implement
+//! debuggers on top of the VM. Like the rest of the debugging functionality, this is only
+//! included in debug builds, or with the `debugging` feature.
+
+use crate::{
+    interpreter::Interpreter,
+    loader::{Function, Loader},
+};
+use move_binary_format::file_format::FunctionDefinitionIndex;
+use move_core_types::language_storage::ModuleId;
+use move_vm_types::{
+    loaded_data::runtime_types::Type,
+    values::{self, Locals},
+};
+use once_cell::sync::Lazy;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+/// Receives control before the interpreter executes an instruction. The execution is paused
+/// until `on_instruction` returns.
+pub trait DebugHook: Send {
+    fn on_instruction(&mut self, state: &InterpreterState);
+}
+
+static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+static DEBUG_HOOK: Lazy<Mutex<Option<Box<dyn DebugHook>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Installs the hook called before every instruction executed by any VM of the process, or
+/// removes the current one.
+pub fn set_debug_hook(hook: Option<Box<dyn DebugHook>>) {
+    let mut current = DEBUG_HOOK.lock().unwrap();
+    HOOK_INSTALLED.store(hook.is_some(), Ordering::SeqCst);
+    *current = hook;
+}
+
+/// A function on the call stack of the interpreter.
+#[derive(Clone, Debug)]
+pub struct FrameInfo {
+    /// `None` for scripts.
+    pub module_id: Option<ModuleId>,
+    pub function_name: String,
+    pub function_index: FunctionDefinitionIndex,
+    /// Offset of the instruction about to be executed, or for callers, of the call instruction.
+    pub pc: u16,
+    pub type_arguments: Vec<String>,
+    /// Values of the parameters followed by the other locals, `None` if unavailable, e.g. moved.
+    pub locals: Vec<Option<String>>,
+}
+
+/// State of the interpreter before the execution of an instruction.
+pub struct InterpreterState<'a> {
+    function: &'a Function,
+    ty_args: &'a [Type],
+    locals: &'a Locals,
+    pc: u16,
+    loader: &'a Loader,
+    interp: &'a Interpreter,
+}
+
+impl<'a> InterpreterState<'a> {
+    pub fn module_id(&self) -> Option<&ModuleId> {
+        self.function.module_id()
+    }
+
+    pub fn function_index(&self) -> FunctionDefinitionIndex {
+        self.function.index()
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.pc
+    }
+
+    /// Number of frames on the call stack, including the current one.
+    pub fn depth(&self) -> usize {
+        self.interp.call_stack_frames().len() + 1
+    }
+
+    /// Describes the call stack, from the current frame to the outermost one. This is more
+    /// expensive than the other accessors, as all locals are rendered.
+    pub fn frames(&self) -> Vec<FrameInfo> {
+        let mut frames = vec![self.frame_info(self.function, self.ty_args, self.locals, self.pc)];
+        for (function, ty_args, locals, pc) in self.interp.call_stack_frames().rev() {
+            frames.push(self.frame_info(function, ty_args, locals, pc));
+        }
+        frames
+    }
+
+    fn frame_info(
+        &self,
+        function: &Function,
+        ty_args: &[Type],
+        locals: &Locals,
+        pc: u16,
+    ) -> FrameInfo {
+        let type_arguments = ty_args
+            .iter()
+            .map(|ty| match self.loader.type_to_type_tag(ty) {
+                Ok(tag) => tag.to_string(),
+                Err(_) => format!("{:?}", ty),
+            })
+            .collect();
+        let locals = (0..function.local_count())
+            .map(|idx| {
+                if locals.is_invalid(idx).unwrap_or(true) {
+                    return None;
+                }
+                let value = locals.copy_loc(idx).ok()?;
+                let mut buf = String::new();
+                values::debug::print_value(&mut buf, &value).ok()?;
+                Some(buf)
+            })
+            .collect();
+        FrameInfo {
+            module_id: function.module_id().cloned(),
+            function_name: function.name().to_string(),
+            function_index: function.index(),
+            pc,
+            type_arguments,
+            locals,
+        }
+    }
+}
+
+pub(crate) fn on_instruction(
+    function: &Function,
+    ty_args: &[Type],
+    locals: &Locals,
+    pc: u16,
+    loader: &Loader,
+    interp: &Interpreter,
+) {
+    if !HOOK_INSTALLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Some(hook) = DEBUG_HOOK.lock().unwrap().as_mut() {
+        hook.on_instruction(&InterpreterState {
+            function,
+            ty_args,
+            locals,
+            pc,
+            loader,
+            interp,
+        });
+    }
+}
@@ -143,6 +143,14 @@ impl Interpreter {
                     .map_err(|err| self.maybe_core_dump(err, &current_frame))?;
             match exit_code {
                 ExitCode::Return => {
+                    gas_meter
+                        .charge_return_values(
+                            self.operand_stack
+                                .last_n(current_frame.function.return_type_count())
+                                .map_err(|e| set_err_info!(current_frame, e))?,
+                        )
+                        .map_err(|e| set_err_info!(current_frame, e))?;
+
                     let non_ref_vals = current_frame
                         .locals
                         .drop_all_values()
@@ -787,6 +795,22 @@ impl Interpreter {
         Ok(())
     }
 
+    /// Returns the frames of the callers of the current function, from the outermost one.
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn call_stack_frames(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (&Function, &[Type], &Locals, u16)> + DoubleEndedIterator
+    {
+        self.call_stack.0.iter().map(|frame| {
+            (
+                frame.function.as_ref(),
+                frame.ty_args.as_slice(),
+                &frame.locals,
+                frame.pc,
+            )
+        })
+    }
+
     /// Generate a string which is the status of the interpreter: call stack, current bytecode
     /// stream, locals and operand stack.
     ///
@@ -1767,6 +1791,7 @@ impl Frame {
             for instruction in &code[self.pc as usize..] {
                 trace!(
                     &self.function,
+                    &self.ty_args,
                     &self.locals,
                     self.pc,
                     instruction,
@@ -26,6 +26,8 @@ pub mod config;
 // Only include debugging functionality in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
 mod debug;
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub mod debugger;
 
 #[cfg(test)]
 mod unit_tests;
@@ -6,13 +6,14 @@
 use crate::debug::DebugContext;
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use crate::{
+    debugger,
     interpreter::Interpreter,
     loader::{Function, Loader},
 };
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use ::{
     move_binary_format::file_format::Bytecode,
-    move_vm_types::values::Locals,
+    move_vm_types::{loaded_data::runtime_types::Type, values::Locals},
     once_cell::sync::Lazy,
     std::{
         env,
@@ -61,6 +62,7 @@ static DEBUG_CONTEXT: Lazy<Mutex<DebugContext>> = Lazy::new(|| Mutex::new(DebugC
 #[cfg(any(debug_assertions, feature = "debugging"))]
 pub(crate) fn trace(
     function_desc: &Function,
+    ty_args: &[Type],
     locals: &Locals,
     pc: u16,
     instr: &Bytecode,
@@ -86,15 +88,25 @@ pub(crate) fn trace(
             .unwrap()
             .debug_loop(function_desc, locals, pc, instr, loader, interp);
     }
+    debugger::on_instruction(function_desc, ty_args, locals, pc, loader, interp);
 }
 
 #[macro_export]
 macro_rules! trace {
-    ($function_desc:expr, $locals:expr, $pc:expr, $instr:tt, $resolver:expr, $interp:expr) => {
+    (
+        $function_desc:expr,
+        $ty_args:expr,
+        $locals:expr,
+        $pc:expr,
+        $instr:tt,
+        $resolver:expr,
+        $interp:expr
+    ) => {
         // Only include this code in debug releases
         #[cfg(any(debug_assertions, feature = "debugging"))]
         $crate::tracing::trace(
             &$function_desc,
+            $ty_args,
             $locals,
             $pc,
             &$instr,
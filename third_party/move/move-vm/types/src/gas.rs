@@ -298,6 +298,17 @@ pub trait GasMeter {
         &mut self,
         locals: impl Iterator<Item = impl ValueView> + Clone,
     ) -> PartialVMResult<()>;
+
+    /// Called right before a (non-native) function returns, with the values it is returning.
+    ///
+    /// This is not used to charge gas, but allows gas meter adapters, like tracers, to observe
+    /// the return values of every call frame. Does nothing by default.
+    fn charge_return_values(
+        &mut self,
+        _ret_vals: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        Ok(())
+    }
 }
 
 /// A dummy gas meter that does not meter anything.
@@ -0,0 +1,58 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Name of the file configuring the formatter, in the root directory of a package.
+pub const CONFIG_FILE_NAME: &str = "movefmt.toml";
+
+/// How `use` declarations are ordered.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportGrouping {
+    /// Keep declarations as written.
+    Preserve,
+    /// Sort declarations within blocks of declarations separated by blank lines, as well as the
+    /// members imported by each declaration.
+    #[default]
+    Sort,
+    /// Sort all consecutive declarations, and group them by address, with blank lines between
+    /// groups.
+    ByAddress,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatConfig {
+    /// Lines longer than this are broken at the delimited lists they contain, if any.
+    pub max_width: usize,
+    /// Number of spaces per indentation level.
+    pub indent_width: usize,
+    pub imports: ImportGrouping,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            max_width: 100,
+            indent_width: 4,
+            imports: ImportGrouping::default(),
+        }
+    }
+}
+
+impl FormatConfig {
+    /// Loads the configuration of the package in the given directory, which is the default one if
+    /// the package has no configuration file.
+    pub fn load(package_dir: &Path) -> Result<Self> {
+        let path = package_dir.join(CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&contents).with_context(|| format!("Invalid {}", path.display()))
+    }
+}
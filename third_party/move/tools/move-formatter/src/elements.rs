@@ -0,0 +1,124 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Splits a source into tokens and comments, recording the whitespace between them, which is all
+//! the formatter needs to know about the source besides it being syntactically valid.
+
+use anyhow::{anyhow, Result};
+use move_command_line_common::files::FileHash;
+use move_compiler::parser::lexer::{Lexer, Tok};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Kind {
+    Token(Tok),
+    LineComment,
+    BlockComment,
+}
+
+#[derive(Clone, Debug)]
+pub struct Element<'a> {
+    pub kind: Kind,
+    pub text: &'a str,
+    /// Number of line breaks between the previous element and this one.
+    pub newlines: usize,
+    /// Whether the previous element and this one are separated by whitespace.
+    pub spaced: bool,
+}
+
+impl<'a> Element<'a> {
+    pub fn token(tok: Tok, text: &'a str) -> Self {
+        Self {
+            kind: Kind::Token(tok),
+            text,
+            newlines: 0,
+            spaced: false,
+        }
+    }
+
+    pub fn tok(&self) -> Option<Tok> {
+        match self.kind {
+            Kind::Token(tok) => Some(tok),
+            Kind::LineComment | Kind::BlockComment => None,
+        }
+    }
+
+    pub fn is(&self, tok: Tok) -> bool {
+        self.tok() == Some(tok)
+    }
+
+    pub fn is_comment(&self) -> bool {
+        self.tok().is_none()
+    }
+
+    /// Text of the element as printed. The lexer includes the space separating `&mut` from the
+    /// type or expression it applies to in the token.
+    pub fn printed_text(&self) -> &'a str {
+        self.text.trim_end()
+    }
+}
+
+/// Splits the given source into tokens and comments.
+pub fn elements(source: &str) -> Result<Vec<Element<'_>>> {
+    let mut lexer = Lexer::new(source, FileHash::new(source));
+    let mut elements = vec![];
+    let mut end = 0;
+    lexer.advance().map_err(|_| anyhow!("Invalid token"))?;
+    while lexer.peek() != Tok::EOF {
+        let start = lexer.start_loc();
+        let mut token = Element::token(lexer.peek(), lexer.content());
+        (token.newlines, token.spaced) = push_comments(&source[end..start], &mut elements);
+        end = start + token.text.len();
+        elements.push(token);
+        lexer.advance().map_err(|_| anyhow!("Invalid token"))?;
+    }
+    push_comments(&source[end..], &mut elements);
+    Ok(elements)
+}
+
+/// Pushes the comments found in whitespace separating two tokens, and returns the number of line
+/// breaks and whether there is any whitespace after the last comment.
+fn push_comments<'a>(mut text: &'a str, elements: &mut Vec<Element<'a>>) -> (usize, bool) {
+    loop {
+        let rest = text.trim_start_matches(|c| matches!(c, ' ' | '\t' | '\r' | '\n'));
+        let whitespace = &text[..text.len() - rest.len()];
+        let newlines = whitespace.matches('\n').count();
+        let spaced = !whitespace.is_empty();
+        let (kind, len) = if rest.starts_with("//") {
+            (Kind::LineComment, rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            (Kind::BlockComment, block_comment_len(rest))
+        } else {
+            return (newlines, spaced);
+        };
+        elements.push(Element {
+            kind,
+            text: rest[..len].trim_end(),
+            newlines,
+            spaced,
+        });
+        text = &rest[len..];
+    }
+}
+
+/// Returns the length of the block comment at the start of the given text, block comments being
+/// possibly nested.
+fn block_comment_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let mut depth = 0;
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx..].starts_with(b"/*") {
+            depth += 1;
+            idx += 2;
+        } else if bytes[idx..].starts_with(b"*/") {
+            depth -= 1;
+            idx += 2;
+            if depth == 0 {
+                return idx;
+            }
+        } else {
+            idx += 1;
+        }
+    }
+    text.len()
+}
@@ -0,0 +1,131 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Ordering of `use` declarations. Only declarations on lines of their own, without comments or
+//! attributes, are moved, so that comments stay next to the code they describe.
+
+use crate::{config::ImportGrouping, elements::Element};
+use move_compiler::parser::lexer::Tok;
+use std::ops::Range;
+
+/// Reorders the `use` declarations of a source, and the members they import.
+pub fn reorder_imports(elements: Vec<Element<'_>>, grouping: ImportGrouping) -> Vec<Element<'_>> {
+    if grouping == ImportGrouping::Preserve {
+        return elements;
+    }
+    let mut result = Vec::with_capacity(elements.len());
+    let mut idx = 0;
+    while idx < elements.len() {
+        let decls = use_declarations(&elements, idx, grouping);
+        let end = match decls.last() {
+            Some(decl) => decl.end,
+            None => {
+                result.push(elements[idx].clone());
+                idx += 1;
+                continue;
+            },
+        };
+        let newlines = elements[idx].newlines;
+        let mut sorted: Vec<_> = decls
+            .into_iter()
+            .map(|decl| sort_members(&elements[decl]))
+            .collect();
+        sorted.sort_by_cached_key(|decl| sort_key(decl, grouping));
+        for (i, mut decl) in sorted.into_iter().enumerate() {
+            decl[0].newlines = if i == 0 {
+                newlines
+            } else if grouping == ImportGrouping::ByAddress
+                && result_address(&result) != Some(decl[1].text)
+            {
+                2
+            } else {
+                1
+            };
+            result.extend(decl);
+        }
+        idx = end;
+    }
+    result
+}
+
+/// Returns the address of the last declaration of the given elements, which are then known to end
+/// with a declaration.
+fn result_address<'a>(elements: &[Element<'a>]) -> Option<&'a str> {
+    let start = elements.iter().rposition(|e| e.is(Tok::Use))?;
+    elements.get(start + 1).map(|e| e.text)
+}
+
+/// Returns the ranges of the consecutive `use` declarations that can be reordered, starting with
+/// the given element.
+fn use_declarations(
+    elements: &[Element<'_>],
+    start: usize,
+    grouping: ImportGrouping,
+) -> Vec<Range<usize>> {
+    let mut decls = vec![];
+    let mut idx = start;
+    while idx > 0 && idx < elements.len() && elements[idx].is(Tok::Use) {
+        // declarations with attributes are left in place
+        if elements[idx].newlines == 0 || elements[idx - 1].is(Tok::RBracket) {
+            break;
+        }
+        // blank lines separate blocks of declarations, unless grouping by address
+        if !decls.is_empty() && elements[idx].newlines > 1 && grouping != ImportGrouping::ByAddress
+        {
+            break;
+        }
+        let end = match elements[idx..]
+            .iter()
+            .position(|e| e.is_comment() || e.is(Tok::Semicolon))
+        {
+            Some(len) if elements[idx + len].is(Tok::Semicolon) => idx + len + 1,
+            _ => break,
+        };
+        // the declaration must be the last thing on its line
+        if elements.get(end).map_or(false, |e| e.newlines == 0) {
+            break;
+        }
+        decls.push(idx..end);
+        idx = end;
+    }
+    decls
+}
+
+fn sort_key(decl: &[Element<'_>], grouping: ImportGrouping) -> (bool, String) {
+    // when grouping by address, the standard library comes first
+    let not_std = grouping == ImportGrouping::ByAddress && decl[1].text != "std";
+    let path = decl[1..].iter().map(|e| e.printed_text()).collect();
+    (not_std, path)
+}
+
+/// Sorts the members imported by a declaration, `Self` first, keeping the layout of the list.
+fn sort_members<'a>(decl: &[Element<'a>]) -> Vec<Element<'a>> {
+    let close = decl.len() - 2;
+    let open = match decl.iter().position(|e| e.is(Tok::LBrace)) {
+        Some(open) if decl[close].is(Tok::RBrace) => open,
+        _ => return decl.to_vec(),
+    };
+    let list = &decl[open + 1..close];
+    let commas: Vec<_> = list.iter().filter(|e| e.is(Tok::Comma)).collect();
+    let mut members: Vec<_> = list.split(|e| e.is(Tok::Comma)).collect();
+    if members.last().map_or(false, |member| member.is_empty()) {
+        members.pop();
+    }
+    let layout: Vec<_> = members
+        .iter()
+        .map(|member| (member[0].newlines, member[0].spaced))
+        .collect();
+    members.sort_by_key(|member| (member[0].text != "Self", member[0].text));
+
+    let mut result = decl[..=open].to_vec();
+    for (i, member) in members.into_iter().enumerate() {
+        let mut member = member.to_vec();
+        (member[0].newlines, member[0].spaced) = layout[i];
+        result.extend(member);
+        if let Some(comma) = commas.get(i) {
+            result.push((*comma).clone());
+        }
+    }
+    result.extend_from_slice(&decl[close..]);
+    result
+}
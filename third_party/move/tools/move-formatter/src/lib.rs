@@ -0,0 +1,59 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Formatter of Move sources. Formatting normalizes the whitespace between tokens and comments,
+//! indents lines according to the nesting of delimiters, breaks lines longer than the configured
+//! width at the delimited lists they contain, and orders `use` declarations. Line breaks are
+//! otherwise kept as written, and comments are kept in place.
+
+mod config;
+mod elements;
+mod imports;
+mod printer;
+
+use anyhow::{bail, Result};
+use elements::Element;
+use move_command_line_common::files::FileHash;
+use move_compiler::{
+    diagnostics::report_diagnostics_to_buffer, parser::parse_source, shared::CompilationEnv, Flags,
+};
+use move_symbol_pool::Symbol;
+use std::collections::HashMap;
+
+pub use config::{FormatConfig, ImportGrouping, CONFIG_FILE_NAME};
+
+/// Formats the source of a Move file. The source must be syntactically valid, the file name being
+/// only used to report errors.
+pub fn format_source(file_name: &str, source: &str, config: &FormatConfig) -> Result<String> {
+    check_syntax(file_name, source)?;
+    let mut elements = imports::reorder_imports(elements::elements(source)?, config.imports);
+    let formatted = printer::print(&mut elements, config);
+
+    // formatting must only change the whitespace between elements
+    let printed = |elements: &[Element<'_>]| {
+        elements
+            .iter()
+            .map(|e| (e.kind, e.printed_text().to_string()))
+            .collect::<Vec<_>>()
+    };
+    if printed(&elements::elements(&formatted)?) != printed(&elements)
+        || check_syntax(file_name, &formatted).is_err()
+    {
+        bail!(
+            "Failed to format {}: formatting would change its code",
+            file_name
+        );
+    }
+    Ok(formatted)
+}
+
+fn check_syntax(file_name: &str, source: &str) -> Result<()> {
+    let file_hash = FileHash::new(source);
+    let mut env = CompilationEnv::new(Flags::empty());
+    if let Err(diags) = parse_source(&mut env, file_hash, source) {
+        let files = HashMap::from([(file_hash, (Symbol::from(file_name), source.to_string()))]);
+        let report = report_diagnostics_to_buffer(&files, diags);
+        bail!("{}", String::from_utf8_lossy(&report));
+    }
+    Ok(())
+}
@@ -0,0 +1,413 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Printing of tokens and comments with canonical whitespace. Line breaks are kept as written,
+//! except that lines which are too long are broken at the delimited lists they contain.
+
+use crate::{
+    config::FormatConfig,
+    elements::{Element, Kind},
+};
+use move_command_line_common::files::FileHash;
+use move_compiler::parser::lexer::{Lexer, Tok};
+
+/// Role of a token whose spacing depends on the context it appears in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Role {
+    Other,
+    /// Prefix operator, e.g. `&x`, `*x` or `!x`
+    Unary,
+    /// Infix operator, e.g. `x & y`, `x * y` or `x || y`
+    Binary,
+    /// Angle bracket delimiting type arguments or parameters, e.g. `vector<u8>`
+    TypeArgs,
+    /// `!` of a macro call, e.g. `assert!(...)`
+    Macro,
+    /// `|` opening the parameters of a lambda or function type, e.g. `|x| x + 1`
+    LambdaOpen,
+    /// `|` closing the parameters of a lambda or function type
+    LambdaClose,
+}
+
+/// Elements printed on a line.
+struct Line {
+    first: usize,
+    last: usize,
+    width: usize,
+}
+
+/// Opening delimiter enclosing the element being printed.
+struct Opener {
+    /// Indentation level of the line the delimiter is on
+    indent: usize,
+    /// Whether the delimiter encloses the condition of an `if` or a `while`
+    condition: bool,
+}
+
+/// Prints the given elements, breaking the lines longer than the maximum width when possible,
+/// which may insert trailing commas.
+pub fn print(elements: &mut Vec<Element<'_>>, config: &FormatConfig) -> String {
+    loop {
+        let roles = roles(elements);
+        let (text, lines) = print_lines(elements, &roles, config);
+        if !break_long_line(elements, &roles, &lines, config.max_width) {
+            return text;
+        }
+    }
+}
+
+fn print_lines(
+    elements: &[Element<'_>],
+    roles: &[Role],
+    config: &FormatConfig,
+) -> (String, Vec<Line>) {
+    let mut text = String::new();
+    let mut lines: Vec<Line> = vec![];
+    let mut openers: Vec<Opener> = vec![];
+    let mut indent = 0;
+    let mut last_token: Option<usize> = None;
+    // whether the last token ends the condition of an `if` or a `while`, or is an `else`, so that
+    // a branch starting on the next line is indented
+    let mut after_condition = false;
+    let mut in_use = false;
+    for (idx, element) in elements.iter().enumerate() {
+        if idx == 0 || element.newlines > 0 || elements[idx - 1].kind == Kind::LineComment {
+            if idx > 0 {
+                text.push('\n');
+                // at most one blank line, and none at the start or end of a delimited list
+                let after_opener = last_token == Some(idx - 1) && is_opener(&elements[idx - 1]);
+                if element.newlines > 1 && !after_opener && !is_closer(element) {
+                    text.push('\n');
+                }
+            }
+            indent = match openers.last() {
+                Some(opener) if is_closer(element) => opener.indent,
+                opener => {
+                    let continued = last_token.map_or(false, |last| {
+                        roles[last] == Role::Binary || elements[last].is(Tok::Equal)
+                    }) || roles[idx] == Role::Binary
+                        || element.is(Tok::Period)
+                        || element.is(Tok::As)
+                        || (after_condition && !element.is(Tok::LBrace));
+                    opener.map_or(0, |opener| opener.indent + 1) + usize::from(continued)
+                },
+            };
+            text.push_str(&" ".repeat(indent * config.indent_width));
+            lines.push(Line {
+                first: idx,
+                last: idx,
+                width: 0,
+            });
+        } else if spaced(elements, roles, idx, in_use) {
+            text.push(' ');
+        }
+        text.push_str(element.printed_text());
+        let line_start = text.rfind('\n').map_or(0, |pos| pos + 1);
+        let line = lines
+            .last_mut()
+            .expect("a line is started with the first element");
+        line.last = idx;
+        line.width = text[line_start..].chars().count();
+
+        let tok = match element.tok() {
+            Some(tok) => tok,
+            None => continue,
+        };
+        after_condition = false;
+        match tok {
+            Tok::LParen | Tok::LBracket | Tok::LBrace => openers.push(Opener {
+                indent,
+                condition: tok == Tok::LParen
+                    && last_token.map_or(false, |last| {
+                        elements[last].is(Tok::If) || elements[last].is(Tok::While)
+                    }),
+            }),
+            Tok::RParen | Tok::RBracket | Tok::RBrace => {
+                after_condition = openers.pop().map_or(false, |opener| opener.condition)
+            },
+            Tok::Else => after_condition = true,
+            Tok::Use => in_use = true,
+            Tok::Semicolon => in_use = false,
+            _ => (),
+        }
+        last_token = Some(idx);
+    }
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    (text, lines)
+}
+
+/// Whether a space separates the given element from the previous one, on the same line.
+fn spaced(elements: &[Element<'_>], roles: &[Role], idx: usize, in_use: bool) -> bool {
+    let (prev, next) = (&elements[idx - 1], &elements[idx]);
+    let (prev_role, next_role) = (roles[idx - 1], roles[idx]);
+    let (prev_tok, next_tok) = match (prev.tok(), next.tok()) {
+        (Some(prev_tok), Some(next_tok)) => (prev_tok, next_tok),
+        // block comments may be attached to the code they describe
+        _ => {
+            return next.spaced
+                || (prev.kind != Kind::BlockComment && next.kind != Kind::BlockComment)
+        },
+    };
+    let space_after = !(matches!(
+        prev_tok,
+        Tok::LParen
+            | Tok::LBracket
+            | Tok::Period
+            | Tok::PeriodPeriod
+            | Tok::ColonColon
+            | Tok::AtSign
+            | Tok::NumSign
+    ) || matches!(prev_role, Role::Unary | Role::Macro | Role::LambdaOpen)
+        || (prev_role == Role::TypeArgs && prev_tok == Tok::Less)
+        || (in_use && prev_tok == Tok::LBrace));
+    let space = space_after
+        && match next_tok {
+            Tok::Comma
+            | Tok::Semicolon
+            | Tok::RParen
+            | Tok::RBracket
+            | Tok::Period
+            | Tok::PeriodPeriod
+            | Tok::ColonColon
+            | Tok::Colon => false,
+            Tok::Less | Tok::Greater | Tok::GreaterGreater => next_role != Role::TypeArgs,
+            Tok::Exclaim => next_role != Role::Macro,
+            Tok::Pipe => next_role != Role::LambdaClose,
+            // calls, and `public(friend)`
+            Tok::LParen => {
+                !(matches!(prev_tok, Tok::Identifier | Tok::Public) || prev_role == Role::TypeArgs)
+            },
+            // indexing
+            Tok::LBracket => {
+                !(matches!(prev_tok, Tok::Identifier | Tok::RParen | Tok::RBracket)
+                    || prev_role == Role::TypeArgs)
+            },
+            Tok::RBrace => !(in_use || prev_tok == Tok::LBrace),
+            _ => true,
+        };
+    space || merged(prev.printed_text(), next.printed_text())
+}
+
+/// Whether two tokens printed without space between them would be lexed differently, e.g. `&`
+/// followed by `&x`.
+fn merged(prev: &str, next: &str) -> bool {
+    let text = format!("{}{}", prev, next);
+    let mut lexer = Lexer::new(&text, FileHash::new(&text));
+    lexer.advance().is_err() || lexer.content().trim_end().len() != prev.len()
+}
+
+/// Determines the role of each token.
+fn roles(elements: &[Element<'_>]) -> Vec<Role> {
+    let mut roles = vec![Role::Other; elements.len()];
+    let mut last: Option<(usize, Tok)> = None;
+    let mut type_args = 0;
+    let mut lambda_params = false;
+    for (idx, element) in elements.iter().enumerate() {
+        let tok = match element.tok() {
+            Some(tok) => tok,
+            None => continue,
+        };
+        let after_operand = last.map_or(false, |(last, last_tok)| {
+            ends_operand(last_tok) || (roles[last] == Role::TypeArgs && last_tok != Tok::Less)
+        });
+        // as in the parser, a `<` right after a name starts type arguments
+        let after_name =
+            matches!(last, Some((last, Tok::Identifier)) if last + 1 == idx) && !element.spaced;
+        roles[idx] = match tok {
+            Tok::Less if after_name => {
+                type_args += 1;
+                Role::TypeArgs
+            },
+            Tok::Greater if type_args > 0 => {
+                type_args -= 1;
+                Role::TypeArgs
+            },
+            Tok::GreaterGreater if type_args > 1 => {
+                type_args -= 2;
+                Role::TypeArgs
+            },
+            Tok::Exclaim if after_name => Role::Macro,
+            Tok::Exclaim => Role::Unary,
+            Tok::Pipe if lambda_params => {
+                lambda_params = false;
+                Role::LambdaClose
+            },
+            Tok::Pipe if !after_operand => {
+                lambda_params = true;
+                Role::LambdaOpen
+            },
+            // lambda without parameters
+            Tok::PipePipe if !after_operand => Role::Other,
+            Tok::Amp | Tok::Star | Tok::Minus if !after_operand => Role::Unary,
+            Tok::Plus
+            | Tok::Minus
+            | Tok::Star
+            | Tok::Slash
+            | Tok::Percent
+            | Tok::Amp
+            | Tok::AmpAmp
+            | Tok::Pipe
+            | Tok::PipePipe
+            | Tok::Caret
+            | Tok::LessLess
+            | Tok::GreaterGreater
+            | Tok::EqualEqual
+            | Tok::ExclaimEqual
+            | Tok::Less
+            | Tok::Greater
+            | Tok::LessEqual
+            | Tok::GreaterEqual
+            | Tok::EqualEqualGreater
+            | Tok::LessEqualEqualGreater => Role::Binary,
+            _ => Role::Other,
+        };
+        last = Some((idx, tok));
+    }
+    roles
+}
+
+/// Whether the given token may end an operand of a binary operator.
+fn ends_operand(tok: Tok) -> bool {
+    matches!(
+        tok,
+        Tok::Identifier
+            | Tok::NumValue
+            | Tok::NumTypedValue
+            | Tok::ByteStringValue
+            | Tok::True
+            | Tok::False
+            | Tok::RParen
+            | Tok::RBracket
+    )
+}
+
+fn is_opener(element: &Element<'_>) -> bool {
+    matches!(
+        element.tok(),
+        Some(Tok::LParen | Tok::LBracket | Tok::LBrace)
+    )
+}
+
+fn is_closer(element: &Element<'_>) -> bool {
+    matches!(
+        element.tok(),
+        Some(Tok::RParen | Tok::RBracket | Tok::RBrace)
+    )
+}
+
+/// Breaks a delimited list on the first line longer than the maximum width which has a list not
+/// broken yet. Returns whether a list was broken.
+fn break_long_line(
+    elements: &mut Vec<Element<'_>>,
+    roles: &[Role],
+    lines: &[Line],
+    max_width: usize,
+) -> bool {
+    let closers = matching_closers(elements);
+    for line in lines.iter().filter(|line| line.width > max_width) {
+        let candidates: Vec<usize> = (line.first..=line.last)
+            .filter(|&idx| {
+                closers[idx].map_or(false, |close| {
+                    close > idx + 1
+                        && elements[idx + 1].newlines == 0
+                        && elements[idx + 1].kind != Kind::LineComment
+                })
+            })
+            .collect();
+        // lists of several items are broken first, then blocks
+        let opener = candidates
+            .iter()
+            .find(|&&idx| !separators(elements, roles, &closers, idx).is_empty())
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .find(|&&idx| elements[idx].is(Tok::LBrace))
+            })
+            .or_else(|| candidates.first());
+        if let Some(&opener) = opener {
+            break_list(elements, roles, &closers, opener);
+            return true;
+        }
+    }
+    false
+}
+
+/// Puts each item of a delimited list on a line of its own, adding a trailing comma to lists of
+/// comma separated items.
+fn break_list(
+    elements: &mut Vec<Element<'_>>,
+    roles: &[Role],
+    closers: &[Option<usize>],
+    opener: usize,
+) {
+    let close = closers[opener].expect("opening delimiter is matched");
+    let separators = separators(elements, roles, closers, opener);
+    elements[opener + 1].newlines = 1;
+    for &idx in &separators {
+        // comments after a separator stay on its line
+        if idx + 1 < close && !elements[idx + 1].is_comment() {
+            elements[idx + 1].newlines = elements[idx + 1].newlines.max(1);
+        }
+    }
+    elements[close].newlines = elements[close].newlines.max(1);
+    let last = (opener + 1..close)
+        .rev()
+        .find(|&idx| !elements[idx].is_comment())
+        .expect("list is not empty");
+    if separators
+        .first()
+        .map_or(false, |&idx| elements[idx].is(Tok::Comma))
+        && !elements[last].is(Tok::Comma)
+    {
+        elements.insert(last + 1, Element::token(Tok::Comma, ","));
+    }
+}
+
+/// Returns the index of the matching closing delimiter of each opening delimiter.
+fn matching_closers(elements: &[Element<'_>]) -> Vec<Option<usize>> {
+    let mut closers = vec![None; elements.len()];
+    let mut open = vec![];
+    for (idx, element) in elements.iter().enumerate() {
+        if is_opener(element) {
+            open.push(idx);
+        } else if is_closer(element) {
+            if let Some(opener) = open.pop() {
+                closers[opener] = Some(idx);
+            }
+        }
+    }
+    closers
+}
+
+/// Returns the indices of the separators of the items of a delimited list: commas, or semicolons
+/// in blocks. Separators of nested lists, type arguments and lambda parameters are skipped.
+fn separators(
+    elements: &[Element<'_>],
+    roles: &[Role],
+    closers: &[Option<usize>],
+    opener: usize,
+) -> Vec<usize> {
+    let close = closers[opener].expect("opening delimiter is matched");
+    let mut result = vec![];
+    let mut type_args = 0;
+    let mut lambda_params = false;
+    let mut idx = opener + 1;
+    while idx < close {
+        match (elements[idx].tok(), roles[idx]) {
+            (Some(Tok::Less), Role::TypeArgs) => type_args += 1,
+            (Some(Tok::Greater), Role::TypeArgs) => type_args -= 1,
+            (Some(Tok::GreaterGreater), Role::TypeArgs) => type_args -= 2,
+            (_, Role::LambdaOpen) => lambda_params = true,
+            (_, Role::LambdaClose) => lambda_params = false,
+            (Some(Tok::Comma | Tok::Semicolon), _) if type_args <= 0 && !lambda_params => {
+                result.push(idx)
+            },
+            _ => (),
+        }
+        // nested lists are skipped
+        idx = closers[idx].map_or(idx + 1, |close| close + 1);
+    }
+    result
+}
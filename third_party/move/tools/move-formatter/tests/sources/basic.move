@@ -0,0 +1,23 @@
+// Formatting of spacing and indentation
+module 0x42::basic {
+use std::vector;
+
+
+  struct S<T: copy + drop> has copy, drop { x: u64, v: vector<T> }
+
+    /// Doc comment
+  public(friend) fun f(a: &mut S<u64>, b: u64): u64 {
+      let x=a.x+b*2;
+    if (x>10&&!is_zero(x)) x=x-1 else {
+     x = *&a.x;
+    };
+   let v = vector[1, 2,3];
+      assert!(vector::length(&v)==3, 0);   // trailing
+     let f = |y| y + 1;
+        x
+  }
+
+
+
+  fun is_zero(x: u64 /* value */): bool { x == 0 }
+}
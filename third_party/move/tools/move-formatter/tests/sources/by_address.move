@@ -0,0 +1,10 @@
+module 0x42::by_address {
+    use 0x42::m;
+    use std::vector;
+
+    use aptos_framework::coin;
+    use std::option;
+    use 0x42::a;
+
+    const E: u64 = 1;
+}
@@ -0,0 +1,14 @@
+module 0x42::imports {
+    use 0x42::zeta::{Self, b, A};
+    use std::vector;
+    use 0x42::alpha;
+
+    use std::option::{some, Option};
+    use 0x1::signer;
+    // A comment keeps the declaration below in place
+    use std::string;
+    #[test_only]
+    use std::debug;
+
+    fun f() {}
+}
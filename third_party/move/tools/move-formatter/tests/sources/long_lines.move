@@ -0,0 +1,7 @@
+module 0x42::long_lines {
+    fun f(first_argument: u64, second_argument: u64, third: u64): u64 {
+        let total = add(first_argument, second_argument) + third + first_argument;
+        if (total > 100) { total = total - 100; total = total * 2 } else { total = total + 1; };
+        total
+    }
+}
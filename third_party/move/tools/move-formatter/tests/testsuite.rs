@@ -0,0 +1,33 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_command_line_common::testing::EXP_EXT;
+use move_formatter::{format_source, FormatConfig};
+use move_prover_test_utils::baseline_test::verify_or_update_baseline;
+use std::path::Path;
+
+fn runner(path: &Path) -> datatest_stable::Result<()> {
+    // a test may come with its own configuration
+    let config_path = path.with_extension("toml");
+    let config: FormatConfig = if config_path.exists() {
+        toml::from_str(&std::fs::read_to_string(config_path)?)?
+    } else {
+        FormatConfig::default()
+    };
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    let source = std::fs::read_to_string(path)?;
+    let output = match format_source(&file_name, &source, &config) {
+        Ok(formatted) => {
+            // formatting is idempotent
+            let reformatted = format_source(&file_name, &formatted, &config)?;
+            assert_eq!(formatted, reformatted, "formatting is not idempotent");
+            formatted
+        },
+        Err(err) => format!("Error: {}", err),
+    };
+    let baseline_path = path.with_extension(EXP_EXT);
+    verify_or_update_baseline(baseline_path.as_path(), &output)?;
+    Ok(())
+}
+
+datatest_stable::harness!(runner, "tests/sources", r".*\.move");
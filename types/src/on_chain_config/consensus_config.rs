@@ -150,6 +150,10 @@ pub enum LeaderReputationType {
     // * use reputation window from recent end
     // * unpredictable seed, based on root hash
     ProposerAndVoterV2(ProposerAndVoterConfig),
+    // Same as version 2, but additionally deprioritizes validators whose
+    // proposals are slow to be certified, or whose votes rarely make it
+    // into quorum certificates.
+    LatencyAware(LatencyAwareConfig),
 }
 
 impl LeaderReputationType {
@@ -189,6 +193,23 @@ pub struct ProposerAndVoterConfig {
     pub use_history_from_previous_epoch_max_count: u32,
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LatencyAwareConfig {
+    // Weights, windows and failure threshold, same as for ProposerAndVoterV2
+    pub proposer_and_voter_config: ProposerAndVoterConfig,
+    // Selection weight for active validators with proposer failures below threshold,
+    // that are slow to get their proposals certified, or to vote
+    pub slow_weight: u64,
+    // Average time (in milliseconds) from a proposal of a validator to the block
+    // of the next round, which carries the quorum certificate of the proposal,
+    // above which the validator is considered slow
+    pub proposal_latency_threshold_ms: u64,
+    // Threshold of quorum certificates in the voter window that include a vote of
+    // a validator, below which the validator is considered slow.
+    // integer values representing percentages, i.e. 12 is 12%.
+    pub vote_inclusion_threshold_percent: u32,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -229,6 +250,39 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_config_serialization_latency_aware() {
+        let proposer_and_voter_config = match ConsensusConfigV1::default().proposer_election_type {
+            ProposerElectionType::LeaderReputation(LeaderReputationType::ProposerAndVoterV2(
+                proposer_and_voter_config,
+            )) => proposer_and_voter_config,
+            _ => unreachable!(),
+        };
+        let leader_reputation_type = LeaderReputationType::LatencyAware(LatencyAwareConfig {
+            proposer_and_voter_config,
+            slow_weight: 100,
+            proposal_latency_threshold_ms: 1500,
+            vote_inclusion_threshold_percent: 50,
+        });
+        assert!(leader_reputation_type.use_root_hash_for_seed());
+        assert!(!leader_reputation_type.use_reputation_window_from_stale_end());
+
+        let config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
+            proposer_election_type: ProposerElectionType::LeaderReputation(leader_reputation_type),
+            ..ConsensusConfigV1::default()
+        });
+        let s = serde_yaml::to_string(&config).unwrap();
+        assert_eq!(
+            serde_yaml::from_str::<OnChainConsensusConfig>(&s).unwrap(),
+            config
+        );
+        let s = bcs::to_bytes(&config).unwrap();
+        assert_eq!(
+            bcs::from_bytes::<OnChainConsensusConfig>(&s).unwrap(),
+            config
+        );
+    }
+
     #[test]
     fn test_config_onchain_payload() {
         let consensus_config = OnChainConsensusConfig::V1(ConsensusConfigV1 {
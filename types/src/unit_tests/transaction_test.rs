@@ -6,13 +6,14 @@ use crate::{
     account_address::AccountAddress,
     chain_id::ChainId,
     transaction::{
-        AccountTransactionsWithProof, RawTransaction, Script, SignedTransaction, Transaction,
-        TransactionInfo, TransactionListWithProof, TransactionPayload, TransactionWithProof,
+        authenticator::AuthenticationKey, AccountTransactionsWithProof, RawTransaction, Script,
+        SignedTransaction, Transaction, TransactionInfo, TransactionListWithProof,
+        TransactionPayload, TransactionWithProof,
     },
 };
 use aptos_crypto::{
     ed25519::{self, Ed25519PrivateKey, Ed25519Signature},
-    PrivateKey, Uniform,
+    secp256k1_ecdsa, PrivateKey, SigningKey, Uniform,
 };
 use bcs::test_helpers::assert_canonical_encode_decode;
 use proptest::prelude::*;
@@ -44,6 +45,17 @@ proptest! {
         assert!(signed_txn.check_signature().is_ok());
     }
 
+    #[test]
+    fn test_sign_raw_transaction_secp256k1_ecdsa(raw_txn in any::<RawTransaction>(), keypair in secp256k1_ecdsa::keypair_strategy()) {
+        let signature = keypair.private_key.sign(&raw_txn).unwrap();
+        let signed_txn = SignedTransaction::new_secp256k1_ecdsa(raw_txn, keypair.public_key.clone(), signature);
+        prop_assert_eq!(
+            signed_txn.authenticator().sender().authentication_key(),
+            AuthenticationKey::secp256k1_ecdsa(&keypair.public_key)
+        );
+        prop_assert!(signed_txn.check_signature().is_ok());
+    }
+
     #[test]
     fn transaction_payload_bcs_roundtrip(txn_payload in any::<TransactionPayload>()) {
         assert_canonical_encode_decode(txn_payload);
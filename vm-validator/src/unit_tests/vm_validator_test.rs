@@ -387,3 +387,76 @@ fn test_validate_chain_id() {
     let ret = vm_validator.validate_transaction(transaction).unwrap();
     assert_eq!(ret.status().unwrap(), StatusCode::BAD_CHAIN_ID);
 }
+
+#[test]
+fn test_validate_fee_payer_invalid_signature() {
+    let vm_validator = TestValidator::new();
+
+    let mut rng = ::rand::rngs::StdRng::from_seed([1u8; 32]);
+    let other_private_key = Ed25519PrivateKey::generate(&mut rng);
+
+    let address = account_config::aptos_test_root_address();
+    let fee_payer = account_address::AccountAddress::random();
+    let program = aptos_stdlib::aptos_coin_transfer(address, 100);
+    // The fee payer signature doesn't match its public key.
+    let transaction = transaction_test_helpers::get_test_unchecked_fee_payer_txn(
+        address,
+        fee_payer,
+        1,
+        &aptos_vm_genesis::GENESIS_KEYPAIR.0,
+        aptos_vm_genesis::GENESIS_KEYPAIR.1.clone(),
+        &other_private_key,
+        aptos_vm_genesis::GENESIS_KEYPAIR.1.clone(),
+        program,
+    );
+    let ret = vm_validator.validate_transaction(transaction).unwrap();
+    assert_eq!(ret.status().unwrap(), StatusCode::INVALID_SIGNATURE);
+}
+
+#[test]
+fn test_validate_fee_payer_duplicate_signers() {
+    let vm_validator = TestValidator::new();
+
+    let address = account_config::aptos_test_root_address();
+    let program = aptos_stdlib::aptos_coin_transfer(address, 100);
+    let transaction = transaction_test_helpers::get_test_unchecked_fee_payer_txn(
+        address,
+        address,
+        1,
+        &aptos_vm_genesis::GENESIS_KEYPAIR.0,
+        aptos_vm_genesis::GENESIS_KEYPAIR.1.clone(),
+        &aptos_vm_genesis::GENESIS_KEYPAIR.0,
+        aptos_vm_genesis::GENESIS_KEYPAIR.1.clone(),
+        program,
+    );
+    let ret = vm_validator.validate_transaction(transaction).unwrap();
+    assert_eq!(
+        ret.status().unwrap(),
+        StatusCode::SIGNERS_CONTAIN_DUPLICATES
+    );
+}
+
+#[test]
+fn test_validate_fee_payer_account_doesnt_exist() {
+    let vm_validator = TestValidator::new();
+
+    let address = account_config::aptos_test_root_address();
+    let fee_payer = account_address::AccountAddress::random();
+    let program = aptos_stdlib::aptos_coin_transfer(address, 100);
+    // The fee payer must hold enough coins to pay for the gas, which a missing account can't.
+    let transaction = transaction_test_helpers::get_test_unchecked_fee_payer_txn(
+        address,
+        fee_payer,
+        0, /* sequence_number */
+        &aptos_vm_genesis::GENESIS_KEYPAIR.0,
+        aptos_vm_genesis::GENESIS_KEYPAIR.1.clone(),
+        &aptos_vm_genesis::GENESIS_KEYPAIR.0,
+        aptos_vm_genesis::GENESIS_KEYPAIR.1.clone(),
+        program,
+    );
+    let ret = vm_validator.validate_transaction(transaction).unwrap();
+    assert_eq!(
+        ret.status().unwrap(),
+        StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE
+    );
+}